@@ -11,7 +11,8 @@ use bstr::{BStr, BString};
 
 ///
 pub mod signature;
-mod time;
+///
+pub mod time;
 
 const SPACE: &[u8; 1] = b" ";
 
@@ -2,6 +2,10 @@ use std::io;
 
 use crate::{Sign, Time, SPACE};
 
+///
+pub mod parse;
+pub use parse::parse;
+
 impl From<i32> for Sign {
     fn from(v: i32) -> Self {
         if v < 0 {
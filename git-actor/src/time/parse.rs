@@ -0,0 +1,372 @@
+use std::{convert::TryFrom, time::SystemTime};
+
+use quick_error::quick_error;
+
+use crate::{Sign, Time};
+
+quick_error! {
+    /// The error returned by [`parse()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        InvalidDateString { input: String } {
+            display("Date string {:?} is not in any of the supported formats", input)
+        }
+        OutOfRange { input: String } {
+            display("The date {:?} can't be represented as seconds since the unix epoch", input)
+        }
+        MissingCurrentTime { input: String } {
+            display("The date {:?} is relative to the current time, which wasn't provided", input)
+        }
+    }
+}
+
+const SECONDS_PER_MINUTE: i64 = 60;
+const SECONDS_PER_HOUR: i64 = 60 * SECONDS_PER_MINUTE;
+const SECONDS_PER_DAY: i64 = 24 * SECONDS_PER_HOUR;
+
+/// Parse `input` as date in any of the formats git understands, using `now` for dates relative to the current time.
+///
+/// The following formats are supported:
+///
+/// * _raw_, as used in commits and tags: `1234567890 +0200`, also with `@` prefix, which is optional for the timestamp itself
+///   if it has at least 9 digits.
+/// * _RFC 2822_: `Thu, 07 Apr 2005 22:13:13 +0200`, with the weekday being optional.
+/// * _ISO 8601_, strict and lax: `2005-04-07T22:13:13+02:00`, `2005-04-07 22:13:13 +0200` or just `2005-04-07`, with the timezone
+///   defaulting to UTC and the time defaulting to midnight.
+/// * `now` and `yesterday`.
+/// * _relative_: `2.weeks.ago` or `1 year 3 days ago`, with units from seconds to years.
+///
+/// Dates relative to `now` always use UTC, and it's an error if `now` is `None` for these.
+pub fn parse(input: &str, now: Option<SystemTime>) -> Result<Time, Error> {
+    let trimmed = input.trim();
+    let time = if trimmed.eq_ignore_ascii_case("now") {
+        Some(relative(input, now, 0)?)
+    } else if trimmed.eq_ignore_ascii_case("yesterday") {
+        Some(relative(input, now, -SECONDS_PER_DAY)?)
+    } else if let Some(time) = raw(trimmed) {
+        Some(time)
+    } else if let Some(time) = iso8601(trimmed) {
+        Some(time)
+    } else if let Some(time) = rfc2822(trimmed) {
+        Some(time)
+    } else {
+        match relative_offset(trimmed) {
+            Some(Some(Offset::Seconds(seconds))) => {
+                let seconds = seconds.checked_neg().ok_or_else(|| out_of_range(input))?;
+                Some(relative(input, now, seconds)?)
+            }
+            Some(Some(Offset::MonthsAndSeconds(months, seconds))) => Some(
+                months_and_seconds_ago(seconds_since_epoch(input, now)?, months, seconds)
+                    .ok_or_else(|| out_of_range(input))?,
+            ),
+            Some(None) => return Err(out_of_range(input)),
+            None => None,
+        }
+    };
+    let time = time.ok_or_else(|| Error::InvalidDateString { input: input.into() })?;
+    time.into_time().ok_or_else(|| out_of_range(input))
+}
+
+fn out_of_range(input: &str) -> Error {
+    Error::OutOfRange { input: input.into() }
+}
+
+/// A time which may not yet be representable by [`Time`].
+struct Timestamp {
+    /// Seconds since the unix epoch in UTC.
+    seconds: i64,
+    offset: i32,
+    sign: Sign,
+}
+
+impl Timestamp {
+    fn from_local(days: i64, seconds_in_day: i64, (offset, sign): (i32, Sign)) -> Self {
+        Timestamp {
+            seconds: days * SECONDS_PER_DAY + seconds_in_day - offset as i64,
+            offset,
+            sign,
+        }
+    }
+
+    fn into_time(self) -> Option<Time> {
+        Some(Time {
+            seconds_since_unix_epoch: u32::try_from(self.seconds).ok()?,
+            offset_in_seconds: self.offset,
+            sign: self.sign,
+        })
+    }
+}
+
+fn seconds_since_epoch(input: &str, now: Option<SystemTime>) -> Result<i64, Error> {
+    let now = now.ok_or_else(|| Error::MissingCurrentTime { input: input.into() })?;
+    Ok(match now.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    })
+}
+
+fn relative(input: &str, now: Option<SystemTime>, offset_in_seconds: i64) -> Result<Timestamp, Error> {
+    Ok(Timestamp {
+        seconds: seconds_since_epoch(input, now)?
+            .checked_add(offset_in_seconds)
+            .ok_or_else(|| out_of_range(input))?,
+        offset: 0,
+        sign: Sign::Plus,
+    })
+}
+
+/// Go back `months` and `seconds` from `now` in seconds since the unix epoch, or return `None` on overflow.
+fn months_and_seconds_ago(now: i64, months: i64, seconds: i64) -> Option<Timestamp> {
+    let now = now.checked_sub(seconds)?;
+    let days = now.div_euclid(SECONDS_PER_DAY);
+    let (year, month, day) = civil_from_days(days);
+    let months_since_year_zero = year.checked_mul(12)?.checked_add(month - 1)?.checked_sub(months)?;
+    let days = days_from_civil(
+        months_since_year_zero.div_euclid(12),
+        months_since_year_zero.rem_euclid(12) + 1,
+        day,
+    )?;
+    Some(Timestamp {
+        seconds: days
+            .checked_mul(SECONDS_PER_DAY)?
+            .checked_add(now.rem_euclid(SECONDS_PER_DAY))?,
+        offset: 0,
+        sign: Sign::Plus,
+    })
+}
+
+/// `[@]<seconds> [<offset>]`
+fn raw(input: &str) -> Option<Timestamp> {
+    let (input, has_at) = match input.strip_prefix('@') {
+        Some(input) => (input, true),
+        None => (input, false),
+    };
+    let mut tokens = input.split_whitespace();
+    let seconds = tokens.next()?;
+    if !seconds.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let offset = match tokens.next() {
+        Some(offset) => Some(timezone(offset)?),
+        None => None,
+    };
+    if tokens.next().is_some() || (!has_at && offset.is_none() && seconds.len() < 9) {
+        return None;
+    }
+    let (offset, sign) = offset.unwrap_or((0, Sign::Plus));
+    Some(Timestamp {
+        seconds: seconds.parse().ok()?,
+        offset,
+        sign,
+    })
+}
+
+/// `YYYY-MM-DD[(T| )HH:MM[:SS]][ ][<offset>]`
+fn iso8601(input: &str) -> Option<Timestamp> {
+    let date = input.get(..10)?;
+    let (year, month, day) = {
+        let mut parts = date.splitn(3, '-');
+        let year = number(parts.next()?, 4)?;
+        let month = number(parts.next()?, 2)?;
+        let day = number(parts.next()?, 2)?;
+        (year, month, day)
+    };
+    validate_date(year, month, day)?;
+    let rest = &input[10..];
+    let (seconds_in_day, rest) = match rest.chars().next() {
+        None => (0, rest),
+        Some('T') | Some(' ') => time_of_day(&rest[1..])?,
+        Some(_) => return None,
+    };
+    let rest = rest.trim_start();
+    let tz = if rest.is_empty() {
+        (0, Sign::Plus)
+    } else {
+        timezone(rest)?
+    };
+    Some(Timestamp::from_local(
+        days_from_civil(year, month, day)?,
+        seconds_in_day,
+        tz,
+    ))
+}
+
+/// `[<weekday>, ]DD <month> YYYY HH:MM[:SS] <offset>`
+fn rfc2822(input: &str) -> Option<Timestamp> {
+    let input = match input.find(',') {
+        Some(pos) => {
+            let weekday = &input[..pos];
+            if !WEEKDAYS.iter().any(|d| weekday.eq_ignore_ascii_case(d)) {
+                return None;
+            }
+            &input[pos + 1..]
+        }
+        None => input,
+    };
+    let mut tokens = input.split_whitespace();
+    let day_token = tokens.next()?;
+    if day_token.is_empty() || day_token.len() > 2 {
+        return None;
+    }
+    let day = number(day_token, day_token.len())?;
+    let month_token = tokens.next()?;
+    let month = MONTHS.iter().position(|m| month_token.eq_ignore_ascii_case(m))? as i64 + 1;
+    let year = number(tokens.next()?, 4)?;
+    validate_date(year, month, day)?;
+    let (seconds_in_day, rest) = time_of_day(tokens.next()?)?;
+    if !rest.is_empty() {
+        return None;
+    }
+    let tz = timezone(tokens.next()?)?;
+    if tokens.next().is_some() {
+        return None;
+    }
+    Some(Timestamp::from_local(
+        days_from_civil(year, month, day)?,
+        seconds_in_day,
+        tz,
+    ))
+}
+
+const WEEKDAYS: &[&str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const MONTHS: &[&str] = &[
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+enum Offset {
+    Seconds(i64),
+    MonthsAndSeconds(i64, i64),
+}
+
+/// `<number> <unit> [<number> <unit>…] ago`, with `.` being a valid separator as well.
+///
+/// Returns `Some(None)` if the input has the right format, but the offset doesn't fit into 64 bits.
+fn relative_offset(input: &str) -> Option<Option<Offset>> {
+    let tokens: Vec<_> = input
+        .split(|c: char| c == '.' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+        .collect();
+    let (last, pairs) = tokens.split_last()?;
+    if !last.eq_ignore_ascii_case("ago") || pairs.is_empty() || pairs.len() % 2 != 0 {
+        return None;
+    }
+    let mut seconds = Some(0i64);
+    let mut months = Some(0i64);
+    for pair in pairs.chunks(2) {
+        let amount: i64 = pair[0].parse().ok()?;
+        let unit = pair[1].to_ascii_lowercase();
+        let unit = unit.strip_suffix('s').unwrap_or(&unit);
+        let (total, factor) = match unit {
+            "second" | "sec" => (&mut seconds, 1),
+            "minute" | "min" => (&mut seconds, SECONDS_PER_MINUTE),
+            "hour" => (&mut seconds, SECONDS_PER_HOUR),
+            "day" => (&mut seconds, SECONDS_PER_DAY),
+            "week" => (&mut seconds, 7 * SECONDS_PER_DAY),
+            "month" => (&mut months, 1),
+            "year" => (&mut months, 12),
+            _ => return None,
+        };
+        *total = total.and_then(|total| total.checked_add(amount.checked_mul(factor)?));
+    }
+    Some(match (seconds, months) {
+        (Some(seconds), Some(0)) => Some(Offset::Seconds(seconds)),
+        (Some(seconds), Some(months)) => Some(Offset::MonthsAndSeconds(months, seconds)),
+        _ => None,
+    })
+}
+
+/// `HH:MM[:SS]`, returning the seconds since midnight and the remaining input.
+fn time_of_day(input: &str) -> Option<(i64, &str)> {
+    let hours = number(input.get(..2)?, 2)?;
+    if input.get(2..3)? != ":" {
+        return None;
+    }
+    let minutes = number(input.get(3..5)?, 2)?;
+    let (seconds, rest) = match input.get(5..6) {
+        Some(":") => (number(input.get(6..8)?, 2)?, &input[8..]),
+        _ => (0, &input[5..]),
+    };
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    Some((hours * SECONDS_PER_HOUR + minutes * SECONDS_PER_MINUTE + seconds, rest))
+}
+
+/// `Z`, `+HHMM`, `-HHMM`, `+HH:MM` or `-HH:MM`
+fn timezone(input: &str) -> Option<(i32, Sign)> {
+    if input == "Z" {
+        return Some((0, Sign::Plus));
+    }
+    let sign = match input.chars().next()? {
+        '+' => Sign::Plus,
+        '-' => Sign::Minus,
+        _ => return None,
+    };
+    let digits = &input[1..];
+    if !digits.is_ascii() {
+        return None;
+    }
+    let digits = match digits.len() {
+        4 => digits.to_owned(),
+        5 if digits.get(2..3) == Some(":") => digits.replace(':', ""),
+        _ => return None,
+    };
+    let hours = number(&digits[..2], 2)?;
+    let minutes = number(&digits[2..], 2)?;
+    if minutes > 59 {
+        return None;
+    }
+    let offset = (hours * SECONDS_PER_HOUR + minutes * SECONDS_PER_MINUTE) as i32;
+    Some((if matches!(sign, Sign::Minus) { -offset } else { offset }, sign))
+}
+
+fn number(input: &str, len: usize) -> Option<i64> {
+    if input.len() != len || !input.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    input.parse().ok()
+}
+
+fn validate_date(year: i64, month: i64, day: i64) -> Option<()> {
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days_in_month).contains(&day).then(|| ())
+}
+
+/// Days since the unix epoch for the given date in the proleptic gregorian calendar, or `None` on overflow.
+///
+/// Days past the end of `month` continue into the next month.
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146097)?.checked_add(day_of_era)?.checked_sub(719468)
+}
+
+/// The inverse of [`days_from_civil()`], returning `(year, month, day)`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
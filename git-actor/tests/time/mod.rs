@@ -35,3 +35,118 @@ fn write_to() -> Result<(), Box<dyn std::error::Error>> {
     }
     Ok(())
 }
+
+mod parse {
+    use std::time::{Duration, SystemTime};
+
+    use git_actor::{time, Sign, Time};
+
+    /// Fri, 15 Oct 2021 12:00:00 +0000
+    const NOW: u64 = 1634299200;
+
+    fn now() -> Option<SystemTime> {
+        Some(SystemTime::UNIX_EPOCH + Duration::from_secs(NOW))
+    }
+
+    fn parse(input: &str) -> Time {
+        time::parse(input, now()).unwrap_or_else(|err| panic!("{:?} should parse: {}", input, err))
+    }
+
+    #[test]
+    fn raw() {
+        assert_eq!(parse("1234567890 +0200"), Time::new(1234567890, 7200));
+        assert_eq!(parse("@1234567890 -0130"), Time::new(1234567890, -5400));
+        assert_eq!(parse("@12345"), Time::new(12345, 0));
+        assert_eq!(parse("1234567890"), Time::new(1234567890, 0));
+        assert_eq!(
+            parse("0 -0000"),
+            Time {
+                seconds_since_unix_epoch: 0,
+                offset_in_seconds: 0,
+                sign: Sign::Minus
+            },
+            "the sign is retained even if the offset is zero"
+        );
+    }
+
+    #[test]
+    fn rfc2822() {
+        assert_eq!(parse("Thu, 07 Apr 2005 22:13:13 +0200"), Time::new(1112904793, 7200));
+        assert_eq!(parse("7 Apr 2005 22:13:13 +0200"), Time::new(1112904793, 7200));
+        assert_eq!(parse("Thu, 07 Apr 2005 20:13 +0000"), Time::new(1112904780, 0));
+    }
+
+    #[test]
+    fn iso8601() {
+        assert_eq!(parse("2005-04-07T22:13:13+02:00"), Time::new(1112904793, 7200));
+        assert_eq!(parse("2005-04-07 22:13:13 +0200"), Time::new(1112904793, 7200));
+        assert_eq!(parse("2005-04-07T20:13:13Z"), Time::new(1112904793, 0));
+        assert_eq!(parse("2005-04-07"), Time::new(1112832000, 0));
+        assert_eq!(parse("2000-02-29 00:00"), Time::new(951782400, 0));
+    }
+
+    #[test]
+    fn relative() {
+        assert_eq!(parse("now"), Time::new(NOW as u32, 0));
+        assert_eq!(parse("yesterday"), Time::new(NOW as u32 - 86400, 0));
+        assert_eq!(parse("2.weeks.ago"), Time::new(NOW as u32 - 14 * 86400, 0));
+        assert_eq!(parse("1 hour 30 minutes ago"), Time::new(NOW as u32 - 5400, 0));
+        assert_eq!(parse("1.month.ago"), parse("2021-09-15 12:00:00 +0000"));
+        assert_eq!(parse("2 years 1 day ago"), parse("2019-10-14 12:00:00 +0000"));
+    }
+
+    #[test]
+    fn relative_dates_need_the_current_time() {
+        assert!(matches!(
+            time::parse("now", None),
+            Err(time::parse::Error::MissingCurrentTime { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid() {
+        for input in &[
+            "",
+            "tomorrow",
+            "2005-02-30",
+            "Thu, 07 Foo 2005 22:13:13 +0200",
+            "2 fortnights ago",
+            "1234567890 +1\u{e9}1",
+        ] {
+            assert!(
+                matches!(
+                    time::parse(input, now()),
+                    Err(time::parse::Error::InvalidDateString { .. })
+                ),
+                "{:?} should be invalid",
+                input
+            );
+        }
+        for input in &[
+            "1960-01-01",
+            "-9223372036854775808 seconds ago",
+            "9223372036854775807 weeks ago",
+            "9223372036854775807 years ago",
+            "9999999999 months ago",
+        ] {
+            assert!(
+                matches!(time::parse(input, now()), Err(time::parse::Error::OutOfRange { .. })),
+                "{:?} should be out of range",
+                input
+            );
+        }
+    }
+}
+
+#[test]
+fn size_matches_serialized_length() -> Result<(), Box<dyn std::error::Error>> {
+    for seconds in [0, 9, 10, 99_999, 1_000_000_000, u32::MAX].iter().copied() {
+        for offset in [0, 3600, -36000, 86399].iter().copied() {
+            let time = Time::new(seconds, offset);
+            let mut output = Vec::new();
+            time.write_to(&mut output)?;
+            assert_eq!(time.size(), output.len(), "{:?}", output.as_bstr());
+        }
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 pub mod pattern {
     use bitflags::bitflags;
+    use bstr::{BStr, BString, ByteSlice};
+
+    use crate::wildmatch;
 
     bitflags! {
         #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -13,4 +16,98 @@ pub mod pattern {
             const NEGATIVE = 1 << 3;
         }
     }
+
+    /// Whether or not paths are matched case-sensitively.
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    pub enum Case {
+        /// Paths must match exactly.
+        Sensitive,
+        /// ASCII characters are matched case-insensitively, as on case-insensitive file systems or with `core.ignoreCase`.
+        Fold,
+    }
+
+    impl Default for Case {
+        fn default() -> Self {
+            Case::Sensitive
+        }
+    }
+
+    /// A parsed pattern of a `.gitignore` or `.gitattributes` file, ready to match paths.
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+    pub struct Pattern {
+        /// The pattern itself, without the leading `!` of negative patterns or the trailing slash of directory patterns.
+        pub text: BString,
+        /// Additional information about the pattern.
+        pub mode: Mode,
+        /// The position of the first character that has a special meaning in a glob, or `None` if the pattern is literal.
+        first_wildcard_pos: Option<usize>,
+    }
+
+    impl Pattern {
+        /// Create a new instance from `text` and `mode` as obtained by parsing a line of a `.gitignore` or
+        /// `.gitattributes` file.
+        pub fn new(text: BString, mode: Mode) -> Self {
+            let first_wildcard_pos = text.find_byteset(br"*?[\");
+            Pattern {
+                text,
+                mode,
+                first_wildcard_pos,
+            }
+        }
+
+        /// Parse `line` like a line in a `.gitignore` file, or return `None` if it is empty.
+        pub fn from_bytes(line: &[u8]) -> Option<Self> {
+            crate::parse::ignore::parse_line(line).map(|(text, mode)| Pattern::new(text, mode))
+        }
+
+        /// Return true if this is a negative pattern, which excludes paths from being matched by previous patterns.
+        pub fn is_negative(&self) -> bool {
+            self.mode.contains(Mode::NEGATIVE)
+        }
+
+        /// Return true if the slash-separated `relative_path` matches this pattern, with `relative_path` being relative
+        /// to the directory containing the file the pattern was read from. `is_dir` is true if the path is a directory,
+        /// and `case` controls case-sensitivity.
+        ///
+        /// Patterns without a slash match the last component of the path, while all others match the whole path.
+        pub fn matches_relative_path(&self, relative_path: &BStr, is_dir: bool, case: Case) -> bool {
+            if self.mode.contains(Mode::MUST_BE_DIR) && !is_dir {
+                return false;
+            }
+            let flags = wildmatch::Mode::NO_MATCH_SLASH_LITERAL
+                | match case {
+                    Case::Fold => wildmatch::Mode::IGNORE_CASE,
+                    Case::Sensitive => wildmatch::Mode::empty(),
+                };
+            if self.mode.contains(Mode::NO_SUB_DIR) {
+                let basename = relative_path
+                    .rfind_byte(b'/')
+                    .map_or(relative_path, |pos| relative_path[pos + 1..].as_bstr());
+                if self.mode.contains(Mode::ENDS_WITH) {
+                    let suffix = &self.text[1..];
+                    basename.len() >= suffix.len() && eq(&basename[basename.len() - suffix.len()..], suffix, case)
+                } else if self.first_wildcard_pos.is_none() {
+                    eq(basename, &self.text, case)
+                } else {
+                    crate::wildmatch(self.text.as_bstr(), basename, flags)
+                }
+            } else {
+                let text = self.text.strip_prefix(b"/").unwrap_or(&self.text[..]);
+                if self.first_wildcard_pos.is_none() {
+                    eq(relative_path, text, case)
+                } else {
+                    crate::wildmatch(text.as_bstr(), relative_path, flags)
+                }
+            }
+        }
+    }
+
+    fn eq(a: &[u8], b: &[u8], case: Case) -> bool {
+        match case {
+            Case::Sensitive => a == b,
+            Case::Fold => a.eq_ignore_ascii_case(b),
+        }
+    }
 }
@@ -3,7 +3,7 @@
 
 use bstr::BStr;
 
-#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub enum State<'a> {
     /// The attribute is listed, or has the special value 'true'
@@ -20,6 +20,11 @@ pub enum State<'a> {
 
 pub mod ignore;
 
+pub mod search;
+
+pub mod wildmatch;
+pub use wildmatch::wildmatch;
+
 pub mod parse;
 
 pub fn parse(buf: &[u8]) -> parse::Lines<'_> {
@@ -0,0 +1,371 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use super::{Mapping, PatternList};
+use crate::{
+    ignore::pattern::{Case, Pattern},
+    parse, State,
+};
+
+/// The owned version of [`State`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub(crate) enum Value {
+    Set,
+    Unset,
+    Value(BString),
+    Unspecified,
+}
+
+impl Value {
+    fn as_state(&self) -> State<'_> {
+        match self {
+            Value::Set => State::Set,
+            Value::Unset => State::Unset,
+            Value::Value(v) => State::Value(v.as_bstr()),
+            Value::Unspecified => State::Unspecified,
+        }
+    }
+}
+
+impl From<State<'_>> for Value {
+    fn from(state: State<'_>) -> Self {
+        match state {
+            State::Set => Value::Set,
+            State::Unset => Value::Unset,
+            State::Value(v) => Value::Value(v.into()),
+            State::Unspecified => Value::Unspecified,
+        }
+    }
+}
+
+/// The attributes assigned on a single line, in order.
+pub(crate) type Assignments = Vec<(BString, Value)>;
+
+mod error {
+    use std::path::PathBuf;
+
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Io { err: std::io::Error, path: PathBuf } {
+                display("Could not read attributes file at '{}'", path.display())
+                source(err)
+            }
+            Parse { err: crate::parse::Error, path: Option<PathBuf> } {
+                display("Could not parse attributes{}", path.as_ref().map(|p| format!(" in '{}'", p.display())).unwrap_or_default())
+                source(err)
+            }
+        }
+    }
+}
+pub use error::Error;
+
+/// A single attribute assigned to a path.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Match<'a> {
+    /// The name of the attribute.
+    pub attribute: &'a BStr,
+    /// Its state.
+    pub state: State<'a>,
+    /// The pattern that matched the path, which assigned the attribute directly or through a macro.
+    pub pattern: &'a Pattern,
+    /// The file the pattern was read from, if any.
+    pub source: Option<&'a Path>,
+    /// The line number of the pattern.
+    pub line_number: usize,
+}
+
+/// The result of [`Attributes::pattern_matching_relative_path()`], reusable across invocations to avoid allocations.
+#[derive(Default, Debug, Clone)]
+pub struct Outcome<'a> {
+    /// All assigned attributes, including the ones that weren't selected as they still take precedence.
+    matches: Vec<Match<'a>>,
+    /// If not empty, only these attributes are looked up.
+    selection: Vec<BString>,
+    /// The amount of selected attributes in `matches`.
+    selected: usize,
+}
+
+impl<'a> Outcome<'a> {
+    /// Create a new instance that only looks up the attributes named in `selection`, which allows matching to stop
+    /// as soon as all of them are found.
+    pub fn with_selection(selection: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        Outcome {
+            selection: selection.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Return all selected attributes assigned to the path, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Match<'a>> {
+        self.matches.iter().filter(move |m| self.is_selected(m.attribute))
+    }
+
+    /// Return the assignment of the selected attribute with `name`, if it was assigned.
+    pub fn get(&self, name: impl AsRef<[u8]>) -> Option<&Match<'a>> {
+        let name = name.as_ref();
+        self.iter().find(|m| m.attribute == name)
+    }
+
+    /// Return the state of the selected attribute with `name`, which is [`Unspecified`][State::Unspecified] if it
+    /// wasn't assigned.
+    pub fn state(&self, name: impl AsRef<[u8]>) -> State<'a> {
+        self.get(name).map_or(State::Unspecified, |m| m.state)
+    }
+
+    fn clear(&mut self) {
+        self.matches.clear();
+        self.selected = 0;
+    }
+
+    fn push(&mut self, m: Match<'a>) {
+        if self.is_selected(m.attribute) {
+            self.selected += 1;
+        }
+        self.matches.push(m);
+    }
+
+    fn is_selected(&self, name: &BStr) -> bool {
+        self.selection.is_empty() || self.selection.iter().any(|s| s == name)
+    }
+
+    fn is_done(&self) -> bool {
+        !self.selection.is_empty() && self.selected == self.selection.len()
+    }
+}
+
+/// All attribute patterns and macros that apply to paths in a worktree, with support for per-directory
+/// `.gitattributes` files that are pushed and popped as directories are entered and left.
+///
+/// Patterns are matched in order of precedence. From lowest to highest, these are the global files like
+/// `$(prefix)/etc/gitattributes` and `core.attributesFile`, the `.gitattributes` files from the root of the worktree
+/// to the directory of the path, and `$GIT_DIR/info/attributes`. Within a file, later lines take precedence.
+#[derive(Debug, Clone)]
+pub struct Attributes {
+    globals: Vec<PatternList<Assignments>>,
+    directories: Vec<PatternList<Assignments>>,
+    overrides: Vec<PatternList<Assignments>>,
+    macros: HashMap<BString, Assignments>,
+}
+
+impl Default for Attributes {
+    /// Create an instance without any patterns, which only knows the built-in `binary` macro.
+    fn default() -> Self {
+        let mut macros = HashMap::new();
+        macros.insert(
+            "binary".into(),
+            vec![
+                ("diff".into(), Value::Unset),
+                ("merge".into(), Value::Unset),
+                ("text".into(), Value::Unset),
+            ],
+        );
+        Attributes {
+            globals: Vec::new(),
+            directories: Vec::new(),
+            overrides: Vec::new(),
+            macros,
+        }
+    }
+}
+
+/// Where to put a list of patterns in the order of precedence.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+enum Kind {
+    Global,
+    Directory,
+    Override,
+}
+
+impl Attributes {
+    /// Add the patterns and macros of the global attributes file at `path`, like `$(prefix)/etc/gitattributes` or the
+    /// one configured in `core.attributesFile`, which take precedence over the ones added previously.
+    /// `buf` is used to read the file. Return false if the file didn't exist.
+    pub fn add_global_patterns_file(&mut self, path: impl Into<PathBuf>, buf: &mut Vec<u8>) -> Result<bool, Error> {
+        self.add_patterns_file(path.into(), "".into(), Kind::Global, buf)
+    }
+
+    /// Add the patterns and macros of `$GIT_DIR/info/attributes` at `path`, which take precedence over all others.
+    /// `buf` is used to read the file. Return false if the file didn't exist.
+    pub fn add_info_patterns_file(&mut self, path: impl Into<PathBuf>, buf: &mut Vec<u8>) -> Result<bool, Error> {
+        self.add_patterns_file(path.into(), "".into(), Kind::Override, buf)
+    }
+
+    /// Push the patterns of the `.gitattributes` file at `path` which is located in the slash-separated directory `dir`,
+    /// relative to the root of the worktree, using `buf` to read it.
+    /// Return false if the file didn't exist, in which case an empty list of patterns is pushed so that each push can be
+    /// undone with [`pop_directory()`][Attributes::pop_directory()].
+    ///
+    /// Macros are only defined if `dir` is the root of the worktree, just like in git.
+    pub fn push_directory_patterns_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        dir: &BStr,
+        buf: &mut Vec<u8>,
+    ) -> Result<bool, Error> {
+        let path = path.into();
+        let found = self.add_patterns_file(path.clone(), dir, Kind::Directory, buf)?;
+        if !found {
+            self.directories.push(PatternList {
+                patterns: Vec::new(),
+                source: Some(path),
+                base: super::base(dir),
+            });
+        }
+        Ok(found)
+    }
+
+    /// Push the patterns in `bytes`, the content of a `.gitattributes` file in the slash-separated directory `dir` and
+    /// possibly read from `source`, for example from the index or a tree.
+    pub fn push_directory_patterns_buffer(
+        &mut self,
+        bytes: &[u8],
+        dir: &BStr,
+        source: Option<PathBuf>,
+    ) -> Result<(), Error> {
+        self.add_patterns_buffer(bytes, source, dir, Kind::Directory)
+    }
+
+    /// Remove the patterns of the most recently pushed directory, and return true if there was one.
+    pub fn pop_directory(&mut self) -> bool {
+        self.directories.pop().is_some()
+    }
+
+    /// Match the slash-separated `relative_path`, relative to the root of the worktree, against all patterns and write
+    /// the assigned attributes, including the ones assigned through macros, into `out`.
+    /// `is_dir` is true if the path is a directory, and `case` controls case-sensitivity.
+    ///
+    /// Return true if at least one selected attribute was assigned.
+    pub fn pattern_matching_relative_path<'a>(
+        &'a self,
+        relative_path: &BStr,
+        is_dir: bool,
+        case: Case,
+        out: &mut Outcome<'a>,
+    ) -> bool {
+        out.clear();
+        let lists = self
+            .overrides
+            .iter()
+            .rev()
+            .chain(self.directories.iter().rev())
+            .chain(self.globals.iter().rev());
+        let mut expanding = Vec::new();
+        'outer: for list in lists {
+            let relative_path = match list.strip_base(relative_path, case) {
+                Some(path) => path,
+                None => continue,
+            };
+            for mapping in list.patterns.iter().rev() {
+                if !mapping.pattern.matches_relative_path(relative_path, is_dir, case) {
+                    continue;
+                }
+                for (name, value) in mapping.value.iter().rev() {
+                    self.fill(name.as_bstr(), value, mapping, list, out, &mut expanding);
+                }
+                if out.is_done() {
+                    break 'outer;
+                }
+            }
+        }
+        out.selected != 0
+    }
+
+    /// Assign `value` to `name` unless it was assigned already, and expand `name` if it's a macro that is set.
+    fn fill<'a>(
+        &'a self,
+        name: &'a BStr,
+        value: &'a Value,
+        mapping: &'a Mapping<Assignments>,
+        list: &'a PatternList<Assignments>,
+        out: &mut Outcome<'a>,
+        expanding: &mut Vec<&'a BStr>,
+    ) {
+        if out.matches.iter().any(|m| m.attribute == name) {
+            return;
+        }
+        out.push(Match {
+            attribute: name,
+            state: value.as_state(),
+            pattern: &mapping.pattern,
+            source: list.source.as_deref(),
+            line_number: mapping.line_number,
+        });
+        if *value != Value::Set || expanding.contains(&name) {
+            return;
+        }
+        if let Some((macro_name, assignments)) = self.macros.get_key_value(name) {
+            expanding.push(macro_name.as_bstr());
+            for (name, value) in assignments.iter().rev() {
+                self.fill(name.as_bstr(), value, mapping, list, out, expanding);
+            }
+            expanding.pop();
+        }
+    }
+
+    fn add_patterns_file(&mut self, path: PathBuf, dir: &BStr, kind: Kind, buf: &mut Vec<u8>) -> Result<bool, Error> {
+        match super::read_in_full_ignore_missing(&path, buf) {
+            Ok(true) => {}
+            Ok(false) => return Ok(false),
+            Err(err) => return Err(Error::Io { err, path }),
+        }
+        let bytes = std::mem::take(buf);
+        let res = self.add_patterns_buffer(&bytes, Some(path), dir, kind);
+        *buf = bytes;
+        res.map(|_| true)
+    }
+
+    fn add_patterns_buffer(
+        &mut self,
+        bytes: &[u8],
+        source: Option<PathBuf>,
+        dir: &BStr,
+        kind: Kind,
+    ) -> Result<(), Error> {
+        let base = super::base(dir);
+        let may_define_macros = base.is_empty();
+        let mut patterns = Vec::new();
+        for line in parse(bytes) {
+            let parsed = line.and_then(|(pattern_kind, attrs, line_number)| {
+                let assignments = attrs
+                    .map(|res| res.map(|(name, state)| (BString::from(name), Value::from(state))))
+                    .collect::<Result<Assignments, _>>()?;
+                Ok((pattern_kind, assignments, line_number))
+            });
+            let (pattern_kind, assignments, line_number) = match parsed {
+                Ok(parsed) => parsed,
+                Err(err) => {
+                    return Err(Error::Parse {
+                        err,
+                        path: source.clone(),
+                    })
+                }
+            };
+            match pattern_kind {
+                parse::Kind::Pattern(text, mode) => patterns.push(Mapping {
+                    pattern: Pattern::new(text, mode),
+                    value: assignments,
+                    line_number,
+                }),
+                parse::Kind::Macro(name) => {
+                    if may_define_macros {
+                        self.macros.insert(name, assignments);
+                    }
+                }
+            }
+        }
+        let list = PatternList { patterns, source, base };
+        match kind {
+            Kind::Global => self.globals.push(list),
+            Kind::Directory => self.directories.push(list),
+            Kind::Override => self.overrides.push(list),
+        }
+        Ok(())
+    }
+}
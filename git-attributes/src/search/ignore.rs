@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+
+use bstr::BStr;
+
+use super::{Mapping, PatternList};
+use crate::ignore::pattern::{Case, Pattern};
+
+mod error {
+    use std::path::PathBuf;
+
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Io { err: std::io::Error, path: PathBuf } {
+                display("Could not read exclude file at '{}'", path.display())
+                source(err)
+            }
+        }
+    }
+}
+pub use error::Error;
+
+/// The pattern that decided whether or not a path is excluded.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct Match<'a> {
+    /// The last pattern matching the path, which excludes it unless it's [negative][Pattern::is_negative()].
+    pub pattern: &'a Pattern,
+    /// The file the pattern was read from, if any.
+    pub source: Option<&'a Path>,
+    /// The line number of the pattern.
+    pub line_number: usize,
+}
+
+impl Match<'_> {
+    /// Return true if the path is excluded, i.e. the pattern isn't negative.
+    pub fn is_excluded(&self) -> bool {
+        !self.pattern.is_negative()
+    }
+}
+
+/// All exclude patterns that apply to paths in a worktree, with support for per-directory `.gitignore` files that are
+/// pushed and popped as directories are entered and left.
+///
+/// Patterns are matched in order of precedence. From lowest to highest, these are the file configured in
+/// `core.excludesFile`, `$GIT_DIR/info/exclude` and the `.gitignore` files from the root of the worktree to the directory
+/// of the path. Within a file, later lines take precedence, and the last matching pattern decides if a path is excluded.
+///
+/// Note that just like in git, paths inside of excluded directories can't be included again by negative patterns, which
+/// is implemented by not descending into excluded directories in the first place.
+#[derive(Default, Debug, Clone)]
+pub struct Ignore {
+    globals: Vec<PatternList<()>>,
+    directories: Vec<PatternList<()>>,
+}
+
+impl Ignore {
+    /// Add the patterns of the global exclude file at `path`, which take precedence over the ones added previously.
+    /// Hence the file configured in `core.excludesFile` should be added before `$GIT_DIR/info/exclude`.
+    /// `buf` is used to read the file. Return false if the file didn't exist.
+    pub fn add_global_patterns_file(&mut self, path: impl Into<PathBuf>, buf: &mut Vec<u8>) -> Result<bool, Error> {
+        let path = path.into();
+        let list = match read_patterns_file(path, "".into(), buf)? {
+            Some(list) => list,
+            None => return Ok(false),
+        };
+        self.globals.push(list);
+        Ok(true)
+    }
+
+    /// Push the patterns of the `.gitignore` file at `path` which is located in the slash-separated directory `dir`,
+    /// relative to the root of the worktree, using `buf` to read it.
+    /// Return false if the file didn't exist, in which case an empty list of patterns is pushed so that each push can be
+    /// undone with [`pop_directory()`][Ignore::pop_directory()].
+    pub fn push_directory_patterns_file(
+        &mut self,
+        path: impl Into<PathBuf>,
+        dir: &BStr,
+        buf: &mut Vec<u8>,
+    ) -> Result<bool, Error> {
+        let path = path.into();
+        let (list, found) = match read_patterns_file(path.clone(), dir, buf)? {
+            Some(list) => (list, true),
+            None => (
+                PatternList {
+                    patterns: Vec::new(),
+                    source: Some(path),
+                    base: super::base(dir),
+                },
+                false,
+            ),
+        };
+        self.directories.push(list);
+        Ok(found)
+    }
+
+    /// Push the patterns in `bytes`, the content of a `.gitignore` file in the slash-separated directory `dir` and
+    /// possibly read from `source`, for example from the index or a tree.
+    pub fn push_directory_patterns_buffer(&mut self, bytes: &[u8], dir: &BStr, source: Option<PathBuf>) {
+        self.directories.push(patterns_from_bytes(bytes, dir, source));
+    }
+
+    /// Remove the patterns of the most recently pushed directory, and return true if there was one.
+    pub fn pop_directory(&mut self) -> bool {
+        self.directories.pop().is_some()
+    }
+
+    /// Match the slash-separated `relative_path`, relative to the root of the worktree, against all patterns and return
+    /// the pattern with the highest precedence that matches it, if any.
+    /// `is_dir` is true if the path is a directory, and `case` controls case-sensitivity.
+    ///
+    /// Note that a match doesn't necessarily mean that the path [is excluded][Match::is_excluded()].
+    pub fn pattern_matching_relative_path(&self, relative_path: &BStr, is_dir: bool, case: Case) -> Option<Match<'_>> {
+        self.directories
+            .iter()
+            .rev()
+            .chain(self.globals.iter().rev())
+            .find_map(|list| {
+                let relative_path = list.strip_base(relative_path, case)?;
+                list.patterns
+                    .iter()
+                    .rev()
+                    .find(|mapping| mapping.pattern.matches_relative_path(relative_path, is_dir, case))
+                    .map(|mapping| Match {
+                        pattern: &mapping.pattern,
+                        source: list.source.as_deref(),
+                        line_number: mapping.line_number,
+                    })
+            })
+    }
+
+    /// Return true if the slash-separated `relative_path` is excluded, see
+    /// [`pattern_matching_relative_path()`][Ignore::pattern_matching_relative_path()] for details.
+    pub fn is_excluded(&self, relative_path: &BStr, is_dir: bool, case: Case) -> bool {
+        self.pattern_matching_relative_path(relative_path, is_dir, case)
+            .map_or(false, |m| m.is_excluded())
+    }
+}
+
+fn read_patterns_file(path: PathBuf, dir: &BStr, buf: &mut Vec<u8>) -> Result<Option<PatternList<()>>, Error> {
+    match super::read_in_full_ignore_missing(&path, buf) {
+        Ok(true) => Ok(Some(patterns_from_bytes(buf, dir, Some(path)))),
+        Ok(false) => Ok(None),
+        Err(err) => Err(Error::Io { err, path }),
+    }
+}
+
+fn patterns_from_bytes(bytes: &[u8], dir: &BStr, source: Option<PathBuf>) -> PatternList<()> {
+    PatternList {
+        patterns: crate::parse::ignore(bytes)
+            .map(|(text, mode, line_number)| Mapping {
+                pattern: Pattern::new(text, mode),
+                value: (),
+                line_number,
+            })
+            .collect(),
+        source,
+        base: super::base(dir),
+    }
+}
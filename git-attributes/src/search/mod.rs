@@ -0,0 +1,66 @@
+//! Match paths against the patterns of `.gitattributes` and `.gitignore` files, with all the precedence rules of git.
+use std::path::{Path, PathBuf};
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::ignore::pattern::{Case, Pattern};
+
+///
+pub mod attributes;
+pub use attributes::Attributes;
+
+///
+pub mod ignore;
+pub use ignore::Ignore;
+
+/// A pattern along with the value it is associated with.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub(crate) struct Mapping<T> {
+    pub pattern: Pattern,
+    pub value: T,
+    pub line_number: usize,
+}
+
+/// All patterns of a single file or buffer.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub(crate) struct PatternList<T> {
+    pub patterns: Vec<Mapping<T>>,
+    /// The file the patterns were read from, if any.
+    pub source: Option<PathBuf>,
+    /// The slash-separated directory the patterns are relative to, with a trailing slash, or empty for the root.
+    pub base: BString,
+}
+
+impl<T> PatternList<T> {
+    /// Return `relative_path` relative to our base, or `None` if it's not inside of it.
+    pub fn strip_base<'a>(&self, relative_path: &'a BStr, case: Case) -> Option<&'a BStr> {
+        if self.base.is_empty() {
+            return Some(relative_path);
+        }
+        let prefix = relative_path.get(..self.base.len())?;
+        let is_prefix = match case {
+            Case::Sensitive => prefix == self.base.as_slice(),
+            Case::Fold => prefix.eq_ignore_ascii_case(&self.base),
+        };
+        is_prefix.then(|| relative_path[self.base.len()..].as_bstr())
+    }
+}
+
+/// Turn the slash-separated directory `dir` into the base of a pattern list.
+pub(crate) fn base(dir: &BStr) -> BString {
+    let mut base: BString = dir.trim_end_with(|c| c == '/').into();
+    if !base.is_empty() {
+        base.push(b'/');
+    }
+    base
+}
+
+/// Read the file at `path` into `buf` and return true, or return false if it doesn't exist.
+pub(crate) fn read_in_full_ignore_missing(path: &Path, buf: &mut Vec<u8>) -> std::io::Result<bool> {
+    buf.clear();
+    match std::fs::File::open(path).and_then(|mut file| std::io::Read::read_to_end(&mut file, buf)) {
+        Ok(_) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,285 @@
+//! A port of git's `wildmatch`, which matches paths against the glob patterns used in `.gitattributes` and `.gitignore` files.
+use bitflags::bitflags;
+use bstr::BStr;
+
+bitflags! {
+    /// Flags to control how [`wildmatch()`][crate::wildmatch()] matches.
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Mode: u8 {
+        /// Let `*` and `?` not match slashes, and `**` match any amount of directories, as is typical for paths.
+        const NO_MATCH_SLASH_LITERAL = 1 << 0;
+        /// Match ASCII characters case-insensitively.
+        const IGNORE_CASE = 1 << 1;
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum MatchResult {
+    Match,
+    NoMatch,
+    AbortAll,
+    AbortToStarStar,
+}
+
+const STAR: u8 = b'*';
+const BACKSLASH: u8 = b'\\';
+const SLASH: u8 = b'/';
+const BRACKET_OPEN: u8 = b'[';
+const BRACKET_CLOSE: u8 = b']';
+const COLON: u8 = b':';
+
+/// Access `s` at `pos`, using a null byte as end marker just like the original implementation does.
+fn at(s: &[u8], pos: usize) -> u8 {
+    s.get(pos).copied().unwrap_or(0)
+}
+
+fn is_glob_special(b: u8) -> bool {
+    matches!(b, b'*' | b'?' | b'[' | b'\\')
+}
+
+fn fold(b: u8, mode: Mode) -> u8 {
+    if mode.contains(Mode::IGNORE_CASE) {
+        b.to_ascii_lowercase()
+    } else {
+        b
+    }
+}
+
+fn match_recursive(pattern: &[u8], text: &[u8], mode: Mode) -> MatchResult {
+    use self::MatchResult::*;
+    let mut p = 0;
+    let mut t = 0;
+    while p < pattern.len() {
+        let mut p_ch = fold(pattern[p], mode);
+        let mut t_ch = at(text, t);
+        if t_ch == 0 && p_ch != STAR {
+            return AbortAll;
+        }
+        t_ch = fold(t_ch, mode);
+        match p_ch {
+            b'?' => {
+                if mode.contains(Mode::NO_MATCH_SLASH_LITERAL) && t_ch == SLASH {
+                    return NoMatch;
+                }
+            }
+            STAR => {
+                let match_slash;
+                p += 1;
+                if at(pattern, p) == STAR {
+                    let leading_slash_or_start = p < 2 || pattern[p - 2] == SLASH;
+                    while at(pattern, p + 1) == STAR {
+                        p += 1;
+                    }
+                    p += 1;
+                    let next = at(pattern, p);
+                    if leading_slash_or_start
+                        && (next == 0 || next == SLASH || (next == BACKSLASH && at(pattern, p + 1) == SLASH))
+                    {
+                        // Assuming we already match `foo/` and are at `**/`, just assume it matches nothing and
+                        // try to match the rest of the pattern with the remaining text, so that `foo/**/bar`
+                        // matches both `foo/bar` and `foo/a/bar`.
+                        if next == SLASH && match_recursive(&pattern[p + 1..], &text[t..], mode) == Match {
+                            return Match;
+                        }
+                        match_slash = true;
+                    } else {
+                        match_slash = !mode.contains(Mode::NO_MATCH_SLASH_LITERAL);
+                    }
+                } else {
+                    // Without paths being special, `*` is the same as `**`.
+                    match_slash = !mode.contains(Mode::NO_MATCH_SLASH_LITERAL);
+                }
+
+                if p >= pattern.len() {
+                    // A trailing `**` matches everything, a trailing `*` only matches if there are no more slashes.
+                    return if !match_slash && text[t..].contains(&SLASH) {
+                        NoMatch
+                    } else {
+                        Match
+                    };
+                } else if !match_slash && pattern[p] == SLASH {
+                    // A single star followed by a slash matches the next directory.
+                    match text[t..].iter().position(|b| *b == SLASH) {
+                        Some(pos) => {
+                            t += pos;
+                            // The slash is consumed below, like for any other character.
+                            p += 1;
+                            t += 1;
+                            continue;
+                        }
+                        None => return NoMatch,
+                    }
+                }
+
+                loop {
+                    if t_ch == 0 {
+                        break;
+                    }
+                    // Advance faster if the star is followed by a literal, as everything before it belongs to the star.
+                    // Don't look past a slash if the star can't match one.
+                    if !is_glob_special(pattern[p]) {
+                        p_ch = fold(pattern[p], mode);
+                        loop {
+                            t_ch = fold(at(text, t), mode);
+                            if t_ch == 0 || (!match_slash && t_ch == SLASH) || t_ch == p_ch {
+                                break;
+                            }
+                            t += 1;
+                        }
+                        if t_ch != p_ch {
+                            return NoMatch;
+                        }
+                    }
+                    let res = match_recursive(&pattern[p..], &text[t..], mode);
+                    if res != NoMatch {
+                        if !match_slash || res != AbortToStarStar {
+                            return res;
+                        }
+                    } else if !match_slash && t_ch == SLASH {
+                        return AbortToStarStar;
+                    }
+                    t += 1;
+                    t_ch = fold(at(text, t), mode);
+                }
+                return AbortAll;
+            }
+            BRACKET_OPEN => {
+                p += 1;
+                p_ch = at(pattern, p);
+                if p_ch == b'^' {
+                    p_ch = b'!';
+                }
+                let negated = p_ch == b'!';
+                if negated {
+                    p += 1;
+                    p_ch = at(pattern, p);
+                }
+                let mut prev_ch = 0;
+                let mut matched = false;
+                loop {
+                    if p_ch == 0 {
+                        return AbortAll;
+                    }
+                    if p_ch == BACKSLASH {
+                        p += 1;
+                        p_ch = at(pattern, p);
+                        if p_ch == 0 {
+                            return AbortAll;
+                        }
+                        if t_ch == fold(p_ch, mode) {
+                            matched = true;
+                        }
+                    } else if p_ch == b'-'
+                        && prev_ch != 0
+                        && at(pattern, p + 1) != 0
+                        && at(pattern, p + 1) != BRACKET_CLOSE
+                    {
+                        p += 1;
+                        p_ch = at(pattern, p);
+                        if p_ch == BACKSLASH {
+                            p += 1;
+                            p_ch = at(pattern, p);
+                            if p_ch == 0 {
+                                return AbortAll;
+                            }
+                        }
+                        let original_t_ch = at(text, t);
+                        if (prev_ch..=p_ch).contains(&original_t_ch) {
+                            matched = true;
+                        } else if mode.contains(Mode::IGNORE_CASE) {
+                            let upper = original_t_ch.to_ascii_uppercase();
+                            let lower = original_t_ch.to_ascii_lowercase();
+                            if (prev_ch..=p_ch).contains(&upper) || (prev_ch..=p_ch).contains(&lower) {
+                                matched = true;
+                            }
+                        }
+                        // This makes `prev_ch` get set to 0.
+                        p_ch = 0;
+                    } else if p_ch == BRACKET_OPEN && at(pattern, p + 1) == COLON {
+                        p += 2;
+                        let class_start = p;
+                        while at(pattern, p) != 0 && at(pattern, p) != BRACKET_CLOSE {
+                            p += 1;
+                        }
+                        if at(pattern, p) == 0 {
+                            return AbortAll;
+                        }
+                        if p == class_start || pattern[p - 1] != COLON {
+                            // Didn't find `:]`, so treat it like a normal set.
+                            p = class_start - 2;
+                            p_ch = BRACKET_OPEN;
+                            if t_ch == p_ch {
+                                matched = true;
+                            }
+                        } else {
+                            let original_t_ch = at(text, t);
+                            let class_matched = match &pattern[class_start..p - 1] {
+                                b"alnum" => original_t_ch.is_ascii_alphanumeric(),
+                                b"alpha" => original_t_ch.is_ascii_alphabetic(),
+                                b"blank" => original_t_ch == b' ' || original_t_ch == b'\t',
+                                b"cntrl" => original_t_ch.is_ascii_control(),
+                                b"digit" => original_t_ch.is_ascii_digit(),
+                                b"graph" => original_t_ch.is_ascii_graphic(),
+                                b"lower" => {
+                                    original_t_ch.is_ascii_lowercase()
+                                        || (mode.contains(Mode::IGNORE_CASE) && original_t_ch.is_ascii_uppercase())
+                                }
+                                b"print" => original_t_ch.is_ascii_graphic() || original_t_ch == b' ',
+                                b"punct" => original_t_ch.is_ascii_punctuation(),
+                                b"space" => matches!(original_t_ch, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c),
+                                b"upper" => {
+                                    original_t_ch.is_ascii_uppercase()
+                                        || (mode.contains(Mode::IGNORE_CASE) && original_t_ch.is_ascii_lowercase())
+                                }
+                                b"xdigit" => original_t_ch.is_ascii_hexdigit(),
+                                // A malformed class name.
+                                _ => return AbortAll,
+                            };
+                            if class_matched {
+                                matched = true;
+                            }
+                            // This makes `prev_ch` get set to 0.
+                            p_ch = 0;
+                        }
+                    } else if t_ch == fold(p_ch, mode) {
+                        matched = true;
+                    }
+
+                    prev_ch = p_ch;
+                    p += 1;
+                    p_ch = at(pattern, p);
+                    if p_ch == BRACKET_CLOSE {
+                        break;
+                    }
+                }
+                if matched == negated || (mode.contains(Mode::NO_MATCH_SLASH_LITERAL) && t_ch == SLASH) {
+                    return NoMatch;
+                }
+            }
+            BACKSLASH => {
+                p += 1;
+                if t_ch != fold(at(pattern, p), mode) {
+                    return NoMatch;
+                }
+            }
+            _ => {
+                if t_ch != p_ch {
+                    return NoMatch;
+                }
+            }
+        }
+        p += 1;
+        t += 1;
+    }
+    if t < text.len() {
+        NoMatch
+    } else {
+        Match
+    }
+}
+
+/// Return true if `value` matches the glob `pattern` according to `mode`, with semantics that are compatible to the ones
+/// used by git to match paths in `.gitignore` and `.gitattributes` files.
+pub fn wildmatch(pattern: &BStr, value: &BStr, mode: Mode) -> bool {
+    match_recursive(pattern, value, mode) == MatchResult::Match
+}
@@ -1 +1,5 @@
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
 mod parse;
+mod search;
+mod wildmatch;
@@ -0,0 +1,177 @@
+use bstr::ByteSlice;
+use git_attributes::{
+    ignore::pattern::Case,
+    search::{attributes::Outcome, Attributes},
+    State,
+};
+use git_testtools::fixture_path;
+
+fn states<'a>(attrs: &'a Attributes, path: &str, names: &[&str]) -> Vec<State<'a>> {
+    let mut out = Outcome::default();
+    attrs.pattern_matching_relative_path(path.into(), false, Case::Sensitive, &mut out);
+    names.iter().map(|name| out.state(name)).collect()
+}
+
+fn root(buf: &str) -> Attributes {
+    let mut attrs = Attributes::default();
+    attrs
+        .push_directory_patterns_buffer(buf.as_bytes(), "".into(), None)
+        .unwrap();
+    attrs
+}
+
+#[test]
+fn later_lines_take_precedence_over_earlier_ones() {
+    let attrs = root("*.txt text eol=crlf\nspecial.txt -text\n*.txt !eol\n");
+    assert_eq!(
+        states(&attrs, "dir/special.txt", &["text", "eol"]),
+        vec![State::Unset, State::Unspecified]
+    );
+    assert_eq!(
+        states(&attrs, "a.txt", &["text", "eol"]),
+        vec![State::Set, State::Unspecified]
+    );
+    assert_eq!(states(&attrs, "a.rs", &["text"]), vec![State::Unspecified]);
+}
+
+#[test]
+fn later_assignments_on_the_same_line_take_precedence() {
+    let attrs = root("a eol=lf eol=crlf\n");
+    assert_eq!(states(&attrs, "a", &["eol"]), vec![State::Value("crlf".into())]);
+}
+
+#[test]
+fn deeper_directories_take_precedence_and_can_be_popped() {
+    let mut attrs = root("*.txt text\n");
+    attrs
+        .push_directory_patterns_buffer(b"*.txt -text\n/top.txt diff\n", "dir".into(), None)
+        .unwrap();
+
+    assert_eq!(states(&attrs, "dir/a.txt", &["text"]), vec![State::Unset]);
+    assert_eq!(states(&attrs, "a.txt", &["text"]), vec![State::Set], "outside of 'dir'");
+    assert_eq!(
+        states(&attrs, "dir/top.txt", &["diff"]),
+        vec![State::Set],
+        "anchored patterns are relative to their directory"
+    );
+    assert_eq!(states(&attrs, "dir/sub/top.txt", &["diff"]), vec![State::Unspecified]);
+
+    assert!(attrs.pop_directory());
+    assert_eq!(states(&attrs, "dir/a.txt", &["text"]), vec![State::Set]);
+    assert!(attrs.pop_directory());
+    assert!(!attrs.pop_directory());
+}
+
+#[test]
+fn info_attributes_override_everything_and_globals_nothing() -> crate::Result {
+    let mut attrs = Attributes::default();
+    let mut buf = Vec::new();
+    assert!(attrs.add_info_patterns_file(fixture_path("attributes/info.txt"), &mut buf)?);
+    assert!(
+        !attrs.add_global_patterns_file(fixture_path("attributes/does-not-exist"), &mut buf)?,
+        "missing files are fine"
+    );
+    attrs.push_directory_patterns_buffer(b"*.txt text eol=crlf diff\n", "".into(), None)?;
+    attrs.add_global_patterns_file(fixture_path("attributes/various.txt"), &mut buf)?;
+
+    let mut out = Outcome::default();
+    assert!(attrs.pattern_matching_relative_path("a.txt".into(), false, Case::Sensitive, &mut out));
+    assert_eq!(out.state("text"), State::Unset);
+    assert_eq!(out.state("eol"), State::Value("lf".into()));
+    assert_eq!(out.state("diff"), State::Set);
+    let m = out.get("eol").expect("present");
+    assert_eq!(m.line_number, 2);
+    assert_eq!(m.source, Some(fixture_path("attributes/info.txt").as_path()));
+    assert_eq!(m.pattern.text, "*.txt");
+
+    assert!(attrs.pattern_matching_relative_path("x.o".into(), false, Case::Sensitive, &mut out));
+    assert_eq!(out.state("c"), State::Set, "globals apply if nothing else matches");
+    Ok(())
+}
+
+#[test]
+fn missing_directory_files_can_still_be_popped() -> crate::Result {
+    let mut attrs = Attributes::default();
+    let mut buf = Vec::new();
+    assert!(!attrs.push_directory_patterns_file(
+        fixture_path("attributes/missing/.gitattributes"),
+        "missing".into(),
+        &mut buf
+    )?);
+    assert!(attrs.pop_directory());
+    Ok(())
+}
+
+#[test]
+fn the_binary_macro_is_built_in_and_expands_recursively() {
+    let attrs =
+        root("[attr]nodiff -diff\n[attr]blob binary nodiff eol=lf\n*.png binary\n*.dat blob\n*.bin binary diff\n");
+    assert_eq!(
+        states(&attrs, "a.png", &["binary", "diff", "merge", "text"]),
+        vec![State::Set, State::Unset, State::Unset, State::Unset]
+    );
+    assert_eq!(
+        states(&attrs, "a.dat", &["blob", "binary", "text", "eol"]),
+        vec![State::Set, State::Set, State::Unset, State::Value("lf".into())]
+    );
+    assert_eq!(
+        states(&attrs, "a.bin", &["diff", "text"]),
+        vec![State::Set, State::Unset],
+        "assignments following the macro on the same line take precedence"
+    );
+}
+
+#[test]
+fn unset_macros_are_not_expanded_and_cycles_terminate() {
+    let attrs = root("[attr]a b\n[attr]b a c\nx -binary\ny a\n");
+    assert_eq!(
+        states(&attrs, "x", &["binary", "text"]),
+        vec![State::Unset, State::Unspecified]
+    );
+    assert_eq!(
+        states(&attrs, "y", &["a", "b", "c"]),
+        vec![State::Set, State::Set, State::Set]
+    );
+}
+
+#[test]
+fn macros_are_only_defined_at_the_root() {
+    let mut attrs = Attributes::default();
+    attrs
+        .push_directory_patterns_buffer(b"[attr]mine text\n*.x mine\n", "dir".into(), None)
+        .unwrap();
+    assert_eq!(
+        states(&attrs, "dir/a.x", &["mine", "text"]),
+        vec![State::Set, State::Unspecified]
+    );
+}
+
+#[test]
+fn selections_only_contain_the_selected_attributes() {
+    let attrs = root("* text diff\n*.png binary\n");
+    let mut out = Outcome::with_selection(["text", "eol"]);
+    assert!(attrs.pattern_matching_relative_path("a.png".into(), false, Case::Sensitive, &mut out));
+    assert_eq!(
+        out.iter().map(|m| (m.attribute, m.state)).collect::<Vec<_>>(),
+        vec![("text".as_bytes().as_bstr(), State::Unset)]
+    );
+}
+
+#[test]
+fn directory_patterns_and_case_folding() {
+    let attrs = root("build/ export-ignore\nREADME diff\n");
+    let mut out = Outcome::default();
+    assert!(!attrs.pattern_matching_relative_path("build".into(), false, Case::Sensitive, &mut out));
+    assert!(attrs.pattern_matching_relative_path("build".into(), true, Case::Sensitive, &mut out));
+    assert!(!attrs.pattern_matching_relative_path("readme".into(), false, Case::Sensitive, &mut out));
+    assert!(attrs.pattern_matching_relative_path("readme".into(), false, Case::Fold, &mut out));
+}
+
+#[test]
+fn invalid_lines_are_errors_with_source() {
+    let mut attrs = Attributes::default();
+    let err = attrs
+        .push_directory_patterns_buffer(b"!negated a\n", "".into(), Some("dir/.gitattributes".into()))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Could not parse attributes in 'dir/.gitattributes'");
+}
@@ -0,0 +1,95 @@
+use git_attributes::{ignore::pattern::Case, search::Ignore};
+use git_testtools::fixture_path;
+
+#[test]
+fn negations_and_directory_patterns() {
+    let mut ignore = Ignore::default();
+    ignore.push_directory_patterns_buffer(b"*.o\n!keep.o\nout/\n", "".into(), None);
+
+    assert!(ignore.is_excluded("a.o".into(), false, Case::Sensitive));
+    assert!(ignore.is_excluded("dir/a.o".into(), false, Case::Sensitive));
+    assert!(!ignore.is_excluded("dir/keep.o".into(), false, Case::Sensitive));
+    let m = ignore
+        .pattern_matching_relative_path("keep.o".into(), false, Case::Sensitive)
+        .expect("negative patterns match as well");
+    assert!(m.pattern.is_negative());
+    assert_eq!(m.line_number, 2);
+
+    assert!(ignore.is_excluded("out".into(), true, Case::Sensitive));
+    assert!(
+        !ignore.is_excluded("out".into(), false, Case::Sensitive),
+        "directory patterns don't match files"
+    );
+    assert!(!ignore.is_excluded("a.c".into(), false, Case::Sensitive));
+    assert!(ignore
+        .pattern_matching_relative_path("a.c".into(), false, Case::Sensitive)
+        .is_none());
+}
+
+#[test]
+fn nested_files_take_precedence_and_can_be_popped() {
+    let mut ignore = Ignore::default();
+    ignore.push_directory_patterns_buffer(b"*.txt\n", "".into(), None);
+    ignore.push_directory_patterns_buffer(b"!*.txt\n/anchored\n", "sub".into(), None);
+
+    assert!(!ignore.is_excluded("sub/a.txt".into(), false, Case::Sensitive));
+    assert!(ignore.is_excluded("a.txt".into(), false, Case::Sensitive));
+    assert!(ignore.is_excluded("other/a.txt".into(), false, Case::Sensitive));
+    assert!(ignore.is_excluded("sub/anchored".into(), false, Case::Sensitive));
+    assert!(!ignore.is_excluded("sub/dir/anchored".into(), false, Case::Sensitive));
+    assert!(!ignore.is_excluded("anchored".into(), false, Case::Sensitive));
+
+    assert!(ignore.pop_directory());
+    assert!(ignore.is_excluded("sub/a.txt".into(), false, Case::Sensitive));
+    assert!(ignore.pop_directory());
+    assert!(!ignore.pop_directory());
+}
+
+#[test]
+fn precedence_of_global_files() -> crate::Result {
+    let mut ignore = Ignore::default();
+    let mut buf = Vec::new();
+    assert!(ignore.add_global_patterns_file(fixture_path("ignore/excludes.txt"), &mut buf)?);
+    assert!(ignore.add_global_patterns_file(fixture_path("ignore/various.txt"), &mut buf)?);
+    assert!(!ignore.add_global_patterns_file(fixture_path("ignore/does-not-exist"), &mut buf)?);
+
+    let m = ignore
+        .pattern_matching_relative_path("dir/a.swp".into(), false, Case::Sensitive)
+        .expect("match");
+    assert_eq!(m.source, Some(fixture_path("ignore/excludes.txt").as_path()));
+    assert_eq!(m.line_number, 2);
+    assert!(ignore.is_excluded("dir/target".into(), true, Case::Sensitive));
+    assert!(
+        !ignore.is_excluded("dir/foo.html".into(), false, Case::Sensitive),
+        "later files take precedence"
+    );
+
+    ignore.push_directory_patterns_buffer(b"!*.swp\n", "".into(), None);
+    assert!(
+        !ignore.is_excluded("dir/a.swp".into(), false, Case::Sensitive),
+        "per-directory files take precedence over global ones"
+    );
+    Ok(())
+}
+
+#[test]
+fn missing_directory_files_can_still_be_popped() -> crate::Result {
+    let mut ignore = Ignore::default();
+    let mut buf = Vec::new();
+    assert!(!ignore.push_directory_patterns_file(
+        fixture_path("ignore/missing/.gitignore"),
+        "missing".into(),
+        &mut buf
+    )?);
+    assert!(ignore.pop_directory());
+    Ok(())
+}
+
+#[test]
+fn case_folding() {
+    let mut ignore = Ignore::default();
+    ignore.push_directory_patterns_buffer(b"/Build/\n*.LOG\n", "Dir".into(), None);
+    assert!(!ignore.is_excluded("dir/build".into(), true, Case::Sensitive));
+    assert!(ignore.is_excluded("dir/build".into(), true, Case::Fold));
+    assert!(ignore.is_excluded("dir/sub/a.log".into(), false, Case::Fold));
+}
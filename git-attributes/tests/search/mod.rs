@@ -0,0 +1,2 @@
+mod attributes;
+mod ignore;
@@ -0,0 +1,99 @@
+use git_attributes::{wildmatch, wildmatch::Mode};
+
+/// A subset of the cases in git's `t3070-wildmatch.sh` as `(pattern, text, matches as path, matches as plain string)`.
+const CASES: &[(&str, &str, bool, bool)] = &[
+    ("foo", "foo", true, true),
+    ("bar", "foo", false, false),
+    ("", "", true, true),
+    ("???", "foo", true, true),
+    ("??", "foo", false, false),
+    ("*", "foo", true, true),
+    ("f*", "foo", true, true),
+    ("*f", "foo", false, false),
+    ("*foo*", "foo", true, true),
+    ("*ob*a*r*", "foobar", true, true),
+    ("*ab", "aaaaaaabababab", true, true),
+    (r"foo\*", "foo*", true, true),
+    (r"foo\*bar", "foobar", false, false),
+    (r"f\\oo", r"f\oo", true, true),
+    ("*[al]?", "ball", true, true),
+    ("[ten]", "ten", false, false),
+    ("**[!te]", "ten", true, true),
+    ("**[!ten]", "ten", false, false),
+    ("t[a-g]n", "ten", true, true),
+    ("t[!a-g]n", "ten", false, false),
+    ("t[!a-g]n", "ton", true, true),
+    ("t[^a-g]n", "ton", true, true),
+    ("a[]]b", "a]b", true, true),
+    ("a[]-]b", "a-b", true, true),
+    ("foo*bar", "foo/baz/bar", false, true),
+    ("foo**bar", "foo/baz/bar", false, true),
+    ("foo/**/bar", "foo/baz/bar", true, true),
+    ("foo/**/**/bar", "foo/baz/bar", true, true),
+    ("foo/**/bar", "foo/b/a/z/bar", true, true),
+    ("foo/**/bar", "foo/bar", true, true),
+    ("foo/**/**/bar", "foo/bar", true, true),
+    ("foo?bar", "foo/bar", false, true),
+    ("**/foo", "foo", true, true),
+    ("**/foo", "XXX/foo", true, true),
+    ("**/foo", "bar/baz/foo", true, true),
+    ("*/foo", "bar/baz/foo", false, true),
+    ("**/bar*", "foo/bar/baz", false, true),
+    ("**/bar/*", "deep/foo/bar/baz", true, true),
+    ("**/bar/**", "deep/foo/bar/baz/", true, true),
+    ("**/bar/*", "deep/foo/bar", false, false),
+    ("**/bar/**", "deep/foo/bar/", true, true),
+    ("*/bar/**", "foo/bar/baz/x", true, true),
+    ("**/**", "foo", true, true),
+    ("*.c", "mozilla-sha1/sha1.c", false, true),
+    ("*/*/*", "foo/bba/arr", true, true),
+    ("*/*/*", "foo/bb/aa/rr", false, true),
+    ("**", "foo/bb/aa/rr", true, true),
+    ("*X*i", "abcXdefXghi", true, true),
+    ("*/*X*/*/*i", "ab/cXd/efXg/hi", true, true),
+    ("**/*X*/**/*i", "ab/cXd/efXg/hi", true, true),
+    ("[[:alpha:]][[:digit:]][[:upper:]]", "a1B", true, true),
+    ("[[:digit:][:upper:][:space:]]", "a", false, false),
+    ("[a-c]b*", "abc", true, true),
+    ("[a-y]*[^c]", "abd", true, true),
+    (
+        "-*-*-*-*-*-*-12-*-*-*-m-*-*-*",
+        "-adobe-courier-bold-o-normal--12-120-75-75-m-70-iso8859-1",
+        true,
+        true,
+    ),
+    (
+        "XXX/*/*/*/*/*/*/12/*/*/*/m/*/*/*",
+        "XXX/adobe/courier/bold/o/normal//12/120/75/75/X/70/iso8859/1",
+        false,
+        false,
+    ),
+];
+
+#[test]
+fn compatibility_with_git() {
+    for &(pattern, text, as_path, as_plain) in CASES {
+        assert_eq!(
+            wildmatch(pattern.into(), text.into(), Mode::NO_MATCH_SLASH_LITERAL),
+            as_path,
+            "'{}' matching path '{}'",
+            pattern,
+            text
+        );
+        assert_eq!(
+            wildmatch(pattern.into(), text.into(), Mode::empty()),
+            as_plain,
+            "'{}' matching '{}'",
+            pattern,
+            text
+        );
+    }
+}
+
+#[test]
+fn case_can_be_ignored() {
+    assert!(!wildmatch("*.TXT".into(), "a.txt".into(), Mode::empty()));
+    assert!(wildmatch("*.TXT".into(), "a.txt".into(), Mode::IGNORE_CASE));
+    assert!(wildmatch("[A-C]x".into(), "bX".into(), Mode::IGNORE_CASE));
+    assert!(wildmatch("[[:upper:]]".into(), "b".into(), Mode::IGNORE_CASE));
+}
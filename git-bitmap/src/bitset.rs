@@ -0,0 +1,130 @@
+use crate::Bitset;
+
+const BITS_PER_WORD: usize = 64;
+
+#[inline]
+fn word_and_mask(index: usize) -> (usize, u64) {
+    (index / BITS_PER_WORD, 1 << (index % BITS_PER_WORD))
+}
+
+impl PartialEq for Bitset {
+    fn eq(&self, other: &Self) -> bool {
+        let (shorter, longer) = if self.words.len() <= other.words.len() {
+            (&self.words, &other.words)
+        } else {
+            (&other.words, &self.words)
+        };
+        shorter == &longer[..shorter.len()] && longer[shorter.len()..].iter().all(|w| *w == 0)
+    }
+}
+
+impl Eq for Bitset {}
+
+/// Initialization
+impl Bitset {
+    /// Create a new set with enough room to hold `num_bits` without reallocation.
+    pub fn with_capacity(num_bits: usize) -> Self {
+        Bitset {
+            words: Vec::with_capacity((num_bits + BITS_PER_WORD - 1) / BITS_PER_WORD),
+        }
+    }
+
+    /// Create a new set from `words`, each of which holds 64 bits with the lowest bit denoting the lowest index.
+    pub fn from_words(words: Vec<u64>) -> Self {
+        Bitset { words }
+    }
+}
+
+/// Access
+impl Bitset {
+    /// Return true if the bit at `index` is set.
+    pub fn get(&self, index: usize) -> bool {
+        let (word, mask) = word_and_mask(index);
+        self.words.get(word).map_or(false, |w| w & mask != 0)
+    }
+
+    /// Set the bit at `index`, growing as needed.
+    pub fn set(&mut self, index: usize) {
+        let (word, mask) = word_and_mask(index);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= mask;
+    }
+
+    /// Clear the bit at `index`.
+    pub fn clear(&mut self, index: usize) {
+        let (word, mask) = word_and_mask(index);
+        if let Some(w) = self.words.get_mut(word) {
+            *w &= !mask;
+        }
+    }
+
+    /// Return the amount of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Return true if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Return an iterator over the indices of all set bits, in ascending order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(word_index, word)| {
+            let mut word = *word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(word_index * BITS_PER_WORD + bit)
+            })
+        })
+    }
+
+    /// Return the words backing this set.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+}
+
+/// Set operations
+impl Bitset {
+    /// Set all bits that are set in `other` as well, making this set the union of both.
+    pub fn union_with(&mut self, other: &Bitset) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a |= b;
+        }
+    }
+
+    /// Clear all bits that are not set in `other`, making this set the intersection of both.
+    pub fn intersect_with(&mut self, other: &Bitset) {
+        self.words.truncate(other.words.len());
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= b;
+        }
+    }
+
+    /// Clear all bits that are set in `other`, leaving only bits that are exclusive to this set.
+    pub fn difference_with(&mut self, other: &Bitset) {
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a &= !b;
+        }
+    }
+
+    /// Toggle all bits that are set in `other`, making this set the symmetric difference of both.
+    pub fn xor_with(&mut self, other: &Bitset) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&other.words) {
+            *a ^= b;
+        }
+    }
+}
@@ -45,6 +45,83 @@ pub fn decode(data: &[u8]) -> Result<(Vec, &[u8]), decode::Error> {
     ))
 }
 
+mod encode {
+    use std::io;
+
+    use super::{access::RLW_LARGEST_LITERAL_COUNT, access::RLW_LARGEST_RUNNING_COUNT, Vec};
+    use crate::Bitset;
+
+    impl Vec {
+        /// Compress all words of `bits` into a new bitmap, with trailing words without set bits removed.
+        pub fn from_bitset(bits: &Bitset) -> Self {
+            let words = bits.as_words();
+            let num_words = words.len() - words.iter().rev().take_while(|w| **w == 0).count();
+            let words = &words[..num_words];
+
+            let mut buf = std::vec::Vec::<u64>::with_capacity(num_words + 1);
+            let mut index = 0;
+            let rlw = loop {
+                let run_bit = words.get(index).map_or(false, |w| *w == u64::MAX);
+                let fill = if run_bit { u64::MAX } else { 0 };
+                let run_start = index;
+                while index < words.len()
+                    && words[index] == fill
+                    && ((index - run_start) as u64) < RLW_LARGEST_RUNNING_COUNT
+                {
+                    index += 1;
+                }
+                let running_len = (index - run_start) as u64;
+
+                let literal_start = index;
+                while index < words.len()
+                    && words[index] != 0
+                    && words[index] != u64::MAX
+                    && ((index - literal_start) as u64) < RLW_LARGEST_LITERAL_COUNT
+                {
+                    index += 1;
+                }
+                let literal_words = &words[literal_start..index];
+
+                let rlw = buf.len();
+                buf.push(u64::from(run_bit) | running_len << 1 | (literal_words.len() as u64) << 33);
+                buf.extend_from_slice(literal_words);
+                if index == words.len() {
+                    break rlw;
+                }
+            };
+
+            Vec {
+                num_bits: (num_words * 64) as u32,
+                bits: buf,
+                rlw: rlw as u64,
+            }
+        }
+
+        /// Like [`from_bitset()`][Vec::from_bitset()], but only count bits up to and including the highest set bit,
+        /// which is how git sizes the bitmaps stored in the index.
+        pub fn from_bitset_trimmed(bits: &Bitset) -> Self {
+            let mut vec = Self::from_bitset(bits);
+            let words = bits.as_words();
+            vec.num_bits = words
+                .iter()
+                .rposition(|w| *w != 0)
+                .map_or(0, |index| index * 64 + (64 - words[index].leading_zeros() as usize))
+                as u32;
+            vec
+        }
+
+        /// Write this bitmap in its on-disk representation to `out`, as read by [`decode()`][super::decode()].
+        pub fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
+            out.write_all(&self.num_bits.to_be_bytes())?;
+            out.write_all(&(self.bits.len() as u32).to_be_bytes())?;
+            for word in &self.bits {
+                out.write_all(&word.to_be_bytes())?;
+            }
+            out.write_all(&(self.rlw as u32).to_be_bytes())
+        }
+    }
+}
+
 mod access {
     use std::convert::{TryFrom, TryInto};
 
@@ -84,6 +161,28 @@ mod access {
             Some(())
         }
 
+        /// Decompress this bitmap into a [`Bitset`][crate::Bitset] for fast set operations and random access.
+        ///
+        /// Bits beyond [`num_bits()`][Vec::num_bits()] are never set, and truncated data is decompressed as far as possible.
+        pub fn to_bitset(&self) -> crate::Bitset {
+            let num_bits = self.num_bits();
+            let num_words = (num_bits + 63) / 64;
+            let mut words = std::vec::Vec::<u64>::with_capacity(num_words);
+            let mut iter = self.bits.iter();
+            while let Some(word) = iter.next() {
+                let fill = if rlw_runbit_is_set(word) { u64::MAX } else { 0 };
+                words.extend(std::iter::repeat(fill).take(rlw_running_len(word) as usize));
+                words.extend(iter.by_ref().take(rlw_literal_words(word) as usize));
+            }
+            words.resize(num_words, 0);
+            if num_bits % 64 != 0 {
+                if let Some(last) = words.last_mut() {
+                    *last &= (1 << (num_bits % 64)) - 1;
+                }
+            }
+            crate::Bitset::from_words(words)
+        }
+
         /// The amount of bits we are currently holding.
         pub fn num_bits(&self) -> usize {
             self.num_bits.try_into().expect("we are not on 16 bit systems")
@@ -111,11 +210,11 @@ mod access {
     }
 
     const RLW_RUNNING_BITS: u64 = 4 * 8;
-    const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    pub(super) const RLW_LARGEST_RUNNING_COUNT: u64 = (1 << RLW_RUNNING_BITS) - 1;
+    pub(super) const RLW_LARGEST_LITERAL_COUNT: u64 = (1 << (64 - 1 - RLW_RUNNING_BITS)) - 1;
 }
 
 /// A growable collection of u64 that are seen as stream of individual bits.
-#[allow(dead_code)]
 pub struct Vec {
     num_bits: u32,
     bits: std::vec::Vec<u64>,
@@ -7,6 +7,18 @@
 /// Bitmap utilities for the advanced word-aligned hybrid bitmap
 pub mod ewah;
 
+mod bitset;
+
+/// An uncompressed and growable set of bits, suitable for fast set operations like union, intersection and difference.
+///
+/// It's typically obtained by [decompressing][ewah::Vec::to_bitset()] an [EWAH bitmap][ewah::Vec].
+///
+/// Sets compare equal if the same bits are set, independently of their capacity.
+#[derive(Default, Clone, Debug)]
+pub struct Bitset {
+    words: std::vec::Vec<u64>,
+}
+
 pub(crate) mod decode {
     use std::convert::TryInto;
 
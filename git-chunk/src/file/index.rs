@@ -65,6 +65,11 @@ impl Index {
         Self::ENTRY_SIZE * (num_entries + 1/*sentinel*/)
     }
 
+    /// Return an iterator over all chunk entries in the order they appear in the table of contents.
+    pub fn iter(&self) -> impl Iterator<Item = &Entry> {
+        self.chunks.iter()
+    }
+
     /// Find a chunk of `kind` and return its offset into the data if found
     pub fn offset_by_id(&self, kind: crate::Id) -> Result<Range<crate::file::Offset>, offset_by_kind::Error> {
         self.chunks
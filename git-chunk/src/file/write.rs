@@ -71,6 +71,78 @@ mod write_chunk {
 }
 pub use write_chunk::Chunk;
 
+mod incremental {
+    use std::io::Write;
+
+    use crate::file::Index;
+
+    /// A writer which collects chunks of yet unknown size in memory, for writing them along with their table of contents
+    /// once all of them are known.
+    ///
+    /// Use it if computing the exact size of chunks [ahead of time][Index::plan_chunk()] is impractical, at the cost of
+    /// keeping all chunk data in memory.
+    #[derive(Default)]
+    pub struct Incremental {
+        chunks: Vec<(crate::Id, Vec<u8>)>,
+    }
+
+    impl Incremental {
+        /// Start a new chunk of `kind`, receiving all bytes written until the next call to this method.
+        pub fn begin_chunk(&mut self, kind: crate::Id) {
+            assert!(
+                !self.chunks.iter().any(|(id, _)| *id == kind),
+                "BUG: must not add chunk of same kind twice: {:?}",
+                std::str::from_utf8(&kind)
+            );
+            self.chunks.push((kind, Vec::new()));
+        }
+
+        /// Return the amount of chunks begun so far, as typically needed in the file header preceding the table of contents.
+        pub fn num_chunks(&self) -> usize {
+            self.chunks.len()
+        }
+
+        /// Return the total size of all chunk data written so far.
+        pub fn storage_size(&self) -> u64 {
+            self.chunks.iter().map(|(_, data)| data.len() as u64).sum()
+        }
+
+        /// Write the table of contents followed by all chunks in the order they were begun to `out`, which continues writing
+        /// at byte position `current_offset`, and return it.
+        pub fn write_to<W>(self, out: W, current_offset: usize) -> std::io::Result<W>
+        where
+            W: std::io::Write,
+        {
+            let mut index = Index::for_writing();
+            for (kind, data) in &self.chunks {
+                index.plan_chunk(*kind, data.len() as u64);
+            }
+            let mut chunk_write = index.into_write(out, current_offset)?;
+            for (_, data) in &self.chunks {
+                chunk_write.next_chunk();
+                chunk_write.write_all(data)?;
+            }
+            assert!(chunk_write.next_chunk().is_none(), "BUG: all chunks were written");
+            Ok(chunk_write.into_inner())
+        }
+    }
+
+    impl std::io::Write for Incremental {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.chunks
+                .last_mut()
+                .expect("BUG: call `begin_chunk()` before writing")
+                .1
+                .write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
+pub use incremental::Incremental;
+
 /// Writing
 impl Index {
     /// Create a new index whose sole purpose is to be receiving chunks using [`plan_chunk()`][Index::plan_chunk()] and to be written to
@@ -0,0 +1,119 @@
+use std::io::Write;
+
+use git_chunk::file;
+
+const HEADER: &[u8] = b"HEAD";
+
+fn decode(data: &[u8], num_chunks: u32) -> Result<file::Index, file::decode::Error> {
+    file::Index::from_bytes(data, HEADER.len(), num_chunks)
+}
+
+mod write {
+    use super::*;
+
+    #[test]
+    fn planned_and_incremental_writes_produce_the_same_file() -> std::io::Result<()> {
+        let mut planned = file::Index::for_writing();
+        planned.plan_chunk(*b"ABCD", 3);
+        planned.plan_chunk(*b"EFGH", 5);
+        let mut out = Vec::from(HEADER);
+        let mut chunk_write = planned.into_write(&mut out, HEADER.len())?;
+        while let Some(kind) = chunk_write.next_chunk() {
+            match &kind {
+                b"ABCD" => chunk_write.write_all(b"abc")?,
+                b"EFGH" => chunk_write.write_all(b"efghi")?,
+                _ => unreachable!("only planned chunks are written"),
+            }
+        }
+
+        let mut incremental = file::write::Incremental::default();
+        incremental.begin_chunk(*b"ABCD");
+        incremental.write_all(b"ab")?;
+        incremental.write_all(b"c")?;
+        incremental.begin_chunk(*b"EFGH");
+        incremental.write_all(b"efghi")?;
+        assert_eq!(incremental.num_chunks(), 2);
+        assert_eq!(incremental.storage_size(), 8);
+        let incremental_out = incremental.write_to(Vec::from(HEADER), HEADER.len())?;
+
+        assert_eq!(out, incremental_out);
+        assert_eq!(
+            out.len(),
+            HEADER.len() + file::Index::size_for_entries(2) + 8,
+            "header, table of contents and chunk data"
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn chunks_of_the_same_kind_cannot_be_added_twice() {
+        let mut incremental = file::write::Incremental::default();
+        incremental.begin_chunk(*b"ABCD");
+        incremental.begin_chunk(*b"ABCD");
+    }
+}
+
+mod decode {
+    use super::*;
+
+    fn two_chunks() -> Vec<u8> {
+        let mut incremental = file::write::Incremental::default();
+        incremental.begin_chunk(*b"ABCD");
+        incremental.write_all(b"abc").unwrap();
+        incremental.begin_chunk(*b"EFGH");
+        incremental.write_all(b"efghi").unwrap();
+        incremental.write_to(Vec::from(HEADER), HEADER.len()).unwrap()
+    }
+
+    #[test]
+    fn written_chunks_can_be_found_by_id() -> Result<(), Box<dyn std::error::Error>> {
+        let data = two_chunks();
+        let index = decode(&data, 2)?;
+        assert_eq!(index.data_by_id(&data, *b"ABCD")?, b"abc");
+        assert_eq!(index.data_by_id(&data, *b"EFGH")?, b"efghi");
+        assert_eq!(index.highest_offset(), data.len() as u64);
+        assert_eq!(
+            index.iter().map(|e| e.kind).collect::<Vec<_>>(),
+            vec![*b"ABCD", *b"EFGH"],
+            "entries are in order of the table of contents"
+        );
+        assert!(index.offset_by_id(*b"NONE").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_chunks_run_into_the_sentinel() {
+        let mut data = two_chunks();
+        // make room for the additional table of contents entry to not fail on its size first
+        data.extend_from_slice(&[0; file::Index::ENTRY_SIZE]);
+        assert!(matches!(decode(&data, 3), Err(file::decode::Error::EarlySentinelValue)));
+    }
+
+    #[test]
+    fn too_few_chunks_miss_the_sentinel() {
+        let data = two_chunks();
+        assert!(matches!(
+            decode(&data, 1),
+            Err(file::decode::Error::MissingSentinelValue { actual }) if &actual == b"EFGH"
+        ));
+    }
+
+    #[test]
+    fn truncated_files_are_detected() {
+        let data = two_chunks();
+        assert!(matches!(
+            decode(&data[..data.len() - 1], 2),
+            Err(file::decode::Error::ChunkSizeOutOfBounds { .. })
+        ));
+        assert!(matches!(
+            decode(&data[..HEADER.len() + 4], 2),
+            Err(file::decode::Error::TocTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn empty_indices_are_rejected() {
+        assert!(matches!(decode(&two_chunks(), 0), Err(file::decode::Error::Empty)));
+    }
+}
@@ -0,0 +1,197 @@
+//! Changed-path Bloom filters as stored in the `BIDX` and `BDAT` chunks of commit-graph files.
+//!
+//! Each commit may have a filter containing the paths it changed compared to its first parent, along with all of their leading
+//! directories. A filter can tell for sure that a path was _not_ changed, which allows to skip diffing trees entirely.
+use bstr::ByteSlice;
+
+/// The only version of Bloom filters we can read and write, using git's murmur3 implementation.
+pub const VERSION: u32 = 1;
+/// The size of the header of the `BDAT` chunk.
+pub const HEADER_LEN: usize = 3 * 4;
+
+/// Parameters that affect how Bloom filters are computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    /// The amount of hashes computed for each path, each one setting a bit.
+    pub num_hashes: u32,
+    /// The amount of bits allocated per changed path.
+    pub bits_per_entry: u32,
+    /// If a commit changes more than this amount of paths, its filter will claim all paths as changed.
+    pub max_changed_paths: usize,
+}
+
+impl Default for Settings {
+    /// The settings used by git.
+    fn default() -> Self {
+        Settings {
+            num_hashes: 7,
+            bits_per_entry: 10,
+            max_changed_paths: 512,
+        }
+    }
+}
+
+/// The hashes of a path, computed once and used to query or populate any amount of [filters][Filter].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Key {
+    hashes: Vec<u32>,
+}
+
+impl Key {
+    /// Compute the key for `path` using the `num_hashes` of `settings`. `path` must not have a leading or trailing slash.
+    pub fn new(path: &[u8], settings: &Settings) -> Self {
+        const SEED0: u32 = 0x293a_e76f;
+        const SEED1: u32 = 0x7e64_6e2c;
+        let hash0 = murmur3_v1(SEED0, path);
+        let hash1 = murmur3_v1(SEED1, path);
+        Key {
+            hashes: (0..settings.num_hashes)
+                .map(|idx| hash0.wrapping_add(idx.wrapping_mul(hash1)))
+                .collect(),
+        }
+    }
+
+    /// Return the keys of `path` as well as all of its leading directories, like `a/b/c`, `a/b` and `a`.
+    pub fn with_leading_directories(path: &[u8], settings: &Settings) -> Vec<Self> {
+        let mut out = vec![Key::new(path, settings)];
+        let mut path = path;
+        while let Some(pos) = path.rfind_byte(b'/') {
+            path = &path[..pos];
+            out.push(Key::new(path, settings));
+        }
+        out
+    }
+}
+
+/// A Bloom filter of paths changed by a single commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    data: Vec<u8>,
+}
+
+impl Filter {
+    /// Create a filter from all `changed_paths` of a commit, including their leading directories, as configured by `settings`.
+    ///
+    /// Paths are expected to be relative to the root of the repository and use slashes as separators. Note that for
+    /// root commits, this would be all paths of the commit's tree.
+    pub fn from_changed_paths<'a>(changed_paths: impl IntoIterator<Item = &'a [u8]>, settings: &Settings) -> Self {
+        let mut num_changes = 0;
+        let mut paths = std::collections::HashSet::new();
+        for path in changed_paths {
+            num_changes += 1;
+            if num_changes > settings.max_changed_paths {
+                return Self::all_paths_changed();
+            }
+            let mut path = path;
+            loop {
+                paths.insert(path);
+                match path.rfind_byte(b'/') {
+                    Some(pos) => path = &path[..pos],
+                    None => break,
+                }
+            }
+        }
+        if paths.len() > settings.max_changed_paths {
+            return Self::all_paths_changed();
+        }
+
+        let len = ((paths.len() * settings.bits_per_entry as usize + 7) / 8).max(1);
+        let mut filter = Filter { data: vec![0; len] };
+        for path in paths {
+            filter.add(&Key::new(path, settings));
+        }
+        filter
+    }
+
+    /// Create a filter that claims that any path was changed, as used for commits with too many changes.
+    pub fn all_paths_changed() -> Self {
+        Filter { data: vec![0xff] }
+    }
+
+    /// Create a filter from its `data` as stored on disk.
+    pub fn from_bytes(data: &[u8]) -> Self {
+        Filter { data: data.to_owned() }
+    }
+
+    /// Return the filter data as stored on disk.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Add `key` to the filter.
+    pub fn add(&mut self, key: &Key) {
+        let num_bits = self.data.len() as u64 * 8;
+        for hash in &key.hashes {
+            let bit = *hash as u64 % num_bits;
+            self.data[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+
+    /// Return `false` if the path of `key` was definitely not changed, or `true` if it may have been changed.
+    ///
+    /// Empty filters can't tell and always return `true`.
+    pub fn contains(&self, key: &Key) -> bool {
+        if self.data.is_empty() {
+            return true;
+        }
+        let num_bits = self.data.len() as u64 * 8;
+        key.hashes.iter().all(|hash| {
+            let bit = *hash as u64 % num_bits;
+            self.data[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+        })
+    }
+}
+
+/// The variant of murmur3 used in git's version 1 Bloom filters, which sign-extends bytes with the high bit set.
+fn murmur3_v1(seed: u32, data: &[u8]) -> u32 {
+    const C1: u32 = 0xcc9e_2d51;
+    const C2: u32 = 0x1b87_3593;
+    const R1: u32 = 15;
+    const R2: u32 = 13;
+    const M: u32 = 5;
+    const N: u32 = 0xe654_6b64;
+    let byte = |b: u8| b as i8 as i32 as u32;
+
+    let mut hash = seed;
+    let mut chunks = data.chunks_exact(4);
+    for chunk in chunks.by_ref() {
+        let mut k = byte(chunk[0]) | byte(chunk[1]) << 8 | byte(chunk[2]) << 16 | byte(chunk[3]) << 24;
+        k = k.wrapping_mul(C1).rotate_left(R1).wrapping_mul(C2);
+        hash ^= k;
+        hash = hash.rotate_left(R2).wrapping_mul(M).wrapping_add(N);
+    }
+
+    let tail = chunks.remainder();
+    if !tail.is_empty() {
+        let mut k = 0;
+        for (idx, b) in tail.iter().enumerate().rev() {
+            k ^= byte(*b) << (idx * 8);
+        }
+        k = k.wrapping_mul(C1).rotate_left(R1).wrapping_mul(C2);
+        hash ^= k;
+    }
+
+    hash ^= data.len() as u32;
+    hash ^= hash >> 16;
+    hash = hash.wrapping_mul(0x85eb_ca6b);
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(0xc2b2_ae35);
+    hash ^= hash >> 16;
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murmur3_matches_git() {
+        // Values from git's `t/helper/test-bloom.c` tests.
+        assert_eq!(murmur3_v1(0, b""), 0x0000_0000);
+        assert_eq!(murmur3_v1(0, b"Hello world!"), 0x627b_0c2c);
+        assert_eq!(
+            murmur3_v1(0, b"The quick brown fox jumps over the lazy dog"),
+            0x2e4f_f723
+        );
+    }
+}
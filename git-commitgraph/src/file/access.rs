@@ -4,7 +4,10 @@ use std::{
     path::Path,
 };
 
-use crate::file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH};
+use crate::{
+    bloom,
+    file::{self, commit::Commit, File, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, GENERATION_DATA_OVERFLOW_MASK},
+};
 
 /// Access
 impl File {
@@ -13,6 +16,11 @@ impl File {
         self.base_graph_count
     }
 
+    /// The settings used to compute the changed-path Bloom filters stored in this file, or `None` if there are none.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.bloom_filter_settings
+    }
+
     /// Returns the commit data for the commit located at the given lexigraphical position.
     ///
     /// `pos` must range from 0 to self.num_commits().
@@ -90,6 +98,11 @@ impl File {
         None
     }
 
+    /// Return true if this file stores corrected commit dates, also known as generation numbers v2.
+    pub fn has_generation_data(&self) -> bool {
+        self.generation_data_offset.is_some()
+    }
+
     /// Returns the number of commits in this graph file.
     ///
     /// The maximum valid `file::Position` that can be used with this file is one less than
@@ -122,6 +135,32 @@ impl File {
         &self.data[start..][..entry_size]
     }
 
+    /// Returns the offset of the corrected commit date of the commit at `pos` relative to its committer timestamp,
+    /// as stored in the Generation Data (GDA2) and Generation Data Overflow (GDO2) chunks.
+    pub(crate) fn generation_data_offset_at(&self, pos: file::Position) -> Option<u64> {
+        let start = self.generation_data_offset? + pos.0 as usize * 4;
+        let offset = u32::from_be_bytes(self.data[start..][..4].try_into().unwrap());
+        if offset & GENERATION_DATA_OVERFLOW_MASK == 0 {
+            return Some(offset.into());
+        }
+        let overflow = self.data.get(self.generation_data_overflow_range.clone()?)?;
+        let start = (offset & !GENERATION_DATA_OVERFLOW_MASK) as usize * 8;
+        overflow
+            .get(start..start + 8)
+            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Returns the changed-path Bloom filter data of the commit at `pos` as stored in the Bloom Filter Data (BDAT) chunk,
+    /// or `None` if there are no filters or if the index is corrupt.
+    pub(crate) fn bloom_filter_data_at(&self, pos: file::Position) -> Option<&[u8]> {
+        let index = &self.data[self.bloom_filter_index_offset?..][..self.num_commits() as usize * 4];
+        let end_at = |pos: usize| u32::from_be_bytes(index[pos * 4..][..4].try_into().unwrap()) as usize;
+        let pos = pos.0 as usize;
+        let start = if pos == 0 { 0 } else { end_at(pos - 1) };
+        let data = &self.data[self.bloom_filter_data_range.clone()?];
+        data.get(start..end_at(pos))
+    }
+
     /// Returns the byte slice for this file's entire Extra Edge List (EDGE) chunk.
     pub(crate) fn extra_edges_data(&self) -> Option<&[u8]> {
         Some(&self.data[self.extra_edges_list_range.clone()?])
@@ -6,6 +6,7 @@ use std::{
 };
 
 use crate::{
+    bloom,
     file::{self, File, EXTENDED_EDGES_MASK, LAST_EXTENDED_EDGE_MASK, NO_PARENT},
     graph,
 };
@@ -63,6 +64,28 @@ impl<'a> Commit<'a> {
         self.commit_timestamp
     }
 
+    /// Returns the Bloom filter of paths this commit changed compared to its first parent, or `None` if the owning file
+    /// doesn't store Bloom filters or if it wasn't computed for this commit.
+    ///
+    /// Keys to query it with must be created with the [settings][File::bloom_filter_settings()] of the owning file.
+    pub fn changed_paths_filter(&self) -> Option<bloom::Filter> {
+        self.file
+            .bloom_filter_data_at(self.pos)
+            .filter(|data| !data.is_empty())
+            .map(bloom::Filter::from_bytes)
+    }
+
+    /// Returns the corrected committer date of this commit, also known as generation number v2, if the owning file stores it.
+    ///
+    /// It is at least as large as the committer timestamp and larger than the corrected committer date of all parents,
+    /// which makes it suitable to order commits topologically even if their clocks were skewed.
+    /// `None` is also returned if the file is corrupt and the stored date doesn't fit into 64 bits.
+    pub fn corrected_commit_date(&self) -> Option<u64> {
+        self.file
+            .generation_data_offset_at(self.pos)
+            .and_then(|offset| self.commit_timestamp.checked_add(offset))
+    }
+
     /// Returns the generation number of this commit.
     ///
     /// Commits without parents have generation number 1. Commits with parents have a generation
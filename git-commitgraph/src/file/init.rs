@@ -6,9 +6,14 @@ use std::{
 use bstr::ByteSlice;
 use memmap2::Mmap;
 
-use crate::file::{
-    ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH,
-    EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+use crate::{
+    bloom,
+    file::{
+        ChunkId, File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID,
+        COMMIT_DATA_CHUNK_ID, COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, FAN_LEN,
+        GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, HEADER_LEN, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID,
+        SIGNATURE,
+    },
 };
 
 /// The error used in [`File::at()`].
@@ -196,6 +201,90 @@ impl TryFrom<&Path> for File {
 
         let extra_edges_list_range = chunks.usize_offset_by_id(EXTENDED_EDGES_LIST_CHUNK_ID).ok();
 
+        let generation_data_offset = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size != commit_data_count as usize * 4 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length {}, got {}",
+                            commit_data_count as usize * 4,
+                            chunk_size
+                        ),
+                    });
+                }
+                Ok(chunk_range.start)
+            })
+            .ok()
+            .transpose()?;
+        let generation_data_overflow_range = chunks
+            .validated_usize_offset_by_id(GENERATION_DATA_OVERFLOW_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() % 8 != 0 {
+                    return Err(Error::InvalidChunkSize {
+                        id: GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                        msg: format!("chunk size {} is not a multiple of 8", chunk_range.len()),
+                    });
+                }
+                Ok(chunk_range)
+            })
+            .ok()
+            .transpose()?;
+
+        let bloom_filter_index_offset = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_INDEX_CHUNK_ID, |chunk_range| {
+                let chunk_size = chunk_range.len();
+                if chunk_size != commit_data_count as usize * 4 {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_INDEX_CHUNK_ID,
+                        msg: format!(
+                            "expected chunk length {}, got {}",
+                            commit_data_count as usize * 4,
+                            chunk_size
+                        ),
+                    });
+                }
+                Ok(chunk_range.start)
+            })
+            .ok()
+            .transpose()?;
+        let bloom_filter_data = chunks
+            .validated_usize_offset_by_id(BLOOM_FILTER_DATA_CHUNK_ID, |chunk_range| {
+                if chunk_range.len() < bloom::HEADER_LEN {
+                    return Err(Error::InvalidChunkSize {
+                        id: BLOOM_FILTER_DATA_CHUNK_ID,
+                        msg: format!(
+                            "chunk size {} is too small to hold the {} bytes header",
+                            chunk_range.len(),
+                            bloom::HEADER_LEN
+                        ),
+                    });
+                }
+                let header = &data[chunk_range.start..][..bloom::HEADER_LEN];
+                let field = |idx: usize| u32::from_be_bytes(header[idx * 4..][..4].try_into().unwrap());
+                // Like git, we ignore filters of versions we don't understand instead of failing.
+                Ok((field(0) == bloom::VERSION).then(|| {
+                    (
+                        chunk_range.start + bloom::HEADER_LEN..chunk_range.end,
+                        bloom::Settings {
+                            num_hashes: field(1),
+                            bits_per_entry: field(2),
+                            ..Default::default()
+                        },
+                    )
+                }))
+            })
+            .ok()
+            .transpose()?
+            .flatten();
+        let (bloom_filter_index_offset, bloom_filter_data_range, bloom_filter_settings) =
+            match (bloom_filter_index_offset, bloom_filter_data) {
+                (Some(index_offset), Some((data_range, settings))) => {
+                    (Some(index_offset), Some(data_range), Some(settings))
+                }
+                _ => (None, None, None),
+            };
+
         let trailer = &data[chunks.highest_offset() as usize..];
         if trailer.len() != object_hash.len_in_bytes() {
             return Err(Error::Trailer(format!(
@@ -232,10 +321,15 @@ impl TryFrom<&Path> for File {
         Ok(File {
             base_graph_count,
             base_graphs_list_offset,
+            bloom_filter_data_range,
+            bloom_filter_index_offset,
+            bloom_filter_settings,
             commit_data_offset,
             data,
             extra_edges_list_range,
             fan,
+            generation_data_offset,
+            generation_data_overflow_range,
             oid_lookup_offset,
             path: path.to_owned(),
             hash_len: object_hash.len_in_bytes(),
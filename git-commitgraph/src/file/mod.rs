@@ -14,6 +14,7 @@ mod access;
 pub mod commit;
 mod init;
 pub mod verify;
+pub mod write;
 
 const COMMIT_DATA_ENTRY_SIZE_SANS_HASH: usize = 16;
 const FAN_LEN: usize = 256;
@@ -25,6 +26,10 @@ type ChunkId = git_chunk::Id;
 const BASE_GRAPHS_LIST_CHUNK_ID: ChunkId = *b"BASE";
 const COMMIT_DATA_CHUNK_ID: ChunkId = *b"CDAT";
 const EXTENDED_EDGES_LIST_CHUNK_ID: ChunkId = *b"EDGE";
+const GENERATION_DATA_CHUNK_ID: ChunkId = *b"GDA2";
+const GENERATION_DATA_OVERFLOW_CHUNK_ID: ChunkId = *b"GDO2";
+const BLOOM_FILTER_INDEX_CHUNK_ID: ChunkId = *b"BIDX";
+const BLOOM_FILTER_DATA_CHUNK_ID: ChunkId = *b"BDAT";
 const OID_FAN_CHUNK_ID: ChunkId = *b"OIDF";
 const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
 
@@ -33,6 +38,7 @@ const OID_LOOKUP_CHUNK_ID: ChunkId = *b"OIDL";
 const NO_PARENT: u32 = 0x7000_0000;
 const EXTENDED_EDGES_MASK: u32 = 0x8000_0000;
 const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
+const GENERATION_DATA_OVERFLOW_MASK: u32 = 0x8000_0000;
 
 /// A single commit-graph file.
 ///
@@ -41,10 +47,15 @@ const LAST_EXTENDED_EDGE_MASK: u32 = 0x8000_0000;
 pub struct File {
     base_graph_count: u8,
     base_graphs_list_offset: Option<usize>,
+    bloom_filter_data_range: Option<Range<usize>>,
+    bloom_filter_index_offset: Option<usize>,
+    bloom_filter_settings: Option<crate::bloom::Settings>,
     commit_data_offset: usize,
     data: Mmap,
     extra_edges_list_range: Option<Range<usize>>,
     fan: [u32; FAN_LEN],
+    generation_data_offset: Option<usize>,
+    generation_data_overflow_range: Option<Range<usize>>,
     oid_lookup_offset: usize,
     path: PathBuf,
     hash_len: usize,
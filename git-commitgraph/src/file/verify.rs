@@ -150,7 +150,7 @@ impl File {
         let data_len_without_trailer = self.data.len() - self.hash_len;
         let mut hasher = git_features::hash::hasher(self.object_hash());
         hasher.update(&self.data[..data_len_without_trailer]);
-        let actual = git_hash::ObjectId::from(hasher.digest().as_ref());
+        let actual = hasher.digest();
 
         let expected = self.checksum();
         if actual == expected {
@@ -0,0 +1,399 @@
+//! Write commit-graph files, either standalone or as a layer on top of an existing [`Graph`].
+use std::{convert::TryInto, io::Write};
+
+use bstr::BString;
+use git_hash::ObjectId;
+
+use crate::{
+    bloom,
+    file::{
+        File, BASE_GRAPHS_LIST_CHUNK_ID, BLOOM_FILTER_DATA_CHUNK_ID, BLOOM_FILTER_INDEX_CHUNK_ID, COMMIT_DATA_CHUNK_ID,
+        COMMIT_DATA_ENTRY_SIZE_SANS_HASH, EXTENDED_EDGES_LIST_CHUNK_ID, EXTENDED_EDGES_MASK, FAN_LEN,
+        GENERATION_DATA_CHUNK_ID, GENERATION_DATA_OVERFLOW_CHUNK_ID, GENERATION_DATA_OVERFLOW_MASK, HEADER_LEN,
+        LAST_EXTENDED_EDGE_MASK, NO_PARENT, OID_FAN_CHUNK_ID, OID_LOOKUP_CHUNK_ID, SIGNATURE,
+    },
+    Graph, GENERATION_NUMBER_MAX, MAX_COMMITS,
+};
+
+mod error {
+    /// The error returned by [`File::write_from_commits()`][crate::file::File::write_from_commits()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Parent {parent} of commit {id} is neither part of the commits to write nor of the base graph")]
+        MissingParent {
+            id: git_hash::ObjectId,
+            parent: git_hash::ObjectId,
+        },
+        #[error("The ancestry of commit {id} contains a cycle")]
+        Cycle { id: git_hash::ObjectId },
+        #[error(
+            "A commit-graph can store at most {} commits, but {0} were provided",
+            crate::MAX_COMMITS
+        )]
+        TooManyCommits(u64),
+    }
+}
+pub use error::Error;
+
+/// A commit as input for writing a commit-graph file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commit {
+    /// The id of the commit.
+    pub id: ObjectId,
+    /// The id of the tree the commit points to.
+    pub root_tree_id: ObjectId,
+    /// The ids of all parents, in order.
+    pub parents: Vec<ObjectId>,
+    /// The committer timestamp in seconds since 1970-01-01 00:00:00 UTC.
+    pub committer_timestamp: u64,
+    /// The paths changed in comparison to the first parent, or all paths of the tree if there is no parent, to be
+    /// stored in a Bloom filter if [enabled][Options::changed_paths].
+    ///
+    /// If `None`, the commit is stored with an empty filter, which indicates that it wasn't computed.
+    pub changed_paths: Option<Vec<BString>>,
+}
+
+/// Configuration for [`File::write_from_commits()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The kind of hash used for all commits.
+    pub object_hash: git_hash::Kind,
+    /// If true, corrected commit dates (generation numbers v2) are written as well, unless a layer of the base graph
+    /// doesn't store them.
+    pub generation_data: bool,
+    /// If set, changed-path Bloom filters are computed with the given settings and written.
+    pub changed_paths: Option<bloom::Settings>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            object_hash: git_hash::Kind::Sha1,
+            generation_data: true,
+            changed_paths: None,
+        }
+    }
+}
+
+/// The result of [`File::write_from_commits()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The checksum of the written file, which is also its trailer.
+    pub checksum: ObjectId,
+    /// The amount of commits written, which excludes commits already present in the base graph.
+    pub num_commits: u32,
+}
+
+#[derive(Clone, Copy)]
+enum Parent {
+    /// The index into the commits we are writing.
+    Local(usize),
+    /// The position in the base graph.
+    Base(u32),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Unvisited,
+    InProgress,
+    Done,
+}
+
+/// Writing
+impl File {
+    /// Write all `commits` into a new commit-graph file to `out`, computing their generation numbers on the fly.
+    ///
+    /// If `base` is set, the file becomes a new layer on top of it and commits already contained in `base` are skipped.
+    /// All parents of each commit must be part of `commits` or `base`. Octopus merges are stored in the extra edges chunk.
+    pub fn write_from_commits(
+        commits: impl IntoIterator<Item = Commit>,
+        base: Option<&Graph>,
+        out: impl std::io::Write,
+        Options {
+            object_hash,
+            generation_data,
+            changed_paths,
+        }: Options,
+    ) -> Result<Outcome, Error> {
+        let num_base_commits = base.map_or(0, |graph| graph.num_commits());
+        let mut commits: Vec<_> = commits
+            .into_iter()
+            .filter(|c| base.map_or(true, |graph| graph.lookup(&c.id).is_none()))
+            .collect();
+        commits.sort_by_key(|c| c.id);
+        commits.dedup_by(|a, b| a.id == b.id);
+        let total_commits = u64::from(num_base_commits) + commits.len() as u64;
+        if total_commits > u64::from(MAX_COMMITS) {
+            return Err(Error::TooManyCommits(total_commits));
+        }
+
+        let parents = commits
+            .iter()
+            .map(|c| {
+                c.parents
+                    .iter()
+                    .map(|parent| {
+                        commits
+                            .binary_search_by(|other| other.id.cmp(parent))
+                            .map(Parent::Local)
+                            .ok()
+                            .or_else(|| {
+                                base.and_then(|graph| graph.lookup(parent))
+                                    .map(|pos| Parent::Base(pos.0))
+                            })
+                            .ok_or(Error::MissingParent {
+                                id: c.id,
+                                parent: *parent,
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut write_generation_data =
+            generation_data && base.map_or(true, |graph| graph.files.iter().all(|f| f.has_generation_data()));
+        let (levels, corrected_dates) = compute_generations(&commits, &parents, base, &mut write_generation_data)?;
+
+        let position = |parent: &Parent| match parent {
+            Parent::Local(idx) => num_base_commits + *idx as u32,
+            Parent::Base(pos) => *pos,
+        };
+        let num_extra_edges: usize = parents.iter().filter(|p| p.len() > 2).map(|p| p.len() - 1).sum();
+        let generation_data_offsets: Vec<u64> = commits
+            .iter()
+            .zip(corrected_dates.iter())
+            .map(|(c, corrected)| corrected.saturating_sub(c.committer_timestamp))
+            .collect();
+        let num_generation_data_overflows = generation_data_offsets
+            .iter()
+            .filter(|offset| **offset > u64::from(!GENERATION_DATA_OVERFLOW_MASK))
+            .count();
+        let filters: Option<Vec<bloom::Filter>> = changed_paths.map(|settings| {
+            commits
+                .iter()
+                .map(|c| match &c.changed_paths {
+                    Some(paths) => bloom::Filter::from_changed_paths(paths.iter().map(|p| p.as_slice()), &settings),
+                    None => bloom::Filter::from_bytes(&[]),
+                })
+                .collect()
+        });
+        let base_files = base.map_or(&[][..], |graph| graph.files.as_slice());
+
+        let mut cf = git_chunk::file::Index::for_writing();
+        cf.plan_chunk(OID_FAN_CHUNK_ID, (FAN_LEN * 4) as u64);
+        cf.plan_chunk(OID_LOOKUP_CHUNK_ID, (commits.len() * object_hash.len_in_bytes()) as u64);
+        cf.plan_chunk(
+            COMMIT_DATA_CHUNK_ID,
+            (commits.len() * (object_hash.len_in_bytes() + COMMIT_DATA_ENTRY_SIZE_SANS_HASH)) as u64,
+        );
+        if write_generation_data {
+            cf.plan_chunk(GENERATION_DATA_CHUNK_ID, (commits.len() * 4) as u64);
+            if num_generation_data_overflows > 0 {
+                cf.plan_chunk(
+                    GENERATION_DATA_OVERFLOW_CHUNK_ID,
+                    (num_generation_data_overflows * 8) as u64,
+                );
+            }
+        }
+        if num_extra_edges > 0 {
+            cf.plan_chunk(EXTENDED_EDGES_LIST_CHUNK_ID, (num_extra_edges * 4) as u64);
+        }
+        if let Some(filters) = &filters {
+            cf.plan_chunk(BLOOM_FILTER_INDEX_CHUNK_ID, (commits.len() * 4) as u64);
+            cf.plan_chunk(
+                BLOOM_FILTER_DATA_CHUNK_ID,
+                (bloom::HEADER_LEN + filters.iter().map(|f| f.as_bytes().len()).sum::<usize>()) as u64,
+            );
+        }
+        if !base_files.is_empty() {
+            cf.plan_chunk(
+                BASE_GRAPHS_LIST_CHUNK_ID,
+                (base_files.len() * object_hash.len_in_bytes()) as u64,
+            );
+        }
+
+        let mut out = git_features::hash::Write::new(out, object_hash);
+        out.write_all(SIGNATURE)?;
+        out.write_all(&[1 /* version */, object_hash as u8])?;
+        out.write_all(&[
+            cf.num_chunks().try_into().expect("BUG: wrote more than 256 chunks"),
+            base_files
+                .len()
+                .try_into()
+                .expect("a chain of at most 255 commit-graph files"),
+        ])?;
+
+        let mut chunk_write = cf.into_write(&mut out, HEADER_LEN)?;
+        while let Some(chunk_to_write) = chunk_write.next_chunk() {
+            match chunk_to_write {
+                OID_FAN_CHUNK_ID => {
+                    let mut count = 0;
+                    for first_byte in 0..FAN_LEN {
+                        count += commits[count..]
+                            .iter()
+                            .take_while(|c| usize::from(c.id.first_byte()) == first_byte)
+                            .count();
+                        chunk_write.write_all(&(count as u32).to_be_bytes())?;
+                    }
+                }
+                OID_LOOKUP_CHUNK_ID => {
+                    for c in &commits {
+                        chunk_write.write_all(c.id.as_slice())?;
+                    }
+                }
+                COMMIT_DATA_CHUNK_ID => {
+                    let mut num_extra_edges = 0;
+                    for ((c, parents), level) in commits.iter().zip(parents.iter()).zip(levels.iter()) {
+                        chunk_write.write_all(c.root_tree_id.as_slice())?;
+                        let parent1 = parents.first().map_or(NO_PARENT, position);
+                        let parent2 = match parents.len() {
+                            0 | 1 => NO_PARENT,
+                            2 => position(&parents[1]),
+                            _ => {
+                                let edge = EXTENDED_EDGES_MASK | num_extra_edges;
+                                num_extra_edges += parents.len() as u32 - 1;
+                                edge
+                            }
+                        };
+                        chunk_write.write_all(&parent1.to_be_bytes())?;
+                        chunk_write.write_all(&parent2.to_be_bytes())?;
+                        let packed_date = (u64::from(*level) << 34) | (c.committer_timestamp & 0x0003_ffff_ffff);
+                        chunk_write.write_all(&packed_date.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_CHUNK_ID => {
+                    let mut num_overflows = 0;
+                    for offset in &generation_data_offsets {
+                        let offset = if *offset > u64::from(!GENERATION_DATA_OVERFLOW_MASK) {
+                            num_overflows += 1;
+                            GENERATION_DATA_OVERFLOW_MASK | (num_overflows - 1)
+                        } else {
+                            *offset as u32
+                        };
+                        chunk_write.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                GENERATION_DATA_OVERFLOW_CHUNK_ID => {
+                    for offset in generation_data_offsets
+                        .iter()
+                        .filter(|offset| **offset > u64::from(!GENERATION_DATA_OVERFLOW_MASK))
+                    {
+                        chunk_write.write_all(&offset.to_be_bytes())?;
+                    }
+                }
+                EXTENDED_EDGES_LIST_CHUNK_ID => {
+                    for parents in parents.iter().filter(|p| p.len() > 2) {
+                        let (last, edges) = parents[1..].split_last().expect("at least two extra edges");
+                        for parent in edges {
+                            chunk_write.write_all(&position(parent).to_be_bytes())?;
+                        }
+                        chunk_write.write_all(&(LAST_EXTENDED_EDGE_MASK | position(last)).to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_INDEX_CHUNK_ID => {
+                    let mut end = 0;
+                    for filter in filters.as_ref().expect("planned only with filters") {
+                        end += filter.as_bytes().len() as u32;
+                        chunk_write.write_all(&end.to_be_bytes())?;
+                    }
+                }
+                BLOOM_FILTER_DATA_CHUNK_ID => {
+                    let settings = changed_paths.expect("planned only with settings");
+                    for field in &[bloom::VERSION, settings.num_hashes, settings.bits_per_entry] {
+                        chunk_write.write_all(&field.to_be_bytes())?;
+                    }
+                    for filter in filters.as_ref().expect("planned only with filters") {
+                        chunk_write.write_all(filter.as_bytes())?;
+                    }
+                }
+                BASE_GRAPHS_LIST_CHUNK_ID => {
+                    for file in base_files {
+                        chunk_write.write_all(file.checksum().as_bytes())?;
+                    }
+                }
+                unknown => unreachable!("BUG: forgot to implement chunk {:?}", std::str::from_utf8(&unknown)),
+            }
+        }
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+        Ok(Outcome {
+            checksum,
+            num_commits: commits.len() as u32,
+        })
+    }
+}
+
+/// Compute the topological level and corrected commit date of each commit, visiting parents before their children.
+///
+/// If a parent in `base` doesn't have a corrected commit date, `write_generation_data` is set to false.
+fn compute_generations(
+    commits: &[Commit],
+    parents: &[Vec<Parent>],
+    base: Option<&Graph>,
+    write_generation_data: &mut bool,
+) -> Result<(Vec<u32>, Vec<u64>), Error> {
+    let mut levels = vec![0u32; commits.len()];
+    let mut corrected_dates = vec![0u64; commits.len()];
+    let mut state = vec![State::Unvisited; commits.len()];
+    let mut stack = Vec::new();
+    for start in 0..commits.len() {
+        if state[start] != State::Unvisited {
+            continue;
+        }
+        stack.push(start);
+        while let Some(&idx) = stack.last() {
+            if state[idx] == State::Done {
+                stack.pop();
+                continue;
+            }
+            state[idx] = State::InProgress;
+            let mut parents_are_done = true;
+            for parent in &parents[idx] {
+                if let Parent::Local(parent_idx) = parent {
+                    match state[*parent_idx] {
+                        State::Unvisited => {
+                            stack.push(*parent_idx);
+                            parents_are_done = false;
+                        }
+                        State::InProgress => return Err(Error::Cycle { id: commits[idx].id }),
+                        State::Done => {}
+                    }
+                }
+            }
+            if !parents_are_done {
+                continue;
+            }
+
+            let (mut max_level, mut max_corrected_date) = (0, 0);
+            for parent in &parents[idx] {
+                let (level, corrected_date) = match parent {
+                    Parent::Local(parent_idx) => (levels[*parent_idx], corrected_dates[*parent_idx]),
+                    Parent::Base(pos) => {
+                        let commit = base
+                            .expect("base parents only with base graph")
+                            .commit_at(crate::graph::Position(*pos));
+                        let corrected_date = commit.corrected_commit_date().unwrap_or_else(|| {
+                            *write_generation_data = false;
+                            0
+                        });
+                        (commit.generation(), corrected_date)
+                    }
+                };
+                max_level = max_level.max(level);
+                max_corrected_date = max_corrected_date.max(corrected_date);
+            }
+            levels[idx] = max_level.min(GENERATION_NUMBER_MAX - 1) + 1;
+            corrected_dates[idx] = commits[idx]
+                .committer_timestamp
+                .max(max_corrected_date.saturating_add(1));
+            state[idx] = State::Done;
+            stack.pop();
+        }
+    }
+    Ok((levels, corrected_dates))
+}
@@ -1,4 +1,5 @@
 use crate::{
+    bloom,
     file::{self, Commit, File},
     graph::{self, Graph},
 };
@@ -50,6 +51,33 @@ impl Graph {
     }
 }
 
+/// Changed-path Bloom filters
+impl Graph {
+    /// Return the settings of the changed-path Bloom filters in this graph, or `None` if there are none.
+    ///
+    /// If layers of a split graph use different settings, only those of the lowest layer with Bloom filters are used.
+    pub fn bloom_filter_settings(&self) -> Option<bloom::Settings> {
+        self.files.iter().find_map(|f| f.bloom_filter_settings())
+    }
+
+    /// Return `Some(false)` if the commit with `id` definitely didn't change the path of `keys` compared to its first
+    /// parent, or `Some(true)` if it may have changed it.
+    /// `None` is returned if the commit isn't in the graph or there is no Bloom filter for it.
+    ///
+    /// `keys` are typically obtained with [`bloom::Key::with_leading_directories()`] using our
+    /// [settings][Graph::bloom_filter_settings()], and all of them must be contained in the filter for the path to be
+    /// possibly changed.
+    pub fn maybe_changed_path(&self, id: impl AsRef<git_hash::oid>, keys: &[bloom::Key]) -> Option<bool> {
+        let settings = self.bloom_filter_settings()?;
+        let r = self.lookup_by_id(id.as_ref())?;
+        if r.file.bloom_filter_settings() != Some(settings) {
+            return None;
+        }
+        let filter = r.file.commit_at(r.file_pos).changed_paths_filter()?;
+        Some(keys.iter().all(|key| filter.contains(key)))
+    }
+}
+
 /// Access fundamentals
 impl Graph {
     fn lookup_by_id(&self, id: &git_hash::oid) -> Option<LookupByIdResult<'_>> {
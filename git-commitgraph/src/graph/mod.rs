@@ -2,6 +2,7 @@
 mod access;
 mod init;
 pub mod verify;
+pub mod write;
 
 use std::fmt;
 
@@ -13,7 +14,7 @@ use crate::file::File;
 /// may come from one or more `objects/info/commit-graphs/graph-*.graph` files. These files are
 /// generated via `git commit-graph write ...` commands.
 pub struct Graph {
-    files: Vec<File>,
+    pub(crate) files: Vec<File>,
 }
 
 /// A generalized position for use in [`Graph`].
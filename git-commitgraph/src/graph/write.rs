@@ -0,0 +1,232 @@
+//! Write commit-graph files into the `objects/info` directory of a repository, as single file or as split chain.
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    file::{self, File},
+    Graph,
+};
+
+mod error {
+    use crate::file;
+
+    /// The error returned by [`Graph::write_to_info_dir()`][crate::Graph::write_to_info_dir()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        OpenGraph(#[from] crate::graph::init::Error),
+        #[error(transparent)]
+        OpenFile(#[from] file::Error),
+        #[error(transparent)]
+        ParentAccess(#[from] file::commit::Error),
+        #[error(transparent)]
+        WriteFile(#[from] file::write::Error),
+    }
+}
+pub use error::Error;
+
+/// Determines how layers of a split commit-graph are merged when a new layer is added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Never merge existing layers, similar to `git commit-graph write --split=no-merge`.
+    NoMerge,
+    /// Merge the topmost layer into the new one as long as it has less than the given factor times as many commits
+    /// as the new layer, similar to `git commit-graph write --split --size-multiple=<factor>`.
+    SizeMultiple(u32),
+}
+
+impl Default for Split {
+    /// The size multiple used by git.
+    fn default() -> Self {
+        Split::SizeMultiple(2)
+    }
+}
+
+/// Configuration for [`Graph::write_to_info_dir()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    /// Options for writing the commit-graph file.
+    pub file: file::write::Options,
+    /// If set, write a new layer on top of the existing commit-graph chain instead of a single `commit-graph` file.
+    pub split: Option<Split>,
+}
+
+/// The result of [`Graph::write_to_info_dir()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The path to the newly written commit-graph file, or `None` if there was nothing to write.
+    pub path: Option<PathBuf>,
+    /// Information about the newly written commit-graph file, or `None` if there was nothing to write.
+    pub written: Option<file::write::Outcome>,
+    /// The amount of files the commit-graph consists of now.
+    pub num_files: usize,
+}
+
+/// Writing
+impl Graph {
+    /// Write `commits` into the commit-graph of the `objects/info` directory at `info_dir`.
+    ///
+    /// Without [`split`][Options::split], the `commit-graph` file is replaced with one that contains exactly `commits`,
+    /// and a commit-graph chain is removed if present.
+    /// Otherwise a new layer with all `commits` that aren't yet in the chain is added to `commit-graphs/`, possibly merging
+    /// existing layers into it. An existing `commit-graph` file becomes the lowest layer of the chain in the process.
+    /// Note that commits moved out of merged layers don't retain their changed-path Bloom filters.
+    pub fn write_to_info_dir(
+        info_dir: impl AsRef<Path>,
+        commits: impl IntoIterator<Item = file::write::Commit>,
+        Options { file, split }: Options,
+    ) -> Result<Outcome, Error> {
+        let info_dir = info_dir.as_ref();
+        let graphs_dir = info_dir.join("commit-graphs");
+        let chain_path = graphs_dir.join("commit-graph-chain");
+        let split = match split {
+            Some(split) => split,
+            None => {
+                let mut tempfile = tempfile::NamedTempFile::new_in(info_dir)?;
+                let written = File::write_from_commits(commits, None, std::io::BufWriter::new(&mut tempfile), file)?;
+                let path = info_dir.join("commit-graph");
+                tempfile.persist(&path).map_err(|err| err.error)?;
+                if chain_path.is_file() {
+                    let layers = read_chain(&chain_path)?;
+                    remove_if_present(&chain_path)?;
+                    for checksum in layers {
+                        remove_if_present(&layer_path(&graphs_dir, &checksum))?;
+                    }
+                }
+                return Ok(Outcome {
+                    path: Some(path),
+                    written: Some(written),
+                    num_files: 1,
+                });
+            }
+        };
+
+        std::fs::create_dir_all(&graphs_dir)?;
+        let mut layers = if chain_path.is_file() {
+            read_chain(&chain_path)?
+        } else {
+            Vec::new()
+        };
+        let single_file_path = info_dir.join("commit-graph");
+        if layers.is_empty() && single_file_path.is_file() {
+            let checksum = File::at(&single_file_path)?.checksum().to_hex().to_string();
+            std::fs::rename(&single_file_path, layer_path(&graphs_dir, &checksum))?;
+            layers.push(checksum);
+            write_chain(&chain_path, &layers)?;
+        }
+
+        let (commits, num_layers_to_keep) = {
+            let graph = if layers.is_empty() {
+                None
+            } else {
+                Some(open_layers(&graphs_dir, &layers)?)
+            };
+            let mut commits: Vec<_> = commits
+                .into_iter()
+                .filter(|c| graph.as_ref().map_or(true, |graph| graph.lookup(&c.id).is_none()))
+                .collect();
+            let mut num_new_commits = commits.len() as u64;
+            let mut num_layers_to_keep = layers.len();
+            if let (Some(graph), Split::SizeMultiple(factor)) = (graph.as_ref(), split) {
+                while let Some(top) = num_layers_to_keep.checked_sub(1).map(|idx| &graph.files[idx]) {
+                    if num_new_commits * u64::from(factor) < u64::from(top.num_commits()) {
+                        break;
+                    }
+                    num_new_commits += u64::from(top.num_commits());
+                    num_layers_to_keep -= 1;
+                }
+            }
+            if let Some(graph) = graph.as_ref() {
+                for file in &graph.files[num_layers_to_keep..] {
+                    for commit in file.iter_commits() {
+                        commits.push(file::write::Commit {
+                            id: commit.id().to_owned(),
+                            root_tree_id: commit.root_tree_id().to_owned(),
+                            parents: commit
+                                .iter_parents()
+                                .map(|pos| pos.map(|pos| graph.id_at(pos).to_owned()))
+                                .collect::<Result<_, _>>()?,
+                            committer_timestamp: commit.committer_timestamp(),
+                            changed_paths: None,
+                        });
+                    }
+                }
+            }
+            (commits, num_layers_to_keep)
+        };
+        if commits.is_empty() {
+            return Ok(Outcome {
+                path: None,
+                written: None,
+                num_files: layers.len(),
+            });
+        }
+
+        let base = if num_layers_to_keep == 0 {
+            None
+        } else {
+            Some(open_layers(&graphs_dir, &layers[..num_layers_to_keep])?)
+        };
+        let mut tempfile = tempfile::NamedTempFile::new_in(&graphs_dir)?;
+        let written = File::write_from_commits(commits, base.as_ref(), std::io::BufWriter::new(&mut tempfile), file)?;
+        let checksum = written.checksum.to_hex().to_string();
+        let path = layer_path(&graphs_dir, &checksum);
+        tempfile.persist(&path).map_err(|err| err.error)?;
+
+        let superseded = layers.split_off(num_layers_to_keep);
+        layers.push(checksum);
+        write_chain(&chain_path, &layers)?;
+        for checksum in superseded.iter().filter(|checksum| !layers.contains(checksum)) {
+            remove_if_present(&layer_path(&graphs_dir, checksum))?;
+        }
+
+        Ok(Outcome {
+            path: Some(path),
+            written: Some(written),
+            num_files: layers.len(),
+        })
+    }
+}
+
+fn layer_path(graphs_dir: &Path, checksum: &str) -> PathBuf {
+    graphs_dir.join(format!("graph-{}.graph", checksum))
+}
+
+/// Open the layers with the given `checksums` in `graphs_dir`, from the lowest to the topmost one.
+fn open_layers(graphs_dir: &Path, checksums: &[String]) -> Result<Graph, Error> {
+    Ok(Graph::new(
+        checksums
+            .iter()
+            .map(|checksum| File::at(layer_path(graphs_dir, checksum)))
+            .collect::<Result<_, _>>()?,
+    )?)
+}
+
+fn write_chain(chain_path: &Path, checksums: &[String]) -> std::io::Result<()> {
+    let mut chain_file = tempfile::NamedTempFile::new_in(chain_path.parent().expect("chain file is in a directory"))?;
+    for checksum in checksums {
+        writeln!(chain_file, "{}", checksum)?;
+    }
+    chain_file.persist(chain_path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn read_chain(chain_path: &Path) -> std::io::Result<Vec<String>> {
+    Ok(std::fs::read_to_string(chain_path)?
+        .lines()
+        .map(|line| line.trim().to_owned())
+        .filter(|line| !line.is_empty())
+        .collect())
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
@@ -69,3 +69,46 @@ fn two_parents() -> crate::Result {
 
     Ok(())
 }
+
+#[test]
+fn changed_path_bloom_filters() -> crate::Result {
+    let repo_dir = make_readonly_repo("changed_paths.sh");
+    let cg = Graph::from_info_dir(repo_dir.join(".git").join("objects").join("info"))?;
+    let settings = cg.bloom_filter_settings().expect("written with --changed-paths");
+    assert_eq!(settings, git_commitgraph::bloom::Settings::default());
+
+    let commit_with_generation = |generation: u32| {
+        cg.iter_commits()
+            .find(|c| c.generation() == generation)
+            .expect("linear history")
+            .id()
+            .to_owned()
+    };
+    let maybe_changed = |generation: u32, path: &str| {
+        let keys = git_commitgraph::bloom::Key::with_leading_directories(path.as_bytes(), &settings);
+        cg.maybe_changed_path(commit_with_generation(generation), &keys)
+            .expect("filter present")
+    };
+
+    for path in &["a", "dir", "dir/b", "dir/sub/c"] {
+        assert!(maybe_changed(1, path), "the root commit adds all paths");
+    }
+    assert!(maybe_changed(2, "a"));
+    assert!(!maybe_changed(2, "dir/sub/c"), "only 'a' was changed");
+    assert!(!maybe_changed(3, "a"));
+    assert!(maybe_changed(3, "dir/b"), "deletions are changes, too");
+    assert!(maybe_changed(3, "dir/sub/c"));
+    assert!(!maybe_changed(3, "dir/other"));
+    assert_eq!(
+        Graph::from_info_dir(
+            make_readonly_repo("single_parent.sh")
+                .join(".git")
+                .join("objects")
+                .join("info")
+        )?
+        .bloom_filter_settings(),
+        None,
+        "filters are only present if they were written"
+    );
+    Ok(())
+}
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+mkdir -p dir/sub
+echo a > a
+echo b > dir/b
+echo c > dir/sub/c
+git add .
+git commit -q -m c1
+
+echo a2 > a
+git commit -q -am c2
+
+echo c2 > dir/sub/c
+git rm -q dir/b
+git commit -q -am c3
+
+git commit-graph write --no-progress --reachable --changed-paths
@@ -0,0 +1,338 @@
+use std::{path::Path, process::Command};
+
+use git_commitgraph::{
+    bloom,
+    file::{self, write},
+    graph, Graph,
+};
+
+use crate::make_readonly_repo;
+
+fn info_dir(repo_dir: &Path) -> std::path::PathBuf {
+    repo_dir.join(".git").join("objects").join("info")
+}
+
+/// Turn the commits of `files` back into their writable form, using `graph` to resolve parent positions.
+fn commits_of<'a>(graph: &Graph, files: impl IntoIterator<Item = &'a file::File>) -> Vec<write::Commit> {
+    files
+        .into_iter()
+        .flat_map(|file| file.iter_commits())
+        .map(|commit| write::Commit {
+            id: commit.id().to_owned(),
+            root_tree_id: commit.root_tree_id().to_owned(),
+            parents: commit
+                .iter_parents()
+                .map(|pos| graph.id_at(pos.expect("valid parent")).to_owned())
+                .collect(),
+            committer_timestamp: commit.committer_timestamp(),
+            changed_paths: None,
+        })
+        .collect()
+}
+
+fn changed_paths(repo_dir: &Path, id: &git_hash::oid) -> Vec<bstr::BString> {
+    let output = Command::new("git")
+        .current_dir(repo_dir)
+        .args(&["diff-tree", "-r", "--no-commit-id", "--name-only", "--root"])
+        .arg(id.to_string())
+        .env_remove("GIT_DIR")
+        .output()
+        .expect("git diff-tree to run");
+    assert!(output.status.success());
+    output
+        .stdout
+        .split(|b| *b == b'\n')
+        .filter(|l| !l.is_empty())
+        .map(Into::into)
+        .collect()
+}
+
+#[test]
+fn octopus_merges_are_written_like_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("octopus_merges.sh");
+    let path = info_dir(&repo_dir).join("commit-graph");
+    let file = file::File::at(&path)?;
+    let graph = Graph::from_file(&path)?;
+
+    let mut out = Vec::new();
+    let outcome = file::File::write_from_commits(
+        commits_of(&graph, Some(&file)),
+        None,
+        &mut out,
+        write::Options {
+            generation_data: file.has_generation_data(),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(outcome.num_commits, 7);
+    assert_eq!(outcome.checksum, file.checksum());
+    assert_eq!(out, std::fs::read(&path)?, "the file is byte-for-byte identical");
+    Ok(())
+}
+
+#[test]
+fn changed_path_bloom_filters_are_written_like_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("changed_paths.sh");
+    let path = info_dir(&repo_dir).join("commit-graph");
+    let file = file::File::at(&path)?;
+    let graph = Graph::from_file(&path)?;
+
+    let mut commits = commits_of(&graph, Some(&file));
+    for commit in &mut commits {
+        commit.changed_paths = Some(changed_paths(&repo_dir, &commit.id));
+    }
+    let mut out = Vec::new();
+    file::File::write_from_commits(
+        commits,
+        None,
+        &mut out,
+        write::Options {
+            generation_data: file.has_generation_data(),
+            changed_paths: Some(bloom::Settings::default()),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(out, std::fs::read(&path)?, "the file is byte-for-byte identical");
+    Ok(())
+}
+
+#[test]
+fn split_chain_layers_are_written_like_git() -> crate::Result {
+    let repo_dir = make_readonly_repo("split_chain.sh");
+    let graphs_dir = info_dir(&repo_dir).join("commit-graphs");
+    let chain = std::fs::read_to_string(graphs_dir.join("commit-graph-chain"))?;
+    let layer_paths: Vec<_> = chain
+        .lines()
+        .map(|hash| graphs_dir.join(format!("graph-{}.graph", hash)))
+        .collect();
+    assert_eq!(layer_paths.len(), 3);
+    let graph = Graph::from_commit_graphs_dir(&graphs_dir)?;
+
+    for (idx, layer_path) in layer_paths.iter().enumerate() {
+        let base = if idx == 0 {
+            None
+        } else {
+            Some(Graph::new(
+                layer_paths[..idx]
+                    .iter()
+                    .map(file::File::at)
+                    .collect::<Result<_, _>>()?,
+            )?)
+        };
+        let file = file::File::at(layer_path)?;
+        let mut out = Vec::new();
+        file::File::write_from_commits(
+            commits_of(&graph, Some(&file)),
+            base.as_ref(),
+            &mut out,
+            write::Options {
+                generation_data: file.has_generation_data(),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            std::fs::read(layer_path)?,
+            "layer {} is byte-for-byte identical",
+            idx
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn generation_numbers_and_corrected_commit_dates() -> crate::Result {
+    let id = |byte: u8| git_hash::ObjectId::from([byte; 20]);
+    let commit = |byte: u8, parents: &[u8], committer_timestamp: u64| write::Commit {
+        id: id(byte),
+        root_tree_id: id(0xff),
+        parents: parents.iter().copied().map(id).collect(),
+        committer_timestamp,
+        changed_paths: None,
+    };
+    let dir = tempfile::tempdir()?;
+    let outcome = Graph::write_to_info_dir(
+        dir.path(),
+        vec![
+            commit(1, &[], 100),
+            // The clock of this commit was skewed.
+            commit(2, &[1], 50),
+            commit(3, &[2], 200),
+            commit(4, &[1, 2, 3], 10),
+        ],
+        Default::default(),
+    )?;
+    assert_eq!(outcome.num_files, 1);
+
+    let graph = Graph::from_info_dir(dir.path())?;
+    let generations: Vec<_> = (1..=4)
+        .map(|byte| {
+            let c = graph.commit_by_id(id(byte)).expect("present");
+            (c.generation(), c.corrected_commit_date())
+        })
+        .collect();
+    assert_eq!(
+        generations,
+        vec![(1, Some(100)), (2, Some(101)), (3, Some(200)), (4, Some(201))]
+    );
+    assert_eq!(
+        graph
+            .commit_by_id(id(4))
+            .expect("present")
+            .iter_parents()
+            .collect::<Result<Vec<_>, _>>()?,
+        vec![graph::Position(0), graph::Position(1), graph::Position(2)],
+        "octopus merges use the extra edges list"
+    );
+    graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+    Ok(())
+}
+
+#[test]
+fn missing_parents_and_cycles_are_errors() {
+    let id = |byte: u8| git_hash::ObjectId::from([byte; 20]);
+    let commit = |byte: u8, parent: u8| write::Commit {
+        id: id(byte),
+        root_tree_id: id(0xff),
+        parents: vec![id(parent)],
+        committer_timestamp: 0,
+        changed_paths: None,
+    };
+    assert!(matches!(
+        file::File::write_from_commits(vec![commit(1, 2)], None, Vec::new(), Default::default()),
+        Err(write::Error::MissingParent { .. })
+    ));
+    assert!(matches!(
+        file::File::write_from_commits(vec![commit(1, 2), commit(2, 1)], None, Vec::new(), Default::default()),
+        Err(write::Error::Cycle { .. })
+    ));
+}
+
+mod split {
+    use git_commitgraph::{
+        file::write,
+        graph::write::{Options, Split},
+        Graph,
+    };
+    use git_testtools::scripted_fixture_repo_writable;
+
+    use super::info_dir;
+
+    fn new_commit_on_top(repo_dir: &std::path::Path) -> Result<write::Commit, Box<dyn std::error::Error>> {
+        let graph = Graph::from_info_dir(info_dir(repo_dir))?;
+        let parent = graph
+            .iter_commits()
+            .max_by_key(|c| c.generation())
+            .expect("at least one commit");
+        Ok(write::Commit {
+            id: git_hash::ObjectId::from([0xaa; 20]),
+            root_tree_id: parent.root_tree_id().to_owned(),
+            parents: vec![parent.id().to_owned()],
+            committer_timestamp: parent.committer_timestamp() + 1,
+            changed_paths: None,
+        })
+    }
+
+    fn write_layer(split: Split, expected_layers: usize) -> crate::Result {
+        let repo = scripted_fixture_repo_writable("split_chain.sh")?;
+        let info_dir = info_dir(repo.path());
+        let commit = new_commit_on_top(repo.path())?;
+        let outcome = Graph::write_to_info_dir(
+            &info_dir,
+            Some(commit.clone()),
+            Options {
+                split: Some(split),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(outcome.num_files, expected_layers);
+        assert_eq!(
+            std::fs::read_dir(info_dir.join("commit-graphs"))?.count(),
+            expected_layers + 1,
+            "superseded layers are removed, and there is the chain file"
+        );
+
+        let graph = Graph::from_info_dir(&info_dir)?;
+        assert_eq!(graph.num_commits(), 4);
+        assert_eq!(graph.commit_by_id(commit.id).expect("present").generation(), 4);
+        graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+
+        let outcome = Graph::write_to_info_dir(
+            &info_dir,
+            Some(commit),
+            Options {
+                split: Some(split),
+                ..Default::default()
+            },
+        )?;
+        assert!(
+            outcome.written.is_none(),
+            "commits that are already present aren't written again"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_merge_adds_a_layer() -> crate::Result {
+        write_layer(Split::NoMerge, 4)
+    }
+
+    #[test]
+    fn size_multiple_merges_small_layers() -> crate::Result {
+        write_layer(Split::default(), 1)
+    }
+
+    #[test]
+    fn a_single_file_becomes_the_lowest_layer() -> crate::Result {
+        let repo = scripted_fixture_repo_writable("single_parent.sh")?;
+        let info_dir = info_dir(repo.path());
+        let commit = new_commit_on_top(repo.path())?;
+        let outcome = Graph::write_to_info_dir(
+            &info_dir,
+            Some(commit.clone()),
+            Options {
+                split: Some(Split::NoMerge),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(outcome.num_files, 2);
+        assert!(
+            !info_dir.join("commit-graph").exists(),
+            "the single file was moved into the chain"
+        );
+
+        let graph = Graph::from_info_dir(&info_dir)?;
+        assert_eq!(graph.num_commits(), 3);
+        assert_eq!(graph.commit_by_id(commit.id).expect("present").generation(), 3);
+        graph.verify_integrity(|_| Ok::<_, std::convert::Infallible>(()))?;
+        Ok(())
+    }
+
+    #[test]
+    fn a_single_file_becomes_a_chain_even_if_there_is_nothing_to_write() -> crate::Result {
+        let repo = scripted_fixture_repo_writable("single_parent.sh")?;
+        let info_dir = info_dir(repo.path());
+        let commits = {
+            let path = info_dir.join("commit-graph");
+            super::commits_of(
+                &Graph::from_file(&path)?,
+                Some(&git_commitgraph::file::File::at(&path)?),
+            )
+        };
+        let outcome = Graph::write_to_info_dir(
+            &info_dir,
+            commits,
+            Options {
+                split: Some(Split::default()),
+                ..Default::default()
+            },
+        )?;
+        assert!(outcome.written.is_none(), "all commits are present already");
+        assert_eq!(outcome.num_files, 1);
+        assert!(info_dir.join("commit-graphs").join("commit-graph-chain").is_file());
+
+        let graph = Graph::from_info_dir(&info_dir)?;
+        assert_eq!(graph.num_commits(), 2);
+        Ok(())
+    }
+}
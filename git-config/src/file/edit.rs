@@ -0,0 +1,272 @@
+//! Editing a [`GitConfig`] by dotted keys like `remote.origin.url`, similar to what `git config` does.
+use std::borrow::Cow;
+
+use quick_error::quick_error;
+
+use crate::{
+    file::{split_key, GitConfig, MutableSection, SectionBody},
+    parser::{Event, Key, SectionHeaderName},
+};
+
+quick_error! {
+    #[derive(Debug, PartialEq, Eq)]
+    /// The error returned when editing a [`GitConfig`] by dotted keys.
+    #[allow(missing_docs)]
+    pub enum Error {
+        InvalidKey(key: String) {
+            display("'{}' is not a valid key of the form 'section[.subsection].name'", key)
+        }
+        InvalidSectionName(name: String) {
+            display("'{}' is not a valid section name, which may only contain alphanumeric characters and '-'", name)
+        }
+        InvalidSubsectionName(name: String) {
+            display("Subsection name {:?} must not contain newlines, null bytes, quotes or backslashes", name)
+        }
+    }
+}
+
+/// # Editing by dotted keys
+///
+/// These functions edit values and sections given a key like `remote.origin.url` or a section name like
+/// `remote.origin`, like `git config` does. Sections are created as needed and names are validated to keep the
+/// configuration valid when it is written.
+impl<'event> GitConfig<'event> {
+    /// Sets the value of `key` like `remote.origin.url` to `value`, like `git config <key> <value>` does.
+    ///
+    /// The last occurrence of the key is replaced. Otherwise the value is appended to the last section of that name,
+    /// which is created if it doesn't exist yet. `value` is quoted and escaped as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use git_config::file::GitConfig;
+    /// # use std::convert::TryFrom;
+    /// let mut config = GitConfig::try_from("[core]\n\tbare = true").unwrap();
+    /// config.set_raw_value_by_key("core.bare", b"false").unwrap();
+    /// config.set_raw_value_by_key("remote.origin.url", b"https://example.com/repo").unwrap();
+    /// assert_eq!(
+    ///     config.to_string(),
+    ///     "[core]\n\tbare = false\n[remote \"origin\"]\n  url=https://example.com/repo\n"
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` isn't a valid key.
+    pub fn set_raw_value_by_key(&mut self, key: &str, value: &[u8]) -> Result<(), Error> {
+        let (section_name, subsection_name, value_name) = parse_key(key)?;
+        let value = escape_value(value);
+        if let Ok(mut existing) = self.get_raw_value_mut(section_name, subsection_name, value_name) {
+            existing.set_bytes(value);
+            return Ok(());
+        }
+
+        let last_section_id = self
+            .get_section_ids_by_name_and_subname(section_name, subsection_name)
+            .ok()
+            .and_then(|ids| ids.last().copied());
+        let mut section = match last_section_id {
+            Some(id) => {
+                let body = self.sections.get_mut(&id).expect("section ids from lookup are valid");
+                let needs_newline = !ends_with_newline(body);
+                let mut section = MutableSection::new(body);
+                if needs_newline {
+                    section.push_newline();
+                }
+                section
+            }
+            None => {
+                self.terminate_last_line();
+                self.new_section(
+                    section_name.to_owned(),
+                    subsection_name.map(|name| Cow::Owned(name.to_owned())),
+                )
+            }
+        };
+        section.push(Key(Cow::Owned(value_name.to_owned())), Cow::Owned(value));
+        Ok(())
+    }
+
+    /// Removes the last occurrence of `key` like `core.bare` along with its line, like `git config --unset <key>`
+    /// does, returning its value if it existed. Keys without value yield an empty value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` isn't a valid key.
+    pub fn unset_by_key(&mut self, key: &str) -> Result<Option<Cow<'event, [u8]>>, Error> {
+        let (section_name, subsection_name, value_name) = parse_key(key)?;
+        let key = Key(Cow::Owned(value_name.to_owned()));
+        let section_ids = self
+            .get_section_ids_by_name_and_subname(section_name, subsection_name)
+            .unwrap_or_default();
+        Ok(section_ids.into_iter().rev().find_map(|id| {
+            remove_line_of_last_key(
+                self.sections.get_mut(&id).expect("section ids from lookup are valid"),
+                &key,
+            )
+        }))
+    }
+
+    /// Removes all occurrences of `key` like `remote.origin.fetch` along with their lines, like
+    /// `git config --unset-all <key>` does, returning the amount of removed values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `key` isn't a valid key.
+    pub fn unset_all_by_key(&mut self, key: &str) -> Result<usize, Error> {
+        let mut count = 0;
+        while self.unset_by_key(key)?.is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Renames all sections called `name` like `remote.origin` to `new_name` like `remote.upstream`, like
+    /// `git config --rename-section` does, returning the amount of renamed sections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` or `new_name` aren't valid section names.
+    pub fn rename_section_by_key(&mut self, name: &str, new_name: &str) -> Result<usize, Error> {
+        let (section_name, subsection_name) = parse_section_name(name)?;
+        let (new_section_name, new_subsection_name) = parse_section_name(new_name)?;
+        let section_ids = self
+            .get_section_ids_by_name_and_subname(section_name, subsection_name)
+            .unwrap_or_default();
+        for id in &section_ids {
+            self.rename_section_by_id(
+                *id,
+                SectionHeaderName(Cow::Owned(new_section_name.to_owned())),
+                new_subsection_name.map(|name| Cow::Owned(name.to_owned())),
+            );
+        }
+        Ok(section_ids.len())
+    }
+
+    /// Removes all sections called `name` like `remote.origin` along with their values, like
+    /// `git config --remove-section` does, returning the amount of removed sections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `name` isn't a valid section name.
+    pub fn remove_section_by_key(&mut self, name: &str) -> Result<usize, Error> {
+        let (section_name, subsection_name) = parse_section_name(name)?;
+        let mut count = 0;
+        while self.remove_section(section_name, subsection_name).is_some() {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Makes sure that the last section, or the frontmatter if there is none, ends with a newline so that a new
+    /// section header can follow.
+    fn terminate_last_line(&mut self) {
+        let last_body = match self.section_order.back() {
+            Some(id) => self.sections.get_mut(id).expect("section order contains valid ids"),
+            None => &mut self.frontmatter_events,
+        };
+        if !last_body.as_ref().is_empty() && !ends_with_newline(last_body) {
+            last_body.as_mut().push(Event::Newline("\n".into()));
+        }
+    }
+}
+
+/// Split `key` into its section name, subsection name and value name and assure they can be written as is.
+fn parse_key(key: &str) -> Result<(&str, Option<&str>, &str), Error> {
+    let (section_name, subsection_name, value_name) = split_key(key).ok_or_else(|| Error::InvalidKey(key.into()))?;
+    validate_section_name(section_name, subsection_name)?;
+    let mut chars = value_name.chars();
+    let starts_with_letter = chars.next().map_or(false, |c| c.is_ascii_alphabetic());
+    if !starts_with_letter || !chars.all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(Error::InvalidKey(key.into()));
+    }
+    Ok((section_name, subsection_name, value_name))
+}
+
+/// Split `name` like `remote.origin` into its section and subsection name and assure they can be written as is.
+fn parse_section_name(name: &str) -> Result<(&str, Option<&str>), Error> {
+    let (section_name, subsection_name) = match name.split_once('.') {
+        Some((section_name, subsection_name)) => (section_name, Some(subsection_name)),
+        None => (name, None),
+    };
+    validate_section_name(section_name, subsection_name)?;
+    Ok((section_name, subsection_name))
+}
+
+fn validate_section_name(section_name: &str, subsection_name: Option<&str>) -> Result<(), Error> {
+    if section_name.is_empty() || !section_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(Error::InvalidSectionName(section_name.into()));
+    }
+    match subsection_name {
+        // Subsection names are kept as they are written, so ones that would need escaping can't be represented.
+        Some(name) if name.contains(&['\n', '\0', '"', '\\'][..]) => Err(Error::InvalidSubsectionName(name.into())),
+        _ => Ok(()),
+    }
+}
+
+/// Quote and escape `value` like git does when writing it, so it reads back unchanged.
+fn escape_value(value: &[u8]) -> Vec<u8> {
+    let needs_quotes =
+        value.first() == Some(&b' ') || value.last() == Some(&b' ') || value.iter().any(|b| *b == b';' || *b == b'#');
+    let mut out = Vec::with_capacity(value.len() + 2);
+    if needs_quotes {
+        out.push(b'"');
+    }
+    for b in value {
+        match b {
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            _ => out.push(*b),
+        }
+    }
+    if needs_quotes {
+        out.push(b'"');
+    }
+    out
+}
+
+fn ends_with_newline(body: &SectionBody<'_>) -> bool {
+    matches!(
+        body.as_ref().iter().rev().find(|e| !matches!(e, Event::Whitespace(_))),
+        Some(Event::Newline(_))
+    )
+}
+
+/// Remove the last occurrence of `key` in `body` along with its value, and its entire line if nothing else is on it.
+fn remove_line_of_last_key<'event>(body: &mut SectionBody<'event>, key: &Key<'event>) -> Option<Cow<'event, [u8]>> {
+    let key_index = body
+        .as_ref()
+        .iter()
+        .rposition(|e| matches!(e, Event::Key(k) if k == key))?;
+    let value = body.value(key).unwrap_or_default();
+    let value_range = body.get_value_range_by_key(key);
+    let events = body.as_mut();
+
+    let mut start = key_index;
+    while start > 0 && matches!(events[start - 1], Event::Whitespace(_)) {
+        start -= 1;
+    }
+    let mut end = if value_range.is_empty() {
+        key_index + 1
+    } else {
+        value_range.end
+    };
+    while matches!(events.get(end), Some(Event::Whitespace(_))) {
+        end += 1;
+    }
+    let is_own_line = start > 0 && matches!(events[start - 1], Event::Newline(_));
+    if is_own_line {
+        if let Some(Event::Newline(newlines)) = events.get_mut(end) {
+            let first_newline_len = if newlines.starts_with("\r\n") { 2 } else { 1 };
+            if newlines.len() == first_newline_len {
+                end += 1;
+            } else {
+                *newlines = Cow::Owned(newlines[first_newline_len..].to_owned());
+            }
+        }
+    }
+    events.drain(start..end);
+    Some(value)
+}
@@ -9,6 +9,8 @@ use std::{
 use crate::{
     file::{
         error::GitConfigError,
+        origin::SectionOrigin,
+        resolve_includes,
         section::{MutableSection, SectionBody},
         value::{EntryData, MutableMultiValue, MutableValue},
         Index, Size,
@@ -100,7 +102,7 @@ pub struct GitConfig<'event> {
     /// The list of events that occur before an actual section. Since a
     /// `git-config` file prohibits global values, this vec is limited to only
     /// comment, newline, and whitespace events.
-    frontmatter_events: SectionBody<'event>,
+    pub(super) frontmatter_events: SectionBody<'event>,
     /// Section name and subsection name to section id lookup tree. This is
     /// effectively a n-tree (opposed to a binary tree) that can have a height
     /// of at most three (including an implicit root node).
@@ -111,16 +113,44 @@ pub struct GitConfig<'event> {
     /// This indirection with the SectionId as the key is critical to flexibly
     /// supporting `git-config` sections, as duplicated keys are permitted.
     pub(super) sections: HashMap<SectionId, SectionBody<'event>>,
-    section_headers: HashMap<SectionId, ParsedSectionHeader<'event>>,
+    pub(super) section_headers: HashMap<SectionId, ParsedSectionHeader<'event>>,
     /// Internal monotonically increasing counter for section ids.
     section_id_counter: usize,
     /// Section order for output ordering.
-    section_order: VecDeque<SectionId>,
+    pub(super) section_order: VecDeque<SectionId>,
+    /// Where each parsed section was defined, used to provide the origin of values.
+    pub(super) section_origins: HashMap<SectionId, SectionOrigin>,
 }
 
-pub mod from_paths {
-    use std::borrow::Cow;
+///
+pub mod write {
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        /// The error returned by [`GitConfig::write_to_path()`][super::GitConfig::write_to_path()].
+        #[allow(missing_docs)]
+        pub enum Error {
+            Lock(err: git_lock::acquire::Error) {
+                display("Could not acquire the lock to write the config file")
+                source(err)
+                from()
+            }
+            Io(err: std::io::Error) {
+                display("Could not write the config to its lock file")
+                source(err)
+                from()
+            }
+            Commit(err: git_lock::commit::Error<git_lock::File>) {
+                display("Could not replace the config file with its lock file")
+                source(err)
+                from()
+            }
+        }
+    }
+}
 
+pub mod from_paths {
     use quick_error::quick_error;
 
     use crate::{parser, values::path::interpolate};
@@ -146,14 +176,18 @@ pub mod from_paths {
             MissingConfigPath {
                 display("Include paths from environment variables must not be relative.")
             }
+            RemoteUrlInHasconfigInclude { path: std::path::PathBuf } {
+                display("Remote urls must not be configured in {:?} as it is included by an includeIf.hasconfig:remote.*.url condition", path)
+            }
         }
     }
 
     /// Options when loading git config using [`GitConfig::from_paths()`][super::GitConfig::from_paths()].
     #[derive(Clone)]
     pub struct Options<'a> {
-        /// The location where gitoxide or git is installed
-        pub git_install_dir: Option<Cow<'a, std::path::Path>>,
+        /// What's available to expand `%(prefix)/`, `~/` and `~user/` in include paths and `gitdir` conditions.
+        /// Paths that need anything missing here can't be included.
+        pub interpolate: interpolate::Context<'a>,
         /// The maximum allowed length of the file include chain built by following nested includes where base level is depth = 0.
         pub max_depth: u8,
         /// When max depth is exceeded while following nested included, return an error if true or silently stop following
@@ -161,14 +195,22 @@ pub mod from_paths {
         ///
         /// Setting this value to false allows to read configuration with cycles, which otherwise always results in an error.
         pub error_on_max_depth_exceeded: bool,
+        /// The location of the `.git` directory of the repository the configuration is loaded for, used to evaluate
+        /// `includeIf "gitdir:…"` and `includeIf "gitdir/i:…"` conditions. These never match if unset.
+        pub git_dir: Option<&'a std::path::Path>,
+        /// The full name of the currently checked out branch, like `refs/heads/main`, used to evaluate
+        /// `includeIf "onbranch:…"` conditions. These never match if unset or if `HEAD` is detached.
+        pub branch_name: Option<&'a [u8]>,
     }
 
     impl<'a> Default for Options<'a> {
         fn default() -> Self {
             Options {
-                git_install_dir: None,
+                interpolate: Default::default(),
                 max_depth: 10,
                 error_on_max_depth_exceeded: true,
+                git_dir: None,
+                branch_name: None,
             }
         }
     }
@@ -227,7 +269,12 @@ impl<'event> GitConfig<'event> {
     /// git-config file.
     #[inline]
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, parser::ParserOrIoError<'static>> {
-        parse_from_path(path).map(Self::from)
+        let path = path.as_ref();
+        let mut config = parse_from_path(path).map(Self::from)?;
+        for origin in config.section_origins.values_mut() {
+            origin.path = Some(path.to_owned());
+        }
+        Ok(config)
     }
 
     /// Constructs a `git-config` file from the provided paths in the order provided.
@@ -238,13 +285,22 @@ impl<'event> GitConfig<'event> {
     /// Returns an error if there was an IO error or if a file wasn't a valid
     /// git-config file.
     ///
+    /// Includes are resolved with `includeIf "hasconfig:remote.*.url:…"` conditions being evaluated against the remote
+    /// urls of all files, which requires a first pass in which these conditions are false.
+    ///
     /// [`git-config`'s documentation]: https://git-scm.com/docs/git-config#Documentation/git-config.txt-FILES
     #[inline]
     pub fn from_paths(paths: Vec<PathBuf>, options: &from_paths::Options) -> Result<Self, from_paths::Error> {
+        let mut configs = paths.iter().map(Self::open).collect::<Result<Vec<_>, _>>()?;
+        let mut configs_with_paths: Vec<_> = configs
+            .iter_mut()
+            .zip(&paths)
+            .map(|(config, path)| (config, Some(path.as_path())))
+            .collect();
+        resolve_includes::resolve_includes(&mut configs_with_paths, options).map_err(|(_, err)| err)?;
+
         let mut target = Self::new();
-        for path in paths {
-            let mut config = Self::open(&path)?;
-            config.resolve_includes(Some(&path), options)?;
+        for config in configs {
             target.append(config);
         }
         Ok(target)
@@ -252,13 +308,16 @@ impl<'event> GitConfig<'event> {
 
     // TODO: add note indicating that probably a lot if not all information about the original files is currently lost,
     //       so can't be written back. This will probably change a lot during refactor, so it's not too important now.
-    fn append(&mut self, mut other: Self) {
+    pub(super) fn append(&mut self, mut other: Self) {
         let mut section_indices: Vec<_> = other.section_headers.keys().cloned().collect();
         // header keys are numeric and ascend in insertion order, hence sorting them gives the order
         // in which they appear in the config file.
         section_indices.sort();
         for section_index in section_indices {
             let section_header = other.section_headers.remove(&section_index).expect("present");
+            if let Some(origin) = other.section_origins.remove(&section_index) {
+                self.section_origins.insert(SectionId(self.section_id_counter), origin);
+            }
             self.push_section(
                 section_header.name.0,
                 section_header.subsection_name,
@@ -272,64 +331,35 @@ impl<'event> GitConfig<'event> {
         config_path: Option<&std::path::Path>,
         options: &from_paths::Options,
     ) -> Result<(), from_paths::Error> {
-        fn resolve_includes_recursive(
-            target_config: &mut GitConfig,
-            target_config_path: Option<&Path>,
-            depth: u8,
-            options: &from_paths::Options,
-        ) -> Result<(), from_paths::Error> {
-            if depth == options.max_depth {
-                return if options.error_on_max_depth_exceeded {
-                    Err(from_paths::Error::IncludeDepthExceeded {
-                        max_depth: options.max_depth,
-                    })
-                } else {
-                    Ok(())
-                };
-            }
-
-            let mut paths_to_include = Vec::new();
-            for path in target_config
-                .multi_value::<values::Path>("include", None, "path")
-                .unwrap_or_default()
-            {
-                let path = path.interpolate(options.git_install_dir.as_deref())?;
-                let path: PathBuf = if path.is_relative() {
-                    target_config_path
-                        .ok_or(from_paths::Error::MissingConfigPath)?
-                        .parent()
-                        .expect("path is a config file which naturally lives in a directory")
-                        .join(path)
-                } else {
-                    path.into()
-                };
-
-                if path.is_file() {
-                    paths_to_include.push(path);
-                }
-            }
-
-            for config_path in paths_to_include {
-                let mut include_config = GitConfig::open(&config_path)?;
-                resolve_includes_recursive(&mut include_config, Some(&config_path), depth + 1, options)?;
-                target_config.append(include_config);
-            }
-            Ok(())
-        }
+        resolve_includes::resolve_includes(&mut [(self, config_path)], options).map_err(|(_, err)| err)
+    }
 
-        resolve_includes_recursive(self, config_path, 0, options)
+    /// Resolve the includes of all `configs`, each along with the path it was loaded from, as if they were one
+    /// configuration. This matters for `includeIf "hasconfig:remote.*.url:…"` conditions, which are evaluated against
+    /// the remote urls of all configurations.
+    ///
+    /// On error, the index of the configuration that failed is returned along with the error.
+    pub(crate) fn resolve_includes_of_all(
+        configs: &mut [(&mut Self, Option<&Path>)],
+        options: &from_paths::Options,
+    ) -> Result<(), (usize, from_paths::Error)> {
+        resolve_includes::resolve_includes(configs, options)
     }
 
     /// Constructs a `git-config` from the default cascading sequence.
     /// This is neither zero-alloc nor zero-copy.
     ///
+    /// The system configuration is skipped if `GIT_CONFIG_NOSYSTEM` is true, and `GIT_CONFIG_SYSTEM` as well as
+    /// `GIT_CONFIG_GLOBAL` replace the default locations of the system and global configuration files respectively.
+    /// Files that don't exist are skipped, like git does.
+    ///
     /// See <https://git-scm.com/docs/git-config#FILES> for details.
     pub fn from_env_paths(options: &from_paths::Options) -> Result<Self, from_paths::Error> {
         use std::env;
 
         let mut paths = vec![];
 
-        if env::var("GIT_CONFIG_NO_SYSTEM").is_err() {
+        if !env_var_is_true("GIT_CONFIG_NOSYSTEM") {
             if let Ok(git_config_system) = env::var("GIT_CONFIG_SYSTEM") {
                 paths.push(PathBuf::from(git_config_system))
             } else {
@@ -359,6 +389,7 @@ impl<'event> GitConfig<'event> {
             paths.push(PathBuf::from(git_dir).join("config"));
         }
 
+        paths.retain(|path| path.is_file());
         Self::from_paths(paths, options)
     }
 
@@ -383,13 +414,7 @@ impl<'event> GitConfig<'event> {
         for i in 0..count {
             let key = env::var(format!("GIT_CONFIG_KEY_{}", i)).map_err(|_| from_env::Error::InvalidKeyId(i))?;
             let value = env::var(format!("GIT_CONFIG_VALUE_{}", i)).map_err(|_| from_env::Error::InvalidValueId(i))?;
-            if let Some((section_name, maybe_subsection)) = key.split_once('.') {
-                let (subsection, key) = if let Some((subsection, key)) = maybe_subsection.rsplit_once('.') {
-                    (Some(subsection), key)
-                } else {
-                    (None, maybe_subsection)
-                };
-
+            if let Some((section_name, subsection, key)) = split_key(&key) {
                 let mut section = if let Ok(section) = config.section_mut(section_name, subsection) {
                     section
                 } else {
@@ -532,6 +557,74 @@ impl<'event> GitConfig<'event> {
             .map_err(|_| GitConfigError::FailedConversion)
     }
 
+    /// Returns the value of `key` like `core.bare` or `remote.origin.mirror` interpreted as boolean the way git does,
+    /// or `None` if the key doesn't exist. The last value wins if there are multiple.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use git_config::file::GitConfig;
+    /// # use std::convert::TryFrom;
+    /// let config = GitConfig::try_from("[core]\n bare = yes\n[remote \"origin\"]\n mirror = 0").unwrap();
+    /// assert_eq!(config.boolean("core.bare"), Some(Ok(true)));
+    /// assert_eq!(config.boolean("remote.origin.mirror"), Some(Ok(false)));
+    /// assert_eq!(config.boolean("core.missing"), None);
+    /// ```
+    #[must_use]
+    pub fn boolean(&self, key: &str) -> Option<Result<bool, values::BooleanError>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.get_raw_value(section_name, subsection_name, key)
+            .ok()
+            .map(|value| values::Boolean::try_from(value).map(Into::into))
+    }
+
+    /// Returns the value of `key` like `core.packedGitLimit` interpreted as integer with its `k`, `m` or `g` suffix
+    /// applied, or `None` if the key doesn't exist. The last value wins if there are multiple.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use git_config::file::GitConfig;
+    /// # use std::convert::TryFrom;
+    /// let config = GitConfig::try_from("[core]\n packedGitLimit = 2k").unwrap();
+    /// assert_eq!(config.integer("core.packedGitLimit").unwrap().unwrap(), 2048);
+    /// ```
+    #[must_use]
+    pub fn integer(&self, key: &str) -> Option<Result<i64, values::IntegerError>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.get_raw_value(section_name, subsection_name, key)
+            .ok()
+            .map(|value| {
+                values::Integer::try_from(value)
+                    .and_then(|integer| integer.to_decimal().ok_or(values::IntegerError::Overflow))
+            })
+    }
+
+    /// Returns the normalized value of `key` like `user.name`, or `None` if the key doesn't exist.
+    /// The last value wins if there are multiple.
+    #[must_use]
+    pub fn string(&self, key: &str) -> Option<Cow<'_, [u8]>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.get_raw_value(section_name, subsection_name, key).ok()
+    }
+
+    /// Returns all normalized values of the multi-valued `key` like `remote.origin.fetch` in the order in which they
+    /// appear, or `None` if the key doesn't exist.
+    #[must_use]
+    pub fn strings(&self, key: &str) -> Option<Vec<Cow<'_, [u8]>>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.get_raw_multi_value(section_name, subsection_name, key).ok()
+    }
+
+    /// Returns the value of `key` like `core.excludesFile` as path, or `None` if the key doesn't exist.
+    /// The last value wins if there are multiple.
+    ///
+    /// Use [`values::Path::interpolate()`] to expand `~/`, `~user/` and `%(prefix)/`.
+    #[must_use]
+    pub fn path(&self, key: &str) -> Option<values::Path<'_>> {
+        self.string(key).map(values::Path::from)
+    }
+
     /// Returns an immutable section reference.
     ///
     /// # Errors
@@ -632,14 +725,13 @@ impl<'event> GitConfig<'event> {
     /// [url "ssh://git@bitbucket.org"]
     ///     insteadOf = https://bitbucket.org/
     /// ```
-    /// Calling this method will yield all section bodies and their header:
+    /// Calling this method will yield all section bodies and their header, in the order in which they appear:
     ///
     /// ```rust
     /// use git_config::file::{GitConfig, GitConfigError};
     /// use git_config::parser::Key;
     /// use std::borrow::Cow;
     /// use std::convert::TryFrom;
-    /// use nom::AsBytes;
     ///
     /// let input = r#"
     /// [url "ssh://git@github.com/"]
@@ -655,8 +747,7 @@ impl<'event> GitConfig<'event> {
     ///     let url = header.subsection_name.as_ref();
     ///     let instead_of = body.value(&Key::from("insteadOf"));
     ///
-    ///     // todo(unstable-order): the order is not always the same, so `i` cannot be used here
-    ///     if instead_of.as_ref().unwrap().as_ref().as_bytes().eq("https://github.com/".as_bytes()) {
+    ///     if i == 0 {
     ///         assert_eq!(instead_of.unwrap().as_ref(), "https://github.com/".as_bytes());
     ///         assert_eq!(url.unwrap().as_ref(), "ssh://git@github.com/");
     ///     } else {
@@ -685,6 +776,76 @@ impl<'event> GitConfig<'event> {
             .collect()
     }
 
+    /// Returns an iterator over all sections along with their header, in the order in which they appear in the config.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use git_config::file::GitConfig;
+    /// # use std::convert::TryFrom;
+    /// let config = GitConfig::try_from("[core]\n a = b\n[remote \"origin\"]\n url = c\n[core]").unwrap();
+    /// let names: Vec<_> = config
+    ///     .sections()
+    ///     .map(|(header, _body)| (header.name.to_string(), header.subsection_name.as_deref().map(ToOwned::to_owned)))
+    ///     .collect();
+    /// assert_eq!(
+    ///     names,
+    ///     vec![
+    ///         ("core".into(), None),
+    ///         ("remote".into(), Some("origin".into())),
+    ///         ("core".into(), None)
+    ///     ]
+    /// );
+    /// ```
+    pub fn sections(&self) -> impl Iterator<Item = (&ParsedSectionHeader<'event>, &SectionBody<'event>)> + '_ {
+        self.section_order.iter().map(move |id| {
+            (
+                self.section_headers
+                    .get(id)
+                    .expect("section doesn't have a section header??"),
+                self.sections.get(id).expect("section doesn't have id from from lookup"),
+            )
+        })
+    }
+
+    /// Returns the names of all subsections of sections named `section_name` in the order of their first appearance,
+    /// without duplicates.
+    ///
+    /// An empty `Vec` is returned if there is no section with `section_name` or if it has no subsections.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use git_config::file::GitConfig;
+    /// # use std::convert::TryFrom;
+    /// let config = GitConfig::try_from(
+    ///     r#"
+    ///     [remote "origin"]
+    ///         url = a
+    ///     [remote]
+    ///         pushDefault = origin
+    ///     [remote "upstream"]
+    ///         url = b
+    ///     [remote "origin"]
+    ///         fetch = +refs/heads/*:refs/remotes/origin/*
+    /// "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(config.subsection_names("remote"), vec!["origin", "upstream"]);
+    /// ```
+    #[must_use]
+    pub fn subsection_names<'lookup>(&self, section_name: &'lookup str) -> Vec<&str> {
+        let mut names = Vec::<&str>::new();
+        for (header, _) in self.sections_by_name_with_header(section_name) {
+            if let Some(name) = header.subsection_name.as_deref() {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        names
+    }
+
     /// Adds a new section to config. If a subsection name was provided, then
     /// the generated header will use the modern subsection syntax. Returns a
     /// reference to the new section for immediate editing.
@@ -773,6 +934,9 @@ impl<'event> GitConfig<'event> {
                 .position(|v| *v == id)
                 .expect("Section order does not contain section that we were trying to remove"),
         );
+        self.remove_from_lookup_tree(id);
+        self.section_headers.remove(&id);
+        self.section_origins.remove(&id);
         self.sections.remove(&id)
     }
 
@@ -819,15 +983,10 @@ impl<'event> GitConfig<'event> {
         new_subsection_name: impl Into<Option<Cow<'event, str>>>,
     ) -> Result<(), GitConfigError<'lookup>> {
         let id = self.get_section_ids_by_name_and_subname(section_name, subsection_name.into())?;
-        let id = id
+        let id = *id
             .last()
             .expect("list of sections were empty, which violates invariant");
-        let header = self
-            .section_headers
-            .get_mut(id)
-            .expect("sections does not have section id from section ids");
-        header.name = new_section_name.into();
-        header.subsection_name = new_subsection_name.into();
+        self.rename_section_by_id(id, new_section_name.into(), new_subsection_name.into());
 
         Ok(())
     }
@@ -845,6 +1004,35 @@ impl<'event> GitConfig<'event> {
     pub fn is_empty(&self) -> bool {
         self.sections.values().all(SectionBody::is_empty)
     }
+
+    /// Serializes this config to `out`. The result is identical to the input this config was parsed from, except for the
+    /// spans that were changed through the mutation API, which preserves comments, ordering and whitespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` couldn't be written to.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        out.write_all(&Vec::<u8>::from(self))
+    }
+
+    /// Writes this config to the file at `path` atomically, by writing it to a lock file next to it first which replaces
+    /// the file once it was written completely. If the lock is held by another process, `lock_mode` determines whether to
+    /// fail immediately or to retry for a while.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the lock couldn't be obtained, or if the lock file couldn't be written or moved into place.
+    /// The lock file is removed in that case, leaving the file at `path` untouched.
+    pub fn write_to_path(
+        &self,
+        path: impl AsRef<Path>,
+        lock_mode: git_lock::acquire::Fail,
+    ) -> Result<(), write::Error> {
+        let mut lock = git_lock::File::acquire_to_update_resource(path, lock_mode, None)?;
+        self.write_to(&mut lock)?;
+        lock.commit()?;
+        Ok(())
+    }
 }
 
 /// # Raw value API
@@ -928,12 +1116,9 @@ impl<'event> GitConfig<'event> {
                         size = Size(1);
                         index = Index(i);
                     }
-                    Event::Newline(_) | Event::Whitespace(_) | Event::ValueNotDone(_) if found_key => {
-                        size += 1;
-                    }
                     Event::ValueDone(_) | Event::Value(_) if found_key => {
                         found_key = false;
-                        size += 1;
+                        size = Size(i + 1 - index.0);
                     }
                     _ => (),
                 }
@@ -1283,10 +1468,26 @@ impl<'event> GitConfig<'event> {
         let new_section_id = SectionId(self.section_id_counter);
         self.section_headers.insert(new_section_id, header.clone());
         self.sections.insert(new_section_id, section);
+        self.add_to_lookup_tree(new_section_id, header);
+        self.section_order.push_back(new_section_id);
+        self.section_id_counter += 1;
+        self.sections.get_mut(&new_section_id).map(MutableSection::new).unwrap()
+    }
+
+    /// Makes the section with `section_id` findable by the name and subsection name in `header`.
+    fn add_to_lookup_tree(&mut self, new_section_id: SectionId, header: ParsedSectionHeader<'event>) {
         let lookup = self.section_lookup_tree.entry(header.name).or_default();
 
         let mut found_node = false;
         if let Some(subsection_name) = header.subsection_name {
+            // Subsections in the deprecated `[section.subsection]` syntax are case-insensitive, just like in git.
+            let subsection_name = if header.separator.as_deref() == Some(".")
+                && subsection_name.bytes().any(|b| b.is_ascii_uppercase())
+            {
+                Cow::Owned(subsection_name.to_ascii_lowercase())
+            } else {
+                subsection_name
+            };
             for node in lookup.iter_mut() {
                 if let LookupTreeNode::NonTerminal(subsection) = node {
                     found_node = true;
@@ -1315,13 +1516,57 @@ impl<'event> GitConfig<'event> {
                 lookup.push(LookupTreeNode::Terminal(vec![new_section_id]));
             }
         }
-        self.section_order.push_back(new_section_id);
-        self.section_id_counter += 1;
-        self.sections.get_mut(&new_section_id).map(MutableSection::new).unwrap()
+    }
+
+    /// Changes the header of the section with `section_id`, keeping the lookup tree in sync.
+    pub(super) fn rename_section_by_id(
+        &mut self,
+        section_id: SectionId,
+        new_section_name: SectionHeaderName<'event>,
+        new_subsection_name: Option<Cow<'event, str>>,
+    ) {
+        let header = self
+            .section_headers
+            .get_mut(&section_id)
+            .expect("sections does not have section id from section ids");
+        header.separator = new_subsection_name.is_some().then(|| " ".into());
+        header.name = new_section_name;
+        header.subsection_name = new_subsection_name;
+        let header = header.clone();
+        self.remove_from_lookup_tree(section_id);
+        self.add_to_lookup_tree(section_id, header);
+    }
+
+    /// Removes the section with `section_id` from the lookup tree, dropping all nodes that become empty so lookups
+    /// keep failing with an error instead of yielding no ids.
+    fn remove_from_lookup_tree(&mut self, section_id: SectionId) {
+        self.section_lookup_tree.retain(|_, lookup| {
+            for node in lookup.iter_mut() {
+                match node {
+                    LookupTreeNode::Terminal(ids) => ids.retain(|id| *id != section_id),
+                    LookupTreeNode::NonTerminal(subsections) => subsections.retain(|_, ids| {
+                        ids.retain(|id| *id != section_id);
+                        !ids.is_empty()
+                    }),
+                }
+            }
+            lookup.retain(|node| match node {
+                LookupTreeNode::Terminal(ids) => !ids.is_empty(),
+                LookupTreeNode::NonTerminal(subsections) => !subsections.is_empty(),
+            });
+            !lookup.is_empty()
+        });
+    }
+
+    /// Adds a section that was parsed with its header on the given 1-based `line`.
+    fn push_parsed_section(&mut self, header: ParsedSectionHeader<'event>, section: SectionBody<'event>, line: usize) {
+        self.section_origins
+            .insert(SectionId(self.section_id_counter), SectionOrigin { path: None, line });
+        self.push_section_internal(header, section);
     }
 
     /// Returns the mapping between section and subsection name to section ids.
-    fn get_section_ids_by_name_and_subname<'lookup>(
+    pub(super) fn get_section_ids_by_name_and_subname<'lookup>(
         &self,
         section_name: impl Into<SectionHeaderName<'lookup>>,
         subsection_name: Option<&'lookup str>,
@@ -1363,18 +1608,46 @@ impl<'event> GitConfig<'event> {
         self.section_lookup_tree
             .get(&section_name)
             .map(|lookup| {
-                lookup
+                let mut ids: Vec<_> = lookup
                     .iter()
                     .flat_map(|node| match node {
                         LookupTreeNode::Terminal(v) => v.clone(),
                         LookupTreeNode::NonTerminal(v) => v.values().flatten().copied().collect(),
                     })
-                    .collect()
+                    .collect();
+                // Ids increase monotonically, so sorting them restores the order in which sections were added.
+                ids.sort_unstable();
+                ids
             })
             .ok_or(GitConfigError::SectionDoesNotExist(section_name))
     }
 }
 
+/// Split a dotted `key` like `remote.origin.url` into its section name, optional subsection name and value name, or
+/// return `None` if the section or value name are missing.
+pub(crate) fn split_key(key: &str) -> Option<(&str, Option<&str>, &str)> {
+    let (section_name, rest) = key.split_once('.')?;
+    let (subsection_name, value_name) = match rest.rsplit_once('.') {
+        Some((subsection_name, value_name)) => (Some(subsection_name), value_name),
+        None => (None, rest),
+    };
+    (!section_name.is_empty() && !value_name.is_empty()).then(|| (section_name, subsection_name, value_name))
+}
+
+/// Interpret the environment variable `name` as boolean the way git does, with unset variables being false.
+///
+/// Values that are neither a known boolean nor a number are considered true, as the variable was set after all.
+pub(crate) fn env_var_is_true(name: &str) -> bool {
+    match std::env::var(name) {
+        Ok(value) => match value.to_ascii_lowercase().as_str() {
+            "" | "no" | "off" | "false" => false,
+            "yes" | "on" | "true" => true,
+            number => number.parse::<i64>().map_or(true, |n| n != 0),
+        },
+        Err(_) => false,
+    }
+}
+
 impl<'a> TryFrom<&'a str> for GitConfig<'a> {
     type Error = Error<'a>;
 
@@ -1421,17 +1694,21 @@ impl<'a> From<Parser<'a>> for GitConfig<'a> {
         // Current section that we're building
         let mut prev_section_header = None;
         let mut section_events = SectionBody::new();
+        // The line the current event is on, and the one the header of the current section is on.
+        let mut line = 1;
+        let mut prev_section_line = 0;
 
         #[allow(clippy::explicit_into_iter_loop)] // it's not really an iterator (yet), needs streaming iterator support
         for event in parser.into_iter() {
             match event {
                 Event::SectionHeader(header) => {
                     if let Some(prev_header) = prev_section_header.take() {
-                        new_self.push_section_internal(prev_header, section_events);
+                        new_self.push_parsed_section(prev_header, section_events, prev_section_line);
                     } else {
                         new_self.frontmatter_events = section_events;
                     }
                     prev_section_header = Some(header);
+                    prev_section_line = line;
                     section_events = SectionBody::new();
                 }
                 e @ Event::Key(_)
@@ -1440,6 +1717,9 @@ impl<'a> From<Parser<'a>> for GitConfig<'a> {
                 | e @ Event::ValueDone(_)
                 | e @ Event::KeyValueSeparator => section_events.as_mut().push(e),
                 e @ Event::Comment(_) | e @ Event::Newline(_) | e @ Event::Whitespace(_) => {
+                    if let Event::Newline(newlines) = &e {
+                        line += newlines.bytes().filter(|b| *b == b'\n').count();
+                    }
                     section_events.as_mut().push(e);
                 }
             }
@@ -1448,7 +1728,7 @@ impl<'a> From<Parser<'a>> for GitConfig<'a> {
         // The last section doesn't get pushed since we only push if there's a
         // new section header, so we need to call push one more time.
         if let Some(header) = prev_section_header {
-            new_self.push_section_internal(header, section_events);
+            new_self.push_parsed_section(header, section_events, prev_section_line);
         } else {
             new_self.frontmatter_events = section_events;
         }
@@ -1460,7 +1740,7 @@ impl<'a> From<Parser<'a>> for GitConfig<'a> {
 impl From<GitConfig<'_>> for Vec<u8> {
     #[inline]
     fn from(c: GitConfig) -> Self {
-        c.into()
+        (&c).into()
     }
 }
 
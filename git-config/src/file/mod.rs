@@ -1,19 +1,26 @@
 //! This module provides a high level wrapper around a single `git-config` file.
 
+pub mod edit;
 mod error;
 mod git_config;
+mod origin;
+mod resolve_includes;
 mod resolved;
 mod section;
+mod snapshot;
 mod value;
 
-use std::ops::{Add, AddAssign};
+use std::ops::Add;
 
 pub use error::*;
+pub use origin::{Entry, Origin};
 pub use resolved::*;
 pub use section::*;
+pub use snapshot::Snapshot;
 pub use value::*;
 
 pub use self::git_config::*;
+pub(crate) use self::git_config::{env_var_is_true, split_key};
 
 /// Newtype to represent an index into some range. This is to differentiate
 /// between raw usizes when multiple are present.
@@ -32,9 +39,3 @@ impl Add<Size> for Index {
 /// when multiple are present.
 #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
 pub(super) struct Size(pub(super) usize);
-
-impl AddAssign<usize> for Size {
-    fn add_assign(&mut self, rhs: usize) {
-        self.0 += rhs;
-    }
-}
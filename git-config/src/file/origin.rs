@@ -0,0 +1,142 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    file::{error::GitConfigError, git_config::SectionId, GitConfig},
+    parser::{Event, Key, ParsedSectionHeader},
+    values::normalize_cow,
+};
+
+/// Where a parsed section was defined, kept by [`GitConfig`] for each section that wasn't added programmatically.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub(super) struct SectionOrigin {
+    /// The file the section was read from, if it was read from a file.
+    pub(super) path: Option<PathBuf>,
+    /// The 1-based line number of the section header.
+    pub(super) line: usize,
+}
+
+/// The location a value was defined at, similar to what `git config --show-origin` displays.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Origin<'a> {
+    /// The file the value was read from, or `None` if the configuration wasn't read from a file.
+    pub path: Option<&'a Path>,
+    /// The 1-based line number the key of the value is on.
+    pub line: usize,
+}
+
+/// A single value of a [`GitConfig`] along with the section it is in and its [`Origin`], as returned by
+/// [`GitConfig::entries()`].
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Entry<'a, 'event> {
+    /// The header of the section containing the value.
+    pub section: &'a ParsedSectionHeader<'event>,
+    /// The name of the value.
+    pub key: &'a Key<'event>,
+    /// The uninterpreted value, with multi-line values joined like [`GitConfig::get_raw_value()`] does.
+    pub value: Cow<'a, [u8]>,
+    /// Where the value was defined, or `None` if its section was added programmatically.
+    pub origin: Option<Origin<'a>>,
+}
+
+impl<'event> GitConfig<'event> {
+    /// Returns all values in the order they appear in, along with their section and origin, similar to
+    /// `git config --list --show-origin`.
+    #[must_use]
+    pub fn entries(&self) -> Vec<Entry<'_, 'event>> {
+        self.section_order
+            .iter()
+            .flat_map(|section_id| self.section_entries(*section_id))
+            .collect()
+    }
+
+    /// Returns an uninterpreted value given a section, an optional subsection and key along with the location it was
+    /// defined at, which is `None` if its section was added programmatically.
+    ///
+    /// Like [`Self::get_raw_value()`], the last value wins if the key is set multiple times.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the key is not in the requested
+    /// section and subsection, or if the section and subsection do not exist.
+    pub fn get_raw_value_with_origin<'lookup>(
+        &self,
+        section_name: &'lookup str,
+        subsection_name: Option<&'lookup str>,
+        key: &'lookup str,
+    ) -> Result<(Cow<'_, [u8]>, Option<Origin<'_>>), GitConfigError<'lookup>> {
+        let key = Key(key.into());
+        for section_id in self
+            .get_section_ids_by_name_and_subname(section_name, subsection_name)?
+            .into_iter()
+            .rev()
+        {
+            if let Some(entry) = self
+                .section_entries(section_id)
+                .into_iter()
+                .rev()
+                .find(|entry| *entry.key == key)
+            {
+                return Ok((entry.value, entry.origin));
+            }
+        }
+
+        Err(GitConfigError::KeyDoesNotExist)
+    }
+
+    fn section_entries(&self, section_id: SectionId) -> Vec<Entry<'_, 'event>> {
+        let section = self
+            .section_headers
+            .get(&section_id)
+            .expect("section_headers does not contain section id from section_order");
+        let origin = self.section_origins.get(&section_id);
+        let mut line = origin.map_or(0, |origin| origin.line);
+        let mut entries = Vec::new();
+        let mut current_key = None;
+        let mut key_line = line;
+        let mut partial_value: Option<Vec<u8>> = None;
+        for event in self
+            .sections
+            .get(&section_id)
+            .expect("sections does not contain section id from section_order")
+            .as_ref()
+        {
+            let value = match event {
+                Event::Key(key) => {
+                    current_key = Some(key);
+                    key_line = line;
+                    continue;
+                }
+                Event::Newline(newlines) => {
+                    line += newlines.bytes().filter(|b| *b == b'\n').count();
+                    continue;
+                }
+                Event::Value(value) => Cow::Borrowed(value.as_ref()),
+                Event::ValueNotDone(value) => {
+                    partial_value.get_or_insert_with(Vec::new).extend_from_slice(value);
+                    continue;
+                }
+                Event::ValueDone(value) => {
+                    let mut partial_value = partial_value.take().unwrap_or_default();
+                    partial_value.extend_from_slice(value);
+                    normalize_cow(Cow::Owned(partial_value))
+                }
+                _ => continue,
+            };
+            if let Some(key) = current_key.take() {
+                entries.push(Entry {
+                    section,
+                    key,
+                    value,
+                    origin: origin.map(|origin| Origin {
+                        path: origin.path.as_deref(),
+                        line: key_line,
+                    }),
+                });
+            }
+        }
+        entries
+    }
+}
@@ -0,0 +1,492 @@
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    file::{from_paths, GitConfig, SectionId},
+    parser::Key,
+    values,
+};
+
+/// The state needed to evaluate `includeIf "hasconfig:remote.*.url:…"` conditions, which depend on the remote urls of
+/// the entire configuration. These are only known after a first pass in which these conditions are false.
+#[derive(Default)]
+struct RemoteUrls {
+    /// The urls of all remotes, or `None` during the first pass.
+    urls: Option<Vec<Vec<u8>>>,
+    /// Set if a `hasconfig:remote.*.url:` condition was encountered, making a second pass necessary.
+    condition_seen: bool,
+}
+
+/// Resolve the includes of all `configs`, each along with the path it was loaded from, evaluating
+/// `hasconfig:remote.*.url:` conditions against the remote urls of all of them like git does.
+///
+/// On error, the index of the configuration that failed is returned along with the error.
+pub(super) fn resolve_includes(
+    configs: &mut [(&mut GitConfig<'_>, Option<&Path>)],
+    options: &from_paths::Options<'_>,
+) -> Result<(), (usize, from_paths::Error)> {
+    let mut remote_urls = RemoteUrls::default();
+    let mut resolved_configs = Vec::with_capacity(configs.len());
+    for (index, (config, config_path)) in configs.iter().enumerate() {
+        let mut config = (*config).clone();
+        resolve_includes_recursive(&mut config, *config_path, 0, options, &mut remote_urls)
+            .map_err(|err| (index, err))?;
+        resolved_configs.push(config);
+    }
+
+    if remote_urls.condition_seen {
+        remote_urls.urls = Some(resolved_configs.iter().flat_map(remote_urls_of).collect());
+        for (index, (config, config_path)) in configs.iter_mut().enumerate() {
+            resolve_includes_recursive(config, *config_path, 0, options, &mut remote_urls)
+                .map_err(|err| (index, err))?;
+        }
+    } else {
+        for ((config, _), resolved_config) in configs.iter_mut().zip(resolved_configs) {
+            **config = resolved_config;
+        }
+    }
+    Ok(())
+}
+
+fn resolve_includes_recursive(
+    target_config: &mut GitConfig<'_>,
+    target_config_path: Option<&Path>,
+    depth: u8,
+    options: &from_paths::Options<'_>,
+    remote_urls: &mut RemoteUrls,
+) -> Result<(), from_paths::Error> {
+    if depth == options.max_depth {
+        return if options.error_on_max_depth_exceeded {
+            Err(from_paths::Error::IncludeDepthExceeded {
+                max_depth: options.max_depth,
+            })
+        } else {
+            Ok(())
+        };
+    }
+
+    let mut paths_to_include = Vec::new();
+    for (id, by_remote_url) in include_section_ids(target_config, target_config_path, options, remote_urls)? {
+        let section = target_config.sections.get(&id).expect("section id is valid");
+        for path in section.values(&Key::from("path")) {
+            let path = values::Path::from(path).interpolate(options.interpolate)?;
+            let path: PathBuf = if path.is_relative() {
+                target_config_path
+                    .ok_or(from_paths::Error::MissingConfigPath)?
+                    .parent()
+                    .expect("path is a config file which naturally lives in a directory")
+                    .join(path)
+            } else {
+                path.into()
+            };
+
+            if path.is_file() {
+                paths_to_include.push((path, by_remote_url));
+            }
+        }
+    }
+
+    for (config_path, by_remote_url) in paths_to_include {
+        let mut include_config = GitConfig::open(&config_path)?;
+        resolve_includes_recursive(&mut include_config, Some(&config_path), depth + 1, options, remote_urls)?;
+        if by_remote_url && !remote_urls_of(&include_config).is_empty() {
+            return Err(from_paths::Error::RemoteUrlInHasconfigInclude { path: config_path });
+        }
+        target_config.append(include_config);
+    }
+    Ok(())
+}
+
+/// Return the ids of all `include` sections as well as the `includeIf` sections whose condition is true, in the order
+/// in which they appear in the configuration, along with whether they were included by a `hasconfig:remote.*.url:`
+/// condition.
+fn include_section_ids(
+    config: &GitConfig<'_>,
+    config_path: Option<&Path>,
+    options: &from_paths::Options<'_>,
+    remote_urls: &mut RemoteUrls,
+) -> Result<Vec<(SectionId, bool)>, from_paths::Error> {
+    let mut ids = Vec::new();
+    for id in &config.section_order {
+        let header = config.section_headers.get(id).expect("section id is valid");
+        if header.name.eq_ignore_ascii_case("include") {
+            if header.subsection_name.is_none() {
+                ids.push((*id, false));
+            }
+        } else if header.name.eq_ignore_ascii_case("includeIf") {
+            if let Some(condition) = &header.subsection_name {
+                if let Some(pattern) = condition.strip_prefix("hasconfig:remote.*.url:") {
+                    remote_urls.condition_seen = true;
+                    if remote_url_matches(pattern, remote_urls) {
+                        ids.push((*id, true));
+                    }
+                } else if condition_is_true(condition, config_path, options)? {
+                    ids.push((*id, false));
+                }
+            }
+        }
+    }
+    Ok(ids)
+}
+
+fn condition_is_true(
+    condition: &str,
+    config_path: Option<&Path>,
+    options: &from_paths::Options<'_>,
+) -> Result<bool, from_paths::Error> {
+    Ok(if let Some(pattern) = condition.strip_prefix("gitdir:") {
+        gitdir_matches(pattern, config_path, options, false)?
+    } else if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+        gitdir_matches(pattern, config_path, options, true)?
+    } else if let Some(pattern) = condition.strip_prefix("onbranch:") {
+        onbranch_matches(pattern, options)
+    } else {
+        // Like git, we ignore conditions we don't know.
+        false
+    })
+}
+
+fn remote_url_matches(pattern: &str, remote_urls: &RemoteUrls) -> bool {
+    remote_urls.urls.as_ref().map_or(false, |urls| {
+        urls.iter().any(|url| wildmatch(pattern.as_bytes(), url, false))
+    })
+}
+
+/// Return the values of all `remote.<name>.url` keys in `config`.
+fn remote_urls_of(config: &GitConfig<'_>) -> Vec<Vec<u8>> {
+    config
+        .section_order
+        .iter()
+        .flat_map(|id| {
+            let header = config.section_headers.get(id).expect("section id is valid");
+            if header.name.eq_ignore_ascii_case("remote") && header.subsection_name.is_some() {
+                config
+                    .sections
+                    .get(id)
+                    .expect("section id is valid")
+                    .values(&Key::from("url"))
+                    .into_iter()
+                    .map(Cow::into_owned)
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        })
+        .collect()
+}
+
+fn gitdir_matches(
+    pattern: &str,
+    config_path: Option<&Path>,
+    options: &from_paths::Options<'_>,
+    ignore_case: bool,
+) -> Result<bool, from_paths::Error> {
+    let git_dir = match options.git_dir {
+        Some(git_dir) => git_dir,
+        None => return Ok(false),
+    };
+
+    let mut literal_prefix_len = 0;
+    let mut pattern: Vec<u8> = if let Some(relative_pattern) = pattern.strip_prefix("./") {
+        let config_dir = config_path
+            .ok_or(from_paths::Error::MissingConfigPath)?
+            .parent()
+            .expect("path is a config file which naturally lives in a directory");
+        let config_dir = config_dir.canonicalize().unwrap_or_else(|_| config_dir.into());
+        let mut config_dir = path_to_bytes(&config_dir);
+        if !config_dir.ends_with(b"/") {
+            config_dir.push(b'/');
+        }
+        literal_prefix_len = config_dir.len();
+        config_dir.extend_from_slice(relative_pattern.as_bytes());
+        config_dir
+    } else if pattern.starts_with('~') || pattern.starts_with("%(prefix)/") {
+        let path = values::Path::from(Cow::Borrowed(pattern.as_bytes())).interpolate(options.interpolate)?;
+        path_to_bytes(&path)
+    } else if pattern.starts_with('/') || Path::new(pattern).is_absolute() {
+        pattern.as_bytes().to_vec()
+    } else {
+        let mut buf = b"**/".to_vec();
+        buf.extend_from_slice(pattern.as_bytes());
+        buf
+    };
+    if pattern.ends_with(b"/") {
+        pattern.extend_from_slice(b"**");
+    }
+
+    let git_dir_matches = |git_dir: &Path| {
+        let git_dir = path_to_bytes(git_dir);
+        let (prefix, pattern) = pattern.split_at(literal_prefix_len);
+        git_dir.len() >= prefix.len()
+            && if ignore_case {
+                git_dir[..prefix.len()].eq_ignore_ascii_case(prefix)
+            } else {
+                &git_dir[..prefix.len()] == prefix
+            }
+            && wildmatch(pattern, &git_dir[prefix.len()..], ignore_case)
+    };
+    Ok(git_dir_matches(git_dir)
+        || git_dir
+            .canonicalize()
+            .map(|git_dir| git_dir_matches(&git_dir))
+            .unwrap_or(false))
+}
+
+fn onbranch_matches(pattern: &str, options: &from_paths::Options<'_>) -> bool {
+    let branch_name = match options.branch_name.and_then(|name| name.strip_prefix(b"refs/heads/")) {
+        Some(name) => name,
+        None => return false,
+    };
+    let mut pattern = pattern.as_bytes().to_vec();
+    if pattern.ends_with(b"/") {
+        pattern.extend_from_slice(b"**");
+    }
+    wildmatch(&pattern, branch_name, false)
+}
+
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    git_features::path::convert::to_unix_separators(git_features::path::into_bytes_or_panic_on_windows(path))
+        .into_owned()
+}
+
+/// Match `text` against the glob `pattern` the way git's `wildmatch()` does with `WM_PATHNAME`, that is `*`, `?` and
+/// bracket expressions never match a slash, while `**` surrounded by slashes matches any amount of directories.
+fn wildmatch(pattern: &[u8], text: &[u8], ignore_case: bool) -> bool {
+    matches!(dowild(pattern, text, ignore_case), Match::Yes)
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Match {
+    Yes,
+    No,
+    AbortAll,
+    AbortToStarStar,
+}
+
+fn dowild(p: &[u8], text: &[u8], ignore_case: bool) -> Match {
+    let fold = |b: u8| if ignore_case { b.to_ascii_lowercase() } else { b };
+    let (mut pi, mut ti) = (0, 0);
+    while pi < p.len() {
+        let mut p_ch = p[pi];
+        let t_ch = match text.get(ti) {
+            Some(t) => fold(*t),
+            None if p_ch != b'*' => return Match::AbortAll,
+            None => 0,
+        };
+        match p_ch {
+            b'\\' => {
+                pi += 1;
+                match p.get(pi) {
+                    Some(escaped) if fold(*escaped) == t_ch => {}
+                    _ => return Match::No,
+                }
+            }
+            b'?' => {
+                if t_ch == b'/' {
+                    return Match::No;
+                }
+            }
+            b'*' => {
+                pi += 1;
+                let match_slash = if p.get(pi) == Some(&b'*') {
+                    let stars_start = pi - 1;
+                    while p.get(pi) == Some(&b'*') {
+                        pi += 1;
+                    }
+                    let starts_component = stars_start == 0 || p[stars_start - 1] == b'/';
+                    let ends_component =
+                        pi == p.len() || p[pi] == b'/' || (p[pi] == b'\\' && p.get(pi + 1) == Some(&b'/'));
+                    if starts_component && ends_component {
+                        if p.get(pi) == Some(&b'/') && dowild(&p[pi + 1..], &text[ti..], ignore_case) == Match::Yes {
+                            return Match::Yes;
+                        }
+                        true
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                };
+                if pi == p.len() {
+                    return if !match_slash && text[ti..].contains(&b'/') {
+                        Match::No
+                    } else {
+                        Match::Yes
+                    };
+                } else if !match_slash && p[pi] == b'/' {
+                    match text[ti..].iter().position(|b| *b == b'/') {
+                        Some(pos) => {
+                            ti += pos;
+                            continue;
+                        }
+                        None => return Match::No,
+                    }
+                }
+                while ti < text.len() {
+                    match dowild(&p[pi..], &text[ti..], ignore_case) {
+                        Match::No => {
+                            if !match_slash && text[ti] == b'/' {
+                                return Match::AbortToStarStar;
+                            }
+                        }
+                        Match::AbortToStarStar if match_slash => {}
+                        other => return other,
+                    }
+                    ti += 1;
+                }
+                return Match::AbortAll;
+            }
+            b'[' => {
+                pi += 1;
+                let negated = matches!(p.get(pi), Some(b'!') | Some(b'^'));
+                if negated {
+                    pi += 1;
+                }
+                let mut matched = false;
+                let mut prev_ch = 0u8;
+                let mut first = true;
+                loop {
+                    p_ch = match p.get(pi) {
+                        Some(ch) => *ch,
+                        None => return Match::AbortAll,
+                    };
+                    if p_ch == b']' && !first {
+                        break;
+                    }
+                    first = false;
+                    if p_ch == b'\\' {
+                        pi += 1;
+                        p_ch = match p.get(pi) {
+                            Some(ch) => *ch,
+                            None => return Match::AbortAll,
+                        };
+                        if fold(p_ch) == t_ch {
+                            matched = true;
+                        }
+                    } else if p_ch == b'-' && prev_ch != 0 && p.get(pi + 1).map_or(false, |ch| *ch != b']') {
+                        pi += 1;
+                        p_ch = p[pi];
+                        if p_ch == b'\\' {
+                            pi += 1;
+                            p_ch = match p.get(pi) {
+                                Some(ch) => *ch,
+                                None => return Match::AbortAll,
+                            };
+                        }
+                        if prev_ch <= t_ch && t_ch <= p_ch {
+                            matched = true;
+                        } else if ignore_case && t_ch.is_ascii_lowercase() {
+                            let t_upper = t_ch.to_ascii_uppercase();
+                            if prev_ch <= t_upper && t_upper <= p_ch {
+                                matched = true;
+                            }
+                        }
+                        p_ch = 0;
+                    } else if p_ch == b'[' && p.get(pi + 1) == Some(&b':') {
+                        let class_start = pi + 2;
+                        let class_len = match p[class_start..].iter().position(|b| *b == b']') {
+                            Some(len) => len,
+                            None => return Match::AbortAll,
+                        };
+                        if class_len == 0 || p[class_start + class_len - 1] != b':' {
+                            // not a character class after all, treat `[` literally
+                            p_ch = b'[';
+                            if p_ch == t_ch {
+                                matched = true;
+                            }
+                        } else {
+                            let class = &p[class_start..class_start + class_len - 1];
+                            let t = text[ti];
+                            let is_match = match class {
+                                b"alnum" => t.is_ascii_alphanumeric(),
+                                b"alpha" => t.is_ascii_alphabetic(),
+                                b"blank" => t == b' ' || t == b'\t',
+                                b"cntrl" => t.is_ascii_control(),
+                                b"digit" => t.is_ascii_digit(),
+                                b"graph" => t.is_ascii_graphic(),
+                                b"lower" => t.is_ascii_lowercase() || (ignore_case && t.is_ascii_uppercase()),
+                                b"print" => t.is_ascii_graphic() || t == b' ',
+                                b"punct" => t.is_ascii_punctuation(),
+                                b"space" => t.is_ascii_whitespace() || t == b'\x0b',
+                                b"upper" => t.is_ascii_uppercase() || (ignore_case && t.is_ascii_lowercase()),
+                                b"xdigit" => t.is_ascii_hexdigit(),
+                                _ => return Match::AbortAll,
+                            };
+                            if is_match {
+                                matched = true;
+                            }
+                            pi = class_start + class_len;
+                            p_ch = 0;
+                        }
+                    } else if fold(p_ch) == t_ch {
+                        matched = true;
+                    }
+                    prev_ch = p_ch;
+                    pi += 1;
+                }
+                if matched == negated || t_ch == b'/' {
+                    return Match::No;
+                }
+            }
+            _ => {
+                if fold(p_ch) != t_ch {
+                    return Match::No;
+                }
+            }
+        }
+        pi += 1;
+        ti += 1;
+    }
+    if ti == text.len() {
+        Match::Yes
+    } else {
+        Match::No
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::wildmatch;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        wildmatch(pattern.as_bytes(), text.as_bytes(), false)
+    }
+
+    #[test]
+    fn literals_and_single_stars() {
+        assert!(is_match("foo", "foo"));
+        assert!(!is_match("foo", "foobar"));
+        assert!(is_match("foo*", "foobar"));
+        assert!(!is_match("foo*", "foo/bar"), "a single star doesn't cross directories");
+        assert!(is_match("foo/*/baz", "foo/bar/baz"));
+        assert!(is_match("f?o", "fao"));
+        assert!(!is_match("f?o", "f/o"));
+    }
+
+    #[test]
+    fn double_stars_match_any_amount_of_directories() {
+        assert!(is_match("**/foo", "foo"));
+        assert!(is_match("**/foo", "a/b/foo"));
+        assert!(is_match("/a/**", "/a/b/c/.git"));
+        assert!(is_match("/a/**/.git", "/a/.git"));
+        assert!(is_match("/a/**/.git", "/a/b/c/.git"));
+        assert!(!is_match("/a/**", "/b/a/c"));
+        assert!(is_match("**/work/**", "/home/user/work/project/.git"));
+    }
+
+    #[test]
+    fn bracket_expressions() {
+        assert!(is_match("v[0-9]", "v5"));
+        assert!(!is_match("v[!0-9]", "v5"));
+        assert!(is_match("v[[:alpha:]]", "vx"));
+        assert!(!is_match("a[/]b", "a/b"));
+    }
+
+    #[test]
+    fn case_folding() {
+        assert!(!is_match("/Work/**", "/work/x"));
+        assert!(wildmatch(b"/Work/**", b"/work/x", true));
+    }
+}
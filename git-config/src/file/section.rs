@@ -7,7 +7,7 @@ use std::{
 };
 
 use crate::{
-    file::{error::GitConfigError, Index},
+    file::{error::GitConfigError, Index, Size},
     parser::{Event, Key},
     values::{normalize_cow, normalize_vec},
 };
@@ -71,25 +71,38 @@ impl<'borrow, 'event> MutableSection<'borrow, 'event> {
     /// Sets the last key value pair if it exists, or adds the new value.
     /// Returns the previous value if it replaced a value, or None if it adds
     /// the value.
+    ///
+    /// Whitespace and comments around the value are kept as they are.
     pub fn set(&mut self, key: Key<'event>, value: Cow<'event, [u8]>) -> Option<Cow<'event, [u8]>> {
         let range = self.get_value_range_by_key(&key);
         if range.is_empty() {
             self.push(key, value);
             return None;
         }
-        let range_start = range.start;
-        let ret = self.remove_internal(range);
-        self.section.0.insert(range_start, Event::Value(value));
+        let key_index = self.key_index_before(range.start);
+        let ret = self.remove_internal(range.clone());
+        replace_value(&mut self.section.0, key_index..range.start, value);
         Some(ret)
     }
 
     /// Removes the latest value by key and returns it, if it exists.
+    ///
+    /// The key is removed along with its value, whereas indentation and comments are kept.
     pub fn remove(&mut self, key: &Key<'event>) -> Option<Cow<'event, [u8]>> {
         let range = self.get_value_range_by_key(key);
         if range.is_empty() {
             return None;
         }
-        Some(self.remove_internal(range))
+        let key_index = self.key_index_before(range.start);
+        Some(self.remove_internal(key_index..range.end))
+    }
+
+    /// Returns the index of the key that owns the value starting at `value_start`.
+    fn key_index_before(&self, value_start: usize) -> usize {
+        self.section.0[..value_start]
+            .iter()
+            .rposition(|e| matches!(e, Event::Key(_)))
+            .expect("a value is always preceded by its key")
     }
 
     /// Performs the removal, assuming the range is valid. This is used to
@@ -165,7 +178,7 @@ impl<'borrow, 'event> MutableSection<'borrow, 'event> {
         // section_id is guaranteed to exist in self.sections, else we have a
         // violated invariant.
 
-        for event in &self.section.0[start.0..=end.0] {
+        for event in &self.section.0[start.0..end.0] {
             match event {
                 Event::Key(event_key) if event_key == key => found_key = true,
                 Event::Value(v) if found_key => {
@@ -193,7 +206,16 @@ impl<'borrow, 'event> MutableSection<'borrow, 'event> {
 
     #[inline]
     pub(super) fn delete(&mut self, start: Index, end: Index) {
-        self.section.0.drain(start.0..=end.0);
+        self.section.0.drain(start.0..end.0);
+    }
+
+    /// Replace the value of the entry whose `size` events start with its key at `index`, and return the new size of the entry.
+    pub(super) fn replace_value(&mut self, index: Index, size: Size, value: Vec<u8>) -> Size {
+        Size(replace_value(
+            &mut self.section.0,
+            index.0..index.0 + size.0,
+            Cow::Owned(value),
+        ))
     }
 
     pub(super) fn set_internal(&mut self, index: Index, key: Key<'event>, value: Vec<u8>) {
@@ -203,6 +225,33 @@ impl<'borrow, 'event> MutableSection<'borrow, 'event> {
     }
 }
 
+/// Replace the value events of the entry spanning `entry` in `events` with `value` and return the new amount of events
+/// of the entry.
+///
+/// The key as well as whitespace and the separator preceding the value are kept, and a separator is added if the value
+/// was implicit, like in `[core] bare`.
+pub(super) fn replace_value<'event>(
+    events: &mut Vec<Event<'event>>,
+    entry: Range<usize>,
+    value: Cow<'event, [u8]>,
+) -> usize {
+    let value_start = events[entry.clone()]
+        .iter()
+        .position(|e| matches!(e, Event::Value(_) | Event::ValueNotDone(_) | Event::ValueDone(_)))
+        .map_or(entry.end, |pos| entry.start + pos);
+    events.drain(value_start..entry.end);
+    let mut value_index = value_start;
+    if !events[entry.start..value_start]
+        .iter()
+        .any(|e| matches!(e, Event::KeyValueSeparator))
+    {
+        events.insert(value_index, Event::KeyValueSeparator);
+        value_index += 1;
+    }
+    events.insert(value_index, Event::Value(value));
+    value_index + 1 - entry.start
+}
+
 impl<'event> Deref for MutableSection<'_, 'event> {
     type Target = SectionBody<'event>;
 
@@ -218,7 +267,7 @@ impl<'event> Deref for MutableSection<'_, 'event> {
 pub struct SectionBody<'event>(Vec<Event<'event>>);
 
 impl<'event> SectionBody<'event> {
-    pub(super) fn as_ref(&self) -> &[Event<'_>] {
+    pub(super) fn as_ref(&self) -> &[Event<'event>] {
         &self.0
     }
 
@@ -368,7 +417,7 @@ impl<'event> SectionBody<'event> {
 
     /// Returns the the range containing the value events for the section.
     /// If the value is not found, then this returns an empty range.
-    fn get_value_range_by_key(&self, key: &Key<'event>) -> Range<usize> {
+    pub(super) fn get_value_range_by_key(&self, key: &Key<'event>) -> Range<usize> {
         let mut values_start = 0;
         // value end needs to be offset by one so that the last value's index
         // is included in the range
@@ -0,0 +1,131 @@
+use std::{borrow::Cow, collections::HashMap, convert::TryFrom, sync::Arc};
+
+use crate::{
+    file::{split_key, Entry, GitConfig},
+    values,
+};
+
+/// Section name and value name in lower case along with the subsection name, which is lower case only if it was
+/// written in the legacy `[section.subsection]` syntax.
+type ValueKey = (String, Option<String>, String);
+
+/// An immutable snapshot of a resolved configuration which is cheap to clone and can be shared across threads.
+///
+/// It only retains the values of the configuration it was created from, in the order of their precedence, without any
+/// comments, whitespace or information about their origin. This makes it suitable for reading configuration many times
+/// without re-parsing it or synchronizing access to it.
+///
+/// # Examples
+///
+/// ```
+/// # use git_config::file::{GitConfig, Snapshot};
+/// # use std::convert::TryFrom;
+/// let config = GitConfig::try_from("[core]\n bare = true\n[remote \"origin\"]\n url = a\n url = b").unwrap();
+/// let snapshot = Snapshot::from(&config);
+/// let handle = std::thread::spawn({
+///     let snapshot = snapshot.clone();
+///     move || snapshot.boolean("core.bare")
+/// });
+/// assert_eq!(handle.join().unwrap(), Some(Ok(true)));
+/// assert_eq!(snapshot.string("remote.origin.url").as_deref(), Some(&b"b"[..]));
+/// assert_eq!(snapshot.strings("remote.origin.url").unwrap().len(), 2);
+/// ```
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Snapshot(Arc<HashMap<ValueKey, Vec<Vec<u8>>>>);
+
+impl Snapshot {
+    /// Create a snapshot from `entries` in order of increasing precedence, so later values win over earlier ones.
+    pub(crate) fn from_entries<'a, 'event: 'a>(entries: impl IntoIterator<Item = Entry<'a, 'event>>) -> Self {
+        let mut values: HashMap<ValueKey, Vec<Vec<u8>>> = HashMap::new();
+        for entry in entries {
+            let subsection_name = entry.section.subsection_name.as_ref().map(|name| {
+                if entry.section.separator.as_deref() == Some(".") {
+                    name.to_ascii_lowercase()
+                } else {
+                    name.to_string()
+                }
+            });
+            values
+                .entry((
+                    entry.section.name.to_ascii_lowercase(),
+                    subsection_name,
+                    entry.key.to_ascii_lowercase(),
+                ))
+                .or_default()
+                .push(entry.value.into_owned());
+        }
+        Snapshot(Arc::new(values))
+    }
+
+    /// Returns the uninterpreted value given a section, an optional subsection and key, or `None` if it doesn't exist.
+    /// The last value wins if there are multiple.
+    #[must_use]
+    pub fn raw_value(&self, section_name: &str, subsection_name: Option<&str>, key: &str) -> Option<&[u8]> {
+        self.raw_multi_value(section_name, subsection_name, key)?
+            .last()
+            .map(Vec::as_slice)
+    }
+
+    /// Returns all uninterpreted values given a section, an optional subsection and key in order of their precedence,
+    /// or `None` if the key doesn't exist.
+    #[must_use]
+    pub fn raw_multi_value(&self, section_name: &str, subsection_name: Option<&str>, key: &str) -> Option<&[Vec<u8>]> {
+        self.0
+            .get(&(
+                section_name.to_ascii_lowercase(),
+                subsection_name.map(ToOwned::to_owned),
+                key.to_ascii_lowercase(),
+            ))
+            .map(Vec::as_slice)
+    }
+
+    /// Returns the value of `key` like `core.bare` interpreted as boolean, or `None` if the key doesn't exist.
+    /// The last value wins if there are multiple.
+    #[must_use]
+    pub fn boolean(&self, key: &str) -> Option<Result<bool, values::BooleanError>> {
+        self.string(key)
+            .map(|value| values::Boolean::try_from(value).map(Into::into))
+    }
+
+    /// Returns the value of `key` like `core.packedGitLimit` interpreted as integer with its `k`, `m` or `g` suffix
+    /// applied, or `None` if the key doesn't exist. The last value wins if there are multiple.
+    #[must_use]
+    pub fn integer(&self, key: &str) -> Option<Result<i64, values::IntegerError>> {
+        self.string(key).map(|value| {
+            values::Integer::try_from(value)
+                .and_then(|integer| integer.to_decimal().ok_or(values::IntegerError::Overflow))
+        })
+    }
+
+    /// Returns the value of `key` like `user.name`, or `None` if the key doesn't exist.
+    /// The last value wins if there are multiple.
+    #[must_use]
+    pub fn string(&self, key: &str) -> Option<Cow<'_, [u8]>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.raw_value(section_name, subsection_name, key).map(Cow::Borrowed)
+    }
+
+    /// Returns all values of the multi-valued `key` like `remote.origin.fetch` in order of their precedence, or
+    /// `None` if the key doesn't exist.
+    #[must_use]
+    pub fn strings(&self, key: &str) -> Option<Vec<Cow<'_, [u8]>>> {
+        let (section_name, subsection_name, key) = split_key(key)?;
+        self.raw_multi_value(section_name, subsection_name, key)
+            .map(|values| values.iter().map(|value| Cow::Borrowed(value.as_slice())).collect())
+    }
+
+    /// Returns the value of `key` like `core.excludesFile` as path, or `None` if the key doesn't exist.
+    /// The last value wins if there are multiple.
+    ///
+    /// Use [`values::Path::interpolate()`] to expand `~/`, `~user/` and `%(prefix)/`.
+    #[must_use]
+    pub fn path(&self, key: &str) -> Option<values::Path<'_>> {
+        self.string(key).map(values::Path::from)
+    }
+}
+
+impl From<&GitConfig<'_>> for Snapshot {
+    fn from(config: &GitConfig<'_>) -> Self {
+        Snapshot::from_entries(config.entries())
+    }
+}
@@ -8,7 +8,7 @@ use crate::{
     file::{
         error::GitConfigError,
         git_config::SectionId,
-        section::{MutableSection, SectionBody},
+        section::{replace_value, MutableSection, SectionBody},
         Index, Size,
     },
     parser::{Event, Key},
@@ -69,14 +69,15 @@ impl<'borrow, 'lookup, 'event> MutableValue<'borrow, 'lookup, 'event> {
 
     /// Update the value to the provided one. This modifies the value such that
     /// the Value event(s) are replaced with a single new event containing the
-    /// new value.
+    /// new value, while whitespace around the key and separator is kept.
     pub fn set_bytes(&mut self, input: Vec<u8>) {
         if self.size.0 > 0 {
-            self.section.delete(self.index, self.index + self.size);
+            self.size = self.section.replace_value(self.index, self.size, input);
+        } else {
+            self.size = Size(3);
+            self.section
+                .set_internal(self.index, Key(Cow::Owned(self.key.to_string())), input);
         }
-        self.size = Size(3);
-        self.section
-            .set_internal(self.index, Key(Cow::Owned(self.key.to_string())), input);
     }
 
     /// Removes the value. Does nothing when called multiple times in
@@ -348,7 +349,11 @@ impl<'borrow, 'lookup, 'event> MutableMultiValue<'borrow, 'lookup, 'event> {
         input: Cow<'a, [u8]>,
     ) {
         let (offset, size) = MutableMultiValue::get_index_and_size(offsets, section_id, offset_index);
-        section.as_mut().drain(offset..offset + size);
+        if size > 0 {
+            let size = replace_value(section.as_mut(), offset..offset + size, input);
+            MutableMultiValue::set_offset(offsets, section_id, offset_index, size);
+            return;
+        }
 
         MutableMultiValue::set_offset(offsets, section_id, offset_index, 3);
         section.as_mut().insert(offset, Event::Value(input));
@@ -1,34 +1,98 @@
-#![allow(unused)]
-#![allow(clippy::result_unit_err)]
-
 use std::{
     borrow::Cow,
     convert::TryFrom,
     path::{Path, PathBuf},
 };
 
-use crate::file::{from_paths, GitConfig, GitConfigError};
+use quick_error::quick_error;
+
+use crate::{
+    file::{
+        edit as file_edit, env_var_is_true, from_env, from_paths, split_key, Entry, GitConfig, GitConfigError, Origin,
+        Snapshot,
+    },
+    values::path::interpolate,
+};
 
+/// The level a configuration was loaded from, listed from lowest to highest precedence.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum ConfigSource {
     /// System-wide configuration path. This is defined as
     /// `$(prefix)/etc/gitconfig`.
     System,
-    /// Also known as the user configuration path. This is usually `~/.gitconfig`.
-    Global,
     /// Second user-specific configuration path; if `$XDG_CONFIG_HOME` is not
     /// set or empty, `$HOME/.config/git/config` will be used. Any single-valued
     /// variable set in this file will be overridden by whatever is in the
     /// Global configuration file.
     User,
-
+    /// Also known as the user configuration path. This is usually `~/.gitconfig`.
+    Global,
+    /// The configuration of the repository, usually `.git/config`.
     Repository,
-    // Worktree(&'a Path),
+    /// The configuration of the current worktree, `.git/config.worktree`, which is only read if
+    /// `extensions.worktreeConfig` is enabled.
+    Worktree,
     /// Config values parsed from the environment.
     Env,
+    /// Config values passed on the command-line, like with `git -c key=value`.
     Cli,
 }
 
+quick_error! {
+    #[derive(Debug)]
+    /// The error returned by [`ConfigBuilder::try_build()`].
+    #[allow(missing_docs)]
+    pub enum Error {
+        Load(kind: ConfigSource, err: from_paths::Error) {
+            display("Could not load the {:?} configuration", kind)
+            source(err)
+        }
+        Env(err: from_env::Error) {
+            display("Could not load configuration from the environment")
+            source(err)
+            from()
+        }
+        InvalidCliValue(value: String) {
+            display("Command-line configuration '{}' is not of the form 'section.name=value'", value)
+        }
+    }
+}
+
+///
+pub mod edit {
+    use std::path::PathBuf;
+
+    use quick_error::quick_error;
+
+    use super::ConfigSource;
+    use crate::{file, parser};
+
+    quick_error! {
+        #[derive(Debug)]
+        /// The error returned by the editing methods of [`Config`][super::Config].
+        #[allow(missing_docs)]
+        pub enum Error {
+            Edit(err: file::edit::Error) {
+                display("The edit could not be applied")
+                source(err)
+                from()
+            }
+            NoFile(source: ConfigSource) {
+                display("The {:?} configuration is not backed by a file that could be edited", source)
+            }
+            Open(path: PathBuf, err: parser::ParserOrIoError<'static>) {
+                display("Could not read the configuration file at '{}' to edit it", path.display())
+                source(err)
+            }
+            Write(err: file::write::Error) {
+                display("Could not write the edited configuration file")
+                source(err)
+                from()
+            }
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Eq, Hash, Default)]
 pub struct ConfigBuilder {
     no_system: bool,
@@ -36,6 +100,11 @@ pub struct ConfigBuilder {
     override_system_config: Option<PathBuf>,
     override_global_config: Option<PathBuf>,
     override_repo_config: Option<PathBuf>,
+    override_worktree_config: Option<PathBuf>,
+    git_dir: Option<PathBuf>,
+    git_install_dir: Option<PathBuf>,
+    branch_name: Option<Vec<u8>>,
+    cli_values: Vec<String>,
 }
 
 impl ConfigBuilder {
@@ -59,7 +128,8 @@ impl ConfigBuilder {
     }
 
     /// Whether or not to respect `GIT_CONFIG_COUNT`, `GIT_CONFIG_KEY_<n>`, and
-    /// `GIT_CONFIG_VALUE_<n>` environment variables. By default, this is true.
+    /// `GIT_CONFIG_VALUE_<n>` environment variables, as well as `GIT_CONFIG_NOSYSTEM`,
+    /// `GIT_CONFIG_SYSTEM` and `GIT_CONFIG_GLOBAL`. By default, this is true.
     #[must_use]
     pub fn load_environment_entries(&mut self, load_conf: bool) -> &mut Self {
         self.load_env_conf = load_conf;
@@ -76,6 +146,8 @@ impl ConfigBuilder {
 
     /// Override the global (user) configuration file location. Providing
     /// [`None`] or not calling this method will use the default location.
+    ///
+    /// Like `GIT_CONFIG_GLOBAL`, this also prevents the [`ConfigSource::User`] configuration from being read.
     #[must_use]
     pub fn global_config_path(&mut self, path: Option<PathBuf>) -> &mut Self {
         self.override_global_config = path;
@@ -91,38 +163,49 @@ impl ConfigBuilder {
         self
     }
 
-    /// Builds a config, ignoring any failed configuration files.
+    /// Sets where to read the worktree-specific configuration file. If none is provided, then the builder will
+    /// read `config.worktree` in the git directory if `extensions.worktreeConfig` is enabled.
     #[must_use]
-    pub fn build(&self) -> Config {
-        let system_conf = if self.no_system { None } else { todo!() };
+    pub fn worktree_config_path(&mut self, path: Option<PathBuf>) -> &mut Self {
+        self.override_worktree_config = path;
+        self
+    }
 
-        let global_conf = {
-            let path = self
-                .override_global_config
-                .as_ref()
-                .map_or_else(|| Path::new(".git/config"), PathBuf::as_path);
+    /// Sets the git directory of the repository, `.git` by default, which is where the repository and worktree
+    /// configuration files are found and which is matched by `includeIf "gitdir:…"` conditions.
+    #[must_use]
+    pub fn git_dir(&mut self, git_dir: Option<PathBuf>) -> &mut Self {
+        self.git_dir = git_dir;
+        self
+    }
 
-            GitConfig::open(path).ok()
-        };
+    /// Sets the location where git or gitoxide is installed, which is needed to expand `%(prefix)/` in include paths.
+    #[must_use]
+    pub fn git_install_dir(&mut self, git_install_dir: Option<PathBuf>) -> &mut Self {
+        self.git_install_dir = git_install_dir;
+        self
+    }
 
-        let env_conf = if self.load_env_conf {
-            // TODO: when bringing up the system, make sure options can be passed. Have to review this entire module first though.
-            GitConfig::from_env(&from_paths::Options::default()).ok().flatten()
-        } else {
-            None
-        };
+    /// Sets the full name of the checked out branch, like `refs/heads/main`, to evaluate
+    /// `includeIf "onbranch:…"` conditions.
+    #[must_use]
+    pub fn branch_name(&mut self, branch_name: Option<Vec<u8>>) -> &mut Self {
+        self.branch_name = branch_name;
+        self
+    }
 
-        // let user_conf = if self.
+    /// Adds `values` of the form `section.name=value` as they would be passed to `git -c`, which take precedence
+    /// over all other configuration. A value without `=` like `section.name` is set to `true`.
+    #[must_use]
+    pub fn cli_values(&mut self, values: impl IntoIterator<Item = impl Into<String>>) -> &mut Self {
+        self.cli_values.extend(values.into_iter().map(Into::into));
+        self
+    }
 
-        Config {
-            system_conf,
-            global_conf,
-            user_conf: todo!(),
-            repository_conf: todo!(),
-            worktree_conf: todo!(),
-            env_conf,
-            cli_conf: todo!(),
-        }
+    /// Builds a config, ignoring any failed configuration files.
+    #[must_use]
+    pub fn build(&self) -> Config<'static> {
+        self.build_inner(false).unwrap_or_else(|(config, _)| config)
     }
 
     /// Attempts to build a config, returning error if the environment variable
@@ -130,12 +213,167 @@ impl ConfigBuilder {
     /// does not exist. This is only recommended when you have a very controlled
     /// system state. Otherwise, this will likely fail more often than you'd
     /// like.
-    pub fn try_build(&self) -> Result<Config, ()> {
-        todo!()
+    pub fn try_build(&self) -> Result<Config<'static>, Error> {
+        self.build_inner(true).map_err(|(_, err)| err)
+    }
+
+    /// Build the config, returning the first error along with what could be loaded regardless.
+    /// If `fail_fast` is true, stop loading once an error is encountered.
+    fn build_inner(&self, fail_fast: bool) -> Result<Config<'static>, (Config<'static>, Error)> {
+        let git_dir = self.git_dir.as_deref().unwrap_or_else(|| Path::new(".git"));
+        let home = interpolate::home_dir();
+        let options = from_paths::Options {
+            git_dir: Some(git_dir),
+            branch_name: self.branch_name.as_deref(),
+            interpolate: interpolate::Context {
+                git_install_dir: self.git_install_dir.as_deref(),
+                home_dir: home.as_deref(),
+                home_for_user: Some(interpolate::home_for_user),
+            },
+            ..Default::default()
+        };
+        let mut config = Config::default();
+        let mut first_error = None;
+        let mut load = |source: ConfigSource,
+                        path: Option<PathBuf>,
+                        required: bool|
+         -> Option<(ConfigSource, GitConfig<'static>, PathBuf)> {
+            let path = path?;
+            // Remember the path even if the file doesn't exist yet, so it can be created when editing this level.
+            config.paths.push((source, path.clone()));
+            if (first_error.is_some() && fail_fast) || (!required && !path.is_file()) {
+                return None;
+            }
+            match GitConfig::open(&path) {
+                Ok(config) => Some((source, config, path)),
+                Err(err) => {
+                    first_error.get_or_insert(Error::Load(source, err.into()));
+                    None
+                }
+            }
+        };
+
+        let mut files = Vec::new();
+        let env_var = |name: &str| self.load_env_conf.then(|| std::env::var_os(name)).flatten();
+        let no_system = self.no_system || (self.load_env_conf && env_var_is_true("GIT_CONFIG_NOSYSTEM"));
+        if !no_system {
+            files.extend(match &self.override_system_config {
+                Some(path) => load(ConfigSource::System, Some(path.clone()), true),
+                None => load(
+                    ConfigSource::System,
+                    // In git the fallback is set to a build time macro which defaults to /etc/gitconfig
+                    Some(env_var("GIT_CONFIG_SYSTEM").map_or_else(|| "/etc/gitconfig".into(), PathBuf::from)),
+                    false,
+                ),
+            });
+        }
+
+        match self
+            .override_global_config
+            .clone()
+            .or_else(|| env_var("GIT_CONFIG_GLOBAL").map(PathBuf::from))
+        {
+            Some(path) => files.extend(load(ConfigSource::Global, Some(path), true)),
+            None => {
+                let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+                    .filter(|v| !v.is_empty())
+                    .map(PathBuf::from)
+                    .or_else(|| home.as_ref().map(|home| home.join(".config")));
+                files.extend(load(
+                    ConfigSource::User,
+                    xdg_config_home.map(|p| p.join("git/config")),
+                    false,
+                ));
+                files.extend(load(
+                    ConfigSource::Global,
+                    home.as_ref().map(|p| p.join(".gitconfig")),
+                    false,
+                ));
+            }
+        }
+
+        files.extend(match &self.override_repo_config {
+            Some(path) => load(ConfigSource::Repository, Some(path.clone()), true),
+            None => load(ConfigSource::Repository, Some(git_dir.join("config")), false),
+        });
+
+        files.extend(match &self.override_worktree_config {
+            Some(path) => load(ConfigSource::Worktree, Some(path.clone()), true),
+            None => {
+                let worktree_config_enabled = files
+                    .iter()
+                    .find(|(source, _, _)| *source == ConfigSource::Repository)
+                    .and_then(|(_, repo, _)| repo.boolean("extensions.worktreeConfig"))
+                    .map_or(false, |enabled| enabled.unwrap_or(false));
+                worktree_config_enabled
+                    .then(|| load(ConfigSource::Worktree, Some(git_dir.join("config.worktree")), false))
+                    .flatten()
+            }
+        });
+
+        // Includes are resolved for all files at once as `hasconfig:remote.*.url` conditions depend on all of them.
+        if !(first_error.is_some() && fail_fast) {
+            let mut configs: Vec<_> = files
+                .iter_mut()
+                .map(|(_, config, path)| (config, Some(path.as_path())))
+                .collect();
+            if let Err((index, err)) = GitConfig::resolve_includes_of_all(&mut configs, &options) {
+                first_error.get_or_insert(Error::Load(files[index].0, err));
+            }
+        }
+        for (source, file, _) in files {
+            *config.slot_mut(source) = Some(file);
+        }
+
+        if self.load_env_conf && !(first_error.is_some() && fail_fast) {
+            match GitConfig::from_env(&options) {
+                Ok(env_conf) => config.env_conf = env_conf,
+                Err(err) => {
+                    first_error.get_or_insert(err.into());
+                }
+            }
+        }
+
+        if !(self.cli_values.is_empty() || (first_error.is_some() && fail_fast)) {
+            let mut cli_conf = GitConfig::new();
+            for cli_value in &self.cli_values {
+                let (key, value) = cli_value.split_once('=').unwrap_or((cli_value.as_str(), "true"));
+                match split_key(key) {
+                    Some((section_name, subsection_name, key)) => {
+                        let mut section = if let Ok(section) = cli_conf.section_mut(section_name, subsection_name) {
+                            section
+                        } else {
+                            cli_conf.new_section(
+                                section_name.to_owned(),
+                                subsection_name.map(|name| Cow::Owned(name.to_owned())),
+                            )
+                        };
+                        section.push(
+                            Cow::<str>::Owned(key.to_owned()).into(),
+                            Cow::Owned(value.as_bytes().to_owned()),
+                        );
+                    }
+                    None => {
+                        first_error.get_or_insert_with(|| Error::InvalidCliValue(cli_value.clone()));
+                    }
+                }
+            }
+            config.cli_conf = Some(cli_conf);
+        }
+
+        match first_error {
+            Some(err) => Err((config, err)),
+            None => Ok(config),
+        }
     }
 }
 
+/// The configuration of all levels, which are consulted in order of precedence to find values, with
+/// [`ConfigSource::Cli`] having the highest precedence and [`ConfigSource::System`] the lowest.
+#[derive(Default, Clone, Debug)]
 pub struct Config<'config> {
+    /// The files backing each level, whether they exist or not.
+    paths: Vec<(ConfigSource, PathBuf)>,
     system_conf: Option<GitConfig<'config>>,
     global_conf: Option<GitConfig<'config>>,
     user_conf: Option<GitConfig<'config>>,
@@ -158,7 +396,9 @@ impl<'config> Config<'config> {
             .map(|(value, _)| value)
     }
 
-    fn value_with_source<T: TryFrom<Cow<'config, [u8]>>>(
+    /// Retrieves the value that takes precedence along with the [`ConfigSource`] it was found in, ignoring values
+    /// that fail to convert.
+    pub fn value_with_source<T: TryFrom<Cow<'config, [u8]>>>(
         &'config self,
         section_name: &str,
         subsection_name: Option<&str>,
@@ -177,6 +417,42 @@ impl<'config> Config<'config> {
         None
     }
 
+    /// Retrieves the uninterpreted value that takes precedence along with the [`ConfigSource`] and [`Origin`] it was
+    /// defined at, with the latter being `None` for values that weren't read from a file.
+    pub fn raw_value_with_origin(
+        &self,
+        section_name: &str,
+        subsection_name: Option<&str>,
+        key: &str,
+    ) -> Option<(Cow<'_, [u8]>, ConfigSource, Option<Origin<'_>>)> {
+        self.mapping().iter().find_map(|(conf, source)| {
+            conf.as_ref()?
+                .get_raw_value_with_origin(section_name, subsection_name, key)
+                .ok()
+                .map(|(value, origin)| (value, *source, origin))
+        })
+    }
+
+    /// Returns all values of all configuration levels from the lowest to the highest precedence, in the order they
+    /// are defined in, along with the [`ConfigSource`] they were loaded from.
+    /// This is what `git config --list --show-origin` displays.
+    #[must_use]
+    pub fn entries(&self) -> Vec<(ConfigSource, Entry<'_, 'config>)> {
+        self.mapping()
+            .iter()
+            .rev()
+            .filter_map(|(conf, source)| conf.as_ref().map(|conf| (conf, *source)))
+            .flat_map(|(conf, source)| conf.entries().into_iter().map(move |entry| (source, entry)))
+            .collect()
+    }
+
+    /// Freezes the values of all configuration levels into a [`Snapshot`] that respects their precedence, is cheap to
+    /// clone and can be shared across threads.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot::from_entries(self.entries().into_iter().map(|(_, entry)| entry))
+    }
+
     #[inline]
     pub fn try_value<'lookup, T: TryFrom<Cow<'config, [u8]>>>(
         &'config self,
@@ -188,10 +464,10 @@ impl<'config> Config<'config> {
             .map(|res| res.map(|(value, _)| value))
     }
 
-    /// Tries to retrieve the value, returning an error if the parsing fails or
-    /// if the key was not found. On a successful parse, the value will be
-    /// returned as well as the source location. This respects the priority of
-    /// the various configuration files.
+    /// Tries to retrieve the value, returning an error if the parsing fails.
+    /// On a successful parse, the value will be returned as well as the source
+    /// location, or `None` if the key was not found. This respects the priority
+    /// of the various configuration files.
     pub fn try_value_with_source<'lookup, T: TryFrom<Cow<'config, [u8]>>>(
         &'config self,
         section_name: &'lookup str,
@@ -202,21 +478,49 @@ impl<'config> Config<'config> {
 
         for (conf, source) in mapping.iter() {
             if let Some(conf) = conf {
-                return Ok(Some((conf.value(section_name, subsection_name, key)?, *source)));
+                match conf.value(section_name, subsection_name, key) {
+                    Ok(value) => return Ok(Some((value, *source))),
+                    Err(GitConfigError::SectionDoesNotExist(_))
+                    | Err(GitConfigError::SubSectionDoesNotExist(_))
+                    | Err(GitConfigError::KeyDoesNotExist) => continue,
+                    Err(err) => return Err(err),
+                }
             }
         }
 
         Ok(None)
     }
 
-    /// Returns a mapping from [`GitConfig`] to [`ConfigSource`]
-    const fn mapping(&self) -> [(&Option<GitConfig>, ConfigSource); 6] {
+    /// Returns the path of the file backing the configuration at `source`, which doesn't necessarily exist, or `None`
+    /// if there is no such file like for [`ConfigSource::Env`].
+    #[must_use]
+    pub fn path(&self, source: ConfigSource) -> Option<&Path> {
+        self.paths
+            .iter()
+            .find_map(|(path_source, path)| (*path_source == source).then(|| path.as_path()))
+    }
+
+    fn slot_mut(&mut self, source: ConfigSource) -> &mut Option<GitConfig<'config>> {
+        match source {
+            ConfigSource::System => &mut self.system_conf,
+            ConfigSource::Global => &mut self.global_conf,
+            ConfigSource::User => &mut self.user_conf,
+            ConfigSource::Repository => &mut self.repository_conf,
+            ConfigSource::Worktree => &mut self.worktree_conf,
+            ConfigSource::Env => &mut self.env_conf,
+            ConfigSource::Cli => &mut self.cli_conf,
+        }
+    }
+
+    /// Returns a mapping from [`GitConfig`] to [`ConfigSource`], from the highest to the lowest precedence.
+    const fn mapping(&self) -> [(&Option<GitConfig<'config>>, ConfigSource); 7] {
         [
             (&self.cli_conf, ConfigSource::Cli),
             (&self.env_conf, ConfigSource::Env),
+            (&self.worktree_conf, ConfigSource::Worktree),
             (&self.repository_conf, ConfigSource::Repository),
-            (&self.user_conf, ConfigSource::User),
             (&self.global_conf, ConfigSource::Global),
+            (&self.user_conf, ConfigSource::User),
             (&self.system_conf, ConfigSource::System),
         ]
     }
@@ -233,6 +537,7 @@ impl<'config> Config<'config> {
             ConfigSource::Global => self.global_conf.as_ref(),
             ConfigSource::User => self.user_conf.as_ref(),
             ConfigSource::Repository => self.repository_conf.as_ref(),
+            ConfigSource::Worktree => self.worktree_conf.as_ref(),
             ConfigSource::Env => self.env_conf.as_ref(),
             ConfigSource::Cli => self.cli_conf.as_ref(),
         }
@@ -247,8 +552,85 @@ impl<'config> Config<'config> {
             ConfigSource::Global => self.global_conf.as_mut(),
             ConfigSource::User => self.user_conf.as_mut(),
             ConfigSource::Repository => self.repository_conf.as_mut(),
+            ConfigSource::Worktree => self.worktree_conf.as_mut(),
             ConfigSource::Env => self.env_conf.as_mut(),
             ConfigSource::Cli => self.cli_conf.as_mut(),
         }
     }
 }
+
+/// # Editing
+///
+/// These functions edit the file backing a single configuration level, like `git config --global` or `git config
+/// --local` do. The file is read anew, edited and written back atomically, so values included from other files or
+/// changed programmatically aren't written to it. The edit is applied to the loaded configuration as well, so it is
+/// visible in subsequent reads. The file is created if it doesn't exist yet.
+impl<'config> Config<'config> {
+    /// Sets `key` like `remote.origin.url` to `value` in the file of `source`, creating the section if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` isn't backed by a file, if `key` isn't valid, or if the file couldn't be read or
+    /// written.
+    pub fn set_raw_value(
+        &mut self,
+        source: ConfigSource,
+        key: &str,
+        value: impl AsRef<[u8]>,
+    ) -> Result<(), edit::Error> {
+        let value = value.as_ref();
+        self.edit(source, |config| config.set_raw_value_by_key(key, value))
+    }
+
+    /// Removes the last value of `key` like `core.bare` from the file of `source`, returning it if it existed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` isn't backed by a file, if `key` isn't valid, or if the file couldn't be read or
+    /// written.
+    pub fn unset(&mut self, source: ConfigSource, key: &str) -> Result<Option<Vec<u8>>, edit::Error> {
+        self.edit(source, |config| {
+            config.unset_by_key(key).map(|value| value.map(Cow::into_owned))
+        })
+    }
+
+    /// Renames all sections called `name` like `remote.origin` to `new_name` in the file of `source`, returning the
+    /// amount of renamed sections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` isn't backed by a file, if the names aren't valid, or if the file couldn't be read
+    /// or written.
+    pub fn rename_section(&mut self, source: ConfigSource, name: &str, new_name: &str) -> Result<usize, edit::Error> {
+        self.edit(source, |config| config.rename_section_by_key(name, new_name))
+    }
+
+    /// Removes all sections called `name` like `remote.origin` from the file of `source`, returning the amount of
+    /// removed sections.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `source` isn't backed by a file, if `name` isn't valid, or if the file couldn't be read or
+    /// written.
+    pub fn remove_section(&mut self, source: ConfigSource, name: &str) -> Result<usize, edit::Error> {
+        self.edit(source, |config| config.remove_section_by_key(name))
+    }
+
+    /// Apply `apply` to the file of `source` and write it back, and to its loaded configuration.
+    fn edit<T>(
+        &mut self,
+        source: ConfigSource,
+        mut apply: impl FnMut(&mut GitConfig<'_>) -> Result<T, file_edit::Error>,
+    ) -> Result<T, edit::Error> {
+        let path = self.path(source).ok_or(edit::Error::NoFile(source))?.to_owned();
+        let mut file = if path.is_file() {
+            GitConfig::open(&path).map_err(|err| edit::Error::Open(path.clone(), err))?
+        } else {
+            GitConfig::new()
+        };
+        let res = apply(&mut file)?;
+        file.write_to_path(&path, git_lock::acquire::Fail::Immediately)?;
+        apply(self.slot_mut(source).get_or_insert_with(GitConfig::new))?;
+        Ok(res)
+    }
+}
@@ -181,18 +181,18 @@ impl<'a> From<Cow<'a, [u8]>> for Bytes<'a> {
 pub mod path {
     use std::borrow::Cow;
 
-    #[cfg(not(target_os = "windows"))]
-    use pwd::Passwd;
     use quick_error::ResultExt;
 
     use crate::values::Path;
 
     pub mod interpolate {
+        use std::path::PathBuf;
+
         use quick_error::quick_error;
 
         quick_error! {
             #[derive(Debug)]
-            /// The error returned by [`Path::interpolate()`].
+            /// The error returned by [`Path::interpolate()`][crate::values::Path::interpolate()].
             #[allow(missing_docs)]
             pub enum Error {
                 Missing { what: &'static str } {
@@ -216,23 +216,61 @@ pub mod path {
                 }
             }
         }
+
+        /// What's available to [`Path::interpolate()`][crate::values::Path::interpolate()] to expand paths with.
+        /// Anything that is unset can't be expanded, causing an error if it is needed.
+        #[derive(Debug, Clone, Copy, Default)]
+        pub struct Context<'a> {
+            /// The location where gitoxide or git is installed, used to expand `%(prefix)/`.
+            pub git_install_dir: Option<&'a std::path::Path>,
+            /// The home directory of the current user, used to expand `~/`.
+            pub home_dir: Option<&'a std::path::Path>,
+            /// A function returning the home directory of the given user, used to expand `~user/`, like
+            /// [`home_for_user()`].
+            pub home_for_user: Option<fn(&str) -> Option<PathBuf>>,
+        }
+
+        /// Return the home directory of the current user like git does, which is the value of the `HOME`
+        /// environment variable.
+        #[must_use]
+        pub fn home_dir() -> Option<PathBuf> {
+            std::env::var_os("HOME").map(PathBuf::from)
+        }
+
+        /// Return the home directory of the user with the given `name` as listed in the password database,
+        /// or `None` if it isn't known or if there is no such database on this platform.
+        #[must_use]
+        pub fn home_for_user(name: &str) -> Option<PathBuf> {
+            #[cfg(not(target_os = "windows"))]
+            {
+                pwd::Passwd::from_name(name).ok()?.map(|user| user.dir.into())
+            }
+            #[cfg(target_os = "windows")]
+            {
+                let _ = name;
+                None
+            }
+        }
     }
 
     impl<'a> Path<'a> {
-        /// Interpolates this path into a file system path.
+        /// Interpolates this path into a file system path using what's available in `context`, like git's
+        /// `interpolate_path()`.
         ///
         /// If this path starts with `~/` or `~user/` or `%(prefix)/`
-        ///  - `~/` is expanded to the value of `$HOME` on unix based systems. On windows, `SHGetKnownFolderPath` is used.
-        /// See also [dirs](https://crates.io/crates/dirs).
-        ///  - `~user/` to the specified user’s home directory, e.g `~alice` might get expanded to `/home/alice` on linux.
-        /// The interpolation uses `getpwnam` sys call and is therefore not available on windows. See also [pwd](https://crates.io/crates/pwd).
-        ///  - `%(prefix)/` is expanded to the location where gitoxide is installed. This location is not known at compile time and therefore need to be
-        /// optionally provided by the caller through `git_install_dir`.
+        ///  - `~/` is expanded to [`home_dir`][interpolate::Context::home_dir], and so is a lone `~`.
+        ///  - `~user/` to the specified user’s home directory as returned by
+        ///    [`home_for_user`][interpolate::Context::home_for_user], e.g `~alice` might get expanded to `/home/alice`
+        ///    on linux.
+        ///  - `%(prefix)/` is expanded to [`git_install_dir`][interpolate::Context::git_install_dir], the location
+        ///    where gitoxide is installed. This location is not known at compile time and therefore needs to be
+        ///    provided by the caller.
         ///
-        /// Any other, non-empty path value is returned unchanged and error is returned in case of an empty path value.
+        /// Any other, non-empty path value is returned unchanged and error is returned in case of an empty path value,
+        /// or if what's needed for the expansion isn't available in `context`.
         pub fn interpolate(
             self,
-            git_install_dir: Option<&std::path::Path>,
+            context: interpolate::Context<'_>,
         ) -> Result<Cow<'a, std::path::Path>, interpolate::Error> {
             if self.is_empty() {
                 return Err(interpolate::Error::Missing { what: "path" });
@@ -241,54 +279,60 @@ pub mod path {
             const PREFIX: &[u8] = b"%(prefix)/";
             const USER_HOME: &[u8] = b"~/";
             if self.starts_with(PREFIX) {
-                let git_install_dir = git_install_dir.ok_or(interpolate::Error::Missing {
+                let git_install_dir = context.git_install_dir.ok_or(interpolate::Error::Missing {
                     what: "git install dir",
                 })?;
                 let (_prefix, path_without_trailing_slash) = self.split_at(PREFIX.len());
                 let path_without_trailing_slash =
                     git_features::path::from_byte_vec(path_without_trailing_slash).context("path past %(prefix)")?;
                 Ok(git_install_dir.join(path_without_trailing_slash).into())
-            } else if self.starts_with(USER_HOME) {
-                let home_path = dirs::home_dir().ok_or(interpolate::Error::Missing { what: "home dir" })?;
-                let (_prefix, val) = self.split_at(USER_HOME.len());
+            } else if self.starts_with(USER_HOME) || &*self.value == b"~" {
+                let home_path = context
+                    .home_dir
+                    .ok_or(interpolate::Error::Missing { what: "home dir" })?;
+                let val = self.get(USER_HOME.len()..).unwrap_or_default();
                 let val = git_features::path::from_bytes(val).context("path past ~/")?;
                 Ok(home_path.join(val).into())
-            } else if self.starts_with(b"~") && self.contains(&b'/') {
-                self.interpolate_user()
+            } else if self.starts_with(b"~") {
+                self.interpolate_user(context)
             } else {
                 Ok(git_features::path::from_bytes(self.value).context("unexpanded path")?)
             }
         }
 
-        #[cfg(target_os = "windows")]
-        fn interpolate_user(self) -> Result<Cow<'a, std::path::Path>, interpolate::Error> {
-            Err(interpolate::Error::UserInterpolationUnsupported)
-        }
-
-        #[cfg(not(target_os = "windows"))]
-        fn interpolate_user(self) -> Result<Cow<'a, std::path::Path>, interpolate::Error> {
-            let (_prefix, val) = self.split_at("/".len());
-            let i = val
-                .iter()
-                .position(|&e| e == b'/')
-                .ok_or(interpolate::Error::Missing { what: "/" })?;
-            let (username, path_with_leading_slash) = val.split_at(i);
+        fn interpolate_user(
+            self,
+            context: interpolate::Context<'_>,
+        ) -> Result<Cow<'a, std::path::Path>, interpolate::Error> {
+            let home_for_user = context
+                .home_for_user
+                .ok_or(interpolate::Error::UserInterpolationUnsupported)?;
+            let (_prefix, val) = self.split_at("~".len());
+            let (username, path_past_user_prefix) = match val.iter().position(|&e| e == b'/') {
+                Some(i) => (&val[..i], &val[i + "/".len()..]),
+                None => (val, &[][..]),
+            };
             let username = std::str::from_utf8(username)?;
-            let home = Passwd::from_name(username)
-                .map_err(|_| interpolate::Error::PwdFileQuery)?
-                .ok_or(interpolate::Error::Missing { what: "pwd user info" })?
-                .dir;
-            let path_past_user_prefix = git_features::path::from_byte_slice(&path_with_leading_slash["/".len()..])
-                .context("path past ~user/")?;
-            Ok(std::path::PathBuf::from(home).join(path_past_user_prefix).into())
+            let home = home_for_user(username).ok_or(interpolate::Error::Missing { what: "pwd user info" })?;
+            let path_past_user_prefix =
+                git_features::path::from_byte_slice(path_past_user_prefix).context("path past ~user/")?;
+            Ok(home.join(path_past_user_prefix).into())
         }
     }
 
     #[cfg(test)]
     mod interpolate_tests {
-        use std::borrow::Cow;
+        use std::{borrow::Cow, path::PathBuf};
+
+        use crate::values::{
+            b,
+            path::interpolate::{Context, Error},
+            Path,
+        };
 
-        use crate::values::{b, path::interpolate::Error, Path};
+        fn home_for_user(name: &str) -> Option<PathBuf> {
+            (name == "alice").then(|| PathBuf::from("/home/alice"))
+        }
 
         #[test]
         fn no_interpolation_for_paths_without_tilde_or_prefix() {
@@ -304,7 +348,7 @@ pub mod path {
         #[test]
         fn empty_path_is_error() {
             assert!(matches!(
-                Path::from(Cow::Borrowed(b(""))).interpolate(None),
+                Path::from(Cow::Borrowed(b(""))).interpolate(Context::default()),
                 Err(Error::Missing { what: "path" })
             ));
         }
@@ -324,7 +368,10 @@ pub mod path {
                     ));
                     assert_eq!(
                         &*Path::from(Cow::Borrowed(*val))
-                            .interpolate(Some(std::path::Path::new(git_install_dir)))
+                            .interpolate(Context {
+                                git_install_dir: Some(std::path::Path::new(git_install_dir)),
+                                ..Default::default()
+                            })
                             .unwrap(),
                         expected,
                         "prefix interpolation keeps separators as they are"
@@ -339,7 +386,10 @@ pub mod path {
             let git_install_dir = "/tmp/git";
             assert_eq!(
                 Path::from(Cow::Borrowed(b(path)))
-                    .interpolate(Some(std::path::Path::new(git_install_dir)))
+                    .interpolate(Context {
+                        git_install_dir: Some(std::path::Path::new(git_install_dir)),
+                        ..Default::default()
+                    })
                     .unwrap(),
                 std::path::Path::new(path)
             );
@@ -347,40 +397,81 @@ pub mod path {
 
         #[test]
         fn tilde_substitutes_current_user() {
-            let path = &b"~/foo/bar"[..];
-            let expected = format!(
-                "{}{}foo/bar",
-                dirs::home_dir().expect("empty home").display(),
-                std::path::MAIN_SEPARATOR
-            );
+            let home = std::path::Path::new("/home/user");
+            let context = Context {
+                home_dir: Some(home),
+                ..Default::default()
+            };
             assert_eq!(
-                Path::from(Cow::Borrowed(path)).interpolate(None).unwrap().as_ref(),
-                std::path::Path::new(&expected),
+                Path::from(Cow::Borrowed(&b"~/foo/bar"[..]))
+                    .interpolate(context)
+                    .unwrap()
+                    .as_ref(),
+                home.join("foo/bar"),
                 "note that path separators are not turned into slashes as we work with `std::path::Path`"
             );
+            assert_eq!(
+                Path::from(Cow::Borrowed(&b"~"[..]))
+                    .interpolate(context)
+                    .unwrap()
+                    .as_ref(),
+                home,
+                "a lone tilde is the home directory as well"
+            );
         }
 
-        #[cfg(target_os = "windows")]
         #[test]
-        fn tilde_with_given_user_is_unsupported_on_windows() {
+        fn missing_context_is_an_error() {
             assert!(matches!(
-                Path::from(Cow::Borrowed(&b"~baz/foo/bar"[..])).interpolate(None),
+                Path::from(Cow::Borrowed(&b"%(prefix)/foo"[..])).interpolate(Context::default()),
+                Err(Error::Missing {
+                    what: "git install dir"
+                })
+            ));
+            assert!(matches!(
+                Path::from(Cow::Borrowed(&b"~/foo"[..])).interpolate(Context::default()),
+                Err(Error::Missing { what: "home dir" })
+            ));
+            assert!(matches!(
+                Path::from(Cow::Borrowed(&b"~baz/foo/bar"[..])).interpolate(Context::default()),
                 Err(Error::UserInterpolationUnsupported)
             ));
         }
 
+        #[test]
+        fn tilde_with_given_user_uses_home_for_user() {
+            let context = Context {
+                home_for_user: Some(home_for_user),
+                ..Default::default()
+            };
+            for (path, expected) in &[("~alice/foo/bar", "/home/alice/foo/bar"), ("~alice", "/home/alice")] {
+                assert_eq!(
+                    Path::from(Cow::Borrowed(b(path))).interpolate(context).unwrap(),
+                    std::path::Path::new(expected)
+                );
+            }
+            assert!(matches!(
+                Path::from(Cow::Borrowed(&b"~bob/foo"[..])).interpolate(context),
+                Err(Error::Missing { what: "pwd user info" })
+            ));
+        }
+
         #[cfg(not(target_os = "windows"))]
         #[test]
         fn tilde_with_given_user() {
             let user = std::env::var("USER").unwrap();
             let home = std::env::var("HOME").unwrap();
             let specific_user_home = format!("~{}", user);
+            let context = Context {
+                home_for_user: Some(crate::values::path::interpolate::home_for_user),
+                ..Default::default()
+            };
 
             for path_suffix in &["foo/bar", "foo\\bar", ""] {
                 let path = format!("{}{}{}", specific_user_home, std::path::MAIN_SEPARATOR, path_suffix);
                 let expected = format!("{}{}{}", home, std::path::MAIN_SEPARATOR, path_suffix);
                 assert_eq!(
-                    Path::from(Cow::Borrowed(b(&path))).interpolate(None).unwrap(),
+                    Path::from(Cow::Borrowed(b(&path))).interpolate(context).unwrap(),
                     std::path::Path::new(&expected),
                     "it keeps path separators as is"
                 );
@@ -425,6 +516,9 @@ impl<'a> From<Cow<'a, [u8]>> for Path<'a> {
 /// documentation has a strict subset of values that may be interpreted as a
 /// boolean value, all of which are ASCII and thus UTF-8 representable.
 /// Consequently, variants hold [`str`]s rather than [`[u8]`]s.
+///
+/// Like in git, `yes`, `on`, `true` and an implicit value (a key without `=`) are true, while `no`, `off` and `false`
+/// are false, all of them case-insensitively. Integers are true unless they are zero.
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[allow(missing_docs)]
 pub enum Boolean<'a> {
@@ -474,7 +568,6 @@ impl<'a> TryFrom<&'a [u8]> for Boolean<'a> {
         if value.eq_ignore_ascii_case(b"no")
             || value.eq_ignore_ascii_case(b"off")
             || value.eq_ignore_ascii_case(b"false")
-            || value.eq_ignore_ascii_case(b"zero")
             || value == b"\"\""
         {
             return Ok(Self::False(
@@ -482,7 +575,17 @@ impl<'a> TryFrom<&'a [u8]> for Boolean<'a> {
             ));
         }
 
-        Err(BooleanError::InvalidFormat)
+        let is_true = Integer::try_from(value)
+            .ok()
+            .and_then(Integer::to_decimal)
+            .map(|number| number != 0)
+            .ok_or(BooleanError::InvalidFormat)?;
+        let value = std::str::from_utf8(value).expect("integers are valid UTF-8").into();
+        Ok(if is_true {
+            Self::True(TrueVariant::Explicit(value))
+        } else {
+            Self::False(value)
+        })
     }
 }
 
@@ -490,18 +593,17 @@ impl TryFrom<Vec<u8>> for Boolean<'_> {
     type Error = BooleanError;
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        if value.eq_ignore_ascii_case(b"no")
-            || value.eq_ignore_ascii_case(b"off")
-            || value.eq_ignore_ascii_case(b"false")
-            || value.eq_ignore_ascii_case(b"zero")
-            || value == b"\"\""
-        {
-            return Ok(Self::False(Cow::Owned(
-                std::string::String::from_utf8(value).expect("value is already validated"),
-            )));
-        }
-
-        TrueVariant::try_from(value).map(Self::True)
+        let is_true = match Boolean::try_from(value.as_slice())? {
+            Boolean::True(TrueVariant::Implicit) => return Ok(Self::True(TrueVariant::Implicit)),
+            Boolean::True(TrueVariant::Explicit(_)) => true,
+            Boolean::False(_) => false,
+        };
+        let value = Cow::Owned(std::string::String::from_utf8(value).expect("value is already validated"));
+        Ok(if is_true {
+            Self::True(TrueVariant::Explicit(value))
+        } else {
+            Self::False(value)
+        })
     }
 }
 
@@ -590,7 +692,6 @@ impl<'a> TryFrom<&'a [u8]> for TrueVariant<'a> {
         if value.eq_ignore_ascii_case(b"yes")
             || value.eq_ignore_ascii_case(b"on")
             || value.eq_ignore_ascii_case(b"true")
-            || value.eq_ignore_ascii_case(b"one")
         {
             Ok(Self::Explicit(
                 std::str::from_utf8(value).expect("value is already validated").into(),
@@ -607,18 +708,11 @@ impl TryFrom<Vec<u8>> for TrueVariant<'_> {
     type Error = BooleanError;
 
     fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
-        if value.eq_ignore_ascii_case(b"yes")
-            || value.eq_ignore_ascii_case(b"on")
-            || value.eq_ignore_ascii_case(b"true")
-            || value.eq_ignore_ascii_case(b"one")
-        {
-            Ok(Self::Explicit(Cow::Owned(
+        match TrueVariant::try_from(value.as_slice())? {
+            TrueVariant::Implicit => Ok(Self::Implicit),
+            TrueVariant::Explicit(_) => Ok(Self::Explicit(Cow::Owned(
                 std::string::String::from_utf8(value).expect("value is already validated"),
-            )))
-        } else if value.is_empty() {
-            Ok(Self::Implicit)
-        } else {
-            Err(BooleanError::InvalidFormat)
+            ))),
         }
     }
 }
@@ -680,6 +774,15 @@ impl Integer {
     pub fn to_vec(self) -> Vec<u8> {
         self.into()
     }
+
+    /// Returns the value with its suffix applied, or `None` if the result doesn't fit into an [`i64`].
+    #[must_use]
+    pub fn to_decimal(self) -> Option<i64> {
+        match self.suffix {
+            Some(suffix) => self.value.checked_mul(1 << suffix.bitwise_offset()),
+            None => Some(self.value),
+        }
+    }
 }
 
 impl Display for Integer {
@@ -726,6 +829,9 @@ quick_error! {
         InvalidSuffix {
             display("Invalid suffix")
         }
+        Overflow {
+            display("The value with its suffix applied doesn't fit into a 64 bit signed integer")
+        }
     }
 }
 
@@ -794,7 +900,7 @@ impl From<&Integer> for Vec<u8> {
 
 /// Integer prefixes that are supported by `git-config`.
 ///
-/// These values are base-2 unit of measurements, not the base-10 variants.
+/// These values are base-2 unit of measurements, not the base-10 variants, and are parsed case-insensitively.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[allow(missing_docs)]
 pub enum IntegerSuffix {
@@ -847,9 +953,9 @@ impl FromStr for IntegerSuffix {
     #[inline]
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "k" => Ok(Self::Kibi),
-            "m" => Ok(Self::Mebi),
-            "g" => Ok(Self::Gibi),
+            "k" | "K" => Ok(Self::Kibi),
+            "m" | "M" => Ok(Self::Mebi),
+            "g" | "G" => Ok(Self::Gibi),
             _ => Err(IntegerError::InvalidSuffix),
         }
     }
@@ -903,19 +1009,23 @@ impl Color {
 
 impl Display for Color {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if let Some(fg) = self.foreground {
-            fg.fmt(f)?;
+        let mut needs_space = false;
+        for color in self.foreground.iter().chain(self.background.iter()) {
+            if needs_space {
+                write!(f, " ")?;
+            }
+            color.fmt(f)?;
+            needs_space = true;
         }
 
-        write!(f, " ")?;
-
-        if let Some(bg) = self.background {
-            bg.fmt(f)?;
+        for attr in &self.attributes {
+            if needs_space {
+                write!(f, " ")?;
+            }
+            attr.fmt(f)?;
+            needs_space = true;
         }
-
-        self.attributes
-            .iter()
-            .try_for_each(|attr| write!(f, " ").and_then(|_| attr.fmt(f)))
+        Ok(())
     }
 }
 
@@ -1033,11 +1143,14 @@ impl From<&Color> for Vec<u8> {
 /// Discriminating enum for [`Color`] values.
 ///
 /// `git-config` supports the eight standard colors, their bright variants, an
-/// ANSI color code, or a 24-bit hex value prefixed with an octothorpe.
+/// ANSI color code, or a 24-bit hex value prefixed with an octothorpe, all of
+/// which are parsed case-insensitively.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[allow(missing_docs)]
 pub enum ColorValue {
     Normal,
+    /// The default color of the terminal.
+    Default,
     Black,
     BrightBlack,
     Red,
@@ -1062,6 +1175,7 @@ impl Display for ColorValue {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Normal => write!(f, "normal"),
+            Self::Default => write!(f, "default"),
             Self::Black => write!(f, "black"),
             Self::BrightBlack => write!(f, "brightblack"),
             Self::Red => write!(f, "red"),
@@ -1098,7 +1212,8 @@ impl FromStr for ColorValue {
     type Err = ColorError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut s = s;
+        let lowercase = s.to_ascii_lowercase();
+        let mut s = lowercase.as_str();
         let bright = if s.starts_with("bright") {
             s = &s[6..];
             true
@@ -1109,6 +1224,8 @@ impl FromStr for ColorValue {
         match s {
             "normal" if !bright => return Ok(Self::Normal),
             "normal" if bright => return Err(ColorError::InvalidFormat),
+            "default" if !bright => return Ok(Self::Default),
+            "default" if bright => return Err(ColorError::InvalidFormat),
             "black" if !bright => return Ok(Self::Black),
             "black" if bright => return Ok(Self::BrightBlack),
             "red" if !bright => return Ok(Self::Red),
@@ -1163,10 +1280,12 @@ impl TryFrom<&[u8]> for ColorValue {
 ///
 /// `git-config` supports modifiers and their negators. The negating color
 /// attributes are equivalent to having a `no` or `no-` prefix to the normal
-/// variant.
+/// variant. `underline` is an alias for `ul`, and names are parsed case-insensitively.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[allow(missing_docs)]
 pub enum ColorAttribute {
+    /// Reset all attributes and colors before applying this color.
+    Reset,
     Bold,
     NoBold,
     Dim,
@@ -1186,6 +1305,7 @@ pub enum ColorAttribute {
 impl Display for ColorAttribute {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Reset => write!(f, "reset"),
             Self::Bold => write!(f, "bold"),
             Self::NoBold => write!(f, "nobold"),
             Self::Dim => write!(f, "dim"),
@@ -1211,6 +1331,7 @@ impl Serialize for ColorAttribute {
         S: serde::Serializer,
     {
         serializer.serialize_str(match self {
+            Self::Reset => "reset",
             Self::Bold => "bold",
             Self::NoBold => "nobold",
             Self::Dim => "dim",
@@ -1233,8 +1354,9 @@ impl FromStr for ColorAttribute {
     type Err = ColorError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let inverted = s.starts_with("no");
-        let mut parsed = s;
+        let lowercase = s.to_ascii_lowercase();
+        let inverted = lowercase.starts_with("no");
+        let mut parsed = lowercase.as_str();
 
         if inverted {
             parsed = &parsed[2..];
@@ -1245,12 +1367,13 @@ impl FromStr for ColorAttribute {
         }
 
         match parsed {
+            "reset" if !inverted => Ok(Self::Reset),
             "bold" if !inverted => Ok(Self::Bold),
             "bold" if inverted => Ok(Self::NoBold),
             "dim" if !inverted => Ok(Self::Dim),
             "dim" if inverted => Ok(Self::NoDim),
-            "ul" if !inverted => Ok(Self::Ul),
-            "ul" if inverted => Ok(Self::NoUl),
+            "ul" | "underline" if !inverted => Ok(Self::Ul),
+            "ul" | "underline" if inverted => Ok(Self::NoUl),
             "blink" if !inverted => Ok(Self::Blink),
             "blink" if inverted => Ok(Self::NoBlink),
             "reverse" if !inverted => Ok(Self::Reverse),
@@ -1339,8 +1462,9 @@ mod boolean {
         assert_eq!(Boolean::try_from(b("no")), Ok(Boolean::False("no".into())));
         assert_eq!(Boolean::try_from(b("off")), Ok(Boolean::False("off".into())));
         assert_eq!(Boolean::try_from(b("false")), Ok(Boolean::False("false".into())));
-        assert_eq!(Boolean::try_from(b("zero")), Ok(Boolean::False("zero".into())));
         assert_eq!(Boolean::try_from(b("\"\"")), Ok(Boolean::False("\"\"".into())));
+        assert_eq!(Boolean::try_from(b("0")), Ok(Boolean::False("0".into())));
+        assert_eq!(Boolean::try_from(b("0k")), Ok(Boolean::False("0k".into())));
     }
 
     #[test]
@@ -1357,16 +1481,32 @@ mod boolean {
             Boolean::try_from(b("true")),
             Ok(Boolean::True(TrueVariant::Explicit("true".into())))
         );
-        assert_eq!(
-            Boolean::try_from(b("one")),
-            Ok(Boolean::True(TrueVariant::Explicit("one".into())))
-        );
+        assert_eq!(Boolean::try_from(b("")), Ok(Boolean::True(TrueVariant::Implicit)));
+        for number in &["1", "-1", "42", "1k"] {
+            assert_eq!(
+                Boolean::try_from(b(number)),
+                Ok(Boolean::True(TrueVariant::Explicit((*number).into()))),
+                "all non-zero integers are true"
+            );
+        }
+    }
+
+    #[test]
+    fn from_vec_matches_from_slice() {
+        for input in &["yes", "", "off", "0", "7"] {
+            assert_eq!(
+                Boolean::try_from(input.as_bytes().to_vec()),
+                Boolean::try_from(b(input)),
+                "{:?}",
+                input
+            );
+        }
     }
 
     #[test]
     fn ignores_case() {
         // Random subset
-        for word in &["no", "yes", "off", "true", "zero"] {
+        for word in &["no", "yes", "off", "true", "on"] {
             let first: bool = Boolean::try_from(b(word)).unwrap().into();
             let second: bool = Boolean::try_from(b(&*word.to_uppercase())).unwrap().into();
             assert_eq!(first, second);
@@ -1377,6 +1517,12 @@ mod boolean {
     fn from_str_err() {
         assert!(Boolean::try_from(b("yesn't")).is_err());
         assert!(Boolean::try_from(b("yesno")).is_err());
+        assert!(Boolean::try_from(b("one")).is_err());
+        assert!(Boolean::try_from(b("zero")).is_err());
+        assert!(
+            Boolean::try_from(b("9223372036854775807g")).is_err(),
+            "overflows aren't booleans"
+        );
     }
 }
 
@@ -1425,6 +1571,27 @@ mod integer {
                 suffix: Some(IntegerSuffix::Gibi),
             }
         );
+
+        assert_eq!(
+            Integer::try_from(b("2K")).unwrap(),
+            Integer {
+                value: 2,
+                suffix: Some(IntegerSuffix::Kibi),
+            },
+            "suffixes are case-insensitive"
+        );
+    }
+
+    #[test]
+    fn to_decimal() {
+        assert_eq!(Integer::try_from(b("-3")).unwrap().to_decimal(), Some(-3));
+        assert_eq!(Integer::try_from(b("2k")).unwrap().to_decimal(), Some(2048));
+        assert_eq!(Integer::try_from(b("3M")).unwrap().to_decimal(), Some(3 * 1024 * 1024));
+        assert_eq!(
+            Integer::try_from(b("1g")).unwrap().to_decimal(),
+            Some(1024 * 1024 * 1024)
+        );
+        assert_eq!(Integer::try_from(b("9223372036854775807k")).unwrap().to_decimal(), None);
     }
 
     #[test]
@@ -1439,6 +1606,35 @@ mod integer {
     }
 }
 
+#[cfg(test)]
+mod color {
+    use std::convert::TryFrom;
+
+    use super::{Color, ColorAttribute, ColorValue};
+    use crate::values::b;
+
+    #[test]
+    fn parse_and_display() {
+        let color = Color::try_from(b("bold Red #ff0010 ul")).unwrap();
+        assert_eq!(
+            color,
+            Color {
+                foreground: Some(ColorValue::Red),
+                background: Some(ColorValue::Rgb(255, 0, 16)),
+                attributes: vec![ColorAttribute::Bold, ColorAttribute::Ul],
+            }
+        );
+        assert_eq!(color.to_string(), "red #ff0010 bold ul");
+        assert_eq!(Color::try_from(b("reset")).unwrap().to_string(), "reset");
+        assert_eq!(Color::default().to_string(), "");
+    }
+
+    #[test]
+    fn at_most_two_colors() {
+        assert!(Color::try_from(b("red green blue")).is_err());
+    }
+}
+
 #[cfg(test)]
 mod color_value {
     use std::str::FromStr;
@@ -1456,6 +1652,8 @@ mod color_value {
         assert_eq!(ColorValue::from_str("magenta"), Ok(ColorValue::Magenta));
         assert_eq!(ColorValue::from_str("cyan"), Ok(ColorValue::Cyan));
         assert_eq!(ColorValue::from_str("white"), Ok(ColorValue::White));
+        assert_eq!(ColorValue::from_str("default"), Ok(ColorValue::Default));
+        assert_eq!(ColorValue::from_str("Red"), Ok(ColorValue::Red));
     }
 
     #[test]
@@ -1486,6 +1684,7 @@ mod color_value {
     #[test]
     fn invalid() {
         assert!(ColorValue::from_str("brightnormal").is_err());
+        assert!(ColorValue::from_str("brightdefault").is_err());
         assert!(ColorValue::from_str("").is_err());
         assert!(ColorValue::from_str("bright").is_err());
         assert!(ColorValue::from_str("256").is_err());
@@ -1510,6 +1709,9 @@ mod color_attribute {
         assert_eq!(ColorAttribute::from_str("reverse"), Ok(ColorAttribute::Reverse));
         assert_eq!(ColorAttribute::from_str("italic"), Ok(ColorAttribute::Italic));
         assert_eq!(ColorAttribute::from_str("strike"), Ok(ColorAttribute::Strike));
+        assert_eq!(ColorAttribute::from_str("reset"), Ok(ColorAttribute::Reset));
+        assert_eq!(ColorAttribute::from_str("underline"), Ok(ColorAttribute::Ul));
+        assert_eq!(ColorAttribute::from_str("BOLD"), Ok(ColorAttribute::Bold));
     }
 
     #[test]
@@ -1541,5 +1743,6 @@ mod color_attribute {
         assert!(ColorAttribute::from_str("").is_err());
         assert!(ColorAttribute::from_str("no").is_err());
         assert!(ColorAttribute::from_str("no-").is_err());
+        assert!(ColorAttribute::from_str("noreset").is_err());
     }
 }
@@ -0,0 +1,215 @@
+#[cfg(test)]
+mod config_builder {
+    use std::{borrow::Cow, fs, path::Path};
+
+    use git_config::{
+        file::Origin,
+        fs::{edit, ConfigBuilder, ConfigSource, Error},
+        values::{Boolean, Integer},
+    };
+    use tempfile::tempdir;
+
+    fn builder(dir: &Path) -> ConfigBuilder {
+        ConfigBuilder::new()
+            .load_environment_entries(false)
+            .system_config_path(Some(dir.join("system")))
+            .global_config_path(Some(dir.join("global")))
+            .git_dir(Some(dir.join("repo.git")))
+            .clone()
+    }
+
+    #[test]
+    fn levels_take_precedence_in_order() -> crate::Result {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("repo.git"))?;
+        fs::write(
+            dir.path().join("system"),
+            "[core]\n\ta = system\n\tb = system\n\tc = system\n",
+        )?;
+        fs::write(dir.path().join("global"), "[core]\n\tb = global\n\tc = global\n")?;
+        fs::write(dir.path().join("repo.git/config"), "[core]\n\tc = repo\n")?;
+
+        let config = builder(dir.path())
+            .cli_values(vec!["core.d=cli", "core.e"])
+            .try_build()?;
+        for (key, expected_value, expected_source) in [
+            ("a", "system", ConfigSource::System),
+            ("b", "global", ConfigSource::Global),
+            ("c", "repo", ConfigSource::Repository),
+            ("d", "cli", ConfigSource::Cli),
+        ] {
+            let (value, source, _) = config.raw_value_with_origin("core", None, key).expect("present");
+            assert_eq!(value, Cow::Borrowed(expected_value.as_bytes()));
+            assert_eq!(source, expected_source);
+        }
+        assert_eq!(
+            config.value::<Boolean>("core", None, "e").map(bool::from),
+            Some(true),
+            "keys without value are true"
+        );
+        assert_eq!(config.try_value::<Integer>("core", None, "missing")?, None);
+
+        let snapshot = config.snapshot();
+        assert_eq!(snapshot.string("core.c"), Some(Cow::Borrowed(&b"repo"[..])));
+        assert_eq!(snapshot.strings("core.c").map(|values| values.len()), Some(3));
+        assert_eq!(snapshot.boolean("core.e"), Some(Ok(true)));
+        Ok(())
+    }
+
+    #[test]
+    fn origins_of_values() -> crate::Result {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("repo.git"))?;
+        let global = dir.path().join("global");
+        fs::write(&global, "# comment\n[user]\n\tname = a\n\temail = b\n")?;
+
+        let config = builder(dir.path())
+            .no_system(true)
+            .cli_values(Some("user.name=c"))
+            .try_build()?;
+        assert_eq!(
+            config.raw_value_with_origin("user", None, "email"),
+            Some((
+                Cow::Borrowed(&b"b"[..]),
+                ConfigSource::Global,
+                Some(Origin {
+                    path: Some(global.as_path()),
+                    line: 4
+                })
+            ))
+        );
+
+        let entries: Vec<_> = config
+            .entries()
+            .into_iter()
+            .map(|(source, entry)| (source, entry.key.to_string(), entry.origin.map(|origin| origin.line)))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (ConfigSource::Global, "name".into(), Some(3)),
+                (ConfigSource::Global, "email".into(), Some(4)),
+                (ConfigSource::Cli, "name".into(), None),
+            ],
+            "entries are listed from the lowest to the highest precedence"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn worktree_config_is_read_if_enabled() -> crate::Result {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("repo.git"))?;
+        fs::write(dir.path().join("global"), "")?;
+        fs::write(
+            dir.path().join("repo.git/config.worktree"),
+            "[core]\n\tsparseCheckout = true\n",
+        )?;
+
+        fs::write(dir.path().join("repo.git/config"), "[core]\n\tsparseCheckout = false\n")?;
+        let config = builder(dir.path()).no_system(true).try_build()?;
+        assert_eq!(
+            config
+                .raw_value_with_origin("core", None, "sparseCheckout")
+                .map(|v| v.1),
+            Some(ConfigSource::Repository)
+        );
+
+        fs::write(
+            dir.path().join("repo.git/config"),
+            "[core]\n\tsparseCheckout = false\n[extensions]\n\tworktreeConfig = true\n",
+        )?;
+        let config = builder(dir.path()).no_system(true).try_build()?;
+        assert_eq!(
+            config
+                .raw_value_with_origin("core", None, "sparseCheckout")
+                .map(|v| v.1),
+            Some(ConfigSource::Worktree)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_overridden_files_fail_only_when_trying() -> crate::Result {
+        let dir = tempdir()?;
+        let config = builder(dir.path()).build();
+        assert!(config.get_config(ConfigSource::System).is_none());
+        assert!(config.get_config(ConfigSource::Repository).is_none());
+        assert!(matches!(
+            builder(dir.path()).try_build(),
+            Err(Error::Load(ConfigSource::System, _))
+        ));
+
+        fs::write(dir.path().join("global"), "")?;
+        assert!(
+            builder(dir.path()).no_system(true).try_build().is_ok(),
+            "the repository configuration is optional"
+        );
+        assert!(matches!(
+            builder(dir.path())
+                .no_system(true)
+                .cli_values(Some("novalue"))
+                .try_build(),
+            Err(Error::InvalidCliValue(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn edits_are_written_to_the_file_of_their_level() -> crate::Result {
+        let dir = tempdir()?;
+        fs::create_dir(dir.path().join("repo.git"))?;
+        fs::write(dir.path().join("system"), "")?;
+        let global = dir.path().join("global");
+        fs::write(&global, "[include]\n\tpath = included\n[user]\n\tname = a\n")?;
+        fs::write(dir.path().join("included"), "[user]\n\temail = b\n")?;
+        let repo = dir.path().join("repo.git/config");
+        let mut config = builder(dir.path()).try_build()?;
+        assert_eq!(config.path(ConfigSource::Repository), Some(repo.as_path()));
+
+        config.set_raw_value(ConfigSource::Global, "user.name", "c")?;
+        config.set_raw_value(ConfigSource::Repository, "remote.origin.url", "https://example.com")?;
+        assert_eq!(
+            fs::read_to_string(&global)?,
+            "[include]\n\tpath = included\n[user]\n\tname = c\n",
+            "included values aren't written"
+        );
+        assert_eq!(
+            fs::read_to_string(&repo)?,
+            "[remote \"origin\"]\n  url=https://example.com\n",
+            "missing files are created"
+        );
+        let snapshot = config.snapshot();
+        assert_eq!(snapshot.string("user.name"), Some(Cow::Borrowed(&b"c"[..])));
+        assert_eq!(snapshot.string("user.email"), Some(Cow::Borrowed(&b"b"[..])));
+        assert_eq!(
+            config
+                .raw_value_with_origin("remote", Some("origin"), "url")
+                .map(|v| v.1),
+            Some(ConfigSource::Repository),
+            "edits are visible in the loaded configuration"
+        );
+
+        assert_eq!(
+            config.rename_section(ConfigSource::Repository, "remote.origin", "remote.upstream")?,
+            1
+        );
+        assert_eq!(config.unset(ConfigSource::Global, "user.name")?, Some(b"c".to_vec()));
+        assert_eq!(fs::read_to_string(&global)?, "[include]\n\tpath = included\n[user]\n");
+        assert_eq!(config.remove_section(ConfigSource::Repository, "remote.upstream")?, 1);
+        assert_eq!(fs::read_to_string(&repo)?, "");
+        assert!(config
+            .raw_value_with_origin("remote", Some("upstream"), "url")
+            .is_none());
+
+        assert!(matches!(
+            config.set_raw_value(ConfigSource::Cli, "a.b", "c"),
+            Err(edit::Error::NoFile(ConfigSource::Cli))
+        ));
+        assert!(matches!(
+            config.set_raw_value(ConfigSource::Global, "invalid", "c"),
+            Err(edit::Error::Edit(_))
+        ));
+        Ok(())
+    }
+}
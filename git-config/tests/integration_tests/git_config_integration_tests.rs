@@ -674,6 +674,170 @@ mod from_paths_tests {
         );
     }
 
+    #[test]
+    fn conditional_includes_by_gitdir() {
+        let dir = tempdir().unwrap();
+        let git_dir = dir.path().join("work").join("project").join(".git");
+        fs::create_dir_all(&git_dir).unwrap();
+
+        let included_path = dir.path().join("work.config");
+        fs::write(included_path.as_path(), "[user]\n  email = work@example.com").unwrap();
+        let config_path = dir.path().join("config");
+        let work_dir = escape_backslashes(&dir.path().join("work"));
+        fs::write(
+            config_path.as_path(),
+            format!(
+                r#"
+            [user]
+              email = private@example.com
+            [includeIf "gitdir:{work}/"]
+              path = work.config
+            [includeIf "gitdir:other/"]
+              path = work.config
+            [includeIf "gitdir:./work/*/.git"]
+              path = work.config
+            [includeIf "gitdir/i:{upper_work}/"]
+              path = work.config
+            [includeIf "unknown:anything"]
+              path = work.config"#,
+                work = work_dir,
+                upper_work = work_dir.to_uppercase(),
+            ),
+        )
+        .unwrap();
+
+        let config = GitConfig::from_paths(vec![config_path.clone()], &Default::default()).unwrap();
+        assert_eq!(
+            config.get_raw_multi_value("user", None, "email").unwrap(),
+            vec![Cow::<[u8]>::Borrowed(b"private@example.com")],
+            "gitdir conditions never match without a git dir"
+        );
+
+        let options = from_paths::Options {
+            git_dir: Some(git_dir.as_path()),
+            ..Default::default()
+        };
+        let config = GitConfig::from_paths(vec![config_path.clone()], &options).unwrap();
+        assert_eq!(
+            config.get_raw_multi_value("user", None, "email").unwrap(),
+            vec![
+                Cow::<[u8]>::Borrowed(b"private@example.com"),
+                Cow::Borrowed(b"work@example.com"),
+                Cow::Borrowed(b"work@example.com"),
+                Cow::Borrowed(b"work@example.com"),
+            ],
+            "the absolute, relative and case-insensitive conditions match, the others don't"
+        );
+
+        let other_git_dir = dir.path().join("private").join(".git");
+        let options = from_paths::Options {
+            git_dir: Some(other_git_dir.as_path()),
+            ..Default::default()
+        };
+        let config = GitConfig::from_paths(vec![config_path], &options).unwrap();
+        assert_eq!(
+            config.get_raw_value("user", None, "email"),
+            Ok(Cow::<[u8]>::Borrowed(b"private@example.com"))
+        );
+    }
+
+    #[test]
+    fn conditional_includes_by_branch() {
+        let dir = tempdir().unwrap();
+        let included_path = dir.path().join("feature.config");
+        fs::write(included_path.as_path(), "[core]\n  b = feature").unwrap();
+        let config_path = dir.path().join("config");
+        fs::write(
+            config_path.as_path(),
+            r#"
+            [core]
+              b = default
+            [includeIf "onbranch:feature/"]
+              path = feature.config
+            [includeIf "onbranch:ma*"]
+              path = feature.config"#,
+        )
+        .unwrap();
+
+        for (branch_name, expected) in &[
+            (Some("refs/heads/feature/a/b"), "feature"),
+            (Some("refs/heads/main"), "feature"),
+            (Some("refs/heads/mainline/x"), "default"),
+            (Some("refs/remotes/feature/a"), "default"),
+            (Some("refs/heads/other"), "default"),
+            (None, "default"),
+        ] {
+            let options = from_paths::Options {
+                branch_name: branch_name.map(str::as_bytes),
+                ..Default::default()
+            };
+            let config = GitConfig::from_paths(vec![config_path.clone()], &options).unwrap();
+            assert_eq!(
+                config.get_raw_value("core", None, "b"),
+                Ok(Cow::Borrowed(expected.as_bytes())),
+                "{:?}",
+                branch_name
+            );
+        }
+    }
+
+    #[test]
+    fn conditional_includes_by_remote_url_of_all_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("work.config"), "[user]\n  email = work@example.com").unwrap();
+        let global_path = dir.path().join("global");
+        fs::write(
+            global_path.as_path(),
+            r#"
+            [user]
+              email = me@example.com
+            [includeIf "hasconfig:remote.*.url:https://example.com/work/**"]
+              path = work.config"#,
+        )
+        .unwrap();
+
+        let repo_path = dir.path().join("repo");
+        for (url, expected) in &[
+            ("https://example.com/work/team/project", "work@example.com"),
+            ("https://example.com/private/project", "me@example.com"),
+        ] {
+            fs::write(repo_path.as_path(), format!("[remote \"origin\"]\n  url = {}", url)).unwrap();
+            let config =
+                GitConfig::from_paths(vec![global_path.clone(), repo_path.clone()], &Default::default()).unwrap();
+            assert_eq!(
+                config.get_raw_value("user", None, "email"),
+                Ok(Cow::Borrowed(expected.as_bytes())),
+                "remote urls of files loaded later are used, {}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn remote_urls_in_includes_by_remote_url_are_an_error() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("a.config"),
+            "[remote \"other\"]\n  url = https://example.com/b",
+        )
+        .unwrap();
+        let config_path = dir.path().join("config");
+        fs::write(
+            config_path.as_path(),
+            r#"
+            [remote "origin"]
+              url = https://example.com/a
+            [includeIf "hasconfig:remote.*.url:https://example.com/*"]
+              path = a.config"#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            GitConfig::from_paths(vec![config_path], &Default::default()),
+            Err(from_paths::Error::RemoteUrlInHasconfigInclude { .. })
+        ));
+    }
+
     #[test]
     fn multiple_paths_single_value() {
         let dir = tempdir().unwrap();
@@ -888,6 +1052,73 @@ mod from_env_tests {
         );
         assert_eq!(config.len(), 5);
     }
+
+    #[test]
+    #[serial]
+    fn error_on_keys_without_section_or_name() {
+        for invalid_key in &["core", ".key", "core."] {
+            let _env = Env::new()
+                .set("GIT_CONFIG_COUNT", "1")
+                .set("GIT_CONFIG_KEY_0", invalid_key)
+                .set("GIT_CONFIG_VALUE_0", "value");
+            let err = GitConfig::from_env(&Options::default()).unwrap_err();
+            assert!(
+                matches!(&err, from_env::Error::InvalidKeyValue(0, key) if key == invalid_key),
+                "{:?}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn system_and_global_locations_can_be_overridden_or_disabled() {
+        let dir = tempdir().unwrap();
+        let system_path = dir.path().join("system");
+        fs::write(&system_path, "[core]\nkey = system\nsystem = true").unwrap();
+        let global_path = dir.path().join("global");
+        fs::write(&global_path, "[core]\nkey = global").unwrap();
+
+        let _env = Env::new()
+            .set("GIT_CONFIG_SYSTEM", system_path.to_str().unwrap())
+            .set("GIT_CONFIG_GLOBAL", global_path.to_str().unwrap());
+        let config = GitConfig::from_env_paths(&Options::default()).unwrap();
+        assert_eq!(
+            config.get_raw_multi_value("core", None, "key").unwrap(),
+            vec![Cow::<[u8]>::Borrowed(b"system"), Cow::Borrowed(b"global")]
+        );
+
+        for disabled in &["1", "true", "yes"] {
+            let _env = Env::new().set("GIT_CONFIG_NOSYSTEM", disabled);
+            let config = GitConfig::from_env_paths(&Options::default()).unwrap();
+            assert_eq!(
+                config.get_raw_multi_value("core", None, "key").unwrap(),
+                vec![Cow::<[u8]>::Borrowed(b"global")]
+            );
+            assert!(config.get_raw_value("core", None, "system").is_err());
+        }
+
+        for enabled in &["0", "false", ""] {
+            let _env = Env::new().set("GIT_CONFIG_NOSYSTEM", enabled);
+            let config = GitConfig::from_env_paths(&Options::default()).unwrap();
+            assert_eq!(
+                config.get_raw_value("core", None, "system"),
+                Ok(Cow::<[u8]>::Borrowed(b"true"))
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn non_existing_files_in_the_cascade_are_skipped() {
+        let dir = tempdir().unwrap();
+        let missing_global = dir.path().join("missing");
+        let _env = Env::new()
+            .set("GIT_CONFIG_SYSTEM", "/this/file/does/not/exist")
+            .set("GIT_CONFIG_GLOBAL", missing_global.to_str().unwrap());
+        let config = GitConfig::from_env_paths(&Options::default()).unwrap();
+        assert!(config.is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -943,6 +1174,41 @@ mod get_raw_value {
         );
     }
 
+    #[test]
+    fn section_and_key_names_are_case_insensitive_but_subsections_are_not() {
+        let config =
+            GitConfig::try_from("[CORE]\nA=b\n[remote \"Origin\"]\nurl=c\n[remote \"origin\"]\nurl=d").unwrap();
+        assert_eq!(config.get_raw_value("core", None, "a"), Ok(Cow::<[u8]>::Borrowed(b"b")));
+        assert_eq!(config.get_raw_value("Core", None, "A"), Ok(Cow::<[u8]>::Borrowed(b"b")));
+        assert_eq!(
+            config.get_raw_value("REMOTE", Some("Origin"), "URL"),
+            Ok(Cow::<[u8]>::Borrowed(b"c"))
+        );
+        assert_eq!(
+            config.get_raw_value("remote", Some("origin"), "url"),
+            Ok(Cow::<[u8]>::Borrowed(b"d"))
+        );
+        assert_eq!(
+            config.get_raw_value("remote", Some("ORIGIN"), "url"),
+            Err(GitConfigError::SubSectionDoesNotExist(Some("ORIGIN")))
+        );
+    }
+
+    #[test]
+    fn subsections_in_legacy_syntax_are_case_insensitive() {
+        let config = GitConfig::try_from("[core.Sub]\na=b\n[core \"sub\"]\na=c").unwrap();
+        assert_eq!(
+            config.get_raw_multi_value("core", Some("sub"), "a").unwrap(),
+            vec![Cow::<[u8]>::Borrowed(b"b"), Cow::Borrowed(b"c")],
+            "the deprecated syntax is lower-cased, just like git does"
+        );
+        assert_eq!(
+            config.to_string(),
+            "[core.Sub]\na=b\n[core \"sub\"]\na=c",
+            "the original spelling is retained when writing"
+        );
+    }
+
     #[test]
     fn subsection_must_be_respected() {
         let config = GitConfig::try_from("[core]a=b\n[core.a]a=c").unwrap();
@@ -1077,6 +1343,30 @@ mod get_raw_multi_value {
         );
     }
 
+    #[test]
+    fn multi_value_in_subsections_keeps_order() {
+        let config = GitConfig::try_from(
+            r#"
+            [remote "origin"]
+                url = https://example.com/repo
+                fetch = +refs/heads/*:refs/remotes/origin/*
+            [remote "upstream"]
+                fetch = +refs/heads/*:refs/remotes/upstream/*
+            [remote "origin"]
+                Fetch = +refs/tags/*:refs/tags/*
+                fetch = ^refs/heads/wip"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.get_raw_multi_value("remote", Some("origin"), "fetch").unwrap(),
+            vec![
+                Cow::<[u8]>::Borrowed(b"+refs/heads/*:refs/remotes/origin/*"),
+                Cow::Borrowed(b"+refs/tags/*:refs/tags/*"),
+                Cow::Borrowed(b"^refs/heads/wip"),
+            ]
+        );
+    }
+
     #[test]
     fn non_relevant_subsection_is_ignored() {
         let config = GitConfig::try_from("[core]\na=b\na=c\n[core]a=d\n[core]g=g").unwrap();
@@ -1087,6 +1377,51 @@ mod get_raw_multi_value {
     }
 }
 
+#[cfg(test)]
+mod sections {
+    use git_config::file::GitConfig;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn iteration_is_in_order_of_appearance() {
+        let config = GitConfig::try_from(
+            r#"
+            [remote "b"]
+            [core]
+            [Remote "a"]
+            [remote]
+            [remote "b"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config
+                .sections()
+                .map(|(header, _)| (
+                    header.name.to_string(),
+                    header.subsection_name.as_deref().map(ToOwned::to_owned)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("remote".to_string(), Some("b".to_string())),
+                ("core".into(), None),
+                ("Remote".into(), Some("a".into())),
+                ("remote".into(), None),
+                ("remote".into(), Some("b".into())),
+            ]
+        );
+
+        let subsections: Vec<_> = config
+            .sections_by_name_with_header("REMOTE")
+            .into_iter()
+            .map(|(header, _)| header.subsection_name.as_deref())
+            .collect();
+        assert_eq!(subsections, vec![Some("b"), Some("a"), None, Some("b")]);
+        assert_eq!(config.subsection_names("remote"), vec!["b", "a"]);
+        assert!(config.subsection_names("core").is_empty());
+        assert!(config.subsection_names("missing").is_empty());
+    }
+}
+
 #[cfg(test)]
 mod display {
     use git_config::file::GitConfig;
@@ -1161,3 +1496,311 @@ mod display {
         assert_eq!(GitConfig::try_from(config).unwrap().to_string(), config);
     }
 }
+
+#[cfg(test)]
+mod lossless_writes {
+    use git_config::{file::GitConfig, parser::Key};
+    use std::{borrow::Cow, convert::TryFrom};
+
+    #[test]
+    fn set_value_keeps_whitespace_and_comments() {
+        let mut git_config = GitConfig::try_from("[core]\n    a = b # comment\n    c = d\n").unwrap();
+
+        let mut value = git_config.get_raw_value_mut("core", None, "a").unwrap();
+        value.set_string("hello".into());
+        assert_eq!(git_config.to_string(), "[core]\n    a = hello # comment\n    c = d\n");
+
+        let mut value = git_config.get_raw_value_mut("core", None, "a").unwrap();
+        value.set_string("world".into());
+        assert_eq!(
+            git_config.to_string(),
+            "[core]\n    a = world # comment\n    c = d\n",
+            "repeated changes don't consume the following newline"
+        );
+    }
+
+    #[test]
+    fn set_implicit_value_adds_separator() {
+        let mut git_config = GitConfig::try_from("[core]\n    autocrlf\n    c = d\n").unwrap();
+
+        let mut value = git_config.get_raw_value_mut("core", None, "autocrlf").unwrap();
+        value.set_string("input".into());
+        assert_eq!(git_config.to_string(), "[core]\n    autocrlf=input\n    c = d\n");
+    }
+
+    #[test]
+    fn set_multi_value_keeps_whitespace() {
+        let mut git_config = GitConfig::try_from("[core]\n    a = b ; one\n    a = c\n").unwrap();
+
+        let mut values = git_config.get_raw_multi_value_mut("core", None, "a").unwrap();
+        values.set_string(0, "x".into());
+        values.set_string(1, "y".into());
+        assert_eq!(git_config.to_string(), "[core]\n    a = x ; one\n    a = y\n");
+    }
+
+    #[test]
+    fn section_remove_removes_key_and_value() {
+        let mut git_config = GitConfig::try_from("[core]\n    a = b\n    c = d\n").unwrap();
+
+        let mut section = git_config.section_mut("core", None).unwrap();
+        assert_eq!(section.remove(&Key::from("a")), Some(Cow::Borrowed(&b"b"[..])));
+        assert_eq!(git_config.to_string(), "[core]\n    \n    c = d\n");
+    }
+
+    #[test]
+    fn write_to_path_replaces_file_atomically() -> Result<(), Box<dyn std::error::Error>> {
+        let input = "# global comment\n[core]\n\ta = b ; trailing\n\n[remote \"origin\"]\n\turl = x\n";
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("config");
+        std::fs::write(&path, "old content")?;
+
+        let mut git_config = GitConfig::try_from(input)?;
+        git_config.write_to_path(&path, git_lock::acquire::Fail::Immediately)?;
+        assert_eq!(std::fs::read_to_string(&path)?, input, "unchanged configs round-trip");
+
+        git_config.get_raw_value_mut("core", None, "a")?.set_string("c".into());
+        git_config.write_to_path(&path, git_lock::acquire::Fail::Immediately)?;
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            "# global comment\n[core]\n\ta = c ; trailing\n\n[remote \"origin\"]\n\turl = x\n"
+        );
+        assert!(
+            !dir.path().join("config.lock").exists(),
+            "the lock is gone after commit"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod origin {
+    use git_config::file::{GitConfig, Origin};
+    use std::{borrow::Cow, convert::TryFrom};
+
+    #[test]
+    fn values_know_their_line() {
+        let config = GitConfig::try_from("# comment\n\n[core]\n\ta = b\n\tc = d\\\ne\n[core]\n\ta = f\n").unwrap();
+
+        let entries: Vec<_> = config
+            .entries()
+            .into_iter()
+            .map(|entry| {
+                (
+                    entry.key.to_string(),
+                    entry.value,
+                    entry.origin.map(|origin| origin.line),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("a".into(), Cow::Borrowed(&b"b"[..]), Some(4)),
+                ("c".into(), Cow::Borrowed(&b"de"[..]), Some(5)),
+                ("a".into(), Cow::Borrowed(&b"f"[..]), Some(8)),
+            ]
+        );
+
+        assert_eq!(
+            config.get_raw_value_with_origin("core", None, "a").unwrap(),
+            (Cow::Borrowed(&b"f"[..]), Some(Origin { path: None, line: 8 })),
+            "the last value wins"
+        );
+    }
+
+    #[test]
+    fn programmatically_added_sections_have_no_origin() {
+        let mut config = GitConfig::new();
+        config.new_section("core", None).push("a".into(), Cow::Borrowed(b"b"));
+        assert_eq!(
+            config.get_raw_value_with_origin("core", None, "a").unwrap(),
+            (Cow::Borrowed(&b"b"[..]), None)
+        );
+    }
+
+    #[test]
+    fn files_and_their_includes_are_recorded() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let a_path = dir.path().join("a");
+        let b_path = dir.path().join("b");
+        std::fs::write(&a_path, "[core]\n\ta = 1\n[include]\n\tpath = b\n")?;
+        std::fs::write(&b_path, "\n[core]\n\tb = 2\n")?;
+
+        let config = GitConfig::from_paths(vec![a_path.clone()], &Default::default())?;
+        assert_eq!(
+            config.get_raw_value_with_origin("core", None, "a")?.1,
+            Some(Origin {
+                path: Some(a_path.as_path()),
+                line: 2
+            })
+        );
+        assert_eq!(
+            config.get_raw_value_with_origin("core", None, "b")?.1,
+            Some(Origin {
+                path: Some(b_path.as_path()),
+                line: 3
+            })
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod snapshot {
+    use git_config::file::{GitConfig, Snapshot};
+    use std::{borrow::Cow, convert::TryFrom};
+
+    #[test]
+    fn values_are_looked_up_like_in_the_config() {
+        let config = GitConfig::try_from(
+            "[Core]\n\tBare = false\n[core]\n\tbare = true\n[remote \"Origin\"]\n\turl = a\n[Branch.Main]\n\tremote = origin",
+        )
+        .unwrap();
+        let snapshot = Snapshot::from(&config);
+
+        assert_eq!(snapshot.boolean("core.bare"), Some(Ok(true)), "the last value wins");
+        assert_eq!(
+            snapshot.raw_multi_value("CORE", None, "BARE"),
+            Some(&[b"false".to_vec(), b"true".to_vec()][..]),
+            "section and value names are case-insensitive"
+        );
+        assert_eq!(snapshot.string("remote.Origin.url"), Some(Cow::Borrowed(&b"a"[..])));
+        assert_eq!(
+            snapshot.string("remote.origin.url"),
+            None,
+            "subsections are case-sensitive"
+        );
+        assert_eq!(
+            snapshot.string("branch.main.remote"),
+            config.string("branch.main.remote"),
+            "unless they use the legacy syntax"
+        );
+        assert!(snapshot.integer("core.missing").is_none());
+    }
+
+    #[test]
+    fn is_shareable_across_threads_and_unaffected_by_changes() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let mut config = GitConfig::try_from("[core]\n\tabbrev = 12").unwrap();
+        let snapshot = Snapshot::from(&config);
+        assert_send_sync(&snapshot);
+        config
+            .get_raw_value_mut("core", None, "abbrev")
+            .unwrap()
+            .set_string("8".into());
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let snapshot = snapshot.clone();
+                std::thread::spawn(move || snapshot.integer("core.abbrev").unwrap().unwrap())
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 12);
+        }
+        assert_eq!(config.integer("core.abbrev").unwrap().unwrap(), 8);
+    }
+}
+
+#[cfg(test)]
+mod edit_by_key {
+    use std::{borrow::Cow, convert::TryFrom};
+
+    use git_config::file::{edit::Error, GitConfig};
+
+    #[test]
+    fn set_replaces_the_last_value_or_appends_to_the_last_section() {
+        let mut config = GitConfig::try_from("[core]\n\ta = 1\n\ta = 2\n[remote \"origin\"]\n\turl = x").unwrap();
+        config.set_raw_value_by_key("core.a", b"3").unwrap();
+        config.set_raw_value_by_key("core.b", b"x y ").unwrap();
+        config
+            .set_raw_value_by_key("remote.origin.fetch", b"+refs/heads/*:refs/remotes/origin/*")
+            .unwrap();
+        config.set_raw_value_by_key("user.name", b"a;b\"\n").unwrap();
+        assert_eq!(
+            config.to_string(),
+            "[core]\n\ta = 1\n\ta = 3\n  b=\"x y \"\n[remote \"origin\"]\n\turl = x\n  fetch=+refs/heads/*:refs/remotes/origin/*\n[user]\n  name=\"a;b\\\"\\n\"\n"
+        );
+        assert_eq!(
+            config.string("remote.origin.url"),
+            Some(Cow::Borrowed(&b"x"[..])),
+            "the lookup tree knows about new sections"
+        );
+        assert!(GitConfig::try_from(config.to_string().as_str()).is_ok());
+    }
+
+    #[test]
+    fn set_validates_names() {
+        let mut config = GitConfig::new();
+        assert_eq!(
+            config.set_raw_value_by_key("core", b"x"),
+            Err(Error::InvalidKey("core".into()))
+        );
+        assert_eq!(
+            config.set_raw_value_by_key("core.1a", b"x"),
+            Err(Error::InvalidKey("core.1a".into()))
+        );
+        assert_eq!(
+            config.set_raw_value_by_key("co_re.a", b"x"),
+            Err(Error::InvalidSectionName("co_re".into()))
+        );
+        assert_eq!(
+            config.set_raw_value_by_key("remote.a\"b.url", b"x"),
+            Err(Error::InvalidSubsectionName("a\"b".into()))
+        );
+        assert!(config.is_empty());
+    }
+
+    #[test]
+    fn unset_removes_the_entire_line_of_the_last_value() {
+        let mut config = GitConfig::try_from("[core]\n\ta = 1\n\tb = 2 ; keep\n\ta = 3\n\n\tbare\n").unwrap();
+        assert_eq!(config.unset_by_key("core.a").unwrap(), Some(Cow::Borrowed(&b"3"[..])));
+        assert_eq!(config.to_string(), "[core]\n\ta = 1\n\tb = 2 ; keep\n\n\tbare\n");
+        assert_eq!(config.unset_by_key("core.b").unwrap(), Some(Cow::Borrowed(&b"2"[..])));
+        assert_eq!(config.to_string(), "[core]\n\ta = 1\n; keep\n\n\tbare\n");
+        assert_eq!(config.unset_by_key("core.bare").unwrap(), Some(Cow::Borrowed(&b""[..])));
+        assert_eq!(config.unset_by_key("core.missing").unwrap(), None);
+        assert_eq!(config.unset_by_key("missing.a").unwrap(), None);
+        assert_eq!(config.unset_all_by_key("core.a").unwrap(), 1);
+        assert_eq!(config.to_string(), "[core]\n; keep\n\n");
+    }
+
+    #[test]
+    fn rename_and_remove_sections() {
+        let mut config = GitConfig::try_from(
+            "[remote \"origin\"]\n\turl = a\n[core]\n\tbare = true\n[remote \"origin\"]\n\turl = b\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config
+                .rename_section_by_key("remote.origin", "remote.upstream")
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            config.rename_section_by_key("remote.origin", "remote.other").unwrap(),
+            0
+        );
+        assert_eq!(
+            config.string("remote.upstream.url"),
+            Some(Cow::Borrowed(&b"b"[..])),
+            "renamed sections can be looked up by their new name"
+        );
+        assert!(config.string("remote.origin.url").is_none());
+
+        assert_eq!(config.remove_section_by_key("remote.upstream").unwrap(), 2);
+        assert_eq!(config.to_string(), "[core]\n\tbare = true\n");
+        assert!(config.string("remote.upstream.url").is_none());
+        config.set_raw_value_by_key("remote.upstream.url", b"c").unwrap();
+        assert_eq!(
+            config.to_string(),
+            "[core]\n\tbare = true\n[remote \"upstream\"]\n  url=c\n"
+        );
+        assert_eq!(
+            config.remove_section_by_key("remote."),
+            Ok(0),
+            "empty subsections are valid"
+        );
+    }
+}
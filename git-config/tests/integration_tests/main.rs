@@ -6,6 +6,7 @@
 
 type Result = std::result::Result<(), Box<dyn std::error::Error>>;
 
+mod fs_integration_tests;
 mod git_config_integration_tests;
 mod parser_integration_tests;
 mod value_integration_tests;
@@ -1,6 +1,9 @@
 use std::{borrow::Cow, convert::TryFrom, path::PathBuf};
 
-use git_config::{file::GitConfig, values::*};
+use git_config::{
+    file::GitConfig,
+    values::{path::interpolate, *},
+};
 
 /// Asserts we can cast into all variants of our type
 #[test]
@@ -75,8 +78,12 @@ fn get_value_for_all_provided_values() -> crate::Result {
         "~/tmp".as_bytes(),
         "no interpolation occurs when querying a path due to lack of context"
     );
-    let expected = PathBuf::from(format!("{}/tmp", dirs::home_dir().expect("empty home dir").display()));
-    assert_eq!(actual.interpolate(None).unwrap(), expected);
+    let home = PathBuf::from("/home/user");
+    let context = interpolate::Context {
+        home_dir: Some(&home),
+        ..Default::default()
+    };
+    assert_eq!(actual.interpolate(context).unwrap(), home.join("tmp"));
 
     Ok(())
 }
@@ -136,3 +143,62 @@ fn value_names_are_case_insensitive() -> crate::Result {
 
     Ok(())
 }
+
+#[test]
+fn typed_getters_by_dotted_key() -> crate::Result {
+    let config = r#"
+        [core]
+            bare
+            filemode = off
+            packedGitLimit = 1m
+            bigFileThreshold = 512k
+            excludesFile = ~/.gitignore
+        [remote "origin"]
+            url = https://example.com/repo
+            fetch = +refs/heads/*:refs/remotes/origin/*
+            fetch = +refs/tags/*:refs/tags/*
+        [remote "my.fork"]
+            mirror = 1
+            bogus = maybe
+    "#;
+    let file = GitConfig::try_from(config)?;
+
+    assert_eq!(file.boolean("core.bare"), Some(Ok(true)), "implicit values are true");
+    assert_eq!(file.boolean("CORE.FileMode"), Some(Ok(false)));
+    assert_eq!(
+        file.boolean("remote.my.fork.mirror"),
+        Some(Ok(true)),
+        "subsections may contain dots"
+    );
+    assert!(file.boolean("remote.my.fork.bogus").expect("present").is_err());
+    assert_eq!(file.boolean("core.missing"), None);
+    assert_eq!(file.boolean("core"), None, "incomplete keys are never found");
+
+    assert_eq!(file.integer("core.packedGitLimit").expect("present")?, 1024 * 1024);
+    assert_eq!(file.integer("core.bigFileThreshold").expect("present")?, 512 * 1024);
+    assert!(file.integer("core.filemode").expect("present").is_err());
+
+    assert_eq!(
+        file.string("remote.origin.url").as_deref(),
+        Some(&b"https://example.com/repo"[..])
+    );
+    assert_eq!(
+        file.strings("remote.origin.fetch").expect("present"),
+        vec![
+            Cow::<[u8]>::Borrowed(b"+refs/heads/*:refs/remotes/origin/*"),
+            Cow::Borrowed(b"+refs/tags/*:refs/tags/*"),
+        ]
+    );
+    assert_eq!(file.strings("remote.upstream.fetch"), None);
+
+    let excludes = file.path("core.excludesFile").expect("present");
+    let home = interpolate::home_dir().expect("home dir");
+    assert_eq!(
+        excludes.interpolate(interpolate::Context {
+            home_dir: Some(&home),
+            ..Default::default()
+        })?,
+        home.join(".gitignore")
+    );
+    Ok(())
+}
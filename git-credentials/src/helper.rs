@@ -0,0 +1,248 @@
+use std::{
+    io,
+    process::{Command, Stdio},
+};
+
+use bstr::{BString, ByteSlice, ByteVec};
+use quick_error::quick_error;
+
+use crate::protocol::{self, Context};
+
+quick_error! {
+    /// The error returned by [`invoke()`] and the methods of [`Cascade`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: io::Error) {
+            display("An IO error occurred while communicating with the credentials helper")
+            from()
+            source(err)
+        }
+        Protocol(err: protocol::Error) {
+            display("The message exchanged with the credentials helper was invalid")
+            from()
+            source(err)
+        }
+        HelperFailed { program: BString, code: Option<i32> } {
+            display("Credentials helper '{}' failed with status code {:?}", program, code)
+        }
+        Quit { program: BString } {
+            display("Credentials helper '{}' requested to stop asking for credentials", program)
+        }
+        Prompt(err: io::Error) {
+            display("Could not prompt for credentials")
+            source(err)
+        }
+    }
+}
+
+/// The action a credentials helper is asked to perform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Return credentials matching the given context, if the helper knows any.
+    Get,
+    /// Store the given credentials as they were accepted by the remote.
+    Store,
+    /// Forget the given credentials as they were rejected by the remote.
+    Erase,
+}
+
+impl Action {
+    /// Return the name of the action as passed to helpers.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Get => "get",
+            Action::Store => "store",
+            Action::Erase => "erase",
+        }
+    }
+}
+
+/// A credentials helper program as configured by a value of `credential.helper`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Program {
+    /// A value like `store --file ~/.creds`, running `git credential-store --file ~/.creds`.
+    Builtin(BString),
+    /// A value like `/usr/bin/helper --flag`, running the given absolute path with its arguments.
+    ExternalPath(BString),
+    /// A value like `!f() { echo password=secret; }; f`, running the shell script following the `!`.
+    ExternalShellScript(BString),
+}
+
+impl Program {
+    /// Parse the value of `credential.helper` into the program it refers to.
+    pub fn from_custom_definition(value: impl Into<BString>) -> Self {
+        let value = value.into();
+        if let Some(script) = value.strip_prefix(b"!") {
+            Program::ExternalShellScript(script.into())
+        } else if value.starts_with(b"/") || is_windows_absolute_path(&value) {
+            Program::ExternalPath(value)
+        } else {
+            Program::Builtin(value)
+        }
+    }
+
+    /// Return the shell command invoking this program, without the action.
+    pub fn to_shell_command(&self) -> BString {
+        match self {
+            Program::Builtin(name) => {
+                let mut cmd = BString::from("git credential-");
+                cmd.push_str(name);
+                cmd
+            }
+            Program::ExternalPath(cmd) | Program::ExternalShellScript(cmd) => cmd.clone(),
+        }
+    }
+
+    /// Return a command to run this program with `action` through the shell, just like git does.
+    pub fn to_command(&self, action: Action) -> io::Result<Command> {
+        let script = self.to_shell_command();
+        let script = script
+            .to_str()
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        let mut cmd = Command::new(if cfg!(windows) { "sh.exe" } else { "sh" });
+        cmd.arg("-c")
+            .arg(format!("{} \"$@\"", script))
+            .arg(script)
+            .arg(action.as_str());
+        Ok(cmd)
+    }
+}
+
+fn is_windows_absolute_path(value: &[u8]) -> bool {
+    cfg!(windows) && value.len() > 2 && value[0].is_ascii_alphabetic() && value[1] == b':'
+}
+
+/// Run `program` to perform `action` with `ctx` as input, returning the context it responded with for [`Action::Get`],
+/// or `None` if it didn't respond.
+pub fn invoke(program: &Program, action: Action, ctx: &Context) -> Result<Option<Context>, Error> {
+    let mut child = program
+        .to_command(action)?
+        .stdin(Stdio::piped())
+        .stdout(if action == Action::Get {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .spawn()?;
+    let stdin = child.stdin.take().expect("stdin to be configured");
+    ctx.write_to(stdin)?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::HelperFailed {
+            program: program.to_shell_command(),
+            code: output.status.code(),
+        });
+    }
+    if output.stdout.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Context::from_bytes(&output.stdout)?))
+}
+
+/// All credentials helpers configured for a URL, consulted in order like git does.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cascade {
+    /// The helpers to consult, in order.
+    pub programs: Vec<Program>,
+}
+
+impl Cascade {
+    /// Create a new instance from all values of `credential.helper` in the order they were configured, where an empty
+    /// value clears all helpers configured before it.
+    pub fn from_config_values(values: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        let mut programs = Vec::new();
+        for value in values {
+            let value = value.into();
+            if value.iter().all(u8::is_ascii_whitespace) {
+                programs.clear();
+            } else {
+                programs.push(Program::from_custom_definition(value));
+            }
+        }
+        Cascade { programs }
+    }
+
+    /// Ask all helpers in order to complete the username and password of `ctx` until both are known, and ask the user through
+    /// `prompt(question, is_secret)` for what's still missing afterwards. `is_secret` is true if the answer shouldn't be
+    /// shown while it is typed.
+    ///
+    /// Helpers which fail are skipped, but if one of them responds with `quit`, no further helpers are consulted and an
+    /// error is returned.
+    pub fn fill(
+        &self,
+        mut ctx: Context,
+        mut prompt: impl FnMut(&str, bool) -> io::Result<String>,
+    ) -> Result<Context, Error> {
+        for program in &self.programs {
+            if ctx.is_complete() {
+                break;
+            }
+            let response = match invoke(program, Action::Get, &ctx) {
+                Ok(Some(response)) => response,
+                Ok(None) | Err(_) => continue,
+            };
+            if response.quit {
+                return Err(Error::Quit {
+                    program: program.to_shell_command(),
+                });
+            }
+            ctx.username = response.username.or(ctx.username);
+            ctx.password = response.password.or(ctx.password);
+        }
+
+        let location = match (&ctx.protocol, &ctx.host) {
+            (Some(protocol), Some(host)) => format!("{}://{}", protocol, host),
+            _ => ctx.url.as_ref().map(|url| url.to_string()).unwrap_or_default(),
+        };
+        if ctx.username.is_none() {
+            ctx.username = Some(prompt(&format!("Username for '{}': ", location), false).map_err(Error::Prompt)?);
+        }
+        if ctx.password.is_none() {
+            let question = format!(
+                "Password for '{}@{}': ",
+                ctx.username.as_deref().unwrap_or_default(),
+                location
+            );
+            ctx.password = Some(prompt(&question, true).map_err(Error::Prompt)?);
+        }
+        Ok(ctx)
+    }
+
+    /// Tell all helpers to store the credentials in `ctx`, after they were accepted by the remote.
+    pub fn approve(&self, ctx: &Context) -> Result<(), Error> {
+        self.for_each(Action::Store, ctx)
+    }
+
+    /// Tell all helpers to forget the credentials in `ctx`, after they were rejected by the remote.
+    pub fn reject(&self, ctx: &Context) -> Result<(), Error> {
+        self.for_each(Action::Erase, ctx)
+    }
+
+    fn for_each(&self, action: Action, ctx: &Context) -> Result<(), Error> {
+        for program in &self.programs {
+            invoke(program, action, ctx)?;
+        }
+        Ok(())
+    }
+}
+
+/// Return a prompt for use with [`Cascade::fill()`] which asks the user as configured in `opts`, without showing the answer
+/// while it's typed if it's a secret.
+///
+/// Use `git_prompt::Options::default().apply_environment(core_askpass)` to obtain options like git does, which
+/// prefer askpass programs over prompting on the terminal.
+pub fn prompt<'a>(opts: git_prompt::Options<'a>) -> impl FnMut(&str, bool) -> io::Result<String> + 'a {
+    move |question, is_secret| {
+        let opts = git_prompt::Options {
+            mode: match opts.mode {
+                git_prompt::Mode::Disable => git_prompt::Mode::Disable,
+                git_prompt::Mode::Visible | git_prompt::Mode::Hidden if is_secret => git_prompt::Mode::Hidden,
+                git_prompt::Mode::Visible | git_prompt::Mode::Hidden => git_prompt::Mode::Visible,
+            },
+            askpass: opts.askpass.clone(),
+        };
+        git_prompt::ask(question, &opts).map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+    }
+}
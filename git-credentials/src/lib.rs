@@ -0,0 +1,13 @@
+//! Interact with git credential helpers to obtain, store and erase credentials, as described in `git help credential`.
+//!
+//! The [`protocol`] module serializes and parses the messages exchanged with helpers, while the [`helper`] module
+//! launches the helpers configured with `credential.helper` and combines them into a [`Cascade`][helper::Cascade]
+//! that falls back to prompting the user if no helper can provide the credentials.
+#![forbid(unsafe_code, rust_2018_idioms)]
+#![deny(missing_docs)]
+
+///
+pub mod protocol;
+
+///
+pub mod helper;
@@ -0,0 +1,142 @@
+use std::io;
+
+use bstr::{BString, ByteSlice};
+use quick_error::quick_error;
+
+quick_error! {
+    /// The error returned by [`Context::from_bytes()`] and [`Context::write_to()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Io(err: io::Error) {
+            display("Could not write the credential context")
+            from()
+            source(err)
+        }
+        Encoding { key: &'static str, value: BString } {
+            display("The value of '{}' must not contain newlines or null bytes, got {:?}", key, value)
+        }
+        Syntax(line: BString) {
+            display("Expected a 'key=value' line, got {:?}", line)
+        }
+        Utf8 { key: BString } {
+            display("The value of '{}' is not valid UTF-8", key)
+        }
+    }
+}
+
+/// The information exchanged with credential helpers, with each field corresponding to an attribute of the same name.
+///
+/// Unset fields are neither sent to nor received from helpers.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Context {
+    /// The protocol over which the credential will be used, like `https`.
+    pub protocol: Option<String>,
+    /// The remote hostname for a network credential, including the port number if one was specified, like `example.com:8080`.
+    pub host: Option<String>,
+    /// The path with which the credential will be used, like the repository path on the remote.
+    pub path: Option<BString>,
+    /// The name of the user, if known.
+    pub username: Option<String>,
+    /// The password of the user, if known.
+    pub password: Option<String>,
+    /// The whole URL, which helpers may use instead of `protocol`, `host` and `path`.
+    pub url: Option<BString>,
+    /// If set by a helper, no further helpers are consulted and the operation fails.
+    pub quit: bool,
+}
+
+impl Context {
+    /// Create a new instance from the parts of `url`, leaving the path unset for `http` and `https` URLs, like git
+    /// does unless `credential.useHttpPath` is set.
+    pub fn from_url(url: &git_url::Url) -> Self {
+        let is_http = matches!(url.scheme, git_url::Scheme::Http | git_url::Scheme::Https);
+        Context {
+            protocol: Some(url.scheme.to_string()),
+            host: url.host.as_ref().map(|host| match url.port {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_owned(),
+            }),
+            path: (!is_http && !url.path.is_empty()).then(|| url.path.trim_start_with(|c| c == '/').into()),
+            username: url.user.clone(),
+            password: url.password.clone(),
+            url: None,
+            quit: false,
+        }
+    }
+
+    /// Return true if both the username and the password are set.
+    pub fn is_complete(&self) -> bool {
+        self.username.is_some() && self.password.is_some()
+    }
+
+    /// Write all set fields to `out` as `key=value` lines, followed by an empty line to end the message.
+    pub fn write_to(&self, mut out: impl io::Write) -> Result<(), Error> {
+        let fields: [(&'static str, Option<&[u8]>); 6] = [
+            ("protocol", self.protocol.as_deref().map(str::as_bytes)),
+            ("host", self.host.as_deref().map(str::as_bytes)),
+            ("path", self.path.as_ref().map(|path| path.as_slice())),
+            ("username", self.username.as_deref().map(str::as_bytes)),
+            ("password", self.password.as_deref().map(str::as_bytes)),
+            ("url", self.url.as_ref().map(|url| url.as_slice())),
+        ];
+        for (key, value) in fields
+            .iter()
+            .filter_map(|(key, value)| value.map(|value| (*key, value)))
+        {
+            if value.contains(&b'\n') || value.contains(&0) {
+                return Err(Error::Encoding {
+                    key,
+                    value: value.into(),
+                });
+            }
+            out.write_all(key.as_bytes())?;
+            out.write_all(b"=")?;
+            out.write_all(value)?;
+            out.write_all(b"\n")?;
+        }
+        if self.quit {
+            out.write_all(b"quit=1\n")?;
+        }
+        out.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Return all set fields serialized into a message as written by [`write_to()`][Context::write_to()].
+    pub fn to_bstring(&self) -> Result<BString, Error> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        Ok(buf.into())
+    }
+
+    /// Parse a message of `key=value` lines as produced by credential helpers, which ends with an empty line or the end of
+    /// `input`. Unknown keys are ignored.
+    pub fn from_bytes(input: &[u8]) -> Result<Self, Error> {
+        let mut ctx = Context::default();
+        for line in input.lines() {
+            if line.is_empty() {
+                break;
+            }
+            let pos = line.find_byte(b'=').ok_or_else(|| Error::Syntax(line.into()))?;
+            let (key, value) = (&line[..pos], &line[pos + 1..]);
+            let string = || {
+                value
+                    .to_str()
+                    .map(ToOwned::to_owned)
+                    .map_err(|_| Error::Utf8 { key: key.into() })
+            };
+            match key {
+                b"protocol" => ctx.protocol = Some(string()?),
+                b"host" => ctx.host = Some(string()?),
+                b"path" => ctx.path = Some(value.into()),
+                b"username" => ctx.username = Some(string()?),
+                b"password" => ctx.password = Some(string()?),
+                b"url" => ctx.url = Some(value.into()),
+                b"quit" => ctx.quit = matches!(value, b"1" | b"true" | b"yes" | b"on"),
+                _unknown => {}
+            }
+        }
+        Ok(ctx)
+    }
+}
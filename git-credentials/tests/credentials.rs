@@ -0,0 +1,133 @@
+mod protocol {
+    use git_credentials::protocol::Context;
+
+    #[test]
+    fn round_trip_with_all_fields() {
+        let ctx = Context {
+            protocol: Some("https".into()),
+            host: Some("example.com:8080".into()),
+            path: Some("org/repo.git".into()),
+            username: Some("user".into()),
+            password: Some("secret".into()),
+            url: None,
+            quit: false,
+        };
+        let message = ctx.to_bstring().unwrap();
+        assert_eq!(
+            message,
+            "protocol=https\nhost=example.com:8080\npath=org/repo.git\nusername=user\npassword=secret\n\n"
+        );
+        assert_eq!(Context::from_bytes(&message).unwrap(), ctx);
+    }
+
+    #[test]
+    fn parsing_stops_at_empty_line_and_ignores_unknown_keys() {
+        let ctx = Context::from_bytes(b"username=user\nunknown=value\nquit=1\n\npassword=ignored\n").unwrap();
+        assert_eq!(ctx.username.as_deref(), Some("user"));
+        assert_eq!(ctx.password, None);
+        assert!(ctx.quit);
+    }
+
+    #[test]
+    fn values_with_newlines_cannot_be_written() {
+        let ctx = Context {
+            username: Some("user\nhost=evil.com".into()),
+            ..Default::default()
+        };
+        assert!(ctx.to_bstring().is_err());
+    }
+
+    #[test]
+    fn lines_without_separator_are_rejected() {
+        assert!(Context::from_bytes(b"username\n").is_err());
+    }
+
+    #[test]
+    fn from_url_omits_path_for_http() {
+        let ctx = Context::from_url(&git_url::parse(b"https://user@example.com:8080/org/repo.git").unwrap());
+        assert_eq!(ctx.protocol.as_deref(), Some("https"));
+        assert_eq!(ctx.host.as_deref(), Some("example.com:8080"));
+        assert_eq!(ctx.username.as_deref(), Some("user"));
+        assert_eq!(ctx.path, None);
+
+        let ctx = Context::from_url(&git_url::parse(b"ssh://example.com/org/repo.git").unwrap());
+        assert_eq!(ctx.path.as_ref().map(|p| p.to_string()), Some("org/repo.git".into()));
+    }
+}
+
+mod helper {
+    use git_credentials::helper::{Cascade, Program};
+
+    #[test]
+    fn program_from_custom_definition() {
+        assert_eq!(
+            Program::from_custom_definition("store --file creds"),
+            Program::Builtin("store --file creds".into())
+        );
+        assert_eq!(
+            Program::from_custom_definition("/usr/bin/helper"),
+            Program::ExternalPath("/usr/bin/helper".into())
+        );
+        assert_eq!(
+            Program::from_custom_definition("!f() { echo; }; f"),
+            Program::ExternalShellScript("f() { echo; }; f".into())
+        );
+        assert_eq!(
+            Program::from_custom_definition("store --file creds").to_shell_command(),
+            "git credential-store --file creds"
+        );
+    }
+
+    #[test]
+    fn empty_values_reset_the_cascade() {
+        let cascade = Cascade::from_config_values(vec!["cache", "", "store", "!script"]);
+        assert_eq!(
+            cascade.programs,
+            vec![
+                Program::Builtin("store".into()),
+                Program::ExternalShellScript("script".into())
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fill_consults_helpers_in_order_and_prompts_for_the_rest() {
+        use git_credentials::protocol::Context;
+
+        let cascade = Cascade::from_config_values(vec![
+            "!f() { test \"$1\" = get && echo username=user; }; f",
+            "!f() { echo password=ignored; echo quit=1; }; f",
+        ]);
+        let ctx = Context {
+            protocol: Some("https".into()),
+            host: Some("example.com".into()),
+            ..Default::default()
+        };
+
+        let err = cascade
+            .fill(ctx.clone(), |_, _| unreachable!("not called"))
+            .unwrap_err();
+        assert!(
+            matches!(err, git_credentials::helper::Error::Quit { .. }),
+            "the second helper stops the cascade"
+        );
+
+        let cascade = Cascade {
+            programs: cascade.programs.into_iter().take(1).collect(),
+        };
+        let mut questions = Vec::new();
+        let filled = cascade
+            .fill(ctx, |question, is_secret| {
+                questions.push((question.to_owned(), is_secret));
+                Ok("secret".into())
+            })
+            .unwrap();
+        assert_eq!(filled.username.as_deref(), Some("user"));
+        assert_eq!(filled.password.as_deref(), Some("secret"));
+        assert_eq!(
+            questions,
+            vec![("Password for 'user@https://example.com': ".to_owned(), true)]
+        );
+    }
+}
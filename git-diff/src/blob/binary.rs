@@ -0,0 +1,150 @@
+/// The amount of bytes at the beginning of a blob that are checked by [`is_binary()`], just like git does.
+pub const FIRST_FEW_BYTES: usize = 8000;
+
+/// Return `true` if `data` is considered binary as it contains a null byte within its [first few bytes][FIRST_FEW_BYTES].
+pub fn is_binary(data: &[u8]) -> bool {
+    data[..data.len().min(FIRST_FEW_BYTES)].contains(&0)
+}
+
+/// The payload of one direction of a binary patch, which is either the literal content or a delta against the other side.
+///
+/// Note that the data isn't compressed yet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Patch {
+    /// The complete content of the resulting blob.
+    Literal(Vec<u8>),
+    /// A [delta][delta()] which turns the other side into the resulting blob.
+    Delta(Vec<u8>),
+}
+
+impl Patch {
+    /// Compute the patch to obtain `target` from `base`, which is a delta if it is smaller than `target` itself,
+    /// or `target` as literal otherwise.
+    pub fn compute(base: &[u8], target: &[u8]) -> Self {
+        let mut out = Vec::new();
+        match delta(base, target, target.len().saturating_sub(1), &mut out) {
+            Some(_) if !base.is_empty() => Patch::Delta(out),
+            _ => Patch::Literal(target.to_owned()),
+        }
+    }
+
+    /// Return the data of the literal or the delta.
+    pub fn data(&self) -> &[u8] {
+        match self {
+            Patch::Literal(data) | Patch::Delta(data) => data,
+        }
+    }
+}
+
+/// The amount of bytes used to find matching sections between base and target.
+const BLOCK_SIZE: usize = 16;
+/// The largest amount of bytes a single insert instruction can carry.
+const MAX_INSERT: usize = 0x7f;
+/// The largest amount of bytes a single copy instruction copies, like git which never produces larger ones.
+const MAX_COPY: usize = 0x10000;
+
+/// Write a delta in the format used by packs and binary patches into `out`, which turns `base` into `target` when applied,
+/// and return the amount of bytes written.
+///
+/// Return `None` without writing anything if `base` is too large to be referred to by copy instructions, or if the delta
+/// would be larger than `max_size`.
+/// Matching sections are found by indexing `base` in blocks similar to how git does it, which is why only sections
+/// of at least 16 bytes can be found.
+pub fn delta(base: &[u8], target: &[u8], max_size: usize, out: &mut Vec<u8>) -> Option<usize> {
+    if base.len() > u32::MAX as usize {
+        return None;
+    }
+    let start = out.len();
+    encode_header_size(base.len() as u64, out);
+    encode_header_size(target.len() as u64, out);
+
+    let mut index = std::collections::HashMap::<&[u8], usize>::with_capacity(base.len() / BLOCK_SIZE);
+    for (block_index, block) in base.chunks_exact(BLOCK_SIZE).enumerate().rev() {
+        index.insert(block, block_index * BLOCK_SIZE);
+    }
+
+    let mut pos = 0;
+    let mut insert_start = 0;
+    while pos + BLOCK_SIZE <= target.len() {
+        let base_ofs = match index.get(&target[pos..pos + BLOCK_SIZE]) {
+            Some(ofs) => *ofs,
+            None => {
+                pos += 1;
+                continue;
+            }
+        };
+        let forward = base[base_ofs..]
+            .iter()
+            .zip(&target[pos..])
+            .take_while(|(a, b)| a == b)
+            .count();
+        let backward = base[..base_ofs]
+            .iter()
+            .rev()
+            .zip(target[insert_start..pos].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        encode_insert(&target[insert_start..pos - backward], out);
+        encode_copy(base_ofs - backward, backward + forward, out);
+        pos += forward;
+        insert_start = pos;
+        if out.len() - start > max_size {
+            out.truncate(start);
+            return None;
+        }
+    }
+    encode_insert(&target[insert_start..], out);
+
+    let written = out.len() - start;
+    if written > max_size {
+        out.truncate(start);
+        return None;
+    }
+    Some(written)
+}
+
+fn encode_header_size(mut size: u64, out: &mut Vec<u8>) {
+    loop {
+        let byte = (size & 0x7f) as u8;
+        size >>= 7;
+        if size == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn encode_insert(mut data: &[u8], out: &mut Vec<u8>) {
+    while !data.is_empty() {
+        let len = data.len().min(MAX_INSERT);
+        out.push(len as u8);
+        out.extend_from_slice(&data[..len]);
+        data = &data[len..];
+    }
+}
+
+fn encode_copy(mut ofs: usize, mut size: usize, out: &mut Vec<u8>) {
+    while size != 0 {
+        let len = size.min(MAX_COPY);
+        let cmd_pos = out.len();
+        let mut cmd = 0b1000_0000;
+        out.push(cmd);
+        for (bit, byte) in (ofs as u32).to_le_bytes().iter().enumerate() {
+            if *byte != 0 {
+                cmd |= 1 << bit;
+                out.push(*byte);
+            }
+        }
+        for (bit, byte) in (len as u32).to_le_bytes()[..3].iter().enumerate() {
+            if *byte != 0 {
+                cmd |= 1 << (bit + 4);
+                out.push(*byte);
+            }
+        }
+        out[cmd_pos] = cmd;
+        ofs += len;
+        size -= len;
+    }
+}
@@ -0,0 +1,121 @@
+//! An implementation of the histogram diff algorithm as used by git, which anchors the diff on the longest common section
+//! with the least frequent tokens, and falls back to Myers if all tokens are too frequent.
+use std::collections::HashMap;
+
+use super::myers::{self, common_prefix_len, common_suffix_len, mark};
+
+/// Tokens occurring more often than this on the _before_ side are not used as anchors, just like in git.
+const MAX_CHAIN_LEN: usize = 64;
+
+/// Mark all tokens in `before` that are `removed` and all tokens in `after` that are `added` to obtain `after` from `before`.
+pub fn diff(before: &[u32], after: &[u32], removed: &mut [bool], added: &mut [bool]) {
+    let common_prefix = common_prefix_len(before, after);
+    let (before, after) = (&before[common_prefix..], &after[common_prefix..]);
+    let (removed, added) = (&mut removed[common_prefix..], &mut added[common_prefix..]);
+
+    let common_suffix = common_suffix_len(before, after);
+    let (before, after) = (
+        &before[..before.len() - common_suffix],
+        &after[..after.len() - common_suffix],
+    );
+    let (removed, added) = (&mut removed[..before.len()], &mut added[..after.len()]);
+
+    if before.is_empty() || after.is_empty() {
+        mark(removed);
+        mark(added);
+        return;
+    }
+
+    match find_anchor(before, after) {
+        Anchor::Region {
+            before: (before_start, before_end),
+            after: (after_start, after_end),
+        } => {
+            diff(
+                &before[..before_start],
+                &after[..after_start],
+                &mut removed[..before_start],
+                &mut added[..after_start],
+            );
+            diff(
+                &before[before_end..],
+                &after[after_end..],
+                &mut removed[before_end..],
+                &mut added[after_end..],
+            );
+        }
+        Anchor::None => {
+            mark(removed);
+            mark(added);
+        }
+        Anchor::TooFrequent => myers::diff(before, after, removed, added),
+    }
+}
+
+enum Anchor {
+    /// The common section to anchor on, as start and end positions on each side.
+    Region {
+        before: (usize, usize),
+        after: (usize, usize),
+    },
+    /// There are no common tokens.
+    None,
+    /// All common tokens occur too often to be used as anchor.
+    TooFrequent,
+}
+
+/// Find the longest common section whose least frequent token is the least frequent among all common sections.
+fn find_anchor(before: &[u32], after: &[u32]) -> Anchor {
+    let mut positions = HashMap::<u32, Vec<usize>>::new();
+    for (pos, token) in before.iter().enumerate() {
+        positions.entry(*token).or_default().push(pos);
+    }
+
+    let mut too_frequent = false;
+    let mut best: Option<(usize, (usize, usize), (usize, usize))> = None;
+    let mut after_pos = 0;
+    while after_pos < after.len() {
+        let mut next_after_pos = after_pos + 1;
+        let candidates = match positions.get(&after[after_pos]) {
+            Some(candidates) if candidates.len() > MAX_CHAIN_LEN => {
+                too_frequent = true;
+                None
+            }
+            candidates => candidates,
+        };
+        for &before_pos in candidates.into_iter().flatten() {
+            let backward = before[..before_pos]
+                .iter()
+                .rev()
+                .zip(after[..after_pos].iter().rev())
+                .take_while(|(a, b)| a == b)
+                .count();
+            let forward = before[before_pos..]
+                .iter()
+                .zip(&after[after_pos..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            let region_before = (before_pos - backward, before_pos + forward);
+            let region_after = (after_pos - backward, after_pos + forward);
+            let count = before[region_before.0..region_before.1]
+                .iter()
+                .map(|token| positions[token].len())
+                .min()
+                .expect("regions are never empty");
+            let is_better = best.map_or(true, |(best_count, best_before, _)| {
+                count < best_count || (count == best_count && forward + backward > best_before.1 - best_before.0)
+            });
+            if is_better {
+                best = Some((count, region_before, region_after));
+                next_after_pos = next_after_pos.max(region_after.1);
+            }
+        }
+        after_pos = next_after_pos;
+    }
+
+    match best {
+        Some((_, before, after)) => Anchor::Region { before, after },
+        None if too_frequent => Anchor::TooFrequent,
+        None => Anchor::None,
+    }
+}
@@ -0,0 +1,109 @@
+use std::ops::Range;
+
+use git_object::bstr::ByteSlice;
+
+use crate::blob::{self, Algorithm, Hunk};
+
+/// The units within lines that are compared by [`ranges()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Granularity {
+    /// Compare runs of alphanumeric characters and `_`, runs of whitespace, and each other character on its own.
+    Words,
+    /// Compare each character, or each sequence of bytes that isn't valid UTF-8.
+    Characters,
+}
+
+impl Default for Granularity {
+    fn default() -> Self {
+        Granularity::Words
+    }
+}
+
+/// A contiguous set of changed bytes, identified by byte ranges in the _before_ and _after_ line.
+///
+/// Empty ranges mark the position at which bytes were inserted into or removed from the respective line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Change {
+    /// The bytes of _before_ that were removed or replaced.
+    pub before: Range<usize>,
+    /// The bytes of _after_ that were added or replace the bytes in `before`.
+    pub after: Range<usize>,
+}
+
+/// Return the pairs of line indices of _before_ and _after_ which are considered to be modified versions of each other in `hunk`.
+///
+/// Lines are paired in order, so lines at the end of the longer side of the hunk have no counterpart and are entirely changed.
+pub fn line_pairs(hunk: &Hunk) -> impl Iterator<Item = (u32, u32)> {
+    hunk.before.clone().zip(hunk.after.clone())
+}
+
+/// Compute the ranges of bytes that changed between the `before` and `after` version of a line at the given `granularity`,
+/// using `algorithm`.
+///
+/// This is useful to highlight the changes within lines that were paired with [`line_pairs()`].
+/// The returned changes are ordered and don't overlap.
+pub fn ranges(algorithm: Algorithm, before: &[u8], after: &[u8], granularity: Granularity) -> Vec<Change> {
+    let before_tokens = tokens(before, granularity);
+    let after_tokens = tokens(after, granularity);
+    let to_bytes = |data: &[u8], tokens: &[Range<usize>], range: Range<u32>| -> Range<usize> {
+        let start = tokens.get(range.start as usize).map_or(data.len(), |token| token.start);
+        if range.is_empty() {
+            start..start
+        } else {
+            start..tokens[range.end as usize - 1].end
+        }
+    };
+    blob::diff_sequences(
+        algorithm,
+        &before_tokens
+            .iter()
+            .map(|token| &before[token.clone()])
+            .collect::<Vec<_>>(),
+        &after_tokens
+            .iter()
+            .map(|token| &after[token.clone()])
+            .collect::<Vec<_>>(),
+    )
+    .into_iter()
+    .map(|hunk| Change {
+        before: to_bytes(before, &before_tokens, hunk.before),
+        after: to_bytes(after, &after_tokens, hunk.after),
+    })
+    .collect()
+}
+
+/// Split `data` into the byte ranges of its tokens at `granularity`.
+fn tokens(data: &[u8], granularity: Granularity) -> Vec<Range<usize>> {
+    #[derive(PartialEq, Eq)]
+    enum Class {
+        Word,
+        Whitespace,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Whitespace
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens: Vec<Range<usize>> = Vec::new();
+    let mut previous_class = None;
+    for (start, end, c) in data.char_indices() {
+        match granularity {
+            Granularity::Characters => tokens.push(start..end),
+            Granularity::Words => {
+                let class = class(c);
+                match tokens.last_mut() {
+                    Some(token) if class != Class::Other && previous_class.as_ref() == Some(&class) => token.end = end,
+                    _ => tokens.push(start..end),
+                }
+                previous_class = Some(class);
+            }
+        }
+    }
+    tokens
+}
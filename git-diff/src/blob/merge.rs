@@ -0,0 +1,392 @@
+use std::ops::Range;
+
+use git_object::bstr::BStr;
+
+use crate::blob::{self, Algorithm, Hunk};
+
+/// How conflicts are presented by [`text()`], similar to `merge.conflictStyle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConflictStyle {
+    /// Show our and their lines of a conflict, after splitting it at lines both sides have in common.
+    Merge,
+    /// Show our and their lines of a conflict along with the lines they are based on.
+    Diff3,
+    /// Like [`Diff3`][ConflictStyle::Diff3], but lines both sides have in common at the beginning and end of a conflict
+    /// are moved out of it.
+    ZealousDiff3,
+}
+
+impl Default for ConflictStyle {
+    fn default() -> Self {
+        ConflictStyle::Merge
+    }
+}
+
+impl ConflictStyle {
+    /// Parse the style from a value of the `merge.conflictStyle` configuration key, or return `None` if it is unknown.
+    pub fn from_config_value(value: &[u8]) -> Option<Self> {
+        Some(match value {
+            b"merge" => ConflictStyle::Merge,
+            b"diff3" => ConflictStyle::Diff3,
+            b"zdiff3" => ConflictStyle::ZealousDiff3,
+            _ => return None,
+        })
+    }
+}
+
+/// Which lines to use for conflicts instead of writing conflict markers, similar to `git merge-file --ours|--theirs|--union`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Favor {
+    /// Use our lines.
+    Ours,
+    /// Use their lines.
+    Theirs,
+    /// Use our lines followed by their lines.
+    Union,
+}
+
+/// Configure how [`text()`] merges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The algorithm to use for finding the changed lines of each side.
+    pub algorithm: Algorithm,
+    /// How to present conflicts.
+    pub style: ConflictStyle,
+    /// The amount of characters of each conflict marker, similar to the `conflict-marker-size` attribute.
+    pub marker_size: usize,
+    /// If set, conflicts are resolved by choosing lines from one or both sides instead of writing conflict markers.
+    pub favor: Option<Favor>,
+}
+
+impl Default for Options {
+    /// Use the Myers algorithm and write conflict markers with 7 characters in the `merge` style, just like git does by default.
+    fn default() -> Self {
+        Options {
+            algorithm: Algorithm::Myers,
+            style: ConflictStyle::Merge,
+            marker_size: 7,
+            favor: None,
+        }
+    }
+}
+
+/// The names of the sides to show next to conflict markers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Labels<'a> {
+    /// The label of the base, shown only with the [`Diff3`][ConflictStyle::Diff3] styles.
+    pub base: Option<&'a BStr>,
+    /// The label of our side.
+    pub ours: Option<&'a BStr>,
+    /// The label of their side.
+    pub theirs: Option<&'a BStr>,
+}
+
+/// The result of [`text()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// The merged content, possibly with conflict markers.
+    pub data: Vec<u8>,
+    /// The amount of conflicts marked in `data`, which is `0` if the merge is clean.
+    pub conflicts: usize,
+}
+
+/// A part of the merged lines, with ranges of lines into the respective side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Chunk {
+    /// Lines that are the same on our and their side.
+    Unchanged { ours: Range<usize>, theirs: Range<usize> },
+    /// Lines that changed only on our side, or the same way on both sides.
+    Ours(Range<usize>),
+    /// Lines that changed only on their side.
+    Theirs(Range<usize>),
+    /// Lines that changed differently on both sides.
+    Conflict {
+        base: Range<usize>,
+        ours: Range<usize>,
+        theirs: Range<usize>,
+    },
+}
+
+/// Merge the changes of `ours` and `theirs` relative to their common `base` line by line, and mark conflicting changes
+/// using `labels` like `git merge-file` does.
+///
+/// Changes of both sides conflict if they touch or overlap in `base` and aren't the same. All content is treated as text.
+pub fn text(base: &[u8], ours: &[u8], theirs: &[u8], labels: Labels<'_>, options: Options) -> Outcome {
+    let base: Vec<_> = blob::lines(base).collect();
+    let ours: Vec<_> = blob::lines(ours).collect();
+    let theirs: Vec<_> = blob::lines(theirs).collect();
+    let mut chunks = chunks(
+        &base,
+        &ours,
+        &theirs,
+        &blob::diff_sequences(options.algorithm, &base, &ours),
+        &blob::diff_sequences(options.algorithm, &base, &theirs),
+    );
+    match options.style {
+        ConflictStyle::Merge => {
+            chunks = split_conflicts(chunks, &ours, &theirs, options.algorithm);
+            chunks = join_close_conflicts(chunks);
+        }
+        ConflictStyle::ZealousDiff3 => chunks = shrink_conflicts(chunks, &ours, &theirs),
+        ConflictStyle::Diff3 => {}
+    }
+
+    let mut out = Outcome {
+        data: Vec::new(),
+        conflicts: 0,
+    };
+    let data = &mut out.data;
+    for chunk in chunks {
+        match chunk {
+            Chunk::Unchanged { ours: lines, .. } | Chunk::Ours(lines) => write_lines(data, &ours[lines]),
+            Chunk::Theirs(lines) => write_lines(data, &theirs[lines]),
+            Chunk::Conflict {
+                base: base_lines,
+                ours: ours_lines,
+                theirs: theirs_lines,
+            } => match options.favor {
+                Some(Favor::Ours) => write_lines(data, &ours[ours_lines]),
+                Some(Favor::Theirs) => write_lines(data, &theirs[theirs_lines]),
+                Some(Favor::Union) => {
+                    write_conflict_side(data, &ours[ours_lines]);
+                    write_lines(data, &theirs[theirs_lines]);
+                }
+                None => {
+                    out.conflicts += 1;
+                    write_marker(data, b'<', options.marker_size, labels.ours);
+                    write_conflict_side(data, &ours[ours_lines]);
+                    if options.style != ConflictStyle::Merge {
+                        write_marker(data, b'|', options.marker_size, labels.base);
+                        write_conflict_side(data, &base[base_lines]);
+                    }
+                    write_marker(data, b'=', options.marker_size, None);
+                    write_conflict_side(data, &theirs[theirs_lines]);
+                    write_marker(data, b'>', options.marker_size, labels.theirs);
+                }
+            },
+        }
+    }
+    out
+}
+
+/// Combine the hunks of both sides into chunks, with hunks that touch or overlap in `base` forming a single chunk.
+fn chunks(base: &[&[u8]], ours: &[&[u8]], theirs: &[&[u8]], ours_hunks: &[Hunk], theirs_hunks: &[Hunk]) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let (mut ours_idx, mut theirs_idx) = (0, 0);
+    let (mut ours_offset, mut theirs_offset) = (0isize, 0isize);
+    let mut base_pos = 0;
+    let unchanged = |chunks: &mut Vec<Chunk>, lines: Range<usize>, ours_offset: isize, theirs_offset: isize| {
+        if !lines.is_empty() {
+            chunks.push(Chunk::Unchanged {
+                ours: shift(&lines, ours_offset),
+                theirs: shift(&lines, theirs_offset),
+            });
+        }
+    };
+    loop {
+        let start = match (ours_hunks.get(ours_idx), theirs_hunks.get(theirs_idx)) {
+            (Some(ours), Some(theirs)) => ours.before.start.min(theirs.before.start),
+            (Some(hunk), None) | (None, Some(hunk)) => hunk.before.start,
+            (None, None) => break,
+        } as usize;
+        let mut end = start;
+        let (ours_start, theirs_start) = (ours_idx, theirs_idx);
+        loop {
+            let previous = (ours_idx, theirs_idx);
+            while let Some(hunk) = ours_hunks
+                .get(ours_idx)
+                .filter(|hunk| hunk.before.start as usize <= end)
+            {
+                end = end.max(hunk.before.end as usize);
+                ours_idx += 1;
+            }
+            while let Some(hunk) = theirs_hunks
+                .get(theirs_idx)
+                .filter(|hunk| hunk.before.start as usize <= end)
+            {
+                end = end.max(hunk.before.end as usize);
+                theirs_idx += 1;
+            }
+            if previous == (ours_idx, theirs_idx) {
+                break;
+            }
+        }
+
+        unchanged(&mut chunks, base_pos..start, ours_offset, theirs_offset);
+        let ours_lines = side_lines(start..end, &ours_hunks[ours_start..ours_idx], &mut ours_offset);
+        let theirs_lines = side_lines(start..end, &theirs_hunks[theirs_start..theirs_idx], &mut theirs_offset);
+        chunks.push(match (ours_idx > ours_start, theirs_idx > theirs_start) {
+            (true, false) => Chunk::Ours(ours_lines),
+            (false, true) => Chunk::Theirs(theirs_lines),
+            _ if ours[ours_lines.clone()] == theirs[theirs_lines.clone()] => Chunk::Ours(ours_lines),
+            _ => Chunk::Conflict {
+                base: start..end,
+                ours: ours_lines,
+                theirs: theirs_lines,
+            },
+        });
+        base_pos = end;
+    }
+    unchanged(&mut chunks, base_pos..base.len(), ours_offset, theirs_offset);
+    chunks
+}
+
+/// Return the lines of a side corresponding to the `base` lines which are changed by `hunks`, and adjust the `offset`
+/// between lines of the base and the side accordingly.
+fn side_lines(base: Range<usize>, hunks: &[Hunk], offset: &mut isize) -> Range<usize> {
+    let start = shift(&(base.start..base.start), *offset).start;
+    *offset += hunks
+        .iter()
+        .map(|hunk| hunk.after.len() as isize - hunk.before.len() as isize)
+        .sum::<isize>();
+    start..shift(&(base.end..base.end), *offset).end
+}
+
+fn shift(lines: &Range<usize>, offset: isize) -> Range<usize> {
+    (lines.start as isize + offset) as usize..(lines.end as isize + offset) as usize
+}
+
+/// Split conflicts into smaller ones at the lines both sides have in common, like git does with `XDL_MERGE_ZEALOUS`.
+fn split_conflicts(chunks: Vec<Chunk>, ours: &[&[u8]], theirs: &[&[u8]], algorithm: Algorithm) -> Vec<Chunk> {
+    let mut out = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let (base_lines, ours_lines, theirs_lines) = match chunk {
+            Chunk::Conflict { base, ours, theirs } if !ours.is_empty() && !theirs.is_empty() => (base, ours, theirs),
+            chunk => {
+                out.push(chunk);
+                continue;
+            }
+        };
+        let (mut ours_pos, mut theirs_pos) = (ours_lines.start, theirs_lines.start);
+        for hunk in blob::diff_sequences(algorithm, &ours[ours_lines.clone()], &theirs[theirs_lines.clone()]) {
+            let ours_hunk = shift(
+                &(hunk.before.start as usize..hunk.before.end as usize),
+                ours_lines.start as isize,
+            );
+            let theirs_hunk = shift(
+                &(hunk.after.start as usize..hunk.after.end as usize),
+                theirs_lines.start as isize,
+            );
+            if ours_pos < ours_hunk.start {
+                out.push(Chunk::Unchanged {
+                    ours: ours_pos..ours_hunk.start,
+                    theirs: theirs_pos..theirs_hunk.start,
+                });
+            }
+            ours_pos = ours_hunk.end;
+            theirs_pos = theirs_hunk.end;
+            out.push(Chunk::Conflict {
+                base: base_lines.clone(),
+                ours: ours_hunk,
+                theirs: theirs_hunk,
+            });
+        }
+        if ours_pos < ours_lines.end {
+            out.push(Chunk::Unchanged {
+                ours: ours_pos..ours_lines.end,
+                theirs: theirs_pos..theirs_lines.end,
+            });
+        }
+    }
+    out
+}
+
+/// Join conflicts that are separated by no more than 3 unchanged lines, as these are easier to resolve as a whole.
+fn join_close_conflicts(chunks: Vec<Chunk>) -> Vec<Chunk> {
+    let mut out: Vec<Chunk> = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        if let Chunk::Conflict { base, ours, theirs } = &chunk {
+            let previous_conflict_idx = match out.as_slice() {
+                [.., Chunk::Conflict { .. }] => Some(out.len() - 1),
+                [.., Chunk::Conflict { .. }, Chunk::Unchanged { ours, .. }] if ours.len() <= 3 => Some(out.len() - 2),
+                _ => None,
+            };
+            if let Some(idx) = previous_conflict_idx {
+                out.truncate(idx + 1);
+                if let Some(Chunk::Conflict {
+                    base: previous_base,
+                    ours: previous_ours,
+                    theirs: previous_theirs,
+                }) = out.last_mut()
+                {
+                    previous_base.end = previous_base.end.max(base.end);
+                    previous_ours.end = ours.end;
+                    previous_theirs.end = theirs.end;
+                }
+                continue;
+            }
+        }
+        out.push(chunk);
+    }
+    out
+}
+
+/// Move lines both sides have in common at the beginning and end of each conflict out of it.
+fn shrink_conflicts(chunks: Vec<Chunk>, ours: &[&[u8]], theirs: &[&[u8]]) -> Vec<Chunk> {
+    let mut out = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let (base_lines, mut ours_lines, mut theirs_lines) = match chunk {
+            Chunk::Conflict { base, ours, theirs } => (base, ours, theirs),
+            chunk => {
+                out.push(chunk);
+                continue;
+            }
+        };
+        let prefix = ours[ours_lines.clone()]
+            .iter()
+            .zip(&theirs[theirs_lines.clone()])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix > 0 {
+            out.push(Chunk::Unchanged {
+                ours: ours_lines.start..ours_lines.start + prefix,
+                theirs: theirs_lines.start..theirs_lines.start + prefix,
+            });
+            ours_lines.start += prefix;
+            theirs_lines.start += prefix;
+        }
+        let suffix = ours[ours_lines.clone()]
+            .iter()
+            .rev()
+            .zip(theirs[theirs_lines.clone()].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        ours_lines.end -= suffix;
+        theirs_lines.end -= suffix;
+        out.push(Chunk::Conflict {
+            base: base_lines,
+            ours: ours_lines.clone(),
+            theirs: theirs_lines.clone(),
+        });
+        if suffix > 0 {
+            out.push(Chunk::Unchanged {
+                ours: ours_lines.end..ours_lines.end + suffix,
+                theirs: theirs_lines.end..theirs_lines.end + suffix,
+            });
+        }
+    }
+    out
+}
+
+fn write_lines(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    for line in lines {
+        out.extend_from_slice(line);
+    }
+}
+
+/// Write `lines` as one side of a conflict, adding a newline if the last one doesn't have one so markers start on their own line.
+fn write_conflict_side(out: &mut Vec<u8>, lines: &[&[u8]]) {
+    write_lines(out, lines);
+    if !lines.is_empty() && !out.ends_with(b"\n") {
+        out.push(b'\n');
+    }
+}
+
+fn write_marker(out: &mut Vec<u8>, marker: u8, size: usize, label: Option<&BStr>) {
+    out.extend(std::iter::repeat(marker).take(size));
+    if let Some(label) = label {
+        out.push(b' ');
+        out.extend_from_slice(label);
+    }
+    out.push(b'\n');
+}
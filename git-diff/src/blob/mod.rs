@@ -0,0 +1,179 @@
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use git_object::bstr::ByteSlice;
+
+mod histogram;
+mod myers;
+mod patience;
+
+/// Binary content detection and deltas between binary blobs.
+pub mod binary;
+
+/// Changes within modified lines, for highlighting them.
+pub mod intra_line;
+
+/// Three-way merges of text with conflict markers.
+pub mod merge;
+
+/// The algorithm to use when computing the changed lines between two blobs, similar to `diff.algorithm`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Algorithm {
+    /// The Myers algorithm, which produces a minimal set of changes. It's the default, just like in git.
+    Myers,
+    /// The patience algorithm, which anchors the diff on lines that occur only once on both sides, like function signatures.
+    Patience,
+    /// The histogram algorithm, an extension of the patience algorithm which also anchors on lines that occur rarely.
+    Histogram,
+}
+
+impl Default for Algorithm {
+    fn default() -> Self {
+        Algorithm::Myers
+    }
+}
+
+impl Algorithm {
+    /// Parse the algorithm from a value of the `diff.algorithm` configuration key, or return `None` if it is unknown.
+    ///
+    /// Note that `minimal` maps to [`Myers`][Algorithm::Myers] as our implementation always produces a minimal diff.
+    pub fn from_config_value(value: &[u8]) -> Option<Self> {
+        Some(match value {
+            b"default" | b"myers" | b"minimal" => Algorithm::Myers,
+            b"patience" => Algorithm::Patience,
+            b"histogram" => Algorithm::Histogram,
+            _ => return None,
+        })
+    }
+}
+
+/// The kind of change a [`Hunk`] represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Kind {
+    /// Lines were added to _after_, none were removed from _before_.
+    Addition,
+    /// Lines were removed from _before_, none were added to _after_.
+    Deletion,
+    /// Lines of _before_ were replaced with lines in _after_.
+    Modification,
+}
+
+/// A contiguous set of changed lines, identified by 0-based line ranges in _before_ and _after_.
+///
+/// Empty ranges mark the position at which lines were inserted into or removed from the respective side.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The lines of _before_ that were removed or replaced.
+    pub before: Range<u32>,
+    /// The lines of _after_ that were added or replace the lines in `before`.
+    pub after: Range<u32>,
+}
+
+impl Hunk {
+    /// Return the kind of change this hunk represents.
+    pub fn kind(&self) -> Kind {
+        if self.before.is_empty() {
+            Kind::Addition
+        } else if self.after.is_empty() {
+            Kind::Deletion
+        } else {
+            Kind::Modification
+        }
+    }
+}
+
+/// Split `data` into lines, each of which includes its line terminator if present.
+pub fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.lines_with_terminator()
+}
+
+/// Compute the hunks needed to transform the lines of the `before` blob into the lines of the `after` blob using `algorithm`.
+///
+/// Lines are compared including their line terminator, so a changed line ending is a changed line.
+/// The returned hunks are ordered and don't overlap.
+pub fn diff(algorithm: Algorithm, before: &[u8], after: &[u8]) -> Vec<Hunk> {
+    diff_sequences(
+        algorithm,
+        &lines(before).collect::<Vec<_>>(),
+        &lines(after).collect::<Vec<_>>(),
+    )
+}
+
+/// The result of [`diff_content()`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    /// At least one side is binary, so no lines were compared.
+    Binary,
+    /// Both sides are text, with the hunks of changed lines between them.
+    Text(Vec<Hunk>),
+}
+
+/// Compute the hunks needed to transform `before` into `after` with `algorithm` like [`diff()`], unless the content is binary.
+///
+/// `diff_attribute` is the state of the `diff` attribute of the path that changed, which marks the content as binary if unset,
+/// or as text if set. Otherwise the content is binary if [either side looks binary][binary::is_binary()].
+pub fn diff_content(
+    algorithm: Algorithm,
+    before: &[u8],
+    after: &[u8],
+    diff_attribute: git_attributes::State<'_>,
+) -> Outcome {
+    let is_binary = match diff_attribute {
+        git_attributes::State::Unset => true,
+        git_attributes::State::Set => false,
+        git_attributes::State::Value(_) | git_attributes::State::Unspecified => {
+            binary::is_binary(before) || binary::is_binary(after)
+        }
+    };
+    if is_binary {
+        Outcome::Binary
+    } else {
+        Outcome::Text(diff(algorithm, before, after))
+    }
+}
+
+/// Compute the hunks needed to transform the `before` sequence of tokens into the `after` sequence using `algorithm`.
+///
+/// Tokens are typically lines, as obtained by [`lines()`], but could also be words or bytes.
+pub fn diff_sequences<'a, T: Hash + Eq>(algorithm: Algorithm, before: &'a [T], after: &'a [T]) -> Vec<Hunk> {
+    let mut interned = HashMap::<&'a T, u32>::new();
+    let mut intern = |token: &'a T| {
+        let next_id = interned.len() as u32;
+        *interned.entry(token).or_insert(next_id)
+    };
+    let before: Vec<_> = before.iter().map(&mut intern).collect();
+    let after: Vec<_> = after.iter().map(&mut intern).collect();
+
+    let mut removed = vec![false; before.len()];
+    let mut added = vec![false; after.len()];
+    match algorithm {
+        Algorithm::Myers => myers::diff(&before, &after, &mut removed, &mut added),
+        Algorithm::Patience => patience::diff(&before, &after, &mut removed, &mut added),
+        Algorithm::Histogram => histogram::diff(&before, &after, &mut removed, &mut added),
+    }
+    hunks(&removed, &added)
+}
+
+/// Pair up the unchanged lines of both sides and collect the changed lines between them into hunks.
+fn hunks(removed: &[bool], added: &[bool]) -> Vec<Hunk> {
+    let mut out = Vec::new();
+    let (mut before, mut after) = (0, 0);
+    while before < removed.len() || after < added.len() {
+        let (before_start, after_start) = (before, after);
+        while removed.get(before).copied().unwrap_or(false) {
+            before += 1;
+        }
+        while added.get(after).copied().unwrap_or(false) {
+            after += 1;
+        }
+        if before != before_start || after != after_start {
+            out.push(Hunk {
+                before: before_start as u32..before as u32,
+                after: after_start as u32..after as u32,
+            });
+        } else {
+            before += 1;
+            after += 1;
+        }
+    }
+    out
+}
@@ -0,0 +1,161 @@
+//! An implementation of the linear-space variant of the Myers diff algorithm as described in
+//! "An O(ND) Difference Algorithm and Its Variations", which finds the middle snake of the edit graph and recurses into both halves.
+use std::ops::{Index, IndexMut};
+
+/// Mark all tokens in `before` that are `removed` and all tokens in `after` that are `added` to obtain `after` from `before`.
+pub fn diff(before: &[u32], after: &[u32], removed: &mut [bool], added: &mut [bool]) {
+    let max_d = max_d(before.len(), after.len());
+    let mut forward = V::new(max_d);
+    let mut backward = V::new(max_d);
+    conquer(before, 0, after, 0, &mut forward, &mut backward, removed, added);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn conquer(
+    mut before: &[u32],
+    mut before_start: usize,
+    mut after: &[u32],
+    mut after_start: usize,
+    forward: &mut V,
+    backward: &mut V,
+    removed: &mut [bool],
+    added: &mut [bool],
+) {
+    let common_prefix = common_prefix_len(before, after);
+    before = &before[common_prefix..];
+    after = &after[common_prefix..];
+    before_start += common_prefix;
+    after_start += common_prefix;
+
+    let common_suffix = common_suffix_len(before, after);
+    before = &before[..before.len() - common_suffix];
+    after = &after[..after.len() - common_suffix];
+
+    if before.is_empty() || after.is_empty() {
+        mark(&mut removed[before_start..][..before.len()]);
+        mark(&mut added[after_start..][..after.len()]);
+        return;
+    }
+
+    match find_middle_snake(before, after, forward, backward) {
+        Some((x, y)) => {
+            conquer(
+                &before[..x],
+                before_start,
+                &after[..y],
+                after_start,
+                forward,
+                backward,
+                removed,
+                added,
+            );
+            conquer(
+                &before[x..],
+                before_start + x,
+                &after[y..],
+                after_start + y,
+                forward,
+                backward,
+                removed,
+                added,
+            );
+        }
+        None => {
+            mark(&mut removed[before_start..][..before.len()]);
+            mark(&mut added[after_start..][..after.len()]);
+        }
+    }
+}
+
+/// Return the point in the edit graph of `before` and `after` at which the shortest edit script can be split in two halves.
+fn find_middle_snake(before: &[u32], after: &[u32], forward: &mut V, backward: &mut V) -> Option<(usize, usize)> {
+    let n = before.len();
+    let m = after.len();
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+    forward[1] = 0;
+    backward[1] = 0;
+
+    for d in 0..max_d(n, m) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && forward[k - 1] < forward[k + 1]) {
+                forward[k + 1]
+            } else {
+                forward[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += common_prefix_len(&before[x..], &after[y..]);
+            }
+            forward[k] = x;
+            if odd && (k - delta).abs() <= d - 1 && forward[k] + backward[-(k - delta)] >= n {
+                return Some((x0, y0));
+            }
+        }
+
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && backward[k - 1] < backward[k + 1]) {
+                backward[k + 1]
+            } else {
+                backward[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let common_suffix = common_suffix_len(&before[..n - x], &after[..m - y]);
+                x += common_suffix;
+                y += common_suffix;
+            }
+            backward[k] = x;
+            if !odd && (k - delta).abs() <= d && backward[k] + forward[-(k - delta)] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+    None
+}
+
+fn max_d(n: usize, m: usize) -> usize {
+    (n + m + 1) / 2 + 1
+}
+
+pub fn mark(flags: &mut [bool]) {
+    flags.iter_mut().for_each(|flag| *flag = true);
+}
+
+pub fn common_prefix_len(a: &[u32], b: &[u32]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+pub fn common_suffix_len(a: &[u32], b: &[u32]) -> usize {
+    a.iter().rev().zip(b.iter().rev()).take_while(|(a, b)| a == b).count()
+}
+
+/// The furthest reaching x coordinates per diagonal `k`, which may be negative.
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> Self {
+        V {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d + 1],
+        }
+    }
+}
+
+impl Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &Self::Output {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for V {
+    fn index_mut(&mut self, k: isize) -> &mut Self::Output {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
@@ -0,0 +1,95 @@
+//! An implementation of the patience diff algorithm, which anchors the diff on lines that are unique on both sides
+//! and falls back to Myers for sections without such lines.
+use std::collections::HashMap;
+
+use super::myers::{self, common_prefix_len, common_suffix_len, mark};
+
+/// Mark all tokens in `before` that are `removed` and all tokens in `after` that are `added` to obtain `after` from `before`.
+pub fn diff(before: &[u32], after: &[u32], removed: &mut [bool], added: &mut [bool]) {
+    let common_prefix = common_prefix_len(before, after);
+    let (before, after) = (&before[common_prefix..], &after[common_prefix..]);
+    let (removed, added) = (&mut removed[common_prefix..], &mut added[common_prefix..]);
+
+    let common_suffix = common_suffix_len(before, after);
+    let (before, after) = (
+        &before[..before.len() - common_suffix],
+        &after[..after.len() - common_suffix],
+    );
+    let (removed, added) = (&mut removed[..before.len()], &mut added[..after.len()]);
+
+    if before.is_empty() || after.is_empty() {
+        mark(removed);
+        mark(added);
+        return;
+    }
+
+    let anchors = unique_common_subsequence(before, after);
+    if anchors.is_empty() {
+        myers::diff(before, after, removed, added);
+        return;
+    }
+
+    let (mut before_start, mut after_start) = (0, 0);
+    for (before_end, after_end) in anchors.into_iter().chain(Some((before.len(), after.len()))) {
+        diff(
+            &before[before_start..before_end],
+            &after[after_start..after_end],
+            &mut removed[before_start..before_end],
+            &mut added[after_start..after_end],
+        );
+        before_start = before_end + 1;
+        after_start = after_end + 1;
+    }
+}
+
+/// Return the positions of the longest sequence of tokens that occur exactly once on both sides and in the same order.
+fn unique_common_subsequence(before: &[u32], after: &[u32]) -> Vec<(usize, usize)> {
+    let mut occurrences = HashMap::<u32, Occurrences>::new();
+    for (pos, token) in before.iter().enumerate() {
+        let entry = occurrences.entry(*token).or_default();
+        entry.before += 1;
+        entry.before_pos = pos;
+    }
+    for (pos, token) in after.iter().enumerate() {
+        if let Some(entry) = occurrences.get_mut(token) {
+            entry.after += 1;
+            entry.after_pos = pos;
+        }
+    }
+    let mut unique: Vec<(usize, usize)> = occurrences
+        .values()
+        .filter(|o| o.before == 1 && o.after == 1)
+        .map(|o| (o.before_pos, o.after_pos))
+        .collect();
+    unique.sort_unstable();
+
+    // Patience sorting: find the longest increasing subsequence of positions in `after`.
+    let mut pile_tops: Vec<usize> = Vec::new();
+    let mut predecessors: Vec<Option<usize>> = Vec::with_capacity(unique.len());
+    for (idx, (_, after_pos)) in unique.iter().enumerate() {
+        let pile = pile_tops.partition_point(|&top| unique[top].1 < *after_pos);
+        predecessors.push(pile.checked_sub(1).map(|previous| pile_tops[previous]));
+        if pile == pile_tops.len() {
+            pile_tops.push(idx);
+        } else {
+            pile_tops[pile] = idx;
+        }
+    }
+
+    let mut out = Vec::with_capacity(pile_tops.len());
+    let mut cursor = pile_tops.last().copied();
+    while let Some(idx) = cursor {
+        out.push(unique[idx]);
+        cursor = predecessors[idx];
+    }
+    out.reverse();
+    out
+}
+
+#[derive(Default)]
+struct Occurrences {
+    before: usize,
+    before_pos: usize,
+    after: usize,
+    after_pos: usize,
+}
@@ -4,3 +4,12 @@
 
 ///
 pub mod tree;
+
+///
+pub mod blob;
+
+///
+pub mod patch;
+
+///
+pub mod stat;
@@ -0,0 +1,411 @@
+use std::path::Path;
+
+use git_object::tree::EntryMode;
+use quick_error::quick_error;
+
+use crate::{
+    blob,
+    patch::parse::{File, Hunk, Line, Rewrite},
+};
+
+quick_error! {
+    /// The error returned by [`file()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Binary {
+            display("Binary patches can't be applied")
+        }
+        SourceMissing {
+            display("The patch changes a file that doesn't exist")
+        }
+        AlreadyExists {
+            display("The patch creates a file that already exists")
+        }
+        DeletionLeavesContent {
+            display("The patch deletes a file but doesn't remove all of its content")
+        }
+    }
+}
+
+/// Configure how hunks are applied by [`file()`] and [`hunks()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The maximal amount of context lines at the beginning and the end of a hunk that may be ignored if the hunk doesn't
+    /// apply otherwise, similar to the fuzz factor of `patch --fuzz=<n>`.
+    pub fuzz: u32,
+    /// If `true`, hunks without leading or trailing context may apply anywhere, which is needed for patches created
+    /// with `git diff -U0`, similar to `git apply --unidiff-zero`.
+    pub unidiff_zero: bool,
+}
+
+impl Default for Options {
+    /// Require all context lines to match, and hunks without leading or trailing context to apply at the beginning or end,
+    /// just like `git apply` does by default.
+    fn default() -> Self {
+        Options {
+            fuzz: 0,
+            unidiff_zero: false,
+        }
+    }
+}
+
+/// The result of applying the hunks of a [`File`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outcome {
+    /// The content after applying all hunks that applied, or `None` if the file was deleted.
+    pub data: Option<Vec<u8>>,
+    /// The indices of the hunks that couldn't be applied, which is empty if the patch applied cleanly.
+    pub rejected: Vec<usize>,
+}
+
+/// Apply the hunks of `patch` to the `before` content of its source, which is `None` if the file doesn't exist.
+///
+/// Changes to paths and modes don't affect the content and are left to the caller, who can find them in `patch`,
+/// or use [`worktree()`] to apply them to the files of a work tree.
+/// Files the patch deletes must be empty once all of its hunks were applied.
+pub fn file(patch: &File, before: Option<&[u8]>, options: Options) -> Result<Outcome, Error> {
+    if patch.binary {
+        return Err(Error::Binary);
+    }
+    let before = match (patch.source_path.is_some(), before) {
+        (true, None) => return Err(Error::SourceMissing),
+        (false, Some(_)) => return Err(Error::AlreadyExists),
+        (_, before) => before.unwrap_or_default(),
+    };
+    let (data, rejected) = hunks(before, &patch.hunks, options);
+    let data = if patch.path.is_none() && rejected.is_empty() {
+        if !data.is_empty() {
+            return Err(Error::DeletionLeavesContent);
+        }
+        None
+    } else {
+        Some(data)
+    };
+    Ok(Outcome { data, rejected })
+}
+
+/// Apply all `files` of a patch to the work tree at `root` and return the outcome of each of them, in order.
+///
+/// Besides applying hunks like [`file()`] does, files are created, deleted, renamed or copied from their source, and their
+/// executable bit is set according to their mode, or kept if the patch doesn't change it. Files turning into symbolic links
+/// are created as such on unix, using their content as target, and as regular files elsewhere.
+///
+/// Like `git apply`, the work tree is only changed if all hunks of all files apply. Otherwise it is left untouched and
+/// the outcomes are returned so the caller can see which hunks were rejected.
+pub fn worktree(files: &[File], root: &Path, options: Options) -> Result<Vec<Outcome>, worktree::Error> {
+    let mut outcomes = Vec::with_capacity(files.len());
+    let mut modes = Vec::with_capacity(files.len());
+    for patch in files {
+        let display_path = patch
+            .path
+            .as_ref()
+            .or(patch.source_path.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let source = patch
+            .source_path
+            .as_ref()
+            .map(|p| worktree::path(root, p))
+            .transpose()?;
+        let destination = patch.path.as_ref().map(|p| worktree::path(root, p)).transpose()?;
+        if patch.rewrite.is_some() && destination.as_ref().map_or(false, |d| d.symlink_metadata().is_ok()) {
+            return Err(worktree::Error::Apply {
+                path: display_path,
+                err: Error::AlreadyExists,
+            });
+        }
+        let existing = match source.as_ref().or(destination.as_ref()) {
+            Some(path) => worktree::read(path)?,
+            None => None,
+        };
+        let outcome = file(patch, existing.as_ref().map(|(data, _)| data.as_slice()), options).map_err(|err| {
+            worktree::Error::Apply {
+                path: display_path.clone(),
+                err,
+            }
+        })?;
+        let mode = patch
+            .mode
+            .or(patch.source_mode)
+            .or_else(|| existing.as_ref().map(|(_, mode)| *mode))
+            .unwrap_or(EntryMode::Blob);
+        if matches!(mode, EntryMode::Tree | EntryMode::Commit) {
+            return Err(worktree::Error::UnsupportedMode { path: display_path });
+        }
+        outcomes.push(outcome);
+        modes.push(mode);
+    }
+    if outcomes.iter().any(|outcome| !outcome.rejected.is_empty()) {
+        return Ok(outcomes);
+    }
+
+    for patch in files.iter().filter(|patch| patch.rewrite != Some(Rewrite::Copy)) {
+        if let Some(source) = &patch.source_path {
+            worktree::remove(root, &worktree::path(root, source)?)?;
+        }
+    }
+    for ((patch, outcome), mode) in files.iter().zip(&outcomes).zip(modes) {
+        if let (Some(path), Some(data)) = (&patch.path, &outcome.data) {
+            worktree::write(&worktree::path(root, path)?, data, mode)?;
+        }
+    }
+    Ok(outcomes)
+}
+
+///
+pub mod worktree {
+    use std::{
+        io,
+        path::{Component, Path, PathBuf},
+    };
+
+    use git_object::{
+        bstr::{BString, ByteSlice, ByteVec},
+        tree::EntryMode,
+    };
+    use quick_error::quick_error;
+
+    quick_error! {
+        /// The error returned by [`worktree()`][super::worktree()].
+        #[derive(Debug)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            Apply { path: BString, err: super::Error } {
+                display("Could not apply the patch of '{}'", path)
+                source(err)
+            }
+            InvalidPath { path: BString } {
+                display("The path '{}' doesn't point into the work tree", path)
+            }
+            UnsupportedMode { path: BString } {
+                display("The patch turns '{}' into a directory or submodule, which isn't supported", path)
+            }
+            Io(err: io::Error) {
+                display("Could not read or write a file in the work tree")
+                from()
+                source(err)
+            }
+        }
+    }
+
+    /// Turn the relative `path` of a patch into a path within `root`, rejecting anything that could point outside of it.
+    pub(crate) fn path(root: &Path, path: &BString) -> Result<PathBuf, Error> {
+        let invalid = || Error::InvalidPath { path: path.to_owned() };
+        let relative = path.to_path().map_err(|_| invalid())?;
+        if relative.as_os_str().is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(invalid());
+        }
+        Ok(root.join(relative))
+    }
+
+    /// Return the content and mode of the file at `path`, or `None` if it doesn't exist.
+    pub(crate) fn read(path: &Path) -> io::Result<Option<(Vec<u8>, EntryMode)>> {
+        let meta = match std::fs::symlink_metadata(path) {
+            Ok(meta) => meta,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        if meta.file_type().is_symlink() {
+            let target = Vec::from_path_buf(std::fs::read_link(path)?)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "symlink target isn't valid UTF-8"))?;
+            return Ok(Some((target, EntryMode::Link)));
+        }
+        #[cfg(unix)]
+        let is_executable = {
+            use std::os::unix::fs::PermissionsExt;
+            meta.permissions().mode() & 0o100 == 0o100
+        };
+        #[cfg(not(unix))]
+        let is_executable = false;
+        let mode = if is_executable {
+            EntryMode::BlobExecutable
+        } else {
+            EntryMode::Blob
+        };
+        Ok(Some((std::fs::read(path)?, mode)))
+    }
+
+    /// Remove the file at `path` along with all directories between it and `root` that became empty.
+    pub(crate) fn remove(root: &Path, path: &Path) -> io::Result<()> {
+        std::fs::remove_file(path)?;
+        for dir in path.ancestors().skip(1).take_while(|dir| *dir != root) {
+            if std::fs::remove_dir(dir).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Create a file with `data` and `mode` at `path`, which must not exist, along with all leading directories.
+    pub(crate) fn write(
+        path: &Path,
+        data: &[u8],
+        #[cfg_attr(not(unix), allow(unused_variables))] mode: EntryMode,
+    ) -> io::Result<()> {
+        use std::io::Write;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        #[cfg(unix)]
+        if mode == EntryMode::Link {
+            let target = data
+                .to_path()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "symlink target isn't valid UTF-8"))?;
+            return std::os::unix::fs::symlink(target, path);
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        if mode == EntryMode::BlobExecutable {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o777);
+        }
+        options.open(path)?.write_all(data)
+    }
+}
+
+/// Apply `hunks` to `before` in order and return the resulting content, along with the indices of all hunks that
+/// couldn't be applied.
+///
+/// A hunk applies if its context and removed lines are found verbatim, preferring the location closest to the one in its header
+/// after adjusting it by the offset of the previously applied hunk. Hunks without leading or trailing context only apply at the
+/// beginning or end of `before` respectively, unless their context was reduced by [fuzz][Options::fuzz] or
+/// [`unidiff_zero`][Options::unidiff_zero] is set.
+pub fn hunks(before: &[u8], hunks: &[Hunk], options: Options) -> (Vec<u8>, Vec<usize>) {
+    let lines: Vec<_> = blob::lines(before).collect();
+    let mut out = Vec::with_capacity(before.len());
+    let mut rejected = Vec::new();
+    let mut pos = 0;
+    let mut offset = 0isize;
+    for (idx, hunk) in hunks.iter().enumerate() {
+        match find_hunk(&lines, pos, offset, hunk, options) {
+            Some(found) => {
+                for line in &lines[pos..found.start] {
+                    out.extend_from_slice(line);
+                }
+                for line in found.lines.iter().filter(|line| !matches!(line, Line::Removal(_))) {
+                    out.extend_from_slice(line.data());
+                }
+                pos = found.start + found.len;
+                offset = found.start as isize - found.expected as isize;
+            }
+            None => rejected.push(idx),
+        }
+    }
+    for line in &lines[pos..] {
+        out.extend_from_slice(line);
+    }
+    (out, rejected)
+}
+
+/// The location of a hunk, which might have been reduced by fuzz.
+struct Found<'a> {
+    /// The lines of the hunk that were matched, without context lines that were ignored due to fuzz.
+    lines: &'a [Line],
+    /// The index of the first line of `lines` in the source.
+    start: usize,
+    /// The amount of lines in the source that `lines` replace.
+    len: usize,
+    /// The index at which `lines` were expected to start according to the hunk header.
+    expected: usize,
+}
+
+fn find_hunk<'a>(lines: &[&[u8]], min: usize, offset: isize, hunk: &'a Hunk, options: Options) -> Option<Found<'a>> {
+    let leading_context = hunk
+        .lines
+        .iter()
+        .take_while(|line| matches!(line, Line::Context(_)))
+        .count();
+    let trailing_context = hunk
+        .lines
+        .iter()
+        .rev()
+        .take_while(|line| matches!(line, Line::Context(_)))
+        .count()
+        .min(hunk.lines.len() - leading_context);
+    let has_changes = leading_context < hunk.lines.len();
+
+    let mut previous_reduction = None;
+    for fuzz in 0..=options.fuzz as usize {
+        let (leading_fuzz, trailing_fuzz) = (fuzz.min(leading_context), fuzz.min(trailing_context));
+        if previous_reduction == Some((leading_fuzz, trailing_fuzz)) {
+            break;
+        }
+        previous_reduction = Some((leading_fuzz, trailing_fuzz));
+
+        let hunk_lines = &hunk.lines[leading_fuzz..hunk.lines.len() - trailing_fuzz];
+        let preimage: Vec<&[u8]> = hunk_lines
+            .iter()
+            .filter(|line| !matches!(line, Line::Addition(_)))
+            .map(|line| &**line.data())
+            .collect();
+        let header_start = if hunk.before_len == 0 {
+            hunk.before_start as usize
+        } else {
+            (hunk.before_start as usize).saturating_sub(1)
+        };
+        let expected = header_start + leading_fuzz;
+        let at_beginning =
+            leading_fuzz == 0 && (hunk.before_start == 0 || (hunk.before_start == 1 && !options.unidiff_zero));
+        let at_end = trailing_fuzz == 0 && !options.unidiff_zero && has_changes && trailing_context == 0;
+        if let Some(start) = find_lines(
+            lines,
+            &preimage,
+            (expected as isize + offset).max(0) as usize,
+            min,
+            at_beginning,
+            at_end,
+        ) {
+            return Some(Found {
+                lines: hunk_lines,
+                start,
+                len: preimage.len(),
+                expected,
+            });
+        }
+    }
+    None
+}
+
+/// Find `needle` in `lines` at an index of at least `min`, choosing the one closest to `expected`.
+/// If `at_beginning` or `at_end` is set, it must match at the beginning or end of `lines` respectively.
+fn find_lines(
+    lines: &[&[u8]],
+    needle: &[&[u8]],
+    expected: usize,
+    min: usize,
+    at_beginning: bool,
+    at_end: bool,
+) -> Option<usize> {
+    let max = lines.len().checked_sub(needle.len())?;
+    if min > max {
+        return None;
+    }
+    let matches_at = |start: usize| lines[start..start + needle.len()] == *needle;
+    if at_beginning {
+        return if min == 0 && (!at_end || max == 0) && matches_at(0) {
+            Some(0)
+        } else {
+            None
+        };
+    }
+    if at_end {
+        return if matches_at(max) { Some(max) } else { None };
+    }
+    let expected = expected.max(min).min(max);
+    for distance in 0.. {
+        let forward = expected + distance;
+        let backward = expected.checked_sub(distance).filter(|start| *start >= min);
+        if forward > max && backward.is_none() {
+            break;
+        }
+        if let Some(start) = backward.filter(|start| matches_at(*start)) {
+            return Some(start);
+        }
+        if forward <= max && matches_at(forward) {
+            return Some(forward);
+        }
+    }
+    None
+}
@@ -0,0 +1,346 @@
+use std::io;
+
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BStr, BString},
+    tree::EntryMode,
+};
+use quick_error::quick_error;
+
+use crate::{
+    blob::{self, binary, Algorithm, Hunk},
+    tree::{recorder, rewrites},
+};
+
+///
+pub mod apply;
+///
+pub mod parse;
+
+quick_error! {
+    /// The error returned by [`write_change()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound { oid: ObjectId } {
+            display("The blob {} to diff was not found in the database", oid)
+        }
+        Io(err: io::Error) {
+            display("Could not write the patch")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// Configure how patches are written by [`write_change()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Options {
+    /// The amount of unchanged lines to show around changed lines, similar to `-U<n>` and `diff.context`.
+    pub context_lines: u32,
+    /// The algorithm to use for finding changed lines, similar to `diff.algorithm`.
+    pub algorithm: Algorithm,
+    /// The amount of hexadecimal characters of object ids shown in `index` lines, similar to `--abbrev=<n>`.
+    ///
+    /// Note that unlike git, ids aren't made longer if they are ambiguous.
+    pub abbrev: usize,
+}
+
+impl Default for Options {
+    /// Show 3 lines of context and abbreviate ids to 7 characters, using the Myers algorithm, just like git does by default.
+    fn default() -> Self {
+        Options {
+            context_lines: 3,
+            algorithm: Algorithm::Myers,
+            abbrev: 7,
+        }
+    }
+}
+
+/// One side of a change.
+pub(crate) struct Side<'a> {
+    pub mode: EntryMode,
+    pub oid: ObjectId,
+    pub path: &'a BStr,
+}
+
+impl<'a> Side<'a> {
+    fn new(mode: &EntryMode, oid: &ObjectId, path: &'a BString) -> Self {
+        Side {
+            mode: *mode,
+            oid: *oid,
+            path: path.as_ref(),
+        }
+    }
+}
+
+/// Write `change` as `diff --git` patch to `out` like `git diff` does, using `find(object_id, &mut buf) -> Option<BlobRef>`
+/// to obtain the content of blobs.
+///
+/// Changes to trees aren't written, as only the changes of the entries they contain show up in patches.
+/// Changed submodules are shown as change of their commit, and binary content is only mentioned but not shown.
+///
+/// Note that paths aren't quoted, which is why patches of paths with special characters differ from the ones git produces.
+pub fn write_change<FindFn>(
+    out: &mut impl io::Write,
+    change: &rewrites::Change,
+    options: Options,
+    mut find: FindFn,
+) -> Result<(), Error>
+where
+    FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+{
+    let (before, after, rewrite) = sides(change);
+    if before.iter().chain(after.iter()).any(|side| side.mode.is_tree()) {
+        return Ok(());
+    }
+
+    let before_path = before
+        .as_ref()
+        .or_else(|| after.as_ref())
+        .expect("one side is set")
+        .path;
+    let after_path = after
+        .as_ref()
+        .or_else(|| before.as_ref())
+        .expect("one side is set")
+        .path;
+    writeln!(out, "diff --git a/{} b/{}", before_path, after_path)?;
+    match (&before, &after) {
+        (None, Some(after)) => writeln!(out, "new file mode {:06o}", after.mode as u16)?,
+        (Some(before), None) => writeln!(out, "deleted file mode {:06o}", before.mode as u16)?,
+        (Some(before), Some(after)) if before.mode != after.mode => {
+            writeln!(out, "old mode {:06o}", before.mode as u16)?;
+            writeln!(out, "new mode {:06o}", after.mode as u16)?;
+        }
+        _ => {}
+    }
+    if let Some((kind, similarity)) = rewrite {
+        writeln!(out, "similarity index {}%", (similarity * 100.0).floor() as u32)?;
+        writeln!(out, "{} from {}", kind, before_path)?;
+        writeln!(out, "{} to {}", kind, after_path)?;
+    }
+
+    let null = |side: &Side<'_>| side.oid.kind().null_ref().to_owned();
+    let before_id = before
+        .as_ref()
+        .map_or_else(|| null(after.as_ref().expect("set")), |s| s.oid);
+    let after_id = after
+        .as_ref()
+        .map_or_else(|| null(before.as_ref().expect("set")), |s| s.oid);
+    if before_id == after_id {
+        return Ok(());
+    }
+    write!(
+        out,
+        "index {}..{}",
+        before_id.to_hex_with_len(options.abbrev),
+        after_id.to_hex_with_len(options.abbrev)
+    )?;
+    match (&before, &after) {
+        (Some(before), Some(after)) if before.mode == after.mode => writeln!(out, " {:06o}", after.mode as u16)?,
+        _ => writeln!(out)?,
+    }
+
+    let mut before_buf = Vec::new();
+    let mut after_buf = Vec::new();
+    let before_data = content(before.as_ref(), &mut before_buf, &mut find).map_err(|oid| Error::NotFound { oid })?;
+    let after_data = content(after.as_ref(), &mut after_buf, &mut find).map_err(|oid| Error::NotFound { oid })?;
+    let before_label = before
+        .as_ref()
+        .map_or_else(|| "/dev/null".into(), |s| format!("a/{}", s.path));
+    let after_label = after
+        .as_ref()
+        .map_or_else(|| "/dev/null".into(), |s| format!("b/{}", s.path));
+
+    if binary::is_binary(before_data) || binary::is_binary(after_data) {
+        writeln!(out, "Binary files {} and {} differ", before_label, after_label)?;
+        return Ok(());
+    }
+    let hunks = blob::diff(options.algorithm, before_data, after_data);
+    if hunks.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "--- {}", before_label)?;
+    writeln!(out, "+++ {}", after_label)?;
+    write_hunks(out, before_data, after_data, &hunks, options.context_lines)?;
+    Ok(())
+}
+
+/// Return the sides of `change`, along with the kind of rewrite and its similarity if it is a rename or copy.
+#[allow(clippy::type_complexity)]
+pub(crate) fn sides(change: &rewrites::Change) -> (Option<Side<'_>>, Option<Side<'_>>, Option<(&'static str, f32)>) {
+    match change {
+        rewrites::Change::Recorded(recorder::Change::Addition { entry_mode, oid, path }) => {
+            (None, Some(Side::new(entry_mode, oid, path)), None)
+        }
+        rewrites::Change::Recorded(recorder::Change::Deletion { entry_mode, oid, path }) => {
+            (Some(Side::new(entry_mode, oid, path)), None, None)
+        }
+        rewrites::Change::Recorded(recorder::Change::Modification {
+            previous_entry_mode,
+            previous_oid,
+            entry_mode,
+            oid,
+            path,
+        }) => (
+            Some(Side::new(previous_entry_mode, previous_oid, path)),
+            Some(Side::new(entry_mode, oid, path)),
+            None,
+        ),
+        rewrites::Change::Rename {
+            source_entry_mode,
+            source_oid,
+            source_path,
+            entry_mode,
+            oid,
+            path,
+            similarity,
+        } => (
+            Some(Side::new(source_entry_mode, source_oid, source_path)),
+            Some(Side::new(entry_mode, oid, path)),
+            Some(("rename", *similarity)),
+        ),
+        rewrites::Change::Copy {
+            source_entry_mode,
+            source_oid,
+            source_path,
+            entry_mode,
+            oid,
+            path,
+            similarity,
+        } => (
+            Some(Side::new(source_entry_mode, source_oid, source_path)),
+            Some(Side::new(entry_mode, oid, path)),
+            Some(("copy", *similarity)),
+        ),
+    }
+}
+
+/// Return the content of `side`, which is empty if there is no such side, or a description of the commit of submodules.
+///
+/// Return the id of the blob that couldn't be found on error.
+pub(crate) fn content<'a, FindFn>(
+    side: Option<&Side<'_>>,
+    buf: &'a mut Vec<u8>,
+    find: &mut FindFn,
+) -> Result<&'a [u8], ObjectId>
+where
+    FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+{
+    match side {
+        None => Ok(&[]),
+        Some(side) if side.mode == EntryMode::Commit => {
+            buf.clear();
+            buf.extend_from_slice(format!("Subproject commit {}\n", side.oid).as_bytes());
+            Ok(buf.as_slice())
+        }
+        Some(side) => find(&side.oid, buf).map(|blob| blob.data).ok_or(side.oid),
+    }
+}
+
+/// Write the `hunks` computed between `before` and `after` in the unified format to `out`, along with `context_lines`
+/// of unchanged lines around them.
+///
+/// Hunks whose context would touch or overlap are merged, and the hunk header shows the closest preceding line
+/// that starts with a letter, `_` or `$`, just like git does without a configured diff driver.
+pub fn write_hunks(
+    out: &mut impl io::Write,
+    before: &[u8],
+    after: &[u8],
+    hunks: &[Hunk],
+    context_lines: u32,
+) -> io::Result<()> {
+    let before: Vec<_> = blob::lines(before).collect();
+    let after: Vec<_> = blob::lines(after).collect();
+    let context = context_lines as usize;
+
+    let mut remaining = hunks;
+    while let Some(first) = remaining.first() {
+        let group_len = 1 + remaining
+            .windows(2)
+            .take_while(|pair| (pair[1].before.start - pair[0].before.end) as usize <= 2 * context)
+            .count();
+        let (group, rest) = remaining.split_at(group_len);
+        remaining = rest;
+        let last = group.last().expect("groups are never empty");
+
+        let leading = context.min(first.before.start as usize);
+        let trailing = context.min(before.len() - last.before.end as usize);
+        let before_start = first.before.start as usize - leading;
+        let before_end = last.before.end as usize + trailing;
+        let after_start = first.after.start as usize - leading;
+        let after_end = last.after.end as usize + trailing;
+
+        write!(
+            out,
+            "@@ -{} +{} @@",
+            LineRange(before_start, before_end - before_start),
+            LineRange(after_start, after_end - after_start)
+        )?;
+        match before[..before_start].iter().rev().find_map(|line| function_name(line)) {
+            Some(name) => {
+                out.write_all(b" ")?;
+                out.write_all(name)?;
+                writeln!(out)?;
+            }
+            None => writeln!(out)?,
+        }
+
+        let mut pos = before_start;
+        for hunk in group {
+            for line in &before[pos..hunk.before.start as usize] {
+                write_line(out, b' ', line)?;
+            }
+            for line in &before[hunk.before.start as usize..hunk.before.end as usize] {
+                write_line(out, b'-', line)?;
+            }
+            for line in &after[hunk.after.start as usize..hunk.after.end as usize] {
+                write_line(out, b'+', line)?;
+            }
+            pos = hunk.before.end as usize;
+        }
+        for line in &before[pos..before_end] {
+            write_line(out, b' ', line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Displays a line range in a hunk header, with `start` being 0-based.
+struct LineRange(usize, usize);
+
+impl std::fmt::Display for LineRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let LineRange(start, count) = *self;
+        match count {
+            0 => write!(f, "{},0", start),
+            1 => write!(f, "{}", start + 1),
+            _ => write!(f, "{},{}", start + 1, count),
+        }
+    }
+}
+
+/// Return `line` without trailing whitespace, truncated to 80 bytes, if it looks like the beginning of a function.
+fn function_name(line: &[u8]) -> Option<&[u8]> {
+    match line.first() {
+        Some(b) if b.is_ascii_alphabetic() || *b == b'_' || *b == b'$' => {
+            let line = &line[..line.len().min(80)];
+            let end = line
+                .iter()
+                .rposition(|b| !b.is_ascii_whitespace())
+                .map_or(0, |pos| pos + 1);
+            Some(&line[..end])
+        }
+        _ => None,
+    }
+}
+
+fn write_line(out: &mut impl io::Write, prefix: u8, line: &[u8]) -> io::Result<()> {
+    out.write_all(&[prefix])?;
+    out.write_all(line)?;
+    if !line.ends_with(b"\n") {
+        out.write_all(b"\n\\ No newline at end of file\n")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,317 @@
+use std::convert::TryFrom;
+
+use git_object::{
+    bstr::{BStr, BString, ByteSlice},
+    tree::EntryMode,
+};
+use quick_error::quick_error;
+
+quick_error! {
+    /// The error returned by [`files()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Syntax { line: usize, message: &'static str } {
+            display("Could not parse the patch at line {}: {}", line, message)
+        }
+    }
+}
+
+/// A line of a [`Hunk`], including its line terminator unless it's the last line of a blob without a trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Line {
+    /// A line that is expected to be present before and after applying the hunk.
+    Context(BString),
+    /// A line that is expected to be present before applying the hunk, and is removed by it.
+    Removal(BString),
+    /// A line that is added by the hunk.
+    Addition(BString),
+}
+
+impl Line {
+    /// Return the content of the line, without the prefix it had in the patch.
+    pub fn data(&self) -> &BStr {
+        match self {
+            Line::Context(data) | Line::Removal(data) | Line::Addition(data) => data.as_ref(),
+        }
+    }
+}
+
+/// A hunk of a patch along with the position its header claims it to be at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Hunk {
+    /// The 1-based line at which the hunk starts in the source, or the line after which lines are inserted if `before_len` is 0.
+    pub before_start: u32,
+    /// The amount of context and removed lines.
+    pub before_len: u32,
+    /// The 1-based line at which the hunk starts in the destination, or the line after which lines were removed if `after_len` is 0.
+    pub after_start: u32,
+    /// The amount of context and added lines.
+    pub after_len: u32,
+    /// All lines of the hunk in order of appearance.
+    pub lines: Vec<Line>,
+}
+
+/// The kind of rewrite a [`File`] represents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rewrite {
+    /// The source was moved to the destination.
+    Rename,
+    /// The source was copied to the destination, and still exists.
+    Copy,
+}
+
+/// All changes to a single file as described by a patch.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The path of the file before the change, or `None` if it is created.
+    pub source_path: Option<BString>,
+    /// The path of the file after the change, or `None` if it is deleted.
+    pub path: Option<BString>,
+    /// The mode of the file before the change, if the patch mentions it.
+    pub source_mode: Option<EntryMode>,
+    /// The mode of the file after the change, if the patch mentions it.
+    pub mode: Option<EntryMode>,
+    /// Set if the file was renamed or copied from `source_path`.
+    pub rewrite: Option<Rewrite>,
+    /// If `true`, the file has binary content whose changes aren't described by `hunks`.
+    pub binary: bool,
+    /// The hunks to apply to the content of the file, in order.
+    pub hunks: Vec<Hunk>,
+}
+
+impl File {
+    fn new(source_path: Option<BString>, path: Option<BString>) -> Self {
+        File {
+            source_path,
+            path,
+            source_mode: None,
+            mode: None,
+            rewrite: None,
+            binary: false,
+            hunks: Vec::new(),
+        }
+    }
+}
+
+/// Parse all file changes from the `diff --git` patches or traditional unified diffs in `input`, ignoring all text
+/// between them like the commit messages produced by `git format-patch`.
+///
+/// The first component of paths in `diff --git`, `---` and `+++` lines is stripped like `git apply -p1` does.
+/// Note that quoted paths aren't supported.
+pub fn files(input: &[u8]) -> Result<Vec<File>, Error> {
+    let lines: Vec<_> = input.lines_with_terminator().collect();
+    let mut files = Vec::new();
+    let mut current: Option<File> = None;
+    // Only set while in the extended header of a `diff --git` patch, before the first hunk.
+    let mut in_git_header = false;
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = trim_newline(lines[idx]);
+        let line_number = idx + 1;
+        let syntax = |message| Error::Syntax {
+            line: line_number,
+            message,
+        };
+        idx += 1;
+
+        if let Some(paths) = line.strip_prefix(b"diff --git ") {
+            files.extend(current.take());
+            let (source, destination) = split_git_header(paths).ok_or_else(|| syntax("invalid 'diff --git' line"))?;
+            current = Some(File::new(
+                Some(strip_component(source)),
+                Some(strip_component(destination)),
+            ));
+            in_git_header = true;
+        } else if line.starts_with(b"--- ") {
+            let next = lines
+                .get(idx)
+                .map(|line| trim_newline(line))
+                .filter(|line| line.starts_with(b"+++ "))
+                .ok_or_else(|| syntax("expected a '+++' line to follow"))?;
+            idx += 1;
+            let source = path_of_unified_header(&line[4..]);
+            let destination = path_of_unified_header(&next[4..]);
+            match current.as_mut() {
+                Some(file) if in_git_header => {
+                    if source.is_none() {
+                        file.source_path = None;
+                    }
+                    if destination.is_none() {
+                        file.path = None;
+                    }
+                }
+                _ => {
+                    files.extend(current.take());
+                    current = Some(File::new(source, destination));
+                }
+            }
+            in_git_header = false;
+        } else if line.starts_with(b"@@ ") {
+            let file = current
+                .as_mut()
+                .ok_or_else(|| syntax("a hunk must follow a file header"))?;
+            let mut hunk = parse_hunk_header(line).ok_or_else(|| syntax("invalid hunk header"))?;
+            let (mut before_remaining, mut after_remaining) = (hunk.before_len, hunk.after_len);
+            while before_remaining > 0 || after_remaining > 0 {
+                let line_number = idx + 1;
+                let line = *lines.get(idx).ok_or(Error::Syntax {
+                    line: line_number,
+                    message: "the hunk is truncated",
+                })?;
+                idx += 1;
+                let (kind, data) = match line.split_first() {
+                    Some((b' ', data)) => ('c', data),
+                    Some((b'-', data)) => ('-', data),
+                    Some((b'+', data)) => ('+', data),
+                    Some((b'\\', _)) => {
+                        strip_newline_of_last_line(&mut hunk);
+                        continue;
+                    }
+                    // Some tools strip trailing whitespace, turning empty context lines into empty lines.
+                    Some((b'\n', _)) | Some((b'\r', _)) => ('c', line),
+                    _ => {
+                        return Err(Error::Syntax {
+                            line: line_number,
+                            message: "unexpected line in hunk",
+                        })
+                    }
+                };
+                let counter = match kind {
+                    'c' => {
+                        hunk.lines.push(Line::Context(data.into()));
+                        after_remaining = after_remaining.checked_sub(1).ok_or(Error::Syntax {
+                            line: line_number,
+                            message: "the hunk has more lines than its header claims",
+                        })?;
+                        &mut before_remaining
+                    }
+                    '-' => {
+                        hunk.lines.push(Line::Removal(data.into()));
+                        &mut before_remaining
+                    }
+                    _ => {
+                        hunk.lines.push(Line::Addition(data.into()));
+                        &mut after_remaining
+                    }
+                };
+                *counter = counter.checked_sub(1).ok_or(Error::Syntax {
+                    line: line_number,
+                    message: "the hunk has more lines than its header claims",
+                })?;
+            }
+            if lines.get(idx).map_or(false, |line| line.starts_with(b"\\")) {
+                strip_newline_of_last_line(&mut hunk);
+                idx += 1;
+            }
+            file.hunks.push(hunk);
+            in_git_header = false;
+        } else if let Some(file) = current.as_mut().filter(|_| in_git_header) {
+            if let Some(mode) = line.strip_prefix(b"old mode ") {
+                file.source_mode = Some(parse_mode(mode).ok_or_else(|| syntax("invalid mode"))?);
+            } else if let Some(mode) = line.strip_prefix(b"new mode ") {
+                file.mode = Some(parse_mode(mode).ok_or_else(|| syntax("invalid mode"))?);
+            } else if let Some(mode) = line.strip_prefix(b"deleted file mode ") {
+                file.source_mode = Some(parse_mode(mode).ok_or_else(|| syntax("invalid mode"))?);
+                file.path = None;
+            } else if let Some(mode) = line.strip_prefix(b"new file mode ") {
+                file.mode = Some(parse_mode(mode).ok_or_else(|| syntax("invalid mode"))?);
+                file.source_path = None;
+            } else if let Some(path) = line.strip_prefix(b"rename from ") {
+                file.source_path = Some(path.into());
+                file.rewrite = Some(Rewrite::Rename);
+            } else if let Some(path) = line.strip_prefix(b"rename to ") {
+                file.path = Some(path.into());
+            } else if let Some(path) = line.strip_prefix(b"copy from ") {
+                file.source_path = Some(path.into());
+                file.rewrite = Some(Rewrite::Copy);
+            } else if let Some(path) = line.strip_prefix(b"copy to ") {
+                file.path = Some(path.into());
+            } else if let Some(ids) = line.strip_prefix(b"index ") {
+                if let Some(pos) = ids.find_byte(b' ') {
+                    let mode = Some(parse_mode(&ids[pos + 1..]).ok_or_else(|| syntax("invalid mode"))?);
+                    file.source_mode = mode;
+                    file.mode = mode;
+                }
+            } else if line.starts_with(b"Binary files ") || line == b"GIT binary patch" {
+                file.binary = true;
+                in_git_header = false;
+            }
+        }
+    }
+    files.extend(current);
+    Ok(files)
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\n").unwrap_or(line);
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Split `a/path b/path` into both paths, which is ambiguous if paths contain spaces. Prefer a split
+/// resulting in the same path on both sides, as only those can't be learned from later lines.
+fn split_git_header(paths: &[u8]) -> Option<(&[u8], &[u8])> {
+    if paths.len() % 2 == 1 {
+        let (source, destination) = (&paths[..paths.len() / 2], &paths[paths.len() / 2 + 1..]);
+        if paths[paths.len() / 2] == b' ' && strip_component(source) == strip_component(destination) {
+            return Some((source, destination));
+        }
+    }
+    let pos = paths.find(" b/").or_else(|| paths.find_byte(b' '))?;
+    Some((&paths[..pos], &paths[pos + 1..]))
+}
+
+fn strip_component(path: &[u8]) -> BString {
+    match path.find_byte(b'/') {
+        Some(pos) => path[pos + 1..].into(),
+        None => path.into(),
+    }
+}
+
+/// Return the path of a `---` or `+++` line without its prefix, or `None` if it is `/dev/null`.
+fn path_of_unified_header(path: &[u8]) -> Option<BString> {
+    let path = match path.find_byte(b'\t') {
+        Some(pos) => &path[..pos],
+        None => path.trim_end(),
+    };
+    (path != b"/dev/null").then(|| strip_component(path))
+}
+
+fn parse_mode(mode: &[u8]) -> Option<EntryMode> {
+    let mode = u32::from_str_radix(mode.to_str().ok()?.trim(), 8).ok()?;
+    EntryMode::try_from(mode).ok()
+}
+
+/// Parse `@@ -<start>[,<len>] +<start>[,<len>] @@` with lengths defaulting to 1.
+fn parse_hunk_header(line: &[u8]) -> Option<Hunk> {
+    let mut tokens = line.get(3..)?.split_str(" ");
+    let range = |token: &[u8], prefix: u8| -> Option<(u32, u32)> {
+        let token = token.strip_prefix(&[prefix])?.to_str().ok()?;
+        Some(match token.split_once(',') {
+            Some((start, len)) => (start.parse().ok()?, len.parse().ok()?),
+            None => (token.parse().ok()?, 1),
+        })
+    };
+    let (before_start, before_len) = range(tokens.next()?, b'-')?;
+    let (after_start, after_len) = range(tokens.next()?, b'+')?;
+    if !tokens.next()?.starts_with(b"@@") {
+        return None;
+    }
+    Some(Hunk {
+        before_start,
+        before_len,
+        after_start,
+        after_len,
+        lines: Vec::new(),
+    })
+}
+
+fn strip_newline_of_last_line(hunk: &mut Hunk) {
+    let data = match hunk.lines.last_mut() {
+        Some(Line::Context(data)) | Some(Line::Removal(data)) | Some(Line::Addition(data)) => data,
+        None => return,
+    };
+    if data.last() == Some(&b'\n') {
+        data.pop();
+    }
+}
@@ -0,0 +1,126 @@
+use git_hash::{oid, ObjectId};
+use git_object::bstr::BString;
+use quick_error::quick_error;
+
+use crate::{
+    blob::{self, binary, Algorithm},
+    patch::{content, sides},
+    tree::rewrites,
+};
+
+quick_error! {
+    /// The error returned by [`compute()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound { oid: ObjectId } {
+            display("The blob {} to count changed lines in was not found in the database", oid)
+        }
+    }
+}
+
+/// What changed in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Counts {
+    /// The amount of added and removed lines of a text file.
+    Lines {
+        /// The amount of lines that were added.
+        insertions: u32,
+        /// The amount of lines that were removed.
+        deletions: u32,
+    },
+    /// The size of a binary file before and after the change, with `0` for a side that doesn't exist.
+    Binary {
+        /// The size of the file before the change in bytes.
+        before_size: u64,
+        /// The size of the file after the change in bytes.
+        after_size: u64,
+    },
+}
+
+/// The statistics of a single changed file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct File {
+    /// The path of the file before it was renamed or copied, if it was.
+    pub source_path: Option<BString>,
+    /// The path of the file.
+    pub path: BString,
+    /// What changed in the file.
+    pub counts: Counts,
+}
+
+/// The statistics of all changed files, as computed by [`compute()`].
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Stats {
+    /// The changed files in the order of the changes they were computed from.
+    pub files: Vec<File>,
+}
+
+impl Stats {
+    /// Return the total amount of added lines in text files.
+    pub fn insertions(&self) -> u64 {
+        self.lines().map(|(insertions, _)| insertions as u64).sum()
+    }
+
+    /// Return the total amount of removed lines in text files.
+    pub fn deletions(&self) -> u64 {
+        self.lines().map(|(_, deletions)| deletions as u64).sum()
+    }
+
+    fn lines(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.files.iter().filter_map(|file| match file.counts {
+            Counts::Lines { insertions, deletions } => Some((insertions, deletions)),
+            Counts::Binary { .. } => None,
+        })
+    }
+}
+
+/// Count the added and removed lines of each file in `changes` using `algorithm`, or obtain their size if they are binary,
+/// with `find(object_id, &mut buf) -> Option<BlobRef>` providing the content of blobs, just like `git diff --numstat`.
+///
+/// Changes to trees are skipped, and submodules count as one changed line each.
+pub fn compute<FindFn>(changes: &[rewrites::Change], algorithm: Algorithm, mut find: FindFn) -> Result<Stats, Error>
+where
+    FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+{
+    let mut files = Vec::with_capacity(changes.len());
+    let (mut before_buf, mut after_buf) = (Vec::new(), Vec::new());
+    for change in changes {
+        let (before, after, rewrite) = sides(change);
+        if before.iter().chain(after.iter()).any(|side| side.mode.is_tree()) {
+            continue;
+        }
+        let counts = if before.as_ref().map(|side| side.oid) == after.as_ref().map(|side| side.oid) {
+            Counts::Lines {
+                insertions: 0,
+                deletions: 0,
+            }
+        } else {
+            let before = content(before.as_ref(), &mut before_buf, &mut find).map_err(|oid| Error::NotFound { oid })?;
+            let after = content(after.as_ref(), &mut after_buf, &mut find).map_err(|oid| Error::NotFound { oid })?;
+            if binary::is_binary(before) || binary::is_binary(after) {
+                Counts::Binary {
+                    before_size: before.len() as u64,
+                    after_size: after.len() as u64,
+                }
+            } else {
+                let hunks = blob::diff(algorithm, before, after);
+                Counts::Lines {
+                    insertions: hunks.iter().map(|hunk| hunk.after.end - hunk.after.start).sum(),
+                    deletions: hunks.iter().map(|hunk| hunk.before.end - hunk.before.start).sum(),
+                }
+            }
+        };
+        files.push(File {
+            source_path: rewrite.and(before.as_ref()).map(|side| side.path.to_owned()),
+            path: after
+                .as_ref()
+                .or_else(|| before.as_ref())
+                .expect("one side is set")
+                .path
+                .to_owned(),
+            counts,
+        });
+    }
+    Ok(Stats { files })
+}
@@ -1,11 +1,12 @@
 use std::{borrow::BorrowMut, collections::VecDeque};
 
 use git_hash::{oid, ObjectId};
+use git_object::bstr::{BStr, BString, ByteVec};
 use quick_error::quick_error;
 
 use crate::{
     tree,
-    tree::{visit::Change, TreeInfoPair},
+    tree::{visit::Change, Pathspec, TreeInfoPair},
 };
 
 quick_error! {
@@ -44,7 +45,7 @@ impl<'a> tree::Changes<'a> {
     /// * it does a breadth first iteration as buffer space only fits two trees, the current one on the one we compare with.
     /// * does not do rename tracking but attempts to reduce allocations to zero (so performance is mostly determined
     ///   by the delegate implementation which should be as specific as possible. Rename tracking can be computed on top of the changes
-    ///   received by the `delegate`.
+    ///   received by the `delegate`, for example with [`tree::rewrites::track()`] on the changes of a [`tree::Recorder`].
     /// * cycle checking is not performed, but can be performed in the delegate which can return [`tree::visit::Action::Cancel`] to stop the traversal.
     /// * [std::mem::ManuallyDrop] is used because `Peekable` is needed. When using it as wrapper around our no-drop iterators, all of the sudden
     ///   borrowcheck complains as Drop is present (even though it's not)
@@ -52,10 +53,31 @@ impl<'a> tree::Changes<'a> {
     /// [git_cmp_c]: https://github.com/git/git/blob/311531c9de557d25ac087c1637818bd2aad6eb3a/tree-diff.c#L49:L65
     /// [git_cmp_rs]: https://github.com/Byron/gitoxide/blob/a4d5f99c8dc99bf814790928a3bf9649cd99486b/git-object/src/mutable/tree.rs#L52-L55
     pub fn needed_to_obtain<FindFn, R, StateMut>(
+        self,
+        other: git_object::TreeRefIter<'_>,
+        state: StateMut,
+        find: FindFn,
+        delegate: &mut R,
+    ) -> Result<(), Error>
+    where
+        FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::TreeRefIter<'b>>,
+        R: tree::Visit,
+        StateMut: BorrowMut<tree::State>,
+    {
+        self.needed_to_obtain_with_pathspec(other, state, find, &Pathspec::default(), delegate)
+    }
+
+    /// Like [`needed_to_obtain()`][Self::needed_to_obtain()], but only changes to entries matching `pathspec` are passed
+    /// to the `delegate`, and only trees matching it or leading to one of its paths are compared.
+    ///
+    /// This makes the cost of the comparison depend on the amount of entries selected by `pathspec` instead of the size of the trees.
+    /// Note that changes of trees leading to one of the paths of `pathspec` are passed to the `delegate` as well.
+    pub fn needed_to_obtain_with_pathspec<FindFn, R, StateMut>(
         mut self,
         other: git_object::TreeRefIter<'_>,
         mut state: StateMut,
         mut find: FindFn,
+        pathspec: &Pathspec,
         delegate: &mut R,
     ) -> Result<(), Error>
     where
@@ -65,8 +87,9 @@ impl<'a> tree::Changes<'a> {
     {
         let state = state.borrow_mut();
         state.clear();
-        let mut lhs_entries = peekable(self.0.take().unwrap_or_default());
-        let mut rhs_entries = peekable(other);
+        state.trees.track_paths = !pathspec.is_empty();
+        let mut lhs_entries = entries(self.0.take().unwrap_or_default(), pathspec, &state.trees.dir);
+        let mut rhs_entries = entries(other, pathspec, &state.trees.dir);
         let mut pop_path = false;
 
         loop {
@@ -80,16 +103,32 @@ impl<'a> tree::Changes<'a> {
                     match state.trees.pop_front() {
                         Some((None, Some(rhs))) => {
                             delegate.pop_front_tracked_path_and_set_current();
-                            rhs_entries = peekable(find(&rhs, &mut state.buf2).ok_or(Error::NotFound { oid: rhs })?);
+                            rhs_entries = entries(
+                                find(&rhs, &mut state.buf2).ok_or(Error::NotFound { oid: rhs })?,
+                                pathspec,
+                                &state.trees.dir,
+                            );
                         }
                         Some((Some(lhs), Some(rhs))) => {
                             delegate.pop_front_tracked_path_and_set_current();
-                            lhs_entries = peekable(find(&lhs, &mut state.buf1).ok_or(Error::NotFound { oid: lhs })?);
-                            rhs_entries = peekable(find(&rhs, &mut state.buf2).ok_or(Error::NotFound { oid: rhs })?);
+                            lhs_entries = entries(
+                                find(&lhs, &mut state.buf1).ok_or(Error::NotFound { oid: lhs })?,
+                                pathspec,
+                                &state.trees.dir,
+                            );
+                            rhs_entries = entries(
+                                find(&rhs, &mut state.buf2).ok_or(Error::NotFound { oid: rhs })?,
+                                pathspec,
+                                &state.trees.dir,
+                            );
                         }
                         Some((Some(lhs), None)) => {
                             delegate.pop_front_tracked_path_and_set_current();
-                            lhs_entries = peekable(find(&lhs, &mut state.buf1).ok_or(Error::NotFound { oid: lhs })?);
+                            lhs_entries = entries(
+                                find(&lhs, &mut state.buf1).ok_or(Error::NotFound { oid: lhs })?,
+                                pathspec,
+                                &state.trees.dir,
+                            );
                         }
                         Some((None, None)) => unreachable!("BUG: it makes no sense to fill the stack with empties"),
                         None => return Ok(()),
@@ -120,7 +159,7 @@ impl<'a> tree::Changes<'a> {
 
 fn delete_entry_schedule_recursion<R: tree::Visit>(
     entry: git_object::tree::EntryRef<'_>,
-    queue: &mut VecDeque<TreeInfoPair>,
+    queue: &mut Queue,
     delegate: &mut R,
 ) -> Result<(), Error> {
     delegate.push_path_component(entry.filename);
@@ -136,14 +175,14 @@ fn delete_entry_schedule_recursion<R: tree::Visit>(
     if entry.mode.is_tree() {
         delegate.pop_path_component();
         delegate.push_back_tracked_path_component(entry.filename);
-        queue.push_back((Some(entry.oid.to_owned()), None));
+        queue.push_back((Some(entry.oid.to_owned()), None), entry.filename);
     }
     Ok(())
 }
 
 fn add_entry_schedule_recursion<R: tree::Visit>(
     entry: git_object::tree::EntryRef<'_>,
-    queue: &mut VecDeque<TreeInfoPair>,
+    queue: &mut Queue,
     delegate: &mut R,
 ) -> Result<(), Error> {
     delegate.push_path_component(entry.filename);
@@ -159,15 +198,15 @@ fn add_entry_schedule_recursion<R: tree::Visit>(
     if entry.mode.is_tree() {
         delegate.pop_path_component();
         delegate.push_back_tracked_path_component(entry.filename);
-        queue.push_back((None, Some(entry.oid.to_owned())))
+        queue.push_back((None, Some(entry.oid.to_owned())), entry.filename);
     }
     Ok(())
 }
 fn catchup_rhs_with_lhs<R: tree::Visit>(
-    rhs_entries: &mut IteratorType<git_object::TreeRefIter<'_>>,
+    rhs_entries: &mut IteratorType<Entries<'_, '_>>,
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
-    queue: &mut VecDeque<TreeInfoPair>,
+    queue: &mut Queue,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -204,10 +243,10 @@ fn catchup_rhs_with_lhs<R: tree::Visit>(
 }
 
 fn catchup_lhs_with_rhs<R: tree::Visit>(
-    lhs_entries: &mut IteratorType<git_object::TreeRefIter<'_>>,
+    lhs_entries: &mut IteratorType<Entries<'_, '_>>,
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
-    queue: &mut VecDeque<TreeInfoPair>,
+    queue: &mut Queue,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use std::cmp::Ordering::*;
@@ -246,7 +285,7 @@ fn catchup_lhs_with_rhs<R: tree::Visit>(
 fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
     lhs: git_object::tree::EntryRef<'_>,
     rhs: git_object::tree::EntryRef<'_>,
-    queue: &mut VecDeque<TreeInfoPair>,
+    queue: &mut Queue,
     delegate: &mut R,
 ) -> Result<(), Error> {
     use git_object::tree::EntryMode::*;
@@ -265,7 +304,7 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
             {
                 return Err(Error::Cancelled);
             }
-            queue.push_back((Some(lhs.oid.to_owned()), Some(rhs.oid.to_owned())));
+            queue.push_back((Some(lhs.oid.to_owned()), Some(rhs.oid.to_owned())), lhs.filename);
         }
         (lhs_mode, Tree) if lhs_mode.is_no_tree() => {
             delegate.push_back_tracked_path_component(lhs.filename);
@@ -287,7 +326,7 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
             {
                 return Err(Error::Cancelled);
             };
-            queue.push_back((None, Some(rhs.oid.to_owned())));
+            queue.push_back((None, Some(rhs.oid.to_owned())), rhs.filename);
         }
         (Tree, rhs_mode) if rhs_mode.is_no_tree() => {
             delegate.push_back_tracked_path_component(lhs.filename);
@@ -309,12 +348,12 @@ fn handle_lhs_and_rhs_with_equal_filenames<R: tree::Visit>(
             {
                 return Err(Error::Cancelled);
             };
-            queue.push_back((Some(lhs.oid.to_owned()), None));
+            queue.push_back((Some(lhs.oid.to_owned()), None), lhs.filename);
         }
         (lhs_non_tree, rhs_non_tree) => {
             delegate.push_path_component(lhs.filename);
             debug_assert!(lhs_non_tree.is_no_tree() && rhs_non_tree.is_no_tree());
-            if lhs.oid != rhs.oid
+            if (lhs.oid != rhs.oid || lhs.mode != rhs.mode)
                 && delegate
                     .visit(Change::Modification {
                         previous_entry_mode: lhs.mode,
@@ -336,3 +375,96 @@ type IteratorType<I> = std::mem::ManuallyDrop<std::iter::Peekable<I>>;
 fn peekable<I: Iterator>(iter: I) -> IteratorType<I> {
     std::mem::ManuallyDrop::new(iter.peekable())
 }
+
+fn entries<'a, 'p>(
+    inner: git_object::TreeRefIter<'a>,
+    pathspec: &'p Pathspec,
+    dir: &BString,
+) -> IteratorType<Entries<'a, 'p>> {
+    peekable(Entries {
+        inner,
+        pathspec,
+        dir: dir.clone(),
+        path: BString::default(),
+    })
+}
+
+/// The trees to compare next, along with their paths if these are needed to match a pathspec.
+#[derive(Default, Clone)]
+pub(crate) struct Queue {
+    trees: VecDeque<TreeInfoPair>,
+    paths: VecDeque<BString>,
+    track_paths: bool,
+    /// The path of the trees that are currently compared.
+    dir: BString,
+}
+
+impl Queue {
+    pub(crate) fn clear(&mut self) {
+        self.trees.clear();
+        self.paths.clear();
+        self.track_paths = false;
+        self.dir.clear();
+    }
+
+    fn push_back(&mut self, trees: TreeInfoPair, name: &BStr) {
+        self.trees.push_back(trees);
+        if self.track_paths {
+            self.paths.push_back(join(self.dir.as_ref(), name));
+        }
+    }
+
+    fn pop_front(&mut self) -> Option<TreeInfoPair> {
+        let trees = self.trees.pop_front()?;
+        if self.track_paths {
+            self.dir = self.paths.pop_front().expect("one path per tree");
+        }
+        Some(trees)
+    }
+}
+
+fn join(dir: &BStr, name: &BStr) -> BString {
+    let mut path = BString::from(Vec::with_capacity(dir.len() + 1 + name.len()));
+    if !dir.is_empty() {
+        path.push_str(dir);
+        path.push(b'/');
+    }
+    path.push_str(name);
+    path
+}
+
+/// The entries of a tree which match a pathspec, or lead to one of its paths.
+pub(crate) struct Entries<'a, 'p> {
+    inner: git_object::TreeRefIter<'a>,
+    pathspec: &'p Pathspec,
+    /// The path of the tree whose entries we iterate.
+    dir: BString,
+    /// A buffer for the path of the current entry.
+    path: BString,
+}
+
+impl<'a, 'p> Iterator for Entries<'a, 'p> {
+    type Item = Result<git_object::tree::EntryRef<'a>, git_object::decode::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pathspec.is_empty() {
+            return self.inner.next();
+        }
+        loop {
+            let entry = match self.inner.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(err)),
+            };
+            self.path.clear();
+            if !self.dir.is_empty() {
+                self.path.push_str(&self.dir);
+                self.path.push(b'/');
+            }
+            self.path.push_str(entry.filename);
+            let path = self.path.as_ref();
+            if self.pathspec.matches(path) || (entry.mode.is_tree() && self.pathspec.is_leading_directory(path)) {
+                return Some(Ok(entry));
+            }
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use std::collections::VecDeque;
-
 use git_hash::ObjectId;
 use git_object::TreeRefIter;
 
@@ -8,7 +6,7 @@ use git_object::TreeRefIter;
 pub struct State {
     buf1: Vec<u8>,
     buf2: Vec<u8>,
-    trees: VecDeque<TreeInfoPair>,
+    trees: changes::Queue,
 }
 
 type TreeInfoPair = (Option<ObjectId>, Option<ObjectId>);
@@ -36,6 +34,9 @@ where
 ///
 pub mod changes;
 
+#[doc(inline)]
+pub use git_traverse::tree::Pathspec;
+
 ///
 pub mod visit;
 #[doc(inline)]
@@ -45,3 +46,6 @@ pub use visit::Visit;
 pub mod recorder;
 #[doc(inline)]
 pub use recorder::Recorder;
+
+/// Detect renames and copies among the changes recorded by a [`Recorder`].
+pub mod rewrites;
@@ -0,0 +1,303 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use git_hash::{oid, ObjectId};
+use git_object::{bstr::BString, tree::EntryMode};
+use quick_error::quick_error;
+
+use crate::{blob, tree::recorder};
+
+quick_error! {
+    /// The error returned by [`track()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NotFound { oid: ObjectId } {
+            display("The blob {} of a rename or copy candidate was not found in the database", oid)
+        }
+    }
+}
+
+/// Configure how renames and copies are detected by [`track()`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    /// The minimal similarity between `0.0` and `1.0` a source and a destination must have to be considered a rename or copy,
+    /// similar to `-M<n>%` and `-C<n>%`.
+    ///
+    /// If `None`, only sources and destinations with identical content are paired up, which doesn't require reading any blob.
+    pub percentage: Option<f32>,
+    /// If `true`, modified and deleted files are also used as sources of copies, similar to `-C`.
+    pub copies: bool,
+    /// If not `0`, the amount of sources and destinations each that are compared by similarity at most, similar to `diff.renameLimit`.
+    ///
+    /// If there are more, only exact renames and copies are detected.
+    pub limit: usize,
+}
+
+impl Default for Options {
+    /// Detect renames with a similarity of at least 50% among up to 1000 candidates, but no copies, just like `git diff -M`.
+    fn default() -> Self {
+        Options {
+            percentage: Some(0.5),
+            copies: false,
+            limit: 1000,
+        }
+    }
+}
+
+/// A change as recorded by a [`Recorder`][recorder::Recorder], or a rename or copy that was derived from recorded changes.
+#[derive(Clone, Debug, PartialEq)]
+#[allow(missing_docs)]
+pub enum Change {
+    /// A change which isn't part of a rename or copy.
+    Recorded(recorder::Change),
+    /// The entry at `source_path` was deleted and appears at `path` with at least the given `similarity`.
+    Rename {
+        source_entry_mode: EntryMode,
+        source_oid: ObjectId,
+        source_path: BString,
+
+        entry_mode: EntryMode,
+        oid: ObjectId,
+        path: BString,
+
+        /// The similarity of source and destination, with `1.0` for identical content.
+        similarity: f32,
+    },
+    /// The entry at `path` was added as copy of the entry at `source_path`, which still exists or was renamed.
+    Copy {
+        source_entry_mode: EntryMode,
+        source_oid: ObjectId,
+        source_path: BString,
+
+        entry_mode: EntryMode,
+        oid: ObjectId,
+        path: BString,
+
+        /// The similarity of source and destination, with `1.0` for identical content.
+        similarity: f32,
+    },
+}
+
+/// Turn pairs of deletions and additions in `changes` into renames, and additions into copies of modified or deleted entries
+/// if enabled in `options`, while keeping all other changes in their original order.
+///
+/// Renames and copies with identical content are detected first, and only for the remaining entries the
+/// similarity of their content is computed, using `find(object_id, &mut buf) -> Option<BlobRef>` to obtain the blobs.
+/// Pairs with the highest similarity are chosen first, while a deleted entry is renamed at most once.
+/// Trees and submodules are never considered, and sources and destinations must both be symbolic links or files.
+pub fn track<FindFn>(changes: Vec<recorder::Change>, options: Options, mut find: FindFn) -> Result<Vec<Change>, Error>
+where
+    FindFn: for<'b> FnMut(&oid, &'b mut Vec<u8>) -> Option<git_object::BlobRef<'b>>,
+{
+    let mut sources = Vec::new();
+    let mut destinations = Vec::new();
+    for (idx, change) in changes.iter().enumerate() {
+        match change {
+            recorder::Change::Deletion { entry_mode, .. } if is_candidate(*entry_mode) => sources.push(idx),
+            recorder::Change::Modification {
+                previous_entry_mode, ..
+            } if options.copies && is_candidate(*previous_entry_mode) => sources.push(idx),
+            recorder::Change::Addition { entry_mode, .. } if is_candidate(*entry_mode) => destinations.push(idx),
+            _ => {}
+        }
+    }
+
+    let mut assignments = Assignments {
+        source_of: vec![None; changes.len()],
+        renamed: vec![false; changes.len()],
+        copies: options.copies,
+    };
+
+    let mut sources_by_id = HashMap::<ObjectId, Vec<usize>>::new();
+    for &src in &sources {
+        sources_by_id.entry(source(&changes[src]).1).or_default().push(src);
+    }
+    for &dst in &destinations {
+        let (entry_mode, id, _) = destination(&changes[dst]);
+        let candidates = match sources_by_id.get(&id) {
+            Some(candidates) => candidates,
+            None => continue,
+        };
+        let mut compatible = candidates
+            .iter()
+            .copied()
+            .filter(|&src| is_link(source(&changes[src]).0) == is_link(entry_mode));
+        let rename_source = compatible
+            .clone()
+            .find(|&src| assignments.can_be_renamed(&changes, src));
+        if let Some(src) = rename_source.or_else(|| compatible.next()) {
+            assignments.assign(&changes, src, dst, 1.0);
+        }
+    }
+
+    if let Some(percentage) = options.percentage {
+        let destinations: Vec<_> = destinations
+            .into_iter()
+            .filter(|&dst| assignments.source_of[dst].is_none())
+            .collect();
+        let sources: Vec<_> = sources
+            .into_iter()
+            .filter(|&src| options.copies || assignments.can_be_renamed(&changes, src))
+            .collect();
+        let within_limit =
+            options.limit == 0 || (sources.len() <= options.limit && destinations.len() <= options.limit);
+        if within_limit && !sources.is_empty() && !destinations.is_empty() {
+            let mut buf = Vec::new();
+            let mut load = |id: &oid| -> Result<Vec<u8>, Error> {
+                find(id, &mut buf)
+                    .map(|blob| blob.data.to_owned())
+                    .ok_or_else(|| Error::NotFound { oid: id.to_owned() })
+            };
+            let source_data = sources
+                .iter()
+                .map(|&src| load(&source(&changes[src]).1))
+                .collect::<Result<Vec<_>, _>>()?;
+            let destination_data = destinations
+                .iter()
+                .map(|&dst| load(&destination(&changes[dst]).1))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut pairs = Vec::new();
+            for (dst, after) in destinations.iter().zip(&destination_data) {
+                for (src, before) in sources.iter().zip(&source_data) {
+                    if is_link(source(&changes[*src]).0) != is_link(destination(&changes[*dst]).0) {
+                        continue;
+                    }
+                    let (min_len, max_len) = (before.len().min(after.len()), before.len().max(after.len()));
+                    if max_len == 0 || (min_len as f32 / max_len as f32) < percentage {
+                        continue;
+                    }
+                    let similarity = similarity(before, after);
+                    if similarity >= percentage {
+                        pairs.push((similarity, *dst, *src));
+                    }
+                }
+            }
+            pairs.sort_by(|a, b| {
+                b.0.partial_cmp(&a.0)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.1.cmp(&b.1))
+                    .then(a.2.cmp(&b.2))
+            });
+            for (similarity, dst, src) in pairs {
+                if assignments.source_of[dst].is_none() {
+                    assignments.assign(&changes, src, dst, similarity);
+                }
+            }
+        }
+    }
+
+    let rewrites: Vec<_> = assignments
+        .source_of
+        .iter()
+        .map(|assignment| {
+            assignment.map(|(src, similarity, is_rename)| {
+                let (entry_mode, oid, path) = source(&changes[src]);
+                (entry_mode, oid, path.to_owned(), similarity, is_rename)
+            })
+        })
+        .collect();
+    Ok(changes
+        .into_iter()
+        .zip(rewrites)
+        .enumerate()
+        .filter(|(idx, _)| !assignments.renamed[*idx])
+        .map(|(_, (change, rewrite))| match (change, rewrite) {
+            (
+                recorder::Change::Addition { entry_mode, oid, path },
+                Some((source_entry_mode, source_oid, source_path, similarity, is_rename)),
+            ) => {
+                if is_rename {
+                    Change::Rename {
+                        source_entry_mode,
+                        source_oid,
+                        source_path,
+                        entry_mode,
+                        oid,
+                        path,
+                        similarity,
+                    }
+                } else {
+                    Change::Copy {
+                        source_entry_mode,
+                        source_oid,
+                        source_path,
+                        entry_mode,
+                        oid,
+                        path,
+                        similarity,
+                    }
+                }
+            }
+            (change, _) => Change::Recorded(change),
+        })
+        .collect())
+}
+
+/// Return the similarity of `before` and `after` as the amount of bytes in unchanged lines relative to the size of the larger one.
+pub fn similarity(before: &[u8], after: &[u8]) -> f32 {
+    let max_len = before.len().max(after.len());
+    if max_len == 0 {
+        return 1.0;
+    }
+    let before_lines: Vec<_> = blob::lines(before).collect();
+    let after_lines: Vec<_> = blob::lines(after).collect();
+    let removed_bytes: usize = blob::diff_sequences(blob::Algorithm::Myers, &before_lines, &after_lines)
+        .iter()
+        .flat_map(|hunk| &before_lines[hunk.before.start as usize..hunk.before.end as usize])
+        .map(|line| line.len())
+        .sum();
+    (before.len() - removed_bytes) as f32 / max_len as f32
+}
+
+/// Keeps track of which destination was paired with which source, and which deletions were consumed by a rename.
+struct Assignments {
+    /// The source of each destination by index into the changes, along with the similarity and whether it's a rename.
+    source_of: Vec<Option<(usize, f32, bool)>>,
+    /// All deletions that were turned into a rename, by index into the changes.
+    renamed: Vec<bool>,
+    copies: bool,
+}
+
+impl Assignments {
+    fn can_be_renamed(&self, changes: &[recorder::Change], src: usize) -> bool {
+        matches!(changes[src], recorder::Change::Deletion { .. }) && !self.renamed[src]
+    }
+
+    fn assign(&mut self, changes: &[recorder::Change], src: usize, dst: usize, similarity: f32) {
+        if self.can_be_renamed(changes, src) {
+            self.renamed[src] = true;
+            self.source_of[dst] = Some((src, similarity, true));
+        } else if self.copies {
+            self.source_of[dst] = Some((src, similarity, false));
+        }
+    }
+}
+
+fn is_candidate(mode: EntryMode) -> bool {
+    !matches!(mode, EntryMode::Tree | EntryMode::Commit)
+}
+
+fn is_link(mode: EntryMode) -> bool {
+    mode == EntryMode::Link
+}
+
+fn source(change: &recorder::Change) -> (EntryMode, ObjectId, &BString) {
+    match change {
+        recorder::Change::Deletion { entry_mode, oid, path } => (*entry_mode, *oid, path),
+        recorder::Change::Modification {
+            previous_entry_mode,
+            previous_oid,
+            path,
+            ..
+        } => (*previous_entry_mode, *previous_oid, path),
+        recorder::Change::Addition { .. } => unreachable!("additions are never sources"),
+    }
+}
+
+fn destination(change: &recorder::Change) -> (EntryMode, ObjectId, &BString) {
+    match change {
+        recorder::Change::Addition { entry_mode, oid, path } => (*entry_mode, *oid, path),
+        _ => unreachable!("only additions are destinations"),
+    }
+}
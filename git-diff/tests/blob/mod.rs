@@ -0,0 +1,380 @@
+use git_diff::blob::{self, binary, Algorithm, Hunk, Kind, Outcome};
+
+fn hunk(before: std::ops::Range<u32>, after: std::ops::Range<u32>) -> Hunk {
+    Hunk { before, after }
+}
+
+#[test]
+fn lines_keep_their_terminator() {
+    assert_eq!(
+        blob::lines(b"a\nb\r\nc").collect::<Vec<_>>(),
+        vec![&b"a\n"[..], b"b\r\n", b"c"]
+    );
+    assert_eq!(blob::lines(b"").count(), 0);
+}
+
+const ALGORITHMS: &[Algorithm] = &[Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram];
+
+#[test]
+fn identical_blobs_have_no_hunks() {
+    for algorithm in ALGORITHMS.iter().copied() {
+        assert_eq!(blob::diff(algorithm, b"", b""), vec![]);
+        assert_eq!(blob::diff(algorithm, b"a\nb\n", b"a\nb\n"), vec![]);
+    }
+}
+
+#[test]
+fn additions_deletions_and_modifications() {
+    for algorithm in ALGORITHMS.iter().copied() {
+        let hunks = blob::diff(algorithm, b"a\nb\nc\n", b"a\nnew\nb\nc\n");
+        assert_eq!(hunks, vec![hunk(1..1, 1..2)]);
+        assert_eq!(hunks[0].kind(), Kind::Addition);
+
+        let hunks = blob::diff(algorithm, b"a\nb\nc\n", b"a\nc\n");
+        assert_eq!(hunks, vec![hunk(1..2, 1..1)]);
+        assert_eq!(hunks[0].kind(), Kind::Deletion);
+
+        let hunks = blob::diff(algorithm, b"a\nb\nc\n", b"a\nB\nc\n");
+        assert_eq!(hunks, vec![hunk(1..2, 1..2)]);
+        assert_eq!(hunks[0].kind(), Kind::Modification);
+
+        assert_eq!(blob::diff(algorithm, b"", b"a\nb\n"), vec![hunk(0..0, 0..2)]);
+        assert_eq!(blob::diff(algorithm, b"a\nb\n", b""), vec![hunk(0..2, 0..0)]);
+        assert_eq!(
+            blob::diff(algorithm, b"a\nb", b"a\nb\n"),
+            vec![hunk(1..2, 1..2)],
+            "line terminators are part of the line"
+        );
+    }
+}
+
+#[test]
+fn multiple_hunks_are_ordered_and_separated_by_unchanged_lines() {
+    for algorithm in ALGORITHMS.iter().copied() {
+        assert_eq!(
+            blob::diff(algorithm, b"1\n2\n3\n4\n5\n6\n7\n", b"0\n1\n3\n4\nfive\n6\n7\n8\n"),
+            vec![hunk(0..0, 0..1), hunk(1..2, 2..2), hunk(4..5, 4..5), hunk(7..7, 7..8)],
+            "{:?}",
+            algorithm
+        );
+    }
+}
+
+#[test]
+fn myers_changes_a_minimal_amount_of_lines() {
+    let before = b"a\nb\nc\na\nb\nb\na\n";
+    let after = b"c\nb\na\nb\na\nc\n";
+    let hunks = blob::diff(Algorithm::Myers, before, after);
+    let removed: u32 = hunks.iter().map(|h| h.before.end - h.before.start).sum();
+    let added: u32 = hunks.iter().map(|h| h.after.end - h.after.start).sum();
+    assert_eq!(
+        (removed, added),
+        (3, 2),
+        "the example from the paper has an edit distance of 5"
+    );
+}
+
+#[test]
+fn algorithms_may_choose_different_lines_to_keep() {
+    let before = b"fn a() {\n    one();\n}\n\nfn b() {\n    two();\n}\n";
+    let after = b"fn b() {\n    two();\n}\n\nfn a() {\n    one();\n}\n";
+    assert_eq!(
+        blob::diff(Algorithm::Myers, before, after),
+        vec![hunk(0..0, 0..4), hunk(2..6, 6..6)]
+    );
+    for algorithm in &[Algorithm::Patience, Algorithm::Histogram] {
+        assert_eq!(
+            blob::diff(*algorithm, before, after),
+            vec![hunk(0..4, 0..0), hunk(6..6, 2..6)],
+            "{:?} anchors on the unique line of the first function in `after`",
+            algorithm
+        );
+    }
+}
+
+#[test]
+fn all_algorithms_produce_hunks_that_turn_before_into_after() {
+    let before = b"a\nb\nc\na\nb\nb\na\n}\n}\nx\n";
+    let after = b"c\nb\n}\na\nb\na\nc\nx\n}\n";
+    let (before_lines, after_lines): (Vec<_>, Vec<_>) = (blob::lines(before).collect(), blob::lines(after).collect());
+    for algorithm in ALGORITHMS.iter().copied() {
+        let mut actual = Vec::new();
+        let mut pos = 0;
+        for hunk in blob::diff(algorithm, before, after) {
+            actual.extend_from_slice(&before_lines[pos..hunk.before.start as usize]);
+            actual.extend_from_slice(&after_lines[hunk.after.start as usize..hunk.after.end as usize]);
+            pos = hunk.before.end as usize;
+        }
+        actual.extend_from_slice(&before_lines[pos..]);
+        assert_eq!(actual, after_lines, "{:?}", algorithm);
+    }
+}
+
+#[test]
+fn algorithms_can_be_parsed_from_configuration_values() {
+    for (value, expected) in &[
+        ("default", Some(Algorithm::Myers)),
+        ("myers", Some(Algorithm::Myers)),
+        ("minimal", Some(Algorithm::Myers)),
+        ("patience", Some(Algorithm::Patience)),
+        ("histogram", Some(Algorithm::Histogram)),
+        ("unknown", None),
+    ] {
+        assert_eq!(Algorithm::from_config_value(value.as_bytes()), *expected);
+    }
+    assert_eq!(Algorithm::default(), Algorithm::Myers);
+}
+
+#[test]
+fn sequences_of_arbitrary_tokens() {
+    assert_eq!(
+        blob::diff_sequences(Algorithm::Myers, &["x", "y", "z"], &["x", "z", "z"]),
+        vec![hunk(1..2, 1..2)]
+    );
+}
+
+mod binary_content {
+    use git_attributes::State;
+
+    use super::*;
+
+    #[test]
+    fn a_null_byte_within_the_first_few_bytes_makes_content_binary() {
+        assert!(!binary::is_binary(b""));
+        assert!(!binary::is_binary(b"text\n"));
+        assert!(binary::is_binary(b"te\0xt"));
+
+        let mut late_null = vec![b'a'; binary::FIRST_FEW_BYTES];
+        late_null.push(0);
+        assert!(!binary::is_binary(&late_null), "only the first few bytes are checked");
+    }
+
+    #[test]
+    fn the_diff_attribute_overrides_the_detection() {
+        let (text, binary) = (&b"a\n"[..], &b"b\0\n"[..]);
+        assert_eq!(
+            blob::diff_content(Algorithm::Myers, text, b"b\n", State::Unspecified),
+            Outcome::Text(vec![hunk(0..1, 0..1)])
+        );
+        assert_eq!(
+            blob::diff_content(Algorithm::Myers, text, binary, State::Unspecified),
+            Outcome::Binary,
+            "one binary side suffices"
+        );
+        assert_eq!(
+            blob::diff_content(Algorithm::Myers, text, b"b\n", State::Unset),
+            Outcome::Binary
+        );
+        assert_eq!(
+            blob::diff_content(Algorithm::Myers, text, binary, State::Set),
+            Outcome::Text(vec![hunk(0..1, 0..1)])
+        );
+    }
+
+    #[test]
+    fn patches_are_deltas_if_they_are_smaller_than_the_literal() {
+        let base: Vec<u8> = (0..10_000u32).flat_map(|n| n.to_le_bytes()).collect();
+        let mut target = base.clone();
+        target[5_000] = 0xff;
+
+        let patch = binary::Patch::compute(&base, &target);
+        let delta = match &patch {
+            binary::Patch::Delta(delta) => delta,
+            binary::Patch::Literal(_) => panic!("a small change results in a delta"),
+        };
+        assert!(delta.len() < 100);
+
+        let (_, base_header_len) = git_odb::pack::data::delta::decode_header_size(delta);
+        let (target_size, target_header_len) =
+            git_odb::pack::data::delta::decode_header_size(&delta[base_header_len..]);
+        let mut actual = vec![0; target_size as usize];
+        git_odb::pack::data::delta::apply(&base, &mut actual, &delta[base_header_len + target_header_len..]);
+        assert_eq!(actual, target);
+
+        assert_eq!(
+            binary::Patch::compute(b"", &target),
+            binary::Patch::Literal(target.clone()),
+            "there is nothing to copy from"
+        );
+        assert_eq!(
+            binary::Patch::compute(&base, b"\0"),
+            binary::Patch::Literal(b"\0".to_vec()),
+            "unrelated content"
+        );
+    }
+}
+
+mod merge {
+    use git_diff::blob::merge::{self, ConflictStyle, Favor, Labels, Options};
+    use git_object::bstr::ByteSlice;
+
+    const BASE: &[u8] = b"1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+    const OURS: &[u8] = b"1\ntwo\n3\n4\n5\nsix\nseven\n8\n9\n";
+    const THEIRS: &[u8] = b"1\n2\n3\n4\n5\nsix\n7\neight\n9\n";
+
+    fn labels() -> Labels<'static> {
+        Labels {
+            base: Some("base".into()),
+            ours: Some("ours".into()),
+            theirs: Some("theirs".into()),
+        }
+    }
+
+    fn merge(style: ConflictStyle, favor: Option<Favor>) -> merge::Outcome {
+        merge::text(
+            BASE,
+            OURS,
+            THEIRS,
+            labels(),
+            Options {
+                style,
+                favor,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn changes_that_dont_touch_merge_cleanly() {
+        let outcome = merge::text(
+            BASE,
+            b"1\ntwo\n3\n4\n5\n6\n7\n8\n9\n",
+            b"1\n2\n3\n4\n5\n6\n7\neight\n9\n",
+            labels(),
+            Default::default(),
+        );
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.data.as_bstr(), "1\ntwo\n3\n4\n5\n6\n7\neight\n9\n");
+
+        let outcome = merge::text(BASE, OURS, OURS, labels(), Default::default());
+        assert_eq!(outcome.conflicts, 0, "the same change on both sides doesn't conflict");
+        assert_eq!(outcome.data.as_bstr(), OURS.as_bstr());
+    }
+
+    #[test]
+    fn conflicts_are_marked_in_the_configured_style_like_git_does() {
+        let outcome = merge(ConflictStyle::Merge, None);
+        assert_eq!(outcome.conflicts, 1);
+        assert_eq!(
+            outcome.data.as_bstr(),
+            "1\ntwo\n3\n4\n5\nsix\n<<<<<<< ours\nseven\n8\n=======\n7\neight\n>>>>>>> theirs\n9\n",
+            "lines both sides have in common are moved out of the conflict"
+        );
+
+        assert_eq!(
+            merge(ConflictStyle::Diff3, None).data.as_bstr(),
+            "1\ntwo\n3\n4\n5\n<<<<<<< ours\nsix\nseven\n8\n||||||| base\n6\n7\n8\n=======\nsix\n7\neight\n>>>>>>> theirs\n9\n"
+        );
+        assert_eq!(
+            merge(ConflictStyle::ZealousDiff3, None).data.as_bstr(),
+            "1\ntwo\n3\n4\n5\nsix\n<<<<<<< ours\nseven\n8\n||||||| base\n6\n7\n8\n=======\n7\neight\n>>>>>>> theirs\n9\n"
+        );
+    }
+
+    #[test]
+    fn conflicts_can_be_resolved_in_favor_of_one_or_both_sides() {
+        let outcome = merge(ConflictStyle::Merge, Some(Favor::Ours));
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.data.as_bstr(), OURS.as_bstr());
+        assert_eq!(
+            merge(ConflictStyle::Merge, Some(Favor::Theirs)).data.as_bstr(),
+            "1\ntwo\n3\n4\n5\nsix\n7\neight\n9\n"
+        );
+        assert_eq!(
+            merge(ConflictStyle::Merge, Some(Favor::Union)).data.as_bstr(),
+            "1\ntwo\n3\n4\n5\nsix\nseven\n8\n7\neight\n9\n"
+        );
+    }
+
+    #[test]
+    fn markers_have_the_configured_size_and_start_on_their_own_line() {
+        let outcome = merge::text(
+            b"a\nb",
+            b"a\nours",
+            b"a\ntheirs",
+            Labels {
+                ours: Some("o".into()),
+                theirs: Some("t".into()),
+                ..Default::default()
+            },
+            Options {
+                marker_size: 3,
+                ..Default::default()
+            },
+        );
+        assert_eq!(outcome.data.as_bstr(), "a\n<<< o\nours\n===\ntheirs\n>>> t\n");
+    }
+
+    #[test]
+    fn conflict_styles_can_be_parsed_from_configuration() {
+        assert_eq!(
+            ConflictStyle::from_config_value(b"zdiff3"),
+            Some(ConflictStyle::ZealousDiff3)
+        );
+        assert_eq!(ConflictStyle::from_config_value(b"diff3"), Some(ConflictStyle::Diff3));
+        assert_eq!(ConflictStyle::from_config_value(b"merge"), Some(ConflictStyle::Merge));
+        assert_eq!(ConflictStyle::from_config_value(b"unknown"), None);
+    }
+}
+
+mod intra_line {
+    use git_diff::blob::{
+        intra_line::{self, Change, Granularity},
+        Algorithm,
+    };
+
+    use super::hunk;
+
+    fn change(before: std::ops::Range<usize>, after: std::ops::Range<usize>) -> Change {
+        Change { before, after }
+    }
+
+    #[test]
+    fn lines_of_a_hunk_are_paired_in_order() {
+        assert_eq!(
+            intra_line::line_pairs(&hunk(2..5, 7..9)).collect::<Vec<_>>(),
+            vec![(2, 7), (3, 8)]
+        );
+        assert_eq!(
+            intra_line::line_pairs(&hunk(2..2, 7..9)).count(),
+            0,
+            "additions have no pairs"
+        );
+    }
+
+    #[test]
+    fn changed_words_are_returned_as_byte_ranges() {
+        let (before, after) = (b"let x = foo(1);\n", b"let y = foo(10);\n");
+        assert_eq!(
+            intra_line::ranges(Algorithm::Myers, before, after, Granularity::Words),
+            vec![change(4..5, 4..5), change(12..13, 12..14)]
+        );
+    }
+
+    #[test]
+    fn changed_characters_are_returned_as_byte_ranges() {
+        let (before, after) = (b"let x = foo(1);\n", b"let y = foo(10);\n");
+        assert_eq!(
+            intra_line::ranges(Algorithm::Myers, before, after, Granularity::Characters),
+            vec![change(4..5, 4..5), change(13..13, 13..14)]
+        );
+        assert_eq!(
+            intra_line::ranges(
+                Algorithm::Myers,
+                "naïve".as_bytes(),
+                "naive".as_bytes(),
+                Granularity::Characters
+            ),
+            vec![change(2..4, 2..3)],
+            "multi-byte characters are compared as a whole"
+        );
+    }
+
+    #[test]
+    fn changes_at_the_end_are_positioned_after_the_last_byte() {
+        assert_eq!(
+            intra_line::ranges(Algorithm::Myers, b"a b", b"a b c", Granularity::Words),
+            vec![change(3..3, 3..5)]
+        );
+    }
+}
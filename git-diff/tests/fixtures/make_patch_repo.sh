@@ -0,0 +1,39 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config core.autocrlf false
+
+git checkout -q -b main
+
+for n in $(seq 1 40); do
+  if [ $n = 20 ]; then echo "fn function_in_the_middle() {"; else echo "    line $n"; fi
+done > a-modified
+echo content > b-mode
+echo content > c-mode-and-content
+printf 'one\ntwo\nthree\n' > d-deleted
+printf 'binary\0content\n' > g-binary
+printf 'one\ntwo' > h-no-newline
+for n in $(seq 1 20); do echo "this line is unchanged when renamed $n"; done > i-renamed-from
+git add .
+git commit -qm "before"
+
+sed -i 's/line 3$/line three/; s/line 30$/line thirty/' a-modified
+chmod +x b-mode c-mode-and-content
+echo changed >> c-mode-and-content
+rm d-deleted
+printf 'new\nfile\n' > e-added
+touch f-empty
+printf 'binary\0changed\n' > g-binary
+printf 'one\n2' > h-no-newline
+ln -s e-added j-link
+git mv i-renamed-from y-renamed-to
+echo "this line was added" >> y-renamed-to
+mkdir zz-dir
+echo nested > zz-dir/file
+git add .
+git commit -qm "after"
+
+git diff HEAD~1 HEAD > expected.patch
+git diff --numstat HEAD~1 HEAD > expected.numstat
@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use git_diff::{
+    blob::{self, Algorithm},
+    patch,
+    tree::{recorder, rewrites, Changes, Recorder, State},
+};
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BString, ByteSlice},
+    BlobRef, TreeRefIter,
+};
+use git_odb::{linked, pack::Find};
+
+use crate::hex_to_id;
+
+fn tree_of_commit<'a>(db: &linked::Store, commit: &oid, buf: &'a mut Vec<u8>) -> crate::Result<TreeRefIter<'a>> {
+    let tree_id = db
+        .try_find(commit, buf)?
+        .expect("commit present")
+        .0
+        .decode()?
+        .into_commit()
+        .expect("id is a commit")
+        .tree();
+    Ok(db
+        .try_find(tree_id, buf)?
+        .expect("tree present")
+        .0
+        .try_into_tree_iter()
+        .expect("id is a tree"))
+}
+
+/// Return the fixture directory, its object database and the changes between its two commits, with renames.
+pub fn fixture_changes() -> crate::Result<(std::path::PathBuf, linked::Store, Vec<rewrites::Change>)> {
+    let dir = git_testtools::scripted_fixture_repo_read_only("make_patch_repo.sh")?;
+    let db = linked::Store::at(dir.join(".git").join("objects"))?;
+
+    let (mut buf, mut other_buf) = (Vec::new(), Vec::new());
+    let before = tree_of_commit(&db, &hex_to_id("02767af301a9432f9d225e3a4e579166c8402afa"), &mut buf)?;
+    let after = tree_of_commit(
+        &db,
+        &hex_to_id("f066de87854fa67f5341df07c076e60ce35f9b5e"),
+        &mut other_buf,
+    )?;
+    let mut recorder = Recorder::default();
+    Changes::from(before).needed_to_obtain(
+        after,
+        State::default(),
+        |oid, buf| {
+            db.try_find(oid, buf)
+                .ok()
+                .flatten()
+                .and_then(|obj| obj.0.try_into_tree_iter())
+        },
+        &mut recorder,
+    )?;
+
+    let changes = rewrites::track(recorder.records, rewrites::Options::default(), |oid, buf| {
+        db.try_find(oid, buf)
+            .ok()
+            .flatten()
+            .map(|obj| BlobRef { data: obj.0.data })
+    })?;
+    Ok((dir, db, changes))
+}
+
+#[test]
+fn changes_with_renames_are_written_like_git_does() -> crate::Result {
+    let (dir, db, changes) = fixture_changes()?;
+    let mut actual = Vec::new();
+    for change in &changes {
+        patch::write_change(&mut actual, change, patch::Options::default(), |oid, buf| {
+            db.try_find(oid, buf)
+                .ok()
+                .flatten()
+                .map(|obj| BlobRef { data: obj.0.data })
+        })?;
+    }
+    let expected = std::fs::read(dir.join("expected.patch"))?;
+    assert_eq!(actual.as_bstr(), expected.as_bstr());
+    Ok(())
+}
+
+#[test]
+fn hunks_with_overlapping_context_are_merged() -> crate::Result {
+    let before = b"1\n2\n3\n4\n5\n6\n7\n8\n";
+    let after = b"1\ntwo\n3\n4\n5\n6\nseven\n8\n";
+    let hunks = blob::diff(Algorithm::Myers, before, after);
+
+    let mut out = Vec::new();
+    patch::write_hunks(&mut out, before, after, &hunks, 1)?;
+    assert_eq!(
+        out.as_bstr(),
+        "@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -6,3 +6,3 @@\n 6\n-7\n+seven\n 8\n",
+        "the hunks are far enough apart"
+    );
+
+    out.clear();
+    patch::write_hunks(&mut out, before, after, &hunks, 2)?;
+    assert_eq!(
+        out.as_bstr(),
+        "@@ -1,8 +1,8 @@\n 1\n-2\n+two\n 3\n 4\n 5\n 6\n-7\n+seven\n 8\n",
+        "the context of both hunks touches"
+    );
+
+    out.clear();
+    patch::write_hunks(&mut out, before, after, &hunks, 0)?;
+    assert_eq!(
+        out.as_bstr(),
+        "@@ -2 +2 @@\n-2\n+two\n@@ -7 +7 @@\n-7\n+seven\n",
+        "without context"
+    );
+    Ok(())
+}
+
+/// Return the blob ids of all changed paths before and after the changes.
+fn blob_ids(changes: &[rewrites::Change]) -> (HashMap<BString, ObjectId>, HashMap<BString, ObjectId>) {
+    let (mut before, mut after) = (HashMap::new(), HashMap::new());
+    for change in changes {
+        match change {
+            rewrites::Change::Recorded(recorder::Change::Addition { oid, path, .. }) => {
+                after.insert(path.clone(), *oid);
+            }
+            rewrites::Change::Recorded(recorder::Change::Deletion { oid, path, .. }) => {
+                before.insert(path.clone(), *oid);
+            }
+            rewrites::Change::Recorded(recorder::Change::Modification {
+                previous_oid,
+                oid,
+                path,
+                ..
+            }) => {
+                before.insert(path.clone(), *previous_oid);
+                after.insert(path.clone(), *oid);
+            }
+            rewrites::Change::Rename {
+                source_oid,
+                source_path,
+                oid,
+                path,
+                ..
+            }
+            | rewrites::Change::Copy {
+                source_oid,
+                source_path,
+                oid,
+                path,
+                ..
+            } => {
+                before.insert(source_path.clone(), *source_oid);
+                after.insert(path.clone(), *oid);
+            }
+        }
+    }
+    (before, after)
+}
+
+#[test]
+fn patches_written_by_git_apply_to_the_source_blobs() -> crate::Result {
+    let (dir, db, changes) = fixture_changes()?;
+    let (before_ids, after_ids) = blob_ids(&changes);
+    let files = patch::parse::files(&std::fs::read(dir.join("expected.patch"))?)?;
+    assert_eq!(files.len(), 11, "one for each changed file, but none for trees");
+
+    let blob = |id: &ObjectId| -> crate::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        Ok(db.try_find(id, &mut buf)?.expect("blob present").0.data.to_owned())
+    };
+    for file in &files {
+        let before = file
+            .source_path
+            .as_ref()
+            .map(|path| blob(&before_ids[path]))
+            .transpose()?;
+        let after = file.path.as_ref().map(|path| blob(&after_ids[path])).transpose()?;
+        if file.binary {
+            assert!(matches!(
+                patch::apply::file(file, before.as_deref(), Default::default()),
+                Err(patch::apply::Error::Binary)
+            ));
+            continue;
+        }
+        let outcome = patch::apply::file(file, before.as_deref(), Default::default())?;
+        assert_eq!(outcome.rejected, Vec::<usize>::new(), "{:?} applies cleanly", file.path);
+        assert_eq!(outcome.data, after, "{:?} has the expected content", file.path);
+    }
+
+    let renamed = files.iter().find(|file| file.rewrite.is_some()).expect("one rename");
+    assert_eq!(renamed.rewrite, Some(patch::parse::Rewrite::Rename));
+    assert_eq!(
+        renamed.source_path.as_ref().map(|p| p.as_bstr()),
+        Some("i-renamed-from".into())
+    );
+    assert_eq!(renamed.path.as_ref().map(|p| p.as_bstr()), Some("y-renamed-to".into()));
+
+    let mode_change = files
+        .iter()
+        .find(|file| file.path == Some("b-mode".into()))
+        .expect("present");
+    assert_eq!(mode_change.source_mode, Some(git_object::tree::EntryMode::Blob));
+    assert_eq!(mode_change.mode, Some(git_object::tree::EntryMode::BlobExecutable));
+    assert!(mode_change.hunks.is_empty());
+    Ok(())
+}
+
+#[test]
+fn hunks_apply_at_an_offset_with_fuzz_or_are_rejected() -> crate::Result {
+    let patch = b"--- a/file\n+++ b/file\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n";
+    let files = patch::parse::files(patch)?;
+    let hunks = &files[0].hunks;
+
+    let (data, rejected) = patch::apply::hunks(b"0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n", hunks, Default::default());
+    assert_eq!(
+        data.as_bstr(),
+        "0\n1\n2\n3\n4\nfive\n6\n7\n8\n9\n",
+        "applied one line later"
+    );
+    assert!(rejected.is_empty());
+
+    let changed_context = b"1\nX\n3\n4\n5\n6\n7\n8\n9\n";
+    let (data, rejected) = patch::apply::hunks(changed_context, hunks, Default::default());
+    assert_eq!(data.as_bstr(), changed_context.as_bstr(), "nothing changes");
+    assert_eq!(rejected, vec![0], "the context doesn't match");
+
+    let (data, rejected) = patch::apply::hunks(
+        changed_context,
+        hunks,
+        patch::apply::Options {
+            fuzz: 1,
+            ..Default::default()
+        },
+    );
+    assert_eq!(data.as_bstr(), "1\nX\n3\n4\nfive\n6\n7\n8\n9\n");
+    assert!(rejected.is_empty(), "the first line of context is ignored");
+    Ok(())
+}
+
+/// Return a writable copy of the patch fixture with the source commit checked out, along with the parsed patch of all
+/// non-binary files.
+fn worktree_of_source_commit() -> crate::Result<(git_testtools::tempfile::TempDir, Vec<patch::parse::File>)> {
+    let dir = git_testtools::scripted_fixture_repo_writable("make_patch_repo.sh")?;
+    assert!(git_testtools::run_git(dir.path(), &["checkout", "-q", "-f", "HEAD~1"])?.success());
+    let mut files = patch::parse::files(&std::fs::read(dir.path().join("expected.patch"))?)?;
+    files.retain(|file| !file.binary);
+    Ok((dir, files))
+}
+
+#[test]
+fn patches_written_by_git_apply_to_the_worktree_of_the_source_commit() -> crate::Result {
+    let (_, db, changes) = fixture_changes()?;
+    let (_, after_ids) = blob_ids(&changes);
+    let (dir, files) = worktree_of_source_commit()?;
+
+    let outcomes = patch::apply::worktree(&files, dir.path(), Default::default())?;
+    assert!(outcomes.iter().all(|outcome| outcome.rejected.is_empty()));
+
+    for file in files
+        .iter()
+        .filter(|file| file.mode != Some(git_object::tree::EntryMode::Link))
+    {
+        let path = match &file.path {
+            Some(path) => path,
+            None => continue,
+        };
+        let mut buf = Vec::new();
+        let expected = db.try_find(after_ids[path], &mut buf)?.expect("blob present").0.data;
+        assert_eq!(
+            std::fs::read(dir.path().join(path.to_path()?))?.as_bstr(),
+            expected.as_bstr(),
+            "{} has the expected content",
+            path
+        );
+    }
+    for deleted in ["d-deleted", "i-renamed-from"] {
+        assert!(!dir.path().join(deleted).exists(), "{} was removed", deleted);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let is_executable = |path: &str| -> std::io::Result<bool> {
+            Ok(std::fs::metadata(dir.path().join(path))?.permissions().mode() & 0o100 == 0o100)
+        };
+        assert!(is_executable("b-mode")?, "the mode changed");
+        assert!(
+            is_executable("c-mode-and-content")?,
+            "the mode changed along with the content"
+        );
+        assert!(!is_executable("a-modified")?, "the mode didn't change");
+        assert_eq!(
+            std::fs::read_link(dir.path().join("j-link"))?,
+            std::path::Path::new("e-added"),
+            "links are created with their content as target"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn worktree_is_untouched_if_a_hunk_is_rejected() -> crate::Result {
+    let (dir, files) = worktree_of_source_commit()?;
+    std::fs::write(dir.path().join("a-modified"), "changed entirely\n")?;
+
+    let outcomes = patch::apply::worktree(&files, dir.path(), Default::default())?;
+    let rejected: Vec<_> = files
+        .iter()
+        .zip(&outcomes)
+        .filter(|(_, outcome)| !outcome.rejected.is_empty())
+        .map(|(file, _)| file.path.as_ref().expect("modified").as_bstr())
+        .collect();
+    assert_eq!(rejected, vec!["a-modified"]);
+
+    assert_eq!(
+        std::fs::read(dir.path().join("a-modified"))?.as_bstr(),
+        "changed entirely\n"
+    );
+    assert!(dir.path().join("d-deleted").exists(), "deletions aren't performed");
+    assert!(dir.path().join("i-renamed-from").exists(), "renames aren't performed");
+    assert!(!dir.path().join("e-added").exists(), "additions aren't performed");
+    Ok(())
+}
+
+#[test]
+fn paths_outside_of_the_worktree_are_rejected() -> crate::Result {
+    let files = patch::parse::files(b"--- /dev/null\n+++ b/../escaped\n@@ -0,0 +1 @@\n+content\n")?;
+    let dir = git_testtools::tempfile::tempdir()?;
+    assert!(matches!(
+        patch::apply::worktree(&files, dir.path(), Default::default()),
+        Err(patch::apply::worktree::Error::InvalidPath { .. })
+    ));
+    Ok(())
+}
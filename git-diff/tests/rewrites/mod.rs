@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use git_diff::tree::{
+    recorder,
+    rewrites::{self, Change, Options},
+};
+use git_hash::ObjectId;
+use git_object::tree::EntryMode;
+
+use crate::hex_to_id;
+
+const NUMBERS: &[u8] = b"1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+const NUMBERS_WITH_WORD: &[u8] = b"1\n2\n3\n4\nfive\n6\n7\n8\n9\n10\n";
+
+fn numbers() -> ObjectId {
+    hex_to_id("1111111111111111111111111111111111111111")
+}
+
+fn numbers_with_word() -> ObjectId {
+    hex_to_id("2222222222222222222222222222222222222222")
+}
+
+fn unrelated() -> ObjectId {
+    hex_to_id("3333333333333333333333333333333333333333")
+}
+
+fn track(changes: Vec<recorder::Change>, options: Options) -> Result<Vec<Change>, rewrites::Error> {
+    let blobs: HashMap<_, _> = vec![
+        (numbers(), NUMBERS),
+        (numbers_with_word(), NUMBERS_WITH_WORD),
+        (unrelated(), &b"something else\n"[..]),
+    ]
+    .into_iter()
+    .collect();
+    rewrites::track(changes, options, |id, buf| {
+        let data = blobs.get(&id.to_owned())?;
+        buf.clear();
+        buf.extend_from_slice(data);
+        Some(git_object::BlobRef { data: buf })
+    })
+}
+
+fn deletion(oid: ObjectId, path: &str) -> recorder::Change {
+    recorder::Change::Deletion {
+        entry_mode: EntryMode::Blob,
+        oid,
+        path: path.into(),
+    }
+}
+
+fn addition(oid: ObjectId, path: &str) -> recorder::Change {
+    recorder::Change::Addition {
+        entry_mode: EntryMode::Blob,
+        oid,
+        path: path.into(),
+    }
+}
+
+fn modification(previous_oid: ObjectId, oid: ObjectId, path: &str) -> recorder::Change {
+    recorder::Change::Modification {
+        previous_entry_mode: EntryMode::Blob,
+        previous_oid,
+        entry_mode: EntryMode::Blob,
+        oid,
+        path: path.into(),
+    }
+}
+
+#[test]
+fn identical_content_is_a_rename_without_reading_blobs() -> crate::Result {
+    let changes = vec![
+        deletion(numbers(), "old"),
+        modification(numbers(), unrelated(), "modified"),
+        addition(numbers(), "new"),
+    ];
+    let actual = rewrites::track(changes, Options::default(), |_, _| None)?;
+    assert_eq!(
+        actual,
+        vec![
+            Change::Recorded(modification(numbers(), unrelated(), "modified")),
+            Change::Rename {
+                source_entry_mode: EntryMode::Blob,
+                source_oid: numbers(),
+                source_path: "old".into(),
+                entry_mode: EntryMode::Blob,
+                oid: numbers(),
+                path: "new".into(),
+                similarity: 1.0,
+            }
+        ],
+        "the deletion is consumed and the addition turns into a rename"
+    );
+    Ok(())
+}
+
+#[test]
+fn similar_content_is_a_rename_if_it_reaches_the_percentage() -> crate::Result {
+    let changes = || {
+        vec![
+            deletion(numbers(), "old"),
+            addition(unrelated(), "other"),
+            addition(numbers_with_word(), "new"),
+        ]
+    };
+    let actual = track(changes(), Options::default())?;
+    assert_eq!(actual.len(), 2);
+    assert_eq!(actual[0], Change::Recorded(addition(unrelated(), "other")));
+    match &actual[1] {
+        Change::Rename {
+            source_path,
+            path,
+            similarity,
+            ..
+        } => {
+            assert_eq!(source_path, "old");
+            assert_eq!(path, "new");
+            assert!(
+                (*similarity - 19.0 / 24.0).abs() < f32::EPSILON,
+                "all bytes but the changed line are the same"
+            );
+        }
+        change => panic!("expected a rename, got {:?}", change),
+    }
+
+    for options in &[
+        Options {
+            percentage: Some(0.8),
+            ..Default::default()
+        },
+        Options {
+            percentage: None,
+            ..Default::default()
+        },
+        Options {
+            limit: 1,
+            ..Default::default()
+        },
+    ] {
+        assert_eq!(
+            track(changes(), *options)?,
+            changes().into_iter().map(Change::Recorded).collect::<Vec<_>>(),
+            "not similar enough, similarity disabled or too many candidates"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn copies_are_detected_from_modified_and_renamed_files_if_enabled() -> crate::Result {
+    let changes = || {
+        vec![
+            modification(numbers(), unrelated(), "modified"),
+            deletion(numbers_with_word(), "old"),
+            addition(numbers_with_word(), "new"),
+            addition(numbers_with_word(), "copy-of-renamed"),
+            addition(numbers(), "copy-of-modified"),
+        ]
+    };
+    assert_eq!(
+        track(changes(), Options::default())?.len(),
+        4,
+        "without copies, only the rename is detected"
+    );
+
+    let actual = track(
+        changes(),
+        Options {
+            copies: true,
+            ..Default::default()
+        },
+    )?;
+    let summary: Vec<_> = actual
+        .iter()
+        .map(|change| match change {
+            Change::Recorded(_) => ("recorded", None),
+            Change::Rename { source_path, .. } => ("rename", Some(source_path.to_string())),
+            Change::Copy { source_path, .. } => ("copy", Some(source_path.to_string())),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("recorded", None),
+            ("rename", Some("old".into())),
+            ("copy", Some("old".into())),
+            ("copy", Some("modified".into())),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn trees_submodules_and_mismatching_kinds_are_never_paired() -> crate::Result {
+    let changes = vec![
+        recorder::Change::Deletion {
+            entry_mode: EntryMode::Tree,
+            oid: numbers(),
+            path: "dir".into(),
+        },
+        recorder::Change::Addition {
+            entry_mode: EntryMode::Tree,
+            oid: numbers(),
+            path: "renamed-dir".into(),
+        },
+        recorder::Change::Deletion {
+            entry_mode: EntryMode::Commit,
+            oid: unrelated(),
+            path: "submodule".into(),
+        },
+        recorder::Change::Addition {
+            entry_mode: EntryMode::Commit,
+            oid: unrelated(),
+            path: "renamed-submodule".into(),
+        },
+        deletion(numbers_with_word(), "file"),
+        recorder::Change::Addition {
+            entry_mode: EntryMode::Link,
+            oid: numbers_with_word(),
+            path: "link".into(),
+        },
+    ];
+    assert_eq!(
+        track(changes.clone(), Options::default())?,
+        changes.into_iter().map(Change::Recorded).collect::<Vec<_>>()
+    );
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use git_diff::{
+    blob::Algorithm,
+    stat::{self, Counts},
+};
+use git_object::{bstr::ByteSlice, BlobRef};
+use git_odb::pack::Find;
+
+#[test]
+fn counts_are_the_same_as_the_ones_of_git() -> crate::Result {
+    let (dir, db, changes) = crate::patch::fixture_changes()?;
+    let stats = stat::compute(&changes, Algorithm::Myers, |oid, buf| {
+        db.try_find(oid, buf)
+            .ok()
+            .flatten()
+            .map(|obj| BlobRef { data: obj.0.data })
+    })?;
+
+    let mut numstat = String::new();
+    for file in &stats.files {
+        let counts = match file.counts {
+            Counts::Lines { insertions, deletions } => format!("{}\t{}", insertions, deletions),
+            Counts::Binary { .. } => "-\t-".into(),
+        };
+        let path = match &file.source_path {
+            Some(source_path) => format!("{} => {}", source_path, file.path),
+            None => file.path.to_string(),
+        };
+        numstat.push_str(&format!("{}\t{}\n", counts, path));
+    }
+    let expected = std::fs::read(dir.join("expected.numstat"))?;
+    assert_eq!(numstat.as_bytes().as_bstr(), expected.as_bstr());
+
+    assert_eq!(stats.files.len(), 11, "trees aren't counted");
+    assert_eq!((stats.insertions(), stats.deletions()), (9, 6));
+    assert!(stats.files.iter().any(|file| file.counts
+        == Counts::Binary {
+            before_size: 15,
+            after_size: 15
+        }));
+    Ok(())
+}
@@ -1,7 +1,7 @@
 #![allow(deprecated)]
 mod changes {
     mod to_obtain_tree {
-        use git_diff::tree::{recorder, recorder::Change::*};
+        use git_diff::tree::{recorder, recorder::Change::*, Pathspec};
         use git_hash::{oid, ObjectId};
         use git_object::{bstr::ByteSlice, tree::EntryMode, TreeRefIter};
         use git_odb::{linked, pack::Find};
@@ -42,6 +42,15 @@ mod changes {
         }
 
         fn diff_commits(db: &linked::Store, lhs: impl Into<Option<ObjectId>>, rhs: &oid) -> crate::Result<Changes> {
+            diff_commits_with_pathspec(db, lhs, rhs, &Pathspec::default())
+        }
+
+        fn diff_commits_with_pathspec(
+            db: &linked::Store,
+            lhs: impl Into<Option<ObjectId>>,
+            rhs: &oid,
+            pathspec: &Pathspec,
+        ) -> crate::Result<Changes> {
             let mut buf = Vec::new();
             let lhs_tree = lhs
                 .into()
@@ -49,7 +58,7 @@ mod changes {
             let mut buf2 = Vec::new();
             let rhs_tree = locate_tree_by_commit(db, rhs, &mut buf2)?;
             let mut recorder = git_diff::tree::Recorder::default();
-            git_diff::tree::Changes::from(lhs_tree).needed_to_obtain(
+            git_diff::tree::Changes::from(lhs_tree).needed_to_obtain_with_pathspec(
                 rhs_tree,
                 git_diff::tree::State::default(),
                 |oid, buf| {
@@ -58,6 +67,7 @@ mod changes {
                         .flatten()
                         .and_then(|obj| obj.0.try_into_tree_iter())
                 },
+                pathspec,
                 &mut recorder,
             )?;
             Ok(recorder.records)
@@ -615,5 +625,50 @@ mod changes {
             );
             Ok(())
         }
+
+        #[test]
+        fn maximal_difference_nested_with_pathspec() -> crate::Result {
+            let db = db(["a"].iter().copied())?;
+            let all_commits = all_commits(&db);
+            let commit = &all_commits[all_commits.len() - 6];
+
+            assert_eq!(
+                diff_commits_with_pathspec(&db, None::<ObjectId>, commit, &Pathspec::new(vec!["a/c", "a/g/"]))?,
+                vec![
+                    Addition {
+                        entry_mode: EntryMode::Tree,
+                        oid: hex_to_id("0df4d0ed769eacd0a231e7512fca25d3cabdeca4"),
+                        path: "a".into()
+                    },
+                    Addition {
+                        entry_mode: EntryMode::Blob,
+                        oid: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                        path: "a/c".into()
+                    },
+                    Addition {
+                        entry_mode: EntryMode::Tree,
+                        oid: hex_to_id("496d6428b9cf92981dc9495211e6e1120fb6f2ba"),
+                        path: "a/g".into()
+                    },
+                    Addition {
+                        entry_mode: EntryMode::Blob,
+                        oid: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+                        path: "a/g/a".into()
+                    }
+                ],
+                "leading trees are reported, and only matching entries are visited"
+            );
+            assert_eq!(
+                diff_commits_with_pathspec(&db, None::<ObjectId>, commit, &Pathspec::new(vec!["b"]))?,
+                vec![],
+                "nothing matches outside of the nested directory"
+            );
+            assert_eq!(
+                diff_commits_with_pathspec(&db, None::<ObjectId>, commit, &Pathspec::default())?,
+                diff_commits(&db, None::<ObjectId>, commit)?,
+                "an empty pathspec matches everything"
+            );
+            Ok(())
+        }
     }
 }
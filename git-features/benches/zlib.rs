@@ -0,0 +1,61 @@
+use std::io::Write;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use git_features::zlib::{stream::deflate, Backend, Inflate};
+
+/// Produce `len` bytes resembling source code, which compresses about as well as typical objects in a repository.
+fn input(len: usize) -> Vec<u8> {
+    let words = [
+        "fn ", "let ", "mut ", "self", ".iter()", "impl ", "pub ", "use ", "std::", "{\n", "}\n", "    ", "Ok(())",
+        "; ",
+    ];
+    let mut state = 0x2545_f491_u32;
+    let mut out = Vec::with_capacity(len + 16);
+    while out.len() < len {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        out.extend_from_slice(words[state as usize % words.len()].as_bytes());
+    }
+    out.truncate(len);
+    out
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut w = deflate::Write::new(Vec::new());
+    w.write_all(data).unwrap();
+    w.flush().unwrap();
+    w.into_inner()
+}
+
+fn deflate(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("deflate with {}", Backend::active()));
+    for len in [4 * 1024, 1024 * 1024] {
+        let data = input(len);
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &data, |b, data| {
+            b.iter(|| compress(black_box(data)))
+        });
+    }
+    group.finish();
+}
+
+fn inflate(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("inflate with {}", Backend::active()));
+    for len in [4 * 1024, 1024 * 1024] {
+        let compressed = compress(&input(len));
+        let mut out = vec![0; len];
+        group.throughput(Throughput::Bytes(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &compressed, |b, compressed| {
+            b.iter(|| {
+                let mut inflate = Inflate::default();
+                let (_status, _consumed, written) = inflate.once(black_box(compressed), &mut out).unwrap();
+                assert_eq!(written, len);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, deflate, inflate);
+criterion_main!(benches);
@@ -3,7 +3,14 @@
 //! With the `fast-sha1` feature, the `Sha1` hash type will use a more elaborate implementation utilizing hardware support
 //! in case it is available. Otherwise the `rustsha1` feature should be set. `fast-sha1` will take precedence.
 //! Otherwise, a minimal yet performant implementation is used instead for a decent trade-off between compile times and run-time performance.
-#[cfg(all(feature = "rustsha1", not(feature = "fast-sha1")))]
+//!
+//! The `sha1dc` feature takes precedence over both and uses an implementation which detects SHA1 collision attacks,
+//! just like git does by default. Use [`Hasher::try_digest()`] to fail on data that is part of such an attack, which is
+//! what should be done when hashing objects received from others. Use [`sha1_uses_cpu_extensions()`] to learn whether
+//! hashing is hardware accelerated.
+//!
+//! [`Sha256`][git_hash::Kind::Sha256] hashes are always supported alongside any of the `Sha1` implementations.
+#[cfg(all(feature = "rustsha1", not(feature = "fast-sha1"), not(feature = "sha1dc")))]
 mod _impl {
     use super::Sha1Digest;
 
@@ -20,14 +27,18 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.digest().bytes()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collision attacks.
+        pub fn try_digest(self) -> Result<Sha1Digest, super::Collision> {
+            Ok(self.digest())
+        }
     }
 }
 
 /// A 20 bytes digest produced by a [`Sha1`] hash implementation.
-#[cfg(any(feature = "fast-sha1", feature = "rustsha1"))]
+#[cfg(any(feature = "fast-sha1", feature = "rustsha1", feature = "sha1dc"))]
 pub type Sha1Digest = [u8; 20];
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1dc")))]
 mod _impl {
     use sha1::Digest;
 
@@ -46,12 +57,179 @@ mod _impl {
         pub fn digest(self) -> Sha1Digest {
             self.0.finalize().into()
         }
+        /// Finalize the hash and produce a digest, which never fails as this implementation doesn't detect collision attacks.
+        pub fn try_digest(self) -> Result<Sha1Digest, super::Collision> {
+            Ok(self.digest())
+        }
+    }
+}
+
+#[cfg(feature = "sha1dc")]
+mod _impl {
+    use sha1_checked::{CollisionResult, Digest};
+
+    use super::{Collision, Sha1Digest};
+
+    /// A implementation of the Sha1 hash which detects collision attacks, and which can be used once.
+    #[derive(Clone)]
+    pub struct Sha1(sha1_checked::Sha1);
+
+    impl Default for Sha1 {
+        fn default() -> Self {
+            Sha1(sha1_checked::Sha1::builder().safe_hash(true).build())
+        }
+    }
+
+    impl Sha1 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            self.0.update(bytes)
+        }
+        /// Finalize the hash and produce a digest.
+        ///
+        /// If a collision attack was detected, the returned digest is the one computed in safe-hash mode, which differs
+        /// from the one the attacker was aiming for. Use [`try_digest()`][Sha1::try_digest()] to learn about the attack.
+        pub fn digest(self) -> Sha1Digest {
+            match self.0.try_finalize() {
+                CollisionResult::Ok(digest)
+                | CollisionResult::Mitigated(digest)
+                | CollisionResult::Collision(digest) => digest.into(),
+            }
+        }
+        /// Finalize the hash and produce a digest, or fail if the hashed data is part of a collision attack.
+        pub fn try_digest(self) -> Result<Sha1Digest, Collision> {
+            match self.0.try_finalize() {
+                CollisionResult::Ok(digest) => Ok(digest.into()),
+                CollisionResult::Mitigated(digest) | CollisionResult::Collision(digest) => Err(Collision {
+                    safe_digest: git_hash::ObjectId::from(<Sha1Digest>::from(digest)),
+                }),
+            }
+        }
     }
 }
 
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 pub use _impl::Sha1;
 
+/// The error returned if hashed data was detected to be part of a SHA1 collision attack.
+///
+/// It is only ever produced if the `sha1dc` feature is enabled.
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+#[derive(Debug, Clone)]
+pub struct Collision {
+    /// The digest computed in safe-hash mode, which differs from the one the attack was aiming for.
+    pub safe_digest: git_hash::ObjectId,
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+impl std::fmt::Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SHA1 collision attack detected, safe digest is {}", self.safe_digest)
+    }
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+impl std::error::Error for Collision {}
+
+/// Return true if the [`Sha1`] implementation uses the SHA extensions of the CPU, which is the case if the `fast-sha1`
+/// feature is enabled on CPUs that support them, like AMD Ryzen, Intel Core starting with Ice Lake, or Apple Silicon.
+///
+/// The `sha1dc` feature takes precedence and never uses them, as detecting collision attacks requires its own implementation.
+pub fn sha1_uses_cpu_extensions() -> bool {
+    if cfg!(feature = "sha1dc") || !cfg!(feature = "fast-sha1") {
+        return false;
+    }
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        std::is_x86_feature_detected!("sha") && std::is_x86_feature_detected!("sse4.1")
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        std::arch::is_aarch64_feature_detected!("sha2")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    {
+        false
+    }
+}
+
+/// A 32 bytes digest produced by a [`Sha256`] hash implementation.
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub type Sha256Digest = [u8; 32];
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+mod _impl_sha256 {
+    use sha2::Digest;
+
+    use super::Sha256Digest;
+
+    /// A implementation of the Sha256 hash, which can be used once.
+    #[derive(Default, Clone)]
+    pub struct Sha256(sha2::Sha256);
+
+    impl Sha256 {
+        /// Digest the given `bytes`.
+        pub fn update(&mut self, bytes: &[u8]) {
+            self.0.update(bytes)
+        }
+        /// Finalize the hash and produce a digest.
+        pub fn digest(self) -> Sha256Digest {
+            self.0.finalize().into()
+        }
+    }
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub use _impl_sha256::Sha256;
+
+/// A hasher for any of the supported [kinds of hashes][git_hash::Kind], as created by [`hasher()`].
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+#[derive(Clone)]
+pub enum Hasher {
+    /// A [`Sha1`] hasher.
+    Sha1(Sha1),
+    /// A [`Sha256`] hasher.
+    Sha256(Sha256),
+}
+
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+impl Hasher {
+    /// Digest the given `bytes`.
+    pub fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha1(h) => h.update(bytes),
+            Hasher::Sha256(h) => h.update(bytes),
+        }
+    }
+
+    /// Finalize the hash and produce the id of the hashed data.
+    pub fn digest(self) -> git_hash::ObjectId {
+        match self {
+            Hasher::Sha1(h) => h.digest().into(),
+            Hasher::Sha256(h) => h.digest().into(),
+        }
+    }
+
+    /// Finalize the hash and produce the id of the hashed data, or fail if the `sha1dc` feature is enabled and the hashed
+    /// data is part of a SHA1 collision attack.
+    ///
+    /// Use this when hashing data received from untrusted sources, like objects in packs sent by a remote.
+    pub fn try_digest(self) -> Result<git_hash::ObjectId, Collision> {
+        Ok(match self {
+            Hasher::Sha1(h) => h.try_digest()?.into(),
+            Hasher::Sha256(h) => h.digest().into(),
+        })
+    }
+
+    /// Return the kind of hash we produce.
+    pub fn kind(&self) -> git_hash::Kind {
+        match self {
+            Hasher::Sha1(_) => git_hash::Kind::Sha1,
+            Hasher::Sha256(_) => git_hash::Kind::Sha256,
+        }
+    }
+}
+
 /// Compute a CRC32 hash from the given `bytes`, returning the CRC32 hash.
 ///
 /// When calling this function for the first time, `previous_value` should be `0`. Otherwise it
@@ -75,10 +253,11 @@ pub fn crc32(bytes: &[u8]) -> u32 {
 }
 
 /// Produce a hasher suitable for the given kind of hash.
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
-pub fn hasher(kind: git_hash::Kind) -> Sha1 {
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
+pub fn hasher(kind: git_hash::Kind) -> Hasher {
     match kind {
-        git_hash::Kind::Sha1 => Sha1::default(),
+        git_hash::Kind::Sha1 => Hasher::Sha1(Sha1::default()),
+        git_hash::Kind::Sha256 => Hasher::Sha256(Sha256::default()),
     }
 }
 
@@ -93,7 +272,10 @@ pub fn hasher(kind: git_hash::Kind) -> Sha1 {
 /// * Only available with the `git-object` feature enabled due to usage of the [`git_hash::Kind`] enum and the
 ///   [`git_hash::ObjectId`] return value.
 /// * [Interrupts][crate::interrupt] are supported.
-#[cfg(all(feature = "progress", any(feature = "rustsha1", feature = "fast-sha1")))]
+#[cfg(all(
+    feature = "progress",
+    any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc")
+))]
 pub fn bytes_of_file(
     path: impl AsRef<std::path::Path>,
     num_bytes_from_start: usize,
@@ -111,7 +293,10 @@ pub fn bytes_of_file(
 }
 
 /// Similar to [`bytes_of_file`], but operates on an already open file.
-#[cfg(all(feature = "progress", any(feature = "rustsha1", feature = "fast-sha1")))]
+#[cfg(all(
+    feature = "progress",
+    any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc")
+))]
 pub fn bytes(
     mut read: impl std::io::Read,
     num_bytes_from_start: usize,
@@ -139,19 +324,19 @@ pub fn bytes(
         }
     }
 
-    let id = git_hash::ObjectId::from(hasher.digest());
+    let id = hasher.digest();
     progress.show_throughput(start);
     Ok(id)
 }
 
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 mod write {
-    use crate::hash::Sha1;
+    use crate::hash::Hasher;
 
     /// A utility to automatically generate a hash while writing into an inner writer.
     pub struct Write<T> {
         /// The hash implementation.
-        pub hash: Sha1,
+        pub hash: Hasher,
         /// The inner writer.
         pub inner: T,
     }
@@ -177,14 +362,12 @@ mod write {
     {
         /// Create a new hash writer which hashes all bytes written to `inner` with a hash of `kind`.
         pub fn new(inner: T, object_hash: git_hash::Kind) -> Self {
-            match object_hash {
-                git_hash::Kind::Sha1 => Write {
-                    inner,
-                    hash: Sha1::default(),
-                },
+            Write {
+                inner,
+                hash: crate::hash::hasher(object_hash),
             }
         }
     }
 }
-#[cfg(any(feature = "rustsha1", feature = "fast-sha1"))]
+#[cfg(any(feature = "rustsha1", feature = "fast-sha1", feature = "sha1dc"))]
 pub use write::Write;
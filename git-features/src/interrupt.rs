@@ -1,4 +1,8 @@
 //! Utilities to cause interruptions in common traits, like Read/Write and Iterator.
+//!
+//! Interrupts are requested through an [`AtomicBool`] that long-running operations check periodically. For applications,
+//! the process-global [`IS_INTERRUPTED`] flag can be set with [`trigger()`], usually from a signal handler reacting to
+//! `Ctrl+C`, to cancel all operations that observe it at once, giving them a chance to clean up after themselves.
 use std::{
     io,
     sync::atomic::{AtomicBool, Ordering},
@@ -123,3 +127,25 @@ where
         self.inner.consume(amt)
     }
 }
+
+/// The process-global flag to request all operations observing it to stop.
+///
+/// It is meant to be passed wherever an interrupt flag is needed, and to be set by [`trigger()`].
+pub static IS_INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if an interrupt is requested.
+pub fn is_triggered() -> bool {
+    IS_INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Trigger an interrupt, signalling to those checking for [`is_triggered()`] to stop what they are doing.
+///
+/// This function never allocates or locks, so it is safe to call from signal handlers.
+pub fn trigger() {
+    IS_INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Sets the interrupt request to false, thus allowing those checking for [`is_triggered()`] to proceed.
+pub fn reset() {
+    IS_INTERRUPTED.store(false, Ordering::SeqCst);
+}
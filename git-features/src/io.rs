@@ -1,4 +1,5 @@
-//!A unidirectional pipe for bytes, analogous to a unix pipe. Available with the `io-pipe` feature toggle.
+//! IO utilities, like a unidirectional pipe for bytes with the `io-pipe` feature toggle, and adapters between blocking and
+//! `async` IO with the `io-async` feature toggle.
 
 /// A unidirectional pipe for bytes, analogous to a unix pipe. Available with the `io-pipe` feature toggle.
 #[cfg(feature = "io-pipe")]
@@ -92,3 +93,128 @@ pub mod pipe {
         )
     }
 }
+
+/// Adapters to use implementors of `futures-io` traits where `std::io` traits are expected, and vice versa.
+/// Available with the `io-async` feature toggle.
+///
+/// This allows crates to implement their IO logic once, either blocking or `async`, and to offer the other flavor by
+/// bridging to it.
+#[cfg(feature = "io-async")]
+pub mod bridge {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+
+    /// Implements [`std::io::Read`], [`std::io::BufRead`] and [`std::io::Write`] for an `inner` implementor of the respective
+    /// `futures-io` traits by blocking the current thread until each operation completes.
+    ///
+    /// Use it to feed `async` data sources into blocking code, like a pack received by an `async` client into an index writer.
+    /// Note that it must not be used on a thread driving an executor that `inner` depends on, as it would deadlock.
+    pub struct BlockOn<T> {
+        /// The `async` IO implementation to block on.
+        pub inner: T,
+    }
+
+    impl<T> BlockOn<T> {
+        /// Create a new instance to block on `inner`.
+        pub fn new(inner: T) -> Self {
+            BlockOn { inner }
+        }
+
+        /// Return the inner IO implementation.
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+    }
+
+    impl<T: AsyncRead + Unpin> io::Read for BlockOn<T> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            futures_lite::future::block_on(futures_lite::io::AsyncReadExt::read(&mut self.inner, buf))
+        }
+    }
+
+    impl<T: AsyncBufRead + Unpin> io::BufRead for BlockOn<T> {
+        fn fill_buf(&mut self) -> io::Result<&[u8]> {
+            futures_lite::future::block_on(futures_lite::io::AsyncBufReadExt::fill_buf(&mut self.inner))
+        }
+
+        fn consume(&mut self, amt: usize) {
+            Pin::new(&mut self.inner).consume(amt)
+        }
+    }
+
+    impl<T: AsyncWrite + Unpin> io::Write for BlockOn<T> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            futures_lite::future::block_on(futures_lite::io::AsyncWriteExt::write(&mut self.inner, buf))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            futures_lite::future::block_on(futures_lite::io::AsyncWriteExt::flush(&mut self.inner))
+        }
+    }
+
+    /// Implements the `futures-io` traits [`AsyncRead`], [`AsyncBufRead`] and [`AsyncWrite`] for an `inner` implementor of
+    /// the respective `std::io` traits by performing each operation right away.
+    ///
+    /// This is only suitable for IO that doesn't block for long, like in-memory buffers, as it would otherwise block
+    /// the executor. Use [`unblock()`] for anything else.
+    pub struct Inline<T> {
+        /// The blocking IO implementation to use directly.
+        pub inner: T,
+    }
+
+    impl<T> Inline<T> {
+        /// Create a new instance using `inner` directly.
+        pub fn new(inner: T) -> Self {
+            Inline { inner }
+        }
+
+        /// Return the inner IO implementation.
+        pub fn into_inner(self) -> T {
+            self.inner
+        }
+    }
+
+    impl<T: io::Read + Unpin> AsyncRead for Inline<T> {
+        fn poll_read(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.inner.read(buf))
+        }
+    }
+
+    impl<T: io::BufRead + Unpin> AsyncBufRead for Inline<T> {
+        fn poll_fill_buf(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+            Poll::Ready(self.get_mut().inner.fill_buf())
+        }
+
+        fn consume(mut self: Pin<&mut Self>, amt: usize) {
+            self.inner.consume(amt)
+        }
+    }
+
+    impl<T: io::Write + Unpin> AsyncWrite for Inline<T> {
+        fn poll_write(mut self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.inner.write(buf))
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(self.inner.flush())
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(self.inner.flush())
+        }
+    }
+
+    /// Turn the blocking `io`, an implementor of [`std::io::Read`] or [`std::io::Write`], into an implementor of
+    /// [`AsyncRead`] or [`AsyncWrite`] respectively, by performing all operations on a thread pool dedicated to blocking
+    /// operations.
+    ///
+    /// Use it to feed blocking data sources, like files or processes, into `async` code without blocking its executor.
+    pub fn unblock<T: Send + 'static>(io: T) -> blocking::Unblock<T> {
+        blocking::Unblock::new(io)
+    }
+}
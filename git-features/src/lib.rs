@@ -21,13 +21,14 @@ pub mod decode;
 pub mod fs;
 pub mod hash;
 pub mod interrupt;
-#[cfg(feature = "io-pipe")]
+#[cfg(any(feature = "io-pipe", feature = "io-async"))]
 pub mod io;
 pub mod parallel;
 pub mod path;
 #[cfg(feature = "progress")]
 pub mod progress;
 pub mod threading;
+pub mod trace;
 ///
 #[cfg(feature = "zlib")]
 pub mod zlib;
@@ -1,4 +1,7 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
 
 use crate::parallel::{num_threads, Reduce};
 
@@ -38,7 +41,7 @@ pub fn in_parallel<I, S, O, R>(
     thread_limit: Option<usize>,
     new_thread_state: impl Fn(usize) -> S + Send + Clone,
     consume: impl Fn(I, &mut S) -> O + Send + Clone,
-    mut reducer: R,
+    reducer: R,
 ) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
 where
     R: Reduce<Input = O>,
@@ -46,23 +49,112 @@ where
     O: Send,
 {
     let num_threads = num_threads(thread_limit);
+    in_parallel_inner(
+        input,
+        num_threads,
+        num_threads,
+        new_thread_state,
+        consume,
+        |_state| None,
+        reducer,
+    )
+}
+
+/// Like [`in_parallel()`], but once `input` is exhausted the state of each thread is turned into a final output by
+/// `finalize(State) -> Output`, which is fed to the `reducer` like all other outputs.
+///
+/// This allows threads to accumulate results in their state instead of sending one output per item, which is useful if
+/// outputs would otherwise be small and numerous, and keeps the `reducer` from becoming a bottleneck.
+pub fn in_parallel_with_finalize<I, S, O, R>(
+    input: impl Iterator<Item = I> + Send,
+    thread_limit: Option<usize>,
+    new_thread_state: impl Fn(usize) -> S + Send + Clone,
+    consume: impl Fn(I, &mut S) -> O + Send + Clone,
+    finalize: impl Fn(S) -> O + Send + Clone,
+    reducer: R,
+) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
+where
+    R: Reduce<Input = O>,
+    I: Send,
+    O: Send,
+{
+    let num_threads = num_threads(thread_limit);
+    in_parallel_inner(
+        input,
+        num_threads,
+        num_threads,
+        new_thread_state,
+        consume,
+        move |state| Some(finalize(state)),
+        reducer,
+    )
+}
+
+/// Like [`in_parallel()`], but with at most `items_in_flight` items being queued for the threads to pick up, and at most
+/// `items_in_flight` outputs being queued for the `reducer`, instead of one of each per thread.
+///
+/// Use this bounded-memory mode if items or outputs are large, to limit how far `input` is read ahead of the threads
+/// and how much unreduced output can pile up if the `reducer` is slower than the threads. An `items_in_flight` of `0`
+/// hands each item and output over directly.
+pub fn in_parallel_bounded<I, S, O, R>(
+    input: impl Iterator<Item = I> + Send,
+    thread_limit: Option<usize>,
+    items_in_flight: usize,
+    new_thread_state: impl Fn(usize) -> S + Send + Clone,
+    consume: impl Fn(I, &mut S) -> O + Send + Clone,
+    reducer: R,
+) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
+where
+    R: Reduce<Input = O>,
+    I: Send,
+    O: Send,
+{
+    in_parallel_inner(
+        input,
+        num_threads(thread_limit),
+        items_in_flight,
+        new_thread_state,
+        consume,
+        |_state| None,
+        reducer,
+    )
+}
+
+fn in_parallel_inner<I, S, O, R>(
+    input: impl Iterator<Item = I> + Send,
+    num_threads: usize,
+    items_in_flight: usize,
+    new_thread_state: impl Fn(usize) -> S + Send + Clone,
+    consume: impl Fn(I, &mut S) -> O + Send + Clone,
+    finalize: impl Fn(S) -> Option<O> + Send + Clone,
+    mut reducer: R,
+) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
+where
+    R: Reduce<Input = O>,
+    I: Send,
+    O: Send,
+{
     crossbeam_utils::thread::scope(move |s| {
         let receive_result = {
-            let (send_input, receive_input) = crossbeam_channel::bounded::<I>(num_threads);
-            let (send_result, receive_result) = crossbeam_channel::bounded::<O>(num_threads);
+            let (send_input, receive_input) = crossbeam_channel::bounded::<I>(items_in_flight);
+            let (send_result, receive_result) = crossbeam_channel::bounded::<O>(items_in_flight);
             for thread_id in 0..num_threads {
                 s.spawn({
                     let send_result = send_result.clone();
                     let receive_input = receive_input.clone();
                     let new_thread_state = new_thread_state.clone();
                     let consume = consume.clone();
+                    let finalize = finalize.clone();
                     move |_| {
                         let mut state = new_thread_state(thread_id);
                         for item in receive_input {
                             if send_result.send(consume(item, &mut state)).is_err() {
-                                break;
+                                return;
                             }
                         }
+                        if let Some(output) = finalize(state) {
+                            send_result.send(output).ok();
+                        }
                     }
                 });
             }
@@ -88,6 +180,11 @@ where
 /// This is only good for operations where near-random access isn't detremental, so it's not usually great
 /// for file-io as it won't make use of sorted inputs well.
 /// Note that `periodic` is not guaranteed to be called in case other threads come up first and finish too fast.
+///
+/// Threads claim chunks of consecutive items from a shared cursor, whose size is adjusted after each chunk to take a few
+/// milliseconds given the throughput observed so far, while never claiming more than a fair share of the
+/// remaining items. That way threads stay busy even if the cost of items varies greatly, as is the case when resolving
+/// deltas in packs.
 // TODO: better docs
 pub fn in_parallel_with_slice<I, S, E>(
     input: &[I],
@@ -105,6 +202,7 @@ where
     let num_items = input.len();
     let mut results = Vec::with_capacity(num_threads);
     let stop_everything = &AtomicBool::default();
+    let next_index = &AtomicUsize::default();
 
     crossbeam_utils::thread::scope({
         move |s| {
@@ -131,15 +229,25 @@ where
                         let mut consume = consume.clone();
                         move |_| {
                             let mut state = new_thread_state(thread_id);
-                            for input_index in (thread_id..num_items).step_by(num_threads) {
-                                if stop_everything.load(Ordering::Relaxed) {
+                            let mut chunk_size = 1;
+                            'chunks: loop {
+                                let start_index = next_index.fetch_add(chunk_size, Ordering::Relaxed);
+                                if start_index >= num_items {
                                     break;
                                 }
-                                let item = &input[input_index];
-                                if let Err(err) = consume(item, &mut state) {
-                                    stop_everything.store(true, Ordering::Relaxed);
-                                    return Err(err);
+                                let chunk = &input[start_index..(start_index + chunk_size).min(num_items)];
+                                let start = Instant::now();
+                                for item in chunk {
+                                    if stop_everything.load(Ordering::Relaxed) {
+                                        break 'chunks;
+                                    }
+                                    if let Err(err) = consume(item, &mut state) {
+                                        stop_everything.store(true, Ordering::Relaxed);
+                                        return Err(err);
+                                    }
                                 }
+                                let num_remaining = num_items.saturating_sub(next_index.load(Ordering::Relaxed));
+                                chunk_size = next_chunk_size(chunk.len(), start.elapsed(), num_remaining, num_threads);
                             }
                             Ok(state)
                         }
@@ -165,3 +273,23 @@ where
     })
     .expect("no panic")
 }
+
+/// The amount of time the processing of a chunk in [`in_parallel_with_slice()`] should take. Shorter durations cause more
+/// contention on the shared cursor, longer ones make it more likely for threads to run out of work at different times.
+const TARGET_CHUNK_DURATION: Duration = Duration::from_millis(5);
+
+/// Return the size of the next chunk to claim after processing `num_processed` items in `elapsed` time, with
+/// `num_remaining` items left to be claimed by `num_threads` threads.
+///
+/// The size grows at most twofold per chunk so that a few cheap items can't cause an expensive one to be claimed
+/// along with many others, and is limited to half of what each thread would get if the remaining items were split evenly.
+fn next_chunk_size(num_processed: usize, elapsed: Duration, num_remaining: usize, num_threads: usize) -> usize {
+    let fair_share = num_remaining / (num_threads * 2);
+    let desired = if elapsed.is_zero() {
+        num_processed * 2
+    } else {
+        let items_per_second = num_processed as f64 / elapsed.as_secs_f64();
+        ((items_per_second * TARGET_CHUNK_DURATION.as_secs_f64()) as usize).min(num_processed * 2)
+    };
+    desired.min(fair_share).max(1)
+}
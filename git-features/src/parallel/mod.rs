@@ -15,6 +15,9 @@
 //! operation which runs as fast as possible, which is cancellable only by merit of stopping the input or stopping the output
 //! aggregation.
 //!
+//! [`in_parallel_with_finalize()`] additionally turns the state of each thread into a final output for the reducer, and
+//! [`in_parallel_bounded()`] limits the amount of items and outputs held in memory while waiting to be processed.
+//!
 //! ### `reduce::Stepwise`
 //!
 //! The [`Stepwise`][reduce::Stepwise] iterator works exactly as [`in_parallel()`] except that the processing of the output produced by
@@ -35,11 +38,13 @@
 #[cfg(feature = "parallel")]
 mod in_parallel;
 #[cfg(feature = "parallel")]
-pub use in_parallel::{in_parallel, in_parallel_with_slice, join, threads};
+pub use in_parallel::{
+    in_parallel, in_parallel_bounded, in_parallel_with_finalize, in_parallel_with_slice, join, threads,
+};
 
 mod serial;
 #[cfg(not(feature = "parallel"))]
-pub use serial::{in_parallel, in_parallel_with_slice, join, threads};
+pub use serial::{in_parallel, in_parallel_bounded, in_parallel_with_finalize, in_parallel_with_slice, join, threads};
 
 mod in_order;
 pub use in_order::{InOrderIter, SequenceId};
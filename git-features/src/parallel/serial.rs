@@ -112,3 +112,46 @@ where
     }
     reducer.finalize()
 }
+
+/// Read items from `input` and `consume` them in a single thread like [`in_parallel()`], and feed the output of
+/// `finalize(State) -> Output` to the `reducer` once `input` is exhausted.
+///
+/// **This serial version performing all calculations on the current thread.**
+#[cfg(not(feature = "parallel"))]
+pub fn in_parallel_with_finalize<I, S, O, R>(
+    input: impl Iterator<Item = I>,
+    _thread_limit: Option<usize>,
+    new_thread_state: impl Fn(usize) -> S,
+    consume: impl Fn(I, &mut S) -> O,
+    finalize: impl Fn(S) -> O,
+    mut reducer: R,
+) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
+where
+    R: Reduce<Input = O>,
+{
+    let mut state = new_thread_state(0);
+    for item in input {
+        drop(reducer.feed(consume(item, &mut state))?);
+    }
+    drop(reducer.feed(finalize(state))?);
+    reducer.finalize()
+}
+
+/// Read items from `input` and `consume` them in a single thread like [`in_parallel()`], which never keeps more than one
+/// item in flight, so `items_in_flight` has no effect.
+///
+/// **This serial version performing all calculations on the current thread.**
+#[cfg(not(feature = "parallel"))]
+pub fn in_parallel_bounded<I, S, O, R>(
+    input: impl Iterator<Item = I>,
+    thread_limit: Option<usize>,
+    _items_in_flight: usize,
+    new_thread_state: impl Fn(usize) -> S,
+    consume: impl Fn(I, &mut S) -> O,
+    reducer: R,
+) -> Result<<R as Reduce>::Output, <R as Reduce>::Error>
+where
+    R: Reduce<Input = O>,
+{
+    in_parallel(input, thread_limit, new_thread_state, consume, reducer)
+}
@@ -1,5 +1,8 @@
 //! Various `prodash` types along with various utilities for comfort.
-use std::io;
+use std::{
+    io,
+    time::{Duration, Instant},
+};
 
 pub use prodash::{
     messages::MessageLevel,
@@ -94,3 +97,195 @@ where
         self.inner.flush()
     }
 }
+
+/// The kind of items progress is made in, used to format amounts and rates for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Amounts are in bytes and displayed with binary prefixes, like `1.5MiB`.
+    Bytes,
+    /// Amounts are counts of items with the given name, like `objects`, and displayed with decimal prefixes like `1.5k objects`.
+    Count(&'static str),
+}
+
+impl Kind {
+    /// Format `amount` for display.
+    pub fn format(&self, amount: f64) -> String {
+        match self {
+            Kind::Bytes => {
+                const PREFIXES: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB"];
+                let (amount, prefix) = scale(amount, 1024.0, PREFIXES);
+                if prefix == PREFIXES[0] {
+                    format!("{:.0}{}", amount, prefix)
+                } else {
+                    format!("{:.1}{}", amount, prefix)
+                }
+            }
+            Kind::Count(name) => {
+                const PREFIXES: &[&str] = &["", "k", "M", "G", "T"];
+                let (amount, prefix) = scale(amount, 1000.0, PREFIXES);
+                if prefix == PREFIXES[0] {
+                    format!("{:.0} {}", amount, name)
+                } else {
+                    format!("{:.1}{} {}", amount, prefix, name)
+                }
+            }
+        }
+    }
+}
+
+fn scale(mut amount: f64, base: f64, prefixes: &[&'static str]) -> (f64, &'static str) {
+    let mut prefix = prefixes[0];
+    for next in &prefixes[1..] {
+        if amount < base {
+            break;
+        }
+        amount /= base;
+        prefix = next;
+    }
+    (amount, prefix)
+}
+
+/// A [`Progress`] implementation wrapping another one to compute the rate at which progress is made and to estimate the
+/// time remaining until [`max()`][Progress::max()] is reached, with rates being smoothed to not fluctuate too much.
+///
+/// Children added with [`add_child()`][Progress::add_child()] compute their own rates in the same way, which allows
+/// all long-running operations to report rates without implementing it themselves.
+pub struct Throughput<P> {
+    inner: P,
+    kind: Kind,
+    step: prodash::progress::Step,
+    max: Option<prodash::progress::Step>,
+    /// The time and step at the beginning of the current measurement window.
+    window: (Instant, prodash::progress::Step),
+    steps_per_second: Option<f64>,
+}
+
+/// The minimal duration over which progress is measured before the rate is updated.
+const THROUGHPUT_WINDOW: Duration = Duration::from_millis(500);
+/// The weight of the most recent measurement when updating the rate, with previous measurements making up the rest.
+const THROUGHPUT_SMOOTHING: f64 = 0.3;
+
+impl<P> Throughput<P>
+where
+    P: Progress,
+{
+    /// Wrap `inner` to compute rates of progress made in items of `kind`.
+    pub fn new(inner: P, kind: Kind) -> Self {
+        Throughput {
+            inner,
+            kind,
+            step: 0,
+            max: None,
+            window: (Instant::now(), 0),
+            steps_per_second: None,
+        }
+    }
+
+    /// Return the smoothed amount of steps made per second, or `None` if not enough progress was made to know it yet.
+    pub fn steps_per_second(&self) -> Option<f64> {
+        self.steps_per_second
+    }
+
+    /// Return the estimated time remaining until the maximum step is reached, or `None` if there is no maximum or
+    /// if the rate isn't known yet.
+    pub fn eta(&self) -> Option<Duration> {
+        let remaining = self.max?.saturating_sub(self.step);
+        let steps_per_second = self.steps_per_second.filter(|rate| *rate > 0.0)?;
+        Some(Duration::from_secs_f64(remaining as f64 / steps_per_second))
+    }
+
+    /// Create an info message with the current step, the rate and the estimated time remaining, as far as they are known.
+    pub fn show_eta(&mut self) {
+        let mut message = self.kind.format(self.step as f64);
+        if let Some(max) = self.max {
+            message.push_str(&format!(" of {}", self.kind.format(max as f64)));
+        }
+        if let Some(steps_per_second) = self.steps_per_second {
+            message.push_str(&format!(", {}/s", self.kind.format(steps_per_second)));
+        }
+        if let Some(eta) = self.eta() {
+            message.push_str(&format!(", {}s remaining", eta.as_secs()));
+        }
+        self.inner.info(message);
+    }
+
+    /// Return the wrapped progress instance.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+
+    fn record(&mut self) {
+        let now = Instant::now();
+        let (window_start, window_start_step) = self.window;
+        let elapsed = now.duration_since(window_start);
+        if elapsed < THROUGHPUT_WINDOW {
+            return;
+        }
+        let rate = self.step.saturating_sub(window_start_step) as f64 / elapsed.as_secs_f64();
+        self.steps_per_second = Some(match self.steps_per_second {
+            Some(previous) => THROUGHPUT_SMOOTHING * rate + (1.0 - THROUGHPUT_SMOOTHING) * previous,
+            None => rate,
+        });
+        self.window = (now, self.step);
+    }
+}
+
+impl<P> Progress for Throughput<P>
+where
+    P: Progress,
+{
+    type SubProgress = Throughput<P::SubProgress>;
+
+    fn add_child(&mut self, name: impl Into<String>) -> Self::SubProgress {
+        Throughput::new(self.inner.add_child(name), self.kind)
+    }
+
+    fn init(&mut self, max: Option<prodash::progress::Step>, unit: Option<Unit>) {
+        self.step = 0;
+        self.max = max;
+        self.window = (Instant::now(), 0);
+        self.steps_per_second = None;
+        self.inner.init(max, unit)
+    }
+
+    fn set(&mut self, step: prodash::progress::Step) {
+        self.step = step;
+        self.record();
+        self.inner.set(step)
+    }
+
+    fn unit(&self) -> Option<Unit> {
+        self.inner.unit()
+    }
+
+    fn max(&self) -> Option<prodash::progress::Step> {
+        self.max
+    }
+
+    fn set_max(&mut self, max: Option<prodash::progress::Step>) -> Option<prodash::progress::Step> {
+        self.max = max;
+        self.inner.set_max(max)
+    }
+
+    fn step(&self) -> prodash::progress::Step {
+        self.step
+    }
+
+    fn inc_by(&mut self, step: prodash::progress::Step) {
+        self.step += step;
+        self.record();
+        self.inner.inc_by(step)
+    }
+
+    fn set_name(&mut self, name: impl Into<String>) {
+        self.inner.set_name(name)
+    }
+
+    fn name(&self) -> Option<String> {
+        self.inner.name()
+    }
+
+    fn message(&mut self, level: MessageLevel, message: impl Into<String>) {
+        self.inner.message(level, message)
+    }
+}
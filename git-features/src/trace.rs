@@ -0,0 +1,89 @@
+//! Instrumentation of major operations with spans and events of the `tracing` crate.
+//!
+//! With the `tracing` feature toggle, the [`trace_span!`][crate::trace_span!], [`trace_info!`][crate::trace_info!] and
+//! [`trace_error!`][crate::trace_error!] macros forward to their `tracing` counterparts, to be observed by whichever
+//! subscriber the application installed. Otherwise they compile to nothing, and their arguments are not evaluated.
+//!
+//! As the macros are expanded depending on the features of this crate, enabling the `tracing` feature here instruments
+//! all crates using them.
+
+/// The `tracing` crate, re-exported for use by the macros of this module.
+#[cfg(feature = "tracing")]
+pub use tracing;
+
+/// A span that is entered on creation and exited once dropped, as created by [`trace_span!`][crate::trace_span!].
+///
+/// Note that it must not be held across `.await` points, as it can only be exited on the thread that entered it.
+#[must_use = "the span is exited when dropped"]
+pub struct Span {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+impl Span {
+    /// Enter `span` and keep it entered until the returned instance is dropped.
+    #[cfg(feature = "tracing")]
+    pub fn enter(span: tracing::Span) -> Self {
+        Span {
+            _entered: span.entered(),
+        }
+    }
+
+    /// Create a span that does nothing, used if the `tracing` feature is disabled.
+    #[cfg(not(feature = "tracing"))]
+    pub fn disabled() -> Self {
+        Span {}
+    }
+}
+
+/// Create and enter a span at the `INFO` level named by the string literal `name`, optionally with `field = value` pairs,
+/// which is exited when the returned [`Span`][crate::trace::Span] is dropped.
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        $crate::trace::Span::enter($crate::trace::tracing::info_span!($name $(, $($fields)*)?))
+    };
+}
+
+/// Create and enter a span at the `INFO` level named by the string literal `name`, optionally with `field = value` pairs,
+/// which is exited when the returned [`Span`][crate::trace::Span] is dropped.
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        $crate::trace::Span::disabled()
+    };
+}
+
+/// Emit an event at the `INFO` level, taking the same arguments as `tracing::info!()`.
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_info {
+    ($($args:tt)+) => {
+        $crate::trace::tracing::info!($($args)+)
+    };
+}
+
+/// Emit an event at the `INFO` level, taking the same arguments as `tracing::info!()`.
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_info {
+    ($($args:tt)+) => {};
+}
+
+/// Emit an event at the `ERROR` level, taking the same arguments as `tracing::error!()`.
+#[cfg(feature = "tracing")]
+#[macro_export]
+macro_rules! trace_error {
+    ($($args:tt)+) => {
+        $crate::trace::tracing::error!($($args)+)
+    };
+}
+
+/// Emit an event at the `ERROR` level, taking the same arguments as `tracing::error!()`.
+#[cfg(not(feature = "tracing"))]
+#[macro_export]
+macro_rules! trace_error {
+    ($($args:tt)+) => {};
+}
@@ -1,5 +1,58 @@
 pub use flate2::{Decompress, Status};
 
+/// The implementation of zlib compression and decompression used by all utilities in this module.
+///
+/// Backends are chosen with feature toggles, and as these are unified across all crates in a build, the one actually in use
+/// can only be known once everything is compiled. Use [`Backend::active()`] to learn which one it is.
+///
+/// The backend is fixed at build time and can't be switched at runtime, as only a single one is ever compiled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// The pure Rust `miniz_oxide` implementation, used if no other backend is enabled.
+    MinizOxide,
+    /// The C implementation of the `zlib` library, linked statically or found on the system, enabled with the `zlib-stock` feature toggle.
+    Zlib,
+    /// The `zlib-ng` C implementation in its zlib-compatible mode, enabled with the `zlib-ng-compat` feature toggle.
+    ///
+    /// It takes precedence over all other backends.
+    ZlibNgCompat,
+}
+
+impl Backend {
+    /// Return the backend compiled into this binary, which is the one all compression and decompression is performed with.
+    ///
+    /// This is determined entirely by feature toggles and is the same for every call.
+    pub fn active() -> Self {
+        if cfg!(feature = "zlib-ng-compat") {
+            Backend::ZlibNgCompat
+        } else if cfg!(feature = "zlib-stock") {
+            Backend::Zlib
+        } else {
+            Backend::MinizOxide
+        }
+    }
+
+    /// Return true if this backend is implemented in C, which is usually significantly faster than the pure Rust one.
+    pub fn is_native(&self) -> bool {
+        !matches!(self, Backend::MinizOxide)
+    }
+
+    /// Return the name of this backend, as suitable for display to the user.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::MinizOxide => "miniz_oxide",
+            Backend::Zlib => "zlib",
+            Backend::ZlibNgCompat => "zlib-ng",
+        }
+    }
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// non-streaming interfaces for decompression
 pub mod inflate {
     use quick_error::quick_error;
@@ -1,13 +1,44 @@
 use git_features::hash::Sha1;
 
-#[cfg(not(feature = "fast-sha1"))]
+#[cfg(not(any(feature = "fast-sha1", feature = "sha1dc")))]
 #[test]
 fn size_of_sha1() {
     assert_eq!(std::mem::size_of::<Sha1>(), 96)
 }
 
-#[cfg(feature = "fast-sha1")]
+#[cfg(all(feature = "fast-sha1", not(feature = "sha1dc")))]
 #[test]
 fn size_of_sha1() {
     assert_eq!(std::mem::size_of::<Sha1>(), 104)
 }
+
+#[test]
+fn hasher_produces_ids_of_the_given_kind() {
+    let mut hasher = git_features::hash::hasher(git_hash::Kind::Sha1);
+    hasher.update(b"tree 0\0");
+    assert_eq!(hasher.digest(), git_hash::ObjectId::empty_tree(git_hash::Kind::Sha1));
+}
+
+#[test]
+fn hasher_supports_sha256() {
+    let mut hasher = git_features::hash::hasher(git_hash::Kind::Sha256);
+    assert_eq!(hasher.kind(), git_hash::Kind::Sha256);
+    hasher.update(b"tree 0\0");
+    assert_eq!(hasher.digest(), git_hash::ObjectId::empty_tree(git_hash::Kind::Sha256));
+}
+
+#[test]
+fn try_digest_succeeds_on_data_without_collision_attack() {
+    let mut hasher = git_features::hash::hasher(git_hash::Kind::Sha1);
+    hasher.update(b"tree 0\0");
+    assert_eq!(
+        hasher.try_digest().expect("no collision"),
+        git_hash::ObjectId::empty_tree(git_hash::Kind::Sha1)
+    );
+}
+
+#[cfg(not(feature = "fast-sha1"))]
+#[test]
+fn sha1_uses_cpu_extensions_only_with_fast_sha1() {
+    assert!(!git_features::hash::sha1_uses_cpu_extensions());
+}
@@ -0,0 +1,41 @@
+use std::io::{BufRead, Read, Write};
+
+use git_features::io::bridge;
+
+#[test]
+fn block_on_reads_and_writes_async_io() {
+    let mut read = bridge::BlockOn::new(futures_lite::io::Cursor::new(b"hello\nworld".to_vec()));
+    let mut line = String::new();
+    read.read_line(&mut line).unwrap();
+    assert_eq!(line, "hello\n");
+    let mut rest = String::new();
+    read.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "world");
+
+    let mut write = bridge::BlockOn::new(futures_lite::io::Cursor::new(Vec::new()));
+    write.write_all(b"written").unwrap();
+    write.flush().unwrap();
+    assert_eq!(write.into_inner().into_inner(), b"written");
+}
+
+#[test]
+fn inline_and_unblock_turn_blocking_io_into_async_io() {
+    use futures_lite::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+    futures_lite::future::block_on(async {
+        let mut read = bridge::Inline::new(std::io::Cursor::new(b"hello\nworld".to_vec()));
+        let mut line = String::new();
+        read.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "hello\n");
+
+        let mut read = bridge::unblock(std::io::Cursor::new(b"from a thread".to_vec()));
+        let mut buf = String::new();
+        read.read_to_string(&mut buf).await.unwrap();
+        assert_eq!(buf, "from a thread");
+
+        let mut write = bridge::Inline::new(Vec::new());
+        write.write_all(b"written").await.unwrap();
+        write.flush().await.unwrap();
+        assert_eq!(write.into_inner(), b"written");
+    });
+}
@@ -58,6 +58,61 @@ fn in_parallel_with_mut_slice_in_chunks() {
     );
 }
 
+#[test]
+fn in_parallel_with_finalize_feeds_thread_state() {
+    let res = parallel::in_parallel_with_finalize(
+        std::iter::from_fn(|| Some(1)).take(100),
+        None,
+        |_n| 0usize,
+        |input, state| {
+            *state += input;
+            0
+        },
+        |state| state,
+        Adder::default(),
+    )
+    .expect("successful computation");
+    assert_eq!(res, 100);
+}
+
+#[test]
+fn in_parallel_bounded() {
+    for items_in_flight in [0, 1, 10] {
+        let res = parallel::in_parallel_bounded(
+            std::iter::from_fn(|| Some(1)).take(100),
+            None,
+            items_in_flight,
+            |_n| (),
+            |input, _state| input,
+            Adder::default(),
+        )
+        .expect("successful computation");
+        assert_eq!(res, 100);
+    }
+}
+
+#[test]
+fn in_parallel_with_slice_consumes_each_item_once_with_skewed_costs() {
+    let input: Vec<usize> = (0..1000).collect();
+    let states = parallel::in_parallel_with_slice(
+        &input,
+        None,
+        |_| Vec::new(),
+        |item, seen| {
+            if item % 100 == 0 {
+                std::thread::sleep(std::time::Duration::from_millis(2));
+            }
+            seen.push(*item);
+            Ok::<_, ()>(())
+        },
+        || Some(std::time::Duration::from_millis(10)),
+    )
+    .unwrap();
+    let mut seen: Vec<_> = states.into_iter().flatten().collect();
+    seen.sort_unstable();
+    assert_eq!(seen, input, "every item is consumed exactly once");
+}
+
 #[test]
 fn stepped_reduce_next() {
     let mut iter = parallel::reduce::Stepwise::new(
@@ -0,0 +1,36 @@
+mod kind {
+    use git_features::progress::Kind;
+
+    #[test]
+    fn bytes_use_binary_prefixes() {
+        assert_eq!(Kind::Bytes.format(512.0), "512B");
+        assert_eq!(Kind::Bytes.format(1536.0), "1.5KiB");
+        assert_eq!(Kind::Bytes.format(3.0 * 1024.0 * 1024.0 * 1024.0), "3.0GiB");
+    }
+
+    #[test]
+    fn counts_use_decimal_prefixes_and_the_name() {
+        assert_eq!(Kind::Count("objects").format(999.0), "999 objects");
+        assert_eq!(Kind::Count("objects").format(12_345.0), "12.3k objects");
+        assert_eq!(Kind::Count("commits").format(2_500_000.0), "2.5M commits");
+    }
+}
+
+mod throughput {
+    use git_features::progress::{self, Kind, Progress, Throughput};
+
+    #[test]
+    fn rates_and_eta_are_unknown_until_enough_progress_was_made() {
+        let mut progress = Throughput::new(progress::Discard, Kind::Count("objects"));
+        progress.init(Some(100), progress::count("objects"));
+        progress.inc_by(10);
+        assert_eq!(progress.step(), 10);
+        assert_eq!(progress.max(), Some(100));
+        assert_eq!(
+            progress.steps_per_second(),
+            None,
+            "the measurement window didn't elapse yet"
+        );
+        assert_eq!(progress.eta(), None);
+    }
+}
@@ -0,0 +1,86 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use git_features::trace::tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// A subscriber recording each span and event as line, with all of their fields.
+#[derive(Default, Clone)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn push(&self, line: String) -> usize {
+        let mut lines = self.0.lock().unwrap();
+        lines.push(line);
+        lines.len()
+    }
+
+    fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let id = self.push(format!("new {}{}", span.metadata().name(), fields.0));
+        span::Id::from_u64(id as u64)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.push(format!("{}{}", event.metadata().level(), fields.0));
+    }
+
+    fn enter(&self, span: &span::Id) {
+        self.push(format!("enter {}", span.into_u64()));
+    }
+
+    fn exit(&self, span: &span::Id) {
+        self.push(format!("exit {}", span.into_u64()));
+    }
+}
+
+#[test]
+fn spans_are_entered_until_dropped_and_events_are_emitted_within() {
+    let recorder = Recorder::default();
+    git_features::trace::tracing::subscriber::with_default(recorder.clone(), || {
+        let span = git_features::trace_span!("outer", num_objects = 42);
+        git_features::trace_info!(round = 1, "negotiation round");
+        drop(span);
+        git_features::trace_error!("failed");
+    });
+    assert_eq!(
+        recorder.lines(),
+        vec![
+            "new outer num_objects=42",
+            "enter 1",
+            "INFO message=negotiation round round=1",
+            "exit 1",
+            "ERROR message=failed",
+        ]
+    );
+}
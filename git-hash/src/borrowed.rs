@@ -1,6 +1,6 @@
 use std::{convert::TryInto, fmt};
 
-use crate::{ObjectId, SIZE_OF_SHA1_DIGEST};
+use crate::{ObjectId, SIZE_OF_SHA1_DIGEST, SIZE_OF_SHA256_DIGEST};
 
 /// A borrowed reference to a hash identifying objects.
 ///
@@ -17,7 +17,6 @@ use crate::{ObjectId, SIZE_OF_SHA1_DIGEST};
 #[derive(PartialEq, Eq, Hash, Ord, PartialOrd)]
 #[repr(transparent)]
 #[allow(non_camel_case_types)]
-#[cfg_attr(feature = "serde1", derive(serde::Serialize))]
 pub struct oid {
     bytes: [u8],
 }
@@ -29,12 +28,49 @@ pub struct HexDisplay<'a> {
     hex_len: usize,
 }
 
-impl<'a> fmt::Display for HexDisplay<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl<'a> HexDisplay<'a> {
+    fn write(&self, f: &mut fmt::Formatter<'_>, uppercase: bool) -> fmt::Result {
         let mut hex = crate::Kind::hex_buf();
         let max_len = self.inner.hex_to_buf(hex.as_mut());
-        let hex = std::str::from_utf8(&hex[..self.hex_len.min(max_len)]).expect("ascii only in hex");
-        f.write_str(hex)
+        let hex = &mut hex[..self.hex_len.min(max_len)];
+        if uppercase {
+            hex.make_ascii_uppercase();
+        }
+        f.write_str(std::str::from_utf8(hex).expect("ascii only in hex"))
+    }
+}
+
+impl<'a> fmt::Display for HexDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+impl<'a> fmt::LowerHex for HexDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, false)
+    }
+}
+
+impl<'a> fmt::UpperHex for HexDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, true)
+    }
+}
+
+/// Format the id in lowercase hexadecimal notation, with the precision, if given, limiting the amount of characters,
+/// i.e. `{:.7x}` prints the first seven characters.
+impl fmt::LowerHex for oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.to_hex_with_len(f.precision().unwrap_or(usize::MAX)), f)
+    }
+}
+
+/// Format the id in uppercase hexadecimal notation, with the precision, if given, limiting the amount of characters,
+/// i.e. `{:.7X}` prints the first seven characters.
+impl fmt::UpperHex for oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.to_hex_with_len(f.precision().unwrap_or(usize::MAX)), f)
     }
 }
 
@@ -45,6 +81,7 @@ impl fmt::Debug for oid {
             "{}({})",
             match self.kind() {
                 crate::Kind::Sha1 => "Sha1",
+                crate::Kind::Sha256 => "Sha256",
             },
             self.to_hex(),
         )
@@ -68,7 +105,7 @@ impl oid {
     #[inline]
     pub fn try_from_bytes(digest: &[u8]) -> Result<&Self, Error> {
         match digest.len() {
-            20 => Ok(
+            SIZE_OF_SHA1_DIGEST | SIZE_OF_SHA256_DIGEST => Ok(
                 #[allow(unsafe_code)]
                 unsafe {
                     &*(digest as *const [u8] as *const oid)
@@ -158,6 +195,12 @@ impl oid {
     pub(crate) fn null_sha1() -> &'static Self {
         oid::from_bytes([0u8; SIZE_OF_SHA1_DIGEST].as_ref())
     }
+
+    /// Returns a Sha256 digest with all bytes being initialized to zero.
+    #[inline]
+    pub(crate) fn null_sha256() -> &'static Self {
+        oid::from_bytes([0u8; SIZE_OF_SHA256_DIGEST].as_ref())
+    }
 }
 
 impl AsRef<oid> for &oid {
@@ -172,6 +215,7 @@ impl ToOwned for oid {
     fn to_owned(&self) -> Self::Owned {
         match self.kind() {
             crate::Kind::Sha1 => crate::ObjectId::Sha1(self.bytes.try_into().expect("no bug in hash detection")),
+            crate::Kind::Sha256 => crate::ObjectId::Sha256(self.bytes.try_into().expect("no bug in hash detection")),
         }
     }
 }
@@ -182,6 +226,12 @@ impl<'a> From<&'a [u8; SIZE_OF_SHA1_DIGEST]> for &'a oid {
     }
 }
 
+impl<'a> From<&'a [u8; SIZE_OF_SHA256_DIGEST]> for &'a oid {
+    fn from(v: &'a [u8; SIZE_OF_SHA256_DIGEST]) -> Self {
+        oid::from_bytes(v.as_ref())
+    }
+}
+
 impl fmt::Display for &oid {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for b in self.as_bytes() {
@@ -197,6 +247,23 @@ impl PartialEq<crate::ObjectId> for &oid {
     }
 }
 
+/// Human-readable formats receive the id as hexadecimal string, others as raw bytes.
+#[cfg(feature = "serde1")]
+impl serde::Serialize for oid {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        if serializer.is_human_readable() {
+            let mut hex = crate::Kind::hex_buf();
+            let hex_len = self.hex_to_buf(&mut hex);
+            serializer.serialize_str(std::str::from_utf8(&hex[..hex_len]).expect("ascii only in hex"))
+        } else {
+            serializer.serialize_bytes(self.as_bytes())
+        }
+    }
+}
+
 /// Manually created from a version that uses a slice, and we forcefully try to convert it into a borrowed array of the desired size
 /// Could be improved by fitting this into serde
 /// Unfortunately the serde::Deserialize derive wouldn't work for borrowed arrays.
@@ -213,7 +280,14 @@ impl<'de: 'a, 'a> serde::Deserialize<'de> for &'a oid {
         impl<'de: 'a, 'a> serde::de::Visitor<'de> for __Visitor<'de, 'a> {
             type Value = &'a oid;
             fn expecting(&self, __formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                std::fmt::Formatter::write_str(__formatter, "tuple struct Digest")
+                std::fmt::Formatter::write_str(__formatter, "a hash digest of 20 or 32 bytes")
+            }
+            #[inline]
+            fn visit_borrowed_bytes<__E>(self, v: &'de [u8]) -> std::result::Result<Self::Value, __E>
+            where
+                __E: serde::de::Error,
+            {
+                oid::try_from_bytes(v).map_err(|_| __E::invalid_length(v.len(), &self))
             }
             #[inline]
             fn visit_newtype_struct<__E>(self, __e: __E) -> std::result::Result<Self::Value, __E::Error>
@@ -250,9 +324,8 @@ impl<'de: 'a, 'a> serde::Deserialize<'de> for &'a oid {
                 Ok(oid::try_from_bytes(__field0).expect("hash of known length"))
             }
         }
-        serde::Deserializer::deserialize_newtype_struct(
+        serde::Deserializer::deserialize_bytes(
             deserializer,
-            "Digest",
             __Visitor {
                 marker: std::marker::PhantomData::<&'a oid>,
                 lifetime: std::marker::PhantomData,
@@ -22,32 +22,46 @@ pub mod decode {
     use crate::owned::ObjectId;
 
     quick_error! {
-        /// An error returned by [`ObjectId::from_40_bytes_in_hex()`]
+        /// An error returned by [`ObjectId::from_hex()`]
         #[derive(Debug)]
         #[allow(missing_docs)]
         pub enum Error {
             InvalidHexEncodingLength(length: usize) {
                 display("A hash sized {} hexadecimal characters is invalid", length)
             }
+            Invalid { c: char, index: usize } {
+                display("Invalid character {} at position {}", c, index)
+            }
         }
     }
 
     /// Hash decoding
     impl ObjectId {
-        /// Create an instance from a `buffer` of 40 bytes encoded with hexadecimal notation.
+        /// Create an instance from a `buffer` of 40 or 64 bytes encoded with hexadecimal notation, for Sha1 or Sha256
+        /// respectively.
         ///
         /// Such a buffer can be obtained using [`oid::write_hex_to(buffer)`][super::oid::write_hex_to()]
         pub fn from_hex(buffer: &[u8]) -> Result<ObjectId, Error> {
             use hex::FromHex;
             match buffer.len() {
-                40 => Ok(ObjectId::Sha1(
-                    <[u8; 20]>::from_hex(buffer).expect("our length check is correct thus we can decode hex"),
-                )),
+                40 => <[u8; 20]>::from_hex(buffer).map(ObjectId::Sha1).map_err(Into::into),
+                64 => <[u8; 32]>::from_hex(buffer).map(ObjectId::Sha256).map_err(Into::into),
                 len => Err(Error::InvalidHexEncodingLength(len)),
             }
         }
     }
 
+    impl From<hex::FromHexError> for Error {
+        fn from(err: hex::FromHexError) -> Self {
+            match err {
+                hex::FromHexError::InvalidHexCharacter { c, index } => Error::Invalid { c, index },
+                hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
+                    unreachable!("our length check is correct thus we can decode hex")
+                }
+            }
+        }
+    }
+
     impl FromStr for ObjectId {
         type Err = Error;
 
@@ -59,6 +73,8 @@ pub mod decode {
 
 /// The size of a SHA1 hash digest in bytes
 const SIZE_OF_SHA1_DIGEST: usize = 20;
+/// The size of a SHA256 hash digest in bytes
+const SIZE_OF_SHA256_DIGEST: usize = 32;
 
 /// Denotes the kind of function to produce a `Id`
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
@@ -66,6 +82,8 @@ const SIZE_OF_SHA1_DIGEST: usize = 20;
 pub enum Kind {
     /// The Sha1 hash with 160 bits.
     Sha1 = 1,
+    /// The Sha256 hash with 256 bits.
+    Sha256 = 2,
 }
 
 impl Default for Kind {
@@ -80,6 +98,7 @@ impl TryFrom<u8> for Kind {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         Ok(match value {
             1 => Kind::Sha1,
+            2 => Kind::Sha256,
             unknown => return Err(unknown),
         })
     }
@@ -91,6 +110,7 @@ impl FromStr for Kind {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Ok(match s {
             "sha1" | "SHA1" => Kind::Sha1,
+            "sha256" | "SHA256" => Kind::Sha256,
             other => return Err(other.into()),
         })
     }
@@ -100,6 +120,7 @@ impl std::fmt::Display for Kind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Kind::Sha1 => f.write_str("SHA1"),
+            Kind::Sha256 => f.write_str("SHA256"),
         }
     }
 }
@@ -114,7 +135,7 @@ impl Kind {
     /// Returns the longest hash we support
     #[inline]
     pub const fn longest() -> Self {
-        Self::Sha1
+        Self::Sha256
     }
 
     /// Returns a buffer suitable to hold the longest possible hash in hex.
@@ -134,6 +155,7 @@ impl Kind {
     pub const fn len_in_hex(&self) -> usize {
         match self {
             Kind::Sha1 => 40,
+            Kind::Sha256 => 64,
         }
     }
     /// Returns the amount of bytes taken up by the hash of the current kind
@@ -141,9 +163,20 @@ impl Kind {
     pub const fn len_in_bytes(&self) -> usize {
         match self {
             Kind::Sha1 => 20,
+            Kind::Sha256 => 32,
         }
     }
 
+    /// Returns the kind of hash whose hexadecimal representation is `hex_len` characters long, or `None` if there is none.
+    #[inline]
+    pub const fn from_hex_len(hex_len: usize) -> Option<Self> {
+        Some(match hex_len {
+            40 => Kind::Sha1,
+            64 => Kind::Sha256,
+            _ => return None,
+        })
+    }
+
     /// Converts a size in bytes as obtained by `Kind::len_in_bytes()` into the corresponding hash kind, if possible.
     ///
     /// **Panics** if the hash length doesn't match a known hash.
@@ -156,6 +189,7 @@ impl Kind {
     pub(crate) fn from_len_in_bytes(bytes: usize) -> Self {
         match bytes {
             20 => Kind::Sha1,
+            32 => Kind::Sha256,
             _ => panic!("BUG: must be called only with valid hash lengths produced by len_in_bytes()"),
         }
     }
@@ -165,6 +199,7 @@ impl Kind {
     pub fn null_ref(&self) -> &'static oid {
         match self {
             Kind::Sha1 => oid::null_sha1(),
+            Kind::Sha256 => oid::null_sha256(),
         }
     }
 
@@ -173,6 +208,7 @@ impl Kind {
     pub const fn null(&self) -> ObjectId {
         match self {
             Kind::Sha1 => ObjectId::null_sha1(),
+            Kind::Sha256 => ObjectId::null_sha256(),
         }
     }
 }
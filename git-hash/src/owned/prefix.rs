@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, ops::RangeInclusive, str::FromStr};
 
 use quick_error::quick_error;
 
@@ -75,9 +75,76 @@ impl Prefix {
             })
     }
 
+    /// Return the range of all full object ids which start with this prefix, from the smallest to the largest one.
+    ///
+    /// This is useful to find all candidates for a prefix in sorted collections of ids, like pack indices.
+    pub fn range(&self) -> RangeInclusive<ObjectId> {
+        let mut end = self.bytes;
+        let b = end.as_mut_slice();
+        let prefix_bytes = self.hex_len / 2;
+        if self.hex_len % 2 == 1 {
+            b[prefix_bytes] |= 0x0f;
+        }
+        for byte in &mut b[(self.hex_len + 1) / 2..] {
+            *byte = 0xff;
+        }
+        self.bytes..=end
+    }
+
     /// Create an instance from the given hexadecimal prefix, e.g. `35e77c16` would yield a `Prefix` with `hex_len()` = 8.
-    pub fn from_hex(_hex: &str) -> Self {
-        todo!("Prefix::from_hex()")
+    ///
+    /// Upper- and lowercase characters are supported. As the kind of hash can't be known, the shortest kind of hash
+    /// which can hold the prefix is used.
+    pub fn from_hex(value: &str) -> Result<Self, from_hex::Error> {
+        let hex_len = value.len();
+        let kind = if hex_len <= crate::Kind::shortest().len_in_hex() {
+            crate::Kind::shortest()
+        } else if hex_len <= crate::Kind::longest().len_in_hex() {
+            crate::Kind::longest()
+        } else {
+            return Err(from_hex::Error::TooLong { hex_len });
+        };
+        if hex_len < 4 {
+            return Err(from_hex::Error::TooShort { hex_len });
+        }
+
+        let mut bytes = ObjectId::null(kind);
+        let b = bytes.as_mut_slice();
+        for (index, c) in value.chars().enumerate() {
+            let nibble = c.to_digit(16).ok_or(from_hex::Error::Invalid { c, index })? as u8;
+            b[index / 2] |= if index % 2 == 0 { nibble << 4 } else { nibble };
+        }
+        Ok(Prefix { bytes, hex_len })
+    }
+}
+
+///
+pub mod from_hex {
+    use quick_error::quick_error;
+
+    quick_error! {
+        /// The error returned by [Prefix::from_hex()][super::Prefix::from_hex()].
+        #[derive(Debug, PartialEq)]
+        #[allow(missing_docs)]
+        pub enum Error {
+            TooShort { hex_len: usize } {
+                display("The minimum hex length of a short object id is 4, got {}", hex_len)
+            }
+            TooLong { hex_len: usize } {
+                display("An id cannot be larger than {} chars in hex, but {} was requested", crate::Kind::longest().len_in_hex(), hex_len)
+            }
+            Invalid { c: char, index: usize } {
+                display("Invalid character {} at position {}", c, index)
+            }
+        }
+    }
+}
+
+impl FromStr for Prefix {
+    type Err = from_hex::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
     }
 }
 
@@ -70,6 +70,56 @@ mod prefix {
             ));
         }
     }
+
+    mod from_hex {
+        use git_hash::{prefix::from_hex::Error, Kind, Prefix};
+        use git_testtools::hex_to_id;
+
+        #[test]
+        fn matches_prefixes_created_from_full_ids() {
+            let id = hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3");
+            for hex_len in 4..=id.kind().len_in_hex() {
+                let hex = id.to_hex_with_len(hex_len).to_string();
+                assert_eq!(Prefix::from_hex(&hex), Ok(Prefix::new(id, hex_len).unwrap()));
+            }
+            assert_eq!(
+                "B920BBB".parse::<Prefix>(),
+                Ok(Prefix::new(id, 7).unwrap()),
+                "uppercase is supported"
+            );
+        }
+
+        #[test]
+        fn longer_prefixes_use_longer_hashes() {
+            let prefix = Prefix::from_hex(&"a".repeat(41)).unwrap();
+            assert_eq!(prefix.as_oid().kind(), Kind::Sha256);
+        }
+
+        #[test]
+        fn errors() {
+            assert_eq!(Prefix::from_hex("abc"), Err(Error::TooShort { hex_len: 3 }));
+            assert_eq!(Prefix::from_hex(&"a".repeat(65)), Err(Error::TooLong { hex_len: 65 }));
+            assert_eq!(Prefix::from_hex("abcx"), Err(Error::Invalid { c: 'x', index: 3 }));
+        }
+    }
+
+    mod range {
+        use git_hash::Prefix;
+        use git_testtools::hex_to_id;
+
+        #[test]
+        fn contains_all_ids_with_the_prefix() {
+            let prefix = Prefix::from_hex("b920b").unwrap();
+            let range = prefix.range();
+            assert_eq!(range.start(), &hex_to_id("b920b00000000000000000000000000000000000"));
+            assert_eq!(range.end(), &hex_to_id("b920bfffffffffffffffffffffffffffffffffff"));
+            assert!(range.contains(&hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3")));
+            assert!(!range.contains(&hex_to_id("b920c00000000000000000000000000000000000")));
+
+            let range = Prefix::from_hex("b920").unwrap().range();
+            assert_eq!(range.end(), &hex_to_id("b920ffffffffffffffffffffffffffffffffffff"));
+        }
+    }
 }
 
 mod short_hex {
@@ -87,3 +137,85 @@ mod short_hex {
         );
     }
 }
+
+mod sha256 {
+    use std::str::FromStr;
+
+    use git_hash::{Kind, ObjectId};
+
+    const EMPTY_TREE: &str = "6ef19b41225c5369f1c104d45d8d85efa9b057b53b14b4b9b939dd74decc5321";
+
+    #[test]
+    fn hex_round_trip() {
+        let id = ObjectId::from_hex(EMPTY_TREE.as_bytes()).expect("valid hex");
+        assert_eq!(id.kind(), Kind::Sha256);
+        assert_eq!(id.as_bytes().len(), 32);
+        assert_eq!(id.to_string(), EMPTY_TREE);
+        assert_eq!(id, ObjectId::empty_tree(Kind::Sha256));
+        assert_eq!(
+            id.as_ref().to_owned(),
+            id,
+            "conversions between oid and ObjectId retain the kind"
+        );
+        assert_eq!(format!("{:?}", id), format!("Sha256({})", EMPTY_TREE));
+    }
+
+    #[test]
+    fn null() {
+        let null = Kind::Sha256.null();
+        assert!(null.is_null());
+        assert_eq!(null.kind(), Kind::Sha256);
+        assert_eq!(Kind::Sha256.null_ref(), null);
+    }
+
+    #[test]
+    fn kind() {
+        assert_eq!(Kind::from_str("sha256"), Ok(Kind::Sha256));
+        assert_eq!(Kind::Sha256.to_string(), "SHA256");
+        assert_eq!(Kind::from_hex_len(64), Some(Kind::Sha256));
+        assert_eq!(Kind::from_hex_len(40), Some(Kind::Sha1));
+        assert_eq!(Kind::from_hex_len(41), None);
+        assert_eq!(Kind::longest(), Kind::Sha256);
+        assert_eq!(Kind::hex_buf().len(), 64);
+        assert!(git_hash::oid::try_from_bytes(&[0; 32]).is_ok());
+        assert!(git_hash::oid::try_from_bytes(&[0; 31]).is_err());
+    }
+
+    #[test]
+    fn lookup_by_oid_in_maps_and_sets() {
+        let ids = [
+            ObjectId::from_hex(EMPTY_TREE.as_bytes()).expect("valid hex"),
+            ObjectId::empty_tree(Kind::Sha1),
+        ];
+        let hashed: std::collections::HashSet<_> = ids.iter().copied().collect();
+        let ordered: std::collections::BTreeSet<_> = ids.iter().copied().collect();
+        for id in &ids {
+            assert!(hashed.contains(id.as_ref()), "hashing is the same as for oid");
+            assert!(ordered.contains(id.as_ref()), "ordering is the same as for oid");
+        }
+    }
+}
+
+mod hex_formatting {
+    use git_hash::ObjectId;
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn lower_and_upper_case_with_optional_precision() {
+        let id = hex_to_id("abcdefabcdefabcdefabcdefabcdefabcdefabcd");
+        assert_eq!(format!("{:x}", id), id.to_string());
+        assert_eq!(format!("{:X}", id), "ABCDEFABCDEFABCDEFABCDEFABCDEFABCDEFABCD");
+        assert_eq!(format!("{:.7x}", id), "abcdefa");
+        assert_eq!(format!("{:.7X}", id.as_ref()), "ABCDEFA");
+        assert_eq!(format!("{:.100x}", id), id.to_string(), "precision is clamped");
+        assert_eq!(format!("{:X}", id.to_hex_with_len(4)), "ABCD");
+    }
+
+    #[test]
+    fn from_hex_rejects_invalid_characters() {
+        assert!(matches!(
+            ObjectId::from_hex(b"abcdefabcdefabcdefabcdefabcdefabcdefabcg"),
+            Err(git_hash::decode::Error::Invalid { c: 'g', index: 39 })
+        ));
+    }
+}
@@ -7,9 +7,26 @@ impl State {
         self.version
     }
 
+    /// The time at which the index was last written, as used to detect entries whose stat information isn't
+    /// sufficient to tell if their files changed.
+    pub fn timestamp(&self) -> filetime::FileTime {
+        self.timestamp
+    }
+
     pub fn entries(&self) -> &[Entry] {
         &self.entries
     }
+    /// Find the entry at `path` with the given `stage` and return its index, or the index at which it would have to be
+    /// inserted to keep all entries sorted.
+    pub fn entry_index_by_path_and_stage(&self, path: &BStr, stage: u32) -> Result<usize, usize> {
+        self.entries
+            .binary_search_by(|e| (&self.path_backing[e.path.clone()], e.stage()).cmp(&(path.as_bytes(), stage)))
+    }
+    pub fn entry_by_path_and_stage(&self, path: &BStr, stage: u32) -> Option<&Entry> {
+        self.entry_index_by_path_and_stage(path, stage)
+            .ok()
+            .map(|idx| &self.entries[idx])
+    }
     pub fn entries_mut(&mut self) -> &mut [Entry] {
         &mut self.entries
     }
@@ -23,6 +40,11 @@ impl State {
     pub fn tree(&self) -> Option<&extension::Tree> {
         self.tree.as_ref()
     }
+    /// Note that the tree needs to be [invalidated][extension::Tree::invalidate_path()] for all paths of entries
+    /// changed via [`entries_mut()`][State::entries_mut()].
+    pub fn tree_mut(&mut self) -> Option<&mut extension::Tree> {
+        self.tree.as_mut()
+    }
     pub fn link(&self) -> Option<&extension::Link> {
         self.link.as_ref()
     }
@@ -32,6 +54,13 @@ impl State {
     pub fn untracked(&self) -> Option<&extension::UntrackedCache> {
         self.untracked.as_ref()
     }
+    pub fn untracked_mut(&mut self) -> Option<&mut extension::UntrackedCache> {
+        self.untracked.as_mut()
+    }
+    /// Set the untracked cache to `untracked`, or remove it, returning the previous one.
+    pub fn set_untracked(&mut self, untracked: Option<extension::UntrackedCache>) -> Option<extension::UntrackedCache> {
+        std::mem::replace(&mut self.untracked, untracked)
+    }
     pub fn fs_monitor(&self) -> Option<&extension::FsMonitor> {
         self.fs_monitor.as_ref()
     }
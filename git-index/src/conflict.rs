@@ -0,0 +1,167 @@
+//! Access and resolve conflicts, which are paths with entries in stage 1 (base), 2 (ours) or 3 (theirs).
+use bstr::BStr;
+
+use crate::{entry, Entry, State};
+
+/// A conflicted path along with the entries of all stages it has.
+pub struct Conflict<'a> {
+    /// The path of all conflicting entries.
+    pub path: &'a BStr,
+    /// The entry of stage 1, the common ancestor, if there is one.
+    pub base: Option<&'a Entry>,
+    /// The entry of stage 2, our version, if it wasn't deleted.
+    pub ours: Option<&'a Entry>,
+    /// The entry of stage 3, their version, if it wasn't deleted.
+    pub theirs: Option<&'a Entry>,
+}
+
+/// Describes how to resolve a conflict with [`State::resolve_conflict()`].
+pub enum Resolution {
+    /// Use the entry of stage 1, the common ancestor.
+    Base,
+    /// Use the entry of stage 2, our version.
+    Ours,
+    /// Use the entry of stage 3, their version.
+    Theirs,
+    /// Use an entry with the given mode and id, for instance after merging the content of all stages.
+    Merged { id: git_hash::ObjectId, mode: entry::Mode },
+    /// Remove the path from the index.
+    Remove,
+}
+
+mod error {
+    use bstr::BString;
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            NotConflicted { path: BString } {
+                display("'{}' is not conflicted", path)
+            }
+            MissingStage { path: BString, stage: u32 } {
+                display("Conflicted path '{}' has no entry in stage {} to resolve with", path, stage)
+            }
+        }
+    }
+}
+pub use error::Error;
+
+impl State {
+    /// Return true if at least one entry is in a stage other than 0.
+    pub fn has_conflicts(&self) -> bool {
+        self.entries.iter().any(|e| e.stage() != 0)
+    }
+
+    /// Return an iterator over all conflicted paths, in order.
+    pub fn conflicts(&self) -> impl Iterator<Item = Conflict<'_>> {
+        let mut entries = self.entries.iter().peekable();
+        std::iter::from_fn(move || {
+            let first = entries.find(|e| e.stage() != 0)?;
+            let path = first.path(self);
+            let mut conflict = Conflict {
+                path,
+                base: None,
+                ours: None,
+                theirs: None,
+            };
+            let mut next = Some(first);
+            while let Some(entry) = next {
+                conflict.set(entry);
+                next = entries.next_if(|e| e.path(self) == path);
+            }
+            Some(conflict)
+        })
+    }
+
+    /// Return the conflict at the slash-separated `path`, if there is one.
+    pub fn conflict(&self, path: &BStr) -> Option<Conflict<'_>> {
+        let start = self.entry_index_by_path_and_stage(path, 1).unwrap_or_else(|idx| idx);
+        let mut conflict = Conflict {
+            path: self.entries.get(start)?.path(self),
+            base: None,
+            ours: None,
+            theirs: None,
+        };
+        if conflict.path != path {
+            return None;
+        }
+        for entry in self.entries[start..].iter().take_while(|e| e.path(self) == path) {
+            conflict.set(entry);
+        }
+        Some(conflict)
+    }
+
+    /// Resolve the conflict at the slash-separated `path` according to `resolution` by replacing all of its stages with
+    /// a single entry in stage 0, or by removing it, and return the index of the new entry if there is one.
+    ///
+    /// The stages are recorded in the resolve-undo extension so the conflict can be [recreated][State::unresolve_conflict()].
+    /// New entries don't have any stat information, so they will appear changed until they are updated from the worktree.
+    pub fn resolve_conflict(&mut self, path: &BStr, resolution: Resolution) -> Result<Option<usize>, Error> {
+        let conflict = self
+            .conflict(path)
+            .ok_or_else(|| Error::NotConflicted { path: path.into() })?;
+        let (id, mode) = match resolution {
+            Resolution::Base | Resolution::Ours | Resolution::Theirs => {
+                let (stage, entry) = match resolution {
+                    Resolution::Base => (1, conflict.base),
+                    Resolution::Ours => (2, conflict.ours),
+                    _ => (3, conflict.theirs),
+                };
+                entry.map(|e| (e.id, e.mode)).ok_or_else(|| Error::MissingStage {
+                    path: path.into(),
+                    stage,
+                })?
+            }
+            Resolution::Merged { id, mode } => (id, mode),
+            Resolution::Remove => {
+                self.remove_path(path);
+                return Ok(None);
+            }
+        };
+        Ok(Some(self.upsert_entry(
+            path,
+            Default::default(),
+            id,
+            mode,
+            entry::Flags::empty(),
+        )))
+    }
+
+    /// Recreate the conflict at the slash-separated `path` from the stages recorded in the resolve-undo extension,
+    /// replacing its stage 0 entry. Return true if the conflict was recorded and recreated.
+    pub fn unresolve_conflict(&mut self, path: &BStr) -> bool {
+        let resolved = match self
+            .resolve_undo
+            .as_mut()
+            .and_then(|paths| paths.iter().position(|p| p.name == path).map(|idx| paths.remove(idx)))
+        {
+            Some(resolved) => resolved,
+            None => return false,
+        };
+        self.remove_entry(path, 0);
+        for (stage, recorded) in (1..=3).zip(resolved.stages.iter()) {
+            if let Some(recorded) = recorded {
+                self.upsert_entry(
+                    path,
+                    Default::default(),
+                    recorded.id,
+                    entry::Mode::from_bits_truncate(recorded.mode),
+                    entry::Flags::from_bits_truncate(stage << 12),
+                );
+            }
+        }
+        true
+    }
+}
+
+impl<'a> Conflict<'a> {
+    fn set(&mut self, entry: &'a Entry) {
+        match entry.stage() {
+            1 => self.base = Some(entry),
+            2 => self.ours = Some(entry),
+            3 => self.theirs = Some(entry),
+            _ => {}
+        }
+    }
+}
@@ -127,7 +127,8 @@ fn load_one<'a>(
         data
     } else {
         let (path, data) = if flags.contains(entry::Flags::PATH_LEN) {
-            split_at_byte_exclusive(data, 0)?
+            let (path, _) = split_at_byte_exclusive(data, 0)?;
+            (path, skip_padding(&data[path.len()..], first_byte_of_entry))
         } else {
             let path_len = (flags.bits() & entry::Flags::PATH_LEN.bits()) as usize;
             let (path, data) = split_at_pos(data, path_len)?;
@@ -1,4 +1,5 @@
 pub(crate) const SIZE: usize = 4 /*signature*/ + 4 /*version*/ + 4 /* num entries */;
+pub(crate) const SIGNATURE: &[u8] = b"DIRC";
 
 use crate::{util::from_be_u32, Version};
 
@@ -27,7 +28,6 @@ pub(crate) fn decode(data: &[u8], object_hash: git_hash::Kind) -> Result<(crate:
         ));
     }
 
-    const SIGNATURE: &[u8] = b"DIRC";
     let (signature, data) = data.split_at(4);
     if signature != SIGNATURE {
         return Err(Error::Corrupt(
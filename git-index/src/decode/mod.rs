@@ -37,7 +37,7 @@ use git_features::parallel::InOrderIter;
 
 use crate::util::read_u32;
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 pub struct Options {
     pub object_hash: git_hash::Kind,
     /// If Some(_), we are allowed to use more than one thread. If Some(N), use no more than N threads. If Some(0)|None, use as many threads
@@ -212,22 +212,23 @@ impl State {
         } = ext;
         is_sparse |= is_sparse_from_ext;
 
-        Ok((
-            State {
-                timestamp,
-                version,
-                entries,
-                path_backing,
-                is_sparse,
+        let mut state = State {
+            timestamp,
+            version,
+            entries,
+            path_backing,
+            is_sparse,
 
-                tree,
-                link,
-                resolve_undo,
-                untracked,
-                fs_monitor,
-            },
-            checksum,
-        ))
+            tree,
+            link,
+            resolve_undo,
+            untracked,
+            fs_monitor,
+
+            shared_index: None,
+        };
+        state.apply_fs_monitor_extension();
+        Ok((state, checksum))
     }
 }
 
@@ -278,11 +279,11 @@ pub(crate) fn stat(data: &[u8]) -> Option<(entry::Stat, &[u8])> {
     let (size, data) = read_u32(data)?;
     Some((
         entry::Stat {
-            mtime: entry::Time {
+            ctime: entry::Time {
                 secs: ctime_secs,
                 nsecs: ctime_nsecs,
             },
-            ctime: entry::Time {
+            mtime: entry::Time {
                 secs: mtime_secs,
                 nsecs: mtime_nsecs,
             },
@@ -20,6 +20,41 @@ pub(crate) mod mode {
         pub fn is_sparse(&self) -> bool {
             *self == Self::DIR
         }
+
+        /// Return the mode of the file described by `meta` as git would record it, or `None` if it's neither a file
+        /// nor a symbolic link.
+        ///
+        /// On platforms without an executable bit, all files are considered non-executable.
+        pub fn from_fs(meta: &std::fs::Metadata) -> Option<Self> {
+            let file_type = meta.file_type();
+            if file_type.is_symlink() {
+                return Some(Self::SYMLINK);
+            }
+            if !file_type.is_file() {
+                return None;
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                if meta.permissions().mode() & 0o100 != 0 {
+                    return Some(Self::FILE_EXECUTABLE);
+                }
+            }
+            Some(Self::FILE)
+        }
+
+        /// Return the mode of this entry as stored in a tree, or `None` if it isn't a known mode.
+        pub fn to_tree_entry_mode(&self) -> Option<git_object::tree::EntryMode> {
+            use git_object::tree::EntryMode;
+            Some(match *self {
+                Self::DIR => EntryMode::Tree,
+                Self::FILE => EntryMode::Blob,
+                Self::FILE_EXECUTABLE => EntryMode::BlobExecutable,
+                Self::SYMLINK => EntryMode::Link,
+                Self::COMMIT => EntryMode::Commit,
+                _ => return None,
+            })
+        }
     }
 }
 
@@ -51,6 +86,10 @@ pub(crate) mod at_rest {
         pub fn to_flags(self) -> Option<super::Flags> {
             super::Flags::from_bits((self.bits as u32) << 16)
         }
+
+        pub fn from_flags(flags: super::Flags) -> Self {
+            Self::from_bits_truncate((flags.bits >> 16) as u16)
+        }
     }
 
     impl Flags {
@@ -58,6 +97,11 @@ pub(crate) mod at_rest {
             super::Flags::from_bits((self & (Flags::PATH_LEN | Flags::STAGE_MASK | Flags::ASSUME_VALID)).bits as u32)
                 .expect("PATHLEN is part of memory representation")
         }
+
+        /// The flags stored at rest, without the path length and the marker for extended flags.
+        pub fn from_flags(flags: super::Flags) -> Self {
+            Self::from_bits_truncate((flags & (super::Flags::STAGE_MASK | super::Flags::ASSUME_VALID)).bits as u16)
+        }
     }
 
     #[cfg(test)]
@@ -111,7 +155,7 @@ impl Flags {
     }
 }
 
-#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy, Default)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Time {
     /// The amount of seconds elapsed since EPOCH
@@ -120,7 +164,7 @@ pub struct Time {
     pub nsecs: u32,
 }
 
-#[derive(PartialEq, Eq, Hash, Ord, PartialOrd, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy, Default)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stat {
     pub mtime: Time,
@@ -133,6 +177,46 @@ pub struct Stat {
     pub size: u32,
 }
 
+impl Stat {
+    /// Obtain the stat information git would record for the file or directory described by `meta`, with all values
+    /// truncated to 32 bits.
+    pub fn from_fs(meta: &std::fs::Metadata) -> Self {
+        let mtime = filetime::FileTime::from_last_modification_time(meta);
+        let to_time = |time: filetime::FileTime| Time {
+            secs: time.unix_seconds() as u32,
+            nsecs: time.nanoseconds(),
+        };
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Stat {
+                mtime: to_time(mtime),
+                ctime: Time {
+                    secs: meta.ctime() as u32,
+                    nsecs: meta.ctime_nsec() as u32,
+                },
+                dev: meta.dev() as u32,
+                ino: meta.ino() as u32,
+                uid: meta.uid(),
+                gid: meta.gid(),
+                size: meta.len() as u32,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            Stat {
+                mtime: to_time(mtime),
+                ctime: to_time(filetime::FileTime::from_creation_time(meta).unwrap_or(mtime)),
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                size: meta.len() as u32,
+            }
+        }
+    }
+}
+
 mod access {
     use bstr::{BStr, ByteSlice};
 
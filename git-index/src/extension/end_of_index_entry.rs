@@ -32,7 +32,7 @@ pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<usize> {
         last_chunk = Some(chunk);
     }
 
-    if hasher.digest() != checksum {
+    if hasher.digest().as_bytes() != checksum {
         return None;
     }
     // The last-to-this chunk ends where ours starts
@@ -45,3 +45,22 @@ pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<usize> {
 
     Some(offset)
 }
+
+/// Write this extension to `out`, given the offset at which the extensions start and the signatures and sizes of all
+/// extensions that precede this one.
+pub fn write_to(
+    mut out: impl std::io::Write,
+    offset_to_extensions: u32,
+    prior_extensions: impl IntoIterator<Item = (Signature, u32)>,
+) -> std::io::Result<()> {
+    out.write_all(&SIGNATURE)?;
+    out.write_all(&(SIZE as u32).to_be_bytes())?;
+    out.write_all(&offset_to_extensions.to_be_bytes())?;
+
+    let mut hasher = git_features::hash::hasher(git_hash::Kind::Sha1);
+    for (signature, size) in prior_extensions {
+        hasher.update(&signature);
+        hasher.update(&size.to_be_bytes());
+    }
+    out.write_all(hasher.digest().as_slice())
+}
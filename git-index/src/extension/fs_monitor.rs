@@ -1,18 +1,221 @@
-use bstr::BString;
+use std::{
+    ffi::OsStr,
+    path::Path,
+    process::{Command, Stdio},
+    time::SystemTime,
+};
+
+use bstr::{BString, ByteSlice};
 
 use crate::{
+    entry,
     extension::{FsMonitor, Signature},
     util::{read_u32, read_u64, split_at_byte_exclusive},
+    State,
 };
 
+/// The token identifying the point in time the file system monitor was queried at.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Token {
+    /// The time of the query in nanoseconds since the unix epoch.
     V1 { nanos_since_1970: u64 },
+    /// An opaque token provided by the file system monitor.
     V2 { token: BString },
 }
 
 pub const SIGNATURE: Signature = *b"FSMN";
 
-pub fn decode(data: &[u8]) -> Option<FsMonitor> {
+/// The paths a file system monitor reported as changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Changed {
+    /// Everything may have changed, for instance because the monitor lost track of changes.
+    All,
+    /// Only the given paths relative to the worktree changed, with directories denoting everything below them.
+    Paths(Vec<BString>),
+}
+
+/// The result of [querying][query_hook()] a file system monitor hook.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The token to use for the next query, identifying the point in time of this one.
+    pub token: Token,
+    /// The paths that changed since the previous token.
+    pub changed: Changed,
+}
+
+pub mod query {
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Spawn(err: std::io::Error) {
+                display("The file system monitor hook could not be run")
+                source(err)
+                from()
+            }
+            Failed(code: Option<i32>) {
+                display("The file system monitor hook failed with exit code {:?}", code)
+            }
+            MissingToken {
+                display("The file system monitor hook didn't print a token")
+            }
+        }
+    }
+}
+
+/// Run the file system monitor `hook`, as configured in `core.fsmonitor`, in `worktree` to learn which paths changed
+/// since the query that produced `token`, using the hook protocol version that matches the token.
+///
+/// Like git, the hook is run by the shell with the protocol version and the token as arguments, and is expected to
+/// print all changed paths separated by null bytes, preceded by the new token in version 2 of the protocol.
+/// Use [`Token::V1`] with zero nanoseconds or an empty [`Token::V2`] if there is no previous token.
+pub fn query_hook(hook: &OsStr, worktree: &Path, token: &Token) -> Result<Outcome, query::Error> {
+    let now = Token::V1 {
+        nanos_since_1970: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64),
+    };
+    let (version, argument) = match token {
+        Token::V1 { nanos_since_1970 } => ("1", nanos_since_1970.to_string().into()),
+        Token::V2 { token } => ("2", token.to_os_str_lossy().into_owned()),
+    };
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(r#"eval "$0" '"$@"'"#)
+        .arg(hook)
+        .arg(version)
+        .arg(argument)
+        .current_dir(worktree)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(query::Error::Failed(output.status.code()));
+    }
+
+    let mut output = output.stdout.split_str("\0");
+    let token = match token {
+        Token::V1 { .. } => now,
+        Token::V2 { .. } => match output.next() {
+            Some(token) if !token.is_empty() => Token::V2 { token: token.into() },
+            _ => return Err(query::Error::MissingToken),
+        },
+    };
+    let paths: Vec<BString> = output.filter(|path| !path.is_empty()).map(Into::into).collect();
+    let changed = if paths.iter().any(|path| path == "/") {
+        Changed::All
+    } else {
+        Changed::Paths(paths)
+    };
+    Ok(Outcome { token, changed })
+}
+
+impl State {
+    /// Record the `outcome` of [querying][query_hook()] the file system monitor, so that only entries that were
+    /// reported as changed or were changed before lack the [`FSMONITOR_VALID`][entry::Flags::FSMONITOR_VALID] flag
+    /// and need to be checked against the worktree.
+    ///
+    /// Without a previous file system monitor extension, all entries are considered changed. Entries that were found
+    /// to be unchanged afterwards may be marked as valid again.
+    pub fn apply_fs_monitor_changes(&mut self, Outcome { token, changed }: Outcome) {
+        let paths = match changed {
+            Changed::Paths(paths) if self.fs_monitor.is_some() => paths,
+            _ => {
+                for entry in &mut self.entries {
+                    entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+                }
+                Vec::new()
+            }
+        };
+        for path in paths {
+            let mut range = self.entry_range_with_prefix(&path, false);
+            if range.is_empty() {
+                // Like git, treat paths not found in the index as directories.
+                let mut dir = path;
+                if !dir.ends_with(b"/") {
+                    dir.push(b'/');
+                }
+                range = self.entry_range_with_prefix(&dir, true);
+            }
+            for entry in &mut self.entries[range] {
+                entry.flags.remove(entry::Flags::FSMONITOR_VALID);
+            }
+        }
+        let entry_dirty = self.fs_monitor_dirty_entries();
+        self.fs_monitor = Some(FsMonitor {
+            token,
+            entry_dirty: git_bitmap::ewah::Vec::from_bitset_trimmed(&entry_dirty),
+        });
+    }
+
+    /// Return the range of entries whose path equals `path`, or starts with it if `is_prefix` is true.
+    fn entry_range_with_prefix(&self, path: &[u8], is_prefix: bool) -> std::ops::Range<usize> {
+        let start = self
+            .entries
+            .partition_point(|entry| &self.path_backing[entry.path.clone()] < path);
+        let len = self.entries[start..]
+            .iter()
+            .take_while(|entry| {
+                let entry_path = &self.path_backing[entry.path.clone()];
+                if is_prefix {
+                    entry_path.starts_with(path)
+                } else {
+                    entry_path == path
+                }
+            })
+            .count();
+        start..start + len
+    }
+
+    /// Return a set with the positions of all entries that aren't known to be unchanged by the file system monitor.
+    pub(crate) fn fs_monitor_dirty_entries(&self) -> git_bitmap::Bitset {
+        let mut entry_dirty = git_bitmap::Bitset::with_capacity(self.entries.len());
+        for (index, entry) in self.entries.iter().enumerate() {
+            if !entry.flags.contains(entry::Flags::FSMONITOR_VALID) {
+                entry_dirty.set(index);
+            }
+        }
+        entry_dirty
+    }
+
+    /// Mark all entries that aren't dirty according to the file system monitor extension as valid.
+    pub(crate) fn apply_fs_monitor_extension(&mut self) {
+        let entry_dirty = match &self.fs_monitor {
+            Some(fs_monitor) => fs_monitor.entry_dirty.to_bitset(),
+            None => return,
+        };
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            entry.flags.set(entry::Flags::FSMONITOR_VALID, !entry_dirty.get(index));
+        }
+    }
+}
+
+impl FsMonitor {
+    pub(crate) fn write_to(
+        &self,
+        entry_dirty: &git_bitmap::Bitset,
+        mut out: impl std::io::Write,
+    ) -> std::io::Result<()> {
+        match &self.token {
+            Token::V1 { nanos_since_1970 } => {
+                out.write_all(&1u32.to_be_bytes())?;
+                out.write_all(&nanos_since_1970.to_be_bytes())?;
+            }
+            Token::V2 { token } => {
+                out.write_all(&2u32.to_be_bytes())?;
+                out.write_all(token)?;
+                out.write_all(b"\0")?;
+            }
+        }
+        let mut bitmap = Vec::new();
+        git_bitmap::ewah::Vec::from_bitset_trimmed(entry_dirty).write_to(&mut bitmap)?;
+        out.write_all(&(bitmap.len() as u32).to_be_bytes())?;
+        out.write_all(&bitmap)
+    }
+}
+
+pub(crate) fn decode(data: &[u8]) -> Option<FsMonitor> {
     let (version, data) = read_u32(data)?;
     let (token, data) = match version {
         1 => {
@@ -27,7 +230,7 @@ pub fn decode(data: &[u8]) -> Option<FsMonitor> {
     };
 
     let (ewah_size, data) = read_u32(data)?;
-    let (entry_dirty, data) = git_bitmap::ewah::decode(&data[..ewah_size as usize]).ok()?;
+    let (entry_dirty, data) = git_bitmap::ewah::decode(data.get(..ewah_size as usize)?).ok()?;
 
     if !data.is_empty() {
         return None;
@@ -1,15 +1,38 @@
+use std::path::{Path, PathBuf};
+
 use crate::{
+    entry,
     extension::{Link, Signature},
     util::split_at_pos,
+    Entry, State,
 };
 
 pub const SIGNATURE: Signature = *b"link";
 
+/// The changes of a split index relative to its shared index.
 pub struct Bitmaps {
+    /// The positions of entries in the shared index that were removed.
     pub delete: git_bitmap::ewah::Vec,
+    /// The positions of entries in the shared index that are replaced by entries of the split index, in order.
     pub replace: git_bitmap::ewah::Vec,
 }
 
+pub mod dissolve {
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            ChecksumMismatch{expected: git_hash::ObjectId, actual: git_hash::ObjectId} {
+                display("The shared index has checksum {} even though the split index refers to {}", actual, expected)
+            }
+            Corrupt(message: &'static str) {
+                display("{}", message)
+            }
+        }
+    }
+}
+
 pub mod decode {
     use quick_error::quick_error;
 
@@ -27,6 +50,30 @@ pub mod decode {
     }
 }
 
+impl Link {
+    /// Return the path to the shared index this split index refers to, assuming the split index is located at
+    /// `split_index_path`, or `None` if no shared index is used.
+    pub fn shared_index_path(&self, split_index_path: &Path) -> Option<PathBuf> {
+        if self.shared_index_checksum.is_null() {
+            return None;
+        }
+        let file_name = format!("sharedindex.{}", self.shared_index_checksum);
+        Some(match split_index_path.parent() {
+            Some(dir) => dir.join(file_name),
+            None => file_name.into(),
+        })
+    }
+
+    pub(crate) fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        out.write_all(self.shared_index_checksum.as_slice())?;
+        if let Some(bitmaps) = &self.bitmaps {
+            bitmaps.delete.write_to(&mut out)?;
+            bitmaps.replace.write_to(&mut out)?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) fn decode(data: &[u8], object_hash: git_hash::Kind) -> Result<Link, decode::Error> {
     let (id, data) = split_at_pos(data, object_hash.len_in_bytes())
         .ok_or(decode::Error::Corrupt(
@@ -55,3 +102,168 @@ pub(crate) fn decode(data: &[u8], object_hash: git_hash::Kind) -> Result<Link, d
         bitmaps: Some(Bitmaps { delete, replace }),
     })
 }
+
+/// Apply the changes recorded in the link extension of `split_index` to the entries of `shared_index` read from a file
+/// with `shared_index_checksum`, and make the result the entries of `split_index`.
+///
+/// The bitmaps of the link extension are consumed, and `shared_index` is kept to be able to write only the changes
+/// relative to it later.
+pub(crate) fn dissolve_into(
+    split_index: &mut State,
+    shared_index: State,
+    shared_index_checksum: git_hash::ObjectId,
+) -> Result<(), dissolve::Error> {
+    use dissolve::Error;
+
+    let bitmaps = match split_index.link.as_mut() {
+        Some(link) if link.shared_index_checksum == shared_index_checksum => link.bitmaps.take(),
+        Some(link) => {
+            return Err(Error::ChecksumMismatch {
+                expected: link.shared_index_checksum,
+                actual: shared_index_checksum,
+            })
+        }
+        None => return Ok(()),
+    };
+    let (delete, replace) = bitmaps
+        .map(|bitmaps| (bitmaps.delete.to_bitset(), bitmaps.replace.to_bitset()))
+        .unwrap_or_default();
+    if delete
+        .iter_ones()
+        .chain(replace.iter_ones())
+        .any(|idx| idx >= shared_index.entries.len())
+    {
+        return Err(Error::Corrupt(
+            "link extension refers to entries beyond the end of the shared index",
+        ));
+    }
+
+    let split_entries = std::mem::take(&mut split_index.entries);
+    let split_path_backing = std::mem::take(&mut split_index.path_backing);
+    let mut entries = Vec::with_capacity(shared_index.entries.len() + split_entries.len());
+    let mut path_backing = Vec::with_capacity(shared_index.path_backing.len() + split_path_backing.len());
+    let mut split_entries = split_entries.into_iter();
+
+    for (idx, base) in shared_index.entries.iter().enumerate() {
+        let source = if replace.get(idx) {
+            let mut replacement = split_entries.next().ok_or(Error::Corrupt(
+                "link extension replaces more entries than there are in the split index",
+            ))?;
+            if !replacement.path.is_empty() {
+                return Err(Error::Corrupt(
+                    "entries replacing shared index entries must not have a path",
+                ));
+            }
+            replacement.flags.remove(entry::Flags::PATH_LEN);
+            replacement.flags |= base.flags & entry::Flags::PATH_LEN;
+            replacement
+        } else {
+            copy_with_path(base, base.path.clone())
+        };
+        // Replacements are applied first, so entries that are replaced and deleted are still consumed.
+        if delete.get(idx) {
+            continue;
+        }
+        let path = push_path(&mut path_backing, &shared_index.path_backing[base.path.clone()]);
+        entries.push(copy_with_path(&source, path));
+    }
+
+    for added in split_entries {
+        let path = &split_path_backing[added.path.clone()];
+        if path.is_empty() {
+            return Err(Error::Corrupt("entries added by the split index must have a path"));
+        }
+        let stage = added.stage();
+        let pos = entries.binary_search_by(|e: &Entry| (&path_backing[e.path.clone()], e.stage()).cmp(&(path, stage)));
+        let added = copy_with_path(&added, push_path(&mut path_backing, path));
+        match pos {
+            Ok(pos) => entries[pos] = added,
+            Err(pos) => {
+                // Like in git, a merged entry replaces all conflicting entries of the same path.
+                if stage == 0 {
+                    while entries
+                        .get(pos)
+                        .map_or(false, |e: &Entry| &path_backing[e.path.clone()] == path)
+                    {
+                        entries.remove(pos);
+                    }
+                }
+                entries.insert(pos, added);
+            }
+        }
+    }
+
+    split_index.entries = entries;
+    split_index.path_backing = path_backing;
+    split_index.is_sparse |= shared_index.is_sparse;
+    split_index.shared_index = Some(Box::new(shared_index));
+    // The file system monitor extension of a split index refers to the merged entries.
+    split_index.apply_fs_monitor_extension();
+    Ok(())
+}
+
+/// Compute the entries to write into the split index to obtain the entries of `state` when applied to `shared_index`,
+/// along with the bitmaps of the link extension describing them.
+///
+/// The entries replacing shared index entries come first and have no path, followed by all added entries.
+pub(crate) fn split_entries<'a>(state: &'a State, shared_index: &State) -> (Vec<(&'a Entry, &'a [u8])>, Bitmaps) {
+    let mut delete = git_bitmap::Bitset::default();
+    let mut replace = git_bitmap::Bitset::default();
+    let mut entries = Vec::new();
+    let mut added = Vec::new();
+
+    let key = |state: &'a State, e: &'a Entry| (&state.path_backing[e.path.clone()], e.stage());
+    let mut current = state.entries.iter().peekable();
+    for (idx, base) in shared_index.entries.iter().enumerate() {
+        let base_key = (&shared_index.path_backing[base.path.clone()], base.stage());
+        while let Some(entry) = current.next_if(|e| key(state, *e) < base_key) {
+            added.push((entry, key(state, entry).0));
+        }
+        match current.next_if(|e| key(state, *e) == base_key) {
+            Some(entry) => {
+                if !has_same_content(entry, base) {
+                    replace.set(idx);
+                    entries.push((entry, &[][..]));
+                }
+            }
+            None => delete.set(idx),
+        }
+    }
+    added.extend(current.map(|entry| (entry, key(state, entry).0)));
+    entries.extend(added);
+
+    (
+        entries,
+        Bitmaps {
+            delete: git_bitmap::ewah::Vec::from_bitset_trimmed(&delete),
+            replace: git_bitmap::ewah::Vec::from_bitset_trimmed(&replace),
+        },
+    )
+}
+
+/// Return true if `lhs` and `rhs` would be written the same, ignoring their paths.
+fn has_same_content(lhs: &Entry, rhs: &Entry) -> bool {
+    let on_disk_flags = |e: &Entry| {
+        (
+            entry::at_rest::Flags::from_flags(e.flags),
+            entry::at_rest::FlagsExtended::from_flags(e.flags),
+        )
+    };
+    lhs.stat == rhs.stat && lhs.id == rhs.id && lhs.mode == rhs.mode && on_disk_flags(lhs) == on_disk_flags(rhs)
+}
+
+fn copy_with_path(e: &Entry, path: std::ops::Range<usize>) -> Entry {
+    Entry {
+        stat: e.stat,
+        id: e.id,
+        flags: e.flags,
+        mode: e.mode,
+        path,
+    }
+}
+
+fn push_path(path_backing: &mut Vec<u8>, path: &[u8]) -> std::ops::Range<usize> {
+    let start = path_backing.len();
+    path_backing.extend_from_slice(path);
+    start..path_backing.len()
+}
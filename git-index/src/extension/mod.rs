@@ -20,7 +20,9 @@ pub struct Tree {
     pub id: git_hash::ObjectId,
     /// The amount of non-tree items in this directory tree, including sub-trees, recursively.
     /// The value of the top-level tree is thus equal to the value of the total amount of entries.
-    pub num_entries: u32,
+    ///
+    /// If `None`, the tree was invalidated by a change to the entries it covers and `id` must not be used.
+    pub num_entries: Option<u32>,
     pub children: Vec<Tree>,
 }
 
@@ -29,33 +31,38 @@ pub struct Link {
     pub bitmaps: Option<link::Bitmaps>,
 }
 
-#[allow(dead_code)]
+/// A cache of untracked files and directories along with the information needed to know when it becomes stale.
 pub struct UntrackedCache {
     /// Something identifying the location and machine that this cache is for.
     /// Should the repository be copied to a different machine, the entire cache can immediately be invalidated.
-    identifier: BString,
-    /// Stat for the .git/info/exclude file
-    info_exclude: Option<untracked_cache::OidStat>,
-    /// Stat for the `core.excludesfile`
-    excludes_file: Option<untracked_cache::OidStat>,
+    pub identifier: BString,
+    /// Stat for the .git/info/exclude file, if it exists
+    pub info_exclude: Option<untracked_cache::OidStat>,
+    /// Stat for the `core.excludesfile`, if it exists
+    pub excludes_file: Option<untracked_cache::OidStat>,
     /// Usually `.gitignore`
-    exclude_filename_per_dir: BString,
-    dir_flags: u32,
+    pub exclude_filename_per_dir: BString,
+    /// The flags of `dir_struct` used when the cache was created, which must match for the cache to be used.
+    pub dir_flags: u32,
 
     /// A list of directories and sub-directories, with `directories[0]` being the root.
-    directories: Vec<untracked_cache::Directory>,
+    pub directories: Vec<untracked_cache::Directory>,
 }
 
-#[allow(dead_code)]
+/// The state of a file system monitor at the time the index was written, and the entries it considered dirty.
 pub struct FsMonitor {
-    token: fs_monitor::Token,
+    /// The token to pass to the file system monitor to learn about changes since the index was written.
+    pub token: fs_monitor::Token,
     /// if a bit is true, the resepctive entry is NOT valid as per the fs monitor.
-    entry_dirty: git_bitmap::ewah::Vec,
+    ///
+    /// Entries are marked as [valid][crate::entry::Flags::FSMONITOR_VALID] accordingly when the index is read,
+    /// and these flags are written back instead.
+    pub entry_dirty: git_bitmap::ewah::Vec,
 }
 
 mod iter;
 
-pub(crate) mod fs_monitor;
+pub mod fs_monitor;
 
 pub(crate) mod decode;
 
@@ -67,7 +74,7 @@ pub(crate) mod index_entry_offset_table;
 
 pub mod link;
 
-pub(crate) mod resolve_undo;
+pub mod resolve_undo;
 
 pub mod untracked_cache;
 
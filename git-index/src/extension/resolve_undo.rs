@@ -1,4 +1,4 @@
-use bstr::BString;
+use bstr::{BStr, BString};
 use git_hash::ObjectId;
 
 use crate::{
@@ -6,26 +6,65 @@ use crate::{
     util::{split_at_byte_exclusive, split_at_pos},
 };
 
+/// All paths whose conflicts were resolved, in the order they are stored in.
 pub type Paths = Vec<ResolvePath>;
 
-#[allow(dead_code)]
+/// A path that had a conflict before it was resolved, along with the stages needed to recreate it.
 pub struct ResolvePath {
     /// relative to the root of the repository, or what would be stored in the index
-    name: BString,
+    pub name: BString,
 
     /// 0 = ancestor/common, 1 = ours, 2 = theirs
-    stages: [Option<Stage>; 3],
+    pub stages: [Option<Stage>; 3],
 }
 
-#[allow(dead_code)]
+/// A single stage of a conflicted path.
 pub struct Stage {
-    mode: u32,
-    id: ObjectId,
+    /// The mode of the entry at this stage.
+    pub mode: u32,
+    /// The id of the blob at this stage.
+    pub id: ObjectId,
 }
 
 pub const SIGNATURE: Signature = *b"REUC";
 
-pub fn decode(mut data: &[u8], object_hash: git_hash::Kind) -> Option<Paths> {
+/// Record the `mode` and `id` of the entry at `path` with `stage`, which must be 1, 2 or 3, so the conflict can be
+/// recreated once it was resolved.
+pub(crate) fn record(paths: &mut Paths, path: &BStr, stage: u32, mode: u32, id: ObjectId) {
+    let stage_index = match stage {
+        1..=3 => stage as usize - 1,
+        _ => return,
+    };
+    let idx = match paths.iter().position(|p| p.name == path) {
+        Some(idx) => idx,
+        None => {
+            paths.push(ResolvePath {
+                name: path.into(),
+                stages: [None, None, None],
+            });
+            paths.len() - 1
+        }
+    };
+    paths[idx].stages[stage_index] = Some(Stage { mode, id });
+}
+
+pub(crate) fn write_to(paths: &[ResolvePath], mut out: impl std::io::Write) -> std::io::Result<()> {
+    for path in paths {
+        out.write_all(&path.name)?;
+        out.write_all(b"\0")?;
+        for stage in &path.stages {
+            let mode = stage.as_ref().map_or(0, |stage| stage.mode);
+            out.write_all(format!("{:o}", mode).as_bytes())?;
+            out.write_all(b"\0")?;
+        }
+        for stage in path.stages.iter().flatten() {
+            out.write_all(stage.id.as_slice())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn decode(mut data: &[u8], object_hash: git_hash::Kind) -> Option<Paths> {
     let hash_len = object_hash.len_in_bytes();
     let mut out = Vec::new();
 
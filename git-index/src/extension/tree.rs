@@ -1,11 +1,13 @@
 use std::cmp::Ordering;
 
-use bstr::ByteSlice;
+use bstr::{BStr, ByteSlice};
 use git_hash::ObjectId;
 
 use crate::{
+    entry,
     extension::{Signature, Tree},
     util::{split_at_byte_exclusive, split_at_pos},
+    Entry, State,
 };
 
 pub const SIGNATURE: Signature = *b"TREE";
@@ -59,7 +61,7 @@ impl Tree {
             let mut entries = 0;
             let mut prev = None::<&Tree>;
             for child in children {
-                entries += child.num_entries;
+                entries += child.num_entries.unwrap_or(0);
                 if let Some(prev) = prev {
                     if prev.name.cmp(&child.name) != Ordering::Less {
                         return Err(verify::Error::OutOfOrder {
@@ -71,7 +73,7 @@ impl Tree {
                 }
                 prev = Some(child);
             }
-            if let Some(buf) = find_buf.as_mut() {
+            if let Some(buf) = find_buf.as_mut().filter(|_| !parent_id.is_null()) {
                 let tree_entries = find(&parent_id, *buf).ok_or(verify::Error::TreeNodeNotFound { oid: parent_id })?;
                 let mut num_entries = 0;
                 for entry in tree_entries
@@ -100,12 +102,9 @@ impl Tree {
                 // This is actually needed here as it's a mut ref, which isn't copy. We do a re-borrow here.
                 #[allow(clippy::needless_option_as_deref)]
                 let actual_num_entries = verify_recursive(child.id, &child.children, find_buf.as_deref_mut(), find)?;
-                if let Some(actual) = actual_num_entries {
-                    if actual > child.num_entries {
-                        return Err(verify::Error::EntriesCount {
-                            actual,
-                            expected: child.num_entries,
-                        });
+                if let (Some(actual), Some(expected)) = (actual_num_entries, child.num_entries) {
+                    if actual > expected {
+                        return Err(verify::Error::EntriesCount { actual, expected });
                     }
                 }
             }
@@ -120,12 +119,9 @@ impl Tree {
 
         let mut buf = Vec::new();
         let declared_entries = verify_recursive(self.id, &self.children, use_find.then(|| &mut buf), &mut find)?;
-        if let Some(actual) = declared_entries {
-            if actual > self.num_entries {
-                return Err(verify::Error::EntriesCount {
-                    actual,
-                    expected: self.num_entries,
-                });
+        if let (Some(actual), Some(expected)) = (declared_entries, self.num_entries) {
+            if actual > expected {
+                return Err(verify::Error::EntriesCount { actual, expected });
             }
         }
 
@@ -133,6 +129,207 @@ impl Tree {
     }
 }
 
+impl Tree {
+    /// Serialize this tree and all of its children in the format of the `TREE` extension to `out`.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        fn write_recursive(tree: &Tree, out: &mut dyn std::io::Write) -> std::io::Result<()> {
+            out.write_all(&tree.name)?;
+            out.write_all(b"\0")?;
+            match tree.num_entries {
+                Some(num_entries) => {
+                    out.write_all(format!("{} {}\n", num_entries, tree.children.len()).as_bytes())?;
+                    out.write_all(tree.id.as_slice())?;
+                }
+                // Invalidated trees are written without their id.
+                None => out.write_all(format!("-1 {}\n", tree.children.len()).as_bytes())?,
+            }
+            for child in &tree.children {
+                write_recursive(child, out)?;
+            }
+            Ok(())
+        }
+        write_recursive(self, &mut out)
+    }
+}
+
+impl Tree {
+    /// Mark this tree and all trees leading to the item at `path` as invalid, to be recomputed when the next tree
+    /// is written from the index.
+    ///
+    /// This needs to be called whenever the entry at `path` is added, removed or changed.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        self.num_entries = None;
+        let mut tree = self;
+        let mut components = path.split(|b| *b == b'/').peekable();
+        while let Some(name) = components.next() {
+            // The last component is the entry itself.
+            if components.peek().is_none() {
+                break;
+            }
+            match tree.children.binary_search_by(|c| c.name[..].cmp(name)) {
+                Ok(idx) => {
+                    tree = &mut tree.children[idx];
+                    tree.num_entries = None;
+                }
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+pub mod write_tree {
+    use bstr::BString;
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Unmerged { path: BString } {
+                display("Entry '{}' is unmerged and can't be written into a tree", path)
+            }
+            UnknownMode { path: BString, mode: u32 } {
+                display("Entry '{}' has unknown mode {:o}", path, mode)
+            }
+            WriteTree(err: Box<dyn std::error::Error + Send + Sync + 'static>) {
+                display("A tree object could not be written")
+                source(&**err)
+            }
+        }
+    }
+}
+
+impl State {
+    /// Write the trees of all entries using `write`, which stores a tree and returns its id, and return the id of
+    /// the root tree.
+    ///
+    /// Only trees that are new or were [invalidated][Tree::invalidate_path()] since the last time are written, all
+    /// others are taken from the tree extension which is updated accordingly.
+    /// Entries marked for removal or as intended to be added are not part of the written trees.
+    pub fn write_tree<F, E>(&mut self, mut write: F) -> Result<ObjectId, write_tree::Error>
+    where
+        F: FnMut(&git_object::Tree) -> Result<ObjectId, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let (tree, _num_entries) = write_recursive(
+            self.tree.take(),
+            &[],
+            &[],
+            &self.entries,
+            &self.path_backing,
+            &mut write,
+        )?;
+        let id = tree.id;
+        self.tree = Some(tree);
+        Ok(id)
+    }
+}
+
+/// Write the tree named `name` containing all leading `entries` whose paths start with `prefix`, reusing `cached` if
+/// it's still valid, and return it along with the amount of entries it covers.
+fn write_recursive<F, E>(
+    cached: Option<Tree>,
+    name: &[u8],
+    prefix: &[u8],
+    entries: &[Entry],
+    path_backing: &[u8],
+    write: &mut F,
+) -> Result<(Tree, usize), write_tree::Error>
+where
+    F: FnMut(&git_object::Tree) -> Result<ObjectId, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut cached_children = match cached {
+        Some(tree) => match tree.num_entries {
+            Some(num_entries) if num_entries as usize <= entries.len() => return Ok((tree, num_entries as usize)),
+            _ => tree.children,
+        },
+        None => Vec::new(),
+    };
+
+    let mut tree = git_object::Tree::empty();
+    let mut children = Vec::new();
+    let mut idx = 0;
+    while let Some(entry) = entries.get(idx) {
+        let path = &path_backing[entry.path.clone()];
+        if !path.starts_with(prefix) {
+            break;
+        }
+        if entry.stage() != 0 {
+            return Err(write_tree::Error::Unmerged { path: path.into() });
+        }
+        let relative_path = &path[prefix.len()..];
+        match relative_path.find_byte(b'/') {
+            Some(pos) => {
+                let child_name = &relative_path[..pos];
+                let cached_child = cached_children
+                    .binary_search_by(|c| c.name[..].cmp(child_name))
+                    .ok()
+                    .map(|idx| cached_children.remove(idx));
+                let (child, num_entries) = if entry.mode.is_sparse() {
+                    // Sparse directories are trees in their own right.
+                    (
+                        Tree {
+                            name: child_name.into(),
+                            id: entry.id,
+                            num_entries: Some(1),
+                            children: Vec::new(),
+                        },
+                        1,
+                    )
+                } else {
+                    write_recursive(
+                        cached_child,
+                        child_name,
+                        &path[..prefix.len() + pos + 1],
+                        &entries[idx..],
+                        path_backing,
+                        write,
+                    )?
+                };
+                tree.entries.push(git_object::tree::Entry {
+                    mode: git_object::tree::EntryMode::Tree,
+                    filename: child_name.into(),
+                    oid: child.id,
+                });
+                children.push(child);
+                idx += num_entries;
+            }
+            None => {
+                if !entry
+                    .flags
+                    .intersects(entry::Flags::REMOVE | entry::Flags::INTENT_TO_ADD)
+                {
+                    tree.entries.push(git_object::tree::Entry {
+                        mode: entry
+                            .mode
+                            .to_tree_entry_mode()
+                            .ok_or_else(|| write_tree::Error::UnknownMode {
+                                path: path.into(),
+                                mode: entry.mode.bits(),
+                            })?,
+                        filename: relative_path.into(),
+                        oid: entry.id,
+                    });
+                }
+                idx += 1;
+            }
+        }
+    }
+
+    // The order of directories in git trees differs from the order of their names.
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    let id = write(&tree).map_err(|err| write_tree::Error::WriteTree(Box::new(err)))?;
+    Ok((
+        Tree {
+            name: name.into(),
+            id,
+            num_entries: Some(idx as u32),
+            children,
+        },
+        idx,
+    ))
+}
+
 /// A recursive data structure
 pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<Tree> {
     let (tree, data) = one_recursive(data, object_hash.len_in_bytes())?;
@@ -148,13 +345,18 @@ pub fn one_recursive(data: &[u8], hash_len: usize) -> Option<(Tree, &[u8])> {
     let (path, data) = split_at_byte_exclusive(data, 0)?;
 
     let (entry_count, data) = split_at_byte_exclusive(data, b' ')?;
-    let num_entries: u32 = atoi::atoi(entry_count)?;
+    let num_entries: i32 = atoi::atoi(entry_count)?;
 
     let (subtree_count, data) = split_at_byte_exclusive(data, b'\n')?;
     let subtree_count: usize = atoi::atoi(subtree_count)?;
 
-    let (hash, mut data) = split_at_pos(data, hash_len)?;
-    let id = ObjectId::from(hash);
+    // Invalidated trees have a negative entry count and no id.
+    let (id, num_entries, mut data) = if num_entries >= 0 {
+        let (hash, data) = split_at_pos(data, hash_len)?;
+        (ObjectId::from(hash), Some(num_entries as u32), data)
+    } else {
+        (ObjectId::null(git_hash::Kind::from_hex_len(hash_len * 2)?), None, data)
+    };
 
     let mut subtrees = Vec::with_capacity(subtree_count);
     for _ in 0..subtree_count {
@@ -187,6 +389,6 @@ mod tests {
 
     #[test]
     fn size_of_tree() {
-        assert_eq!(std::mem::size_of::<Tree>(), 80);
+        assert_eq!(std::mem::size_of::<Tree>(), 104);
     }
 }
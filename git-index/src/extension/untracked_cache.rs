@@ -1,19 +1,29 @@
-use std::convert::TryInto;
+use std::{convert::TryInto, io, path::Path};
 
-use bstr::BString;
+use bstr::{BStr, BString, ByteSlice};
 use git_hash::ObjectId;
 
 use crate::{
     entry,
     extension::{Signature, UntrackedCache},
-    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int},
+    util::{read_u32, split_at_byte_exclusive, split_at_pos, var_int, write_stat, write_var_int},
 };
 
+/// The stat information and object id of a file that affects the untracked cache.
 pub struct OidStat {
+    /// The stat information of the file at the time the cache was written.
     pub stat: entry::Stat,
+    /// The id of the file's content, which may be null if it wasn't computed.
     pub id: ObjectId,
 }
 
+impl OidStat {
+    /// Return true if there is no information at all, which is how files that don't exist are stored.
+    fn is_unset(&self) -> bool {
+        self.id.is_null() && self.stat == entry::Stat::default()
+    }
+}
+
 /// A directory with information about its untracked files, and its sub-directories
 pub struct Directory {
     /// The directories name, or an empty string if this is the root directory.
@@ -23,19 +33,331 @@ pub struct Directory {
     /// indices for sub-directories similar to this one.
     pub sub_directories: Vec<usize>,
 
-    /// The directories stat data, if available or valid // TODO: or is it the exclude file?
+    /// The stat data of the directory, if it is valid.
     pub stat: Option<entry::Stat>,
     /// The oid of a .gitignore file, if it exists
     pub exclude_file_oid: Option<ObjectId>,
-    /// TODO: figure out what this really does
+    /// If true, the untracked entries only tell whether the directory contains untracked files at all, as it was
+    /// only checked for their existence.
     pub check_only: bool,
 }
 
 /// Only used as an indicator
 pub const SIGNATURE: Signature = *b"UNTR";
 
-#[allow(unused)]
-pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<UntrackedCache> {
+/// Initialization and validation
+impl UntrackedCache {
+    /// Create a cache for the worktree identified by `identifier` without any information, to be filled in while
+    /// scanning for untracked files with the given `dir_flags` and `exclude_filename_per_dir`, usually `.gitignore`.
+    pub fn new(identifier: impl Into<BString>, dir_flags: u32, exclude_filename_per_dir: impl Into<BString>) -> Self {
+        UntrackedCache {
+            identifier: identifier.into(),
+            info_exclude: None,
+            excludes_file: None,
+            exclude_filename_per_dir: exclude_filename_per_dir.into(),
+            dir_flags,
+            directories: vec![Directory::new("")],
+        }
+    }
+
+    /// Return the identifier git uses for the worktree at `worktree` on a system named `system_name`, which is what
+    /// `uname -s` prints, like `Linux`.
+    pub fn identifier_for(worktree: &Path, system_name: &str) -> BString {
+        let mut identifier = BString::from(format!("Location {}, system {}", worktree.display(), system_name));
+        identifier.push(0);
+        identifier
+    }
+
+    /// Return true if this cache was created for the worktree identified by `identifier` while scanning with the same
+    /// `dir_flags` and `exclude_filename_per_dir`, which is required for any of its information to be usable.
+    pub fn is_usable_for(&self, identifier: &[u8], dir_flags: u32, exclude_filename_per_dir: &[u8]) -> bool {
+        self.identifier == identifier
+            && self.dir_flags == dir_flags
+            && self.exclude_filename_per_dir == exclude_filename_per_dir
+    }
+
+    /// Invalidate all information that changes on disk may have made stale, returning the amount of invalidated
+    /// directories.
+    ///
+    /// Directories whose stat information doesn't match their directory below `worktree` anymore lose their untracked
+    /// entries, while a changed exclude file in a directory invalidates it along with all of its sub-directories.
+    /// All directories are invalidated if the exclude files at `info_exclude` or `excludes_file` changed, whose
+    /// new stat information and ids are recorded. Ids of changed exclude files are computed with `object_hash`.
+    pub fn invalidate_outdated(
+        &mut self,
+        worktree: &Path,
+        info_exclude: Option<&Path>,
+        excludes_file: Option<&Path>,
+        object_hash: git_hash::Kind,
+    ) -> io::Result<usize> {
+        let mut invalidated = vec![false; self.directories.len()];
+        let info_exclude_changed = update_oid_stat(&mut self.info_exclude, info_exclude, object_hash)?;
+        let excludes_file_changed = update_oid_stat(&mut self.excludes_file, excludes_file, object_hash)?;
+        if self.directories.is_empty() {
+            return Ok(0);
+        }
+        let exclude_filename_per_dir = git_features::path::from_byte_slice(&self.exclude_filename_per_dir)
+            .ok()
+            .map(ToOwned::to_owned);
+        if info_exclude_changed || excludes_file_changed || exclude_filename_per_dir.is_none() {
+            self.invalidate_recursively(0, &mut invalidated);
+        }
+
+        let mut stack = vec![(0, worktree.to_owned())];
+        while let Some((index, path)) = stack.pop() {
+            if invalidated[index] {
+                continue;
+            }
+            if let Some(file_name) = &exclude_filename_per_dir {
+                let exclude_file_oid = blob_id_of_file(&path.join(file_name), object_hash)?;
+                if exclude_file_oid != self.directories[index].exclude_file_oid {
+                    self.invalidate_recursively(index, &mut invalidated);
+                    continue;
+                }
+            }
+
+            let dir = &mut self.directories[index];
+            if let Some(stat) = dir.stat {
+                let current_stat = match std::fs::symlink_metadata(&path) {
+                    Ok(meta) => Some(entry::Stat::from_fs(&meta)),
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                    Err(err) => return Err(err),
+                };
+                if current_stat != Some(stat) {
+                    dir.invalidate();
+                    invalidated[index] = true;
+                }
+            }
+            for sub_directory in dir.sub_directories.clone() {
+                match git_features::path::from_byte_slice(&self.directories[sub_directory].name) {
+                    Ok(name) => stack.push((sub_directory, path.join(name))),
+                    Err(_) => self.invalidate_recursively(sub_directory, &mut invalidated),
+                }
+            }
+        }
+        Ok(invalidated.into_iter().filter(|invalidated| *invalidated).count())
+    }
+
+    fn invalidate_recursively(&mut self, index: usize, invalidated: &mut [bool]) {
+        let mut stack = vec![index];
+        while let Some(index) = stack.pop() {
+            let dir = &mut self.directories[index];
+            dir.invalidate();
+            dir.exclude_file_oid = None;
+            invalidated[index] = true;
+            stack.extend_from_slice(&dir.sub_directories);
+        }
+    }
+}
+
+/// Mutation
+impl UntrackedCache {
+    /// Return the index of the directory at the slash-separated `path` relative to the worktree root, with an empty path
+    /// being the root, adding it and all of its parent directories without any information if they don't exist yet.
+    pub fn directory_index_or_insert(&mut self, path: &BStr) -> usize {
+        if self.directories.is_empty() {
+            self.directories.push(Directory::new(""));
+        }
+        let mut index = 0;
+        for name in path.split_str("/").filter(|name| !name.is_empty()) {
+            let directories = &self.directories;
+            let pos = self.directories[index]
+                .sub_directories
+                .binary_search_by(|sub_directory| directories[*sub_directory].name.as_slice().cmp(name));
+            index = match pos {
+                Ok(pos) => self.directories[index].sub_directories[pos],
+                Err(pos) => {
+                    let sub_directory = self.directories.len();
+                    self.directories.push(Directory::new(name));
+                    self.directories[index].sub_directories.insert(pos, sub_directory);
+                    sub_directory
+                }
+            };
+        }
+        index
+    }
+
+    /// Invalidate the directory containing the file or directory at the slash-separated `path` relative to the worktree
+    /// root, along with all of its parent directories, as an entry at `path` was added to or removed from the index.
+    pub fn invalidate_path(&mut self, path: &BStr) {
+        if self.directories.is_empty() {
+            return;
+        }
+        let mut index = 0;
+        self.directories[index].invalidate();
+        let mut components = path.split_str("/").filter(|name| !name.is_empty()).peekable();
+        while let Some(name) = components.next() {
+            if components.peek().is_none() {
+                break;
+            }
+            let directories = &self.directories;
+            match self.directories[index]
+                .sub_directories
+                .binary_search_by(|sub_directory| directories[*sub_directory].name.as_slice().cmp(name))
+            {
+                Ok(pos) => index = self.directories[index].sub_directories[pos],
+                Err(_) => break,
+            }
+            self.directories[index].invalidate();
+        }
+    }
+
+    /// Record that the directory at `index` was scanned and found to contain `untracked_entries`, with directories
+    /// having a trailing slash, while having the given `stat` and an exclude file with `exclude_file_oid`.
+    pub fn set_directory_scanned(
+        &mut self,
+        index: usize,
+        stat: entry::Stat,
+        exclude_file_oid: Option<ObjectId>,
+        untracked_entries: Vec<BString>,
+    ) {
+        let dir = &mut self.directories[index];
+        dir.stat = Some(stat);
+        dir.exclude_file_oid = exclude_file_oid;
+        dir.untracked_entries = untracked_entries;
+    }
+}
+
+impl Directory {
+    fn new(name: impl Into<BString>) -> Self {
+        Directory {
+            name: name.into(),
+            untracked_entries: Vec::new(),
+            sub_directories: Vec::new(),
+            stat: None,
+            exclude_file_oid: None,
+            check_only: false,
+        }
+    }
+
+    /// Return true if the untracked entries of this directory can be used without scanning it again.
+    pub fn is_valid(&self) -> bool {
+        self.stat.is_some()
+    }
+
+    fn invalidate(&mut self) {
+        self.stat = None;
+        self.untracked_entries.clear();
+    }
+}
+
+impl UntrackedCache {
+    /// Serialize this cache in the format of the `UNTR` extension to `out`, with directories that can't be reached
+    /// from the root directory being omitted.
+    pub(crate) fn write_to(&self, mut out: impl io::Write, object_hash: git_hash::Kind) -> io::Result<()> {
+        write_var_int(&mut out, self.identifier.len() as u64)?;
+        out.write_all(&self.identifier)?;
+        for oid_stat in &[&self.info_exclude, &self.excludes_file] {
+            match oid_stat {
+                Some(oid_stat) => {
+                    write_stat(&mut out, &oid_stat.stat)?;
+                    out.write_all(oid_stat.id.as_slice())?;
+                }
+                None => {
+                    write_stat(&mut out, &entry::Stat::default())?;
+                    out.write_all(ObjectId::null(object_hash).as_slice())?;
+                }
+            }
+        }
+        out.write_all(&self.dir_flags.to_be_bytes())?;
+        out.write_all(&self.exclude_filename_per_dir)?;
+        out.write_all(b"\0")?;
+
+        if self.directories.is_empty() {
+            write_var_int(&mut out, 0)?;
+            return Ok(());
+        }
+        // Directories are written depth-first, which also determines their position in the bitmaps.
+        let mut directories = Vec::with_capacity(self.directories.len());
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let dir = &self.directories[index];
+            directories.push(dir);
+            stack.extend(dir.sub_directories.iter().rev());
+        }
+
+        write_var_int(&mut out, directories.len() as u64)?;
+        for dir in &directories {
+            write_var_int(&mut out, dir.untracked_entries.len() as u64)?;
+            write_var_int(&mut out, dir.sub_directories.len() as u64)?;
+            out.write_all(&dir.name)?;
+            out.write_all(b"\0")?;
+            for entry in &dir.untracked_entries {
+                out.write_all(entry)?;
+                out.write_all(b"\0")?;
+            }
+        }
+
+        let mut valid = git_bitmap::Bitset::default();
+        let mut check_only = git_bitmap::Bitset::default();
+        let mut hash_valid = git_bitmap::Bitset::default();
+        for (index, dir) in directories.iter().enumerate() {
+            if dir.stat.is_some() {
+                valid.set(index);
+            }
+            if dir.check_only {
+                check_only.set(index);
+            }
+            if dir.exclude_file_oid.is_some() {
+                hash_valid.set(index);
+            }
+        }
+        for bitmap in &[valid, check_only, hash_valid] {
+            git_bitmap::ewah::Vec::from_bitset_trimmed(bitmap).write_to(&mut out)?;
+        }
+        for stat in directories.iter().filter_map(|dir| dir.stat.as_ref()) {
+            write_stat(&mut out, stat)?;
+        }
+        for id in directories.iter().filter_map(|dir| dir.exclude_file_oid.as_ref()) {
+            out.write_all(id.as_slice())?;
+        }
+        out.write_all(b"\0")
+    }
+}
+
+/// Update `oid_stat` to match the file at `path` and return true if its id changed.
+fn update_oid_stat(
+    oid_stat: &mut Option<OidStat>,
+    path: Option<&Path>,
+    object_hash: git_hash::Kind,
+) -> io::Result<bool> {
+    let meta = match path.map(std::fs::symlink_metadata) {
+        Some(Ok(meta)) => Some(meta),
+        Some(Err(err)) if err.kind() == io::ErrorKind::NotFound => None,
+        Some(Err(err)) => return Err(err),
+        None => None,
+    };
+    let (path, meta) = match (path, meta) {
+        (Some(path), Some(meta)) => (path, meta),
+        _ => return Ok(oid_stat.take().is_some()),
+    };
+    let stat = entry::Stat::from_fs(&meta);
+    if oid_stat.as_ref().map_or(false, |oid_stat| oid_stat.stat == stat) {
+        return Ok(false);
+    }
+    let id = match blob_id_of_file(path, object_hash)? {
+        Some(id) => id,
+        None => return Ok(oid_stat.take().is_some()),
+    };
+    let changed = oid_stat.as_ref().map_or(true, |oid_stat| oid_stat.id != id);
+    *oid_stat = Some(OidStat { stat, id });
+    Ok(changed)
+}
+
+/// Return the id the file at `path` would have as blob, or `None` if it doesn't exist.
+fn blob_id_of_file(path: &Path, object_hash: git_hash::Kind) -> io::Result<Option<ObjectId>> {
+    let data = match std::fs::read(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let mut hasher = git_features::hash::hasher(object_hash);
+    hasher.update(&git_object::encode::loose_header(git_object::Kind::Blob, data.len()));
+    hasher.update(&data);
+    Ok(Some(hasher.digest()))
+}
+
+pub(crate) fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<UntrackedCache> {
     if !data.last().map(|b| *b == 0).unwrap_or(false) {
         return None;
     }
@@ -52,8 +374,8 @@ pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<UntrackedCache
 
     let mut res = UntrackedCache {
         identifier: identifier.into(),
-        info_exclude: (!info_exclude.id.is_null()).then(|| info_exclude),
-        excludes_file: (!excludes_file.id.is_null()).then(|| excludes_file),
+        info_exclude: (!info_exclude.is_unset()).then(|| info_exclude),
+        excludes_file: (!excludes_file.is_unset()).then(|| excludes_file),
         exclude_filename_per_dir: exclude_filename_per_dir.into(),
         dir_flags,
         directories: Vec::new(),
@@ -90,13 +412,13 @@ pub fn decode(data: &[u8], object_hash: git_hash::Kind) -> Option<UntrackedCache
         directories[index].stat = stat.into();
         data = rest;
         Some(())
-    });
+    })?;
     hash_valid.for_each_set_bit(|index| {
         let (hash, rest) = split_at_pos(data, hash_len)?;
         data = rest;
         directories[index].exclude_file_oid = ObjectId::from(hash).into();
         Some(())
-    });
+    })?;
 
     // null-byte checked in the beginning
     if data.len() != 1 {
@@ -22,6 +22,15 @@ mod error {
                 source(err)
                 from()
             }
+            SharedIndex{path: std::path::PathBuf, err: Box<Error>} {
+                display("The shared index at '{}' could not be read", path.display())
+                source(&**err)
+            }
+            Link(err: crate::extension::link::dissolve::Error) {
+                display("The split index could not be merged with its shared index")
+                source(err)
+                from()
+            }
         }
     }
 }
@@ -29,17 +38,42 @@ mod error {
 pub use error::Error;
 
 impl File {
+    /// Open the index file at `path` and decode it with `options`.
+    ///
+    /// If it is a split index, the shared index it refers to is read from the same directory and merged with it, so
+    /// that all entries are available.
     pub fn at(path: impl Into<PathBuf>, options: decode::Options) -> Result<Self, Error> {
         let path = path.into();
-        let (data, mtime) = {
-            // SAFETY: we have to take the risk of somebody changing the file underneath. Git never writes into the same file.
-            let file = std::fs::File::open(&path)?;
-            #[allow(unsafe_code)]
-            let data = unsafe { Mmap::map(&file)? };
-            (data, filetime::FileTime::from_last_modification_time(&file.metadata()?))
-        };
-
-        let (state, checksum) = State::from_bytes(&data, mtime, options)?;
+        let (mut state, checksum) = state_at(&path, options)?;
+
+        if let Some(shared_index_path) = state.link.as_ref().and_then(|link| link.shared_index_path(&path)) {
+            let (shared_index, shared_index_checksum) =
+                state_at(&shared_index_path, options).map_err(|err| Error::SharedIndex {
+                    path: shared_index_path,
+                    err: Box::new(err),
+                })?;
+            extension::link::dissolve_into(&mut state, shared_index, shared_index_checksum)?;
+        }
         Ok(File { state, path, checksum })
     }
+
+    /// Create a file for `state` which will be written to `path`, without accessing the file system.
+    pub fn from_state(state: State, path: impl Into<PathBuf>) -> Self {
+        File {
+            checksum: git_hash::Kind::Sha1.null(),
+            state,
+            path: path.into(),
+        }
+    }
+}
+
+fn state_at(path: &Path, options: decode::Options) -> Result<(State, git_hash::ObjectId), Error> {
+    let (data, mtime) = {
+        // SAFETY: we have to take the risk of somebody changing the file underneath. Git never writes into the same file.
+        let file = std::fs::File::open(path)?;
+        #[allow(unsafe_code)]
+        let data = unsafe { Mmap::map(&file)? };
+        (data, filetime::FileTime::from_last_modification_time(&file.metadata()?))
+    };
+    Ok(State::from_bytes(&data, mtime, options)?)
 }
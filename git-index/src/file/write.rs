@@ -0,0 +1,45 @@
+use crate::{write, File};
+
+mod error {
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Io(err: std::io::Error) {
+                display("Could not write the index to its lock file")
+                source(err)
+                from()
+            }
+            Lock(err: git_lock::acquire::Error) {
+                display("Could not acquire the lock to write the index")
+                source(err)
+                from()
+            }
+            Commit(err: git_lock::commit::Error<git_lock::File>) {
+                display("Could not replace the index with its lock file")
+                source(err)
+                from()
+            }
+        }
+    }
+}
+
+pub use error::Error;
+
+impl File {
+    /// Write the index to the file at our path atomically, by writing it to a lock file next to it first which replaces
+    /// the file once it was written completely. If the lock is held by another process, `lock_mode` determines whether
+    /// to fail immediately or to retry for a while.
+    ///
+    /// Our version and checksum are updated to match the written file.
+    pub fn write(&mut self, options: write::Options, lock_mode: git_lock::acquire::Fail) -> Result<(), Error> {
+        let mut lock = git_lock::File::acquire_to_update_resource(&self.path, lock_mode, None)?;
+        let (version, checksum) = self.state.write_to(std::io::BufWriter::new(&mut lock), options)?;
+        lock.commit()?;
+
+        self.state.version = version;
+        self.checksum = checksum;
+        Ok(())
+    }
+}
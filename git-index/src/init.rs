@@ -0,0 +1,117 @@
+//! Create new index states, for instance from the tree of a commit.
+use bstr::{BStr, BString, ByteVec};
+use git_hash::{oid, ObjectId};
+use git_object::{tree::EntryMode, TreeRefIter};
+
+use crate::{entry, Entry, State, Version};
+
+///
+pub mod from_tree {
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            NotFound { id: git_hash::ObjectId } {
+                display("The tree {} could not be found", id)
+            }
+            Decode { id: git_hash::ObjectId, err: git_object::decode::Error } {
+                display("The tree {} could not be decoded", id)
+                source(err)
+            }
+        }
+    }
+}
+
+impl State {
+    /// Create an index without any entries or extensions, to be written as `version`.
+    pub fn new(version: Version) -> Self {
+        State {
+            timestamp: filetime::FileTime::now(),
+            version,
+            entries: Vec::new(),
+            path_backing: Vec::new(),
+            is_sparse: false,
+
+            tree: None,
+            link: None,
+            resolve_undo: None,
+            untracked: None,
+            fs_monitor: None,
+
+            shared_index: None,
+        }
+    }
+
+    /// Create an index with one entry for each blob, symbolic link and submodule in the `tree` and its subtrees,
+    /// using `find` to obtain trees, just like `git read-tree` does.
+    ///
+    /// As no files exist yet, all entries carry empty stat information and will be checked thoroughly when compared to
+    /// files in the worktree.
+    pub fn from_tree<Find>(tree: &oid, mut find: Find) -> Result<Self, from_tree::Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+    {
+        let mut state = State::new(Version::V2);
+        let mut buf = Vec::new();
+        let mut prefix = BString::default();
+        add_tree_entries(&mut state, tree, &mut prefix, &mut buf, &mut find)?;
+        Ok(state)
+    }
+}
+
+fn add_tree_entries<Find>(
+    state: &mut State,
+    tree: &oid,
+    prefix: &mut BString,
+    buf: &mut Vec<u8>,
+    find: &mut Find,
+) -> Result<(), from_tree::Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    let entries = find(tree, buf)
+        .ok_or_else(|| from_tree::Error::NotFound { id: tree.to_owned() })?
+        .map(|entry| entry.map(|e| (e.mode, e.filename.to_owned(), e.oid.to_owned())))
+        .collect::<Result<Vec<(EntryMode, BString, ObjectId)>, _>>()
+        .map_err(|err| from_tree::Error::Decode {
+            id: tree.to_owned(),
+            err,
+        })?;
+
+    for (mode, filename, id) in entries {
+        let prefix_len = prefix.len();
+        if prefix_len != 0 {
+            prefix.push(b'/');
+        }
+        prefix.push_str(&filename);
+        let mode = match mode {
+            EntryMode::Tree => {
+                add_tree_entries(state, &id, prefix, buf, find)?;
+                prefix.truncate(prefix_len);
+                continue;
+            }
+            EntryMode::Blob => entry::Mode::FILE,
+            EntryMode::BlobExecutable => entry::Mode::FILE_EXECUTABLE,
+            EntryMode::Link => entry::Mode::SYMLINK,
+            EntryMode::Commit => entry::Mode::COMMIT,
+        };
+        push_entry(state, prefix.as_ref(), id, mode);
+        prefix.truncate(prefix_len);
+    }
+    Ok(())
+}
+
+/// Append an entry at `path`, which is known to sort after all existing entries as trees are ordered.
+fn push_entry(state: &mut State, path: &BStr, id: ObjectId, mode: entry::Mode) {
+    let start = state.path_backing.len();
+    state.path_backing.extend_from_slice(path);
+    let path_len = path.len().min(entry::Flags::PATH_LEN.bits() as usize) as u32;
+    state.entries.push(Entry {
+        stat: Default::default(),
+        id,
+        flags: entry::Flags::from_bits_truncate(path_len),
+        mode,
+        path: start..state.path_backing.len(),
+    });
+}
@@ -18,10 +18,20 @@ pub mod entry;
 
 mod access;
 
+pub mod init;
+
 pub mod decode;
 
 pub mod verify;
 
+pub mod write;
+
+pub mod sparse;
+
+pub mod update;
+
+pub mod conflict;
+
 /// All known versions of a git index file.
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -60,14 +70,12 @@ pub struct State {
     ///
     /// Note that on platforms that only have a precisions of a second for this time, we will treat all entries with the
     /// same timestamp as this as potentially changed, checking more thoroughly if a change actually happened.
-    #[allow(dead_code)]
     timestamp: FileTime,
     version: Version,
     entries: Vec<Entry>,
     /// A memory area keeping all index paths, in full length, independently of the index version.
     path_backing: Vec<u8>,
     /// True if one entry in the index has a special marker mode
-    #[allow(dead_code)]
     is_sparse: bool,
 
     // Extensions
@@ -76,10 +84,13 @@ pub struct State {
     resolve_undo: Option<extension::resolve_undo::Paths>,
     untracked: Option<extension::UntrackedCache>,
     fs_monitor: Option<extension::FsMonitor>,
+
+    /// The shared index the entries of a split index were merged with, to write only the changes relative to it.
+    shared_index: Option<Box<State>>,
 }
 
 pub(crate) mod util {
-    use std::convert::TryInto;
+    use std::{convert::TryInto, io};
 
     #[inline]
     pub fn var_int(data: &[u8]) -> Option<(u64, &[u8])> {
@@ -88,6 +99,42 @@ pub(crate) mod util {
         (num, data).into()
     }
 
+    /// Write `n` in the variable length encoding read by [`var_int()`], returning the amount of bytes written.
+    pub fn write_var_int(mut out: impl io::Write, mut n: u64) -> io::Result<usize> {
+        let mut buf = [0u8; 10];
+        let mut bytes_written = 1;
+        buf[buf.len() - 1] = n as u8 & 0b0111_1111;
+        for byte in buf.iter_mut().rev().skip(1) {
+            n >>= 7;
+            if n == 0 {
+                break;
+            }
+            n -= 1;
+            *byte = 0b1000_0000 | (n as u8 & 0b0111_1111);
+            bytes_written += 1;
+        }
+        out.write_all(&buf[buf.len() - bytes_written..])?;
+        Ok(bytes_written)
+    }
+
+    /// Write `stat` in the format read by [`decode::stat()`][crate::decode::stat()].
+    pub fn write_stat(mut out: impl io::Write, stat: &crate::entry::Stat) -> io::Result<()> {
+        for field in &[
+            stat.ctime.secs,
+            stat.ctime.nsecs,
+            stat.mtime.secs,
+            stat.mtime.nsecs,
+            stat.dev,
+            stat.ino,
+            stat.uid,
+            stat.gid,
+            stat.size,
+        ] {
+            out.write_all(&field.to_be_bytes())?;
+        }
+        Ok(())
+    }
+
     #[inline]
     pub fn read_u32(data: &[u8]) -> Option<(u32, &[u8])> {
         split_at_pos(data, 4).map(|(num, data)| (u32::from_be_bytes(num.try_into().unwrap()), data))
@@ -131,7 +178,7 @@ pub(crate) mod util {
 
 #[test]
 fn size_of_entry() {
-    assert_eq!(std::mem::size_of::<crate::Entry>(), 80);
+    assert_eq!(std::mem::size_of::<crate::Entry>(), 96);
 
     // the reason we have our own time is half the size.
     assert_eq!(std::mem::size_of::<crate::entry::Time>(), 8);
@@ -0,0 +1,133 @@
+//! Support for sparse indices, whose entries may be directories outside of the sparse-checkout cone.
+//!
+//! Such directory entries have the [`DIR`][entry::Mode::DIR] mode, the id of the tree they represent and a path
+//! ending in a slash. They are [skipped in the worktree][entry::Flags::SKIP_WORKTREE] and can be expanded into the
+//! entries of all files they contain on demand, similar to what `git` does when `index.sparse` is `false` or when
+//! a command needs to see all entries.
+use bstr::{BString, ByteSlice};
+
+use crate::{entry, Entry, State};
+
+pub mod expand {
+    use bstr::BString;
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            TreeNotFound { oid: git_hash::ObjectId, path: BString } {
+                display("The tree {} of sparse directory '{}' wasn't found in the object database", oid, path)
+            }
+            DecodeTree { oid: git_hash::ObjectId, err: git_object::decode::Error } {
+                display("The tree {} could not be decoded", oid)
+                source(err)
+            }
+        }
+    }
+}
+
+impl State {
+    /// Return true if at least one entry is a sparse directory, or if the index was marked as sparse.
+    pub fn is_sparse(&self) -> bool {
+        self.is_sparse
+    }
+
+    /// Return an iterator over all sparse directory entries.
+    pub fn sparse_directories(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter().filter(|e| e.mode.is_sparse())
+    }
+
+    /// Replace all sparse directory entries with the entries of the files they contain, recursively, using `find`
+    /// to obtain the trees they refer to. All new entries are marked to be skipped in the worktree.
+    ///
+    /// Afterwards the index isn't sparse anymore and will be written without the sparse directory marker.
+    /// As the tree extension counts sparse directories as single entries, it is removed and needs to be recomputed.
+    pub fn expand_sparse_directories<F>(&mut self, mut find: F) -> Result<(), expand::Error>
+    where
+        F: for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<git_object::TreeRefIter<'a>>,
+    {
+        if !self.is_sparse {
+            return Ok(());
+        }
+        let mut entries = Vec::with_capacity(self.entries.len());
+        let mut path_backing = Vec::with_capacity(self.path_backing.len());
+        let mut buf = Vec::new();
+        for entry in &self.entries {
+            let path = &self.path_backing[entry.path.clone()];
+            if entry.mode.is_sparse() {
+                // The order of tree entries in git matches the index order of their full paths, and no other entries
+                // may live within a sparse directory, so entries remain sorted.
+                expand_tree(
+                    entry.id,
+                    &mut BString::from(path),
+                    entry.flags & entry::Flags::STAGE_MASK,
+                    &mut entries,
+                    &mut path_backing,
+                    &mut buf,
+                    &mut find,
+                )?;
+            } else {
+                entries.push(Entry {
+                    stat: entry.stat,
+                    id: entry.id,
+                    flags: entry.flags,
+                    mode: entry.mode,
+                    path: push_path(&mut path_backing, path),
+                });
+            }
+        }
+        self.entries = entries;
+        self.path_backing = path_backing;
+        self.is_sparse = false;
+        self.tree = None;
+        Ok(())
+    }
+}
+
+fn expand_tree<F>(
+    id: git_hash::ObjectId,
+    dir: &mut BString,
+    stage: entry::Flags,
+    entries: &mut Vec<Entry>,
+    path_backing: &mut Vec<u8>,
+    buf: &mut Vec<u8>,
+    find: &mut F,
+) -> Result<(), expand::Error>
+where
+    F: for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<git_object::TreeRefIter<'a>>,
+{
+    let tree_entries = find(&id, buf)
+        .ok_or_else(|| expand::Error::TreeNotFound {
+            oid: id,
+            path: dir.clone(),
+        })?
+        .map(|res| res.map(|e| (e.mode, e.filename.to_owned(), e.oid.to_owned())))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| expand::Error::DecodeTree { oid: id, err })?;
+
+    for (mode, filename, oid) in tree_entries {
+        let dir_len = dir.len();
+        dir.extend_from_slice(&filename);
+        if mode.is_tree() {
+            dir.push(b'/');
+            expand_tree(oid, dir, stage, entries, path_backing, buf, find)?;
+        } else {
+            let path_len = dir.len().min(entry::Flags::PATH_LEN.bits() as usize) as u32;
+            entries.push(Entry {
+                stat: Default::default(),
+                id: oid,
+                flags: stage | entry::Flags::SKIP_WORKTREE | entry::Flags::from_bits_truncate(path_len),
+                mode: entry::Mode::from_bits_truncate(mode as u32),
+                path: push_path(path_backing, dir.as_bytes()),
+            });
+        }
+        dir.truncate(dir_len);
+    }
+    Ok(())
+}
+
+fn push_path(path_backing: &mut Vec<u8>, path: &[u8]) -> std::ops::Range<usize> {
+    let start = path_backing.len();
+    path_backing.extend_from_slice(path);
+    start..path_backing.len()
+}
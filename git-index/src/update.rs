@@ -0,0 +1,213 @@
+//! Add, update and remove entries while keeping them sorted and all extensions that depend on them up to date.
+use std::path::Path;
+
+use bstr::{BStr, ByteSlice};
+
+use crate::{entry, extension, Entry, State};
+
+/// Configuration for [`State::add_from_worktree()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If false, the executable bit of files in the worktree is ignored and the mode of existing entries is kept,
+    /// similar to `core.fileMode = false`.
+    pub trust_executable_bit: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            trust_executable_bit: cfg!(unix),
+        }
+    }
+}
+
+mod error {
+    use bstr::BString;
+    use quick_error::quick_error;
+
+    quick_error! {
+        #[derive(Debug)]
+        pub enum Error {
+            Io(err: std::io::Error) {
+                display("The file could not be read from the worktree")
+                source(err)
+                from()
+            }
+            IllformedPath { path: BString } {
+                display("The path '{}' can't be represented on this platform", path)
+            }
+            UnsupportedFileType { path: BString } {
+                display("'{}' is neither a file nor a symbolic link", path)
+            }
+            WriteBlob(err: Box<dyn std::error::Error + Send + Sync + 'static>) {
+                display("The blob could not be written")
+                source(&**err)
+            }
+        }
+    }
+}
+pub use error::Error;
+
+impl State {
+    /// Add or update the stage 0 entry at the slash-separated `path` from the file or symbolic link at `path` below
+    /// `worktree`, passing its content to `write_blob` to store it and obtain its id, just like `git add` does.
+    ///
+    /// The entry will carry the current stat information of the file so it can be detected as unchanged later.
+    /// Return the index of the entry.
+    pub fn add_from_worktree<F, E>(
+        &mut self,
+        worktree: &Path,
+        path: &BStr,
+        Options { trust_executable_bit }: Options,
+        mut write_blob: F,
+    ) -> Result<usize, Error>
+    where
+        F: FnMut(&[u8]) -> Result<git_hash::ObjectId, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let fs_path = git_features::path::from_byte_slice(path)
+            .map(|path| worktree.join(path))
+            .map_err(|_| Error::IllformedPath { path: path.into() })?;
+        let meta = std::fs::symlink_metadata(&fs_path)?;
+        let mut mode = entry::Mode::from_fs(&meta).ok_or_else(|| Error::UnsupportedFileType { path: path.into() })?;
+        if !trust_executable_bit && mode != entry::Mode::SYMLINK {
+            mode = self
+                .entry_by_path_and_stage(path, 0)
+                .map(|e| e.mode)
+                .filter(|mode| *mode == entry::Mode::FILE_EXECUTABLE)
+                .unwrap_or(entry::Mode::FILE);
+        }
+
+        let id = if mode == entry::Mode::SYMLINK {
+            let target = std::fs::read_link(&fs_path)?;
+            let target =
+                git_features::path::into_bytes(target).map_err(|_| Error::IllformedPath { path: path.into() })?;
+            write_blob(&git_features::path::convert::to_unix_separators(target))
+        } else {
+            write_blob(&std::fs::read(&fs_path)?)
+        }
+        .map_err(|err| Error::WriteBlob(Box::new(err)))?;
+
+        Ok(self.upsert_entry(path, entry::Stat::from_fs(&meta), id, mode, entry::Flags::empty()))
+    }
+
+    /// Insert a new entry at the slash-separated `path` with the given `stat`, `id`, `mode` and `flags`, or replace the
+    /// entry at the same path and stage as encoded in `flags`. Return the index of the entry.
+    ///
+    /// Like in git, adding a stage 0 entry removes all conflicting entries at `path`, along with entries that would
+    /// be in a directory at `path` or that are files in place of one of its parent directories.
+    pub fn upsert_entry(
+        &mut self,
+        path: &BStr,
+        stat: entry::Stat,
+        id: git_hash::ObjectId,
+        mode: entry::Mode,
+        flags: entry::Flags,
+    ) -> usize {
+        let stage = flags.stage();
+        if stage == 0 {
+            self.remove_conflicting_entries(path);
+        }
+        self.invalidate_path(path);
+
+        let start = self.path_backing.len();
+        self.path_backing.extend_from_slice(path);
+        let path_len = path.len().min(entry::Flags::PATH_LEN.bits() as usize) as u32;
+        let entry = Entry {
+            stat,
+            id,
+            flags: (flags - entry::Flags::PATH_LEN) | entry::Flags::from_bits_truncate(path_len),
+            mode,
+            path: start..self.path_backing.len(),
+        };
+        match self.entry_index_by_path_and_stage(path, stage) {
+            Ok(idx) => {
+                self.entries[idx] = entry;
+                idx
+            }
+            Err(idx) => {
+                self.entries.insert(idx, entry);
+                idx
+            }
+        }
+    }
+
+    /// Remove the entry at the slash-separated `path` with `stage` and return it, if it exists.
+    ///
+    /// Removed entries of a conflict are recorded in the resolve-undo extension.
+    pub fn remove_entry(&mut self, path: &BStr, stage: u32) -> Option<Entry> {
+        let idx = self.entry_index_by_path_and_stage(path, stage).ok()?;
+        self.invalidate_path(path);
+        let entry = self.entries.remove(idx);
+        self.record_resolve_undo(path, &entry);
+        Some(entry)
+    }
+
+    /// Remove all entries at the slash-separated `path`, in all stages, and return how many were removed.
+    ///
+    /// Removed entries of a conflict are recorded in the resolve-undo extension.
+    pub fn remove_path(&mut self, path: &BStr) -> usize {
+        let start = self
+            .entries
+            .partition_point(|e| &self.path_backing[e.path.clone()] < path.as_bytes());
+        let num_entries = self.entries[start..]
+            .iter()
+            .take_while(|e| &self.path_backing[e.path.clone()] == path.as_bytes())
+            .count();
+        if num_entries != 0 {
+            self.invalidate_path(path);
+            for entry in self.entries.drain(start..start + num_entries).collect::<Vec<_>>() {
+                self.record_resolve_undo(path, &entry);
+            }
+        }
+        num_entries
+    }
+
+    /// Remove all entries that conflict with a stage 0 entry at `path`, recording removed stages of a conflict
+    /// at `path` in the resolve-undo extension.
+    fn remove_conflicting_entries(&mut self, path: &BStr) {
+        let idx = self.entry_index_by_path_and_stage(path, 1).unwrap_or_else(|idx| idx);
+        while self
+            .entries
+            .get(idx)
+            .map_or(false, |e| &self.path_backing[e.path.clone()] == path.as_bytes())
+        {
+            let entry = self.entries.remove(idx);
+            self.record_resolve_undo(path, &entry);
+        }
+        let path_backing = &self.path_backing;
+        let is_parent_directory = |entry_path: &[u8]| {
+            path.len() > entry_path.len() && path.starts_with(entry_path) && path[entry_path.len()] == b'/'
+        };
+        let is_in_directory = |entry_path: &[u8]| {
+            entry_path.len() > path.len() && entry_path.starts_with(path) && entry_path[path.len()] == b'/'
+        };
+        self.entries.retain(|e| {
+            let entry_path = &path_backing[e.path.clone()];
+            !(is_parent_directory(entry_path) || is_in_directory(entry_path))
+        });
+    }
+
+    /// Remember `entry` at `path` if it is part of a conflict, so the conflict can be recreated after its resolution.
+    fn record_resolve_undo(&mut self, path: &BStr, entry: &Entry) {
+        if entry.stage() != 0 {
+            extension::resolve_undo::record(
+                self.resolve_undo.get_or_insert_with(Vec::new),
+                path,
+                entry.stage(),
+                entry.mode.bits(),
+                entry.id,
+            );
+        }
+    }
+
+    /// Invalidate all extensions caching information about `path`.
+    fn invalidate_path(&mut self, path: &BStr) {
+        if let Some(tree) = self.tree.as_mut() {
+            tree.invalidate_path(path);
+        }
+        if let Some(untracked) = self.untracked.as_mut() {
+            untracked.invalidate_path(path);
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Serialize a [`State`] into the on-disk format of the index.
+use std::io::Write;
+
+use bstr::ByteSlice;
+
+use crate::{decode::header, entry, extension, util::write_var_int, Entry, State, Version};
+
+/// The optional extensions to write along with the entries.
+///
+/// Extensions that are needed to interpret the entries, like `link` for split indices and the `sdir` marker for
+/// sparse indices, are always written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Extensions {
+    /// Write the `TREE` extension to speed up creating trees from the index, if present.
+    pub tree_cache: bool,
+    /// Write the `REUC` extension to allow undoing conflict resolutions, if present.
+    pub resolve_undo: bool,
+    /// Write the `UNTR` extension to avoid scanning unchanged directories for untracked files, if present.
+    pub untracked_cache: bool,
+    /// Write the `FSMN` extension to avoid checking entries the file system monitor knows to be unchanged, if present.
+    pub fs_monitor: bool,
+    /// Write the `EOIE` extension to allow loading extensions in parallel to the entries.
+    pub end_of_index_entry: bool,
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Extensions {
+            tree_cache: true,
+            resolve_undo: true,
+            untracked_cache: true,
+            fs_monitor: true,
+            end_of_index_entry: true,
+        }
+    }
+}
+
+/// Configuration for [`State::write_to()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The kind of hash of all object ids, which is also used for the trailing checksum.
+    pub object_hash: git_hash::Kind,
+    /// The optional extensions to write.
+    pub extensions: Extensions,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            object_hash: git_hash::Kind::Sha1,
+            extensions: Default::default(),
+        }
+    }
+}
+
+impl State {
+    /// Serialize this state to `out` in the format of an index file of the lowest version able to represent all
+    /// entries, followed by the checksum of all written bytes.
+    ///
+    /// If this state was merged from a split index and its shared index, only the changes relative to the shared index
+    /// are written, which is expected to remain unchanged next to the written file.
+    ///
+    /// Return the version that was written, along with the checksum.
+    pub fn write_to(
+        &self,
+        out: impl std::io::Write,
+        Options {
+            object_hash,
+            extensions,
+        }: Options,
+    ) -> std::io::Result<(Version, git_hash::ObjectId)> {
+        let split_link;
+        let (entries, link) = match (&self.link, &self.shared_index) {
+            (Some(link), Some(shared_index)) => {
+                let (entries, bitmaps) = extension::link::split_entries(self, shared_index);
+                split_link = extension::Link {
+                    shared_index_checksum: link.shared_index_checksum,
+                    bitmaps: Some(bitmaps),
+                };
+                (entries, Some(&split_link))
+            }
+            _ => (
+                self.entries
+                    .iter()
+                    .map(|entry| (entry, entry.path(self).as_bytes()))
+                    .collect(),
+                self.link.as_ref(),
+            ),
+        };
+        let version = self.detect_required_version(&entries);
+        let mut out = git_features::hash::Write::new(out, object_hash);
+
+        out.write_all(header::SIGNATURE)?;
+        out.write_all(&(version as u32).to_be_bytes())?;
+        out.write_all(&(entries.len() as u32).to_be_bytes())?;
+        let offset_to_extensions = header::SIZE + write_entries(&entries, &mut out, object_hash, version)?;
+
+        let mut extension_data = Vec::<(extension::Signature, Vec<u8>)>::new();
+        if let Some(link) = link {
+            let mut buf = Vec::new();
+            link.write_to(&mut buf)?;
+            extension_data.push((extension::link::SIGNATURE, buf));
+        }
+        if let Some(tree) = self.tree.as_ref().filter(|_| extensions.tree_cache) {
+            let mut buf = Vec::new();
+            tree.write_to(&mut buf)?;
+            extension_data.push((extension::tree::SIGNATURE, buf));
+        }
+        if let Some(paths) = self.resolve_undo.as_ref().filter(|_| extensions.resolve_undo) {
+            let mut buf = Vec::new();
+            extension::resolve_undo::write_to(paths, &mut buf)?;
+            extension_data.push((extension::resolve_undo::SIGNATURE, buf));
+        }
+        if let Some(untracked) = self.untracked.as_ref().filter(|_| extensions.untracked_cache) {
+            let mut buf = Vec::new();
+            untracked.write_to(&mut buf, object_hash)?;
+            extension_data.push((extension::untracked_cache::SIGNATURE, buf));
+        }
+        if let Some(fs_monitor) = self.fs_monitor.as_ref().filter(|_| extensions.fs_monitor) {
+            let mut buf = Vec::new();
+            fs_monitor.write_to(&self.fs_monitor_dirty_entries(), &mut buf)?;
+            extension_data.push((extension::fs_monitor::SIGNATURE, buf));
+        }
+        if self.is_sparse {
+            extension_data.push((extension::sparse::SIGNATURE, Vec::new()));
+        }
+
+        for (signature, data) in &extension_data {
+            out.write_all(signature)?;
+            out.write_all(&(data.len() as u32).to_be_bytes())?;
+            out.write_all(data)?;
+        }
+        if extensions.end_of_index_entry && !extension_data.is_empty() {
+            extension::end_of_index_entry::write_to(
+                &mut out,
+                offset_to_extensions as u32,
+                extension_data
+                    .iter()
+                    .map(|(signature, data)| (*signature, data.len() as u32)),
+            )?;
+        }
+
+        let checksum = out.hash.digest();
+        out.inner.write_all(checksum.as_slice())?;
+        out.inner.flush()?;
+        Ok((version, checksum))
+    }
+
+    /// V4 is kept as it was chosen for its path compression, otherwise V3 is needed only if extended flags are used.
+    fn detect_required_version(&self, entries: &[(&Entry, &[u8])]) -> Version {
+        match self.version {
+            Version::V4 => Version::V4,
+            Version::V2 | Version::V3 => {
+                if entries
+                    .iter()
+                    .any(|(entry, _)| !entry::at_rest::FlagsExtended::from_flags(entry.flags).is_empty())
+                {
+                    Version::V3
+                } else {
+                    Version::V2
+                }
+            }
+        }
+    }
+}
+
+/// Write all `entries` with their paths to `out` and return the amount of bytes written.
+fn write_entries(
+    entries: &[(&Entry, &[u8])],
+    out: &mut impl std::io::Write,
+    object_hash: git_hash::Kind,
+    version: Version,
+) -> std::io::Result<usize> {
+    let mut written = 0;
+    let mut prev_path: &[u8] = &[];
+    for &(entry, path) in entries {
+        let mut flags = entry::at_rest::Flags::from_flags(entry.flags);
+        let extended_flags = entry::at_rest::FlagsExtended::from_flags(entry.flags);
+        if !extended_flags.is_empty() {
+            flags |= entry::at_rest::Flags::EXTENDED;
+        }
+        // Longer paths are marked with the maximum length and are null-terminated.
+        let path_len = path.len().min(entry::at_rest::Flags::PATH_LEN.bits() as usize) as u16;
+
+        let stat = &entry.stat;
+        for field in &[
+            stat.ctime.secs,
+            stat.ctime.nsecs,
+            stat.mtime.secs,
+            stat.mtime.nsecs,
+            stat.dev,
+            stat.ino,
+            entry.mode.bits(),
+            stat.uid,
+            stat.gid,
+            stat.size,
+        ] {
+            out.write_all(&field.to_be_bytes())?;
+        }
+        out.write_all(entry.id.as_slice())?;
+        out.write_all(&(flags.bits() | path_len).to_be_bytes())?;
+        let mut entry_size = 10 * 4 + object_hash.len_in_bytes() + 2;
+        if !extended_flags.is_empty() {
+            out.write_all(&extended_flags.bits().to_be_bytes())?;
+            entry_size += 2;
+        }
+
+        match version {
+            Version::V4 => {
+                let common_len = prev_path.iter().zip(path).take_while(|(lhs, rhs)| lhs == rhs).count();
+                entry_size += write_var_int(&mut *out, (prev_path.len() - common_len) as u64)?;
+                out.write_all(&path[common_len..])?;
+                out.write_all(b"\0")?;
+                entry_size += path.len() - common_len + 1;
+            }
+            Version::V2 | Version::V3 => {
+                // Entries are padded with 1 to 8 null bytes to be a multiple of 8 bytes in size.
+                let padded_entry_size = (entry_size + path.len() + 8) & !7;
+                out.write_all(path)?;
+                out.write_all(&[0; 8][..padded_entry_size - entry_size - path.len()])?;
+                entry_size = padded_entry_size;
+            }
+        }
+        written += entry_size;
+        prev_path = path;
+    }
+    Ok(written)
+}
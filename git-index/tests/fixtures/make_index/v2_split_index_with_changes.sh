@@ -0,0 +1,19 @@
+#!/bin/bash
+set -eu -o pipefail
+
+export GIT_INDEX_VERSION=2
+git init -q
+git config commit.gpgsign false
+git config index.threads 1
+git config splitIndex.maxPercentChange 100
+
+touch a b c d
+git add .
+git commit -m "empty"
+
+git update-index --split-index
+
+echo modified > a
+rm c
+touch e
+git add -A
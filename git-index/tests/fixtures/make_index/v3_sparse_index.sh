@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config index.threads 1
+
+mkdir -p a d/sub
+touch a/file b d/c d/sub/e
+git add .
+git commit -q -m "init"
+
+git sparse-checkout set --cone --sparse-index a
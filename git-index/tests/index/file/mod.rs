@@ -49,7 +49,7 @@ mod init {
             assert_eq!(entry.path(&file.state), "a");
 
             let tree = file.tree().unwrap();
-            assert_eq!(tree.num_entries, 1);
+            assert_eq!(tree.num_entries, Some(1));
             assert_eq!(tree.id, hex_to_id("496d6428b9cf92981dc9495211e6e1120fb6f2ba"));
             assert!(tree.name.is_empty());
             assert!(tree.children.is_empty());
@@ -61,7 +61,7 @@ mod init {
         assert_eq!(file.version(), Version::V2);
         assert_eq!(file.entries().len(), 0);
         let tree = file.tree().unwrap();
-        assert_eq!(tree.num_entries, 0);
+        assert_eq!(tree.num_entries, Some(0));
         assert!(tree.name.is_empty());
         assert!(tree.children.is_empty());
         assert_eq!(tree.id, hex_to_id("4b825dc642cb6eb9a060e54bf8d69288fbee4904"));
@@ -83,13 +83,13 @@ mod init {
 
         let tree = file.tree().unwrap();
         assert_eq!(tree.id, hex_to_id("c9b29c3168d8e677450cc650238b23d9390801fb"));
-        assert_eq!(tree.num_entries, 6);
+        assert_eq!(tree.num_entries, Some(6));
         assert!(tree.name.is_empty());
         assert_eq!(tree.children.len(), 1);
 
         let tree = &tree.children[0];
         assert_eq!(tree.id, hex_to_id("765b32c65d38f04c4f287abda055818ec0f26912"));
-        assert_eq!(tree.num_entries, 3);
+        assert_eq!(tree.num_entries, Some(3));
         assert_eq!(tree.name.as_bstr(), "d");
     }
 
@@ -118,7 +118,37 @@ mod init {
         let file = file("v2_split_index");
         assert_eq!(file.version(), Version::V2);
 
+        let link = file.link().expect("split index");
+        assert!(
+            link.bitmaps.is_none(),
+            "bitmaps are consumed when merging with the shared index"
+        );
+        assert_eq!(file.entries().len(), 1, "all entries are in the shared index");
+        assert_eq!(file.entries()[0].path(&file), "a");
+    }
+
+    #[test]
+    fn read_v2_split_index_with_changes() {
+        let file = file("v2_split_index_with_changes");
+        assert_eq!(file.version(), Version::V2);
         assert!(file.link().is_some());
+
+        let paths_and_ids: Vec<_> = file
+            .entries()
+            .iter()
+            .map(|e| (e.path(&file).to_string(), e.id))
+            .collect();
+        let empty = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        assert_eq!(
+            paths_and_ids,
+            vec![
+                ("a".into(), hex_to_id("2e0996000b7e9019eabcad29391bf0f5c7702f0b")),
+                ("b".into(), empty),
+                ("d".into(), empty),
+                ("e".into(), empty),
+            ],
+            "'a' was replaced, 'c' was deleted and 'e' was added"
+        );
     }
 
     #[test]
@@ -147,7 +177,22 @@ mod init {
         let file = loose_file("REUC");
         assert_eq!(file.version(), Version::V2);
 
-        assert!(file.resolve_undo().is_some());
+        let paths = file.resolve_undo().unwrap();
+        assert_eq!(paths.len(), 1);
+        let path = &paths[0];
+        assert_eq!(path.name, "fi/le");
+        for (stage, expected_id) in path.stages.iter().zip(
+            [
+                "9c59e24b8393179a5d712de4f990178df5734d99",
+                "e019be006cf33489e2d0177a3837a2384eddebc5",
+                "234496b1caf2c7682b8441f9b866a7e2420d9748",
+            ]
+            .iter(),
+        ) {
+            let stage = stage.as_ref().expect("all stages are present");
+            assert_eq!(stage.mode, 0o100644);
+            assert_eq!(stage.id, hex_to_id(expected_id));
+        }
     }
 
     #[test]
@@ -163,7 +208,24 @@ mod init {
         let file = loose_file("UNTR-with-oids");
         assert_eq!(file.version(), Version::V2);
 
-        assert!(file.untracked().is_some());
+        let untracked = file.untracked().unwrap();
+        assert!(untracked.identifier.starts_with(b"Location "));
+        assert!(
+            untracked.info_exclude.as_ref().map_or(false, |file| file.id.is_null()),
+            "stat information is kept even if git didn't hash the file"
+        );
+        assert_eq!(
+            untracked.excludes_file.as_ref().map(|file| file.id),
+            Some(hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"))
+        );
+        assert_eq!(untracked.exclude_filename_per_dir, ".gitignore");
+
+        let names: Vec<_> = untracked.directories.iter().map(|dir| dir.name.to_string()).collect();
+        assert_eq!(names, ["", "done", "dthree", "dtwo"]);
+        let root = &untracked.directories[0];
+        assert_eq!(root.untracked_entries, ["three", ".gitignore", "dtwo/", "dthree/"]);
+        assert_eq!(root.sub_directories, [1, 2, 3]);
+        assert_eq!(untracked.directories[2].untracked_entries, ["three"]);
     }
 
     #[test]
@@ -172,6 +234,12 @@ mod init {
         assert_eq!(file.version(), Version::V2);
 
         assert!(file.fs_monitor().is_some());
+        assert!(
+            file.entries()
+                .iter()
+                .all(|e| !e.flags.contains(entry::Flags::FSMONITOR_VALID)),
+            "all entries are marked dirty in the fixture"
+        );
     }
 
     #[test]
@@ -209,4 +277,733 @@ mod init {
             assert_eq!(e.id, hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"))
         }
     }
+
+    #[test]
+    fn read_v3_sparse_index_with_sparse_directory() {
+        let file = file("v3_sparse_index");
+        assert_eq!(file.version(), Version::V3);
+        assert!(file.is_sparse());
+
+        assert_eq!(
+            file.entries()
+                .iter()
+                .map(|e| e.path(&file).to_string())
+                .collect::<Vec<_>>(),
+            ["a/file", "b", "d/"]
+        );
+        let dirs: Vec<_> = file.sparse_directories().collect();
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].mode, entry::Mode::DIR);
+        assert!(dirs[0].flags.contains(entry::Flags::SKIP_WORKTREE));
+        assert_eq!(dirs[0].id, hex_to_id("a1fc5c397b327e5a3e8d99ef0fe037f72c419f20"));
+    }
+
+    #[test]
+    fn sparse_directories_can_be_expanded_into_all_their_files() {
+        use git_odb::pack::FindExt;
+
+        let path = crate::fixture_path("v3_sparse_index");
+        let db = git_odb::at(path.parent().expect("in .git").join("objects")).unwrap();
+        let mut file = file("v3_sparse_index");
+        file.expand_sparse_directories(|oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0))
+            .unwrap();
+        file.verify_entries().unwrap();
+
+        assert!(!file.is_sparse());
+        assert_eq!(file.sparse_directories().count(), 0);
+        assert!(file.tree().is_none(), "the tree extension is invalidated");
+        assert_eq!(
+            file.entries()
+                .iter()
+                .map(|e| (e.path(&file).to_string(), e.flags.contains(entry::Flags::SKIP_WORKTREE)))
+                .collect::<Vec<_>>(),
+            [
+                ("a/file".into(), false),
+                ("b".into(), false),
+                ("d/c".into(), true),
+                ("d/sub/e".into(), true)
+            ]
+        );
+        for e in file.entries() {
+            assert_eq!(e.mode, entry::Mode::FILE);
+            assert_eq!(e.id, hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"));
+        }
+    }
+}
+
+mod write {
+    use std::path::{Path, PathBuf};
+
+    use filetime::FileTime;
+    use git_index::{entry, write, Version};
+
+    fn loose_file_path(name: &str) -> PathBuf {
+        git_testtools::fixture_path(Path::new("loose_index").join(name).with_extension("git-index"))
+    }
+
+    fn loose_file(name: &str) -> git_index::File {
+        git_index::File::at(loose_file_path(name), git_index::decode::Options::default()).unwrap()
+    }
+
+    fn decode(data: &[u8]) -> git_index::State {
+        git_index::State::from_bytes(data, FileTime::now(), git_index::decode::Options::default())
+            .unwrap()
+            .0
+    }
+
+    fn paths_and_ids(state: &git_index::State) -> Vec<(String, git_hash::ObjectId)> {
+        state
+            .entries()
+            .iter()
+            .map(|e| (e.path(state).to_string(), e.id))
+            .collect()
+    }
+
+    #[test]
+    fn roundtrips_byte_for_byte_without_end_of_index_entry_extension() {
+        for name in [
+            "REUC",
+            "conflicting-file",
+            "extended-flags",
+            "very-long-path",
+            "UNTR",
+            "UNTR-with-oids",
+            "FSMN",
+        ] {
+            let expected = std::fs::read(loose_file_path(name)).unwrap();
+            let file = loose_file(name);
+            let mut buf = Vec::new();
+            let (version, checksum) = file
+                .write_to(
+                    &mut buf,
+                    write::Options {
+                        extensions: write::Extensions {
+                            end_of_index_entry: false,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+            assert_eq!(version, file.version(), "{}", name);
+            assert_eq!(checksum, file.checksum, "{}", name);
+            assert_eq!(buf, expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn v4_with_end_of_index_entry_extension_can_be_read_back() {
+        let file = git_index::File::at(crate::fixture_path("v4_more_files_IEOT"), Default::default()).unwrap();
+        let mut buf = Vec::new();
+        let (version, _) = file.write_to(&mut buf, Default::default()).unwrap();
+        assert_eq!(version, Version::V4);
+
+        let state = decode(&buf);
+        assert_eq!(state.version(), Version::V4);
+        assert_eq!(paths_and_ids(&state), paths_and_ids(&file));
+        assert_eq!(state.tree().map(|t| t.id), file.tree().map(|t| t.id));
+    }
+
+    #[test]
+    fn version_is_lowered_to_v2_if_no_extended_flags_are_used() {
+        let mut file = loose_file("extended-flags");
+        assert_eq!(file.version(), Version::V3);
+        for entry in file.entries_mut() {
+            entry
+                .flags
+                .remove(entry::Flags::INTENT_TO_ADD | entry::Flags::SKIP_WORKTREE);
+        }
+
+        let mut buf = Vec::new();
+        let (version, _) = file.write_to(&mut buf, Default::default()).unwrap();
+        assert_eq!(version, Version::V2);
+
+        let state = decode(&buf);
+        assert_eq!(state.version(), Version::V2);
+        assert_eq!(paths_and_ids(&state), paths_and_ids(&file));
+    }
+
+    #[test]
+    fn split_index_is_written_as_changes_to_its_shared_index() {
+        let dir = git_testtools::scripted_fixture_repo_writable("make_index/v2_split_index_with_changes.sh").unwrap();
+        let path = dir.path().join(".git").join("index");
+        let split_index_by_git = decode(&std::fs::read(&path).unwrap());
+        let mut file = git_index::File::at(&path, Default::default()).unwrap();
+        let shared_index_checksum = file.link().expect("split index").shared_index_checksum;
+
+        let mut buf = Vec::new();
+        file.write_to(&mut buf, Default::default()).unwrap();
+        let split_index = decode(&buf);
+        assert_eq!(
+            split_index.link().map(|link| link.shared_index_checksum),
+            Some(shared_index_checksum)
+        );
+        assert_eq!(
+            paths_and_ids(&split_index),
+            paths_and_ids(&split_index_by_git),
+            "only changed entries are written, like git does"
+        );
+
+        let expected = paths_and_ids(&file);
+        file.write(Default::default(), git_lock::acquire::Fail::Immediately)
+            .unwrap();
+        let reread = git_index::File::at(&path, Default::default()).unwrap();
+        reread.verify_integrity().unwrap();
+        reread.verify_entries().unwrap();
+        assert_eq!(paths_and_ids(&reread), expected);
+        assert_eq!(
+            reread.link().map(|link| link.shared_index_checksum),
+            Some(shared_index_checksum)
+        );
+    }
+
+    #[test]
+    fn file_write_replaces_the_index_on_disk() {
+        let dir = git_testtools::tempfile::tempdir().unwrap();
+        let path = dir.path().join("index");
+        std::fs::copy(loose_file_path("REUC"), &path).unwrap();
+
+        let mut file = git_index::File::at(&path, Default::default()).unwrap();
+        file.write(Default::default(), git_lock::acquire::Fail::Immediately)
+            .unwrap();
+
+        let reread = git_index::File::at(&path, Default::default()).unwrap();
+        reread.verify_integrity().unwrap();
+        assert_eq!(
+            reread.checksum, file.checksum,
+            "the checksum is updated to match the written file"
+        );
+        assert_eq!(paths_and_ids(&reread), paths_and_ids(&file));
+        assert_eq!(
+            reread.resolve_undo().map(|paths| paths.len()),
+            file.resolve_undo().map(|paths| paths.len())
+        );
+        assert!(!path.with_extension("lock").exists(), "the lock was released");
+    }
+}
+
+mod untracked_cache {
+    use std::path::Path;
+
+    use bstr::{BString, ByteSlice};
+    use git_index::{entry, extension::UntrackedCache};
+
+    fn scan(cache: &mut UntrackedCache, worktree: &Path, dir: &str, untracked: &[&str]) -> usize {
+        let index = cache.directory_index_or_insert(dir.as_bytes().as_bstr());
+        let stat = entry::Stat::from_fs(&std::fs::symlink_metadata(worktree.join(dir)).unwrap());
+        cache.set_directory_scanned(index, stat, None, untracked.iter().map(|e| BString::from(*e)).collect());
+        index
+    }
+
+    fn invalidate_outdated(cache: &mut UntrackedCache, worktree: &Path) -> usize {
+        cache
+            .invalidate_outdated(worktree, None, None, git_hash::Kind::Sha1)
+            .unwrap()
+    }
+
+    #[test]
+    fn directories_are_invalidated_if_their_stat_or_exclude_file_changes() {
+        let dir = git_testtools::tempfile::tempdir().unwrap();
+        let worktree = dir.path();
+        std::fs::create_dir(worktree.join("sub")).unwrap();
+        std::fs::write(worktree.join("sub").join("untracked"), b"").unwrap();
+
+        let identifier = UntrackedCache::identifier_for(worktree, "Linux");
+        assert!(identifier.ends_with(b"\0"), "git keeps a trailing null byte");
+        let mut cache = UntrackedCache::new(identifier.clone(), 0, ".gitignore");
+        assert!(cache.is_usable_for(&identifier, 0, b".gitignore"));
+        assert!(!cache.is_usable_for(&identifier, 1, b".gitignore"));
+
+        let root = scan(&mut cache, worktree, "", &["sub/"]);
+        let sub = scan(&mut cache, worktree, "sub", &["untracked"]);
+        assert_eq!(root, 0, "the root directory always exists");
+        assert_eq!(cache.directory_index_or_insert(b"sub/".as_bstr()), sub);
+        assert_eq!(cache.directories[root].sub_directories, vec![sub]);
+        assert_eq!(invalidate_outdated(&mut cache, worktree), 0, "nothing changed");
+
+        filetime::set_file_mtime(
+            worktree.join("sub"),
+            filetime::FileTime::from_unix_time(1_000_000_000, 0),
+        )
+        .unwrap();
+        assert_eq!(invalidate_outdated(&mut cache, worktree), 1);
+        assert!(cache.directories[root].is_valid());
+        assert!(!cache.directories[sub].is_valid());
+        assert!(cache.directories[sub].untracked_entries.is_empty());
+
+        scan(&mut cache, worktree, "sub", &["untracked"]);
+        std::fs::write(worktree.join(".gitignore"), b"untracked\n").unwrap();
+        assert_eq!(
+            invalidate_outdated(&mut cache, worktree),
+            2,
+            "a changed exclude file affects all sub-directories"
+        );
+        assert!(cache.directories.iter().all(|dir| !dir.is_valid()));
+    }
+
+    #[test]
+    fn changed_global_exclude_files_invalidate_everything() {
+        let dir = git_testtools::tempfile::tempdir().unwrap();
+        let worktree = dir.path();
+        let excludes_file = worktree.join("excludes");
+        std::fs::write(&excludes_file, b"").unwrap();
+
+        let mut cache = UntrackedCache::new("identifier", 0, ".gitignore");
+        let check = |cache: &mut UntrackedCache| {
+            cache
+                .invalidate_outdated(worktree, None, Some(&excludes_file), git_hash::Kind::Sha1)
+                .unwrap()
+        };
+        assert_eq!(check(&mut cache), 1, "the excludes file is new");
+        assert_eq!(
+            cache.excludes_file.as_ref().map(|file| file.id),
+            Some(git_testtools::hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"))
+        );
+
+        scan(&mut cache, worktree, "", &["excludes"]);
+        assert_eq!(check(&mut cache), 0);
+
+        std::fs::write(&excludes_file, b"excludes\n").unwrap();
+        assert_eq!(check(&mut cache), 1);
+        assert!(!cache.directories[0].is_valid());
+    }
+
+    #[test]
+    fn updates_are_written_and_read_back() {
+        let mut file = git_index::File::at(
+            git_testtools::fixture_path("loose_index/UNTR-with-oids.git-index"),
+            Default::default(),
+        )
+        .unwrap();
+        let cache = file.untracked_mut().expect("present");
+        let index = cache.directory_index_or_insert(b"dtwo/new".as_bstr());
+        cache.set_directory_scanned(index, entry::Stat::default(), None, vec!["file".into()]);
+
+        let mut buf = Vec::new();
+        file.write_to(&mut buf, Default::default()).unwrap();
+        let (state, _) = git_index::State::from_bytes(&buf, filetime::FileTime::now(), Default::default()).unwrap();
+        let cache = state.untracked().expect("written");
+        let names: Vec<_> = cache.directories.iter().map(|dir| dir.name.to_string()).collect();
+        assert_eq!(
+            names,
+            ["", "done", "dthree", "dtwo", "new"],
+            "directories are written depth-first"
+        );
+        let new = &cache.directories[4];
+        assert!(new.is_valid());
+        assert_eq!(new.untracked_entries, ["file"]);
+        assert_eq!(cache.directories[3].sub_directories, vec![4]);
+    }
+}
+
+mod fs_monitor {
+    use bstr::BString;
+    use git_index::{
+        entry,
+        extension::fs_monitor::{query_hook, Changed, Outcome, Token},
+    };
+
+    fn file_with_all_entries_valid() -> git_index::File {
+        let mut file = git_index::File::at(
+            git_testtools::fixture_path("loose_index/FSMN.git-index"),
+            Default::default(),
+        )
+        .unwrap();
+        for entry in file.entries_mut() {
+            entry.flags.insert(entry::Flags::FSMONITOR_VALID);
+        }
+        file
+    }
+
+    fn valid_paths(file: &git_index::File) -> Vec<String> {
+        file.entries()
+            .iter()
+            .filter(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+            .map(|e| e.path(file).to_string())
+            .collect()
+    }
+
+    #[test]
+    fn changed_files_and_directories_are_marked_dirty() {
+        let mut file = file_with_all_entries_valid();
+        let token = Token::V2 { token: "next".into() };
+        file.apply_fs_monitor_changes(Outcome {
+            token: token.clone(),
+            changed: Changed::Paths(vec!["modified".into(), "dir1".into(), "dir2/modified".into()]),
+        });
+        assert_eq!(
+            valid_paths(&file),
+            ["dir2/tracked", "tracked"],
+            "paths not in the index are assumed to be directories"
+        );
+        assert_eq!(file.fs_monitor().map(|fsm| &fsm.token), Some(&token));
+
+        let mut buf = Vec::new();
+        file.write_to(&mut buf, Default::default()).unwrap();
+        let (state, _) = git_index::State::from_bytes(&buf, filetime::FileTime::now(), Default::default()).unwrap();
+        assert_eq!(
+            state
+                .entries()
+                .iter()
+                .filter(|e| e.flags.contains(entry::Flags::FSMONITOR_VALID))
+                .map(|e| e.path(&state).to_string())
+                .collect::<Vec<_>>(),
+            ["dir2/tracked", "tracked"],
+            "the dirty entries are written"
+        );
+    }
+
+    #[test]
+    fn everything_is_dirty_if_the_monitor_says_so() {
+        let mut file = file_with_all_entries_valid();
+        file.apply_fs_monitor_changes(Outcome {
+            token: Token::V1 { nanos_since_1970: 42 },
+            changed: Changed::All,
+        });
+        assert!(valid_paths(&file).is_empty());
+    }
+
+    #[test]
+    fn hooks_receive_the_version_and_token_and_report_changes() {
+        let dir = git_testtools::tempfile::tempdir().unwrap();
+        let hook = dir.path().join("hook.sh");
+        std::fs::write(&hook, "printf 'new-token\\0%s\\0%s\\0' \"$1\" \"$2\"\n").unwrap();
+        let hook = format!("sh '{}'", hook.display());
+
+        let outcome = query_hook(hook.as_ref(), dir.path(), &Token::V2 { token: "old".into() }).unwrap();
+        assert_eq!(
+            outcome,
+            Outcome {
+                token: Token::V2 {
+                    token: "new-token".into()
+                },
+                changed: Changed::Paths(vec![BString::from("2"), "old".into()])
+            }
+        );
+
+        let outcome = query_hook(hook.as_ref(), dir.path(), &Token::V1 { nanos_since_1970: 5 }).unwrap();
+        assert!(matches!(outcome.token, Token::V1 { nanos_since_1970 } if nanos_since_1970 > 5));
+        assert_eq!(
+            outcome.changed,
+            Changed::Paths(vec!["new-token".into(), "1".into(), "5".into()]),
+            "version 1 hooks don't print a token"
+        );
+
+        assert!(query_hook("exit 1".as_ref(), dir.path(), &Token::V1 { nanos_since_1970: 5 }).is_err());
+    }
+}
+
+mod write_tree {
+    use git_odb::Write;
+    use git_testtools::hex_to_id;
+
+    fn file(name: &str) -> git_index::File {
+        git_index::File::at(crate::fixture_path(name), git_index::decode::Options::default()).unwrap()
+    }
+
+    fn write_tree(file: &mut git_index::File) -> (git_hash::ObjectId, usize) {
+        let sink = git_odb::sink(git_hash::Kind::Sha1);
+        let mut num_written = 0;
+        let id = file
+            .write_tree(|tree| {
+                num_written += 1;
+                sink.write(tree)
+            })
+            .unwrap();
+        (id, num_written)
+    }
+
+    #[test]
+    fn only_invalidated_trees_are_written() {
+        let mut file = file("v2_more_files");
+        let expected_id = hex_to_id("c9b29c3168d8e677450cc650238b23d9390801fb");
+        assert_eq!(write_tree(&mut file), (expected_id, 0), "all cached trees are valid");
+
+        file.tree_mut().unwrap().invalidate_path("a".into());
+        assert_eq!(file.tree().unwrap().num_entries, None);
+        assert_eq!(file.tree().unwrap().children[0].num_entries, Some(3));
+        assert_eq!(
+            write_tree(&mut file),
+            (expected_id, 1),
+            "only the root tree was invalid"
+        );
+
+        file.tree_mut().unwrap().invalidate_path("d/b".into());
+        assert_eq!(file.tree().unwrap().children[0].num_entries, None);
+        assert_eq!(write_tree(&mut file), (expected_id, 2));
+
+        let tree = file.tree().unwrap();
+        assert_eq!(tree.num_entries, Some(6));
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].num_entries, Some(3));
+        assert_eq!(
+            tree.children[0].id,
+            hex_to_id("765b32c65d38f04c4f287abda055818ec0f26912")
+        );
+    }
+
+    #[test]
+    fn invalidated_trees_are_written_and_read_back_without_their_id() {
+        let mut file = file("v2_more_files");
+        file.tree_mut().unwrap().invalidate_path("d/a".into());
+
+        let mut buf = Vec::new();
+        file.write_to(&mut buf, Default::default()).unwrap();
+        let (mut state, _) = git_index::State::from_bytes(&buf, filetime::FileTime::now(), Default::default()).unwrap();
+        let tree = state.tree().unwrap();
+        assert_eq!(tree.num_entries, None);
+        assert_eq!(tree.children[0].num_entries, None);
+        assert!(tree.children[0].id.is_null());
+
+        let sink = git_odb::sink(git_hash::Kind::Sha1);
+        assert_eq!(
+            state.write_tree(|tree| sink.write(tree)).unwrap(),
+            hex_to_id("c9b29c3168d8e677450cc650238b23d9390801fb")
+        );
+    }
+}
+
+mod from_tree {
+    use git_odb::FindExt;
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn entries_match_those_written_by_git() {
+        let index_path = crate::fixture_path("v2_more_files");
+        let expected = git_index::File::at(&index_path, git_index::decode::Options::default()).unwrap();
+        let odb = git_odb::at(index_path.parent().unwrap().join("objects")).unwrap();
+
+        let state = git_index::State::from_tree(&hex_to_id("c9b29c3168d8e677450cc650238b23d9390801fb"), |id, buf| {
+            odb.find_tree_iter(id, buf).ok()
+        })
+        .unwrap();
+
+        assert_eq!(state.version(), git_index::Version::V2);
+        assert_eq!(state.entries().len(), expected.entries().len());
+        for (actual, expected_entry) in state.entries().iter().zip(expected.entries()) {
+            assert_eq!(actual.path(&state), expected_entry.path(&expected));
+            assert_eq!(actual.id, expected_entry.id);
+            assert_eq!(actual.mode, expected_entry.mode);
+            assert_eq!(actual.flags, expected_entry.flags, "path length and stage are set");
+            assert_eq!(actual.stat, Default::default(), "nothing was checked out yet");
+        }
+        assert!(state.tree().is_none());
+    }
+
+    #[test]
+    fn missing_trees_are_an_error() {
+        let err = git_index::State::from_tree(&git_hash::Kind::Sha1.null(), |_, _| None).unwrap_err();
+        assert!(matches!(err, git_index::init::from_tree::Error::NotFound { .. }));
+    }
+}
+
+mod update {
+    use git_index::{entry, update};
+    use git_odb::Write;
+    use git_testtools::hex_to_id;
+
+    fn file(name: &str) -> git_index::File {
+        git_index::File::at(crate::fixture_path(name), git_index::decode::Options::default()).unwrap()
+    }
+
+    fn add(file: &mut git_index::File, worktree: &std::path::Path, path: &str) -> usize {
+        let sink = git_odb::sink(git_hash::Kind::Sha1);
+        file.add_from_worktree(worktree, path.into(), update::Options::default(), |data| {
+            sink.write_buf(git_object::Kind::Blob, data)
+        })
+        .unwrap()
+    }
+
+    fn paths(file: &git_index::File) -> Vec<String> {
+        file.entries().iter().map(|e| e.path(file).to_string()).collect()
+    }
+
+    #[test]
+    fn files_are_added_or_updated_in_order_with_their_stat_information() {
+        let mut file = file("v2_more_files");
+        let worktree = git_testtools::tempfile::tempdir().unwrap();
+        std::fs::write(worktree.path().join("a"), "hello").unwrap();
+        std::fs::write(worktree.path().join("ba"), "").unwrap();
+
+        assert_eq!(add(&mut file, worktree.path(), "a"), 0, "existing entries are updated");
+        assert_eq!(
+            add(&mut file, worktree.path(), "ba"),
+            2,
+            "new entries are inserted in order"
+        );
+        file.verify_entries().unwrap();
+        assert_eq!(paths(&file), ["a", "b", "ba", "c", "d/a", "d/b", "d/c"]);
+
+        let entry = &file.entries()[0];
+        assert_eq!(entry.id, hex_to_id("b6fc4c620b67d95f953a5c1c1230aaab5db5a1b0"));
+        assert_eq!(entry.mode, entry::Mode::FILE);
+        assert_eq!(entry.stat.size, 5);
+        assert_eq!(
+            entry.stat,
+            entry::Stat::from_fs(&std::fs::symlink_metadata(worktree.path().join("a")).unwrap())
+        );
+        assert_eq!(file.tree().unwrap().num_entries, None, "the tree is invalidated");
+        assert_eq!(file.tree().unwrap().children[0].num_entries, Some(3));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn executable_files_and_symlinks_are_detected() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut file = file("v2_more_files");
+        let worktree = git_testtools::tempfile::tempdir().unwrap();
+        let exe = worktree.path().join("exe");
+        std::fs::write(&exe, "").unwrap();
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("a", worktree.path().join("link")).unwrap();
+
+        let idx = add(&mut file, worktree.path(), "exe");
+        assert_eq!(file.entries()[idx].mode, entry::Mode::FILE_EXECUTABLE);
+        let idx = add(&mut file, worktree.path(), "link");
+        assert_eq!(file.entries()[idx].mode, entry::Mode::SYMLINK);
+        assert_eq!(
+            file.entries()[idx].id,
+            hex_to_id("2e65efe2a145dda7ee51d1741299f848e5bf752e"),
+            "the link target is the content"
+        );
+    }
+
+    #[test]
+    fn adding_entries_removes_conflicting_stages_and_directories() {
+        let mut file = file("v2_more_files");
+        let id = hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+        let stage = |n: u32| entry::Flags::from_bits_truncate(n << 12);
+        for n in 1..=3 {
+            file.upsert_entry("e".into(), Default::default(), id, entry::Mode::FILE, stage(n));
+        }
+        assert_eq!(paths(&file), ["a", "b", "c", "d/a", "d/b", "d/c", "e", "e", "e"]);
+        file.verify_entries().unwrap();
+
+        file.upsert_entry(
+            "e".into(),
+            Default::default(),
+            id,
+            entry::Mode::FILE,
+            entry::Flags::empty(),
+        );
+        assert_eq!(paths(&file), ["a", "b", "c", "d/a", "d/b", "d/c", "e"]);
+
+        file.upsert_entry(
+            "d".into(),
+            Default::default(),
+            id,
+            entry::Mode::FILE,
+            entry::Flags::empty(),
+        );
+        assert_eq!(paths(&file), ["a", "b", "c", "d", "e"], "files replace directories");
+
+        file.upsert_entry(
+            "a/b".into(),
+            Default::default(),
+            id,
+            entry::Mode::FILE,
+            entry::Flags::empty(),
+        );
+        assert_eq!(paths(&file), ["a/b", "b", "c", "d", "e"], "directories replace files");
+        file.verify_entries().unwrap();
+    }
+
+    #[test]
+    fn entries_can_be_removed_by_path_and_stage() {
+        let mut file = file("v2_more_files");
+        assert!(file.remove_entry("d/a".into(), 1).is_none());
+        assert_eq!(file.remove_entry("d/a".into(), 0).unwrap().path(&file), "d/a");
+        assert_eq!(file.remove_path("d/b".into()), 1);
+        assert_eq!(file.remove_path("d/b".into()), 0);
+        assert_eq!(paths(&file), ["a", "b", "c", "d/c"]);
+        assert_eq!(file.tree().unwrap().children[0].num_entries, None);
+
+        let sink = git_odb::sink(git_hash::Kind::Sha1);
+        let id = file.write_tree(|tree| sink.write(tree)).unwrap();
+        assert_ne!(id, hex_to_id("c9b29c3168d8e677450cc650238b23d9390801fb"));
+        assert_eq!(file.tree().unwrap().num_entries, Some(4));
+    }
+}
+
+mod conflict {
+    use bstr::BString;
+    use git_index::{conflict::Resolution, entry};
+
+    fn conflicting_file() -> (git_index::File, BString) {
+        let file = git_index::File::at(
+            git_testtools::fixture_path("loose_index/conflicting-file.git-index"),
+            Default::default(),
+        )
+        .unwrap();
+        let path = file.conflicts().next().expect("one conflict").path.to_owned();
+        (file, path)
+    }
+
+    #[test]
+    fn conflicts_provide_access_to_all_stages() {
+        let (file, path) = conflicting_file();
+        assert!(file.has_conflicts());
+        assert_eq!(file.conflicts().count(), 1);
+
+        let conflict = file.conflict(path.as_ref()).unwrap();
+        for (entry, stage) in [conflict.base, conflict.ours, conflict.theirs].iter().zip(1..) {
+            let entry = entry.expect("all stages are present");
+            assert_eq!(entry.stage(), stage);
+            assert_eq!(entry.path(&file), path);
+        }
+        assert!(file.conflict("missing".into()).is_none());
+    }
+
+    #[test]
+    fn resolutions_collapse_stages_and_can_be_undone() {
+        let (mut file, path) = conflicting_file();
+        let theirs_id = file.conflict(path.as_ref()).unwrap().theirs.unwrap().id;
+
+        let idx = file
+            .resolve_conflict(path.as_ref(), Resolution::Theirs)
+            .unwrap()
+            .expect("an entry was added");
+        assert!(!file.has_conflicts());
+        assert_eq!(file.entries().len(), 1);
+        assert_eq!(file.entries()[idx].id, theirs_id);
+        assert_eq!(file.entries()[idx].stage(), 0);
+        assert!(file.resolve_conflict(path.as_ref(), Resolution::Ours).is_err());
+
+        let recorded = file.resolve_undo().expect("stages were recorded");
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].name, path);
+        assert!(recorded[0].stages.iter().all(Option::is_some));
+
+        let mut buf = Vec::new();
+        file.write_to(&mut buf, Default::default()).unwrap();
+        let (mut state, _) = git_index::State::from_bytes(&buf, filetime::FileTime::now(), Default::default()).unwrap();
+        assert!(state.unresolve_conflict(path.as_ref()));
+        assert!(!state.unresolve_conflict(path.as_ref()), "the record is consumed");
+        state.verify_entries().unwrap();
+        assert_eq!(state.entries().len(), 3);
+        assert_eq!(state.conflict(path.as_ref()).unwrap().theirs.unwrap().id, theirs_id);
+    }
+
+    #[test]
+    fn conflicts_can_be_resolved_by_removal_or_with_merged_content() {
+        let (mut file, path) = conflicting_file();
+        assert_eq!(file.resolve_conflict(path.as_ref(), Resolution::Remove).unwrap(), None);
+        assert!(file.entries().is_empty());
+
+        let (mut file, path) = conflicting_file();
+        let id = git_hash::ObjectId::null(git_hash::Kind::Sha1);
+        let idx = file
+            .resolve_conflict(
+                path.as_ref(),
+                Resolution::Merged {
+                    id,
+                    mode: entry::Mode::FILE_EXECUTABLE,
+                },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(file.entries()[idx].mode, entry::Mode::FILE_EXECUTABLE);
+        assert_eq!(file.entries()[idx].id, id);
+    }
 }
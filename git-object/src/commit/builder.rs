@@ -0,0 +1,213 @@
+use bstr::{BString, ByteSlice};
+use git_hash::ObjectId;
+use quick_error::quick_error;
+use smallvec::SmallVec;
+
+use crate::{Commit, Tag, WriteTo};
+
+/// The name of the header holding the cryptographic signature of a commit.
+pub const SIGNATURE_HEADER: &str = "gpgsig";
+
+/// The name of the header holding a tag embedded into a merge commit.
+pub const MERGETAG_HEADER: &str = "mergetag";
+
+/// Header names which are written by the commit itself and can't be used as extra headers.
+const RESERVED_HEADER_NAMES: &[&str] = &["tree", "parent", "author", "committer", "encoding"];
+
+quick_error! {
+    /// The error returned by [`Builder::build()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        MissingAuthor {
+            display("The author of the commit must be set")
+        }
+        MissingCommitter {
+            display("The committer of the commit must be set")
+        }
+        HashKindMismatch { tree: git_hash::Kind, parent: ObjectId } {
+            display("Parent {} doesn't have the same hash kind as the tree, which is {:?}", parent, tree)
+        }
+        DuplicateParent { id: ObjectId } {
+            display("Parent {} was specified more than once", id)
+        }
+        InvalidHeaderName { name: BString } {
+            display("Header name {:?} must not be empty or contain whitespace", name)
+        }
+        ReservedHeaderName { name: BString } {
+            display("Header name {:?} is reserved for fields of the commit itself", name)
+        }
+        EmptyHeaderValue { name: BString } {
+            display("The value of header {:?} must not be empty", name)
+        }
+        InvalidMergeTag(err: std::io::Error) {
+            display("A merge tag could not be serialized")
+            source(err)
+        }
+    }
+}
+
+/// A builder for [`Commit`] objects, validating all fields once [built][Builder::build()].
+///
+/// [Merge tags][Builder::mergetag()] are written first, followed by extra headers in the order they are added, with multi-line
+/// values being folded on serialization, and the [signature][Builder::signature()] if one was set, which matches the
+/// placement git uses.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Builder {
+    tree: ObjectId,
+    parents: SmallVec<[ObjectId; 1]>,
+    author: Option<git_actor::Signature>,
+    committer: Option<git_actor::Signature>,
+    encoding: Option<BString>,
+    message: BString,
+    mergetags: Vec<Tag>,
+    extra_headers: Vec<(BString, BString)>,
+    signature: Option<BString>,
+}
+
+impl Commit {
+    /// Start building a new commit pointing to `tree`.
+    pub fn builder(tree: impl Into<ObjectId>) -> Builder {
+        Builder::new(tree)
+    }
+}
+
+/// Lifecycle
+impl Builder {
+    /// Create a new instance pointing to `tree`, without any parents.
+    pub fn new(tree: impl Into<ObjectId>) -> Self {
+        Builder {
+            tree: tree.into(),
+            parents: Default::default(),
+            author: None,
+            committer: None,
+            encoding: None,
+            message: Default::default(),
+            mergetags: Vec::new(),
+            extra_headers: Vec::new(),
+            signature: None,
+        }
+    }
+
+    /// Validate all fields and produce the commit.
+    pub fn build(self) -> Result<Commit, Error> {
+        let tree_kind = self.tree.kind();
+        for (idx, parent) in self.parents.iter().enumerate() {
+            if parent.kind() != tree_kind {
+                return Err(Error::HashKindMismatch {
+                    tree: tree_kind,
+                    parent: *parent,
+                });
+            }
+            if self.parents[..idx].contains(parent) {
+                return Err(Error::DuplicateParent { id: *parent });
+            }
+        }
+        for (name, value) in &self.extra_headers {
+            validate_header(name, value)?;
+        }
+
+        let mut extra_headers = Vec::with_capacity(self.mergetags.len() + self.extra_headers.len() + 1);
+        for tag in &self.mergetags {
+            let mut value = Vec::with_capacity(tag.size());
+            tag.write_to(&mut value).map_err(Error::InvalidMergeTag)?;
+            if value.last() == Some(&b'\n') {
+                value.pop();
+            }
+            extra_headers.push((MERGETAG_HEADER.into(), value.into()));
+        }
+        extra_headers.extend(self.extra_headers);
+        if let Some(signature) = self.signature {
+            extra_headers.push((SIGNATURE_HEADER.into(), signature));
+        }
+        Ok(Commit {
+            tree: self.tree,
+            parents: self.parents,
+            author: self.author.ok_or(Error::MissingAuthor)?,
+            committer: self.committer.ok_or(Error::MissingCommitter)?,
+            encoding: self.encoding,
+            message: self.message,
+            extra_headers,
+        })
+    }
+}
+
+/// Configuration
+impl Builder {
+    /// Add `id` as another parent of the commit. The first parent added is the first parent of the commit.
+    pub fn parent(mut self, id: impl Into<ObjectId>) -> Self {
+        self.parents.push(id.into());
+        self
+    }
+
+    /// Add all `ids` as parents of the commit, in order.
+    pub fn parents(mut self, ids: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.parents.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set the author of the commit.
+    pub fn author(mut self, author: git_actor::Signature) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    /// Set the committer of the commit.
+    pub fn committer(mut self, committer: git_actor::Signature) -> Self {
+        self.committer = Some(committer);
+        self
+    }
+
+    /// Set the encoding of the message, which is otherwise assumed to be UTF-8.
+    pub fn encoding(mut self, encoding: impl Into<BString>) -> Self {
+        self.encoding = Some(encoding.into());
+        self
+    }
+
+    /// Set the commit message.
+    pub fn message(mut self, message: impl Into<BString>) -> Self {
+        self.message = message.into();
+        self
+    }
+
+    /// Add an extra header with `name` and `value`, which may span multiple lines, like `mergetag`.
+    ///
+    /// Use [`signature()`][Builder::signature()] to set the `gpgsig` header instead.
+    pub fn extra_header(mut self, name: impl Into<BString>, value: impl Into<BString>) -> Self {
+        self.extra_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Embed `tag` into the commit, as done by git when merging a signed tag so its signature can be verified later.
+    ///
+    /// Use [`ExtraHeaders::mergetags()`][crate::commit::ExtraHeaders::mergetags()] to obtain embedded tags from parsed commits.
+    pub fn mergetag(mut self, tag: Tag) -> Self {
+        self.mergetags.push(tag);
+        self
+    }
+
+    /// Set the cryptographic `signature` as produced by gpg, ssh or x509 tooling, to be stored in the `gpgsig` header.
+    ///
+    /// A single trailing newline, as commonly produced by signing tools, is removed.
+    pub fn signature(mut self, signature: impl Into<BString>) -> Self {
+        let mut signature = signature.into();
+        if signature.last() == Some(&b'\n') {
+            signature.pop();
+        }
+        self.signature = Some(signature);
+        self
+    }
+}
+
+fn validate_header(name: &BString, value: &BString) -> Result<(), Error> {
+    if name.is_empty() || name.find_byteset(b" \t\n").is_some() {
+        return Err(Error::InvalidHeaderName { name: name.clone() });
+    }
+    if RESERVED_HEADER_NAMES.iter().any(|reserved| name == reserved) {
+        return Err(Error::ReservedHeaderName { name: name.clone() });
+    }
+    if value.is_empty() {
+        return Err(Error::EmptyHeaderValue { name: name.clone() });
+    }
+    Ok(())
+}
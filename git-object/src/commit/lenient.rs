@@ -0,0 +1,222 @@
+//! Decoding of commits found in real-world repositories that don't pass the strict parser, for example due to broken
+//! timezones, missing committers or illegal bytes in names.
+//!
+//! Instead of failing, the lenient decoder recovers as much information as possible and records a [`Warning`] for
+//! each problem it worked around. Only commits without a valid `tree` can't be decoded at all.
+use std::borrow::Cow;
+
+use bstr::{BStr, BString, ByteSlice};
+use smallvec::SmallVec;
+
+use crate::{parse, CommitRef};
+
+/// A problem encountered and worked around while decoding a commit leniently.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+#[allow(missing_docs)]
+pub enum Warning {
+    /// A `parent` header didn't contain a valid hash and was ignored.
+    InvalidParent { value: BString },
+    /// A signature of the header `field` wasn't of the form `name <email> time`, and the whole `value` was used as name.
+    InvalidSignature { field: &'static str, value: BString },
+    /// The time of the signature in header `field` couldn't be parsed and was set to the unix epoch.
+    InvalidTime { field: &'static str, value: BString },
+    /// The timezone of the signature in header `field` couldn't be parsed and was set to UTC.
+    InvalidTimezone { field: &'static str, value: BString },
+    /// There was no author, so the committer was used in its place.
+    MissingAuthor,
+    /// There was no committer, so the author was used in its place.
+    MissingCommitter,
+    /// A header which may only appear once was seen again and ignored.
+    DuplicateHeader { name: &'static str },
+    /// The headers weren't followed by an empty line, so the message is empty.
+    MissingMessage,
+}
+
+impl<'a> CommitRef<'a> {
+    /// Deserialize a commit from `data` like [`from_bytes()`][CommitRef::from_bytes()], but recover from all errors
+    /// but a missing or invalid `tree`, returning the commit along with [warnings][Warning] about everything
+    /// that was worked around.
+    ///
+    /// Note that commits decoded this way aren't guaranteed to serialize back into `data`.
+    pub fn from_bytes_lenient(data: &'a [u8]) -> Result<(CommitRef<'a>, Vec<Warning>), crate::decode::Error> {
+        decode(data).ok_or_else(crate::decode::empty_error)
+    }
+}
+
+fn decode(data: &[u8]) -> Option<(CommitRef<'_>, Vec<Warning>)> {
+    let mut warnings = Vec::new();
+    let mut tree = None;
+    let mut parents = SmallVec::new();
+    let mut author = None;
+    let mut committer = None;
+    let mut encoding = None;
+    let mut extra_headers: Vec<(&BStr, Cow<'_, BStr>)> = Vec::new();
+    let mut message = None;
+
+    let mut rest = data;
+    while !rest.is_empty() {
+        let line_end = rest.find_byte(b'\n').map(|pos| pos + 1).unwrap_or(rest.len());
+        let (line, next) = rest.split_at(line_end);
+        let line = line.strip_suffix(b"\n").unwrap_or(line);
+        rest = next;
+        if line.is_empty() {
+            message = Some(rest.as_bstr());
+            break;
+        }
+        let (name, value) = match line.find_byte(b' ') {
+            Some(pos) => (&line[..pos], &line[pos + 1..]),
+            None => (line, &b""[..]),
+        };
+        match name {
+            b"tree" if tree.is_some() => warnings.push(Warning::DuplicateHeader { name: "tree" }),
+            b"tree" => tree = Some(hash(value)?),
+            b"parent" => match hash(value) {
+                Some(id) => parents.push(id),
+                None => warnings.push(Warning::InvalidParent { value: value.into() }),
+            },
+            b"author" if author.is_some() => warnings.push(Warning::DuplicateHeader { name: "author" }),
+            b"author" => author = Some(signature("author", value, &mut warnings)),
+            b"committer" if committer.is_some() => warnings.push(Warning::DuplicateHeader { name: "committer" }),
+            b"committer" => committer = Some(signature("committer", value, &mut warnings)),
+            b"encoding" if encoding.is_some() => warnings.push(Warning::DuplicateHeader { name: "encoding" }),
+            b"encoding" => encoding = Some(value.as_bstr()),
+            _ => {
+                let mut continuation_lines = 0;
+                while rest.first() == Some(&b' ') {
+                    let line_end = rest.find_byte(b'\n').map(|pos| pos + 1).unwrap_or(rest.len());
+                    continuation_lines += line_end;
+                    rest = &rest[line_end..];
+                }
+                let value = if continuation_lines == 0 {
+                    Cow::Borrowed(value.as_bstr())
+                } else {
+                    let folded = &next[..continuation_lines];
+                    let mut unfolded = BString::from(value);
+                    for line in folded.lines() {
+                        unfolded.push(b'\n');
+                        unfolded.extend_from_slice(&line[1..]);
+                    }
+                    Cow::Owned(unfolded)
+                };
+                extra_headers.push((name.as_bstr(), value));
+            }
+        }
+    }
+
+    let (author, committer) = match (author, committer) {
+        (Some(author), Some(committer)) => (author, committer),
+        (Some(author), None) => {
+            warnings.push(Warning::MissingCommitter);
+            (author, author)
+        }
+        (None, Some(committer)) => {
+            warnings.push(Warning::MissingAuthor);
+            (committer, committer)
+        }
+        (None, None) => {
+            warnings.push(Warning::MissingAuthor);
+            warnings.push(Warning::MissingCommitter);
+            Default::default()
+        }
+    };
+    let message = message.unwrap_or_else(|| {
+        warnings.push(Warning::MissingMessage);
+        b"".as_bstr()
+    });
+
+    Some((
+        CommitRef {
+            tree: tree?,
+            parents,
+            author,
+            committer,
+            encoding,
+            message,
+            extra_headers,
+        },
+        warnings,
+    ))
+}
+
+fn hash(value: &[u8]) -> Option<&BStr> {
+    match parse::hex_hash::<()>(value) {
+        Ok(([], hex)) => Some(hex),
+        _ => None,
+    }
+}
+
+/// Parse `name <email> seconds timezone`, finding the email from the back to allow names to contain `<` and `>`.
+fn signature<'a>(field: &'static str, value: &'a [u8], warnings: &mut Vec<Warning>) -> git_actor::SignatureRef<'a> {
+    let email_end = value.rfind_byte(b'>');
+    let email_start = email_end.and_then(|end| value[..end].rfind_byte(b'<'));
+    let (email_start, email_end) = match (email_start, email_end) {
+        (Some(start), Some(end)) => (start, end),
+        _ => {
+            warnings.push(Warning::InvalidSignature {
+                field,
+                value: value.into(),
+            });
+            return git_actor::SignatureRef {
+                name: value.as_bstr(),
+                ..Default::default()
+            };
+        }
+    };
+    let name = &value[..email_start];
+    let mut time = git_actor::Time::default();
+    let mut tokens = value[email_end + 1..].split_str(" ").filter(|t| !t.is_empty());
+    match tokens.next().map(|t| (t, btoi::btoi::<u32>(t))) {
+        Some((_, Ok(seconds))) => time.seconds_since_unix_epoch = seconds,
+        Some((token, Err(_))) => warnings.push(Warning::InvalidTime {
+            field,
+            value: token.into(),
+        }),
+        None => warnings.push(Warning::InvalidTime {
+            field,
+            value: Default::default(),
+        }),
+    }
+    let tz = tokens.next().unwrap_or_default();
+    match timezone(tz) {
+        Some((offset, sign)) => {
+            time.offset_in_seconds = offset;
+            time.sign = sign;
+        }
+        None => warnings.push(Warning::InvalidTimezone {
+            field,
+            value: tz.into(),
+        }),
+    }
+    git_actor::SignatureRef {
+        name: name.strip_suffix(b" ").unwrap_or(name).as_bstr(),
+        email: value[email_start + 1..email_end].as_bstr(),
+        time,
+    }
+}
+
+/// Parse `[+-]HHMM`.
+fn timezone(tz: &[u8]) -> Option<(i32, git_actor::Sign)> {
+    let (sign, digits) = tz.split_first()?;
+    let sign = match sign {
+        b'+' => git_actor::Sign::Plus,
+        b'-' => git_actor::Sign::Minus,
+        _ => return None,
+    };
+    if digits.len() != 4 || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let hours = btoi::btoi::<i32>(&digits[..2]).ok()?;
+    let minutes = btoi::btoi::<i32>(&digits[2..]).ok()?;
+    if minutes > 59 {
+        return None;
+    }
+    let offset = hours * 3600 + minutes * 60;
+    Some((
+        if sign == git_actor::Sign::Minus {
+            -offset
+        } else {
+            offset
+        },
+        sign,
+    ))
+}
@@ -1,4 +1,4 @@
-use std::ops::Deref;
+use std::{borrow::Cow, ops::Deref};
 
 use nom::{
     bytes::complete::{tag, take_until1},
@@ -9,13 +9,14 @@ use nom::{
 };
 
 use crate::{
-    bstr::{BStr, ByteSlice},
+    bstr::{BStr, BString, ByteSlice, ByteVec},
     commit::message::BodyRef,
 };
 
 /// An iterator over trailers as parsed from a commit message body.
 ///
-/// lines with parsing failures will be skipped
+/// Lines with parsing failures will be skipped, and lines starting with whitespace right after a trailer
+/// are considered a continuation of its value, just like `git interpret-trailers` does.
 pub struct Trailers<'a> {
     pub(crate) cursor: &'a [u8],
 }
@@ -28,10 +29,53 @@ pub struct TrailerRef<'a> {
     #[cfg_attr(feature = "serde1", serde(borrow))]
     pub token: &'a BStr,
     /// The value right after the separator ": ", with leading and trailing whitespace trimmed.
-    /// Note that multi-line values aren't currently supported.
+    ///
+    /// If the value was folded onto multiple lines, it includes all of them verbatim, use
+    /// [`value_unfolded()`][TrailerRef::value_unfolded()] to obtain it as single line.
     pub value: &'a BStr,
 }
 
+impl<'a> TrailerRef<'a> {
+    /// Return our value with folded lines joined by a single space, as `git interpret-trailers --unfold` would.
+    pub fn value_unfolded(&self) -> Cow<'a, BStr> {
+        if self.value.find_byte(b'\n').is_none() {
+            return Cow::Borrowed(self.value);
+        }
+        let mut out = BString::from(Vec::with_capacity(self.value.len()));
+        for (idx, line) in self.value.lines().enumerate() {
+            if idx != 0 {
+                out.push_byte(b' ');
+            }
+            out.push_str(line.trim());
+        }
+        Cow::Owned(out)
+    }
+
+    /// Return true if our token matches `token`, ignoring case like git does.
+    pub fn is_token(&self, token: &str) -> bool {
+        self.token.eq_ignore_ascii_case(token.as_bytes())
+    }
+
+    /// Return true if this is a `Signed-off-by` trailer.
+    pub fn is_signed_off_by(&self) -> bool {
+        self.is_token("Signed-off-by")
+    }
+
+    /// Return true if this is a `Co-authored-by` trailer.
+    pub fn is_co_authored_by(&self) -> bool {
+        self.is_token("Co-authored-by")
+    }
+
+    /// Return true if this is a `Reviewed-by` trailer.
+    pub fn is_reviewed_by(&self) -> bool {
+        self.is_token("Reviewed-by")
+    }
+}
+
+fn is_continuation(line: &[u8]) -> bool {
+    matches!(line.first(), Some(b' ') | Some(b'\t')) && !line.trim().is_empty()
+}
+
 fn parse_single_line_trailer<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], (&'a BStr, &'a BStr), E> {
     let (value, token) = terminated(take_until1(b":".as_ref()), tag(b": "))(i.trim_end())?;
     if token.trim_end().len() != token.len() || value.trim_start().len() != value.len() {
@@ -45,20 +89,28 @@ impl<'a> Iterator for Trailers<'a> {
     type Item = TrailerRef<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.cursor.is_empty() {
-            return None;
-        }
-        for line in self.cursor.lines_with_terminator() {
-            self.cursor = &self.cursor[line.len()..];
-            if let Some(trailer) =
-                all_consuming(parse_single_line_trailer::<()>)(line)
-                    .ok()
-                    .map(|(_, (token, value))| TrailerRef {
-                        token: token.trim().as_bstr(),
-                        value: value.trim().as_bstr(),
-                    })
-            {
-                return Some(trailer);
+        while !self.cursor.is_empty() {
+            let input = self.cursor;
+            let line_len = input.find_byte(b'\n').map(|pos| pos + 1).unwrap_or(input.len());
+            let line = &input[..line_len];
+            self.cursor = &input[line_len..];
+
+            if let Ok((_, (token, value))) = all_consuming(parse_single_line_trailer::<()>)(line) {
+                let value_start = value.as_ptr() as usize - input.as_ptr() as usize;
+                let mut value_end = value_start + value.len();
+                let mut consumed = line_len;
+                for continuation in self.cursor.lines_with_terminator() {
+                    if !is_continuation(continuation) {
+                        break;
+                    }
+                    value_end = consumed + continuation.trim_end().len();
+                    consumed += continuation.len();
+                }
+                self.cursor = &input[consumed..];
+                return Some(TrailerRef {
+                    token: token.trim().as_bstr(),
+                    value: input[value_start..value_end].trim().as_bstr(),
+                });
             }
         }
         None
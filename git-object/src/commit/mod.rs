@@ -2,8 +2,14 @@ use bstr::{BStr, ByteSlice};
 
 use crate::{Commit, CommitRef, TagRef};
 
+///
+pub mod builder;
+pub use builder::Builder;
+
 mod decode;
 ///
+pub mod lenient;
+///
 pub mod message;
 
 /// A parsed commit message that assumes a title separated from the body by two consecutive newlines.
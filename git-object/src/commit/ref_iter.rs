@@ -125,7 +125,7 @@ impl<'a> CommitRefIter<'a> {
         use State::*;
         Ok(match state {
             Tree => {
-                let (i, tree) = context("tree <40 lowercase hex char>", |i| {
+                let (i, tree) = context("tree <lowercase hex hash>", |i| {
                     parse::header_field(i, b"tree", parse::hex_hash)
                 })(i)?;
                 *state = State::Parents;
@@ -138,7 +138,7 @@ impl<'a> CommitRefIter<'a> {
             }
             Parents => {
                 let (i, parent) = context(
-                    "commit <40 lowercase hex char>",
+                    "commit <lowercase hex hash>",
                     opt(|i| parse::header_field(i, b"parent", parse::hex_hash)),
                 )(i)?;
                 match parent {
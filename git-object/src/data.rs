@@ -2,6 +2,21 @@
 
 use crate::{BlobRef, CommitRef, CommitRefIter, Data, Kind, ObjectRef, TagRef, TagRefIter, TreeRef, TreeRefIter};
 
+impl<'a> crate::WriteTo for Data<'a> {
+    /// Write the already serialized object data to `out` verbatim.
+    fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        out.write_all(self.data)
+    }
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    fn size(&self) -> usize {
+        self.data.len()
+    }
+}
+
 impl<'a> Data<'a> {
     /// Constructs a new data object from `kind` and `data`.
     pub fn new(kind: Kind, data: &'a [u8]) -> Data<'a> {
@@ -74,7 +89,7 @@ pub mod verify {
             hasher.update(&crate::encode::loose_header(self.kind, self.data.len()));
             hasher.update(self.data);
 
-            let actual_id = git_hash::ObjectId::from(hasher.digest());
+            let actual_id = hasher.digest();
             if desired != actual_id {
                 return Err(Error::ChecksumMismatch {
                     desired: desired.into(),
@@ -27,8 +27,12 @@ mod blob;
 ///
 pub mod data;
 
+pub mod signature;
+
+pub mod peek;
+
 mod traits;
-pub use traits::WriteTo;
+pub use traits::{compute_hash, WriteTo};
 
 pub mod encode;
 pub(crate) mod parse;
@@ -220,6 +224,8 @@ pub struct TreeRef<'a> {
 pub struct TreeRefIter<'a> {
     /// The directories and files contained in this tree.
     data: &'a [u8],
+    /// The kind of hash used for entry ids, which determines their length.
+    object_hash: git_hash::Kind,
 }
 
 /// A mutable Tree, containing other trees, blobs or commits.
@@ -13,7 +13,7 @@ impl From<TagRef<'_>> for Tag {
             pgp_signature,
         } = other;
         Tag {
-            target: git_hash::ObjectId::from_hex(target).expect("40 bytes hex sha1"),
+            target: git_hash::ObjectId::from_hex(target).expect("prior validation of hashes during parsing"),
             name: name.to_owned(),
             target_kind,
             message: message.to_owned(),
@@ -35,10 +35,10 @@ impl From<CommitRef<'_>> for Commit {
             extra_headers,
         } = other;
         Commit {
-            tree: git_hash::ObjectId::from_hex(tree).expect("40 bytes hex sha1"),
+            tree: git_hash::ObjectId::from_hex(tree).expect("prior validation of hashes during parsing"),
             parents: parents
                 .iter()
-                .map(|parent| git_hash::ObjectId::from_hex(parent).expect("40 bytes hex sha1"))
+                .map(|parent| git_hash::ObjectId::from_hex(parent).expect("prior validation of hashes during parsing"))
                 .collect(),
             author: author.into(),
             committer: committer.into(),
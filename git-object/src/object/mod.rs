@@ -185,10 +185,20 @@ impl<'a> ObjectRef<'a> {
         Ok(Self::from_bytes(kind, &data[offset..][..size])?)
     }
 
-    /// Deserialize an object of `kind` from the given `data`.
+    /// Deserialize an object of `kind` from the given `data`, assuming it refers to other objects by
+    /// [Sha1][git_hash::Kind::Sha1] hashes.
     pub fn from_bytes(kind: Kind, data: &'a [u8]) -> Result<ObjectRef<'a>, crate::decode::Error> {
+        Self::from_bytes_with_object_hash(kind, data, git_hash::Kind::Sha1)
+    }
+
+    /// Deserialize an object of `kind` from the given `data`, which refers to other objects by hashes of kind `object_hash`.
+    pub fn from_bytes_with_object_hash(
+        kind: Kind,
+        data: &'a [u8],
+        object_hash: git_hash::Kind,
+    ) -> Result<ObjectRef<'a>, crate::decode::Error> {
         Ok(match kind {
-            Kind::Tree => ObjectRef::Tree(TreeRef::from_bytes(data)?),
+            Kind::Tree => ObjectRef::Tree(TreeRef::from_bytes_with_object_hash(data, object_hash)?),
             Kind::Blob => ObjectRef::Blob(BlobRef { data }),
             Kind::Commit => ObjectRef::Commit(CommitRef::from_bytes(data)?),
             Kind::Tag => ObjectRef::Tag(TagRef::from_bytes(data)?),
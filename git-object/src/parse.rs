@@ -65,13 +65,17 @@ fn is_hex_digit_lc(b: u8) -> bool {
     matches!(b, b'0'..=b'9' | b'a'..=b'f')
 }
 
+/// Parse a lower-case hexadecimal hash of any of the supported [hash kinds][git_hash::Kind].
 pub fn hex_hash<'a, E: ParseError<&'a [u8]>>(i: &'a [u8]) -> IResult<&'a [u8], &'a BStr, E> {
-    take_while_m_n(
+    let (rest, hex) = take_while_m_n(
         git_hash::Kind::shortest().len_in_hex(),
         git_hash::Kind::longest().len_in_hex(),
         is_hex_digit_lc,
-    )(i)
-    .map(|(i, hex)| (i, hex.as_bstr()))
+    )(i)?;
+    if git_hash::Kind::from_hex_len(hex.len()).is_none() {
+        return Err(nom::Err::Error(E::from_error_kind(i, nom::error::ErrorKind::MapRes)));
+    }
+    Ok((rest, hex.as_bstr()))
 }
 
 pub(crate) fn signature<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
@@ -0,0 +1,58 @@
+//! Decoding of only the headers of commits and tags which make up the object graph, leaving signatures and
+//! messages untouched, to accelerate traversals that don't need anything else.
+use bstr::BStr;
+use git_hash::ObjectId;
+use nom::{bytes::complete::take_while1, character::is_alphabetic, multi::many0};
+use smallvec::SmallVec;
+
+use crate::{parse, Kind};
+
+/// The part of a commit that links it into the object graph.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct CommitHeader {
+    /// The tree the commit points to.
+    pub tree: ObjectId,
+    /// The parents of the commit, in order.
+    pub parents: SmallVec<[ObjectId; 1]>,
+}
+
+/// The part of a tag that links it into the object graph.
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy)]
+pub struct TagHeader {
+    /// The object the tag points to.
+    pub target: ObjectId,
+    /// The kind of object the tag points to.
+    pub target_kind: Kind,
+}
+
+/// Decode only the `tree` and `parent` headers of the serialized commit in `data`.
+///
+/// Note that the remainder of the commit isn't validated at all.
+pub fn commit(data: &[u8]) -> Result<CommitHeader, crate::decode::Error> {
+    let (i, tree): (_, &BStr) = parse::header_field(data, b"tree", parse::hex_hash).map_err(decode_error)?;
+    let (_, parents): (_, Vec<&BStr>) =
+        many0(|i| parse::header_field(i, b"parent", parse::hex_hash))(i).map_err(decode_error)?;
+    Ok(CommitHeader {
+        tree: ObjectId::from_hex(tree).expect("prior validation of hashes during parsing"),
+        parents: parents
+            .into_iter()
+            .map(|parent| ObjectId::from_hex(parent).expect("prior validation of hashes during parsing"))
+            .collect(),
+    })
+}
+
+/// Decode only the `object` and `type` headers of the serialized tag in `data`.
+///
+/// Note that the remainder of the tag isn't validated at all.
+pub fn tag(data: &[u8]) -> Result<TagHeader, crate::decode::Error> {
+    let (i, target): (_, &BStr) = parse::header_field(data, b"object", parse::hex_hash).map_err(decode_error)?;
+    let (_, kind): (_, &[u8]) = parse::header_field(i, b"type", take_while1(is_alphabetic)).map_err(decode_error)?;
+    Ok(TagHeader {
+        target: ObjectId::from_hex(target).expect("prior validation of hashes during parsing"),
+        target_kind: Kind::from_bytes(kind).map_err(|_| crate::decode::empty_error())?,
+    })
+}
+
+fn decode_error(err: nom::Err<crate::decode::ParseError<'_>>) -> crate::decode::Error {
+    err.into()
+}
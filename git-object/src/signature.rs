@@ -0,0 +1,141 @@
+//! Extraction of cryptographic signatures and the payload they sign from commits and tags, exactly the way git does it,
+//! so that external tools like `gpg`, `gpgsm` or `ssh-keygen` can be used to verify them.
+use std::borrow::Cow;
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{CommitRef, TagRef};
+
+/// The format of a cryptographic signature, as determined by its armor.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Format {
+    /// A signature created by `gpg`.
+    OpenPgp,
+    /// A signature created by `gpgsm` or other S/MIME tooling.
+    X509,
+    /// A signature created by `ssh-keygen -Y sign`.
+    Ssh,
+}
+
+impl Format {
+    /// The lines at which signatures of this format may begin.
+    pub fn begin_markers(&self) -> &'static [&'static [u8]] {
+        match self {
+            Format::OpenPgp => &[b"-----BEGIN PGP SIGNATURE-----", b"-----BEGIN PGP MESSAGE-----"],
+            Format::X509 => &[b"-----BEGIN SIGNED MESSAGE-----"],
+            Format::Ssh => &[b"-----BEGIN SSH SIGNATURE-----"],
+        }
+    }
+
+    /// Determine the format of `signature` by the marker it begins with, or `None` if it's unknown.
+    pub fn from_signature(signature: &[u8]) -> Option<Self> {
+        [Format::OpenPgp, Format::X509, Format::Ssh]
+            .iter()
+            .copied()
+            .find(|format| format.begin_markers().iter().any(|m| signature.starts_with(m)))
+    }
+}
+
+/// A signature along with the data it is signing.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub struct Signed<'a> {
+    /// The signature itself, including the newline ending its last line.
+    pub signature: Cow<'a, BStr>,
+    /// The data the signature was created for, which is the serialized object without its signature.
+    pub payload: Cow<'a, BStr>,
+}
+
+impl<'a> Signed<'a> {
+    /// Return the format of our signature, or `None` if it is unknown.
+    pub fn format(&self) -> Option<Format> {
+        Format::from_signature(self.signature.as_ref())
+    }
+}
+
+/// The name of the commit header holding the signature for objects hashed with `kind`.
+pub fn commit_header_name(kind: git_hash::Kind) -> &'static str {
+    match kind {
+        git_hash::Kind::Sha1 => "gpgsig",
+        git_hash::Kind::Sha256 => "gpgsig-sha256",
+    }
+}
+
+impl<'a> CommitRef<'a> {
+    /// Split the serialized commit in `data` into its signature and the payload that was signed, or return `None` if
+    /// it isn't signed. `hash_kind` determines the signature header to look for.
+    ///
+    /// Like git, the signature headers of all hash kinds are removed from the payload, along with all of their continuation
+    /// lines, so that commits signed for both hash kinds can be verified. The signature is reconstructed from the value of
+    /// the header for `hash_kind` by removing the leading space of each continuation line.
+    pub fn split_signature(data: &'a [u8], hash_kind: git_hash::Kind) -> Option<Signed<'a>> {
+        let wanted_header = commit_header_name(hash_kind).as_bytes();
+        let mut payload = BString::from(Vec::with_capacity(data.len()));
+        let mut signature = BString::default();
+        let mut in_header = None;
+        let mut saw_signature = false;
+
+        let mut rest = data;
+        while !rest.is_empty() {
+            let line_end = rest.find_byte(b'\n').map(|pos| pos + 1).unwrap_or(rest.len());
+            let line = &rest[..line_end];
+            let sig = match in_header {
+                Some(header) if line.first() == Some(&b' ') => Some((header, &line[1..])),
+                _ => [git_hash::Kind::Sha1, git_hash::Kind::Sha256]
+                    .iter()
+                    .map(|kind| commit_header_name(*kind).as_bytes())
+                    .find(|header| line.starts_with(header) && line.get(header.len()) == Some(&b' '))
+                    .map(|header| {
+                        in_header = Some(header);
+                        (header, &line[header.len() + 1..])
+                    }),
+            };
+            match sig {
+                Some((header, sig)) => {
+                    if header == wanted_header {
+                        signature.extend_from_slice(sig);
+                        saw_signature = true;
+                    }
+                    rest = &rest[line_end..];
+                }
+                None => {
+                    if line == b"\n" {
+                        // The message is copied verbatim.
+                        payload.extend_from_slice(rest);
+                        break;
+                    }
+                    payload.extend_from_slice(line);
+                    in_header = None;
+                    rest = &rest[line_end..];
+                }
+            }
+        }
+
+        saw_signature.then(|| Signed {
+            signature: Cow::Owned(signature),
+            payload: Cow::Owned(payload),
+        })
+    }
+}
+
+impl<'a> TagRef<'a> {
+    /// Split the serialized tag in `data` into its signature and the payload that was signed, or return `None` if
+    /// it isn't signed.
+    ///
+    /// Like git, the signature is assumed to start at the last line of the message beginning with a known signature marker
+    /// of any [format][Format], and everything before it is the payload.
+    pub fn split_signature(data: &'a [u8]) -> Option<Signed<'a>> {
+        let mut start = None;
+        let mut offset = 0;
+        for line in data.lines_with_terminator() {
+            if Format::from_signature(line).is_some() {
+                start = Some(offset);
+            }
+            offset += line.len();
+        }
+        start.map(|start| Signed {
+            signature: Cow::Borrowed(data[start..].as_bstr()),
+            payload: Cow::Borrowed(data[..start].as_bstr()),
+        })
+    }
+}
@@ -11,7 +11,7 @@ use nom::{
 use crate::{parse, parse::NL, BStr, ByteSlice, TagRef};
 
 pub fn git_tag<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(i: &'a [u8]) -> IResult<&[u8], TagRef<'a>, E> {
-    let (i, target) = context("object <40 lowercase hex char>", |i| {
+    let (i, target) = context("object <lowercase hex hash>", |i| {
         parse::header_field(i, b"object", parse::hex_hash)
     })(i)?;
 
@@ -54,7 +54,7 @@ impl<'a> TagRefIter<'a> {
         use State::*;
         Ok(match state {
             Target => {
-                let (i, target) = context("object <40 lowercase hex char>", |i| {
+                let (i, target) = context("object <lowercase hex hash>", |i| {
                     parse::header_field(i, b"object", parse::hex_hash)
                 })(i)?;
                 *state = State::TargetKind;
@@ -3,6 +3,11 @@ use std::io::Write;
 use crate::Kind;
 
 /// Writing of objects to a `Write` implementation
+///
+/// It's implemented by all owned and borrowed object types, allowing them to be serialized into any `io::Write` without
+/// intermediate allocations. As the [size][WriteTo::size()] of the serialized object is known upfront, the
+/// [loose header][WriteTo::loose_header()] can be written first, which allows to [hash][crate::compute_hash()] objects or
+/// to write them into an object database in a single streaming pass.
 pub trait WriteTo {
     /// Write a representation of this instance to `out`.
     fn write_to(&self, out: impl std::io::Write) -> std::io::Result<()>;
@@ -13,6 +18,9 @@ pub trait WriteTo {
     /// Returns the size of this object's representation (the amount
     /// of data which would be written by [`write_to`](Self::write_to)).
     ///
+    /// The size is exact and computed without serializing the object, which allows to produce the
+    /// [loose header][WriteTo::loose_header()] before streaming the object itself.
+    ///
     /// [`size`](Self::size)'s value has no bearing on the validity of
     /// the object, as such it's possible for [`size`](Self::size) to
     /// return a sensible value but [`write_to`](Self::write_to) to
@@ -41,3 +49,14 @@ where
         <T as WriteTo>::kind(self)
     }
 }
+
+/// Compute the id of `object` as it would be stored in an object database using `object_hash`, by streaming its
+/// loose header and serialized form directly into the hasher.
+///
+/// It fails only if the object can't be serialized, for example due to invalid data.
+pub fn compute_hash(object_hash: git_hash::Kind, object: impl WriteTo) -> std::io::Result<git_hash::ObjectId> {
+    let mut hasher = git_features::hash::Write::new(std::io::sink(), object_hash);
+    hasher.write_all(&object.loose_header())?;
+    object.write_to(&mut hasher)?;
+    Ok(hasher.hash.digest())
+}
@@ -0,0 +1,350 @@
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use git_hash::{oid, ObjectId};
+use quick_error::quick_error;
+
+use crate::{
+    tree::{Entry, EntryMode},
+    Tree, TreeRefIter,
+};
+
+quick_error! {
+    /// The error returned by [`Editor`] operations.
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        EmptyPath {
+            display("Paths to edit must contain at least one component")
+        }
+        EmptyPathComponent {
+            display("Path components must not be empty")
+        }
+        NotFound{ oid: ObjectId } {
+            display("The tree {} could not be found", oid)
+        }
+        Decode(err: crate::decode::Error) {
+            display("A tree could not be decoded")
+            source(err)
+            from()
+        }
+        Write(err: Box<dyn std::error::Error + Send + Sync + 'static>) {
+            display("A tree could not be written")
+            source(&**err)
+        }
+    }
+}
+
+/// A way to constructively edit a tree, starting with an existing one or with an empty one, to produce new trees
+/// with entries inserted, removed or renamed at arbitrary depths.
+///
+/// Trees that need to be changed are loaded lazily with `find` and kept in memory until [`write()`][Editor::write()]
+/// is called, which writes all changed trees, deepest first, and returns the id of the new root tree.
+pub struct Editor<Find> {
+    find: Find,
+    object_hash: git_hash::Kind,
+    /// All trees we currently hold in memory, keyed by their slash-separated path relative to the root, which is the empty path.
+    trees: BTreeMap<BString, Tree>,
+    tree_buf: Vec<u8>,
+}
+
+/// Lifecycle
+impl<Find> Editor<Find>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    /// Create a new editor to modify `root`, using `find` to load subtrees as they are needed.
+    /// `object_hash` is used to determine the length of ids of newly created trees.
+    ///
+    /// Use [`Tree::empty()`] as `root` to create trees from scratch.
+    pub fn new(mut root: Tree, find: Find, object_hash: git_hash::Kind) -> Self {
+        root.entries.sort_by(cmp_names);
+        let mut trees = BTreeMap::new();
+        trees.insert(BString::default(), root);
+        Editor {
+            find,
+            object_hash,
+            trees,
+            tree_buf: Vec::new(),
+        }
+    }
+}
+
+/// Editing
+impl<Find> Editor<Find>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    /// Insert a new entry at `path` with `mode` pointing to `id`, or replace the entry that already exists there.
+    ///
+    /// Intermediate trees are created as needed, and non-tree entries in their way are replaced by trees.
+    /// If an existing tree is replaced with a non-tree, all of its edited subtrees are discarded.
+    pub fn upsert<I, C>(&mut self, path: I, mode: EntryMode, id: impl Into<ObjectId>) -> Result<&mut Self, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        let (parent, filename) = self.load_parents(path, true)?.expect("parents are created");
+        let id = id.into();
+        let tree = self.trees.get_mut(&parent).expect("parent tree is loaded");
+        match tree.entries.binary_search_by(|e| cmp_filename(e, filename.as_bstr())) {
+            Ok(pos) => {
+                let entry = &mut tree.entries[pos];
+                let previous_was_tree = entry.mode.is_tree();
+                entry.mode = mode;
+                entry.oid = id;
+                if previous_was_tree {
+                    self.remove_trees_at(join(parent.as_bstr(), filename.as_bstr()).as_bstr());
+                }
+            }
+            Err(pos) => tree.entries.insert(
+                pos,
+                Entry {
+                    mode,
+                    filename,
+                    oid: id,
+                },
+            ),
+        }
+        Ok(self)
+    }
+
+    /// Remove the entry at `path`, which may be a tree, or do nothing if it doesn't exist.
+    ///
+    /// Trees which become empty as a result will be removed as well once [written][Editor::write()].
+    pub fn remove<I, C>(&mut self, path: I) -> Result<&mut Self, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        if let Some((path, entry)) = self.take_entry(path)? {
+            if entry.mode.is_tree() {
+                self.remove_trees_at(path.as_bstr());
+            }
+        }
+        Ok(self)
+    }
+
+    /// Move the entry at `source` to `destination`, replacing what was there, or do nothing if `source` doesn't exist.
+    ///
+    /// If `source` is a tree, all edits made to it so far are retained.
+    pub fn rename<I, C, I2, C2>(&mut self, source: I, destination: I2) -> Result<&mut Self, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+        I2: IntoIterator<Item = C2>,
+        C2: AsRef<BStr>,
+    {
+        let (source_path, entry) = match self.take_entry(source)? {
+            Some(v) => v,
+            None => return Ok(self),
+        };
+        let moved_trees = self.split_off_trees_at(source_path.as_bstr());
+        let destination: Vec<BString> = destination.into_iter().map(|c| c.as_ref().to_owned()).collect();
+        self.upsert(destination.iter(), entry.mode, entry.oid)?;
+        if !moved_trees.is_empty() {
+            let destination_path = destination
+                .iter()
+                .fold(BString::default(), |p, c| join(p.as_bstr(), c.as_bstr()));
+            for (path, tree) in moved_trees {
+                let mut new_path = destination_path.clone();
+                new_path.push_str(&path[source_path.len()..]);
+                self.trees.insert(new_path, tree);
+            }
+        }
+        Ok(self)
+    }
+}
+
+/// Writing
+impl<Find> Editor<Find>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    /// Write all trees that were edited, deepest first, by passing them to `write`, which is expected to return their id,
+    /// and return the id of the root tree. Empty trees below the root are removed from their parent instead of being written.
+    ///
+    /// Written trees are canonical, their entries are sorted the way git expects. After a successful write, the editor
+    /// can be used for more edits based on the newly written trees.
+    pub fn write<E>(&mut self, mut write: impl FnMut(&Tree) -> Result<ObjectId, E>) -> Result<ObjectId, Error>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut paths: Vec<BString> = self.trees.keys().cloned().collect();
+        paths.sort_by(|a, b| depth(b.as_bstr()).cmp(&depth(a.as_bstr())).then_with(|| a.cmp(b)));
+
+        let mut root_id = None;
+        for path in paths {
+            let tree = self.trees.get_mut(&path).expect("present");
+            let is_root = path.is_empty();
+            let id = if tree.entries.is_empty() && !is_root {
+                None
+            } else {
+                tree.sort_canonically();
+                let id = write(tree).map_err(|err| Error::Write(err.into()))?;
+                tree.entries.sort_by(cmp_names);
+                Some(id)
+            };
+            if is_root {
+                root_id = id;
+                continue;
+            }
+            let (parent, filename) = split_parent(path.as_bstr());
+            let parent = self
+                .trees
+                .get_mut(parent)
+                .expect("parents are always loaded before children");
+            if let Ok(pos) = parent.entries.binary_search_by(|e| cmp_filename(e, filename)) {
+                match id {
+                    Some(id) => parent.entries[pos].oid = id,
+                    None => {
+                        parent.entries.remove(pos);
+                        self.trees.remove(&path);
+                    }
+                }
+            }
+        }
+        Ok(root_id.expect("root tree is always present"))
+    }
+}
+
+/// Utilities
+impl<Find> Editor<Find>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+{
+    /// Make sure all trees leading up to the last component of `path` are loaded, and return the path of the
+    /// parent tree along with the last component. If `create` is false, return `None` if one of the parents doesn't exist.
+    fn load_parents<I, C>(&mut self, path: I, create: bool) -> Result<Option<(BString, BString)>, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        let mut components = path.into_iter().peekable();
+        let mut parent = BString::default();
+        loop {
+            let component = components.next().ok_or(Error::EmptyPath)?;
+            let component = component.as_ref();
+            if component.is_empty() {
+                return Err(Error::EmptyPathComponent);
+            }
+            if components.peek().is_none() {
+                return Ok(Some((parent, component.to_owned())));
+            }
+            let path = join(parent.as_bstr(), component);
+            if !self.trees.contains_key(&path) {
+                let tree = self.trees.get_mut(&parent).expect("parent tree is loaded");
+                let subtree = match tree.entries.binary_search_by(|e| cmp_filename(e, component)) {
+                    Ok(pos) if tree.entries[pos].mode.is_tree() => {
+                        let id = tree.entries[pos].oid;
+                        let mut entries = (self.find)(&id, &mut self.tree_buf)
+                            .ok_or(Error::NotFound { oid: id })?
+                            .map(|entry| entry.map(Into::into))
+                            .collect::<Result<Vec<Entry>, _>>()?;
+                        entries.sort_by(cmp_names);
+                        Tree { entries }
+                    }
+                    Ok(pos) => {
+                        if !create {
+                            return Ok(None);
+                        }
+                        let entry = &mut tree.entries[pos];
+                        entry.mode = EntryMode::Tree;
+                        entry.oid = self.object_hash.null();
+                        Tree::empty()
+                    }
+                    Err(pos) => {
+                        if !create {
+                            return Ok(None);
+                        }
+                        tree.entries.insert(
+                            pos,
+                            Entry {
+                                mode: EntryMode::Tree,
+                                filename: component.to_owned(),
+                                oid: self.object_hash.null(),
+                            },
+                        );
+                        Tree::empty()
+                    }
+                };
+                self.trees.insert(path.clone(), subtree);
+            }
+            parent = path;
+        }
+    }
+
+    fn take_entry<I, C>(&mut self, path: I) -> Result<Option<(BString, Entry)>, Error>
+    where
+        I: IntoIterator<Item = C>,
+        C: AsRef<BStr>,
+    {
+        let (parent, filename) = match self.load_parents(path, false)? {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+        let tree = self.trees.get_mut(&parent).expect("parent tree is loaded");
+        Ok(
+            match tree.entries.binary_search_by(|e| cmp_filename(e, filename.as_bstr())) {
+                Ok(pos) => Some((join(parent.as_bstr(), filename.as_bstr()), tree.entries.remove(pos))),
+                Err(_) => None,
+            },
+        )
+    }
+
+    fn remove_trees_at(&mut self, path: &BStr) {
+        self.split_off_trees_at(path);
+    }
+
+    /// Remove the tree at `path` and all trees below it, and return them.
+    fn split_off_trees_at(&mut self, path: &BStr) -> Vec<(BString, Tree)> {
+        let mut prefix = path.to_owned();
+        prefix.push_byte(b'/');
+        let keys: Vec<_> = self
+            .trees
+            .keys()
+            .filter(|key| key.as_bstr() == path || key.starts_with(&prefix))
+            .cloned()
+            .collect();
+        keys.into_iter()
+            .map(|key| {
+                let tree = self.trees.remove(&key).expect("present");
+                (key, tree)
+            })
+            .collect()
+    }
+}
+
+fn join(parent: &BStr, component: &BStr) -> BString {
+    let mut path = parent.to_owned();
+    if !path.is_empty() {
+        path.push_byte(b'/');
+    }
+    path.push_str(component);
+    path
+}
+
+fn split_parent(path: &BStr) -> (&BStr, &BStr) {
+    match path.rfind_byte(b'/') {
+        Some(pos) => (path[..pos].as_bstr(), path[pos + 1..].as_bstr()),
+        None => ("".into(), path),
+    }
+}
+
+fn depth(path: &BStr) -> usize {
+    if path.is_empty() {
+        0
+    } else {
+        path.find_iter(b"/").count() + 1
+    }
+}
+
+/// While editing, entries are kept sorted by name alone as names are unique per tree, which allows lookups without
+/// knowing the mode of the entry.
+fn cmp_filename(entry: &Entry, filename: &BStr) -> Ordering {
+    entry.filename.as_bstr().cmp(filename)
+}
+
+fn cmp_names(a: &Entry, b: &Entry) -> Ordering {
+    a.filename.cmp(&b.filename)
+}
@@ -5,6 +5,12 @@ use crate::{
     tree,
 };
 
+///
+pub mod editor;
+pub use editor::Editor;
+
+///
+pub mod order;
 mod ref_iter;
 ///
 pub mod write;
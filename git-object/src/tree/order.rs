@@ -0,0 +1,119 @@
+use std::{cmp::Ordering, io};
+
+use bstr::{BStr, BString, ByteSlice};
+use quick_error::quick_error;
+
+use crate::{
+    tree::{Entry, EntryMode, EntryRef},
+    Tree, TreeRef,
+};
+
+quick_error! {
+    /// The error returned by [`Tree::verify_order()`] and [`TreeRef::verify_order()`].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Duplicate { filename: BString } {
+            display("The entry {:?} is contained more than once", filename)
+        }
+        Unsorted { previous: BString, current: BString } {
+            display("The entry {:?} must be sorted before {:?}", current, previous)
+        }
+    }
+}
+
+/// Compare the entries with `filename` and `mode` the way git does, which sorts trees as if their name had a trailing slash.
+///
+/// This is the order in which entries must be stored in serialized trees.
+pub fn cmp(a_filename: &BStr, a_mode: EntryMode, b_filename: &BStr, b_mode: EntryMode) -> Ordering {
+    let common = a_filename.len().min(b_filename.len());
+    a_filename[..common].cmp(&b_filename[..common]).then_with(|| {
+        let a = a_filename
+            .get(common)
+            .copied()
+            .or_else(|| a_mode.is_tree().then(|| b'/'));
+        let b = b_filename
+            .get(common)
+            .copied()
+            .or_else(|| b_mode.is_tree().then(|| b'/'));
+        a.cmp(&b)
+    })
+}
+
+impl Entry {
+    /// Compare this entry to `other` in the order git expects in serialized trees.
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        cmp(self.filename.as_bstr(), self.mode, other.filename.as_bstr(), other.mode)
+    }
+}
+
+impl<'a> EntryRef<'a> {
+    /// Compare this entry to `other` in the order git expects in serialized trees.
+    pub fn cmp_canonical(&self, other: &Self) -> Ordering {
+        cmp(self.filename, self.mode, other.filename, other.mode)
+    }
+}
+
+/// Assure the entries yielded as `(filename, mode)` are sorted canonically and contain no adjacent duplicates, like
+/// `git fsck` would.
+fn verify<'a>(mut entries: impl Iterator<Item = (&'a BStr, EntryMode)>) -> Result<(), Error> {
+    let (mut prev_name, mut prev_mode) = match entries.next() {
+        Some(entry) => entry,
+        None => return Ok(()),
+    };
+    for (name, mode) in entries {
+        if name == prev_name {
+            return Err(Error::Duplicate { filename: name.into() });
+        }
+        if cmp(prev_name, prev_mode, name, mode) != Ordering::Less {
+            return Err(Error::Unsorted {
+                previous: prev_name.into(),
+                current: name.into(),
+            });
+        }
+        prev_name = name;
+        prev_mode = mode;
+    }
+    Ok(())
+}
+
+/// Ordering
+impl Tree {
+    /// Return an error if our entries are not sorted in the order git expects, or if a name is contained twice.
+    ///
+    /// Note that only adjacent duplicates are detected.
+    pub fn verify_order(&self) -> Result<(), Error> {
+        verify(self.entries.iter().map(|e| (e.filename.as_bstr(), e.mode)))
+    }
+
+    /// Sort our entries in the order git expects in serialized trees.
+    pub fn sort_canonically(&mut self) {
+        self.entries.sort_by(Entry::cmp_canonical);
+    }
+
+    /// Serialize this tree to `out` like [`write_to()`][crate::WriteTo::write_to()], but with entries sorted the way git
+    /// expects no matter the order they are stored in.
+    pub fn write_canonical_to(&self, mut out: impl io::Write) -> io::Result<()> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|a, b| a.cmp_canonical(b));
+        for entry in entries {
+            super::write::entry(entry.mode, entry.filename.as_bstr(), &entry.oid, &mut out)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ordering
+impl<'a> TreeRef<'a> {
+    /// Return an error if our entries are not sorted in the order git expects, or if a name is contained twice.
+    ///
+    /// Note that only adjacent duplicates are detected.
+    pub fn verify_order(&self) -> Result<(), Error> {
+        verify(self.entries.iter().map(|e| (e.filename, e.mode)))
+    }
+
+    /// Sort our entries in the order git expects in serialized trees.
+    pub fn sort_canonically(&mut self) {
+        self.entries.sort_by(EntryRef::cmp_canonical);
+    }
+}
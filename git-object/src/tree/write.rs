@@ -1,11 +1,11 @@
 use std::io;
 
-use bstr::{BString, ByteSlice};
+use bstr::{BStr, BString, ByteSlice};
 use quick_error::quick_error;
 
 use crate::{
     encode::SPACE,
-    tree::{Entry, EntryRef},
+    tree::{Entry, EntryMode, EntryRef},
     Kind, Tree, TreeRef,
 };
 
@@ -30,26 +30,12 @@ impl From<Error> for io::Error {
 impl crate::WriteTo for Tree {
     /// Serialize this tree to `out` in the git internal format.
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
-        debug_assert_eq!(
-            &{
-                let mut entries_sorted = self.entries.clone();
-                entries_sorted.sort();
-                entries_sorted
-            },
-            &self.entries,
-            "entries for serialization must be sorted by filename"
+        debug_assert!(
+            self.verify_order().is_ok(),
+            "entries for serialization must be sorted canonically, see Tree::sort_canonically()"
         );
         for Entry { mode, filename, oid } in &self.entries {
-            out.write_all(mode.as_bytes())?;
-            out.write_all(SPACE)?;
-
-            if filename.find_byte(b'\n').is_some() {
-                return Err(Error::NewlineInFilename((*filename).to_owned()).into());
-            }
-            out.write_all(filename)?;
-            out.write_all(&[b'\0'])?;
-
-            out.write_all(oid.as_bytes())?;
+            entry(*mode, filename.as_bstr(), oid, &mut out)?;
         }
         Ok(())
     }
@@ -70,26 +56,12 @@ impl crate::WriteTo for Tree {
 impl<'a> crate::WriteTo for TreeRef<'a> {
     /// Serialize this tree to `out` in the git internal format.
     fn write_to(&self, mut out: impl io::Write) -> io::Result<()> {
-        debug_assert_eq!(
-            &{
-                let mut entries_sorted = self.entries.clone();
-                entries_sorted.sort();
-                entries_sorted
-            },
-            &self.entries,
-            "entries for serialization must be sorted by filename"
+        debug_assert!(
+            self.verify_order().is_ok(),
+            "entries for serialization must be sorted canonically, see TreeRef::sort_canonically()"
         );
         for EntryRef { mode, filename, oid } in &self.entries {
-            out.write_all(mode.as_bytes())?;
-            out.write_all(SPACE)?;
-
-            if filename.find_byte(b'\n').is_some() {
-                return Err(Error::NewlineInFilename((*filename).to_owned()).into());
-            }
-            out.write_all(filename)?;
-            out.write_all(&[b'\0'])?;
-
-            out.write_all(oid.as_bytes())?;
+            entry(*mode, filename, oid, &mut out)?;
         }
         Ok(())
     }
@@ -107,3 +79,16 @@ impl<'a> crate::WriteTo for TreeRef<'a> {
         Kind::Tree
     }
 }
+
+pub(crate) fn entry(mode: EntryMode, filename: &BStr, oid: &git_hash::oid, mut out: impl io::Write) -> io::Result<()> {
+    out.write_all(mode.as_bytes())?;
+    out.write_all(SPACE)?;
+
+    if filename.find_byte(b'\n').is_some() {
+        return Err(Error::NewlineInFilename(filename.to_owned()).into());
+    }
+    out.write_all(filename)?;
+    out.write_all(&[b'\0'])?;
+
+    out.write_all(oid.as_bytes())
+}
@@ -27,22 +27,26 @@ macro_rules! round_trip {
                 let item = <$borrowed>::from_bytes(&input)?;
                 item.write_to(&mut output)?;
                 assert_eq!(output.as_bstr(), input.as_bstr());
+                assert_eq!(item.size(), input.len(), "the size is exact and computed without serializing");
 
                 let item: $owned = item.into();
                 output.clear();
                 item.write_to(&mut output)?;
                 assert_eq!(output.as_bstr(), input.as_bstr());
+                assert_eq!(item.size(), input.len());
 
                 // Test the parse->borrowed->owned->write chain for the top-level objects
                 let item = ObjectRef::from(<$borrowed>::from_bytes(&input)?);
                 output.clear();
                 item.write_to(&mut output)?;
                 assert_eq!(output.as_bstr(), input.as_bstr());
+                assert_eq!(item.size(), input.len());
 
                 let item: Object = item.into();
                 output.clear();
                 item.write_to(&mut output)?;
                 assert_eq!(output.as_bstr(), input.as_bstr());
+                assert_eq!(item.size(), input.len());
 
                 // Test the loose serialisation -> parse chain for an object kind
                 let item = <$borrowed>::from_bytes(&input)?;
@@ -97,3 +101,110 @@ mod blob {
     // It doesn't matter which data we use - it's not interpreted.
     round_trip!(git_object::Blob, git_object::BlobRef, "tree/everything.tree");
 }
+
+mod commit_builder {
+    use bstr::ByteSlice;
+    use git_object::{commit, Commit, CommitRef, WriteTo};
+
+    use crate::{fixture_bytes, hex_to_id};
+
+    #[test]
+    fn signed_commits_serialize_like_git() -> crate::Result {
+        let input = fixture_bytes("commit/signed.txt");
+        let expected = CommitRef::from_bytes(&input)?;
+        let signature = expected.extra_headers().pgp_signature().expect("signed").to_owned();
+
+        let mut signature_with_newline = signature.clone();
+        signature_with_newline.push(b'\n');
+        let commit = Commit::builder(expected.tree())
+            .parents(expected.parents())
+            .author(expected.author.into())
+            .committer(expected.committer.into())
+            .message(expected.message)
+            .signature(signature_with_newline)
+            .build()?;
+        assert_eq!(
+            commit.extra_headers().pgp_signature(),
+            Some(signature.as_bstr()),
+            "trailing newlines produced by signing tools are removed"
+        );
+
+        let mut out = Vec::new();
+        commit.write_to(&mut out)?;
+        assert_eq!(out.as_bstr(), input.as_bstr());
+        Ok(())
+    }
+
+    #[test]
+    fn mergetags_serialize_like_git() -> crate::Result {
+        let input = fixture_bytes("commit/mergetag.txt");
+        let expected = CommitRef::from_bytes(&input)?;
+        let tag = expected.extra_headers().mergetags().next().expect("one mergetag")?;
+        assert_eq!(tag.name, "thermal-v5.8-rc1");
+        assert_eq!(tag.target(), hex_to_id("8d485da0ddee79d0e6713405694253d401e41b93"));
+        assert!(tag.pgp_signature.is_some());
+
+        let commit = Commit::builder(expected.tree())
+            .parents(expected.parents())
+            .author(expected.author.into())
+            .committer(expected.committer.into())
+            .message(expected.message)
+            .mergetag(tag.into())
+            .build()?;
+        let mut out = Vec::new();
+        commit.write_to(&mut out)?;
+        assert_eq!(out.as_bstr(), input.as_bstr());
+        Ok(())
+    }
+
+    #[test]
+    fn validation() {
+        let tree = hex_to_id("00fc39317701176e326974ce44f5bd545a32ec0b");
+        let parent = hex_to_id("09d8d3a12e161a7f6afb522dbe8900a9c09bce06");
+        let builder = || {
+            Commit::builder(tree)
+                .author(Default::default())
+                .committer(Default::default())
+        };
+        assert!(builder().parent(parent).build().is_ok());
+        assert!(matches!(
+            Commit::builder(tree).committer(Default::default()).build(),
+            Err(commit::builder::Error::MissingAuthor)
+        ));
+        assert!(matches!(
+            builder().parent(parent).parent(parent).build(),
+            Err(commit::builder::Error::DuplicateParent { .. })
+        ));
+        assert!(matches!(
+            builder().extra_header("parent", "value").build(),
+            Err(commit::builder::Error::ReservedHeaderName { .. })
+        ));
+        assert!(matches!(
+            builder().extra_header("with space", "value").build(),
+            Err(commit::builder::Error::InvalidHeaderName { .. })
+        ));
+        assert!(matches!(
+            builder().extra_header("name", "").build(),
+            Err(commit::builder::Error::EmptyHeaderValue { .. })
+        ));
+    }
+}
+
+mod compute_hash {
+    use git_object::{Data, Kind, Tree, WriteTo};
+
+    #[test]
+    fn matches_well_known_ids_and_raw_data() -> crate::Result {
+        let empty_tree = git_object::compute_hash(git_hash::Kind::Sha1, Tree::empty())?;
+        assert_eq!(empty_tree, git_hash::ObjectId::empty_tree(git_hash::Kind::Sha1));
+
+        let data = Data::new(Kind::Blob, b"");
+        assert_eq!(data.size(), 0);
+        assert_eq!(
+            git_object::compute_hash(git_hash::Kind::Sha1, data)?,
+            crate::hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+            "the empty blob"
+        );
+        Ok(())
+    }
+}
@@ -264,3 +264,33 @@ fn newline_right_after_signature_multiline_header() -> crate::Result {
     assert!(commit.message.starts_with(b"Rollup"));
     Ok(())
 }
+
+#[test]
+fn hashes_must_have_the_length_of_a_known_hash_kind() {
+    let commit = |tree: &str| {
+        format!(
+            "tree {}\nauthor a <a@example.com> 0 +0000\ncommitter a <a@example.com> 0 +0000\n\nmessage",
+            tree
+        )
+    };
+    for valid in &[
+        "1b2dfb4ac5e42080b682fc676e9738c94ce6d54d",
+        "1b2dfb4ac5e42080b682fc676e9738c94ce6d54d1b2dfb4ac5e42080b682fc67",
+    ] {
+        assert!(
+            CommitRef::from_bytes(commit(valid).as_bytes()).is_ok(),
+            "{} is a Sha1 or Sha256 hash",
+            valid
+        );
+    }
+    for invalid in &[
+        "1b2dfb4ac5e42080b682fc676e9738c94ce6d54",
+        "1b2dfb4ac5e42080b682fc676e9738c94ce6d54d1b2dfb4a",
+    ] {
+        assert!(
+            CommitRef::from_bytes(commit(invalid).as_bytes()).is_err(),
+            "{} has no known hash length",
+            invalid
+        );
+    }
+}
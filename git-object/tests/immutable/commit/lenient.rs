@@ -0,0 +1,64 @@
+use git_object::{commit::lenient::Warning, CommitRef};
+use pretty_assertions::assert_eq;
+
+use crate::immutable::fixture_bytes;
+
+#[test]
+fn valid_commits_decode_like_the_strict_parser_without_warnings() -> crate::Result {
+    for name in &[
+        "unsigned.txt",
+        "merge.txt",
+        "mergetag.txt",
+        "signed.txt",
+        "signed-whitespace.txt",
+        "two-multiline-headers.txt",
+        "with-encoding.txt",
+        "whitespace.txt",
+    ] {
+        let fixture = fixture_bytes("commit", name);
+        let (commit, warnings) = CommitRef::from_bytes_lenient(&fixture)?;
+        assert_eq!(commit, CommitRef::from_bytes(&fixture)?, "{}", name);
+        assert!(warnings.is_empty(), "{}: {:?}", name, warnings);
+    }
+    Ok(())
+}
+
+#[test]
+fn broken_commits_are_recovered_with_warnings() -> crate::Result {
+    let fixture = fixture_bytes("commit", "lenient-broken.txt");
+    assert!(CommitRef::from_bytes(&fixture).is_err());
+
+    let (commit, warnings) = CommitRef::from_bytes_lenient(&fixture)?;
+    assert_eq!(commit.tree, "1b2dfb4ac5e42080b682fc676e9738c94ce6d54d");
+    assert_eq!(commit.parents.as_slice(), &["09d8d3a12e161a7f6afb522dbe8900a9c09bce06"]);
+    assert_eq!(
+        commit.author.name, "Sebastian <Thiel>",
+        "illegal bytes in names are retained"
+    );
+    assert_eq!(commit.author.email, "byronimo@gmail.com");
+    assert_eq!(commit.author.time.seconds_since_unix_epoch, 1592437401);
+    assert_eq!(commit.author.time.offset_in_seconds, 0);
+    assert_eq!(commit.committer, commit.author);
+    assert_eq!(commit.extra_headers().find("x-custom"), Some("first\nsecond".into()));
+    assert_eq!(commit.message, "broken\n");
+    assert_eq!(
+        warnings,
+        vec![
+            Warning::InvalidParent {
+                value: "not-a-hash".into()
+            },
+            Warning::InvalidTimezone {
+                field: "author",
+                value: "+05030".into()
+            },
+            Warning::MissingCommitter
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn a_missing_tree_is_fatal() {
+    assert!(CommitRef::from_bytes_lenient(b"parent 09d8d3a12e161a7f6afb522dbe8900a9c09bce06\n\nmessage").is_err());
+    assert!(CommitRef::from_bytes_lenient(b"tree abc\n\nmessage").is_err());
+}
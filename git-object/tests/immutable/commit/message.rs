@@ -213,6 +213,40 @@ mod body {
         )
     }
 
+    #[test]
+    fn folded_trailers_are_unfolded_on_demand() {
+        let input = "body\n\nSigned-off-by: Name\n  <email>\nReviewed-by: a\n\tb \r\n  c\nfoo: bar\n";
+        let body = body(input);
+        assert_eq!(body.as_ref(), "body");
+        let trailers = body.trailers().collect::<Vec<_>>();
+        assert_eq!(
+            trailers,
+            vec![
+                TrailerRef {
+                    token: "Signed-off-by".into(),
+                    value: "Name\n  <email>".into()
+                },
+                TrailerRef {
+                    token: "Reviewed-by".into(),
+                    value: "a\n\tb \r\n  c".into()
+                },
+                TrailerRef {
+                    token: "foo".into(),
+                    value: "bar".into()
+                }
+            ],
+            "continuation lines are part of the value"
+        );
+        assert_eq!(trailers[0].value_unfolded().as_ref(), "Name <email>");
+        assert_eq!(trailers[1].value_unfolded().as_ref(), "a b c");
+        assert_eq!(trailers[2].value_unfolded().as_ref(), "bar");
+
+        assert!(trailers[0].is_signed_off_by());
+        assert!(trailers[1].is_reviewed_by());
+        assert!(!trailers[2].is_co_authored_by());
+        assert!(trailers[0].is_token("signed-off-by"), "tokens are case-insensitive");
+    }
+
     #[test]
     fn no_trailer_after_a_paragraph_windows() {
         let input = "foo\nbar\n\nbar\r\n\r\nbaz";
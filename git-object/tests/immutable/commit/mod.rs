@@ -148,4 +148,5 @@ mod method {
 
 mod from_bytes;
 mod iter;
+mod lenient;
 mod message;
@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use git_actor::{Sign, Time};
 
 mod commit;
+mod peek;
+mod signature;
 mod tag;
 mod tree;
 
@@ -0,0 +1,35 @@
+use git_object::{peek, CommitRef, Kind, TagRef};
+
+use crate::{hex_to_id, immutable::fixture_bytes};
+
+#[test]
+fn commit_headers_match_fully_decoded_commits() -> crate::Result {
+    for name in &["unsigned.txt", "merge.txt", "mergetag.txt", "signed.txt"] {
+        let data = fixture_bytes("commit", name);
+        let commit = CommitRef::from_bytes(&data)?;
+        let header = peek::commit(&data)?;
+        assert_eq!(header.tree, commit.tree());
+        assert!(header.parents.iter().copied().eq(commit.parents()), "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn commit_remainder_is_not_decoded() -> crate::Result {
+    let header = peek::commit(b"tree 1b2dfb4ac5e42080b682fc676e9738c94ce6d54d\nauthor is broken\n")?;
+    assert_eq!(header.tree, hex_to_id("1b2dfb4ac5e42080b682fc676e9738c94ce6d54d"));
+    assert!(header.parents.is_empty());
+    assert!(peek::commit(b"parent 1b2dfb4ac5e42080b682fc676e9738c94ce6d54d\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn tag_headers_match_fully_decoded_tags() -> crate::Result {
+    let data = fixture_bytes("tag", "signed.txt");
+    let tag = TagRef::from_bytes(&data)?;
+    let header = peek::tag(&data)?;
+    assert_eq!(header.target, tag.target());
+    assert_eq!(header.target_kind, tag.target_kind);
+    assert_eq!(header.target_kind, Kind::Commit);
+    Ok(())
+}
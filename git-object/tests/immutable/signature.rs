@@ -0,0 +1,85 @@
+use git_object::{bstr::ByteSlice, signature::Format, Commit, CommitRef, TagRef, WriteTo};
+
+use crate::immutable::fixture_bytes;
+
+#[test]
+fn commit_payload_is_the_commit_without_signature_header() -> crate::Result {
+    for name in &[
+        "signed.txt",
+        "signed-singleline.txt",
+        "signed-with-encoding.txt",
+        "signed-whitespace.txt",
+    ] {
+        let data = fixture_bytes("commit", name);
+        let signed = CommitRef::split_signature(&data, git_hash::Kind::Sha1).expect("signed");
+
+        let commit = CommitRef::from_bytes(&data)?;
+        let mut expected_signature = commit.extra_headers().pgp_signature().expect("signed").to_owned();
+        expected_signature.push(b'\n');
+        assert_eq!(signed.signature.as_ref(), expected_signature.as_bstr(), "{}", name);
+
+        let mut unsigned: Commit = commit.into();
+        unsigned.extra_headers.retain(|(name, _)| name != "gpgsig");
+        let mut expected_payload = Vec::new();
+        unsigned.write_to(&mut expected_payload)?;
+        assert_eq!(signed.payload.as_ref(), expected_payload.as_bstr(), "{}", name);
+    }
+    Ok(())
+}
+
+#[test]
+fn commit_signed_for_both_hash_kinds_has_both_signatures_removed_from_payload() -> crate::Result {
+    let data = fixture_bytes("commit", "signed-dual.txt");
+    let commit = CommitRef::from_bytes(&data)?;
+    let mut unsigned: Commit = commit.into();
+    unsigned
+        .extra_headers
+        .retain(|(name, _)| name != "gpgsig" && name != "gpgsig-sha256");
+    let mut expected_payload = Vec::new();
+    unsigned.write_to(&mut expected_payload)?;
+
+    for (kind, expected_format) in [
+        (git_hash::Kind::Sha1, Format::OpenPgp),
+        (git_hash::Kind::Sha256, Format::Ssh),
+    ] {
+        let signed = CommitRef::split_signature(&data, kind).expect("signed");
+        assert_eq!(signed.format(), Some(expected_format));
+        assert!(signed.signature.ends_with(b"-----\n"), "{:?}", kind);
+        assert_eq!(signed.payload.as_ref(), expected_payload.as_bstr(), "{:?}", kind);
+    }
+    Ok(())
+}
+
+#[test]
+fn unsigned_commits_have_no_signature() {
+    for name in &["unsigned.txt", "mergetag.txt", "with-encoding.txt"] {
+        assert!(CommitRef::split_signature(&fixture_bytes("commit", name), git_hash::Kind::Sha1).is_none());
+    }
+}
+
+#[test]
+fn tag_signature_is_split_off_at_the_last_marker() -> crate::Result {
+    let data = fixture_bytes("tag", "signed.txt");
+    let signed = TagRef::split_signature(&data).expect("signed");
+    let tag = TagRef::from_bytes(&data)?;
+    assert_eq!(Some(signed.signature.as_ref()), tag.pgp_signature);
+    assert_eq!(signed.format(), Some(Format::OpenPgp));
+    assert!(signed.payload.ends_with(b"\n"), "the payload ends with the message");
+    assert_eq!(signed.payload.len() + signed.signature.len(), data.len());
+
+    assert!(TagRef::split_signature(&fixture_bytes("tag", "empty.txt")).is_none());
+    Ok(())
+}
+
+#[test]
+fn format_detection() {
+    assert_eq!(
+        Format::from_signature(b"-----BEGIN SSH SIGNATURE-----\nabc"),
+        Some(Format::Ssh)
+    );
+    assert_eq!(
+        Format::from_signature(b"-----BEGIN SIGNED MESSAGE-----\nabc"),
+        Some(Format::X509)
+    );
+    assert_eq!(Format::from_signature(b"garbage"), None);
+}
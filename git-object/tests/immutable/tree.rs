@@ -107,6 +107,20 @@ mod from_bytes {
         Ok(())
     }
 
+    #[test]
+    fn with_explicit_object_hash() -> crate::Result {
+        let data = fixture_bytes("tree", "everything.tree");
+        assert_eq!(
+            TreeRef::from_bytes_with_object_hash(&data, git_hash::Kind::Sha1)?,
+            TreeRef::from_bytes(&data)?
+        );
+        assert_eq!(
+            git_object::TreeRefIter::from_bytes_with_object_hash(&data, git_hash::Kind::Sha1).count(),
+            5
+        );
+        Ok(())
+    }
+
     #[test]
     fn maybe_special() -> crate::Result {
         assert_eq!(
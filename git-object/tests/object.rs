@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 mod encode;
 mod immutable;
+mod tree;
 
 type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -32,7 +33,7 @@ fn fixture_bytes(path: &str) -> Vec<u8> {
 fn size_in_memory() {
     assert_eq!(
         std::mem::size_of::<git_object::Object>(),
-        264,
+        296,
         "Prevent unexpected growth of what should be lightweight objects"
     )
 }
@@ -0,0 +1,179 @@
+mod editor {
+    use std::{cell::RefCell, collections::HashMap, convert::Infallible};
+
+    use git_hash::ObjectId;
+    use git_object::{
+        bstr::BString,
+        tree::{Editor, EntryMode},
+        Tree, TreeRefIter, WriteTo,
+    };
+
+    use crate::hex_to_id;
+
+    type Store = RefCell<HashMap<ObjectId, Vec<u8>>>;
+
+    fn write_to(store: &Store) -> impl FnMut(&Tree) -> Result<ObjectId, Infallible> + '_ {
+        move |tree| {
+            let mut buf = Vec::new();
+            tree.write_to(&mut buf).expect("write to vec");
+            let mut hasher = git_features::hash::hasher(git_hash::Kind::Sha1);
+            hasher.update(&tree.loose_header());
+            hasher.update(&buf);
+            let id = hasher.digest();
+            store.borrow_mut().insert(id, buf);
+            Ok(id)
+        }
+    }
+
+    fn entries(store: &Store, id: ObjectId) -> Vec<(EntryMode, BString)> {
+        let store = store.borrow();
+        TreeRefIter::from_bytes(store.get(&id).expect("tree present"))
+            .map(|e| e.map(|e| (e.mode, e.filename.to_owned())))
+            .collect::<Result<_, _>>()
+            .expect("valid tree")
+    }
+
+    fn blob() -> ObjectId {
+        hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391")
+    }
+
+    #[test]
+    fn from_empty_tree_with_nested_entries_and_removals() -> crate::Result {
+        let store = Store::default();
+        let mut editor = Editor::new(
+            Tree::empty(),
+            |id, buf| {
+                buf.clear();
+                buf.extend_from_slice(store.borrow().get(id)?);
+                Some(TreeRefIter::from_bytes(buf))
+            },
+            git_hash::Kind::Sha1,
+        );
+        editor
+            .upsert(["a", "b", "file"], EntryMode::Blob, blob())?
+            .upsert(["a.b"], EntryMode::Blob, blob())?
+            .upsert(["a", "exe"], EntryMode::BlobExecutable, blob())?
+            .upsert(["a", "c"], EntryMode::Blob, blob())?
+            .remove(["a", "c"])?
+            .remove(["does-not-exist", "c"])?;
+        let root = editor.write(write_to(&store))?;
+
+        assert_eq!(
+            entries(&store, root),
+            vec![(EntryMode::Blob, "a.b".into()), (EntryMode::Tree, "a".into())],
+            "trees sort as if they had a trailing slash"
+        );
+        assert_eq!(store.borrow().len(), 3, "root, a and a/b");
+
+        editor.remove(["a", "b", "file"])?;
+        let root = editor.write(write_to(&store))?;
+        let a = {
+            let store = store.borrow();
+            let root = TreeRefIter::from_bytes(store.get(&root).expect("present")).entries()?;
+            root.iter().find(|e| e.filename == "a").expect("a").oid.to_owned()
+        };
+        assert_eq!(
+            entries(&store, a),
+            vec![(EntryMode::BlobExecutable, "exe".into())],
+            "empty trees are removed"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rename_keeps_edits_of_subtrees() -> crate::Result {
+        let store = Store::default();
+        let mut editor = Editor::new(
+            Tree::empty(),
+            |id, buf| {
+                buf.clear();
+                buf.extend_from_slice(store.borrow().get(id)?);
+                Some(TreeRefIter::from_bytes(buf))
+            },
+            git_hash::Kind::Sha1,
+        );
+        editor
+            .upsert(["dir", "file"], EntryMode::Blob, blob())?
+            .rename(["dir"], ["renamed", "dir"])?
+            .upsert(["file"], EntryMode::Blob, blob())?
+            .upsert(["file", "now-a-tree"], EntryMode::Link, blob())?;
+        let root = editor.write(write_to(&store))?;
+
+        assert_eq!(
+            entries(&store, root),
+            vec![(EntryMode::Tree, "file".into()), (EntryMode::Tree, "renamed".into())],
+            "non-trees in the way of a path are replaced with trees"
+        );
+        assert_eq!(store.borrow().len(), 4, "root, file, renamed and renamed/dir");
+        Ok(())
+    }
+}
+
+mod order {
+    use git_object::{
+        tree::{order, Entry, EntryMode},
+        Tree, TreeRef, WriteTo,
+    };
+
+    use crate::{fixture_bytes, hex_to_id};
+
+    fn entry(filename: &str, mode: EntryMode) -> Entry {
+        Entry {
+            mode,
+            filename: filename.into(),
+            oid: hex_to_id("e69de29bb2d1d6434b8b29ae775ad8c2e48c5391"),
+        }
+    }
+
+    fn names(tree: &Tree) -> Vec<&str> {
+        tree.entries
+            .iter()
+            .map(|e| std::str::from_utf8(&e.filename).expect("valid"))
+            .collect()
+    }
+
+    #[test]
+    fn trees_sort_as_if_they_had_a_trailing_slash() {
+        let mut tree = Tree {
+            entries: vec![
+                entry("a", EntryMode::Tree),
+                entry("a0", EntryMode::Blob),
+                entry("a.b", EntryMode::Blob),
+                entry("a-", EntryMode::Blob),
+            ],
+        };
+        assert!(matches!(tree.verify_order(), Err(order::Error::Unsorted { .. })));
+
+        let mut out = Vec::new();
+        tree.write_canonical_to(&mut out).expect("valid names");
+
+        tree.sort_canonically();
+        assert_eq!(names(&tree), ["a-", "a.b", "a", "a0"]);
+        assert!(tree.verify_order().is_ok());
+
+        let mut expected = Vec::new();
+        tree.write_to(&mut expected).expect("valid names");
+        assert_eq!(out, expected, "the canonical writer produces sorted trees");
+
+        let mut tree = Tree {
+            entries: vec![entry("a", EntryMode::Blob), entry("a.b", EntryMode::Blob)],
+        };
+        tree.sort_canonically();
+        assert_eq!(names(&tree), ["a", "a.b"], "blobs sort by name");
+    }
+
+    #[test]
+    fn duplicates_are_detected() {
+        let tree = Tree {
+            entries: vec![entry("a", EntryMode::Blob), entry("a", EntryMode::Tree)],
+        };
+        assert!(matches!(tree.verify_order(), Err(order::Error::Duplicate { .. })));
+    }
+
+    #[test]
+    fn trees_written_by_git_are_canonical() -> crate::Result {
+        let data = fixture_bytes("tree/everything.tree");
+        assert!(TreeRef::from_bytes(&data)?.verify_order().is_ok());
+        Ok(())
+    }
+}
@@ -6,9 +6,9 @@
 //!
 //! ```text
 //! # a comment, empty lines are also allowed
-//! # relative paths resolve relative to the parent git repository
-//! ../path/relative/to/repo/.git
-//! /absolute/path/to/repo/.git
+//! # relative paths resolve relative to the objects directory containing this file
+//! ../../path/relative/to/repo/.git/objects
+//! /absolute/path/to/repo/.git/objects
 //!
 //! "/a/ansi-c-quoted/path/with/tabs\t/.git"
 //!
@@ -33,35 +33,61 @@ pub enum Error {
     Cycle(Vec<PathBuf>),
 }
 
+/// The maximum amount of alternates files to follow from the initial objects directory, like in git.
+/// Alternates of object directories nested more deeply are ignored.
+pub const MAX_DEPTH: usize = 5;
+
 /// Given an objects directory, try to resolve alternate object directories possibly located in the
 /// `./info/alternates` file into canonical paths.
 /// If no alternate object database was resolved, the resulting `Vec` is empty (it is not an error
 /// if there are no alternates).
-/// It is an error once a repository is seen again as it would lead to a cycle.
+///
+/// Relative paths are resolved relative to the objects directory containing the `alternates` file, and alternates are
+/// returned in the order they are encountered, each followed by its own alternates, which is also the order in which
+/// they should be searched for objects.
+/// Alternate directories which don't exist or which were seen before are skipped, and alternates are not followed
+/// beyond [`MAX_DEPTH`].
+/// It is an error if an alternate refers back to one of the object directories leading up to it as it would lead to a cycle.
 pub fn resolve(objects_directory: impl Into<PathBuf>) -> Result<Vec<PathBuf>, Error> {
-    let relative_base = objects_directory.into();
-    let mut dirs = vec![(0, relative_base.clone())];
+    let objects_directory = objects_directory.into();
+    let canonicalized = objects_directory.canonicalize()?;
+    let mut dirs = vec![(objects_directory, vec![canonicalized.clone()])];
     let mut out = Vec::new();
-    let mut seen = vec![relative_base.canonicalize()?];
-    while let Some((depth, dir)) = dirs.pop() {
+    let mut seen = vec![canonicalized];
+    while let Some((dir, chain)) = dirs.pop() {
+        let depth = chain.len() - 1;
+        if depth != 0 {
+            out.push(dir.clone());
+        }
+        if depth == MAX_DEPTH {
+            continue;
+        }
         match fs::read(dir.join("info").join("alternates")) {
             Ok(input) => {
+                let mut alternates = Vec::new();
                 for path in parse::content(&input)?.into_iter() {
-                    let path = relative_base.join(path);
-                    let path_canonicalized = path.canonicalize()?;
+                    let path = dir.join(path);
+                    let path_canonicalized = match path.canonicalize() {
+                        Ok(path) => path,
+                        Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+                        Err(err) => return Err(err.into()),
+                    };
+                    if chain.contains(&path_canonicalized) {
+                        return Err(Error::Cycle(chain));
+                    }
                     if seen.contains(&path_canonicalized) {
-                        return Err(Error::Cycle(seen));
+                        continue;
                     }
-                    seen.push(path_canonicalized);
-                    dirs.push((depth + 1, path));
+                    seen.push(path_canonicalized.clone());
+                    let mut chain = chain.clone();
+                    chain.push(path_canonicalized);
+                    alternates.push((path, chain));
                 }
+                dirs.extend(alternates.into_iter().rev());
             }
             Err(err) if err.kind() == io::ErrorKind::NotFound => {}
             Err(err) => return Err(err.into()),
         };
-        if depth != 0 {
-            out.push(dir);
-        }
     }
     Ok(out)
 }
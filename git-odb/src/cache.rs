@@ -17,6 +17,50 @@ pub type ObjectCache = dyn git_pack::cache::Object + Send + 'static;
 /// A constructor for boxed object caches.
 pub type NewObjectCacheFn = dyn Fn() -> Box<ObjectCache> + Send + Sync + 'static;
 
+/// Parameters to configure the pack and object caches of a [`Cache`] with [`Cache::set_caches()`].
+///
+/// Note that caches are created per handle, and thus per thread, so memory limits apply to each of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The amount of memory in bytes to use for caching delta-base objects, or `Some(0)` to disable the pack cache.
+    ///
+    /// If `None`, a cache for a fixed amount of recently used objects is used, which is fast enough to nearly never cause a (marginal)
+    /// slow-down while providing some gains most of the time.
+    pub pack_cache_bytes: Option<usize>,
+    /// The amount of memory in bytes to use for caching entire objects, or `0` to disable the object cache.
+    ///
+    /// Only use it if the same objects are repeatedly accessed, usually during diffing of trees.
+    pub object_cache_bytes: usize,
+    /// The maximum amount of buffers of replaced cache entries to keep around for reuse, or `None` to keep all of them.
+    ///
+    /// Reusing buffers avoids allocations, but keeps their memory alive for longer.
+    pub free_list_limit: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            pack_cache_bytes: None,
+            object_cache_bytes: 0,
+            free_list_limit: None,
+        }
+    }
+}
+
+/// Counters about the use of the pack and object caches of a [`Cache`], as returned by [`Cache::cache_metrics()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    /// The amount of delta base objects that were served from the pack cache.
+    pub pack_cache_hits: u64,
+    /// The amount of delta base objects that had to be decoded as they weren't in the pack cache.
+    pub pack_cache_misses: u64,
+    /// The amount of objects that were served from the object cache.
+    pub object_cache_hits: u64,
+    /// The amount of objects that had to be looked up as they weren't in the object cache.
+    pub object_cache_misses: u64,
+}
+
 impl Cache<crate::store::Handle<Rc<crate::Store>>> {
     /// Convert this cache's handle into one that keeps its store in an arc. This creates an entirely new store,
     /// so should be done early to avoid unnecessary work (and mappings).
@@ -28,6 +72,7 @@ impl Cache<crate::store::Handle<Rc<crate::Store>>> {
             new_object_cache: self.new_object_cache,
             pack_cache: self.pack_cache,
             object_cache: self.object_cache,
+            metrics: self.metrics,
         })
     }
 }
@@ -39,6 +84,14 @@ impl Cache<crate::store::Handle<Arc<crate::Store>>> {
 }
 
 impl<S> Cache<S> {
+    /// Return counters about the use of our pack and object caches since this instance was created.
+    ///
+    /// Note that these are counted for this instance only, and clones start counting from zero as they also receive new caches.
+    /// Only lookups in caches that are actually set are counted, which includes pack caches passed to
+    /// [`try_find_cached()`][git_pack::Find::try_find_cached()].
+    pub fn cache_metrics(&self) -> Metrics {
+        self.metrics.get()
+    }
     /// Dissolve this instance, discard all caches, and return the inner implementation.
     pub fn into_inner(self) -> S {
         self.inner
@@ -71,6 +124,42 @@ impl<S> Cache<S> {
         self.object_cache = Some(RefCell::new(create()));
         self.new_object_cache = Some(Arc::new(create));
     }
+    /// Replace the pack and object caches as well as their constructors with the ones configured by `options`.
+    ///
+    /// Caches that aren't available as the respective cargo features are disabled will be unset instead.
+    pub fn set_caches(&mut self, options: Options) {
+        #[cfg_attr(
+            not(any(
+                feature = "pack-cache-lru-dynamic",
+                feature = "pack-cache-lru-static",
+                feature = "object-cache-dynamic"
+            )),
+            allow(unused_variables)
+        )]
+        let free_list_limit = options.free_list_limit.unwrap_or(usize::MAX);
+        match options.pack_cache_bytes {
+            Some(0) => self.unset_pack_cache(),
+            #[cfg(feature = "pack-cache-lru-dynamic")]
+            Some(bytes) => self.set_pack_cache(move || {
+                Box::new(git_pack::cache::lru::MemoryCappedHashmap::new(bytes).with_free_list_limit(free_list_limit))
+            }),
+            #[cfg(feature = "pack-cache-lru-static")]
+            None => self.set_pack_cache(move || {
+                Box::new(git_pack::cache::lru::StaticLinkedList::<64>::default().with_free_list_limit(free_list_limit))
+            }),
+            #[allow(unreachable_patterns)]
+            _ => self.unset_pack_cache(),
+        }
+        match options.object_cache_bytes {
+            0 => self.unset_object_cache(),
+            #[cfg(feature = "object-cache-dynamic")]
+            bytes => self.set_object_cache(move || {
+                Box::new(git_pack::cache::object::MemoryCappedHashmap::new(bytes).with_free_list_limit(free_list_limit))
+            }),
+            #[cfg(not(feature = "object-cache-dynamic"))]
+            _ => self.unset_object_cache(),
+        }
+    }
     /// Remove the current pack cache as well as its constructor from this instance.
     pub fn unset_pack_cache(&mut self) {
         self.pack_cache = None;
@@ -94,6 +183,7 @@ where
             new_pack_cache: None,
             object_cache: None,
             new_object_cache: None,
+            metrics: Default::default(),
         }
     }
 }
@@ -106,6 +196,7 @@ impl<S: Clone> Clone for Cache<S> {
             new_object_cache: self.new_object_cache.clone(),
             pack_cache: self.new_pack_cache.as_ref().map(|create| RefCell::new(create())),
             object_cache: self.new_object_cache.as_ref().map(|create| RefCell::new(create())),
+            metrics: Default::default(),
         }
     }
 }
@@ -125,12 +216,13 @@ impl<S> DerefMut for Cache<S> {
 }
 
 mod impls {
-    use std::{io::Read, ops::DerefMut};
+    use std::{cell::Cell, io::Read, ops::DerefMut};
 
     use git_hash::{oid, ObjectId};
     use git_object::{Data, Kind};
     use git_pack::cache::Object;
 
+    use super::Metrics;
     use crate::{pack::data::entry::Location, Cache};
 
     impl<S> crate::Write for Cache<S>
@@ -176,7 +268,7 @@ mod impls {
         ) -> Result<Option<(Data<'a>, Option<Location>)>, Self::Error> {
             match self.pack_cache.as_ref().map(|rc| rc.borrow_mut()) {
                 Some(mut pack_cache) => self.try_find_cached(id, buffer, pack_cache.deref_mut()),
-                None => self.try_find_cached(id, buffer, &mut git_pack::cache::Never),
+                None => self.try_find_cached_inner(id.as_ref(), buffer, &mut git_pack::cache::Never),
             }
         }
 
@@ -186,30 +278,86 @@ mod impls {
             buffer: &'a mut Vec<u8>,
             pack_cache: &mut impl git_pack::cache::DecodeEntry,
         ) -> Result<Option<(Data<'a>, Option<git_pack::data::entry::Location>)>, Self::Error> {
+            self.try_find_cached_inner(
+                id.as_ref(),
+                buffer,
+                &mut CountingPackCache {
+                    inner: pack_cache,
+                    metrics: &self.metrics,
+                },
+            )
+        }
+
+        fn location_by_oid(&self, id: impl AsRef<oid>, buf: &mut Vec<u8>) -> Option<git_pack::data::entry::Location> {
+            self.inner.location_by_oid(id, buf)
+        }
+
+        fn pack_offsets_and_oid(&self, pack_id: u32) -> Option<Vec<(u64, git_hash::ObjectId)>> {
+            self.inner.pack_offsets_and_oid(pack_id)
+        }
+
+        fn entry_by_location(&self, location: &Location) -> Option<git_pack::find::Entry> {
+            self.inner.entry_by_location(location)
+        }
+    }
+
+    impl<S> Cache<S>
+    where
+        S: git_pack::Find,
+    {
+        fn try_find_cached_inner<'a>(
+            &self,
+            id: &oid,
+            buffer: &'a mut Vec<u8>,
+            pack_cache: &mut impl git_pack::cache::DecodeEntry,
+        ) -> Result<Option<(Data<'a>, Option<git_pack::data::entry::Location>)>, S::Error> {
             if let Some(mut obj_cache) = self.object_cache.as_ref().map(|rc| rc.borrow_mut()) {
-                if let Some(kind) = obj_cache.get(&id.as_ref().to_owned(), buffer) {
+                let kind = obj_cache.get(&id.to_owned(), buffer);
+                update(&self.metrics, |m| match kind {
+                    Some(_) => m.object_cache_hits += 1,
+                    None => m.object_cache_misses += 1,
+                });
+                if let Some(kind) = kind {
                     return Ok(Some((Data::new(kind, buffer), None)));
                 }
             }
-            let possibly_obj = self.inner.try_find_cached(id.as_ref(), buffer, pack_cache)?;
+            let possibly_obj = self.inner.try_find_cached(id, buffer, pack_cache)?;
             if let (Some(mut obj_cache), Some((obj, _location))) =
                 (self.object_cache.as_ref().map(|rc| rc.borrow_mut()), &possibly_obj)
             {
-                obj_cache.put(id.as_ref().to_owned(), obj.kind, obj.data);
+                obj_cache.put(id.to_owned(), obj.kind, obj.data);
             }
             Ok(possibly_obj)
         }
+    }
 
-        fn location_by_oid(&self, id: impl AsRef<oid>, buf: &mut Vec<u8>) -> Option<git_pack::data::entry::Location> {
-            self.inner.location_by_oid(id, buf)
-        }
+    /// A pack cache which counts hits and misses of the pack cache it wraps.
+    struct CountingPackCache<'a, C> {
+        inner: &'a mut C,
+        metrics: &'a Cell<Metrics>,
+    }
 
-        fn pack_offsets_and_oid(&self, pack_id: u32) -> Option<Vec<(u64, git_hash::ObjectId)>> {
-            self.inner.pack_offsets_and_oid(pack_id)
+    impl<C> git_pack::cache::DecodeEntry for CountingPackCache<'_, C>
+    where
+        C: git_pack::cache::DecodeEntry,
+    {
+        fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: Kind, compressed_size: usize) {
+            self.inner.put(pack_id, offset, data, kind, compressed_size)
         }
 
-        fn entry_by_location(&self, location: &Location) -> Option<git_pack::find::Entry> {
-            self.inner.entry_by_location(location)
+        fn get(&mut self, pack_id: u32, offset: u64, out: &mut Vec<u8>) -> Option<(Kind, usize)> {
+            let res = self.inner.get(pack_id, offset, out);
+            update(self.metrics, |m| match res {
+                Some(_) => m.pack_cache_hits += 1,
+                None => m.pack_cache_misses += 1,
+            });
+            res
         }
     }
+
+    fn update(metrics: &Cell<Metrics>, f: impl FnOnce(&mut Metrics)) {
+        let mut m = metrics.get();
+        f(&mut m);
+        metrics.set(m);
+    }
 }
@@ -13,7 +13,7 @@
 #![allow(deprecated)]
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     path::PathBuf,
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -41,6 +41,7 @@ pub struct Cache<S> {
     new_object_cache: Option<Arc<cache::NewObjectCacheFn>>,
     pack_cache: Option<RefCell<Box<cache::PackCache>>>,
     object_cache: Option<RefCell<Box<cache::ObjectCache>>>,
+    metrics: Cell<cache::Metrics>,
 }
 
 ///
@@ -65,6 +66,20 @@ pub fn sink(object_hash: git_hash::Kind) -> Sink {
 ///
 pub mod sink;
 
+/// A reader over the decompressed data of a single object which avoids holding all of it in memory where possible.
+///
+/// It's obtained with [`loose::Store::try_stream()`] or [`store::Handle::try_stream()`].
+pub struct Stream {
+    /// The kind of the object.
+    pub kind: git_object::Kind,
+    /// The size of the object's data in bytes, which is the amount of bytes that can be read.
+    pub size: u64,
+    inner: stream::Inner,
+}
+
+///
+pub mod stream;
+
 ///
 pub mod find;
 
@@ -116,10 +131,20 @@ pub struct Store {
 
     /// The amount of times we re-read the disk state to consolidate our in-memory representation.
     pub(crate) num_disk_state_consolidation: AtomicUsize,
+    /// Counters about object access through all handles.
+    pub(crate) access: types::AccessCounters,
     /// If true, we are allowed to use multi-pack indices and they must have the `object_hash` or be ignored.
     use_multi_pack_index: bool,
     /// The hash kind to use for some operations
     object_hash: git_hash::Kind,
+    /// The options to use when writing loose objects
+    loose_write_options: loose::write::Options,
+    /// Pairs of original object ids and their replacements, sorted by the original id.
+    replacements: Vec<(git_hash::ObjectId, git_hash::ObjectId)>,
+    /// The refresh mode new handles are created with.
+    refresh: store::RefreshMode,
+    /// How pack data files are accessed once loaded.
+    pack_mapping: git_pack::data::Mapping,
 }
 
 /// Create a new cached handle to the object store with support for additional options.
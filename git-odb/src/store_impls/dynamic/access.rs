@@ -15,4 +15,14 @@ impl Store {
     pub fn use_multi_pack_index(&self) -> bool {
         self.use_multi_pack_index
     }
+
+    /// How the data of pack files is accessed after loading them.
+    pub fn pack_mapping(&self) -> git_pack::data::Mapping {
+        self.pack_mapping
+    }
+
+    /// An iterator over pairs of original object ids and the ids of the objects replacing them, sorted by original id.
+    pub fn replacements(&self) -> impl Iterator<Item = (git_hash::ObjectId, git_hash::ObjectId)> + '_ {
+        self.replacements.iter().cloned()
+    }
 }
@@ -0,0 +1,94 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    ops::Deref,
+    sync::Arc,
+};
+
+use git_hash::{oid, ObjectId};
+
+/// The minimum amount of objects sharing a fan-out directory to list the directory instead of checking each object path.
+const MIN_OBJECTS_TO_LIST_LOOSE_DIR: usize = 8;
+
+/// Batched existence checks
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = super::Store> + Clone,
+{
+    /// Return whether each of the given `ids` exists in the object database, in the order of `ids`.
+    ///
+    /// Just like [`contains()`][crate::Find::contains()], only pack indices, multi-pack indices and entries of loose
+    /// object directories are consulted, and no object data is mapped or decoded. However, all loaded indices are searched
+    /// in one pass, each loose object directory is checked once, and more indices are only loaded while objects are still
+    /// missing. Fan-out directories of loose object databases that would be checked for many objects are listed instead
+    /// of checking the path of each object. This makes it suitable for connectivity checks and negotiations with many objects.
+    ///
+    /// Note that replacements aren't applied and missing objects aren't fetched from promisor remotes.
+    pub fn contains_many(&self, ids: impl IntoIterator<Item = impl AsRef<oid>>) -> Vec<bool> {
+        let ids: Vec<ObjectId> = ids.into_iter().map(|id| id.as_ref().to_owned()).collect();
+        let mut found = vec![false; ids.len()];
+        let mut missing: Vec<usize> = (0..ids.len()).collect();
+        let mut snapshot = self.snapshot.borrow_mut();
+        let mut checked_loose_dbs: Option<Arc<Vec<crate::loose::Store>>> = None;
+        while !missing.is_empty() {
+            missing.retain(|&idx| {
+                let is_found = snapshot.indices.iter().any(|index| index.contains(&ids[idx]));
+                found[idx] = is_found;
+                !is_found
+            });
+            if missing.is_empty() {
+                break;
+            }
+
+            // Loose databases may only appear once the first indices are loaded, so check them whenever they change.
+            if checked_loose_dbs
+                .as_ref()
+                .map_or(true, |dbs| !Arc::ptr_eq(dbs, &snapshot.loose_dbs))
+            {
+                checked_loose_dbs = Some(Arc::clone(&snapshot.loose_dbs));
+                for lodb in snapshot.loose_dbs.iter() {
+                    let mut missing_by_fan_out = BTreeMap::<u8, Vec<usize>>::new();
+                    for &idx in &missing {
+                        missing_by_fan_out.entry(ids[idx].first_byte()).or_default().push(idx);
+                    }
+                    for (fan_out, indices) in missing_by_fan_out {
+                        if indices.len() < MIN_OBJECTS_TO_LIST_LOOSE_DIR {
+                            for idx in indices {
+                                found[idx] = lodb.contains(&ids[idx]);
+                            }
+                        } else {
+                            let names = loose_object_names(&lodb.path().join(format!("{:02x}", fan_out)));
+                            for idx in indices {
+                                found[idx] = names.contains(&ids[idx].to_hex().to_string()[2..]);
+                            }
+                        }
+                    }
+                    missing.retain(|&idx| !found[idx]);
+                }
+                if missing.is_empty() {
+                    break;
+                }
+            }
+
+            match self.store.load_one_index(self.refresh, snapshot.marker) {
+                Ok(Some(new_snapshot)) => {
+                    *snapshot = new_snapshot;
+                }
+                Ok(None) => break, // nothing more to load, or our refresh mode doesn't allow disk refreshes
+                Err(_) => break,   // something went wrong, nothing we can communicate here just like with `contains()`
+            }
+        }
+        found
+    }
+}
+
+/// Return the names of all files in the fan-out directory at `path`, or an empty set if it couldn't be read.
+fn loose_object_names(path: &std::path::Path) -> HashSet<String> {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
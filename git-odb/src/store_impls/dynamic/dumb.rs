@@ -0,0 +1,318 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    io,
+    io::Read,
+    ops::Deref,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use git_features::{progress::Progress, zlib};
+use git_hash::{oid, ObjectId};
+use git_object::{tree::EntryMode, ObjectRef};
+use git_pack::{data, index};
+
+use crate::{
+    store::{self, RefreshMode},
+    Find, Write,
+};
+
+mod error {
+    use git_hash::ObjectId;
+
+    use crate::store;
+
+    /// The error returned by [`fetch()`][super::fetch()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("An IO error occurred when transferring a file from the remote or writing it locally")]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Find(#[from] store::find::Error),
+        #[error(transparent)]
+        Write(#[from] store::write::Error),
+        #[error("The header of the loose object {id} fetched from the remote could not be decoded")]
+        LooseHeaderDecode {
+            source: git_object::decode::LooseHeaderDecodeError,
+            id: ObjectId,
+        },
+        #[error("The loose object fetched as {expected} actually hashed to {actual}")]
+        ObjectHashMismatch { expected: ObjectId, actual: ObjectId },
+        #[error("The object {id} could not be decoded to find the objects it refers to")]
+        ObjectDecode {
+            source: git_object::decode::Error,
+            id: ObjectId,
+        },
+        #[error("The index of the remote pack '{name}' could not be opened")]
+        IndexOpen {
+            source: git_pack::index::init::Error,
+            name: String,
+        },
+        #[error("The remote pack '{name}' could not be opened")]
+        PackOpen {
+            source: git_pack::data::header::decode::Error,
+            name: String,
+        },
+        #[error(transparent)]
+        Checksum(#[from] git_pack::verify::checksum::Error),
+        #[error("The remote pack '{name}' has checksum {actual}, but its index expected {expected}")]
+        PackChecksumMismatch {
+            name: String,
+            expected: ObjectId,
+            actual: ObjectId,
+        },
+        #[error("The object {id} could neither be found as loose object nor in any pack of the remote")]
+        NotFound { id: ObjectId },
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Read-only access to the files of a remote repository, as provided by servers that speak the dumb protocol.
+pub trait Remote {
+    /// Return a reader over the file at `path` relative to the remote `$GIT_DIR`, like `objects/info/packs`,
+    /// or `None` if it doesn't exist.
+    fn get(&mut self, path: &str) -> io::Result<Option<Box<dyn io::Read + '_>>>;
+}
+
+/// The outcome of [`fetch()`].
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The amount of objects reachable from the tips, all of which are now present locally.
+    pub num_objects: usize,
+    /// The amount of loose objects that were fetched from the remote.
+    pub num_loose_objects: usize,
+    /// The names of the remote packs which were fetched and installed locally, like `pack-<hash>.pack`.
+    pub packs: Vec<String>,
+}
+
+/// A pack listed by the remote in `objects/info/packs`.
+struct RemotePack {
+    /// The file name of the pack data file, like `pack-<hash>.pack`.
+    name: String,
+    /// The index of the pack along with the temporary file it was downloaded to, once it was needed.
+    index: Option<(index::File, tempfile::TempPath)>,
+    /// If true, the pack is installed in the local object database and must not be looked at anymore.
+    installed: bool,
+}
+
+/// Walk all objects reachable from `tips` and fetch those that are missing in `odb` from `remote`, the way git does it
+/// when cloning or fetching from a remote speaking the dumb protocol.
+///
+/// Each missing object is first fetched as loose object, which is verified and written to `odb`. If it doesn't exist,
+/// the packs listed in the remotes `objects/info/packs` file are tried instead, downloading their indices as needed
+/// and installing the pack containing the object after verifying its checksum.
+///
+/// Note that objects that are already present in `odb` are traversed as well, which is why this is best used for new clones.
+pub fn fetch<S>(
+    odb: &store::Handle<S>,
+    remote: &mut impl Remote,
+    tips: impl IntoIterator<Item = ObjectId>,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<Outcome, Error>
+where
+    S: Deref<Target = crate::Store> + Clone,
+{
+    let start = Instant::now();
+    // We must see packs we install ourselves, no matter what the handle is configured to do.
+    let mut odb = odb.clone();
+    odb.refresh = RefreshMode::AfterAllIndicesLoaded;
+
+    let mut remote_packs = None;
+    let mut outcome = Outcome {
+        num_objects: 0,
+        num_loose_objects: 0,
+        packs: Vec::new(),
+    };
+    let mut seen: HashSet<_> = HashSet::new();
+    let mut queue: VecDeque<_> = tips.into_iter().filter(|id| seen.insert(*id)).collect();
+    let mut buf = Vec::new();
+    progress.init(None, git_features::progress::count("objects"));
+
+    while let Some(id) = queue.pop_front() {
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+        if !odb.contains(&id) {
+            if fetch_loose(&odb, remote, &id)? {
+                outcome.num_loose_objects += 1;
+            } else {
+                if remote_packs.is_none() {
+                    remote_packs = Some(list_packs(remote)?);
+                }
+                let packs = remote_packs.as_mut().expect("just set");
+                let name =
+                    fetch_pack_containing(&odb, remote, packs, &id, should_interrupt)?.ok_or(Error::NotFound { id })?;
+                outcome.packs.push(name);
+            }
+        }
+
+        let object = odb.try_find(&id, &mut buf)?.ok_or(Error::NotFound { id })?;
+        match object.decode().map_err(|err| Error::ObjectDecode { source: err, id })? {
+            ObjectRef::Commit(commit) => {
+                queue.extend(
+                    std::iter::once(commit.tree())
+                        .chain(commit.parents())
+                        .filter(|id| seen.insert(*id)),
+                );
+            }
+            ObjectRef::Tree(tree) => queue.extend(
+                tree.entries
+                    .iter()
+                    .filter(|entry| entry.mode != EntryMode::Commit)
+                    .map(|entry| entry.oid.to_owned())
+                    .filter(|id| seen.insert(*id)),
+            ),
+            ObjectRef::Tag(tag) => {
+                let target = tag.target();
+                if seen.insert(target) {
+                    queue.push_back(target);
+                }
+            }
+            ObjectRef::Blob(_) => {}
+        }
+        outcome.num_objects += 1;
+        progress.inc();
+    }
+
+    progress.show_throughput(start);
+    Ok(outcome)
+}
+
+/// Fetch `id` as loose object from `remote` and write it into `odb` after validating its hash,
+/// or return `false` if the remote doesn't have it.
+fn fetch_loose<S>(odb: &store::Handle<S>, remote: &mut impl Remote, id: &oid) -> Result<bool, Error>
+where
+    S: Deref<Target = crate::Store> + Clone,
+{
+    let hex = id.to_hex().to_string();
+    let read = match remote.get(&format!("objects/{}/{}", &hex[..2], &hex[2..]))? {
+        Some(read) => read,
+        None => return Ok(false),
+    };
+    let mut data = Vec::new();
+    zlib::stream::inflate::ReadBoxed {
+        inner: io::BufReader::new(read),
+        decompressor: Box::new(zlib::Decompress::new(true)),
+    }
+    .read_to_end(&mut data)?;
+    let (kind, size, header_size) =
+        git_object::decode::loose_header(&data).map_err(|err| Error::LooseHeaderDecode {
+            source: err,
+            id: id.to_owned(),
+        })?;
+    let object_data = data.get(header_size..header_size + size).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("loose object {} is shorter than its header claims", id),
+        )
+    })?;
+    let actual = odb.write_buf(kind, object_data)?;
+    if actual.as_ref() != id {
+        return Err(Error::ObjectHashMismatch {
+            expected: id.to_owned(),
+            actual,
+        });
+    }
+    Ok(true)
+}
+
+/// Parse the remotes `objects/info/packs` file, which may not exist if there are no packs.
+fn list_packs(remote: &mut impl Remote) -> Result<Vec<RemotePack>, Error> {
+    let mut content = String::new();
+    if let Some(mut read) = remote.get("objects/info/packs")? {
+        read.read_to_string(&mut content)?;
+    }
+    Ok(content
+        .lines()
+        .filter_map(|line| line.strip_prefix("P "))
+        .map(|name| RemotePack {
+            name: name.trim().to_owned(),
+            index: None,
+            installed: false,
+        })
+        .collect())
+}
+
+/// Find the not-yet-installed remote pack containing `id`, download it into the local pack directory along with its index
+/// and return its name, or `None` if no remote pack contains `id`.
+fn fetch_pack_containing<S>(
+    odb: &store::Handle<S>,
+    remote: &mut impl Remote,
+    packs: &mut [RemotePack],
+    id: &oid,
+    should_interrupt: &AtomicBool,
+) -> Result<Option<String>, Error>
+where
+    S: Deref<Target = crate::Store> + Clone,
+{
+    let pack_dir = odb.store_ref().path().join("pack");
+    for pack in packs.iter_mut().filter(|pack| !pack.installed) {
+        let index_name = pack.name.replace(".pack", ".idx");
+        if pack.index.is_none() {
+            let mut read = match remote.get(&format!("objects/pack/{}", index_name))? {
+                Some(read) => read,
+                None => {
+                    pack.installed = true;
+                    continue;
+                }
+            };
+            std::fs::create_dir_all(&pack_dir)?;
+            let mut file = tempfile::NamedTempFile::new_in(&pack_dir)?;
+            io::copy(&mut read, &mut file)?;
+            let path = file.into_temp_path();
+            let index = index::File::at(&path, odb.store_ref().object_hash()).map_err(|err| Error::IndexOpen {
+                source: err,
+                name: index_name.clone(),
+            })?;
+            index.verify_checksum(git_features::progress::Discard, should_interrupt)?;
+            pack.index = Some((index, path));
+        }
+
+        let (index, _) = pack.index.as_ref().expect("index was just fetched");
+        if index.lookup(id).is_none() {
+            continue;
+        }
+        let mut read = remote.get(&format!("objects/pack/{}", pack.name))?.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("remote pack '{}' is missing", pack.name),
+            )
+        })?;
+        let mut file = tempfile::NamedTempFile::new_in(&pack_dir)?;
+        io::copy(&mut read, &mut file)?;
+        let pack_path = file.into_temp_path();
+        let data = data::File::at_with_mapping(
+            &pack_path,
+            odb.store_ref().object_hash(),
+            odb.store_ref().pack_mapping(),
+        )
+        .map_err(|err| Error::PackOpen {
+            source: err,
+            name: pack.name.clone(),
+        })?;
+        let actual = data.verify_checksum(git_features::progress::Discard, should_interrupt)?;
+        if actual != index.pack_checksum() {
+            return Err(Error::PackChecksumMismatch {
+                name: pack.name.clone(),
+                expected: index.pack_checksum(),
+                actual,
+            });
+        }
+        drop(data);
+
+        // The pack goes first, as an index without its pack is considered corrupt.
+        pack_path.persist(pack_dir.join(&pack.name)).map_err(|err| err.error)?;
+        let (_index, index_path) = pack.index.take().expect("present");
+        index_path
+            .persist(pack_dir.join(&index_name))
+            .map_err(|err| err.error)?;
+        pack.installed = true;
+        return Ok(Some(pack.name.clone()));
+    }
+    Ok(None)
+}
@@ -1,4 +1,4 @@
-use std::{convert::TryInto, ops::Deref};
+use std::{convert::TryInto, ops::Deref, sync::atomic::Ordering};
 
 use git_hash::{oid, ObjectId};
 use git_object::Data;
@@ -30,6 +30,20 @@ mod error {
             /// The original object to lookup
             id: git_hash::ObjectId,
         },
+        #[error("Could not fetch missing object {} from the promisor remote", .id)]
+        LazyFetch {
+            #[source]
+            err: Box<dyn std::error::Error + Send + Sync + 'static>,
+            /// The object that was missing
+            id: git_hash::ObjectId,
+        },
+        #[error("Replacements of object {} are nested more than {} levels deep", .id, .max_depth)]
+        ReplacementDepthExceeded {
+            /// the maximum amount of replacements to follow.
+            max_depth: usize,
+            /// The original object to lookup
+            id: git_hash::ObjectId,
+        },
         #[error("Reached recursion limit of {} while resolving ref delta bases for {}", .max_depth, .id)]
         DeltaBaseRecursionLimit {
             /// the maximum recursion depth we encountered.
@@ -80,7 +94,7 @@ mod error {
 
         #[test]
         fn error_size() {
-            assert_eq!(std::mem::size_of::<Error>(), 88, "should not grow without us noticing");
+            assert_eq!(std::mem::size_of::<Error>(), 104, "should not grow without us noticing");
         }
     }
 }
@@ -92,6 +106,29 @@ use crate::{
     Find,
 };
 
+impl super::Store {
+    /// The maximum amount of replacements to follow, the same as git uses.
+    const MAX_REPLACEMENT_DEPTH: usize = 5;
+
+    /// Return the id of the object to use in place of `id`, following replacements of replacements, or `id` itself if it isn't replaced.
+    pub(crate) fn replacement_of<'a>(&'a self, id: &'a oid) -> Result<&'a oid, Error> {
+        let mut current = id;
+        for _ in 0..=Self::MAX_REPLACEMENT_DEPTH {
+            match self
+                .replacements
+                .binary_search_by(|(original, _)| original.as_ref().cmp(current))
+            {
+                Ok(idx) => current = self.replacements[idx].1.as_ref(),
+                Err(_) => return Ok(current),
+            }
+        }
+        Err(Error::ReplacementDepthExceeded {
+            max_depth: Self::MAX_REPLACEMENT_DEPTH,
+            id: id.to_owned(),
+        })
+    }
+}
+
 impl<S> super::Handle<S>
 where
     S: Deref<Target = super::Store> + Clone,
@@ -168,6 +205,71 @@ where
         }
     }
 
+    /// Return a [`Stream`][crate::Stream] over the decompressed data of the object identified by `id`, or `None` if it doesn't exist.
+    ///
+    /// Loose objects and packed objects which aren't deltified are decompressed while reading, allowing to access huge objects
+    /// like blobs without holding them in memory. Deltified objects need to be resolved and are spooled into memory entirely.
+    pub fn try_stream(&self, id: impl AsRef<oid>) -> Result<Option<crate::Stream>, Error> {
+        let id = id.as_ref();
+        let id = if self.ignore_replacements {
+            id
+        } else {
+            self.store.replacement_of(id)?
+        };
+        if let Some(stream) = self.try_stream_undeltified(id)? {
+            return Ok(Some(stream));
+        }
+        let mut buf = Vec::new();
+        let kind = match git_pack::Find::try_find(self, id, &mut buf)? {
+            Some((data, _location)) => data.kind,
+            None => return Ok(None),
+        };
+        Ok(Some(crate::Stream::from_data(kind, buf)))
+    }
+
+    /// Stream loose objects or undeltified objects in packs, or return `None` if the object is deltified or doesn't exist.
+    fn try_stream_undeltified(&self, id: &oid) -> Result<Option<crate::Stream>, Error> {
+        let mut snapshot = self.snapshot.borrow_mut();
+        loop {
+            let marker = snapshot.marker;
+            for index in snapshot.indices.iter_mut() {
+                if let Some(handle::index_lookup::Outcome {
+                    object_index: handle::IndexForObjectInPack { pack_id, pack_offset },
+                    index_file: _,
+                    pack: possibly_pack,
+                }) = index.lookup(id)
+                {
+                    let pack = match possibly_pack {
+                        Some(pack) => pack.clone(),
+                        None => match self.store.load_pack(pack_id, marker)? {
+                            Some(pack) => {
+                                *possibly_pack = Some(pack.clone());
+                                pack
+                            }
+                            None => return Ok(None),
+                        },
+                    };
+                    let entry = pack.entry(pack_offset)?;
+                    return Ok(entry
+                        .header
+                        .as_kind()
+                        .map(|kind| crate::Stream::from_pack_entry(kind, pack, &entry)));
+                }
+            }
+
+            for lodb in snapshot.loose_dbs.iter() {
+                if let Some(stream) = lodb.try_stream(id)? {
+                    return Ok(Some(stream));
+                }
+            }
+
+            match self.store.load_one_index(self.refresh, snapshot.marker)? {
+                Some(new_snapshot) => *snapshot = new_snapshot,
+                None => return Ok(None),
+            }
+        }
+    }
+
     fn try_find_cached_inner<'a>(
         &self,
         id: &git_hash::oid,
@@ -184,6 +286,7 @@ where
                 });
             }
         }
+        let mut did_fetch = false;
         'outer: loop {
             {
                 let marker = snapshot.marker;
@@ -218,7 +321,7 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack.entry(pack_offset)?;
                         let header_size = entry.header_size();
                         let res = match pack.decode_entry(
                             entry,
@@ -226,21 +329,25 @@ where
                             |id, _out| {
                                 index_file
                                     .pack_offset_by_id(id)
-                                    .map(|pack_offset| git_pack::data::ResolvedBase::InPack(pack.entry(pack_offset)))
+                                    .and_then(|pack_offset| pack.entry(pack_offset).ok())
+                                    .map(git_pack::data::ResolvedBase::InPack)
                             },
                             pack_cache,
                         ) {
-                            Ok(r) => Ok((
-                                git_object::Data {
-                                    kind: r.kind,
-                                    data: buffer.as_slice(),
-                                },
-                                Some(git_pack::data::entry::Location {
-                                    pack_id: pack.id,
-                                    pack_offset,
-                                    entry_size: r.compressed_size + header_size,
-                                }),
-                            )),
+                            Ok(r) => {
+                                self.store.access.record_packed_object(&r);
+                                Ok((
+                                    git_object::Data {
+                                        kind: r.kind,
+                                        data: buffer.as_slice(),
+                                    },
+                                    Some(git_pack::data::entry::Location {
+                                        pack_id: pack.id,
+                                        pack_offset,
+                                        entry_size: r.compressed_size + header_size,
+                                    }),
+                                ))
+                            }
                             Err(git_pack::data::decode_entry::Error::DeltaBaseUnresolved(base_id)) => {
                                 // Only with multi-pack indices it's allowed to jump to refer to other packs within this
                                 // multi-pack. Otherwise this would constitute a thin pack which is only allowed in transit.
@@ -288,7 +395,7 @@ where
                                 let pack = possibly_pack
                                     .as_ref()
                                     .expect("pack to still be available like just now");
-                                let entry = pack.entry(pack_offset);
+                                let entry = pack.entry(pack_offset)?;
                                 let header_size = entry.header_size();
                                 pack.decode_entry(
                                     entry,
@@ -296,9 +403,8 @@ where
                                     |id, out| {
                                         index_file
                                             .pack_offset_by_id(id)
-                                            .map(|pack_offset| {
-                                                git_pack::data::ResolvedBase::InPack(pack.entry(pack_offset))
-                                            })
+                                            .and_then(|pack_offset| pack.entry(pack_offset).ok())
+                                            .map(git_pack::data::ResolvedBase::InPack)
                                             .or_else(|| {
                                                 (id == base_id).then(|| {
                                                     out.resize(buf.len(), 0);
@@ -313,6 +419,7 @@ where
                                     pack_cache,
                                 )
                                 .map(move |r| {
+                                    self.store.access.record_packed_object(&r);
                                     (
                                         git_object::Data {
                                             kind: r.kind,
@@ -342,7 +449,12 @@ where
                 if lodb.contains(id) {
                     return lodb
                         .try_find(id, buffer)
-                        .map(|obj| obj.map(|obj| (obj, None)))
+                        .map(|obj| {
+                            obj.map(|obj| {
+                                self.store.access.loose_objects.fetch_add(1, Ordering::Relaxed);
+                                (obj, None)
+                            })
+                        })
                         .map_err(Into::into);
                 }
             }
@@ -351,7 +463,19 @@ where
                 Some(new_snapshot) => {
                     *snapshot = new_snapshot;
                 }
-                None => return Ok(None),
+                None => match self.lazy_fetch.as_ref().filter(|_| !did_fetch) {
+                    Some(fetch) => {
+                        fetch(id).map_err(|err| Error::LazyFetch { err, id: id.to_owned() })?;
+                        did_fetch = true;
+                        if let Some(new_snapshot) = self
+                            .store
+                            .load_one_index(super::RefreshMode::AfterAllIndicesLoaded, snapshot.marker)?
+                        {
+                            *snapshot = new_snapshot;
+                        }
+                    }
+                    None => return Ok(None),
+                },
             }
         }
     }
@@ -400,6 +524,11 @@ where
         pack_cache: &mut impl DecodeEntry,
     ) -> Result<Option<(Data<'a>, Option<Location>)>, Self::Error> {
         let id = id.as_ref();
+        let id = if self.ignore_replacements {
+            id
+        } else {
+            self.store.replacement_of(id)?
+        };
         let mut snapshot = self.snapshot.borrow_mut();
         self.try_find_cached_inner(id, buffer, pack_cache, &mut snapshot, None)
     }
@@ -445,7 +574,7 @@ where
                                 }
                             },
                         };
-                        let entry = pack.entry(pack_offset);
+                        let entry = pack.entry(pack_offset).ok()?;
 
                         buf.resize(entry.decompressed_size.try_into().expect("representable size"), 0);
                         assert_eq!(pack.id, pack_id.to_intrinsic_pack_id(), "both ids must always match");
@@ -526,7 +655,7 @@ where
                         return pack
                             .entry_slice(location.entry_range(location.pack_offset))
                             .map(|data| git_pack::find::Entry {
-                                data: data.to_owned(),
+                                data: data.to_vec(),
                                 version: pack.version(),
                             });
                     }
@@ -245,10 +245,12 @@ impl super::Store {
         let token = self.register_handle();
         super::Handle {
             store: self.clone(),
-            refresh: RefreshMode::default(),
+            refresh: self.refresh,
             token: Some(token),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
+            lazy_fetch: None,
+            ignore_replacements: false,
         }
     }
 
@@ -259,10 +261,12 @@ impl super::Store {
         let token = self.register_handle();
         super::Handle {
             store: self.clone(),
-            refresh: Default::default(),
+            refresh: self.refresh,
             token: Some(token),
             snapshot: RefCell::new(self.collect_snapshot()),
             max_recursion_depth: Self::INITIAL_MAX_RECURSION_DEPTH,
+            lazy_fetch: None,
+            ignore_replacements: false,
         }
     }
 
@@ -310,7 +314,7 @@ where
     }
 
     /// Return the current refresh mode.
-    pub fn refresh_mode(&mut self) -> RefreshMode {
+    pub fn refresh_mode(&self) -> RefreshMode {
         self.refresh
     }
 }
@@ -336,6 +340,10 @@ impl TryFrom<&super::Store> for super::Store {
                 slots: crate::store::init::Slots::Given(s.files.len().try_into().expect("BUG: too many slots")),
                 object_hash: Default::default(),
                 use_multi_pack_index: false,
+                loose_write_options: s.loose_write_options,
+                replacements: s.replacements.clone(),
+                refresh: s.refresh,
+                pack_mapping: s.pack_mapping,
             },
         )
     }
@@ -348,6 +356,8 @@ impl super::Handle<Rc<super::Store>> {
         let mut cache = store.to_handle_arc();
         cache.refresh = self.refresh;
         cache.max_recursion_depth = self.max_recursion_depth;
+        cache.lazy_fetch = self.lazy_fetch.clone();
+        cache.ignore_replacements = self.ignore_replacements;
         Ok(cache)
     }
 }
@@ -377,6 +387,8 @@ where
             },
             snapshot: RefCell::new(self.store.collect_snapshot()),
             max_recursion_depth: self.max_recursion_depth,
+            lazy_fetch: self.lazy_fetch.clone(),
+            ignore_replacements: self.ignore_replacements,
         }
     }
 }
@@ -3,12 +3,15 @@ use std::{iter::FromIterator, path::PathBuf, sync::Arc};
 use arc_swap::ArcSwap;
 
 use crate::{
-    store::types::{MutableIndexAndPack, SlotMapIndex},
+    store::{
+        types::{MutableIndexAndPack, SlotMapIndex},
+        RefreshMode,
+    },
     Store,
 };
 
 /// Options for use in [`Store::at_opts()`].
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Options {
     /// How to obtain a size for the slot map.
     pub slots: Slots,
@@ -16,6 +19,19 @@ pub struct Options {
     pub object_hash: git_hash::Kind,
     /// If false, no multi-pack indices will be used. If true, they will be used if their hash matches `object_hash`.
     pub use_multi_pack_index: bool,
+    /// Control durability and permissions of loose objects written through this store.
+    pub loose_write_options: crate::loose::write::Options,
+    /// A list of pairs of the original object id and the id of the object to return in its place, similar to what git
+    /// reads from `refs/replace/*`.
+    ///
+    /// Lookups through handles to the store will transparently return the replacement object unless
+    /// [`ignore_replacements`][crate::store::Handle::ignore_replacements] is set.
+    pub replacements: Vec<(git_hash::ObjectId, git_hash::ObjectId)>,
+    /// The refresh mode handles to this store start out with, which decides if a miss re-scans the objects directory
+    /// for packs created by other processes in the mean time.
+    pub refresh: RefreshMode,
+    /// How the data of packs is made accessible, which allows to limit the address space used for huge packs.
+    pub pack_mapping: git_pack::data::Mapping,
 }
 
 impl Default for Options {
@@ -24,6 +40,10 @@ impl Default for Options {
             slots: Default::default(),
             object_hash: Default::default(),
             use_multi_pack_index: true,
+            loose_write_options: Default::default(),
+            replacements: Vec::new(),
+            refresh: RefreshMode::default(),
+            pack_mapping: Default::default(),
         }
     }
 }
@@ -69,6 +89,10 @@ impl Store {
             slots,
             object_hash,
             use_multi_pack_index,
+            loose_write_options,
+            mut replacements,
+            refresh,
+            pack_mapping,
         }: Options,
     ) -> std::io::Result<Self> {
         let objects_dir = objects_dir.into();
@@ -97,6 +121,8 @@ impl Store {
                 "Cannot use more than 1^15 slots",
             ));
         }
+        replacements.sort_by(|a, b| a.0.cmp(&b.0));
+        replacements.dedup_by(|a, b| a.0 == b.0);
         Ok(Store {
             write: Default::default(),
             path: objects_dir,
@@ -104,9 +130,14 @@ impl Store {
             index: ArcSwap::new(Arc::new(SlotMapIndex::default())),
             use_multi_pack_index,
             object_hash,
+            loose_write_options,
+            replacements,
+            refresh,
+            pack_mapping,
             num_handles_stable: Default::default(),
             num_handles_unstable: Default::default(),
             num_disk_state_consolidation: Default::default(),
+            access: Default::default(),
         })
     }
 }
@@ -0,0 +1,66 @@
+use std::{
+    ffi::OsStr,
+    io,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::Store;
+
+/// Keep files
+///
+/// A pack with a `.keep` file next to it is never removed or rolled up into another pack by maintenance like
+/// [geometric repacks][Store::geometric_repack()]. Pack paths may point to any file of the pack, like its index or data
+/// file, and relative paths are relative to our `pack` directory.
+impl Store {
+    /// Mark the pack at `path` as kept by writing `reason` into its `.keep` file, and return the path to it.
+    ///
+    /// If the pack is already kept, its keep file is left unchanged.
+    pub fn keep_pack(&self, path: impl AsRef<Path>, reason: &str) -> io::Result<PathBuf> {
+        let keep_path = self.keep_path(path.as_ref());
+        let mut file = match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&keep_path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => return Ok(keep_path),
+            Err(err) => return Err(err),
+        };
+        if !reason.is_empty() {
+            writeln!(file, "{}", reason)?;
+        }
+        Ok(keep_path)
+    }
+
+    /// Remove the `.keep` file of the pack at `path` to let maintenance handle it again, and return true if there was one.
+    pub fn unkeep_pack(&self, path: impl AsRef<Path>) -> io::Result<bool> {
+        match std::fs::remove_file(self.keep_path(path.as_ref())) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Return true if the pack at `path` has a `.keep` file.
+    pub fn is_pack_kept(&self, path: impl AsRef<Path>) -> bool {
+        self.keep_path(path.as_ref()).is_file()
+    }
+
+    /// Return the paths to the indices of all packs in our `pack` directory that have a `.keep` file, sorted by path.
+    pub fn kept_packs(&self) -> io::Result<Vec<PathBuf>> {
+        let mut out = Vec::new();
+        for entry in std::fs::read_dir(self.path.join("pack"))? {
+            let path = entry?.path();
+            if path.extension() == Some(OsStr::new("keep")) && path.with_extension("idx").is_file() {
+                out.push(path.with_extension("idx"));
+            }
+        }
+        out.sort();
+        Ok(out)
+    }
+
+    fn keep_path(&self, path: &Path) -> PathBuf {
+        self.path.join("pack").join(path).with_extension("keep")
+    }
+}
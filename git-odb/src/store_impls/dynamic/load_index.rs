@@ -212,7 +212,7 @@ impl super::Store {
             Arc::new(
                 db_paths
                     .iter()
-                    .map(|path| crate::loose::Store::at(path, self.object_hash))
+                    .map(|path| crate::loose::Store::at_opts(path, self.object_hash, self.loose_write_options))
                     .collect::<Vec<_>>(),
             )
         } else {
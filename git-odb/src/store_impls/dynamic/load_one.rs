@@ -1,6 +1,9 @@
 use std::{
     path::Path,
-    sync::{atomic::Ordering, Arc},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use crate::store::{handle, types};
@@ -24,10 +27,13 @@ impl super::Store {
             path: &Path,
             id: types::PackId,
             object_hash: git_hash::Kind,
+            mapping: git_pack::data::Mapping,
+            num_packs_mapped: &AtomicU64,
         ) -> std::io::Result<Arc<git_pack::data::File>> {
-            git_pack::data::File::at(path, object_hash)
+            git_pack::data::File::at_with_mapping(path, object_hash, mapping)
                 .map(|mut pack| {
                     pack.id = id.to_intrinsic_pack_id();
+                    num_packs_mapped.fetch_add(1, Ordering::Relaxed);
                     Arc::new(pack)
                 })
                 .map_err(|err| match err {
@@ -56,9 +62,17 @@ impl super::Store {
                                 let mut files = slot.files.load_full();
                                 let files_mut = Arc::make_mut(&mut files);
                                 let pack = match files_mut {
-                                    Some(types::IndexAndPacks::Index(bundle)) => bundle
-                                        .data
-                                        .load_with_recovery(|path| load_pack(path, id, self.object_hash))?,
+                                    Some(types::IndexAndPacks::Index(bundle)) => {
+                                        bundle.data.load_with_recovery(|path| {
+                                            load_pack(
+                                                path,
+                                                id,
+                                                self.object_hash,
+                                                self.pack_mapping,
+                                                &self.access.packs_mapped,
+                                            )
+                                        })?
+                                    }
                                     Some(types::IndexAndPacks::MultiIndex(_)) => {
                                         // something changed between us getting the lock, trigger a complete index refresh.
                                         None
@@ -100,7 +114,15 @@ impl super::Store {
                                             .data
                                             .get_mut(pack_index as usize)
                                             .expect("BUG: must set this handle to be stable")
-                                            .load_with_recovery(|path| load_pack(path, id, self.object_hash))?,
+                                            .load_with_recovery(|path| {
+                                                load_pack(
+                                                    path,
+                                                    id,
+                                                    self.object_hash,
+                                                    self.pack_mapping,
+                                                    &self.access.packs_mapped,
+                                                )
+                                            })?,
                                         None => {
                                             unreachable!("BUG: must set this handle to be stable to avoid slots to be cleared/changed")
                                         }
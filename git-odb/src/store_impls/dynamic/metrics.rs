@@ -83,4 +83,12 @@ impl super::Store {
             unreachable_packs,
         }
     }
+
+    /// Return counters about object access through all handles of this store since it was created, collected in a racy fashion.
+    ///
+    /// Use this to monitor object access performance in long-running services, possibly by comparing snapshots taken
+    /// at different times.
+    pub fn access_metrics(&self) -> types::AccessMetrics {
+        self.access.snapshot()
+    }
 }
@@ -18,13 +18,18 @@ where
     /// This value doesn't have to be huge as in typical scenarios, these kind of objects are rare and chains supposedly are
     /// even more rare.
     pub max_recursion_depth: usize,
+    /// The function to call to fetch objects that are missing in all object databases from a promisor remote.
+    pub(crate) lazy_fetch: Option<std::sync::Arc<promisor::FetchFn>>,
+    /// If true, objects are returned as they are stored even if the store knows [replacements][crate::store::init::Options::replacements]
+    /// for them, similar to setting `GIT_NO_REPLACE_OBJECTS` in git.
+    pub ignore_replacements: bool,
 
     pub(crate) token: Option<handle::Mode>,
     snapshot: RefCell<load_index::Snapshot>,
 }
 
 /// Decide what happens when all indices are loaded.
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RefreshMode {
     /// Check for new or changed pack indices (and pack data files) when the last known index is loaded.
     /// During runtime we will keep pack indices stable by never reusing them, however, there is the option for
@@ -61,7 +66,7 @@ pub mod write;
 pub mod init;
 
 pub(crate) mod types;
-pub use types::Metrics;
+pub use types::{AccessMetrics, Metrics};
 
 pub(crate) mod handle;
 
@@ -71,12 +76,31 @@ pub mod load_index;
 ///
 pub mod verify;
 
+///
+pub mod multi_index;
+
+///
+pub mod repack;
+
+///
+pub mod promisor;
+
+///
+pub mod dumb;
+
+///
+pub mod quarantine;
+
 mod load_one;
 
 mod metrics;
 
 mod access;
 
+mod keep;
+
+mod contains;
+
 ///
 pub mod structure {
     use std::path::PathBuf;
@@ -0,0 +1,128 @@
+use std::{
+    ffi::OsStr,
+    io::Write,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use git_features::progress::Progress;
+
+use crate::{pack, Store};
+
+mod error {
+    use crate::pack;
+
+    /// The error returned by [`Store::write_multi_pack_index()`][crate::Store::write_multi_pack_index()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error(transparent)]
+        Write(#[from] pack::multi_index::write::Error),
+        #[error(transparent)]
+        Open(#[from] pack::multi_index::init::Error),
+        #[error(transparent)]
+        Verify(#[from] pack::multi_index::verify::integrity::Error),
+        #[error("The multi-pack index was written with checksum {expected}, but verification computed {actual}")]
+        ChecksumMismatch {
+            expected: git_hash::ObjectId,
+            actual: git_hash::ObjectId,
+        },
+        #[error("Could not move the new multi-pack index into place")]
+        Persist(#[from] tempfile::PersistError),
+    }
+}
+pub use error::Error;
+
+/// The result of [`Store::write_multi_pack_index()`][crate::Store::write_multi_pack_index()].
+pub struct Outcome<P> {
+    /// The checksum of the multi-pack index written, or `None` if the existing one was up to date with the pack indices on disk.
+    pub multi_index_checksum: Option<git_hash::ObjectId>,
+    /// The path to the multi-pack index, whether it was written or not.
+    pub path: PathBuf,
+    /// The amount of pack indices the multi-pack index refers to.
+    pub num_indices: usize,
+    /// The input progress
+    pub progress: P,
+}
+
+/// Maintenance
+impl Store {
+    /// Write a `multi-pack-index` file for all pack indices whose packs are present in our `pack` directory, replacing a
+    /// possibly existing one unless it already refers to exactly these pack indices.
+    ///
+    /// The new file is verified before it replaces the old one, so readers will never see a broken multi-pack index.
+    /// Note that the store refreshes automatically and will use the new file once it learns about it.
+    pub fn write_multi_pack_index<P>(&self, mut progress: P, should_interrupt: &AtomicBool) -> Result<Outcome<P>, Error>
+    where
+        P: Progress,
+    {
+        let pack_dir = self.path.join("pack");
+        let path = pack_dir.join("multi-pack-index");
+
+        let mut index_paths = Vec::new();
+        for entry in std::fs::read_dir(&pack_dir)? {
+            let index_path = entry?.path();
+            if index_path.extension() == Some(OsStr::new("idx")) && index_path.with_extension("pack").is_file() {
+                index_paths.push(index_path);
+            }
+        }
+        index_paths.sort();
+        let num_indices = index_paths.len();
+
+        let is_up_to_date = pack::multi_index::File::at(&path)
+            .map(|midx| {
+                midx.object_hash() == self.object_hash
+                    && midx.index_names().len() == num_indices
+                    && midx
+                        .index_names()
+                        .iter()
+                        .zip(&index_paths)
+                        .all(|(name, path)| path.file_name() == Some(name.as_os_str()))
+            })
+            .unwrap_or(false);
+        if is_up_to_date {
+            return Ok(Outcome {
+                multi_index_checksum: None,
+                path,
+                num_indices,
+                progress,
+            });
+        }
+
+        let mut tempfile = tempfile::NamedTempFile::new_in(&pack_dir)?;
+        let outcome = pack::multi_index::File::write_from_index_paths(
+            index_paths,
+            std::io::BufWriter::new(&mut tempfile),
+            progress.add_child("write multi-pack index"),
+            should_interrupt,
+            pack::multi_index::write::Options {
+                object_hash: self.object_hash,
+            },
+        )?;
+        tempfile.flush()?;
+        if should_interrupt.load(Ordering::Relaxed) {
+            return Err(Error::Interrupted);
+        }
+
+        let (actual, _) = pack::multi_index::File::at(tempfile.path())?
+            .verify_integrity_fast(progress.add_child("verify multi-pack index"), should_interrupt)?;
+        if actual != outcome.multi_index_checksum {
+            return Err(Error::ChecksumMismatch {
+                expected: outcome.multi_index_checksum,
+                actual,
+            });
+        }
+        tempfile.persist(&path)?;
+
+        Ok(Outcome {
+            multi_index_checksum: Some(outcome.multi_index_checksum),
+            path,
+            num_indices,
+            progress,
+        })
+    }
+}
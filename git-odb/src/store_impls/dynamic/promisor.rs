@@ -0,0 +1,112 @@
+use std::{collections::HashSet, ffi::OsStr, ops::Deref, path::PathBuf, sync::Arc};
+
+use git_hash::ObjectId;
+use git_object::ObjectRef;
+
+use crate::{pack, Store};
+
+/// A function to obtain the object with the given id from a promisor remote, for example by invoking `git fetch`.
+///
+/// It is called once the object couldn't be found in any of the object databases, and is expected to put it into the
+/// object database as loose object or as part of a pack before returning.
+pub type FetchFn =
+    dyn Fn(&git_hash::oid) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> + Send + Sync + 'static;
+
+mod error {
+    use crate::pack;
+
+    /// The error returned by [`Store::promised_objects()`][crate::Store::promised_objects()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        OpenPack(#[from] pack::bundle::init::Error),
+        #[error("Could not decode object at index {index} in pack '{}'", .path.display())]
+        DecodeEntry {
+            source: pack::data::decode_entry::Error,
+            index: u32,
+            path: std::path::PathBuf,
+        },
+        #[error(transparent)]
+        DecodeObject(#[from] git_object::decode::Error),
+    }
+}
+pub use error::Error;
+
+/// Partial clones
+impl Store {
+    /// Return the paths to all pack data files in our `pack` directory which were received from a promisor remote,
+    /// as indicated by a `.promisor` file next to them.
+    pub fn promisor_packs(&self) -> std::io::Result<Vec<PathBuf>> {
+        let mut packs = Vec::new();
+        for entry in std::fs::read_dir(self.path.join("pack"))? {
+            let path = entry?.path();
+            if path.extension() == Some(OsStr::new("promisor")) && path.with_extension("pack").is_file() {
+                packs.push(path.with_extension("pack"));
+            }
+        }
+        packs.sort();
+        Ok(packs)
+    }
+
+    /// Return the ids of all objects the promisor remote promised to provide, that is all objects in
+    /// [promisor packs][Store::promisor_packs()] along with all objects they refer to.
+    ///
+    /// Missing objects in this set aren't an indication of corruption, but can be [fetched on demand][super::Handle::set_lazy_fetch()],
+    /// which is why connectivity checks should ignore them.
+    pub fn promised_objects(&self) -> Result<HashSet<ObjectId>, Error> {
+        let mut out = HashSet::new();
+        let mut buf = Vec::new();
+        for pack_path in self.promisor_packs()? {
+            let bundle = pack::Bundle::at(pack_path.with_extension("idx"), self.object_hash)?;
+            for index in 0..bundle.index.num_objects() {
+                out.insert(bundle.index.oid_at_index(index).to_owned());
+                let (data, _location) = bundle
+                    .get_object_by_index(index, &mut buf, &mut pack::cache::Never)
+                    .map_err(|source| Error::DecodeEntry {
+                        source,
+                        index,
+                        path: pack_path.clone(),
+                    })?;
+                match data.decode()? {
+                    ObjectRef::Commit(commit) => {
+                        out.insert(commit.tree());
+                        out.extend(commit.parents());
+                    }
+                    ObjectRef::Tree(tree) => out.extend(tree.entries.iter().map(|entry| entry.oid.to_owned())),
+                    ObjectRef::Tag(tag) => {
+                        out.insert(tag.target());
+                    }
+                    ObjectRef::Blob(_) => {}
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+impl<S> super::Handle<S>
+where
+    S: Deref<Target = Store> + Clone,
+{
+    /// Set the function to call to `fetch` objects from a promisor remote if they can't be found in any object database,
+    /// enabling partial-clone workflows. Lookups are retried once after the function returned successfully.
+    ///
+    /// Note that checking for existence with `contains()` never triggers a fetch.
+    pub fn set_lazy_fetch(
+        &mut self,
+        fetch: impl Fn(&git_hash::oid) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        self.lazy_fetch = Some(Arc::new(fetch));
+    }
+
+    /// Remove the function to fetch missing objects, if one was set.
+    pub fn unset_lazy_fetch(&mut self) {
+        self.lazy_fetch = None;
+    }
+}
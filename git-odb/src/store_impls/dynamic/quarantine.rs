@@ -0,0 +1,242 @@
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use git_features::{progress::Progress, threading::OwnShared};
+use git_hash::ObjectId;
+use git_object::{tree::EntryMode, ObjectRef};
+
+use crate::{store::init, Find, Store};
+
+mod error {
+    use git_hash::ObjectId;
+
+    use crate::store;
+
+    /// The error returned by [`Quarantine::check_connectivity()`][super::Quarantine::check_connectivity()] and
+    /// [`Quarantine::migrate()`][super::Quarantine::migrate()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error(transparent)]
+        Find(#[from] store::find::Error),
+        #[error("The object {id} could not be decoded to find the objects it refers to")]
+        ObjectDecode {
+            source: git_object::decode::Error,
+            id: ObjectId,
+        },
+        #[error("The object {id} is neither present in the quarantine nor in the object database")]
+        Missing { id: ObjectId },
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// The outcome of [`Quarantine::migrate()`].
+#[derive(Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The amount of loose objects moved into the object database, not counting those that already existed there.
+    pub num_loose_objects: usize,
+    /// The paths to the pack data files now located in the `pack` directory of the object database.
+    pub packs: Vec<PathBuf>,
+}
+
+/// A temporary objects directory within an object database, similar to what git uses when receiving a push.
+///
+/// Objects are written into the quarantine using a [store opened on it][Quarantine::to_store()], which also sees all
+/// objects of the object database it was created for. Once they are known to be complete with
+/// [`check_connectivity()`][Quarantine::check_connectivity()], they can be [migrated][Quarantine::migrate()] into
+/// the object database, or they are discarded along with the quarantine when it's dropped.
+pub struct Quarantine {
+    dir: tempfile::TempDir,
+    objects_dir: PathBuf,
+    options: init::Options,
+}
+
+impl Store {
+    /// Create a new quarantine for incoming objects inside of our objects directory, which is removed along with all objects in
+    /// it unless it is [migrated][Quarantine::migrate()].
+    pub fn quarantine(&self) -> io::Result<Quarantine> {
+        let dir = tempfile::Builder::new()
+            .prefix("tmp_objdir-incoming-")
+            .tempdir_in(&self.path)?;
+        let info_dir = dir.path().join("info");
+        std::fs::create_dir(&info_dir)?;
+        // The quarantine lives right inside of our objects directory.
+        std::fs::write(info_dir.join("alternates"), b"..\n")?;
+        std::fs::create_dir(dir.path().join("pack"))?;
+        Ok(Quarantine {
+            dir,
+            objects_dir: self.path.clone(),
+            options: init::Options {
+                slots: Default::default(),
+                object_hash: self.object_hash,
+                use_multi_pack_index: self.use_multi_pack_index,
+                loose_write_options: self.loose_write_options,
+                // Objects must be seen as they are to judge if they are complete.
+                replacements: Vec::new(),
+                refresh: self.refresh,
+                pack_mapping: self.pack_mapping,
+            },
+        })
+    }
+}
+
+impl Quarantine {
+    /// The objects directory of the quarantine, into which loose objects and packs can be written.
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Open a new store on the quarantine to write objects into it, while objects of the object database it belongs to
+    /// remain readable through it.
+    pub fn to_store(&self) -> io::Result<Store> {
+        Store::at_opts(self.path(), self.options.clone())
+    }
+
+    /// Assure all objects reachable from `tips` are present, either in the quarantine or in the object database, and return
+    /// the amount of objects in the quarantine we traversed.
+    ///
+    /// Like git, we assume that objects already present in the object database are complete, and don't traverse them.
+    pub fn check_connectivity(
+        &self,
+        tips: impl IntoIterator<Item = ObjectId>,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<usize, Error> {
+        let start = Instant::now();
+        let incoming = OwnShared::new(self.to_store()?).to_handle();
+        let existing = OwnShared::new(Store::at_opts(&self.objects_dir, self.options.clone())?).to_handle();
+
+        let mut seen: HashSet<_> = HashSet::new();
+        let mut queue: Vec<_> = tips.into_iter().filter(|id| seen.insert(*id)).collect();
+        let mut buf = Vec::new();
+        let mut num_objects = 0;
+        progress.init(None, git_features::progress::count("objects"));
+        while let Some(id) = queue.pop() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            if existing.contains(&id) {
+                continue;
+            }
+            let object = incoming.try_find(&id, &mut buf)?.ok_or(Error::Missing { id })?;
+            match object.decode().map_err(|err| Error::ObjectDecode { source: err, id })? {
+                ObjectRef::Commit(commit) => {
+                    queue.extend(
+                        std::iter::once(commit.tree())
+                            .chain(commit.parents())
+                            .filter(|id| seen.insert(*id)),
+                    );
+                }
+                ObjectRef::Tree(tree) => queue.extend(
+                    tree.entries
+                        .iter()
+                        .filter(|entry| entry.mode != EntryMode::Commit)
+                        .map(|entry| entry.oid.to_owned())
+                        .filter(|id| seen.insert(*id)),
+                ),
+                ObjectRef::Tag(tag) => {
+                    let target = tag.target();
+                    if seen.insert(target) {
+                        queue.push(target);
+                    }
+                }
+                ObjectRef::Blob(_) => {}
+            }
+            num_objects += 1;
+            progress.inc();
+        }
+        progress.show_throughput(start);
+        Ok(num_objects)
+    }
+
+    /// Move all loose objects and packs into the object database and remove the quarantine.
+    ///
+    /// Loose objects go first, followed by packs whose index is moved last so they are complete once they can be seen.
+    /// Objects that already exist in the object database are left in place, and so are packs of the same name.
+    /// Handles to the object database will pick up the new packs when refreshing after a miss.
+    pub fn migrate(self) -> Result<Outcome, Error> {
+        let mut out = Outcome::default();
+        for entry in std::fs::read_dir(self.path())? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let is_fan_out_dir = name.to_str().map_or(false, |name| {
+                name.len() == 2 && name.bytes().all(|b| b.is_ascii_hexdigit())
+            });
+            if !is_fan_out_dir || !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let dest_dir = self.objects_dir.join(&name);
+            for object in std::fs::read_dir(entry.path())? {
+                let object = object?;
+                std::fs::create_dir_all(&dest_dir)?;
+                if rename_unless_exists(&object.path(), &dest_dir.join(object.file_name()))? {
+                    out.num_loose_objects += 1;
+                }
+            }
+        }
+
+        let mut pack_files = Vec::new();
+        for entry in std::fs::read_dir(self.path().join("pack"))? {
+            let entry = entry?;
+            let priority = entry.file_name().to_str().and_then(pack_copy_priority);
+            if let Some(priority) = priority {
+                pack_files.push((priority, entry.path()));
+            }
+        }
+        pack_files.sort();
+        if !pack_files.is_empty() {
+            std::fs::create_dir_all(self.objects_dir.join("pack"))?;
+        }
+        for (_, path) in pack_files {
+            let dest = self
+                .objects_dir
+                .join("pack")
+                .join(path.file_name().expect("read from directory"));
+            let is_pack = dest.extension().map_or(false, |ext| ext == "pack");
+            rename_unless_exists(&path, &dest)?;
+            if is_pack {
+                out.packs.push(dest);
+            }
+        }
+        self.dir.close()?;
+        Ok(out)
+    }
+
+    /// Remove the quarantine with all objects in it, which is also done when it's dropped.
+    pub fn discard(self) -> io::Result<()> {
+        self.dir.close()
+    }
+}
+
+/// Determine the order in which the files of a pack are moved, or `None` if the file doesn't belong to a pack.
+fn pack_copy_priority(name: &str) -> Option<u8> {
+    if !name.starts_with("pack-") {
+        return None;
+    }
+    Some(match Path::new(name).extension().and_then(|ext| ext.to_str()) {
+        Some("keep") => 0,
+        Some("pack") => 1,
+        Some("rev") => 2,
+        Some("idx") => 3,
+        _ => 4,
+    })
+}
+
+/// Move `from` to `to` and return true, or return false if `to` already exists.
+fn rename_unless_exists(from: &Path, to: &Path) -> io::Result<bool> {
+    if to.exists() {
+        return Ok(false);
+    }
+    std::fs::rename(from, to)?;
+    Ok(true)
+}
@@ -0,0 +1,319 @@
+use std::{
+    convert::Infallible,
+    ffi::OsStr,
+    io::{Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use git_features::{parallel::InOrderIter, progress, progress::Progress};
+
+use crate::{pack, Store};
+
+mod error {
+    use std::convert::Infallible;
+
+    use crate::{pack, store};
+
+    /// The error returned by [`Store::geometric_repack()`][crate::Store::geometric_repack()] and
+    /// [`Store::geometric_repack_plan()`][crate::Store::geometric_repack_plan()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error("The geometric factor must be at least 2, got {factor}")]
+        InvalidFactor { factor: u32 },
+        #[error(transparent)]
+        OpenIndex(#[from] pack::index::init::Error),
+        #[error(transparent)]
+        CountObjects(
+            #[from]
+            pack::data::output::count::objects::Error<pack::find::existing::Error<store::find::Error>, Infallible>,
+        ),
+        #[error(transparent)]
+        WritePack(
+            #[from]
+            pack::data::output::bytes::Error<pack::data::output::entry::iter_from_counts::Error<store::find::Error>>,
+        ),
+        #[error(transparent)]
+        IndexPack(#[from] pack::bundle::write::Error),
+        #[error(transparent)]
+        MultiIndex(#[from] store::multi_index::Error),
+    }
+}
+pub use error::Error;
+
+/// A pack which takes part in a [geometric repack][Store::geometric_repack_plan()].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pack {
+    /// The path to the pack index file.
+    pub index_path: PathBuf,
+    /// The amount of objects in the pack, which is its weight within the geometric progression.
+    pub num_objects: u32,
+}
+
+/// Describes which packs to roll up into a new pack so that the remaining ones form a geometric progression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    /// The factor by which each pack has to be larger than the next smaller one.
+    pub factor: u32,
+    /// The packs to combine into a single new pack, smallest first. It's empty if there is nothing to do.
+    pub combine: Vec<Pack>,
+    /// The packs that are left untouched as they already form a geometric progression or are marked with a `.keep` file,
+    /// smallest first.
+    pub keep: Vec<Pack>,
+}
+
+impl Plan {
+    /// Return true if there are no packs to roll up.
+    pub fn is_empty(&self) -> bool {
+        self.combine.is_empty()
+    }
+
+    /// The sum of objects in all packs to combine. The new pack may contain fewer objects if some of them are duplicates.
+    pub fn num_objects_to_combine(&self) -> u64 {
+        self.combine.iter().map(|p| p.num_objects as u64).sum()
+    }
+}
+
+/// Configuration for [`Store::geometric_repack()`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The factor by which each pack has to be larger than the next smaller one, similar to `git repack --geometric=<factor>`.
+    pub factor: u32,
+    /// The amount of threads to use at most when creating and indexing the new pack. If `None`, all logical cores are used.
+    pub thread_limit: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            factor: 2,
+            thread_limit: None,
+        }
+    }
+}
+
+/// The result of [`Store::geometric_repack()`].
+pub struct Outcome<P> {
+    /// The plan that was executed.
+    pub plan: Plan,
+    /// Information about the newly written pack, or `None` if the packs already formed a geometric progression.
+    pub written: Option<pack::bundle::write::Outcome>,
+    /// The checksum of the rewritten multi-pack index, if there was one that had to be updated.
+    pub multi_index_checksum: Option<git_hash::ObjectId>,
+    /// The input progress
+    pub progress: P,
+}
+
+/// Maintenance
+impl Store {
+    /// Determine which packs in our `pack` directory should be rolled up into a new pack so that all remaining packs form
+    /// a geometric progression, each having at least `factor` times as many objects as the next smaller one.
+    ///
+    /// This is the same algorithm that `git repack --geometric=<factor>` uses. Packs with a `.keep` file are never combined.
+    pub fn geometric_repack_plan(&self, factor: u32) -> Result<Plan, Error> {
+        if factor < 2 {
+            return Err(Error::InvalidFactor { factor });
+        }
+        let mut packs = Vec::new();
+        let mut keep = Vec::new();
+        for entry in std::fs::read_dir(self.path.join("pack"))? {
+            let index_path = entry?.path();
+            if index_path.extension() != Some(OsStr::new("idx")) || !index_path.with_extension("pack").is_file() {
+                continue;
+            }
+            let pack = Pack {
+                num_objects: pack::index::File::at(&index_path, self.object_hash)?.num_objects(),
+                index_path,
+            };
+            if self.is_pack_kept(&pack.index_path) {
+                keep.push(pack);
+            } else {
+                packs.push(pack);
+            }
+        }
+        packs.sort_by(|a, b| {
+            a.num_objects
+                .cmp(&b.num_objects)
+                .then_with(|| a.index_path.cmp(&b.index_path))
+        });
+
+        let split = geometric_split(&packs, factor as u64);
+        let mut combine = packs;
+        keep.extend(combine.drain(split..));
+        if combine.len() < 2 {
+            keep.extend(combine.drain(..));
+        }
+        keep.sort_by(|a, b| {
+            a.num_objects
+                .cmp(&b.num_objects)
+                .then_with(|| a.index_path.cmp(&b.index_path))
+        });
+        Ok(Plan { factor, combine, keep })
+    }
+
+    /// Roll up small packs into a new pack so that all packs form a geometric progression according to
+    /// [`geometric_repack_plan()`][Store::geometric_repack_plan()], and delete the packs that were combined.
+    ///
+    /// Readers of this store remain functional throughout: the new pack is fully written and indexed before any of the old
+    /// ones are removed, and the old pack indices are removed before their data files so that no new reader will pick them up.
+    /// A `multi-pack-index` file, if present, is rewritten to refer to the new pack while the old pack data still exists.
+    /// Packs that were [marked as kept][Store::keep_pack()] while the new pack was written are left in place.
+    pub fn geometric_repack<P>(
+        self: &Arc<Self>,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+        Options { factor, thread_limit }: Options,
+    ) -> Result<Outcome<P>, Error>
+    where
+        P: Progress,
+    {
+        let plan = self.geometric_repack_plan(factor)?;
+        if plan.is_empty() {
+            return Ok(Outcome {
+                plan,
+                written: None,
+                multi_index_checksum: None,
+                progress,
+            });
+        }
+
+        let pack_dir = self.path.join("pack");
+        let mut ids = Vec::with_capacity(plan.num_objects_to_combine() as usize);
+        for pack in &plan.combine {
+            ids.extend(
+                pack::index::File::at(&pack.index_path, self.object_hash)?
+                    .iter()
+                    .map(|entry| entry.oid),
+            );
+        }
+        ids.sort();
+        ids.dedup();
+        let num_objects = ids.len();
+
+        let mut handle = self.to_handle_arc();
+        handle.prevent_pack_unload();
+        let (counts, _) = pack::data::output::count::objects_unthreaded(
+            handle.clone(),
+            ids.into_iter().map(Ok::<_, Infallible>),
+            progress.add_child("counting"),
+            should_interrupt,
+            pack::data::output::count::objects::ObjectExpansion::AsIs,
+        )?;
+
+        let mut entries = InOrderIter::from(pack::data::output::entry::iter_from_counts(
+            counts,
+            handle,
+            progress.add_child("creating entries"),
+            pack::data::output::entry::iter_from_counts::Options {
+                thread_limit,
+                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                allow_thin_pack: false,
+                version: pack::data::Version::V2,
+                ..Default::default()
+            },
+        ));
+
+        let mut tempfile = tempfile::NamedTempFile::new_in(&pack_dir)?;
+        {
+            let mut write_progress = progress.add_child("writing");
+            write_progress.init(None, progress::bytes());
+            let mut pack_writer = pack::data::output::bytes::FromEntriesIter::new(
+                entries.by_ref(),
+                std::io::BufWriter::new(&mut tempfile),
+                num_objects as u32,
+                pack::data::Version::V2,
+                self.object_hash,
+            );
+            for written in pack_writer.by_ref() {
+                write_progress.inc_by(written? as usize);
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+            }
+            pack_writer.into_write().flush()?;
+        }
+        tempfile.seek(SeekFrom::Start(0))?;
+
+        let written = pack::Bundle::write_to_directory(
+            std::io::BufReader::new(&mut tempfile),
+            Some(&pack_dir),
+            progress.add_child("index pack"),
+            should_interrupt,
+            None,
+            pack::bundle::write::Options {
+                thread_limit,
+                iteration_mode: pack::data::input::Mode::Verify,
+                index_kind: pack::index::Version::default(),
+                object_hash: self.object_hash,
+                keep: None,
+            },
+        )?;
+
+        let superseded: Vec<_> = plan
+            .combine
+            .iter()
+            .map(|pack| pack.index_path.as_path())
+            .filter(|path| written.index_path.as_deref() != Some(*path) && !self.is_pack_kept(path))
+            .collect();
+        for index_path in &superseded {
+            remove_if_present(index_path)?;
+        }
+        let multi_index_checksum = if pack_dir.join("multi-pack-index").is_file() {
+            self.write_multi_pack_index(progress.add_child("multi-pack index"), should_interrupt)?
+                .multi_index_checksum
+        } else {
+            None
+        };
+        for index_path in superseded {
+            for extension in &["pack", "rev", "bitmap"] {
+                remove_if_present(&index_path.with_extension(extension))?;
+            }
+        }
+
+        Ok(Outcome {
+            plan,
+            written: Some(written),
+            multi_index_checksum,
+            progress,
+        })
+    }
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        res => res,
+    }
+}
+
+/// Return the amount of `packs`, sorted by ascending amount of objects, that need to be combined so the remaining ones
+/// form a geometric progression with `factor`.
+fn geometric_split(packs: &[Pack], factor: u64) -> usize {
+    let weight = |idx: usize| packs[idx].num_objects as u64;
+    if packs.is_empty() {
+        return 0;
+    }
+    let mut split = (1..packs.len())
+        .rev()
+        .find(|&idx| weight(idx) < factor * weight(idx - 1))
+        .unwrap_or(0);
+    if split != 0 {
+        // The larger pack of the pair that broke the progression can't be part of it either.
+        split += 1;
+    }
+
+    let mut total: u64 = (0..split).map(weight).sum();
+    while split < packs.len() && weight(split) < factor * total {
+        total += weight(split);
+        split += 1;
+    }
+    split
+}
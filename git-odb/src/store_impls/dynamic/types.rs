@@ -1,7 +1,7 @@
 use std::{
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU16, AtomicU32, AtomicUsize, Ordering},
+        atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     time::SystemTime,
@@ -423,6 +423,54 @@ pub struct Metrics {
     pub loose_dbs: usize,
 }
 
+/// Counters about object access through all handles of a store, updated in a racy fashion.
+#[derive(Default)]
+pub(crate) struct AccessCounters {
+    pub(crate) packed_objects: AtomicU64,
+    pub(crate) loose_objects: AtomicU64,
+    pub(crate) deltas_resolved: AtomicU64,
+    pub(crate) packs_mapped: AtomicU64,
+    pub(crate) bytes_read: AtomicU64,
+}
+
+impl AccessCounters {
+    pub(crate) fn record_packed_object(&self, outcome: &git_pack::data::decode_entry::Outcome) {
+        self.packed_objects.fetch_add(1, Ordering::Relaxed);
+        self.deltas_resolved
+            .fetch_add(outcome.num_deltas as u64, Ordering::Relaxed);
+        self.bytes_read
+            .fetch_add(outcome.compressed_size as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> AccessMetrics {
+        AccessMetrics {
+            packed_objects: self.packed_objects.load(Ordering::Relaxed),
+            loose_objects: self.loose_objects.load(Ordering::Relaxed),
+            deltas_resolved: self.deltas_resolved.load(Ordering::Relaxed),
+            packs_mapped: self.packs_mapped.load(Ordering::Relaxed),
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of counters about object access through all handles of a store since it was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccessMetrics {
+    /// The amount of objects decoded from packs.
+    pub packed_objects: u64,
+    /// The amount of objects decompressed from loose object files.
+    pub loose_objects: u64,
+    /// The amount of deltas applied to obtain packed objects, which is lower if delta bases are served from pack caches.
+    pub deltas_resolved: u64,
+    /// The amount of pack data files that were memory-mapped or read into memory.
+    pub packs_mapped: u64,
+    /// The amount of compressed bytes of pack entries read to obtain packed objects, including their delta bases.
+    ///
+    /// Loose objects aren't included.
+    pub bytes_read: u64,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
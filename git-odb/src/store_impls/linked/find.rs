@@ -65,7 +65,7 @@ impl git_pack::Find for linked::Store {
             {
                 let bundle = &db.bundles[bundle_index];
                 let pack_offset = bundle.index.pack_offset_at_index(entry_index);
-                let entry = bundle.pack.entry(pack_offset);
+                let entry = bundle.pack.entry(pack_offset).ok()?;
 
                 buf.resize(entry.decompressed_size.try_into().expect("representable size"), 0);
                 return bundle
@@ -97,7 +97,7 @@ impl git_pack::Find for linked::Store {
             .map(|b| (b, location))
             .and_then(|(bundle, l)| {
                 bundle.pack.entry_slice(l.entry_range(l.pack_offset)).map(|data| Entry {
-                    data: data.to_owned(),
+                    data: data.to_vec(),
                     version: bundle.pack.version(),
                 })
             })
@@ -1,4 +1,9 @@
-use std::{cmp::Ordering, fs, io::Read, path::PathBuf};
+use std::{
+    cmp::Ordering,
+    fs,
+    io::{self, Read},
+    path::PathBuf,
+};
 
 use git_features::zlib;
 
@@ -105,6 +110,46 @@ impl Store {
         }
     }
 
+    /// Return a [`Stream`][crate::Stream] over the decompressed data of the object identified by `id` if present in this database,
+    /// which is decompressed while reading instead of being read into memory in full.
+    ///
+    /// Returns `Ok<None>` if there was no such object.
+    pub fn try_stream(&self, id: impl AsRef<git_hash::oid>) -> Result<Option<crate::Stream>, Error> {
+        debug_assert_eq!(self.object_hash, id.as_ref().kind());
+        let path = hash_path(id.as_ref(), self.path.clone());
+        let file = match fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Error::Io {
+                    source: err,
+                    action: Self::OPEN_ACTION,
+                    path,
+                })
+            }
+        };
+        let mut read = zlib::stream::inflate::ReadBoxed {
+            inner: io::BufReader::new(file),
+            decompressor: Box::new(zlib::Decompress::new(true)),
+        };
+
+        let mut header = Vec::new();
+        let mut byte = [0u8; 1];
+        while header.len() < HEADER_READ_UNCOMPRESSED_BYTES && header.last() != Some(&0) {
+            let num_read = read.read(&mut byte).map_err(|e| Error::Io {
+                source: e,
+                action: "deflate",
+                path: path.to_owned(),
+            })?;
+            if num_read == 0 {
+                break;
+            }
+            header.push(byte[0]);
+        }
+        let (kind, size, _header_size) = git_object::decode::loose_header(&header)?;
+        Ok(Some(crate::Stream::from_loose(kind, size as u64, read)))
+    }
+
     fn find_inner<'a>(&self, id: &git_hash::oid, buf: &'a mut Vec<u8>) -> Result<git_object::Data<'a>, Error> {
         let path = hash_path(id, self.path.clone());
 
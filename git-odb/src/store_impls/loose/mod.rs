@@ -11,6 +11,8 @@ pub struct Store {
     pub(crate) path: PathBuf,
     /// The kind of hash we should assume during iteration and when writing new objects.
     pub(crate) object_hash: git_hash::Kind,
+    /// Configure how objects are written.
+    pub(crate) write_options: write::Options,
 }
 
 /// Initialization
@@ -22,9 +24,19 @@ impl Store {
     ///
     /// The `object_hash` determines which hash to use when writing, finding or iterating objects.
     pub fn at(objects_directory: impl Into<PathBuf>, object_hash: git_hash::Kind) -> Store {
+        Self::at_opts(objects_directory, object_hash, Default::default())
+    }
+
+    /// Like [`at()`][Store::at()], but with `write_options` to control durability and permissions of written objects.
+    pub fn at_opts(
+        objects_directory: impl Into<PathBuf>,
+        object_hash: git_hash::Kind,
+        write_options: write::Options,
+    ) -> Store {
         Store {
             path: objects_directory.into(),
             object_hash,
+            write_options,
         }
     }
 
@@ -37,6 +49,11 @@ impl Store {
     pub fn object_hash(&self) -> git_hash::Kind {
         self.object_hash
     }
+
+    /// Return the options used when writing objects.
+    pub fn write_options(&self) -> write::Options {
+        self.write_options
+    }
 }
 
 fn hash_path(id: &git_hash::oid, mut root: PathBuf) -> PathBuf {
@@ -53,6 +70,10 @@ pub mod find;
 ///
 pub mod iter;
 ///
+pub mod prune;
+///
+pub mod unpack;
+///
 pub mod verify;
 
 /// The type for an iterator over `Result<git_hash::ObjectId, Error>)`
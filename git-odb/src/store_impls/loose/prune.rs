@@ -0,0 +1,141 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant, SystemTime},
+};
+
+use git_features::progress::Progress;
+use git_hash::{oid, ObjectId};
+
+use crate::loose::{hash_path, Store};
+
+mod error {
+    use std::path::PathBuf;
+
+    /// The error returned by [`prune()`][crate::loose::Store::prune()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Iter(#[from] crate::loose::iter::Error),
+        #[error("Could not {action} '{path}'")]
+        Io {
+            source: std::io::Error,
+            action: &'static str,
+            path: PathBuf,
+        },
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Configuration for [`prune()`][crate::loose::Store::prune()].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// Unreachable objects must not have been modified within this duration before the time of the call to be removed.
+    ///
+    /// This protects objects just written by other processes which aren't referenced yet, like `gc.pruneExpire` does.
+    /// Use `Duration::from_secs(0)` to remove all unreachable objects.
+    pub expire: Duration,
+    /// If true, don't delete anything but report which objects would have been removed.
+    pub dry_run: bool,
+}
+
+impl Default for Options {
+    /// Expire objects after two weeks, just like git does by default, and actually delete them.
+    fn default() -> Self {
+        Options {
+            expire: Duration::from_secs(60 * 60 * 24 * 14),
+            dry_run: false,
+        }
+    }
+}
+
+/// The outcome of [`prune()`][crate::loose::Store::prune()].
+#[derive(Debug, Default, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The amount of loose objects we looked at.
+    pub num_objects: usize,
+    /// The unreachable objects that were old enough to be removed, or would have been removed in a dry-run.
+    pub pruned: Vec<ObjectId>,
+    /// The amount of bytes the pruned objects used on disk.
+    pub pruned_bytes: u64,
+}
+
+impl Store {
+    /// Remove all loose objects for which `is_reachable` returns false and which are older than the configured
+    /// [expiry][Options::expire], or only report them if [`dry_run`][Options::dry_run] is set.
+    ///
+    /// Fan-out directories that are empty afterwards are removed as well.
+    ///
+    /// Determining reachability is up to the caller, who typically walks all objects reachable from references, their reflogs
+    /// and the index. Note that objects that are concurrently written may be removed if they are older than the expiry
+    /// but not yet referenced by the caller's notion of reachability.
+    pub fn prune(
+        &self,
+        mut is_reachable: impl FnMut(&oid) -> bool,
+        Options { expire, dry_run }: Options,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let start = Instant::now();
+        let cutoff = SystemTime::now().checked_sub(expire).unwrap_or(SystemTime::UNIX_EPOCH);
+        let mut out = Outcome::default();
+        progress.init(None, git_features::progress::count("loose objects"));
+
+        for id in self.iter() {
+            let id = id?;
+            out.num_objects += 1;
+            progress.inc();
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            if is_reachable(&id) {
+                continue;
+            }
+            let path = hash_path(&id, self.path.clone());
+            let metadata = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(err) => {
+                    return Err(Error::Io {
+                        source: err,
+                        action: "obtain metadata of",
+                        path,
+                    })
+                }
+            };
+            let modified = metadata.modified().map_err(|err| Error::Io {
+                source: err,
+                action: "obtain modification time of",
+                path: path.clone(),
+            })?;
+            if modified > cutoff {
+                continue;
+            }
+            if !dry_run {
+                match std::fs::remove_file(&path) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => {
+                        return Err(Error::Io {
+                            source: err,
+                            action: "remove",
+                            path,
+                        })
+                    }
+                }
+                if let Some(dir) = path.parent() {
+                    // Fails if the directory isn't empty, which is exactly what we want.
+                    std::fs::remove_dir(dir).ok();
+                }
+            }
+            out.pruned_bytes += metadata.len();
+            out.pruned.push(id);
+        }
+
+        progress.show_throughput(start);
+        Ok(out)
+    }
+}
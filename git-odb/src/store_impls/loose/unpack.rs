@@ -0,0 +1,275 @@
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use git_features::{progress::Progress, zlib};
+use git_hash::ObjectId;
+use git_pack::data::{self, input};
+
+use crate::{loose::Store, Write};
+
+mod error {
+    /// The error returned by [`unpack_objects()`][crate::loose::Store::unpack_objects()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        PackIter(#[from] git_pack::data::input::Error),
+        #[error("The compressed data of the entry at pack offset {pack_offset} could not be inflated")]
+        Inflate {
+            source: git_features::zlib::inflate::Error,
+            pack_offset: u64,
+        },
+        #[error("The entry at pack offset {pack_offset} refers to an object at offset {base_pack_offset} which isn't a valid entry")]
+        InvalidBaseOffset { pack_offset: u64, base_pack_offset: u64 },
+        #[error("Could not find the base object {base_id} of {num_deltas} delta(s) neither in the pack nor in the object database")]
+        UnresolvedDeltas { base_id: ObjectId, num_deltas: usize },
+        #[error("The delta at pack offset {pack_offset} expected a base object of {expected} bytes, but it had {actual} bytes")]
+        DeltaBaseSizeMismatch {
+            pack_offset: u64,
+            expected: u64,
+            actual: u64,
+        },
+        #[error("Failed to read the base object {id} back from the loose object database")]
+        FindBase {
+            source: crate::loose::find::Error,
+            id: ObjectId,
+        },
+        #[error("The base object {id} was removed from the loose object database while unpacking")]
+        MissingBase { id: ObjectId },
+        #[error(transparent)]
+        Write(#[from] crate::loose::write::Error),
+        #[error("Interrupted")]
+        Interrupted,
+    }
+
+    use git_hash::ObjectId;
+}
+pub use error::Error;
+
+/// The outcome of [`unpack_objects()`][crate::loose::Store::unpack_objects()].
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct Outcome {
+    /// The checksum of the pack, as verified against the hash of all bytes we read.
+    pub pack_checksum: ObjectId,
+    /// The amount of objects written as loose objects, which is the amount of objects in the pack.
+    pub num_objects: usize,
+    /// The amount of distinct objects outside of the pack which served as base for deltas in the pack, i.e. in thin packs.
+    pub num_external_bases: usize,
+}
+
+/// A delta whose base object wasn't available when it was encountered.
+struct PendingDelta {
+    pack_offset: u64,
+    data: Vec<u8>,
+}
+
+impl Store {
+    /// Read a pack data stream from `pack`, starting at its header, and write all of its objects into this database as loose objects.
+    ///
+    /// `find` is used to lookup base objects of thin packs that aren't contained in `pack` or in this database, and it is
+    /// expected to write the object data into the given buffer, returning it on success.
+    ///
+    /// The pack trailer is verified against the data we read, and each object is hashed while it's written, so corrupt packs
+    /// are detected once the stream is consumed. Note that objects written up to that point are not removed.
+    /// This is like `git unpack-objects`, but without the ability to recover from corrupt packs.
+    pub fn unpack_objects<F>(
+        &self,
+        pack: impl io::BufRead,
+        mut find: F,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error>
+    where
+        F: for<'a> FnMut(&git_hash::oid, &'a mut Vec<u8>) -> Option<git_object::Data<'a>>,
+    {
+        let start = Instant::now();
+        let iter = input::BytesToEntriesIter::new_from_header(
+            pack,
+            input::Mode::Verify,
+            input::EntryDataMode::Keep,
+            self.object_hash,
+        )?;
+        progress.init(Some(iter.len()), git_features::progress::count("objects"));
+
+        let mut id_by_offset = HashMap::<u64, ObjectId>::new();
+        let mut pending_by_base_id = HashMap::<ObjectId, Vec<PendingDelta>>::new();
+        let mut pending_by_base_offset = HashMap::<u64, Vec<PendingDelta>>::new();
+        let mut base_buf = Vec::new();
+        let mut pack_checksum = None;
+        let mut num_objects = 0;
+        let mut num_external_bases = 0;
+
+        for entry in iter {
+            let entry = entry?;
+            let data = inflate(&entry)?;
+            let pack_offset = entry.pack_offset;
+            pack_checksum = entry.trailer.or(pack_checksum);
+
+            let id = match entry.header {
+                data::entry::Header::OfsDelta { base_distance } => {
+                    let base_pack_offset = data::entry::Header::verified_base_pack_offset(pack_offset, base_distance)
+                        .ok_or(Error::InvalidBaseOffset {
+                        pack_offset,
+                        base_pack_offset: pack_offset.saturating_sub(base_distance),
+                    })?;
+                    match id_by_offset.get(&base_pack_offset) {
+                        Some(base_id) => {
+                            let base = self.find_base(base_id, &mut base_buf)?;
+                            self.write_delta(base, pack_offset, &data)?
+                        }
+                        None => {
+                            pending_by_base_offset
+                                .entry(base_pack_offset)
+                                .or_default()
+                                .push(PendingDelta { pack_offset, data });
+                            continue;
+                        }
+                    }
+                }
+                data::entry::Header::RefDelta { base_id } => {
+                    if self.contains(&base_id) {
+                        let base = self.find_base(&base_id, &mut base_buf)?;
+                        self.write_delta(base, pack_offset, &data)?
+                    } else {
+                        pending_by_base_id
+                            .entry(base_id)
+                            .or_default()
+                            .push(PendingDelta { pack_offset, data });
+                        continue;
+                    }
+                }
+                header => self.write_buf(header.as_kind().expect("base object"), &data)?,
+            };
+            num_objects += self.resolve_pending(
+                id,
+                pack_offset,
+                &mut id_by_offset,
+                &mut pending_by_base_id,
+                &mut pending_by_base_offset,
+                &mut base_buf,
+                &mut progress,
+            )?;
+            if should_interrupt.load(Ordering::SeqCst) {
+                return Err(Error::Interrupted);
+            }
+        }
+
+        // Whatever is left refers to objects outside of the pack which are only needed to resolve the deltas, not written.
+        let mut external_buf = Vec::new();
+        while let Some(base_id) = pending_by_base_id.keys().next().cloned() {
+            let deltas = pending_by_base_id.remove(&base_id).expect("key exists");
+            let base = find(&base_id, &mut external_buf).ok_or(Error::UnresolvedDeltas {
+                base_id,
+                num_deltas: deltas.len(),
+            })?;
+            num_external_bases += 1;
+            for delta in deltas {
+                let id = self.write_delta(base.clone(), delta.pack_offset, &delta.data)?;
+                num_objects += self.resolve_pending(
+                    id,
+                    delta.pack_offset,
+                    &mut id_by_offset,
+                    &mut pending_by_base_id,
+                    &mut pending_by_base_offset,
+                    &mut base_buf,
+                    &mut progress,
+                )?;
+            }
+            if should_interrupt.load(Ordering::SeqCst) {
+                return Err(Error::Interrupted);
+            }
+        }
+        if let Some((base_pack_offset, deltas)) = pending_by_base_offset.into_iter().next() {
+            return Err(Error::InvalidBaseOffset {
+                pack_offset: deltas[0].pack_offset,
+                base_pack_offset,
+            });
+        }
+
+        progress.show_throughput(start);
+        Ok(Outcome {
+            pack_checksum: pack_checksum.expect("the iterator provides a trailer with the last entry"),
+            num_objects,
+            num_external_bases,
+        })
+    }
+
+    /// Record that `id` was written for the entry at `pack_offset` and write all deltas waiting for it, recursively.
+    /// Return the amount of objects written, including `id`.
+    #[allow(clippy::too_many_arguments)]
+    fn resolve_pending(
+        &self,
+        id: ObjectId,
+        pack_offset: u64,
+        id_by_offset: &mut HashMap<u64, ObjectId>,
+        pending_by_base_id: &mut HashMap<ObjectId, Vec<PendingDelta>>,
+        pending_by_base_offset: &mut HashMap<u64, Vec<PendingDelta>>,
+        base_buf: &mut Vec<u8>,
+        progress: &mut impl Progress,
+    ) -> Result<usize, Error> {
+        let mut num_objects = 0;
+        let mut resolved = vec![(id, pack_offset)];
+        while let Some((base_id, base_pack_offset)) = resolved.pop() {
+            id_by_offset.insert(base_pack_offset, base_id);
+            num_objects += 1;
+            progress.inc();
+
+            let deltas = pending_by_base_id
+                .remove(&base_id)
+                .into_iter()
+                .chain(pending_by_base_offset.remove(&base_pack_offset))
+                .flatten()
+                .collect::<Vec<_>>();
+            if deltas.is_empty() {
+                continue;
+            }
+            let base = self.find_base(&base_id, base_buf)?;
+            for delta in deltas {
+                let id = self.write_delta(base.clone(), delta.pack_offset, &delta.data)?;
+                resolved.push((id, delta.pack_offset));
+            }
+        }
+        Ok(num_objects)
+    }
+
+    fn find_base<'a>(&self, id: &git_hash::oid, buf: &'a mut Vec<u8>) -> Result<git_object::Data<'a>, Error> {
+        self.try_find(id, buf)
+            .map_err(|err| Error::FindBase {
+                source: err,
+                id: id.to_owned(),
+            })?
+            .ok_or_else(|| Error::MissingBase { id: id.to_owned() })
+    }
+
+    fn write_delta(&self, base: git_object::Data<'_>, pack_offset: u64, delta: &[u8]) -> Result<ObjectId, Error> {
+        let (base_size, consumed) = data::delta::decode_header_size(delta);
+        if base_size != base.data.len() as u64 {
+            return Err(Error::DeltaBaseSizeMismatch {
+                pack_offset,
+                expected: base_size,
+                actual: base.data.len() as u64,
+            });
+        }
+        let (result_size, result_consumed) = data::delta::decode_header_size(&delta[consumed..]);
+        let mut out = vec![0; result_size.try_into().expect("object size fits into usize")];
+        data::delta::apply(base.data, &mut out, &delta[consumed + result_consumed..]);
+        Ok(self.write_buf(base.kind, &out)?)
+    }
+}
+
+fn inflate(entry: &input::Entry) -> Result<Vec<u8>, Error> {
+    let mut out = vec![0; entry.decompressed_size.try_into().expect("object size fits into usize")];
+    zlib::Inflate::default()
+        .once(entry.compressed.as_ref().expect("compressed bytes are kept"), &mut out)
+        .map_err(|err| Error::Inflate {
+            source: err,
+            pack_offset: entry.pack_offset,
+        })?;
+    Ok(out)
+}
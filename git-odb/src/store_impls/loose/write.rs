@@ -1,4 +1,9 @@
-use std::{convert::TryInto, fs, io, io::Write, path::PathBuf};
+use std::{
+    convert::TryInto,
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use git_features::{hash, zlib::stream::deflate};
 use git_object::WriteTo;
@@ -7,6 +12,96 @@ use tempfile::NamedTempFile;
 use super::Store;
 use crate::store_impls::loose;
 
+/// Determines which files and directories are synced to disk when writing loose objects, similar to `core.fsyncObjectFiles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fsync {
+    /// Leave it to the operating system to write data to disk eventually.
+    None,
+    /// Sync each object file before moving it into place.
+    Objects,
+    /// Like [`Objects`][Fsync::Objects], but also sync the directories containing the object after it was moved into place,
+    /// making the new entry itself durable.
+    ObjectsAndDirectories,
+}
+
+impl Default for Fsync {
+    fn default() -> Self {
+        Fsync::None
+    }
+}
+
+/// Controls how permissions of written files and directories are adjusted to allow sharing the repository, similar to
+/// `core.sharedRepository`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedRepository {
+    /// Use the permissions as provided by the `umask` of the writing process.
+    Umask,
+    /// Make files group-readable and writable if they are writable by their owner, and directories group-accessible.
+    Group,
+    /// Like [`Group`][SharedRepository::Group], but also make files and directories readable by everybody.
+    All,
+    /// Set the given permission bits in octal, like `0640`, instead of the ones provided by the `umask`.
+    /// Write permissions are only granted on files that are writable by their owner.
+    Mode(u32),
+}
+
+impl Default for SharedRepository {
+    fn default() -> Self {
+        SharedRepository::Umask
+    }
+}
+
+impl SharedRepository {
+    /// Parse a `core.sharedRepository` configuration `value`, or return `None` if it is invalid.
+    pub fn from_config_value(value: &[u8]) -> Option<Self> {
+        Some(match value {
+            b"" | b"umask" | b"false" | b"no" | b"off" | b"0" => SharedRepository::Umask,
+            b"group" | b"true" | b"yes" | b"on" | b"1" => SharedRepository::Group,
+            b"all" | b"world" | b"everybody" | b"2" => SharedRepository::All,
+            octal => {
+                let mode = u32::from_str_radix(std::str::from_utf8(octal).ok()?, 8).ok()?;
+                if mode & 0o600 != 0o600 {
+                    return None;
+                }
+                SharedRepository::Mode(mode & 0o666)
+            }
+        })
+    }
+
+    /// Return the given file or directory `mode` adjusted for sharing, the way git does it.
+    pub fn adjust_mode(&self, mode: u32, is_dir: bool) -> u32 {
+        let (mut tweak, replace) = match *self {
+            SharedRepository::Umask => return mode,
+            SharedRepository::Group => (0o660, false),
+            SharedRepository::All => (0o664, false),
+            SharedRepository::Mode(mode) => (mode, true),
+        };
+        if mode & 0o200 == 0 {
+            tweak &= !0o222;
+        }
+        if mode & 0o100 != 0 {
+            tweak |= (tweak & 0o444) >> 2;
+        }
+        let mode = if replace { (mode & !0o777) | tweak } else { mode | tweak };
+        if is_dir {
+            mode | 0o2000
+        } else {
+            mode
+        }
+    }
+}
+
+/// Configure how loose objects are written to disk.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// What to sync to disk before an object is considered written.
+    pub fsync: Fsync,
+    /// If true, object files will not be writable, as they are immutable once written. This is what git does.
+    pub read_only: bool,
+    /// How to adjust permissions of object files and their directories.
+    pub shared_repository: SharedRepository,
+}
+
 /// Returned by the [`crate::Write`] trait implementation of [`Store`]
 #[derive(thiserror::Error, Debug)]
 #[allow(missing_docs)]
@@ -119,17 +214,96 @@ impl Store {
         let object_dir = object_path
             .parent()
             .expect("each object path has a 1 hex-bytes directory");
-        if let Err(err) = fs::create_dir(object_dir) {
-            match err.kind() {
-                io::ErrorKind::AlreadyExists => {}
+        let created_object_dir = match fs::create_dir(object_dir) {
+            Ok(()) => {
+                self.adjust_permissions(object_dir, true)?;
+                true
+            }
+            Err(err) => match err.kind() {
+                io::ErrorKind::AlreadyExists => false,
                 _ => return Err(err.into()),
+            },
+        };
+        let file = file.into_inner();
+        if self.write_options.fsync != Fsync::None {
+            file.as_file().sync_all().map_err(|err| Error::Io {
+                source: err,
+                message: "sync object file in",
+                path: self.path.to_owned(),
+            })?;
+        }
+        self.adjust_permissions(file.path(), false)?;
+        if let Err(err) = file.persist(&object_path) {
+            // Objects are immutable, so if it exists (possibly read-only) it's already what we wanted to write.
+            if !object_path.is_file() {
+                return Err(Error::Persist {
+                    source: err,
+                    target: object_path,
+                });
+            }
+        }
+        if self.write_options.fsync == Fsync::ObjectsAndDirectories {
+            sync_dir(object_dir)?;
+            if created_object_dir {
+                sync_dir(&self.path)?;
             }
         }
-        let file = file.into_inner();
-        file.persist(&object_path).map_err(|err| Error::Persist {
-            source: err,
-            target: object_path,
-        })?;
         Ok(id)
     }
+
+    fn adjust_permissions(&self, path: &Path, is_dir: bool) -> Result<(), Error> {
+        let Options {
+            read_only,
+            shared_repository,
+            ..
+        } = self.write_options;
+        if !read_only && shared_repository == SharedRepository::Umask {
+            return Ok(());
+        }
+        let mut permissions = fs::metadata(path)
+            .map_err(|err| Error::Io {
+                source: err,
+                message: "obtain permissions of",
+                path: path.to_owned(),
+            })?
+            .permissions();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = if read_only && !is_dir {
+                0o444
+            } else {
+                permissions.mode()
+            };
+            permissions.set_mode(shared_repository.adjust_mode(mode, is_dir));
+        }
+        #[cfg(not(unix))]
+        {
+            if read_only && !is_dir {
+                permissions.set_readonly(true);
+            }
+        }
+        fs::set_permissions(path, permissions).map_err(|err| Error::Io {
+            source: err,
+            message: "set permissions of",
+            path: path.to_owned(),
+        })
+    }
+}
+
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), Error> {
+    fs::File::open(dir)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|err| Error::Io {
+            source: err,
+            message: "sync directory",
+            path: dir.to_owned(),
+        })
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), Error> {
+    // Directories can't be opened for syncing here, and their entries are made durable with the file itself.
+    Ok(())
 }
@@ -0,0 +1,88 @@
+use std::{io, sync::Arc};
+
+use git_features::zlib;
+
+use crate::Stream;
+
+pub(crate) enum Inner {
+    /// A loose object file whose header was already consumed.
+    Loose(zlib::stream::inflate::ReadBoxed<io::BufReader<std::fs::File>>),
+    /// A pack entry that isn't a delta, with `offset` pointing to the compressed data yet to be read.
+    Pack {
+        pack: Arc<git_pack::data::File>,
+        offset: git_pack::data::Offset,
+        decompressor: Box<zlib::Decompress>,
+    },
+    /// Fully decoded object data, as needed for deltified objects.
+    Buffer(io::Cursor<Vec<u8>>),
+}
+
+impl Stream {
+    /// Create a stream over `data` of an object of the given `kind` that was fully read into memory.
+    pub fn from_data(kind: git_object::Kind, data: Vec<u8>) -> Self {
+        Stream {
+            kind,
+            size: data.len() as u64,
+            inner: Inner::Buffer(io::Cursor::new(data)),
+        }
+    }
+
+    /// Create a stream that decompresses the undeltified `entry` of an object of the given `kind` from `pack` while reading.
+    pub(crate) fn from_pack_entry(
+        kind: git_object::Kind,
+        pack: Arc<git_pack::data::File>,
+        entry: &git_pack::data::Entry,
+    ) -> Self {
+        Stream {
+            kind,
+            size: entry.decompressed_size,
+            inner: Inner::Pack {
+                pack,
+                offset: entry.data_offset,
+                decompressor: Box::new(zlib::Decompress::new(true)),
+            },
+        }
+    }
+
+    pub(crate) fn from_loose(
+        kind: git_object::Kind,
+        size: u64,
+        read: zlib::stream::inflate::ReadBoxed<io::BufReader<std::fs::File>>,
+    ) -> Self {
+        Stream {
+            kind,
+            size,
+            inner: Inner::Loose(read),
+        }
+    }
+}
+
+impl io::Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Loose(read) => read.read(buf),
+            Inner::Pack {
+                pack,
+                offset,
+                decompressor,
+            } => {
+                // Read the compressed data in chunks, as the pack may not be mapped into memory as a whole.
+                const CHUNK_SIZE: u64 = 64 * 1024;
+                let pack_end = pack.pack_end() as u64;
+                loop {
+                    let chunk = pack
+                        .entry_slice(*offset..(*offset + CHUNK_SIZE).min(pack_end))
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "pack entry is out of bounds"))?;
+                    let mut input = &chunk[..];
+                    let num_read = zlib::stream::inflate::read(&mut input, decompressor, buf)?;
+                    let consumed = (chunk.len() - input.len()) as u64;
+                    *offset += consumed;
+                    if num_read != 0 || buf.is_empty() || consumed == 0 || *offset == pack_end {
+                        break Ok(num_read);
+                    }
+                }
+            }
+            Inner::Buffer(read) => read.read(buf),
+        }
+    }
+}
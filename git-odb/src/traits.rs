@@ -12,7 +12,7 @@ pub trait Write {
     /// Write objects using the intrinsic kind of [`hash`][git_hash::Kind] into the database,
     /// returning id to reference it in subsequent reads.
     fn write(&self, object: impl WriteTo) -> Result<git_hash::ObjectId, Self::Error> {
-        let mut buf = Vec::with_capacity(2048);
+        let mut buf = Vec::with_capacity(object.size());
         object.write_to(&mut buf)?;
         self.write_stream(object.kind(), buf.len() as u64, buf.as_slice())
     }
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+echo a > a
+git add a
+git commit -qm "packed"
+mkdir dir
+echo b > dir/b
+git add dir
+git commit -qm "packed as well"
+git repack -adq
+
+echo c > dir/c
+git add dir
+git commit -qm "loose"
+git tag -m "a tag object" loose-tag
+
+git update-server-info
@@ -100,3 +100,57 @@ fn no_alternate_in_first_objects_dir() -> crate::Result {
     assert!(alternate::resolve(tmp.path())?.is_empty());
     Ok(())
 }
+
+#[test]
+fn nested_relative_paths_resolve_against_the_objects_dir_containing_the_alternates_file() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let (from, b) = alternate(tmp.path().join("a"), tmp.path().join("b"))?;
+    let (_, c) = alternate_with_content(&b, tmp.path().join("b").join("c"), b"c".to_vec(), None)?;
+
+    let alternates = alternate::resolve(from)?;
+    assert_eq!(alternates, vec![b.clone(), b.join("c")]);
+    assert_eq!(alternates[1].canonicalize()?, c.canonicalize()?);
+    Ok(())
+}
+
+#[test]
+fn missing_and_repeated_alternates_are_skipped_in_order() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let (a, b, c) = (tmp.path().join("a"), tmp.path().join("b"), tmp.path().join("c"));
+    let to_content = |paths: &[&Path]| {
+        paths
+            .iter()
+            .map(|p| p.to_str().expect("valid UTF-8"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .into_bytes()
+    };
+    let missing = tmp.path().join("missing");
+    alternate_with_content(&a, &b, to_content(&[b.as_path(), missing.as_path(), c.as_path()]), None)?;
+    alternate_with_content(&b, &c, to_content(&[c.as_path()]), None)?;
+
+    assert_eq!(
+        alternate::resolve(&a)?,
+        vec![b.clone(), c.clone()],
+        "c is reachable through a and b but is only listed once, and it's not a cycle"
+    );
+    Ok(())
+}
+
+#[test]
+fn alternates_are_not_followed_beyond_the_maximum_depth() -> crate::Result {
+    let tmp = git_testtools::tempfile::TempDir::new()?;
+    let dirs: Vec<_> = (0..=alternate::MAX_DEPTH + 1)
+        .map(|idx| tmp.path().join(idx.to_string()))
+        .collect();
+    for pair in dirs.windows(2) {
+        alternate(&pair[0], &pair[1])?;
+    }
+
+    assert_eq!(
+        alternate::resolve(&dirs[0])?,
+        dirs[1..=alternate::MAX_DEPTH].to_vec(),
+        "the last directory is too deeply nested"
+    );
+    Ok(())
+}
@@ -204,6 +204,473 @@ fn write() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn a_miss_picks_up_packs_written_by_other_processes_unless_refreshes_are_disabled() -> crate::Result {
+    let pack_name = "pack-11fdfa9e156ab73caae3b6da867192221f2089c2";
+    let id = hex_to_id("501b297447a8255d3533c6858bb692575cdefaa0");
+    for (refresh, expect_found) in [
+        (store::RefreshMode::AfterAllIndicesLoaded, true),
+        (store::RefreshMode::Never, false),
+    ] {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        std::fs::create_dir(objects_dir.path().join("pack"))?;
+        let handle = git_odb::at_opts(
+            objects_dir.path(),
+            store::init::Options {
+                refresh,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            handle.refresh_mode(),
+            refresh,
+            "handles inherit the refresh mode of the store"
+        );
+        assert!(!handle.contains(id), "the object isn't present yet");
+
+        for extension in &["idx", "pack"] {
+            let file_name = format!("{}.{}", pack_name, extension);
+            std::fs::copy(
+                fixture_path("objects/pack").join(&file_name),
+                objects_dir.path().join("pack").join(file_name),
+            )?;
+        }
+        assert_eq!(handle.contains(id), expect_found);
+    }
+    Ok(())
+}
+
+#[test]
+fn write_multi_pack_index() -> crate::Result {
+    let objects_dir = git_testtools::tempfile::tempdir()?;
+    git_testtools::copy_recursively_into_existing_dir(fixture_path("objects"), &objects_dir)?;
+    let handle = git_odb::at(objects_dir.path())?;
+    let should_interrupt = std::sync::atomic::AtomicBool::default();
+
+    let outcome = handle
+        .store_ref()
+        .write_multi_pack_index(git_features::progress::Discard, &should_interrupt)?;
+    assert!(
+        outcome.multi_index_checksum.is_some(),
+        "there was no multi-pack index yet"
+    );
+    assert_eq!(outcome.num_indices, 3);
+    assert_eq!(outcome.path, objects_dir.path().join("pack/multi-pack-index"));
+
+    let midx = git_odb::pack::multi_index::File::at(&outcome.path)?;
+    assert_eq!(midx.num_indices(), 3);
+    assert_eq!(Some(midx.checksum()), outcome.multi_index_checksum);
+
+    let outcome = handle
+        .store_ref()
+        .write_multi_pack_index(git_features::progress::Discard, &should_interrupt)?;
+    assert!(
+        outcome.multi_index_checksum.is_none(),
+        "the multi-pack index is up to date and isn't rewritten"
+    );
+
+    std::fs::remove_file(
+        objects_dir
+            .path()
+            .join("pack/pack-11fdfa9e156ab73caae3b6da867192221f2089c2.pack"),
+    )?;
+    let outcome = handle
+        .store_ref()
+        .write_multi_pack_index(git_features::progress::Discard, &should_interrupt)?;
+    assert!(
+        outcome.multi_index_checksum.is_some(),
+        "packs without their data file are not included, and the index is rewritten"
+    );
+    assert_eq!(outcome.num_indices, 2);
+
+    let handle = git_odb::at(objects_dir.path())?;
+    let mut buf = Vec::new();
+    for oid in handle.iter()? {
+        let oid = oid?;
+        assert!(
+            handle.find(oid, &mut buf).is_ok(),
+            "objects are found through the new multi-pack index"
+        );
+    }
+    Ok(())
+}
+
+mod geometric_repack {
+    use std::sync::{atomic::AtomicBool, Arc};
+
+    use git_odb::{store::repack, FindExt};
+    use git_testtools::fixture_path;
+
+    fn store_copy() -> crate::Result<(Arc<git_odb::Store>, tempfile::TempDir)> {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        git_testtools::copy_recursively_into_existing_dir(fixture_path("objects"), &objects_dir)?;
+        let store = git_odb::Store::at_opts(objects_dir.path(), Default::default())?;
+        Ok((Arc::new(store), objects_dir))
+    }
+
+    fn num_packs(objects_dir: &std::path::Path) -> crate::Result<usize> {
+        Ok(std::fs::read_dir(objects_dir.join("pack"))?
+            .filter_map(Result::ok)
+            .filter(|e| e.path().extension() == Some(std::ffi::OsStr::new("pack")))
+            .count())
+    }
+
+    #[test]
+    fn plan() -> crate::Result {
+        let (store, objects_dir) = store_copy()?;
+        assert!(matches!(
+            store.geometric_repack_plan(1),
+            Err(repack::Error::InvalidFactor { factor: 1 })
+        ));
+
+        let plan = store.geometric_repack_plan(2)?;
+        assert_eq!(
+            plan.combine.iter().map(|p| p.num_objects).collect::<Vec<_>>(),
+            vec![30, 42, 67],
+            "none of the packs is twice as large as the next smaller one"
+        );
+        assert!(plan.keep.is_empty());
+        assert_eq!(plan.num_objects_to_combine(), 139);
+
+        std::fs::write(
+            objects_dir
+                .path()
+                .join("pack/pack-c0438c19fb16422b6bbcce24387b3264416d485b.keep"),
+            b"",
+        )?;
+        let plan = store.geometric_repack_plan(2)?;
+        assert_eq!(
+            plan.combine.iter().map(|p| p.num_objects).collect::<Vec<_>>(),
+            vec![30, 42],
+            "packs with a keep-file are never combined"
+        );
+        assert_eq!(plan.keep.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn packs_are_rolled_up_and_old_ones_are_removed() -> crate::Result {
+        let (store, objects_dir) = store_copy()?;
+        let should_interrupt = AtomicBool::default();
+        store.write_multi_pack_index(git_features::progress::Discard, &should_interrupt)?;
+
+        let reader = git_odb::at(objects_dir.path())?;
+        let ids = reader.iter()?.collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(ids.len(), 146, "packed and loose objects");
+
+        let outcome = store.geometric_repack(
+            git_features::progress::Discard,
+            &should_interrupt,
+            repack::Options::default(),
+        )?;
+        assert_eq!(outcome.plan.combine.len(), 3);
+        let written = outcome.written.expect("a pack was written");
+        assert_eq!(written.index.num_objects, 139);
+        assert!(written.data_path.expect("written to disk").is_file());
+        assert_eq!(num_packs(objects_dir.path())?, 1, "the superseded packs were deleted");
+
+        let midx = git_odb::pack::multi_index::File::at(objects_dir.path().join("pack/multi-pack-index"))?;
+        assert_eq!(
+            midx.num_indices(),
+            1,
+            "the multi-pack index was updated to only contain the new pack"
+        );
+        assert_eq!(Some(midx.checksum()), outcome.multi_index_checksum);
+
+        let mut buf = Vec::new();
+        for id in &ids {
+            reader.find(id, &mut buf)?;
+        }
+        let reopened = git_odb::at(objects_dir.path())?;
+        for id in &ids {
+            reopened.find(id, &mut buf)?;
+        }
+
+        let outcome = store.geometric_repack(
+            git_features::progress::Discard,
+            &should_interrupt,
+            repack::Options::default(),
+        )?;
+        assert!(outcome.plan.is_empty(), "a single pack is a geometric progression");
+        assert!(outcome.written.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn kept_packs_are_left_alone() -> crate::Result {
+        let (store, objects_dir) = store_copy()?;
+        let kept = "pack-c0438c19fb16422b6bbcce24387b3264416d485b.idx";
+        assert!(store.kept_packs()?.is_empty());
+        let keep_path = store.keep_pack(kept, "received")?;
+        assert_eq!(std::fs::read(&keep_path)?, b"received\n");
+        assert_eq!(
+            store.keep_pack(kept, "other reason")?,
+            keep_path,
+            "keeping twice is fine and retains the original reason"
+        );
+        assert_eq!(std::fs::read(&keep_path)?, b"received\n");
+        assert!(store.is_pack_kept(objects_dir.path().join("pack").join(kept).with_extension("pack")));
+        assert_eq!(store.kept_packs()?, vec![objects_dir.path().join("pack").join(kept)]);
+
+        let outcome = store.geometric_repack(
+            git_features::progress::Discard,
+            &AtomicBool::default(),
+            repack::Options::default(),
+        )?;
+        assert_eq!(outcome.plan.combine.len(), 2);
+        assert_eq!(num_packs(objects_dir.path())?, 2, "the kept pack and the new one");
+        assert!(objects_dir.path().join("pack").join(kept).is_file());
+
+        assert!(store.unkeep_pack(kept)?);
+        assert!(!store.unkeep_pack(kept)?, "there is nothing left to remove");
+        assert!(!store.is_pack_kept(kept));
+        Ok(())
+    }
+}
+
+mod promisor {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use git_odb::{store, Find, FindExt};
+    use git_testtools::fixture_path;
+
+    const PACK: &str = "pack-11fdfa9e156ab73caae3b6da867192221f2089c2";
+
+    #[test]
+    fn promised_objects_include_the_ones_referred_to_by_promisor_packs() -> crate::Result {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        git_testtools::copy_recursively_into_existing_dir(fixture_path("objects"), &objects_dir)?;
+        let store = git_odb::Store::at_opts(objects_dir.path(), Default::default())?;
+        assert!(store.promisor_packs()?.is_empty());
+        assert!(store.promised_objects()?.is_empty());
+
+        std::fs::write(objects_dir.path().join("pack").join(format!("{}.promisor", PACK)), b"")?;
+        let pack_path = objects_dir.path().join("pack").join(format!("{}.pack", PACK));
+        assert_eq!(store.promisor_packs()?, vec![pack_path.clone()]);
+
+        let promised = store.promised_objects()?;
+        let index = git_odb::pack::index::File::at(pack_path.with_extension("idx"), git_hash::Kind::Sha1)?;
+        assert!(index.iter().all(|entry| promised.contains(&entry.oid)));
+        assert_eq!(
+            promised.len(),
+            1273,
+            "trees and parents referred to by objects in the pack are promised"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_are_fetched_lazily() -> crate::Result {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        std::fs::create_dir(objects_dir.path().join("pack"))?;
+        let index_path = fixture_path("objects/pack").join(format!("{}.idx", PACK));
+        let id = git_odb::pack::index::File::at(&index_path, git_hash::Kind::Sha1)?
+            .oid_at_index(0)
+            .to_owned();
+
+        let mut handle = git_odb::at(objects_dir.path())?;
+        let mut buf = Vec::new();
+        assert!(
+            handle.try_find(id, &mut buf)?.is_none(),
+            "there is no fetch function yet"
+        );
+
+        let num_fetches = Arc::new(AtomicUsize::default());
+        handle.set_lazy_fetch({
+            let num_fetches = Arc::clone(&num_fetches);
+            let pack_dir = objects_dir.path().join("pack");
+            move |_id| {
+                num_fetches.fetch_add(1, Ordering::SeqCst);
+                for extension in &["pack", "idx"] {
+                    let file_name = format!("{}.{}", PACK, extension);
+                    if !pack_dir.join(&file_name).is_file() {
+                        std::fs::copy(fixture_path("objects/pack").join(&file_name), pack_dir.join(&file_name))?;
+                    }
+                }
+                Ok(())
+            }
+        });
+
+        assert!(!handle.contains(id), "checking for existence never fetches objects");
+        assert_eq!(num_fetches.load(Ordering::SeqCst), 0);
+        handle.find(id, &mut buf)?;
+        assert_eq!(
+            num_fetches.load(Ordering::SeqCst),
+            1,
+            "the pack was fetched and the object found"
+        );
+        handle.find(id, &mut buf)?;
+        assert_eq!(
+            num_fetches.load(Ordering::SeqCst),
+            1,
+            "the object is now available locally"
+        );
+
+        let missing = git_hash::ObjectId::null(git_hash::Kind::Sha1);
+        assert!(handle.try_find(missing, &mut buf)?.is_none());
+        assert_eq!(
+            num_fetches.load(Ordering::SeqCst),
+            2,
+            "objects are fetched only once, and lookups give up if they didn't arrive"
+        );
+
+        handle.unset_lazy_fetch();
+        let err = {
+            let mut handle = handle.clone();
+            handle.set_lazy_fetch(|_id| Err("offline".into()));
+            handle.try_find(missing, &mut buf).unwrap_err()
+        };
+        assert!(matches!(err, store::find::Error::LazyFetch { .. }));
+        Ok(())
+    }
+}
+
+mod dumb_fetch {
+    use std::{io, path::PathBuf, sync::atomic::AtomicBool};
+
+    use git_features::progress;
+    use git_odb::{store::dumb, Find};
+    use git_testtools::{hex_to_id, scripted_fixture_repo_read_only};
+
+    /// A remote which serves the files of a local repository like a dumb HTTP server would.
+    struct Files(PathBuf);
+
+    impl dumb::Remote for Files {
+        fn get(&mut self, path: &str) -> io::Result<Option<Box<dyn io::Read + '_>>> {
+            match std::fs::File::open(self.0.join(path)) {
+                Ok(file) => Ok(Some(Box::new(file))),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    #[test]
+    fn loose_objects_and_packs_are_fetched_as_needed() -> crate::Result {
+        let remote_dir = scripted_fixture_repo_read_only("make_repo_for_dumb_fetch.sh")?.join(".git");
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        let handle = git_odb::at(objects_dir.path())?;
+        let tag = hex_to_id("b96b73e5f754a14fc805ca70b24c98468be917cf");
+
+        let outcome = dumb::fetch(
+            &handle,
+            &mut Files(remote_dir.clone()),
+            Some(tag),
+            progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(outcome.num_objects, 12, "all objects reachable from the tag");
+        assert_eq!(
+            outcome.num_loose_objects, 5,
+            "the tag and all objects of the last commit"
+        );
+        assert_eq!(
+            outcome.packs.len(),
+            1,
+            "the pack with the remaining objects is installed"
+        );
+        assert!(
+            objects_dir.path().join("pack").join(&outcome.packs[0]).is_file(),
+            "packs keep their name"
+        );
+
+        let pack_ids: Vec<_> = {
+            let index_name = outcome.packs[0].replace(".pack", ".idx");
+            let index =
+                git_odb::pack::index::File::at(objects_dir.path().join("pack").join(index_name), git_hash::Kind::Sha1)?;
+            index.iter().map(|e| e.oid).collect()
+        };
+        assert_eq!(pack_ids.len(), 7);
+        for id in pack_ids {
+            assert!(handle.contains(id));
+        }
+
+        let outcome = dumb::fetch(
+            &handle,
+            &mut Files(remote_dir),
+            Some(tag),
+            progress::Discard,
+            &AtomicBool::default(),
+        )?;
+        assert_eq!(
+            (outcome.num_objects, outcome.num_loose_objects, outcome.packs.len()),
+            (12, 0, 0),
+            "nothing is fetched if all objects are present"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_are_an_error() -> crate::Result {
+        let remote_dir = scripted_fixture_repo_read_only("make_repo_for_dumb_fetch.sh")?.join(".git");
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        let handle = git_odb::at(objects_dir.path())?;
+
+        let err = dumb::fetch(
+            &handle,
+            &mut Files(remote_dir),
+            Some(hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")),
+            progress::Discard,
+            &AtomicBool::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, dumb::Error::NotFound { .. }));
+        Ok(())
+    }
+}
+
+mod replacements {
+    use git_odb::{store, Find, FindExt};
+    use git_testtools::{fixture_path, hex_to_id};
+
+    #[test]
+    fn replaced_objects_are_returned_in_place_of_the_original_unless_ignored() -> crate::Result {
+        let original = hex_to_id("501b297447a8255d3533c6858bb692575cdefaa0");
+        let replacement = hex_to_id("4dac9989f96bc5b5b1263b582c08f0c5f0b58542");
+        let looping = hex_to_id("dd25c539efbb0ab018caa4cda2d133285634e9b5");
+        let mut handle = git_odb::at_opts(
+            fixture_path("objects"),
+            store::init::Options {
+                replacements: vec![(original, replacement), (looping, looping)],
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            handle.store_ref().replacements().collect::<Vec<_>>(),
+            vec![(original, replacement), (looping, looping)],
+            "replacements are sorted by the original id"
+        );
+
+        let (mut buf, mut expected) = (Vec::new(), Vec::new());
+        handle.ignore_replacements = true;
+        let expected_kind = handle.find(replacement, &mut expected)?.kind;
+        handle.find(original, &mut buf)?;
+        assert_ne!(buf, expected, "replacements can be ignored");
+
+        handle.ignore_replacements = false;
+        let obj = handle.find(original, &mut buf)?;
+        assert_eq!(obj.kind, expected_kind);
+        assert_eq!(
+            obj.data,
+            &expected[..],
+            "the replacement is returned for the original id"
+        );
+        assert!(
+            handle.contains(original),
+            "existence checks are not affected, the original object is still present"
+        );
+
+        assert!(matches!(
+            handle.try_find(looping, &mut buf),
+            Err(store::find::Error::ReplacementDepthExceeded { .. })
+        ));
+        Ok(())
+    }
+}
+
 #[test]
 fn contains() {
     let handle = db();
@@ -592,6 +1059,177 @@ fn iterate_over_a_bunch_of_loose_and_packed_objects() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn contains_many_checks_all_objects_at_once_without_opening_packs() -> crate::Result {
+    let (db, tmp) = db_with_all_object_sources()?;
+    let mut ids = db.iter()?.collect::<Result<Vec<_>, _>>()?;
+    let missing = hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+    ids.insert(ids.len() / 2, missing);
+    ids.push(missing);
+
+    let found = git_odb::at(tmp.path())?.contains_many(&ids);
+    assert_eq!(found.len(), ids.len());
+    for (id, found) in ids.iter().zip(found) {
+        assert_eq!(found, *id != missing, "{} is found unless it's the missing one", id);
+    }
+
+    let handle = git_odb::at(tmp.path())?;
+    assert_eq!(
+        handle.contains_many(ids.iter().rev()).iter().filter(|f| **f).count(),
+        146
+    );
+    assert_eq!(
+        handle.store_ref().metrics().open_reachable_packs,
+        0,
+        "only indices and loose object directories are consulted"
+    );
+    assert!(db.contains_many(Vec::<git_hash::ObjectId>::new()).is_empty());
+    Ok(())
+}
+
+mod metrics {
+    use std::collections::HashMap;
+
+    use git_odb::FindExt;
+    use git_testtools::hex_to_id;
+
+    #[derive(Default)]
+    struct ObjectCache(HashMap<git_hash::ObjectId, (git_object::Kind, Vec<u8>)>);
+
+    impl git_pack::cache::Object for ObjectCache {
+        fn put(&mut self, id: git_hash::ObjectId, kind: git_object::Kind, data: &[u8]) {
+            self.0.insert(id, (kind, data.to_owned()));
+        }
+
+        fn get(&mut self, id: &git_hash::ObjectId, out: &mut Vec<u8>) -> Option<git_object::Kind> {
+            self.0.get(id).map(|(kind, data)| {
+                out.clear();
+                out.extend_from_slice(data);
+                *kind
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct PackCache(HashMap<(u32, u64), (git_object::Kind, Vec<u8>, usize)>);
+
+    impl git_pack::cache::DecodeEntry for PackCache {
+        fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: git_object::Kind, compressed_size: usize) {
+            self.0
+                .insert((pack_id, offset), (kind, data.to_owned(), compressed_size));
+        }
+
+        fn get(&mut self, pack_id: u32, offset: u64, out: &mut Vec<u8>) -> Option<(git_object::Kind, usize)> {
+            self.0.get(&(pack_id, offset)).map(|(kind, data, compressed_size)| {
+                out.clear();
+                out.extend_from_slice(data);
+                (*kind, *compressed_size)
+            })
+        }
+    }
+
+    #[test]
+    fn object_access_is_counted_per_store() -> crate::Result {
+        let handle = super::db();
+        assert_eq!(
+            handle.store_ref().access_metrics(),
+            git_odb::store::AccessMetrics::default(),
+            "nothing was accessed yet"
+        );
+
+        let mut buf = Vec::new();
+        handle.find(hex_to_id("37d4e6c5c48ba0d245164c4e10d5f41140cab980"), &mut buf)?; // loose object
+        handle.find(hex_to_id("dd25c539efbb0ab018caa4cda2d133285634e9b5"), &mut buf)?; // pack c043
+        let metrics = handle.store_ref().access_metrics();
+        assert_eq!(metrics.loose_objects, 1);
+        assert_eq!(metrics.packed_objects, 1);
+        assert_eq!(
+            metrics.packs_mapped, 1,
+            "only the pack containing the object was mapped"
+        );
+        assert!(metrics.bytes_read > 0);
+
+        let mut count = 0;
+        for id in handle.iter()? {
+            handle.find(id?, &mut buf)?;
+            count += 1;
+        }
+        let metrics = handle.store_ref().access_metrics();
+        assert_eq!(metrics.loose_objects + metrics.packed_objects, 2 + count);
+        assert_eq!(metrics.packs_mapped, 3);
+        assert!(metrics.deltas_resolved > 0, "some objects are stored as deltas");
+        assert_eq!(
+            handle.cache_metrics(),
+            git_odb::cache::Metrics::default(),
+            "without caches, there is nothing to count"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cache_use_is_counted_per_handle() -> crate::Result {
+        let handle = super::db()
+            .with_object_cache(|| Box::new(ObjectCache::default()))
+            .with_pack_cache(|| Box::new(PackCache::default()));
+        let id = hex_to_id("dd25c539efbb0ab018caa4cda2d133285634e9b5");
+        let mut buf = Vec::new();
+        handle.find(id, &mut buf)?;
+        handle.find(id, &mut buf)?;
+        let metrics = handle.cache_metrics();
+        assert_eq!(metrics.object_cache_misses, 1);
+        assert_eq!(metrics.object_cache_hits, 1);
+        assert_eq!(
+            handle.store_ref().access_metrics().packed_objects,
+            1,
+            "the second lookup was served from the object cache"
+        );
+
+        for id in handle.iter()? {
+            handle.find(id?, &mut buf)?;
+        }
+        let metrics = handle.cache_metrics();
+        assert!(
+            metrics.pack_cache_hits + metrics.pack_cache_misses > 0,
+            "delta bases are looked up in the pack cache"
+        );
+        assert_eq!(
+            handle.clone().cache_metrics(),
+            git_odb::cache::Metrics::default(),
+            "clones have their own caches and start counting anew"
+        );
+        Ok(())
+    }
+}
+
+#[test]
+fn streamed_objects_match_the_ones_read_into_memory() -> crate::Result {
+    use std::io::Read;
+
+    let (db, _tmp) = db_with_all_object_sources()?;
+    let (mut buf, mut streamed) = (Vec::new(), Vec::new());
+    let mut count = 0;
+    for id in db.iter()? {
+        let id = id?;
+        let mut stream = db.try_stream(id)?.expect("object exists");
+        streamed.clear();
+        stream.read_to_end(&mut streamed)?;
+
+        let obj = db.find(id, &mut buf)?;
+        assert_eq!(stream.kind, obj.kind);
+        assert_eq!(stream.size, obj.data.len() as u64);
+        assert_eq!(
+            streamed, obj.data,
+            "loose, packed and deltified objects stream the same data"
+        );
+        count += 1;
+    }
+    assert_eq!(count, 146);
+    assert!(db
+        .try_stream(hex_to_id("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"))?
+        .is_none());
+    Ok(())
+}
+
 #[test]
 fn auto_refresh_with_and_without_id_stability() -> crate::Result {
     let tmp = git_testtools::tempfile::TempDir::new()?;
@@ -798,3 +1436,103 @@ mod verify {
         );
     }
 }
+
+mod quarantine {
+    use std::sync::atomic::AtomicBool;
+
+    use git_features::progress;
+    use git_odb::{store::quarantine, Find, Write};
+    use git_testtools::fixture_path;
+
+    const PACK: &str = "objects/pack/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1";
+
+    fn tree_with_entry(id: &git_hash::oid) -> Vec<u8> {
+        let mut tree = b"100644 file\0".to_vec();
+        tree.extend_from_slice(id.as_bytes());
+        tree
+    }
+
+    #[test]
+    fn complete_objects_are_migrated_into_the_object_database() -> crate::Result {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        let odb = git_odb::at(objects_dir.path())?;
+        let quarantine = odb.store_ref().quarantine()?;
+        assert!(quarantine.path().starts_with(objects_dir.path()));
+
+        for extension in &["pack", "idx"] {
+            let file_name = format!("{}.{}", PACK, extension);
+            std::fs::copy(
+                fixture_path(&file_name),
+                quarantine
+                    .path()
+                    .join("pack")
+                    .join(std::path::Path::new(&file_name).file_name().expect("present")),
+            )?;
+        }
+        let incoming = git_features::threading::OwnShared::new(quarantine.to_store()?).to_handle();
+        let blob = incoming.write_buf(git_object::Kind::Blob, b"hello")?;
+        let tree = incoming.write_buf(git_object::Kind::Tree, &tree_with_entry(&blob))?;
+
+        let pack_ids: Vec<_> =
+            git_odb::pack::index::File::at(fixture_path(format!("{}.idx", PACK)), git_hash::Kind::Sha1)?
+                .iter()
+                .map(|e| e.oid)
+                .collect();
+        assert_eq!(
+            quarantine.check_connectivity(
+                pack_ids.iter().copied().chain(Some(tree)),
+                progress::Discard,
+                &AtomicBool::default()
+            )?,
+            pack_ids.len() + 2,
+            "all objects are in the quarantine"
+        );
+        assert!(
+            !odb.contains(tree),
+            "quarantined objects aren't visible in the object database"
+        );
+
+        let quarantine_path = quarantine.path().to_owned();
+        let outcome = quarantine.migrate()?;
+        assert_eq!(outcome.num_loose_objects, 2);
+        assert_eq!(
+            outcome.packs,
+            vec![objects_dir
+                .path()
+                .join("pack/pack-a2bf8e71d8c18879e499335762dd95119d93d9f1.pack")]
+        );
+        assert!(!quarantine_path.exists(), "the quarantine is removed after migration");
+        for id in pack_ids.iter().chain(Some(&tree)).chain(Some(&blob)) {
+            assert!(odb.contains(id), "{} was migrated", id);
+        }
+
+        let quarantine = odb.store_ref().quarantine()?;
+        assert_eq!(
+            quarantine.check_connectivity(Some(tree), progress::Discard, &AtomicBool::default())?,
+            0,
+            "objects in the object database are assumed to be complete"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_objects_are_detected_and_discarded_objects_are_gone() -> crate::Result {
+        let objects_dir = git_testtools::tempfile::tempdir()?;
+        let odb = git_odb::at(objects_dir.path())?;
+        let quarantine = odb.store_ref().quarantine()?;
+        let incoming = git_features::threading::OwnShared::new(quarantine.to_store()?).to_handle();
+        let missing = git_hash::ObjectId::null(git_hash::Kind::Sha1);
+        let tree = incoming.write_buf(git_object::Kind::Tree, &tree_with_entry(&missing))?;
+
+        let err = quarantine
+            .check_connectivity(Some(tree), progress::Discard, &AtomicBool::default())
+            .unwrap_err();
+        assert!(matches!(err, quarantine::Error::Missing { id } if id == missing));
+
+        let quarantine_path = quarantine.path().to_owned();
+        quarantine.discard()?;
+        assert!(!quarantine_path.exists());
+        assert!(!odb.contains(tree));
+        Ok(())
+    }
+}
@@ -70,6 +70,195 @@ mod write {
         }
         Ok(())
     }
+
+    #[test]
+    fn durable_read_only_and_shared_objects_can_be_written_repeatedly() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let db = loose::Store::at_opts(
+            dir.path(),
+            git_hash::Kind::Sha1,
+            loose::write::Options {
+                fsync: loose::write::Fsync::ObjectsAndDirectories,
+                read_only: true,
+                shared_repository: loose::write::SharedRepository::Group,
+            },
+        );
+        let mut buf = Vec::new();
+        let mut buf2 = Vec::new();
+        for oid in object_ids() {
+            let obj = locate_oid(oid, &mut buf);
+            for _ in 0..2 {
+                assert_eq!(
+                    db.write_buf(obj.kind, obj.data)?,
+                    oid,
+                    "existing read-only objects are no problem"
+                );
+            }
+            assert!(db.try_find(oid, &mut buf2)?.is_some());
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let oid = object_ids()[0].to_hex().to_string();
+            let object_dir = dir.path().join(&oid[..2]);
+            assert_eq!(
+                std::fs::metadata(object_dir.join(&oid[2..]))?.permissions().mode() & 0o7777,
+                0o444,
+                "object files are read-only, and group write permissions aren't added to them"
+            );
+            assert_eq!(
+                std::fs::metadata(&object_dir)?.permissions().mode() & 0o2070,
+                0o2070,
+                "directories are accessible by the group and inherit it"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn shared_repository_from_config_value_and_mode_adjustments() {
+        use loose::write::SharedRepository;
+        for (value, expected) in [
+            ("umask", Some(SharedRepository::Umask)),
+            ("false", Some(SharedRepository::Umask)),
+            ("group", Some(SharedRepository::Group)),
+            ("1", Some(SharedRepository::Group)),
+            ("everybody", Some(SharedRepository::All)),
+            ("0640", Some(SharedRepository::Mode(0o640))),
+            ("0440", None),
+            ("everyone", None),
+        ] {
+            assert_eq!(
+                SharedRepository::from_config_value(value.as_bytes()),
+                expected,
+                "{}",
+                value
+            );
+        }
+
+        assert_eq!(SharedRepository::Umask.adjust_mode(0o600, false), 0o600);
+        assert_eq!(SharedRepository::Group.adjust_mode(0o644, false), 0o664);
+        assert_eq!(SharedRepository::Group.adjust_mode(0o444, false), 0o444);
+        assert_eq!(SharedRepository::All.adjust_mode(0o700, true), 0o2775);
+        assert_eq!(SharedRepository::Mode(0o640).adjust_mode(0o666, false), 0o640);
+        assert_eq!(SharedRepository::Mode(0o640).adjust_mode(0o755, true), 0o2750);
+    }
+}
+
+mod unpack {
+    use std::sync::atomic::AtomicBool;
+
+    use git_features::progress;
+    use git_odb::{loose, pack, FindExt};
+
+    use crate::fixture_path;
+
+    const PACK: &str = "objects/pack/pack-11fdfa9e156ab73caae3b6da867192221f2089c2.pack";
+
+    #[test]
+    fn all_objects_of_a_pack_are_written_as_loose_objects() -> crate::Result {
+        let pack_path = fixture_path(PACK);
+        let index = pack::index::File::at(pack_path.with_extension("idx"), git_hash::Kind::Sha1)?;
+        let dir = tempfile::tempdir()?;
+        let db = loose::Store::at(dir.path(), git_hash::Kind::Sha1);
+
+        let outcome = db.unpack_objects(
+            std::io::BufReader::new(std::fs::File::open(&pack_path)?),
+            |_, _| None,
+            progress::Discard,
+            &AtomicBool::new(false),
+        )?;
+        assert_eq!(outcome.num_objects, index.num_objects() as usize);
+        assert_eq!(outcome.num_external_bases, 0, "the pack is not thin");
+        assert_eq!(outcome.pack_checksum, index.pack_checksum());
+
+        let odb = git_odb::at(fixture_path("objects"))?;
+        let (mut buf, mut expected_buf) = (Vec::new(), Vec::new());
+        for entry in index.iter() {
+            let expected = odb.find(entry.oid, &mut expected_buf)?;
+            let actual = db.try_find(entry.oid, &mut buf)?.expect("object was unpacked");
+            assert_eq!(actual, expected);
+        }
+        assert_eq!(db.iter().count(), outcome.num_objects);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_packs_are_detected() -> crate::Result {
+        let mut data = std::fs::read(fixture_path(PACK))?;
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        let dir = tempfile::tempdir()?;
+        let db = loose::Store::at(dir.path(), git_hash::Kind::Sha1);
+
+        let err = db
+            .unpack_objects(&data[..], |_, _| None, progress::Discard, &AtomicBool::new(false))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            loose::unpack::Error::PackIter(pack::data::input::Error::ChecksumMismatch { .. })
+        ));
+        Ok(())
+    }
+}
+
+mod prune {
+    use std::{sync::atomic::AtomicBool, time::Duration};
+
+    use git_features::progress;
+    use git_odb::{loose, Write};
+
+    use crate::store::loose::{locate_oid, object_ids};
+
+    #[test]
+    fn only_unreachable_objects_older_than_the_expiry_are_removed() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let db = loose::Store::at(dir.path(), git_hash::Kind::Sha1);
+        let mut buf = Vec::new();
+        for id in object_ids() {
+            let obj = locate_oid(id, &mut buf);
+            db.write_buf(obj.kind, obj.data)?;
+        }
+        let ids = object_ids();
+        let (reachable, unreachable) = ids.split_at(5);
+        let prune = |options| {
+            db.prune(
+                |id| reachable.iter().any(|r| r.as_ref() == id),
+                options,
+                progress::Discard,
+                &AtomicBool::default(),
+            )
+        };
+
+        let outcome = prune(loose::prune::Options::default())?;
+        assert_eq!(outcome.num_objects, 7);
+        assert!(
+            outcome.pruned.is_empty(),
+            "all objects are new and protected by the grace period"
+        );
+
+        let no_grace = loose::prune::Options {
+            expire: Duration::from_secs(0),
+            dry_run: true,
+        };
+        let mut outcome = prune(no_grace)?;
+        outcome.pruned.sort();
+        assert_eq!(outcome.pruned, unreachable);
+        assert!(outcome.pruned_bytes > 0);
+        assert_eq!(db.iter().count(), 7, "nothing is removed in dry-run mode");
+
+        let mut outcome = prune(loose::prune::Options {
+            dry_run: false,
+            ..no_grace
+        })?;
+        outcome.pruned.sort();
+        assert_eq!(outcome.pruned, unreachable);
+        let mut remaining = db.iter().collect::<Result<Vec<_>, _>>()?;
+        remaining.sort();
+        assert_eq!(remaining, reachable);
+        Ok(())
+    }
 }
 
 mod contains {
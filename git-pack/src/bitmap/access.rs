@@ -0,0 +1,168 @@
+use std::{convert::TryFrom, path::Path};
+
+use git_bitmap::Bitset;
+
+use crate::{
+    bitmap::{EntryIndex, File, PackOrder, PackPosition},
+    index,
+};
+
+/// Constants
+impl File {
+    pub(crate) const SIGNATURE: &'static [u8] = b"BITM";
+    /// Signature, version, flags and amount of entries.
+    pub(crate) const HEADER_LEN: usize = 4 + 2 + 2 + 4;
+    /// Bitmaps of all commits are closed under reachability, which is always set by git.
+    pub(crate) const FLAG_FULL_DAG: u16 = 0x1;
+    /// A cache of name-hashes for each object in pack order follows the bitmaps.
+    pub(crate) const FLAG_HASH_CACHE: u16 = 0x4;
+    /// A table to lookup commit entries without reading all of them follows the name-hash cache.
+    pub(crate) const FLAG_LOOKUP_TABLE: u16 = 0x10;
+}
+
+/// Access
+impl File {
+    /// The path of the opened bitmap file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The kind of hash we assume.
+    pub fn object_hash(&self) -> git_hash::Kind {
+        self.object_hash
+    }
+
+    /// The checksum of the pack whose objects are referred to by this bitmap.
+    pub fn pack_checksum(&self) -> git_hash::ObjectId {
+        let start = Self::HEADER_LEN;
+        git_hash::ObjectId::from(&self.data[start..][..self.object_hash.len_in_bytes()])
+    }
+
+    /// The checksum over the entire content of the file (excluding the checksum itself).
+    pub fn checksum(&self) -> git_hash::ObjectId {
+        git_hash::ObjectId::from(&self.data[self.data.len() - self.object_hash.len_in_bytes()..])
+    }
+
+    /// The amount of commits that have a bitmap of reachable objects.
+    pub fn num_commits(&self) -> u32 {
+        self.entries.len() as u32
+    }
+
+    /// Returns true if the file contains a name-hash for each object, as accessible with [`name_hash()`][File::name_hash()].
+    pub fn has_name_hash_cache(&self) -> bool {
+        self.name_hashes.is_some()
+    }
+
+    /// The name-hash of the object at `pack_position`, useful to group similar objects when looking for delta bases,
+    /// or `None` if there is no name-hash cache or if the position is out of bounds.
+    pub fn name_hash(&self, pack_position: PackPosition) -> Option<u32> {
+        let range = self.name_hashes.as_ref()?;
+        let start = range.start + pack_position as usize * 4;
+        (start + 4 <= range.end).then(|| crate::read_u32(&self.data[start..][..4]))
+    }
+
+    /// Return the set of all objects in the pack of the given `kind`.
+    pub fn objects_of_kind(&self, kind: git_object::Kind) -> Bitset {
+        use git_object::Kind::*;
+        match kind {
+            Commit => &self.commits,
+            Tree => &self.trees,
+            Blob => &self.blobs,
+            Tag => &self.tags,
+        }
+        .to_bitset()
+    }
+
+    /// Return the position of the commit at `entry_index` in the pack index, for use with [`index::File::oid_at_index()`].
+    ///
+    /// # Panics
+    ///
+    /// If `entry_index` is out of bounds.
+    pub fn commit_index_position_at(&self, entry_index: EntryIndex) -> index::EntryIndex {
+        self.entries[entry_index as usize].index_position
+    }
+
+    /// Return the set of all objects reachable from the commit at `entry_index`, including the commit itself.
+    ///
+    /// # Panics
+    ///
+    /// If `entry_index` is out of bounds.
+    pub fn reachable_at(&self, entry_index: EntryIndex) -> Bitset {
+        let mut entry_index = entry_index as usize;
+        let mut bits = self.entries[entry_index].bitmap.to_bitset();
+        loop {
+            let xor_offset = self.entries[entry_index].xor_offset as usize;
+            if xor_offset == 0 {
+                break bits;
+            }
+            // Validated to be in bounds when opening the file.
+            entry_index -= xor_offset;
+            bits.xor_with(&self.entries[entry_index].bitmap.to_bitset());
+        }
+    }
+
+    /// Return the set of all objects reachable from the commit with `id`, including the commit itself,
+    /// using `index` to learn about the commit's position in the pack.
+    ///
+    /// Returns `None` if the commit isn't in the pack or wasn't selected to receive a bitmap, in which case
+    /// the caller has to traverse the commit graph until it encounters commits that have one.
+    pub fn reachable_from(&self, index: &index::File, id: impl AsRef<git_hash::oid>) -> Option<Bitset> {
+        let index_position = index.lookup(id)?;
+        self.entry_by_index_position
+            .get(&index_position)
+            .map(|entry_index| self.reachable_at(*entry_index))
+    }
+}
+
+/// Initialization
+impl PackOrder {
+    /// Compute the pack order of all objects in `index`.
+    pub fn from_index(index: &index::File) -> Self {
+        let mut offsets_and_index_positions: Vec<_> = index
+            .iter()
+            .enumerate()
+            .map(|(index_position, entry)| (entry.pack_offset, index_position as index::EntryIndex))
+            .collect();
+        offsets_and_index_positions.sort_unstable();
+
+        let index_position_by_pack_position: Vec<_> = offsets_and_index_positions
+            .into_iter()
+            .map(|(_, index_position)| index_position)
+            .collect();
+        let mut pack_position_by_index_position = vec![0; index_position_by_pack_position.len()];
+        for (pack_position, index_position) in index_position_by_pack_position.iter().enumerate() {
+            pack_position_by_index_position[*index_position as usize] = pack_position as PackPosition;
+        }
+        PackOrder {
+            index_position_by_pack_position,
+            pack_position_by_index_position,
+        }
+    }
+}
+
+/// Access
+impl PackOrder {
+    /// Return the position of the object at `pack_position` in the pack index, or `None` if it is out of bounds.
+    pub fn index_position(&self, pack_position: PackPosition) -> Option<index::EntryIndex> {
+        self.index_position_by_pack_position
+            .get(pack_position as usize)
+            .copied()
+    }
+
+    /// Return the position in pack order of the object at `index_position` in the pack index, or `None` if it is out of bounds.
+    pub fn pack_position(&self, index_position: index::EntryIndex) -> Option<PackPosition> {
+        self.pack_position_by_index_position
+            .get(index_position as usize)
+            .copied()
+    }
+
+    /// Return an iterator over the ids of all objects set in `bits`, looked up in `index`, in pack order.
+    ///
+    /// Bits that are out of bounds are ignored.
+    pub fn ids<'a>(&'a self, index: &'a index::File, bits: &'a Bitset) -> impl Iterator<Item = &'a git_hash::oid> + 'a {
+        bits.iter_ones()
+            .filter_map(move |pack_position| PackPosition::try_from(pack_position).ok())
+            .filter_map(move |pack_position| self.index_position(pack_position))
+            .map(move |index_position| index.oid_at_index(index_position))
+    }
+}
@@ -0,0 +1,155 @@
+use std::{collections::HashMap, convert::TryFrom, path::Path};
+
+use crate::bitmap::File;
+
+mod error {
+    /// The error returned by [File::at()][super::File::at()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not open bitmap file at '{path}'")]
+        Io {
+            source: std::io::Error,
+            path: std::path::PathBuf,
+        },
+        #[error("{message}")]
+        Corrupt { message: &'static str },
+        #[error("Unsupported bitmap version: {version}")]
+        UnsupportedVersion { version: u16 },
+        #[error("Bitmaps which don't cover the full commit graph are unsupported")]
+        MissingFullDag,
+        #[error("Could not decode the {kind} bitmap")]
+        Ewah {
+            source: git_bitmap::ewah::decode::Error,
+            kind: &'static str,
+        },
+    }
+}
+
+pub use error::Error;
+
+/// An entry for a single commit, whose bitmap may have to be combined with the one of a previous entry.
+pub(crate) struct Entry {
+    pub(crate) index_position: crate::index::EntryIndex,
+    pub(crate) xor_offset: u8,
+    pub(crate) bitmap: git_bitmap::ewah::Vec,
+}
+
+/// The largest distance to a previous entry to xor our bitmap with, as written by git.
+const MAX_XOR_OFFSET: u8 = 160;
+
+/// Initialization
+impl File {
+    /// Open the bitmap file at the given `path`, expecting object ids of kind `object_hash`.
+    pub fn at(path: impl AsRef<Path>, object_hash: git_hash::Kind) -> Result<Self, Error> {
+        Self::try_from((path.as_ref(), object_hash))
+    }
+}
+
+impl TryFrom<(&Path, git_hash::Kind)> for File {
+    type Error = Error;
+
+    fn try_from((path, object_hash): (&Path, git_hash::Kind)) -> Result<Self, Self::Error> {
+        let data = crate::mmap::read_only(path).map_err(|source| Error::Io {
+            source,
+            path: path.to_owned(),
+        })?;
+
+        let hash_len = object_hash.len_in_bytes();
+        let pack_checksum_and_trailer_len = hash_len * 2;
+        if data.len() < Self::HEADER_LEN + pack_checksum_and_trailer_len {
+            return Err(Error::Corrupt {
+                message: "bitmap file is truncated and too short",
+            });
+        }
+
+        let (flags, num_entries) = {
+            let (signature, rest) = data.split_at(4);
+            if signature != Self::SIGNATURE {
+                return Err(Error::Corrupt {
+                    message: "Invalid signature",
+                });
+            }
+            let version = u16::from_be_bytes([rest[0], rest[1]]);
+            if version != 1 {
+                return Err(Error::UnsupportedVersion { version });
+            }
+            let flags = u16::from_be_bytes([rest[2], rest[3]]);
+            if flags & Self::FLAG_FULL_DAG == 0 {
+                return Err(Error::MissingFullDag);
+            }
+            (flags, crate::read_u32(&rest[4..8]))
+        };
+
+        let body_end = data.len() - hash_len;
+        let mut body = &data[Self::HEADER_LEN + hash_len..body_end];
+
+        let decode = |kind: &'static str, body: &mut &[u8]| {
+            let (bitmap, rest) = git_bitmap::ewah::decode(*body).map_err(|source| Error::Ewah { source, kind })?;
+            *body = rest;
+            Ok::<_, Error>(bitmap)
+        };
+        let commits = decode("commit type", &mut body)?;
+        let trees = decode("tree type", &mut body)?;
+        let blobs = decode("blob type", &mut body)?;
+        let tags = decode("tag type", &mut body)?;
+
+        let mut entries = Vec::with_capacity(num_entries as usize);
+        let mut entry_by_index_position = HashMap::with_capacity(num_entries as usize);
+        for entry_index in 0..num_entries {
+            if body.len() < 6 {
+                return Err(Error::Corrupt {
+                    message: "bitmap entry header is truncated",
+                });
+            }
+            let index_position = crate::read_u32(&body[..4]);
+            let xor_offset = body[4];
+            body = &body[6..];
+            if xor_offset > MAX_XOR_OFFSET || u32::from(xor_offset) > entry_index {
+                return Err(Error::Corrupt {
+                    message: "bitmap entry refers to an invalid previous entry to xor with",
+                });
+            }
+            let bitmap = decode("commit", &mut body)?;
+            entries.push(Entry {
+                index_position,
+                xor_offset,
+                bitmap,
+            });
+            entry_by_index_position.insert(index_position, entry_index);
+        }
+
+        let name_hashes = if flags & Self::FLAG_HASH_CACHE != 0 {
+            let lookup_table_len = if flags & Self::FLAG_LOOKUP_TABLE != 0 {
+                num_entries as usize * (4 + 8 + 4)
+            } else {
+                0
+            };
+            let name_hash_len = body.len().checked_sub(lookup_table_len).ok_or(Error::Corrupt {
+                message: "bitmap lookup table is truncated",
+            })?;
+            if name_hash_len % 4 != 0 {
+                return Err(Error::Corrupt {
+                    message: "the name-hash cache doesn't consist of 4 byte entries",
+                });
+            }
+            let start = body_end - body.len();
+            Some(start..start + name_hash_len)
+        } else {
+            None
+        };
+
+        Ok(File {
+            data,
+            path: path.to_owned(),
+            object_hash,
+            commits,
+            trees,
+            blobs,
+            tags,
+            entries,
+            entry_by_index_position,
+            name_hashes,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::PathBuf;
+
+use memmap2::Mmap;
+
+/// The type for referring to a position of an object within the pack, with objects ordered by their pack offset.
+///
+/// This is how bits in all bitmaps are addressed.
+pub type PackPosition = u32;
+
+/// The type for referring to a commit [entry][File::num_commits()] of the bitmap file.
+pub type EntryIndex = u32;
+
+/// A reachability bitmap file, typically stored alongside its pack as `pack-<checksum>.bitmap`.
+///
+/// It associates selected commits with the set of all objects reachable from them, as well as a set of objects for each object kind.
+/// Each bit refers to an object in the pack by its [position in pack order][PackPosition], which can be translated to object ids
+/// with a [`PackOrder`].
+pub struct File {
+    data: Mmap,
+    path: PathBuf,
+    object_hash: git_hash::Kind,
+
+    commits: git_bitmap::ewah::Vec,
+    trees: git_bitmap::ewah::Vec,
+    blobs: git_bitmap::ewah::Vec,
+    tags: git_bitmap::ewah::Vec,
+    entries: Vec<init::Entry>,
+    entry_by_index_position: std::collections::HashMap<crate::index::EntryIndex, EntryIndex>,
+    name_hashes: Option<std::ops::Range<usize>>,
+}
+
+/// A mapping between the positions of objects in a pack index and their position in pack order, as used in bitmaps.
+pub struct PackOrder {
+    index_position_by_pack_position: Vec<crate::index::EntryIndex>,
+    pack_position_by_index_position: Vec<PackPosition>,
+}
+
+mod access;
+
+///
+pub mod init;
+
+///
+pub mod reachable;
+
+///
+pub mod write;
@@ -0,0 +1,133 @@
+use git_bitmap::Bitset;
+use git_hash::{oid, ObjectId};
+use git_object::{tree::EntryMode, Kind};
+
+use crate::{
+    bitmap::{File, PackOrder, PackPosition},
+    find, index, FindExt,
+};
+
+/// The error returned by [`File::objects_reachable_from()`] and related methods.
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error<E: std::error::Error + 'static> {
+    #[error(transparent)]
+    FindExisting(#[from] find::existing::Error<E>),
+    #[error(transparent)]
+    ObjectDecode(#[from] git_object::decode::Error),
+}
+
+/// Reachability
+impl File {
+    /// Return the set of all objects reachable from `tips` as bits in [pack order][PackOrder], using the bitmaps of commits
+    /// where available. Other objects are looked up with `find` and decoded to traverse their parents, trees and targets until
+    /// objects with a bitmap or objects already known to be reachable are encountered.
+    ///
+    /// `index` and `order` must belong to the pack of this bitmap. Returns `None` if an object reachable from `tips` which
+    /// isn't covered by a bitmap isn't contained in the pack, as it can't be represented by bits. The caller has to fall back
+    /// to traversing the object graph in this case.
+    pub fn objects_reachable_from<F>(
+        &self,
+        index: &index::File,
+        order: &PackOrder,
+        tips: impl IntoIterator<Item = ObjectId>,
+        find: F,
+    ) -> Result<Option<Bitset>, Error<F::Error>>
+    where
+        F: crate::Find,
+    {
+        let position = |id: &oid| -> Option<(index::EntryIndex, PackPosition)> {
+            let index_position = index.lookup(id)?;
+            Some((index_position, order.pack_position(index_position)?))
+        };
+        let mut bits = Bitset::with_capacity(index.num_objects() as usize);
+        let mut queue: Vec<ObjectId> = tips.into_iter().collect();
+        let mut buf = Vec::new();
+        while let Some(id) = queue.pop() {
+            let (index_position, pack_position) = match position(&id) {
+                Some(positions) => positions,
+                None => return Ok(None),
+            };
+            if bits.get(pack_position as usize) {
+                continue;
+            }
+            if let Some(entry_index) = self.entry_by_index_position.get(&index_position) {
+                bits.union_with(&self.reachable_at(*entry_index));
+                continue;
+            }
+            bits.set(pack_position as usize);
+
+            let (object, _location) = find.find(&id, &mut buf)?;
+            match object.kind {
+                Kind::Commit => {
+                    let mut commit = git_object::CommitRefIter::from_bytes(object.data);
+                    queue.push(commit.tree_id()?);
+                    queue.extend(commit.parent_ids());
+                }
+                Kind::Tag => queue.push(git_object::TagRefIter::from_bytes(object.data).target_id()?),
+                Kind::Tree => {
+                    for entry in git_object::TreeRefIter::from_bytes(object.data) {
+                        let entry = entry?;
+                        match entry.mode {
+                            EntryMode::Tree => queue.push(entry.oid.to_owned()),
+                            EntryMode::Commit => {}
+                            _blob => match position(entry.oid) {
+                                Some((_, pack_position)) => bits.set(pack_position as usize),
+                                None => return Ok(None),
+                            },
+                        }
+                    }
+                }
+                Kind::Blob => {}
+            }
+        }
+        Ok(Some(bits))
+    }
+
+    /// Return the set of all objects reachable from `tips` but not from `haves` as bits in [pack order][PackOrder], or
+    /// `None` if this can't be determined with bitmaps. See [`objects_reachable_from()`][File::objects_reachable_from()]
+    /// for details.
+    pub fn objects_reachable_from_excluding<F>(
+        &self,
+        index: &index::File,
+        order: &PackOrder,
+        tips: impl IntoIterator<Item = ObjectId>,
+        haves: impl IntoIterator<Item = ObjectId>,
+        find: F,
+    ) -> Result<Option<Bitset>, Error<F::Error>>
+    where
+        F: crate::Find,
+    {
+        let mut bits = match self.objects_reachable_from(index, order, tips, &find)? {
+            Some(bits) => bits,
+            None => return Ok(None),
+        };
+        match self.objects_reachable_from(index, order, haves, &find)? {
+            Some(excluded) => bits.difference_with(&excluded),
+            None => return Ok(None),
+        }
+        Ok(Some(bits))
+    }
+
+    /// Return true if the object with `id` is reachable from `tips` or is one of them, or `None` if this can't be determined
+    /// with bitmaps. See [`objects_reachable_from()`][File::objects_reachable_from()] for details.
+    pub fn is_reachable_from<F>(
+        &self,
+        index: &index::File,
+        order: &PackOrder,
+        id: impl AsRef<oid>,
+        tips: impl IntoIterator<Item = ObjectId>,
+        find: F,
+    ) -> Result<Option<bool>, Error<F::Error>>
+    where
+        F: crate::Find,
+    {
+        Ok(self.objects_reachable_from(index, order, tips, find)?.map(|bits| {
+            // Objects outside of the pack can't be reachable as all reachable objects are in the pack.
+            index
+                .lookup(id)
+                .and_then(|index_position| order.pack_position(index_position))
+                .map_or(false, |pack_position| bits.get(pack_position as usize))
+        }))
+    }
+}
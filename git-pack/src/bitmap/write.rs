@@ -0,0 +1,388 @@
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    io,
+    io::Write,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
+};
+
+use git_bitmap::Bitset;
+use git_features::progress::Progress;
+use git_hash::ObjectId;
+use git_object::tree::EntryMode;
+
+use crate::{
+    bitmap::{File, PackOrder, PackPosition},
+    cache, data, index,
+};
+
+mod error {
+    /// The error returned by [`bitmap::File::write_from_bundle()`][crate::bitmap::File::write_from_bundle()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Io(#[from] std::io::Error),
+        #[error("Interrupted")]
+        Interrupted,
+        #[error(transparent)]
+        DecodeEntry(#[from] crate::data::decode_entry::Error),
+        #[error(transparent)]
+        DecodeObject(#[from] git_object::decode::Error),
+        #[error("The object {id} is reachable but isn't contained in the pack, which is required for bitmaps")]
+        MissingObject { id: git_hash::ObjectId },
+        #[error("The object {id} was expected to be a {expected}, but was a {actual}")]
+        ObjectKind {
+            id: git_hash::ObjectId,
+            expected: git_object::Kind,
+            actual: git_object::Kind,
+        },
+        #[error("Bitmaps with more than {} commits or objects are unsupported", u32::MAX)]
+        TooManyObjects,
+    }
+}
+pub use error::Error;
+
+/// Options for use in [`bitmap::File::write_from_bundle()`][crate::bitmap::File::write_from_bundle()].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// The kind of hash to use for the trailing checksum, which has to match the one used by the pack.
+    pub object_hash: git_hash::Kind,
+    /// If set, don't select more than the given amount of commits to receive a bitmap.
+    pub max_bitmaps: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            object_hash: Default::default(),
+            max_bitmaps: None,
+        }
+    }
+}
+
+/// The successful result of [`bitmap::File::write_from_bundle()`][crate::bitmap::File::write_from_bundle()].
+pub struct Outcome<P> {
+    /// The checksum over the written bitmap file.
+    pub bitmap_checksum: git_hash::ObjectId,
+    /// The amount of commits that were selected to receive a bitmap.
+    pub num_commits: u32,
+    /// The input progress
+    pub progress: P,
+}
+
+/// A commit in the pack along with everything needed to select it and traverse its history.
+struct Commit {
+    pack_position: PackPosition,
+    time: u32,
+    tree: ObjectId,
+    parents: Vec<ObjectId>,
+    is_tip: bool,
+    is_merge: bool,
+}
+
+/// Writing
+impl File {
+    /// Write a bitmap for the pack in `bundle` to `out`, selecting commits to receive a bitmap like git does.
+    ///
+    /// `tips` are the ids of the objects referred to by references, commonly obtained by peeling all references. Commits among them,
+    /// or those pointed to by tag objects among them, always receive a bitmap. Tips which aren't in the pack are ignored.
+    ///
+    /// The pack must be closed under reachability, as bitmaps describe all objects reachable from a commit.
+    /// Name-hashes are not written.
+    pub fn write_from_bundle<P>(
+        bundle: &crate::Bundle,
+        tips: impl IntoIterator<Item = ObjectId>,
+        out: impl io::Write,
+        mut progress: P,
+        should_interrupt: &AtomicBool,
+        Options {
+            object_hash,
+            max_bitmaps,
+        }: Options,
+    ) -> Result<Outcome<P>, Error>
+    where
+        P: Progress,
+    {
+        let index = &bundle.index;
+        let num_objects = index.num_objects();
+        let order = PackOrder::from_index(index);
+        let mut buf = Vec::new();
+        let mut cache = cache::Never;
+
+        let mut types = [
+            Bitset::default(),
+            Bitset::default(),
+            Bitset::default(),
+            Bitset::default(),
+        ];
+        let mut commits = Vec::new();
+        let mut commit_by_index_position = HashMap::new();
+        {
+            let mut progress = progress.add_child("classify objects");
+            progress.init(Some(num_objects as usize), git_features::progress::count("objects"));
+            let start = Instant::now();
+            for pack_position in 0..num_objects {
+                let index_position = order.index_position(pack_position).expect("in bounds");
+                let kind = kind_of(bundle, index.pack_offset_at_index(index_position))?;
+                types[type_slot(kind)].set(pack_position as usize);
+                if kind == git_object::Kind::Commit {
+                    let (data, _) = bundle.get_object_by_index(index_position, &mut buf, &mut cache)?;
+                    let mut iter = data.try_into_commit_iter().expect("commit");
+                    let tree = iter.tree_id()?;
+                    let parents: Vec<_> = iter.parent_ids().collect();
+                    let time = iter.committer()?.time.seconds_since_unix_epoch;
+                    commit_by_index_position.insert(index_position, commits.len());
+                    commits.push(Commit {
+                        pack_position,
+                        time,
+                        tree,
+                        is_merge: parents.len() > 1,
+                        parents,
+                        is_tip: false,
+                    });
+                }
+                progress.inc();
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+            }
+            progress.show_throughput(start);
+        }
+
+        for mut tip in tips {
+            loop {
+                let index_position = match index.lookup(tip) {
+                    Some(index_position) => index_position,
+                    None => break,
+                };
+                if let Some(commit_index) = commit_by_index_position.get(&index_position) {
+                    commits[*commit_index].is_tip = true;
+                    break;
+                }
+                let (data, _) = bundle.get_object_by_index(index_position, &mut buf, &mut cache)?;
+                match data.try_into_tag_iter() {
+                    Some(mut iter) => tip = iter.target_id()?,
+                    None => break,
+                }
+            }
+        }
+
+        let selected = select_commits(&commits, max_bitmaps);
+        let mut bitmap_by_commit = HashMap::<usize, Bitset>::with_capacity(selected.len());
+        {
+            let mut progress = progress.add_child("compute bitmaps");
+            progress.init(Some(selected.len()), git_features::progress::count("commits"));
+            let start = Instant::now();
+            let mut ordered = selected.clone();
+            ordered.sort_by_key(|commit_index| (commits[*commit_index].time, commits[*commit_index].pack_position));
+            for commit_index in ordered {
+                let bits = reachable_from(
+                    bundle,
+                    &order,
+                    &commits,
+                    &commit_by_index_position,
+                    &bitmap_by_commit,
+                    commit_index,
+                    &mut buf,
+                )?;
+                bitmap_by_commit.insert(commit_index, bits);
+                progress.inc();
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+            }
+            progress.show_throughput(start);
+        }
+
+        let mut out = git_features::hash::Write::new(out, object_hash);
+        out.write_all(File::SIGNATURE)?;
+        out.write_all(&1u16.to_be_bytes())?;
+        out.write_all(&File::FLAG_FULL_DAG.to_be_bytes())?;
+        let num_commits = u32::try_from(selected.len()).map_err(|_| Error::TooManyObjects)?;
+        out.write_all(&num_commits.to_be_bytes())?;
+        out.write_all(bundle.pack.checksum().as_slice())?;
+        for bits in &types {
+            git_bitmap::ewah::Vec::from_bitset(bits).write_to(&mut out)?;
+        }
+        for commit_index in &selected {
+            let commit = &commits[*commit_index];
+            let index_position = order.index_position(commit.pack_position).expect("in bounds");
+            out.write_all(&index_position.to_be_bytes())?;
+            out.write_all(&[0 /* xor offset */, 0 /* flags */])?;
+            git_bitmap::ewah::Vec::from_bitset(&bitmap_by_commit[commit_index]).write_to(&mut out)?;
+        }
+
+        let bitmap_checksum = out.hash.digest();
+        out.inner.write_all(bitmap_checksum.as_slice())?;
+        out.inner.flush()?;
+
+        Ok(Outcome {
+            bitmap_checksum,
+            num_commits,
+            progress,
+        })
+    }
+}
+
+fn type_slot(kind: git_object::Kind) -> usize {
+    match kind {
+        git_object::Kind::Commit => 0,
+        git_object::Kind::Tree => 1,
+        git_object::Kind::Blob => 2,
+        git_object::Kind::Tag => 3,
+    }
+}
+
+/// Follow the delta chain of the entry at `pack_offset` to learn its kind, without decoding any object.
+fn kind_of(bundle: &crate::Bundle, mut pack_offset: data::Offset) -> Result<git_object::Kind, Error> {
+    loop {
+        let entry = bundle.pack.entry(pack_offset)?;
+        pack_offset = match entry.header {
+            data::entry::Header::OfsDelta { base_distance } => entry.base_pack_offset(base_distance),
+            data::entry::Header::RefDelta { base_id } => bundle
+                .index
+                .lookup(base_id)
+                .map(|index_position| bundle.index.pack_offset_at_index(index_position))
+                .ok_or(Error::MissingObject { id: base_id })?,
+            header => return Ok(header.as_kind().expect("base object")),
+        }
+    }
+}
+
+/// The distance to the next commit to select, given the amount of commits already seen, as done by git.
+fn next_commit_distance(index: usize) -> usize {
+    const MIN_COMMITS: usize = 100;
+    const MAX_COMMITS: usize = 5000;
+    const MUST_REGION: usize = 100;
+    const MIN_REGION: usize = 20000;
+
+    if index <= MUST_REGION {
+        0
+    } else if index <= MIN_REGION {
+        (index - MUST_REGION).min(MIN_COMMITS)
+    } else {
+        (index - MIN_REGION).min(MAX_COMMITS).max(MIN_COMMITS)
+    }
+}
+
+/// Select commits to receive a bitmap, the most recent ones more densely, preferring tips and merges, and return their indices
+/// into `commits`.
+fn select_commits(commits: &[Commit], max_bitmaps: Option<usize>) -> Vec<usize> {
+    let mut by_recency: Vec<_> = (0..commits.len()).collect();
+    by_recency.sort_by_key(|commit_index| {
+        let commit = &commits[*commit_index];
+        (std::cmp::Reverse(commit.time), commit.pack_position)
+    });
+    let max_bitmaps = max_bitmaps.unwrap_or(usize::MAX);
+
+    if by_recency.len() < 100 {
+        by_recency.truncate(max_bitmaps);
+        return by_recency;
+    }
+
+    let mut selected = Vec::new();
+    let mut index = 0;
+    loop {
+        let distance = next_commit_distance(index);
+        if index + distance >= by_recency.len() || selected.len() >= max_bitmaps {
+            break;
+        }
+        let chosen = if distance == 0 {
+            by_recency[index]
+        } else {
+            let mut chosen = by_recency[index + distance];
+            for commit_index in &by_recency[index..=index + distance] {
+                let commit = &commits[*commit_index];
+                if commit.is_tip {
+                    chosen = *commit_index;
+                    break;
+                }
+                if commit.is_merge {
+                    chosen = *commit_index;
+                }
+            }
+            chosen
+        };
+        selected.push(chosen);
+        index += distance + 1;
+    }
+    selected
+}
+
+/// Compute the set of all objects reachable from the commit at `commit_index`, reusing previously computed bitmaps when
+/// encountering their commits.
+fn reachable_from(
+    bundle: &crate::Bundle,
+    order: &PackOrder,
+    commits: &[Commit],
+    commit_by_index_position: &HashMap<index::EntryIndex, usize>,
+    bitmap_by_commit: &HashMap<usize, Bitset>,
+    commit_index: usize,
+    buf: &mut Vec<u8>,
+) -> Result<Bitset, Error> {
+    let pack_position_of = |id: &git_hash::oid| {
+        bundle
+            .index
+            .lookup(id)
+            .map(|index_position| (index_position, order.pack_position(index_position).expect("in bounds")))
+            .ok_or_else(|| Error::MissingObject { id: id.to_owned() })
+    };
+    let mut bits = Bitset::with_capacity(bundle.index.num_objects() as usize);
+    let mut trees = Vec::new();
+    let mut next_commits = vec![commit_index];
+    while let Some(commit_index) = next_commits.pop() {
+        let commit = &commits[commit_index];
+        if bits.get(commit.pack_position as usize) {
+            continue;
+        }
+        if let Some(bitmap) = bitmap_by_commit.get(&commit_index) {
+            bits.union_with(bitmap);
+            continue;
+        }
+        bits.set(commit.pack_position as usize);
+        trees.push(commit.tree);
+        for parent in &commit.parents {
+            let (index_position, _) = pack_position_of(parent)?;
+            let parent_index = match commit_by_index_position.get(&index_position) {
+                Some(parent_index) => *parent_index,
+                None => {
+                    return Err(Error::ObjectKind {
+                        id: parent.to_owned(),
+                        expected: git_object::Kind::Commit,
+                        actual: kind_of(bundle, bundle.index.pack_offset_at_index(index_position))?,
+                    })
+                }
+            };
+            next_commits.push(parent_index);
+        }
+
+        while let Some(tree) = trees.pop() {
+            let (index_position, pack_position) = pack_position_of(&tree)?;
+            if bits.get(pack_position as usize) {
+                continue;
+            }
+            bits.set(pack_position as usize);
+            let (data, _) = bundle.get_object_by_index(index_position, buf, &mut cache::Never)?;
+            let actual = data.kind;
+            let iter = data.try_into_tree_iter().ok_or(Error::ObjectKind {
+                id: tree,
+                expected: git_object::Kind::Tree,
+                actual,
+            })?;
+            for entry in iter {
+                let entry = entry?;
+                match entry.mode {
+                    EntryMode::Tree => trees.push(entry.oid.to_owned()),
+                    EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => {
+                        let (_, pack_position) = pack_position_of(entry.oid)?;
+                        bits.set(pack_position as usize);
+                    }
+                    EntryMode::Commit => {}
+                }
+            }
+        }
+    }
+    Ok(bits)
+}
@@ -10,7 +10,7 @@ use git_tempfile::{handle::Writable, AutoRemove, ContainingDirectory};
 use crate::data;
 
 mod error;
-use error::Error;
+pub use error::Error;
 
 mod types;
 use types::{LockWriter, PassThrough};
@@ -29,8 +29,13 @@ impl crate::Bundle {
     /// * `should_interrupt` is checked regularly and when true, the whole operation will stop.
     /// * `thin_pack_base_object_lookup_fn` If set, we expect to see a thin-pack with objects that reference their base object by object id which is
     /// expected to exist in the object database the bundle is contained within.
+    /// Each missing base object is looked up and inserted in front of the first delta referring to it, which then refers to it
+    /// by offset, like `git index-pack --fix-thin` does. The written pack is self-contained, with header and trailing
+    /// checksum adjusted accordingly.
     /// `options` further configure how the task is performed.
     ///
+    /// Base objects are indexed while the pack is still being received, leaving only deltas to be resolved once it is complete.
+    ///
     /// # Note
     ///
     /// * the resulting pack may be empty, that is, contains zero objects in some situations. This is a valid reply by a server and should
@@ -75,7 +80,8 @@ impl crate::Bundle {
                         options.iteration_mode,
                         data::input::EntryDataMode::KeepAndCrc32,
                         object_hash,
-                    )?,
+                    )?
+                    .with_object_ids(true),
                     thin_pack_lookup_fn,
                 );
                 let pack_kind = pack_entries_iter.inner.kind();
@@ -85,7 +91,7 @@ impl crate::Bundle {
                         writer: data_file.clone(),
                     },
                     pack_kind,
-                    git_hash::Kind::Sha1,
+                    object_hash,
                 );
                 (Box::new(pack_entries_iter), pack_kind)
             }
@@ -107,12 +113,13 @@ impl crate::Bundle {
                     options.iteration_mode,
                     data::input::EntryDataMode::Crc32,
                     object_hash,
-                )?;
+                )?
+                .with_object_ids(true);
                 let pack_kind = pack_entries_iter.kind();
                 (Box::new(pack_entries_iter), pack_kind)
             }
         };
-        let (outcome, data_path, index_path) = crate::Bundle::inner_write(
+        let (outcome, data_path, index_path, keep_path) = crate::Bundle::inner_write(
             directory,
             progress,
             options,
@@ -127,6 +134,7 @@ impl crate::Bundle {
             pack_kind,
             data_path,
             index_path,
+            keep_path,
         })
     }
 
@@ -175,10 +183,19 @@ impl crate::Bundle {
                         options.iteration_mode,
                         data::input::EntryDataMode::KeepAndCrc32,
                         object_hash,
-                    )?,
+                    )?
+                    .with_object_ids(true),
                     thin_pack_lookup_fn,
                 );
                 let pack_kind = pack_entries_iter.inner.kind();
+                let pack_entries_iter = data::input::EntriesToBytesIter::new(
+                    pack_entries_iter,
+                    LockWriter {
+                        writer: data_file.clone(),
+                    },
+                    pack_kind,
+                    object_hash,
+                );
                 (Box::new(pack_entries_iter), pack_kind)
             }
             None => {
@@ -195,7 +212,8 @@ impl crate::Bundle {
                     options.iteration_mode,
                     data::input::EntryDataMode::Crc32,
                     object_hash,
-                )?;
+                )?
+                .with_object_ids(true);
                 let pack_kind = pack_entries_iter.kind();
                 (Box::new(pack_entries_iter), pack_kind)
             }
@@ -204,7 +222,7 @@ impl crate::Bundle {
         let pack_entries_iter =
             git_features::parallel::EagerIterIf::new(move || num_objects > 25_000, pack_entries_iter, 5_000, 5);
 
-        let (outcome, data_path, index_path) = crate::Bundle::inner_write(
+        let (outcome, data_path, index_path, keep_path) = crate::Bundle::inner_write(
             directory,
             progress,
             options,
@@ -219,6 +237,7 @@ impl crate::Bundle {
             pack_kind,
             data_path,
             index_path,
+            keep_path,
         })
     }
 
@@ -230,11 +249,20 @@ impl crate::Bundle {
             iteration_mode: _,
             index_kind,
             object_hash,
+            keep,
         }: Options,
         data_file: Arc<parking_lot::Mutex<git_tempfile::Handle<Writable>>>,
         pack_entries_iter: impl Iterator<Item = Result<data::input::Entry, data::input::Error>>,
         should_interrupt: &AtomicBool,
-    ) -> Result<(crate::index::write::Outcome, Option<PathBuf>, Option<PathBuf>), Error> {
+    ) -> Result<
+        (
+            crate::index::write::Outcome,
+            Option<PathBuf>,
+            Option<PathBuf>,
+            Option<PathBuf>,
+        ),
+        Error,
+    > {
         let indexing_progress = progress.add_child("create index file");
         Ok(match directory {
             Some(directory) => {
@@ -257,6 +285,14 @@ impl crate::Bundle {
 
                 let data_path = directory.join(format!("{}.pack", outcome.data_hash.to_hex()));
                 let index_path = data_path.with_extension("idx");
+                let keep_path = match keep {
+                    Some(reason) => {
+                        let keep_path = data_path.with_extension("keep");
+                        write_keep_file(&keep_path, &reason)?;
+                        Some(keep_path)
+                    }
+                    None => None,
+                };
 
                 Arc::try_unwrap(data_file)
                     .expect("only one handle left after pack was consumed")
@@ -271,7 +307,7 @@ impl crate::Bundle {
                         ));
                         err
                     })?;
-                (outcome, Some(data_path), Some(index_path))
+                (outcome, Some(data_path), Some(index_path), keep_path)
             }
             None => (
                 crate::index::File::write_data_iter_to_stream(
@@ -286,11 +322,26 @@ impl crate::Bundle {
                 )?,
                 None,
                 None,
+                None,
             ),
         })
     }
 }
 
+/// Write `reason` into a new keep file at `path`, leaving it untouched if it already exists as the pack is kept either way.
+fn write_keep_file(path: &Path, reason: &str) -> io::Result<()> {
+    use std::io::Write;
+    let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !reason.is_empty() {
+        writeln!(file, "{}", reason)?;
+    }
+    Ok(())
+}
+
 fn new_pack_file_resolver(
     data_file: Arc<parking_lot::Mutex<git_tempfile::Handle<Writable>>>,
 ) -> io::Result<impl Fn(data::EntryRange, &mut Vec<u8>) -> Option<()> + Send + Clone> {
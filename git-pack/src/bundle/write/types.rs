@@ -14,6 +14,10 @@ pub struct Options {
     pub index_kind: crate::index::Version,
     /// The kind of hash to use when writing the bundle.
     pub object_hash: git_hash::Kind,
+    /// If set, write a `.keep` file with the given reason as content along with the pack, before the pack itself is moved
+    /// into place. This prevents maintenance like repacks from touching the pack even if it runs concurrently,
+    /// similar to `git index-pack --keep`.
+    pub keep: Option<String>,
 }
 
 impl Default for Options {
@@ -24,6 +28,7 @@ impl Default for Options {
             iteration_mode: crate::data::input::Mode::Verify,
             index_kind: Default::default(),
             object_hash: Default::default(),
+            keep: None,
         }
     }
 }
@@ -44,6 +49,8 @@ pub struct Outcome {
     pub index_path: Option<PathBuf>,
     /// The path to the pack data file
     pub data_path: Option<PathBuf>,
+    /// The path to the `.keep` file, if one was [requested][Options::keep].
+    pub keep_path: Option<PathBuf>,
 }
 
 impl Outcome {
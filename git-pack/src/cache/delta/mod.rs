@@ -108,6 +108,23 @@ impl<T> Tree<T> {
         Ok(())
     }
 
+    /// Remove and return all roots without children for which `is_resolved(data)` returns true, as traversing them is
+    /// unnecessary. `pack_entries_end` is the offset one past the last entry, as passed to [`traverse()`][Tree::traverse()].
+    pub(crate) fn take_resolved_roots_without_children(
+        &mut self,
+        pack_entries_end: crate::data::Offset,
+        mut is_resolved: impl FnMut(&T) -> bool,
+    ) -> Result<Vec<Item<T>>, traverse::Error> {
+        self.set_pack_entries_end_and_resolve_ref_offsets(pack_entries_end)?;
+        // The last item may be gone, and all offsets are known by now.
+        self.last_seen = None;
+        let (resolved, unresolved) = std::mem::take(&mut self.root_items)
+            .into_iter()
+            .partition(|item| item.children.is_empty() && is_resolved(&item.data));
+        self.root_items = unresolved;
+        Ok(resolved)
+    }
+
     /// Add a new root node, one that only has children but is not a child itself, at the given pack `offset` and associate
     /// custom `data` with it.
     pub fn add_root(&mut self, offset: crate::data::Offset, data: T) -> Result<(), Error> {
@@ -218,7 +235,7 @@ mod tests {
         );
         assert_eq!(
             std::mem::size_of::<[TreeItemOption<Entry>; 7_500_000]>(),
-            480_000_000,
+            540_000_000,
             "it should be as small as possible"
         );
     }
@@ -238,7 +255,7 @@ mod tests {
 
         assert_eq!(
             std::mem::size_of::<[TreeItem<EntryWithDefault>; 7_500_000]>(),
-            780_000_000
+            900_000_000
         );
     }
 }
@@ -27,6 +27,7 @@ mod memory {
     pub struct MemoryCappedHashmap {
         inner: clru::CLruCache<Key, Entry, std::collections::hash_map::RandomState, CustomScale>,
         free_list: Vec<Vec<u8>>,
+        free_list_limit: usize,
         debug: git_features::cache::Debug,
     }
 
@@ -40,9 +41,17 @@ mod memory {
                         .with_scale(CustomScale),
                 ),
                 free_list: Vec::new(),
+                free_list_limit: usize::MAX,
                 debug: git_features::cache::Debug::new(format!("MemoryCappedHashmap({}B)", memory_cap_in_bytes)),
             }
         }
+
+        /// Keep at most `limit` buffers of replaced entries around for reuse, or `0` to never reuse them, which trades
+        /// allocations for memory. By default, all of them are kept.
+        pub fn with_free_list_limit(mut self, limit: usize) -> Self {
+            self.free_list_limit = limit;
+            self
+        }
     }
 
     impl DecodeEntry for MemoryCappedHashmap {
@@ -65,7 +74,9 @@ mod memory {
                     compressed_size,
                 },
             ) {
-                self.free_list.push(previous_entry.data)
+                if self.free_list.len() < self.free_list_limit {
+                    self.free_list.push(previous_entry.data)
+                }
             }
         }
 
@@ -105,6 +116,7 @@ mod _static {
     pub struct StaticLinkedList<const SIZE: usize> {
         inner: uluru::LRUCache<Entry, SIZE>,
         free_list: Vec<Vec<u8>>,
+        free_list_limit: usize,
         debug: git_features::cache::Debug,
     }
 
@@ -113,11 +125,21 @@ mod _static {
             StaticLinkedList {
                 inner: Default::default(),
                 free_list: Vec::new(),
+                free_list_limit: usize::MAX,
                 debug: git_features::cache::Debug::new(format!("StaticLinkedList<{}>", SIZE)),
             }
         }
     }
 
+    impl<const SIZE: usize> StaticLinkedList<SIZE> {
+        /// Keep at most `limit` buffers of replaced entries around for reuse, or `0` to never reuse them, which trades
+        /// allocations for memory. By default, all of them are kept.
+        pub fn with_free_list_limit(mut self, limit: usize) -> Self {
+            self.free_list_limit = limit;
+            self
+        }
+    }
+
     impl<const SIZE: usize> DecodeEntry for StaticLinkedList<SIZE> {
         fn put(&mut self, pack_id: u32, offset: u64, data: &[u8], kind: git_object::Kind, compressed_size: usize) {
             self.debug.put();
@@ -137,7 +159,9 @@ mod _static {
                 kind,
                 compressed_size,
             }) {
-                self.free_list.push(previous.data)
+                if self.free_list.len() < self.free_list_limit {
+                    self.free_list.push(previous.data)
+                }
             }
         }
 
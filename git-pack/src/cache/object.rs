@@ -31,6 +31,7 @@ mod memory {
     pub struct MemoryCappedHashmap {
         inner: clru::CLruCache<Key, Entry, hash_hasher::HashBuildHasher, CustomScale>,
         free_list: Vec<Vec<u8>>,
+        free_list_limit: usize,
         debug: git_features::cache::Debug,
     }
 
@@ -49,9 +50,17 @@ mod memory {
                         .with_scale(CustomScale),
                 ),
                 free_list: Vec::new(),
+                free_list_limit: usize::MAX,
                 debug: git_features::cache::Debug::new(format!("MemoryCappedObjectHashmap({}B)", memory_cap_in_bytes)),
             }
         }
+
+        /// Keep at most `limit` buffers of replaced entries around for reuse, or `0` to never reuse them, which trades
+        /// allocations for memory. By default, all of them are kept.
+        pub fn with_free_list_limit(mut self, limit: usize) -> Self {
+            self.free_list_limit = limit;
+            self
+        }
     }
 
     impl cache::Object for MemoryCappedHashmap {
@@ -74,7 +83,9 @@ mod memory {
                     kind,
                 },
             ) {
-                self.free_list.push(previous_entry.data)
+                if self.free_list.len() < self.free_list_limit {
+                    self.free_list.push(previous_entry.data)
+                }
             }
         }
 
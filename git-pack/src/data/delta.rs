@@ -15,6 +15,9 @@ pub fn decode_header_size(d: &[u8]) -> (u64, usize) {
     (size, consumed)
 }
 
+/// Apply the delta instructions in `data` to `base`, writing the result into `target`, which must have the size decoded from the delta header.
+///
+/// `data` is expected to not contain the delta header anymore.
 pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     let mut i = 0;
     while let Some(cmd) = data.get(i) {
@@ -68,3 +71,56 @@ pub fn apply(base: &[u8], mut target: &mut [u8], data: &[u8]) {
     assert_eq!(i, data.len());
     assert_eq!(target.len(), 0);
 }
+
+/// Create a delta between a base and a target object, which can be [applied][apply()] to reproduce the target.
+pub use git_diff::blob::binary::delta as create;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(base: &[u8], target: &[u8]) -> usize {
+        let mut delta = Vec::new();
+        let written = create(base, target, usize::MAX, &mut delta).expect("unlimited size");
+        assert_eq!(written, delta.len());
+
+        let (base_size, consumed) = decode_header_size(&delta);
+        assert_eq!(base_size, base.len() as u64);
+        let (target_size, consumed_target) = decode_header_size(&delta[consumed..]);
+        assert_eq!(target_size, target.len() as u64);
+
+        let mut actual = vec![0; target.len()];
+        apply(base, &mut actual, &delta[consumed + consumed_target..]);
+        assert_eq!(actual, target);
+        written
+    }
+
+    #[test]
+    fn create_and_apply() {
+        let base: Vec<u8> = (0..100_000u32).flat_map(|n| n.to_le_bytes()).collect();
+        let mut target = b"prefix".to_vec();
+        target.extend_from_slice(&base[7..50_000]);
+        target.extend_from_slice(b"in between");
+        target.extend_from_slice(&base[200_000..]);
+        target.extend_from_slice(b"suffix");
+
+        let delta_size = round_trip(&base, &target);
+        assert!(
+            delta_size < 100,
+            "large matching sections are copied, got {} bytes",
+            delta_size
+        );
+
+        round_trip(&base, b"");
+        round_trip(b"", &target[..1000]);
+        round_trip(b"short", b"shorter");
+        round_trip(&base[..300], &base[..300]);
+    }
+
+    #[test]
+    fn create_respects_max_size() {
+        let mut delta = vec![1, 2];
+        assert_eq!(create(b"", &[42; 200], 100, &mut delta), None);
+        assert_eq!(delta, [1, 2], "nothing is written if the delta would be too large");
+    }
+}
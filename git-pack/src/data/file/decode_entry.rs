@@ -9,12 +9,14 @@ use crate::{
     data::{delta, File},
 };
 
-/// Returned by [`File::decompress_entry()`] and [`File::decode_entry()`]
+/// Returned by [`File::entry()`], [`File::decompress_entry()`] and [`File::decode_entry()`]
 #[derive(thiserror::Error, Debug)]
 #[allow(missing_docs)]
 pub enum Error {
     #[error("Failed to decompress pack entry")]
     ZlibInflate(#[from] zlib::inflate::Error),
+    #[error("Failed to read the pack entry from the pack data file")]
+    Io(#[from] std::io::Error),
     #[error("A delta chain could not be applied as the ref base with id {0} could not be found")]
     DeltaBaseUnresolved(git_hash::ObjectId),
 }
@@ -102,13 +104,21 @@ impl File {
     /// Obtain the [`Entry`][crate::data::Entry] at the given `offset` into the pack.
     ///
     /// The `offset` is typically obtained from the pack index file.
-    pub fn entry(&self, offset: data::Offset) -> crate::data::Entry {
+    /// Reading the entry header can only fail if the pack isn't [fully mapped][data::Mapping::Full].
+    pub fn entry(&self, offset: data::Offset) -> Result<crate::data::Entry, Error> {
         self.assure_v2();
         let pack_offset: usize = offset.try_into().expect("offset representable by machine");
         assert!(pack_offset <= self.data.len(), "offset out of bounds");
 
-        let object_data = &self.data[pack_offset..];
-        crate::data::Entry::from_bytes(object_data, offset, self.hash_len)
+        let object_data = match self.data.as_full_mapping() {
+            Some(data) => data::Bytes::from(&data[pack_offset..]),
+            None => {
+                // Enough for the header of any entry, even with the longest size and longest hash.
+                let header_end = (pack_offset + 64).min(self.data.len());
+                self.data.bytes(pack_offset..header_end)?
+            }
+        };
+        Ok(crate::data::Entry::from_bytes(&object_data, offset, self.hash_len))
     }
 
     /// Decompress the object expected at the given data offset, sans pack header. This information is only
@@ -120,10 +130,30 @@ impl File {
         let offset: usize = data_offset.try_into().expect("offset representable by machine");
         assert!(offset < self.data.len(), "entry offset out of bounds");
 
-        zlib::Inflate::default()
-            .once(&self.data[offset..], out)
-            .map_err(Into::into)
-            .map(|(_status, consumed_in, _consumed_out)| consumed_in)
+        let mut inflate = zlib::Inflate::default();
+        if let Some(data) = self.data.as_full_mapping() {
+            return inflate
+                .once(&data[offset..], out)
+                .map_err(Into::into)
+                .map(|(_status, consumed_in, _consumed_out)| consumed_in);
+        }
+
+        // Without the whole file at hand, feed the compressed data in chunks until the stream ends.
+        const CHUNK_SIZE: usize = 64 * 1024;
+        let (mut total_in, mut total_out) = (0, 0);
+        loop {
+            let start = offset + total_in;
+            let chunk = self.data.bytes(start..(start + CHUNK_SIZE).min(self.data.len()))?;
+            let (status, consumed_in, consumed_out) = inflate.once(&chunk, &mut out[total_out..])?;
+            total_in += consumed_in;
+            total_out += consumed_out;
+            if status == zlib::Status::StreamEnd
+                || offset + total_in == self.data.len()
+                || (consumed_in == 0 && consumed_out == 0)
+            {
+                break Ok(total_in);
+            }
+        }
     }
 
     /// Decode an entry, resolving delta's as needed, while growing the `out` vector if there is not enough
@@ -213,7 +243,7 @@ impl File {
             });
             use crate::data::entry::Header;
             cursor = match cursor.header {
-                Header::OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance)),
+                Header::OfsDelta { base_distance } => self.entry(cursor.base_pack_offset(base_distance))?,
                 Header::RefDelta { base_id } => match resolve(base_id.as_ref(), out) {
                     Some(ResolvedBase::InPack(entry)) => entry,
                     Some(ResolvedBase::OutOfPack { end, kind }) => {
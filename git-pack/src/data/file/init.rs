@@ -9,17 +9,32 @@ impl data::File {
     /// The `object_hash` is a way to read (and write) the same file format with different hashes, as the hash kind
     /// isn't stored within the file format itself.
     pub fn at(path: impl AsRef<Path>, object_hash: git_hash::Kind) -> Result<data::File, data::header::decode::Error> {
-        Self::at_inner(path.as_ref(), object_hash)
+        Self::at_inner(path.as_ref(), object_hash, data::Mapping::default())
     }
 
-    fn at_inner(path: &Path, object_hash: git_hash::Kind) -> Result<data::File, data::header::decode::Error> {
+    /// Like [`at()`][data::File::at()], but with control over how the pack data is accessed using `mapping`,
+    /// which allows to limit the address space used for huge packs.
+    pub fn at_with_mapping(
+        path: impl AsRef<Path>,
+        object_hash: git_hash::Kind,
+        mapping: data::Mapping,
+    ) -> Result<data::File, data::header::decode::Error> {
+        Self::at_inner(path.as_ref(), object_hash, mapping)
+    }
+
+    fn at_inner(
+        path: &Path,
+        object_hash: git_hash::Kind,
+        mapping: data::Mapping,
+    ) -> Result<data::File, data::header::decode::Error> {
         use crate::data::header::N32_SIZE;
         let hash_len = object_hash.len_in_bytes();
 
-        let data = crate::mmap::read_only(path).map_err(|e| data::header::decode::Error::Io {
+        let io_err = |e| data::header::decode::Error::Io {
             source: e,
             path: path.to_owned(),
-        })?;
+        };
+        let data = super::Backing::open(path, mapping).map_err(io_err)?;
         let pack_len = data.len();
         if pack_len < N32_SIZE * 3 + hash_len {
             return Err(data::header::decode::Error::Corrupt(format!(
@@ -27,8 +42,10 @@ impl data::File {
                 pack_len
             )));
         }
+        let header = data.bytes(0..12).map_err(io_err)?;
         let (kind, num_objects) =
-            data::header::decode(&data[..12].try_into().expect("enough data after previous check"))?;
+            data::header::decode(&header[..].try_into().expect("enough data after previous check"))?;
+        let checksum = git_hash::ObjectId::from(&*data.bytes(pack_len - hash_len..pack_len).map_err(io_err)?);
         Ok(data::File {
             data,
             path: path.to_owned(),
@@ -37,6 +54,7 @@ impl data::File {
             num_objects,
             hash_len,
             object_hash,
+            checksum,
         })
     }
 }
@@ -0,0 +1,231 @@
+use std::{
+    convert::TryInto,
+    io,
+    ops::{Deref, Range},
+    path::Path,
+    sync::Arc,
+};
+
+use memmap2::Mmap;
+
+/// Determines how the bytes of pack data files are made accessible, trading speed for address space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mapping {
+    /// Map the whole file into memory, which is fastest but needs as much address space as the pack is large.
+    Full,
+    /// Map windows of at least `window_size` bytes on demand and keep at most `max_windows` of them, similar to
+    /// `core.packedGitWindowSize` and `core.packedGitLimit`.
+    Windowed {
+        /// The size of each mapped window in bytes. Entries larger than this get a window of their own size.
+        window_size: usize,
+        /// The amount of windows to keep mapped, with the least recently used one being unmapped first.
+        max_windows: usize,
+    },
+    /// Don't map anything but read the bytes of each entry with positioned reads, using no additional address space at all.
+    Read,
+}
+
+impl Default for Mapping {
+    /// Map packs fully on 64 bit platforms, and use windows with the same limits as git on 32 bit platforms.
+    fn default() -> Self {
+        if cfg!(target_pointer_width = "64") {
+            Mapping::Full
+        } else {
+            Mapping::Windowed {
+                window_size: 32 * 1024 * 1024,
+                max_windows: 8,
+            }
+        }
+    }
+}
+
+/// Bytes of a pack data file, either borrowed from a full mapping, shared with a mapped window, or read into memory.
+pub struct Bytes<'a>(Inner<'a>);
+
+enum Inner<'a> {
+    Borrowed(&'a [u8]),
+    Window { map: Arc<Mmap>, range: Range<usize> },
+    Owned(Vec<u8>),
+}
+
+impl Deref for Bytes<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Inner::Borrowed(bytes) => bytes,
+            Inner::Window { map, range } => &map[range.clone()],
+            Inner::Owned(bytes) => bytes,
+        }
+    }
+}
+
+impl<'a> From<&'a [u8]> for Bytes<'a> {
+    fn from(bytes: &'a [u8]) -> Self {
+        Bytes(Inner::Borrowed(bytes))
+    }
+}
+
+/// Windows must start at multiples of this, which works with the page sizes and allocation granularity of all platforms.
+const WINDOW_ALIGNMENT: usize = 64 * 1024;
+
+pub(crate) struct Window {
+    start: usize,
+    map: Arc<Mmap>,
+}
+
+impl Window {
+    fn contains(&self, range: &Range<usize>) -> bool {
+        range.start >= self.start && range.end <= self.start + self.map.len()
+    }
+}
+
+pub(crate) enum Backing {
+    Full(Mmap),
+    Windowed {
+        file: std::fs::File,
+        len: usize,
+        window_size: usize,
+        max_windows: usize,
+        /// Windows ordered from least to most recently used.
+        windows: parking_lot::Mutex<Vec<Window>>,
+    },
+    Read {
+        file: std::fs::File,
+        len: usize,
+    },
+}
+
+impl Backing {
+    pub(crate) fn open(path: &Path, mapping: Mapping) -> io::Result<Self> {
+        Ok(match mapping {
+            Mapping::Full => Backing::Full(crate::mmap::read_only(path)?),
+            Mapping::Windowed {
+                window_size,
+                max_windows,
+            } => {
+                let file = std::fs::File::open(path)?;
+                Backing::Windowed {
+                    len: file_len(&file)?,
+                    file,
+                    window_size: window_size.max(WINDOW_ALIGNMENT),
+                    max_windows: max_windows.max(1),
+                    windows: Default::default(),
+                }
+            }
+            Mapping::Read => {
+                let file = std::fs::File::open(path)?;
+                Backing::Read {
+                    len: file_len(&file)?,
+                    file,
+                }
+            }
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Backing::Full(map) => map.len(),
+            Backing::Windowed { len, .. } | Backing::Read { len, .. } => *len,
+        }
+    }
+
+    /// Return the bytes in `range`, which must be within our bounds.
+    pub(crate) fn bytes(&self, range: Range<usize>) -> io::Result<Bytes<'_>> {
+        debug_assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range out of bounds"
+        );
+        if range.is_empty() {
+            return Ok(Bytes(Inner::Owned(Vec::new())));
+        }
+        Ok(Bytes(match self {
+            Backing::Full(map) => Inner::Borrowed(&map[range]),
+            Backing::Windowed {
+                file,
+                len,
+                window_size,
+                max_windows,
+                windows,
+            } => {
+                let mut windows = windows.lock();
+                let map = match windows.iter().position(|w| w.contains(&range)) {
+                    Some(pos) => {
+                        let window = windows.remove(pos);
+                        let map = window.map.clone();
+                        windows.push(window);
+                        map
+                    }
+                    None => {
+                        let start = range.start / WINDOW_ALIGNMENT * WINDOW_ALIGNMENT;
+                        let window_len = (*window_size).max(range.end - start).min(len - start);
+                        // SAFETY: we have to take the risk of somebody changing the file underneath. Git never writes into the same file.
+                        #[allow(unsafe_code)]
+                        let map = Arc::new(unsafe {
+                            memmap2::MmapOptions::new()
+                                .offset(start as u64)
+                                .len(window_len)
+                                .map(file)?
+                        });
+                        if windows.len() >= *max_windows {
+                            windows.remove(0);
+                        }
+                        windows.push(Window {
+                            start,
+                            map: map.clone(),
+                        });
+                        map
+                    }
+                };
+                let start = windows.last().expect("just pushed").start;
+                Inner::Window {
+                    map,
+                    range: range.start - start..range.end - start,
+                }
+            }
+            Backing::Read { file, .. } => {
+                let mut buf = vec![0; range.len()];
+                read_exact_at(file, &mut buf, range.start as u64)?;
+                Inner::Owned(buf)
+            }
+        }))
+    }
+
+    /// Return the full mapping if the whole file is mapped.
+    pub(crate) fn as_full_mapping(&self) -> Option<&[u8]> {
+        match self {
+            Backing::Full(map) => Some(map),
+            Backing::Windowed { .. } | Backing::Read { .. } => None,
+        }
+    }
+}
+
+fn file_len(file: &std::fs::File) -> io::Result<usize> {
+    file.metadata()?
+        .len()
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "pack data file is too large for this platform"))
+}
+
+#[cfg(unix)]
+fn read_exact_at(file: &std::fs::File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_exact_at(file: &std::fs::File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    while !buf.is_empty() {
+        match file.seek_read(buf, offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
 ///
 pub mod decode_entry;
 mod init;
+mod mapping;
+pub(crate) use mapping::Backing;
+pub use mapping::{Bytes, Mapping};
 ///
 pub mod verify;
 
@@ -14,7 +14,7 @@ pub mod checksum {
 impl File {
     /// The checksum in the trailer of this pack data file
     pub fn checksum(&self) -> git_hash::ObjectId {
-        git_hash::ObjectId::from(&self.data[self.data.len() - self.hash_len..])
+        self.checksum
     }
 
     /// Verifies that the checksum of the packfile over all bytes preceding it indeed matches the actual checksum,
@@ -30,13 +30,23 @@ impl File {
         progress: impl Progress,
         should_interrupt: &AtomicBool,
     ) -> Result<git_hash::ObjectId, checksum::Error> {
-        crate::verify::checksum_on_disk_or_mmap(
-            self.path(),
-            &self.data,
-            self.checksum(),
-            self.object_hash,
-            progress,
-            should_interrupt,
-        )
+        match self.data.as_full_mapping() {
+            Some(data) => crate::verify::checksum_on_disk_or_mmap(
+                self.path(),
+                data,
+                self.checksum(),
+                self.object_hash,
+                progress,
+                should_interrupt,
+            ),
+            None => crate::verify::checksum_on_disk(
+                self.path(),
+                self.data.len(),
+                self.checksum(),
+                self.object_hash,
+                progress,
+                should_interrupt,
+            ),
+        }
     }
 }
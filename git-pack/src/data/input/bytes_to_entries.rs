@@ -2,7 +2,7 @@ use std::{fs, io};
 
 use git_features::{
     hash,
-    hash::Sha1,
+    hash::Hasher,
     zlib::{stream::inflate::ReadBoxed, Decompress},
 };
 
@@ -18,11 +18,13 @@ pub struct BytesToEntriesIter<BR> {
     had_error: bool,
     kind: crate::data::Version,
     objects_left: u32,
-    hash: Option<Sha1>,
+    hash: Option<Hasher>,
     mode: input::Mode,
     compressed: input::EntryDataMode,
     compressed_buf: Option<Vec<u8>>,
     hash_len: usize,
+    object_hash: git_hash::Kind,
+    object_ids: bool,
 }
 
 impl<BR> BytesToEntriesIter<BR>
@@ -74,9 +76,18 @@ where
             mode,
             compressed_buf: None,
             hash_len: object_hash.len_in_bytes(),
+            object_hash,
+            object_ids: false,
         })
     }
 
+    /// If `toggle` is true, compute the [id][input::Entry::id] of each base object while it is decompressed anyway, which
+    /// allows to build an index progressively while the pack is still being received.
+    pub fn with_object_ids(mut self, toggle: bool) -> Self {
+        self.object_ids = toggle;
+        self
+    }
+
     fn next_inner(&mut self) -> Result<input::Entry, input::Error> {
         self.objects_left -= 1; // even an error counts as objects
 
@@ -105,6 +116,7 @@ where
             .unwrap_or_else(|| Box::new(Decompress::new(true)));
         let compressed_buf = self.compressed_buf.take().unwrap_or_else(|| Vec::with_capacity(4096));
         decompressor.reset(true);
+        let (object_ids, object_hash) = (self.object_ids, self.object_hash);
         let mut decompressed_reader = ReadBoxed {
             inner: read_and_pass_to(
                 &mut self.read,
@@ -117,7 +129,22 @@ where
             decompressor,
         };
 
-        let bytes_copied = io::copy(&mut decompressed_reader, &mut io::sink())?;
+        let (bytes_copied, id) = match entry.header.as_kind().filter(|_| object_ids) {
+            Some(kind) => {
+                let mut hash = hash::hasher(object_hash);
+                hash.update(&git_object::encode::loose_header(
+                    kind,
+                    entry.decompressed_size as usize,
+                ));
+                let mut write = hash::Write {
+                    inner: io::sink(),
+                    hash,
+                };
+                let bytes_copied = io::copy(&mut decompressed_reader, &mut write)?;
+                (bytes_copied, Some(write.hash.try_digest()?))
+            }
+            None => (io::copy(&mut decompressed_reader, &mut io::sink())?, None),
+        };
         if bytes_copied != entry.decompressed_size {
             return Err(input::Error::IncompletePack {
                 actual: bytes_copied,
@@ -195,6 +222,7 @@ where
             pack_offset,
             decompressed_size: bytes_copied,
             trailer,
+            id,
         })
     }
 }
@@ -21,6 +21,9 @@ pub struct Entry {
     pub crc32: Option<u32>,
     /// The amount of decompressed bytes of the entry.
     pub decompressed_size: u64,
+    /// The id of the object if it is a base object and ids are computed while reading, see
+    /// [`BytesToEntriesIter::with_object_ids()`].
+    pub id: Option<git_hash::ObjectId>,
     /// Set for the last object in the iteration, providing the hash over all bytes of the iteration
     /// for use as trailer in a pack or to verify it matches the trailer.
     pub trailer: Option<git_hash::ObjectId>,
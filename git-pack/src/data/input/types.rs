@@ -18,6 +18,8 @@ pub enum Error {
     IncompletePack { actual: u64, expected: u64 },
     #[error("The object {object_id} could not be decoded or wasn't found")]
     NotFound { object_id: git_hash::ObjectId },
+    #[error("An object in the pack is part of a SHA1 collision attack")]
+    Collision(#[from] git_features::hash::Collision),
 }
 
 /// Iteration Mode
@@ -7,8 +7,6 @@ pub type Offset = u64;
 /// An identifier to uniquely identify all packs loaded within a known context or namespace.
 pub type Id = u32;
 
-use memmap2::Mmap;
-
 /// An representing an full- or delta-object within a pack
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
@@ -22,7 +20,7 @@ pub struct Entry {
 }
 
 mod file;
-pub use file::{decode_entry, verify, ResolvedBase};
+pub use file::{decode_entry, verify, Bytes, Mapping, ResolvedBase};
 ///
 pub mod header;
 
@@ -62,7 +60,7 @@ impl Default for Version {
 
 /// A pack data file
 pub struct File {
-    data: Mmap,
+    data: file::Backing,
     path: std::path::PathBuf,
     /// A value to represent this pack uniquely when used with cache lookup, or a way to identify this pack by its location on disk.
     /// The same location on disk should yield the same id.
@@ -78,6 +76,7 @@ pub struct File {
     /// based on their configuration.
     hash_len: usize,
     object_hash: git_hash::Kind,
+    checksum: git_hash::ObjectId,
 }
 
 /// Information about the pack data file itself
@@ -90,7 +89,7 @@ impl File {
     pub fn num_objects(&self) -> u32 {
         self.num_objects
     }
-    /// The length of all mapped data, including the pack header and the pack trailer
+    /// The length of all pack data, including the pack header and the pack trailer
     pub fn data_len(&self) -> usize {
         self.data.len()
     }
@@ -108,11 +107,16 @@ impl File {
         &self.path
     }
 
-    /// Returns the pack data at the given slice if its range is contained in the mapped pack data
-    pub fn entry_slice(&self, slice: EntryRange) -> Option<&[u8]> {
+    /// Returns the pack data at the given slice if its range is contained in the pack data and could be read.
+    ///
+    /// Depending on the [`Mapping`], the bytes are borrowed from the mapped file or read on demand.
+    pub fn entry_slice(&self, slice: EntryRange) -> Option<Bytes<'_>> {
         let entry_end: usize = slice.end.try_into().expect("end of pack fits into usize");
         let entry_start = slice.start as usize;
-        self.data.get(entry_start..entry_end)
+        if entry_start > entry_end || entry_end > self.data.len() {
+            return None;
+        }
+        self.data.bytes(entry_start..entry_end).ok()
     }
 
     /// Returns the CRC32 of the pack data indicated by `pack_offset` and the `size` of the mapped data.
@@ -120,13 +124,19 @@ impl File {
     /// _Note:_ finding the right size is only possible by decompressing
     /// the pack entry beforehand, or by using the (to be sorted) offsets stored in an index file.
     ///
+    /// Reading the entry data can only fail if the pack isn't [fully mapped][Mapping::Full].
+    ///
     /// # Panics
     ///
-    /// If `pack_offset` or `size` are pointing to a range outside of the mapped pack data.
-    pub fn entry_crc32(&self, pack_offset: Offset, size: usize) -> u32 {
+    /// If `pack_offset` or `size` are pointing to a range outside of the pack data.
+    pub fn entry_crc32(&self, pack_offset: Offset, size: usize) -> std::io::Result<u32> {
         let pack_offset: usize = pack_offset.try_into().expect("pack_size fits into usize");
-        git_features::hash::crc32(&self.data[pack_offset..pack_offset + size])
+        assert!(pack_offset + size <= self.data.len(), "entry out of bounds");
+        Ok(git_features::hash::crc32(
+            &self.data.bytes(pack_offset..pack_offset + size)?,
+        ))
     }
 }
 
-pub(crate) mod delta;
+///
+pub mod delta;
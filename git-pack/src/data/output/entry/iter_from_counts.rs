@@ -2,7 +2,7 @@ use std::{cmp::Ordering, sync::Arc};
 
 use git_features::{parallel, parallel::SequenceId, progress::Progress};
 
-use crate::data::output;
+use crate::data::{output, output::island::Islands};
 
 /// Given a known list of object `counts`, calculate entries ready to be put into a data pack.
 ///
@@ -34,8 +34,26 @@ use crate::data::output;
 ///  or keeping enough state to write a pack and then generate an index with recorded data.
 ///
 pub fn iter_from_counts<Find>(
+    counts: Vec<output::Count>,
+    db: Find,
+    progress: impl Progress,
+    options: Options,
+) -> impl Iterator<Item = Result<(SequenceId, Vec<output::Entry>), Error<Find::Error>>>
+       + parallel::reduce::Finalize<Reduce = reduce::Statistics<Error<Find::Error>>>
+where
+    Find: crate::Find + Send + Clone + 'static,
+    <Find as crate::Find>::Error: Send,
+{
+    iter_from_counts_with_islands(counts, db, None, progress, options)
+}
+
+/// Like [`iter_from_counts()`], but if `islands` are set, objects are only stored as delta against bases that are in all of
+/// their [islands][Islands], which applies to deltas copied from packs as well as to those computed in
+/// [`Mode::PackCopyAndDeltas`]. Deltas copied from packs that don't qualify are decoded instead.
+pub fn iter_from_counts_with_islands<Find>(
     mut counts: Vec<output::Count>,
     db: Find,
+    islands: Option<Arc<Islands>>,
     mut progress: impl Progress,
     Options {
         version,
@@ -54,6 +72,10 @@ where
         matches!(version, crate::data::Version::V2),
         "currently we can only write version 2"
     );
+    let chunk_size = match mode {
+        Mode::PackCopyAndBaseObjects => chunk_size,
+        Mode::PackCopyAndDeltas { window, .. } => chunk_size.max(window),
+    };
     let (chunk_size, thread_limit, _) =
         parallel::optimize_chunk_size_and_thread_limit(chunk_size, Some(counts.len()), thread_limit, None);
     {
@@ -88,7 +110,7 @@ where
         progress.lock().show_throughput(start);
     }
     let counts_range_by_pack_id = match mode {
-        Mode::PackCopyAndBaseObjects => {
+        Mode::PackCopyAndBaseObjects | Mode::PackCopyAndDeltas { .. } => {
             let mut progress = progress.add_child("sorting");
             progress.init(Some(counts.len()), git_features::progress::count("counts"));
             let start = std::time::Instant::now();
@@ -145,12 +167,20 @@ where
             let counts = Arc::clone(&counts);
             move |(chunk_id, chunk_range): (SequenceId, std::ops::Range<usize>), (buf, progress)| {
                 let mut out = Vec::new();
+                let chunk_start = chunk_range.start;
                 let chunk = &counts[chunk_range];
                 let mut stats = Outcome::default();
                 let mut pack_offsets_to_id = None;
+                let mut window = match mode {
+                    Mode::PackCopyAndBaseObjects => None,
+                    Mode::PackCopyAndDeltas { window, max_depth } => {
+                        Some(window::Window::new(window, max_depth, islands.clone()))
+                    }
+                };
                 progress.init(Some(chunk.len()), git_features::progress::count("objects"));
 
-                for count in chunk.iter() {
+                for (count_index, count) in chunk.iter().enumerate() {
+                    let object_index = chunk_start + count_index;
                     out.push(match count
                         .entry_pack_location
                         .as_ref()
@@ -194,32 +224,17 @@ where
                                 }),
                                 version,
                             ) {
-                                Some(entry) => {
+                                Some(entry)
+                                    if (window.is_none() || !is_base_object(&entry))
+                                        && respects_islands(&entry, &counts, islands.as_deref()) =>
+                                {
                                     stats.objects_copied_from_pack += 1;
                                     entry
                                 }
-                                None => match db.try_find(count.id, buf).map_err(Error::FindExisting)? {
-                                    Some((obj, _location)) => {
-                                        stats.decoded_and_recompressed_objects += 1;
-                                        output::Entry::from_data(count, &obj)
-                                    }
-                                    None => {
-                                        stats.missing_objects += 1;
-                                        Ok(output::Entry::invalid())
-                                    }
-                                },
+                                _ => decode_entry(&db, count, object_index, buf, window.as_mut(), &mut stats)?,
                             }
                         }
-                        None => match db.try_find(count.id, buf).map_err(Error::FindExisting)? {
-                            Some((obj, _location)) => {
-                                stats.decoded_and_recompressed_objects += 1;
-                                output::Entry::from_data(count, &obj)
-                            }
-                            None => {
-                                stats.missing_objects += 1;
-                                Ok(output::Entry::invalid())
-                            }
-                        },
+                        None => decode_entry(&db, count, object_index, buf, window.as_mut(), &mut stats)?,
                     }?);
                     progress.inc();
                 }
@@ -230,6 +245,174 @@ where
     )
 }
 
+/// Returns true if `entry` was copied from a pack as base object, and could be delta-compressed instead.
+fn is_base_object(entry: &Result<output::Entry, output::entry::Error>) -> bool {
+    matches!(
+        entry,
+        Ok(output::Entry {
+            kind: output::entry::Kind::Base(_),
+            ..
+        })
+    )
+}
+
+/// Returns true if `entry` isn't a delta, or a delta whose base is in all islands `entry` is in.
+fn respects_islands(
+    entry: &Result<output::Entry, output::entry::Error>,
+    counts: &[output::Count],
+    islands: Option<&Islands>,
+) -> bool {
+    use output::entry::Kind::*;
+    match (islands, entry) {
+        (Some(islands), Ok(output::Entry { id, kind, .. })) => match kind {
+            Base(_) => true,
+            DeltaRef { object_index } => islands.may_delta(id, &counts[*object_index].id),
+            DeltaOid { id: base } => islands.may_delta(id, base),
+        },
+        _ => true,
+    }
+}
+
+/// Decode the object of `count` and turn it into an entry, as delta against an object in `window` if possible.
+fn decode_entry<Find>(
+    db: &Find,
+    count: &output::Count,
+    object_index: usize,
+    buf: &mut Vec<u8>,
+    window: Option<&mut window::Window>,
+    stats: &mut Outcome,
+) -> Result<Result<output::Entry, output::entry::Error>, Error<Find::Error>>
+where
+    Find: crate::Find,
+{
+    Ok(match db.try_find(count.id, buf).map_err(Error::FindExisting)? {
+        Some((obj, _location)) => match window {
+            Some(window) => window.entry(object_index, count, &obj, stats),
+            None => {
+                stats.decoded_and_recompressed_objects += 1;
+                output::Entry::from_data(count, &obj)
+            }
+        },
+        None => {
+            stats.missing_objects += 1;
+            Ok(output::Entry::invalid())
+        }
+    })
+}
+
+mod window {
+    use std::{collections::VecDeque, sync::Arc};
+
+    use git_hash::ObjectId;
+
+    use super::Outcome;
+    use crate::data::{delta, output, output::island::Islands};
+
+    /// The hash of the base object is part of the delta header as well, and a delta must be smaller than half of the
+    /// object to be worth it.
+    const MIN_OBJECT_SIZE: usize = 50;
+
+    struct Candidate {
+        object_index: usize,
+        id: ObjectId,
+        kind: git_object::Kind,
+        data: Vec<u8>,
+        depth: usize,
+    }
+
+    /// The most recently decoded objects which serve as potential delta bases for the next one.
+    pub struct Window {
+        candidates: VecDeque<Candidate>,
+        size: usize,
+        max_depth: usize,
+        delta: Vec<u8>,
+        best_delta: Vec<u8>,
+        islands: Option<Arc<Islands>>,
+    }
+
+    impl Window {
+        pub fn new(size: usize, max_depth: usize, islands: Option<Arc<Islands>>) -> Self {
+            Window {
+                candidates: VecDeque::with_capacity(size),
+                size,
+                max_depth,
+                islands,
+                delta: Vec::new(),
+                best_delta: Vec::new(),
+            }
+        }
+
+        /// Produce an entry for `obj` at `object_index`, as delta against the candidate that produces the smallest delta
+        /// or as base object if there is none, and keep it as candidate for the objects that follow.
+        pub fn entry(
+            &mut self,
+            object_index: usize,
+            count: &output::Count,
+            obj: &git_object::Data<'_>,
+            stats: &mut Outcome,
+        ) -> Result<output::Entry, output::entry::Error> {
+            let best = self.find_delta(&count.id, obj);
+            let (entry, depth) = match best {
+                Some((base_index, base_depth)) => {
+                    stats.delta_compressed_objects += 1;
+                    (
+                        output::Entry::from_delta(count, base_index, &self.best_delta)?,
+                        base_depth + 1,
+                    )
+                }
+                None => {
+                    stats.decoded_and_recompressed_objects += 1;
+                    (output::Entry::from_data(count, obj)?, 0)
+                }
+            };
+            if self.size != 0 {
+                if self.candidates.len() == self.size {
+                    self.candidates.pop_back();
+                }
+                self.candidates.push_front(Candidate {
+                    object_index,
+                    id: count.id,
+                    kind: obj.kind,
+                    data: obj.data.to_owned(),
+                    depth,
+                });
+            }
+            Ok(entry)
+        }
+
+        /// Return the object index and depth of the candidate with the smallest delta, which is placed in `best_delta`.
+        fn find_delta(&mut self, id: &ObjectId, obj: &git_object::Data<'_>) -> Option<(usize, usize)> {
+            if obj.data.len() < MIN_OBJECT_SIZE {
+                return None;
+            }
+            let mut max_size = obj.data.len() / 2 - 20;
+            let mut best = None;
+            let (max_depth, islands) = (self.max_depth, self.islands.as_deref());
+            for candidate in self.candidates.iter().filter(|c| {
+                c.kind == obj.kind
+                    && c.depth < max_depth
+                    && islands.map_or(true, |islands| islands.may_delta(id, &c.id))
+            }) {
+                let size_difference = if candidate.data.len() > obj.data.len() {
+                    candidate.data.len() - obj.data.len()
+                } else {
+                    obj.data.len() - candidate.data.len()
+                };
+                if size_difference >= max_size {
+                    continue;
+                }
+                self.delta.clear();
+                if let Some(delta_size) = delta::create(&candidate.data, obj.data, max_size, &mut self.delta) {
+                    std::mem::swap(&mut self.delta, &mut self.best_delta);
+                    best = Some((candidate.object_index, candidate.depth));
+                    max_size = delta_size.saturating_sub(1);
+                }
+            }
+            best
+        }
+    }
+}
+
 mod util {
     #[derive(Clone)]
     pub struct ChunkRanges {
@@ -321,6 +504,9 @@ mod types {
         pub objects_copied_from_pack: usize,
         /// The amount of objects that ref to their base as ref-delta, an indication for a thin back being created.
         pub ref_delta_objects: usize,
+        /// The amount of decoded objects which were stored as delta against another object in the pack, which only happens
+        /// in [`Mode::PackCopyAndDeltas`].
+        pub delta_compressed_objects: usize,
     }
 
     impl Outcome {
@@ -331,12 +517,14 @@ mod types {
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             }: Self,
         ) {
             self.decoded_and_recompressed_objects += decoded_objects;
             self.missing_objects += missing_objects;
             self.objects_copied_from_pack += objects_copied_from_pack;
             self.ref_delta_objects += ref_delta_objects;
+            self.delta_compressed_objects += delta_compressed_objects;
         }
     }
 
@@ -349,6 +537,19 @@ mod types {
         /// from existing pack compression and spending the smallest possible time on compressing unpacked objects at
         /// the cost of bandwidth.
         PackCopyAndBaseObjects,
+        /// Like [`PackCopyAndBaseObjects`][Mode::PackCopyAndBaseObjects], but only deltas are copied from packs. All other
+        /// objects are decoded and stored as delta against one of the `window` objects decoded before them if that's
+        /// smaller, as long as delta chains don't get longer than `max_depth`.
+        ///
+        /// Objects are processed in chunks of at least `window` objects in parallel, and the search for deltas never crosses
+        /// chunk boundaries. Thus the larger the [chunk size][Options::chunk_size], the better the compression, at the
+        /// cost of parallelism.
+        PackCopyAndDeltas {
+            /// The amount of previously decoded objects to try as base for a delta, like `pack.window` in git.
+            window: usize,
+            /// The maximum length of delta chains, like `pack.depth` in git.
+            max_depth: usize,
+        },
     }
 
     /// Configuration options for the pack generation functions provied in [this module][crate::data::output].
@@ -6,7 +6,7 @@ use crate::{data, data::output, find};
 
 ///
 pub mod iter_from_counts;
-pub use iter_from_counts::iter_from_counts;
+pub use iter_from_counts::{iter_from_counts, iter_from_counts_with_islands};
 
 /// The kind of pack entry to be written
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
@@ -132,17 +132,20 @@ impl output::Entry {
             id: count.id.to_owned(),
             kind: Kind::Base(obj.kind),
             decompressed_size: obj.data.len(),
-            compressed_data: {
-                let mut out = git_features::zlib::stream::deflate::Write::new(Vec::new());
-                if let Err(err) = std::io::copy(&mut &*obj.data, &mut out) {
-                    match err.kind() {
-                        std::io::ErrorKind::Other => return Err(Error::ZlibDeflate(err)),
-                        err => unreachable!("Should never see other errors than zlib, but got {:?}", err,),
-                    }
-                };
-                out.flush()?;
-                out.into_inner()
+            compressed_data: deflate(obj.data)?,
+        })
+    }
+
+    /// Create a new instance from the given `oid` and the `delta` which produces its data from the object at
+    /// `base_object_index`, which must have been written before.
+    pub fn from_delta(count: &output::Count, base_object_index: usize, delta: &[u8]) -> Result<Self, Error> {
+        Ok(output::Entry {
+            id: count.id.to_owned(),
+            kind: Kind::DeltaRef {
+                object_index: base_object_index,
             },
+            decompressed_size: delta.len(),
+            compressed_data: deflate(delta)?,
         })
     }
 
@@ -179,3 +182,15 @@ impl output::Entry {
         }
     }
 }
+
+fn deflate(mut data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut out = git_features::zlib::stream::deflate::Write::new(Vec::new());
+    if let Err(err) = std::io::copy(&mut data, &mut out) {
+        match err.kind() {
+            std::io::ErrorKind::Other => return Err(Error::ZlibDeflate(err)),
+            err => unreachable!("Should never see other errors than zlib, but got {:?}", err,),
+        }
+    };
+    out.flush()?;
+    Ok(out.into_inner())
+}
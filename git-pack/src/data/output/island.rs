@@ -0,0 +1,128 @@
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use git_features::progress::Progress;
+use git_hash::{oid, ObjectId};
+use git_object::{tree::EntryMode, ObjectRef};
+use hash_hasher::HashedMap;
+
+mod error {
+    use git_hash::ObjectId;
+
+    /// The error returned by [`Islands::from_tips()`][super::Islands::from_tips()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error<FindErr>
+    where
+        FindErr: std::error::Error + 'static,
+    {
+        #[error(transparent)]
+        FindExisting(FindErr),
+        #[error("The object {id} reachable from an island could not be found")]
+        NotFound { id: ObjectId },
+        #[error("The object {id} could not be decoded to find the objects it refers to")]
+        ObjectDecode {
+            source: git_object::decode::Error,
+            id: ObjectId,
+        },
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Delta islands, similar to `pack.island` in git, which assign each object to the islands it is reachable from.
+///
+/// When generating a pack, an object may only be stored as delta against a base that is in all of its islands. That way,
+/// each island can be served with a pack containing only its own objects without having to break up deltas, which is useful
+/// when hosting forks of the same repository in one object database with their references in distinct namespaces.
+///
+/// Objects that aren't reachable from any island may use any base.
+#[derive(Default, Debug, Clone)]
+pub struct Islands {
+    /// The index into `sets` for each object that is in at least one island.
+    set_by_object: HashedMap<ObjectId, usize>,
+    /// Unique sets of islands, one bit per island.
+    sets: Vec<Vec<u64>>,
+}
+
+impl Islands {
+    /// Create a new instance from `islands`, each of which is a list of tips that are traversed to find all objects
+    /// reachable from them using `db`. Submodule commits in trees are ignored.
+    pub fn from_tips<Find>(
+        islands: impl IntoIterator<Item = impl IntoIterator<Item = ObjectId>>,
+        db: Find,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Self, Error<Find::Error>>
+    where
+        Find: crate::Find,
+    {
+        let mut out = Islands::default();
+        let mut set_index_by_set = HashMap::<Vec<u64>, usize>::new();
+        let islands: Vec<Vec<ObjectId>> = islands.into_iter().map(|tips| tips.into_iter().collect()).collect();
+        let num_words = (islands.len() + 63) / 64;
+        let mut buf = Vec::new();
+        progress.init(Some(islands.len()), git_features::progress::count("islands"));
+        for (island, tips) in islands.into_iter().enumerate() {
+            let (word, bit) = (island / 64, 1u64 << (island % 64));
+            let mut queue = tips;
+            while let Some(id) = queue.pop() {
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                let mut set = match out.set_by_object.get(&id) {
+                    Some(&set_index) if out.sets[set_index][word] & bit != 0 => continue,
+                    Some(&set_index) => out.sets[set_index].clone(),
+                    None => vec![0; num_words],
+                };
+                set[word] |= bit;
+                let next_set_index = out.sets.len();
+                let set_index = *set_index_by_set.entry(set.clone()).or_insert(next_set_index);
+                if set_index == next_set_index {
+                    out.sets.push(set);
+                }
+                out.set_by_object.insert(id, set_index);
+
+                let (object, _location) = db
+                    .try_find(&id, &mut buf)
+                    .map_err(Error::FindExisting)?
+                    .ok_or(Error::NotFound { id })?;
+                match object.decode().map_err(|err| Error::ObjectDecode { source: err, id })? {
+                    ObjectRef::Commit(commit) => {
+                        queue.push(commit.tree());
+                        queue.extend(commit.parents());
+                    }
+                    ObjectRef::Tree(tree) => queue.extend(
+                        tree.entries
+                            .iter()
+                            .filter(|entry| entry.mode != EntryMode::Commit)
+                            .map(|entry| entry.oid.to_owned()),
+                    ),
+                    ObjectRef::Tag(tag) => queue.push(tag.target()),
+                    ObjectRef::Blob(_) => {}
+                }
+            }
+            progress.inc();
+        }
+        Ok(out)
+    }
+
+    /// Return true if the object `id` may be stored as delta against `base`, which is the case if `base` is in all islands
+    /// that `id` is in.
+    pub fn may_delta(&self, id: &oid, base: &oid) -> bool {
+        let set = match self.set_by_object.get(&id.to_owned()) {
+            Some(&set_index) => &self.sets[set_index],
+            None => return true,
+        };
+        match self.set_by_object.get(&base.to_owned()) {
+            Some(&base_set_index) => set
+                .iter()
+                .zip(&self.sets[base_set_index])
+                .all(|(islands, base_islands)| islands & !base_islands == 0),
+            None => false,
+        }
+    }
+}
@@ -3,6 +3,9 @@ use git_hash::ObjectId;
 ///
 pub mod count;
 
+///
+pub mod island;
+
 /// An item representing a future Entry in the leanest way possible.
 ///
 /// One can expect to have one of these in memory when building big objects, so smaller is better here.
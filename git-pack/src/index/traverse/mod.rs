@@ -15,7 +15,7 @@ mod error;
 pub use error::Error;
 
 mod types;
-pub use types::{Algorithm, SafetyCheck, Statistics};
+pub use types::{Algorithm, CorruptObject, SafetyCheck, Statistics};
 
 /// Traversal options for [`index::File::traverse()`].
 #[derive(Debug, Clone)]
@@ -150,6 +150,40 @@ impl index::File {
         })
     }
 
+    /// Count delta objects and how many of them use each base object, which only needs the pack entry headers.
+    fn add_delta_statistics<E>(&self, pack: &crate::data::File, stats: &mut Statistics) -> Result<(), Error<E>>
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut deltas_per_base = std::collections::HashMap::<u64, u32>::new();
+        for index_entry in self.iter() {
+            let pack_entry = pack.entry(index_entry.pack_offset).map_err(|e| Error::PackDecode {
+                source: e,
+                id: index_entry.oid,
+                offset: index_entry.pack_offset,
+            })?;
+            let base_pack_offset = match pack_entry.header {
+                crate::data::entry::Header::OfsDelta { base_distance } => {
+                    stats.num_ofs_deltas += 1;
+                    pack_entry.base_pack_offset(base_distance)
+                }
+                crate::data::entry::Header::RefDelta { base_id } => {
+                    stats.num_ref_deltas += 1;
+                    match self.lookup(base_id) {
+                        Some(index) => self.pack_offset_at_index(index),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            *deltas_per_base.entry(base_pack_offset).or_insert(0) += 1;
+        }
+        for num_deltas in deltas_per_base.values() {
+            *stats.bases_per_delta_count.entry(*num_deltas).or_insert(0) += 1;
+        }
+        Ok(())
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn decode_and_process_entry<C, P, E>(
         &self,
@@ -166,7 +200,12 @@ impl index::File {
         P: Progress,
         E: std::error::Error + Send + Sync + 'static,
     {
-        let pack_entry = pack.entry(index_entry.pack_offset);
+        let pack_decode_error = |e| Error::PackDecode {
+            source: e,
+            id: index_entry.oid,
+            offset: index_entry.pack_offset,
+        };
+        let pack_entry = pack.entry(index_entry.pack_offset).map_err(pack_decode_error)?;
         let pack_entry_data_offset = pack_entry.data_offset;
         let entry_stats = pack
             .decode_entry(
@@ -174,15 +213,12 @@ impl index::File {
                 buf,
                 |id, _| {
                     self.lookup(id)
-                        .map(|index| crate::data::ResolvedBase::InPack(pack.entry(self.pack_offset_at_index(index))))
+                        .and_then(|index| pack.entry(self.pack_offset_at_index(index)).ok())
+                        .map(crate::data::ResolvedBase::InPack)
                 },
                 cache,
             )
-            .map_err(|e| Error::PackDecode {
-                source: e,
-                id: index_entry.oid,
-                offset: index_entry.pack_offset,
-            })?;
+            .map_err(pack_decode_error)?;
         let object_kind = entry_stats.kind;
         let header_size = (pack_entry_data_offset - index_entry.pack_offset) as usize;
         let entry_len = header_size + entry_stats.compressed_size;
@@ -207,7 +243,7 @@ fn process_entry<P, E>(
     decompressed: &[u8],
     progress: &mut P,
     index_entry: &crate::index::Entry,
-    pack_entry_crc32: impl FnOnce() -> u32,
+    pack_entry_crc32: impl FnOnce() -> std::io::Result<u32>,
     processor: &mut impl FnMut(git_object::Kind, &[u8], &index::Entry, &mut P) -> Result<(), E>,
 ) -> Result<(), Error<E>>
 where
@@ -229,7 +265,11 @@ where
             });
         }
         if let Some(desired_crc32) = index_entry.crc32 {
-            let actual_crc32 = pack_entry_crc32();
+            let actual_crc32 = pack_entry_crc32().map_err(|e| Error::PackDecode {
+                source: e.into(),
+                id: index_entry.oid,
+                offset: index_entry.pack_offset,
+            })?;
             if actual_crc32 != desired_crc32 {
                 return Err(Error::Crc32Mismatch {
                     actual: actual_crc32,
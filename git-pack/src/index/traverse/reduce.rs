@@ -68,13 +68,13 @@ where
     P: Progress,
     E: std::error::Error + Send + Sync + 'static,
 {
-    type Input = Result<Vec<data::decode_entry::Outcome>, traverse::Error<E>>;
+    type Input = Result<(Vec<data::decode_entry::Outcome>, Vec<traverse::CorruptObject>), traverse::Error<E>>;
     type FeedProduce = ();
     type Output = traverse::Statistics;
     type Error = traverse::Error<E>;
 
     fn feed(&mut self, input: Self::Input) -> Result<(), Self::Error> {
-        let chunk_stats: Vec<_> = match input {
+        let (chunk_stats, corrupt_objects) = match input {
             Err(err @ traverse::Error::PackDecode { .. }) if !self.check.fatal_decode_error() => {
                 lock(&self.progress).info(format!("Ignoring decode error: {}", err));
                 return Ok(());
@@ -82,6 +82,7 @@ where
             res => res,
         }?;
         self.entries_seen += chunk_stats.len();
+        self.stats.corrupt_objects.extend(corrupt_objects);
 
         let chunk_total = chunk_stats.into_iter().fold(
             data::decode_entry::Outcome::default_from_kind(git_object::Kind::Tree),
@@ -113,6 +114,7 @@ where
 
     fn finalize(mut self) -> Result<Self::Output, Self::Error> {
         div_decode_result(&mut self.stats.average, self.entries_seen as usize);
+        self.stats.corrupt_objects.sort_by_key(|object| object.pack_offset);
 
         let elapsed_s = self.then.elapsed().as_secs_f32();
         let objects_per_second = (self.entries_seen as f32 / elapsed_s) as u32;
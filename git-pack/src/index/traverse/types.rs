@@ -27,6 +27,31 @@ pub struct Statistics {
     pub num_tags: u32,
     /// The amount of objects encountered that where blobs
     pub num_blobs: u32,
+    /// The amount of delta objects referring to their base object by offset into the pack.
+    pub num_ofs_deltas: u32,
+    /// The amount of delta objects referring to their base object by id.
+    pub num_ref_deltas: u32,
+    /// A mapping of the amount of delta objects directly based on an object to the amount of such base objects.
+    ///
+    /// Objects that don't serve as base for any delta object aren't counted.
+    pub bases_per_delta_count: BTreeMap<u32, u32>,
+    /// Objects that couldn't be decoded, in order of their position in the pack.
+    ///
+    /// This is only ever non-empty if decode errors are [not fatal][SafetyCheck::SkipFileAndObjectChecksumVerificationAndNoAbortOnDecodeError]
+    /// and the [lookup algorithm][Algorithm::Lookup] is used.
+    pub corrupt_objects: Vec<CorruptObject>,
+}
+
+/// An object that couldn't be decoded during [`traverse()`][crate::index::File::traverse()].
+#[derive(Debug, PartialEq, Eq, Hash, Ord, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct CorruptObject {
+    /// The offset of the object's entry into the pack.
+    pub pack_offset: u64,
+    /// The id of the object as recorded in the index.
+    pub id: git_hash::ObjectId,
+    /// A description of the decode error.
+    pub message: String,
 }
 
 impl Default for Statistics {
@@ -42,6 +67,10 @@ impl Default for Statistics {
             num_commits: 0,
             num_trees: 0,
             num_tags: 0,
+            num_ofs_deltas: 0,
+            num_ref_deltas: 0,
+            bases_per_delta_count: Default::default(),
+            corrupt_objects: Vec::new(),
         }
     }
 }
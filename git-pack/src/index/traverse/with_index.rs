@@ -75,7 +75,7 @@ impl index::File {
                 let there_are_enough_objects = || self.num_objects > 10_000;
                 let mut outcome = digest_statistics(tree.traverse(
                     there_are_enough_objects,
-                    |slice, out| pack.entry_slice(slice).map(|entry| out.copy_from_slice(entry)),
+                    |slice, out| pack.entry_slice(slice).map(|entry| out.copy_from_slice(&entry)),
                     pack.pack_end() as u64,
                     new_processor,
                     |data,
@@ -103,10 +103,14 @@ impl index::File {
                                 // TODO: Fix this - we overwrite the header of 'data' which also changes the computed entry size,
                                 // causing index and pack to seemingly mismatch. This is surprising, and should be done differently.
                                 // debug_assert_eq!(&data.index_entry.pack_offset, &pack_entry.pack_offset());
-                                git_features::hash::crc32(
-                                    pack.entry_slice(data.index_entry.pack_offset..entry_end)
-                                        .expect("slice pointing into the pack (by now data is verified)"),
-                                )
+                                pack.entry_slice(data.index_entry.pack_offset..entry_end)
+                                    .map(|entry| git_features::hash::crc32(&entry))
+                                    .ok_or_else(|| {
+                                        std::io::Error::new(
+                                            std::io::ErrorKind::Other,
+                                            "pack entry could not be read for computing its CRC32",
+                                        )
+                                    })
                             },
                             processor,
                         );
@@ -130,9 +134,12 @@ impl index::File {
                 Ok(outcome)
             },
         );
+        let actual_index_checksum = verify_result?;
+        let mut statistics = traversal_result?;
+        self.add_delta_statistics(pack, &mut statistics)?;
         Ok(Outcome {
-            actual_index_checksum: verify_result?,
-            statistics: traversal_result?,
+            actual_index_checksum,
+            statistics,
             progress,
         })
     }
@@ -5,7 +5,7 @@ use git_features::{
     progress::{self, unit, Progress},
 };
 
-use super::{Error, Reducer};
+use super::{CorruptObject, Error, Reducer};
 use crate::{data, index, index::util};
 
 /// Traversal options for [`traverse()`][crate::index::File::traverse_with_lookup()]
@@ -113,7 +113,7 @@ impl index::File {
                     state_per_thread,
                     |entries: &[index::Entry],
                      (cache, ref mut processor, buf, progress)|
-                     -> Result<Vec<data::decode_entry::Outcome>, Error<_>> {
+                     -> Result<(Vec<data::decode_entry::Outcome>, Vec<CorruptObject>), Error<_>> {
                         progress.init(
                             Some(entries.len()),
                             Some(unit::dynamic(unit::Human::new(
@@ -122,6 +122,7 @@ impl index::File {
                             ))),
                         );
                         let mut stats = Vec::with_capacity(entries.len());
+                        let mut corrupt_objects = Vec::new();
                         progress.set(0);
                         for index_entry in entries.iter() {
                             let result = self.decode_and_process_entry(
@@ -135,23 +136,34 @@ impl index::File {
                             );
                             progress.inc();
                             let stat = match result {
-                                Err(err @ Error::PackDecode { .. }) if !check.fatal_decode_error() => {
-                                    progress.info(format!("Ignoring decode error: {}", err));
+                                Err(Error::PackDecode { id, offset, source }) if !check.fatal_decode_error() => {
+                                    progress.info(format!(
+                                        "Ignoring decode error of object {} at offset {}: {}",
+                                        id, offset, source
+                                    ));
+                                    corrupt_objects.push(CorruptObject {
+                                        pack_offset: offset,
+                                        id,
+                                        message: source.to_string(),
+                                    });
                                     continue;
                                 }
                                 res => res,
                             }?;
                             stats.push(stat);
                         }
-                        Ok(stats)
+                        Ok((stats, corrupt_objects))
                     },
                     Reducer::from_progress(reduce_progress, pack.data_len(), check, should_interrupt),
                 )
             },
         );
+        let actual_index_checksum = verify_result?;
+        let mut statistics = traversal_result?;
+        self.add_delta_statistics(pack, &mut statistics)?;
         Ok(Outcome {
-            actual_index_checksum: verify_result?,
-            statistics: traversal_result?,
+            actual_index_checksum,
+            statistics,
             progress,
         })
     }
@@ -20,6 +20,8 @@ pub enum Error {
     IteratorInvariantTooManyObjects(usize),
     #[error("{pack_offset} is not a valid offset for pack offset {distance}")]
     IteratorInvariantBaseOffset { pack_offset: u64, distance: u64 },
+    #[error("An object in the pack is part of a SHA1 collision attack")]
+    Collision(#[from] git_features::hash::Collision),
     #[error(transparent)]
     Tree(#[from] crate::cache::delta::Error),
     #[error(transparent)]
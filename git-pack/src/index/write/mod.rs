@@ -43,6 +43,9 @@ impl crate::index::File {
     /// # Remarks
     ///
     /// * neither in-pack nor out-of-pack Ref Deltas are supported here, these must have been resolved beforehand.
+    /// * base objects whose [id][crate::data::input::Entry::id] is known are indexed while `entries` are still coming in,
+    /// and unless deltas refer to them, they are never decoded again. Thus the index is built progressively if ids are
+    /// computed by the iterator, leaving only delta objects to be resolved once the pack is complete.
     /// * `make_resolver()` will only be called after the iterator stopped returning elements and produces a function that
     /// provides all bytes belonging to a pack entry writing them to the given mutable output `Vec`.
     /// It should return `None` if the entry cannot be resolved from the pack that produced the `entries` iterator, causing
@@ -80,60 +83,64 @@ impl crate::index::File {
         decompressed_progress.init(None, progress::bytes());
         let mut pack_entries_end: u64 = 0;
 
-        for (eid, entry) in entries.enumerate() {
-            let crate::data::input::Entry {
-                header,
-                pack_offset,
-                crc32,
-                header_size,
-                compressed: _,
-                compressed_size,
-                decompressed_size,
-                trailer,
-            } = entry?;
-
-            bytes_to_process += decompressed_size;
-            decompressed_progress.inc_by(decompressed_size as usize);
-
-            let entry_len = header_size as u64 + compressed_size;
-            pack_entries_end = pack_offset + entry_len;
-
-            let crc32 = crc32.expect("crc32 to be computed by the iterator. Caller assures correct configuration.");
-
-            use crate::data::entry::Header::*;
-            match header {
-                Tree | Blob | Commit | Tag => {
-                    last_base_index = Some(eid);
-                    tree.add_root(
-                        pack_offset,
-                        TreeEntry {
-                            id: object_hash.null(),
-                            crc32,
-                        },
-                    )?;
-                }
-                RefDelta { .. } => return Err(Error::IteratorInvariantNoRefDelta),
-                OfsDelta { base_distance } => {
-                    let base_pack_offset =
-                        crate::data::entry::Header::verified_base_pack_offset(pack_offset, base_distance).ok_or(
-                            Error::IteratorInvariantBaseOffset {
-                                pack_offset,
-                                distance: base_distance,
+        {
+            let _span = git_features::trace_span!("index pack entries");
+            for (eid, entry) in entries.enumerate() {
+                let crate::data::input::Entry {
+                    header,
+                    pack_offset,
+                    crc32,
+                    header_size,
+                    compressed: _,
+                    compressed_size,
+                    decompressed_size,
+                    trailer,
+                    id,
+                } = entry?;
+
+                bytes_to_process += decompressed_size;
+                decompressed_progress.inc_by(decompressed_size as usize);
+
+                let entry_len = header_size as u64 + compressed_size;
+                pack_entries_end = pack_offset + entry_len;
+
+                let crc32 = crc32.expect("crc32 to be computed by the iterator. Caller assures correct configuration.");
+
+                use crate::data::entry::Header::*;
+                match header {
+                    Tree | Blob | Commit | Tag => {
+                        last_base_index = Some(eid);
+                        tree.add_root(
+                            pack_offset,
+                            TreeEntry {
+                                id: id.unwrap_or_else(|| object_hash.null()),
+                                crc32,
                             },
                         )?;
-                    tree.add_child(
-                        base_pack_offset,
-                        pack_offset,
-                        TreeEntry {
-                            id: object_hash.null(),
-                            crc32,
-                        },
-                    )?;
-                }
-            };
-            last_seen_trailer = trailer;
-            num_objects += 1;
-            objects_progress.inc();
+                    }
+                    RefDelta { .. } => return Err(Error::IteratorInvariantNoRefDelta),
+                    OfsDelta { base_distance } => {
+                        let base_pack_offset =
+                            crate::data::entry::Header::verified_base_pack_offset(pack_offset, base_distance).ok_or(
+                                Error::IteratorInvariantBaseOffset {
+                                    pack_offset,
+                                    distance: base_distance,
+                                },
+                            )?;
+                        tree.add_child(
+                            base_pack_offset,
+                            pack_offset,
+                            TreeEntry {
+                                id: object_hash.null(),
+                                crc32,
+                            },
+                        )?;
+                    }
+                };
+                last_seen_trailer = trailer;
+                num_objects += 1;
+                objects_progress.inc();
+            }
         }
         if num_objects != anticipated_num_objects {
             objects_progress.info(format!(
@@ -145,6 +152,7 @@ impl crate::index::File {
             .try_into()
             .map_err(|_| Error::IteratorInvariantTooManyObjects(num_objects))?;
         last_base_index.ok_or(Error::IteratorInvariantBasesPresent)?;
+        git_features::trace_info!(num_objects, bytes_to_process, "indexed pack entries");
 
         objects_progress.show_throughput(indexing_start);
         decompressed_progress.show_throughput(indexing_start);
@@ -153,8 +161,11 @@ impl crate::index::File {
 
         root_progress.inc();
 
+        let resolved_roots =
+            tree.take_resolved_roots_without_children(pack_entries_end, |entry| !entry.id.is_null())?;
         let resolver = make_resolver()?;
         let sorted_pack_offsets_by_oid = {
+            let _span = git_features::trace_span!("resolve deltas", num_objects);
             let in_parallel_if_pack_is_big_enough = || bytes_to_process > 5_000_000;
             let traverse::Outcome { roots, children } = tree.traverse(
                 in_parallel_if_pack_is_big_enough,
@@ -167,10 +178,7 @@ impl crate::index::File {
                      entry,
                      decompressed: bytes,
                      ..
-                 }| {
-                    modify_base(data, entry, bytes, kind.hash());
-                    Ok::<_, Error>(())
-                },
+                 }| { modify_base(data, entry, bytes, kind.hash()) },
                 crate::cache::delta::traverse::Options {
                     object_progress: root_progress.add_child("Resolving"),
                     size_progress: root_progress.add_child("Decoding"),
@@ -183,6 +191,7 @@ impl crate::index::File {
 
             let mut items = roots;
             items.extend(children);
+            items.extend(resolved_roots);
             {
                 let _progress = root_progress.add_child("sorting by id");
                 items.sort_by_key(|e| e.data.id);
@@ -193,6 +202,7 @@ impl crate::index::File {
         };
 
         let pack_hash = last_seen_trailer.ok_or(Error::IteratorInvariantTrailer)?;
+        let _span = git_features::trace_span!("write index file");
         let index_hash = encode::write_to(
             out,
             sorted_pack_offsets_by_oid,
@@ -220,15 +230,23 @@ fn modify_base(
     pack_entry: &crate::data::Entry,
     decompressed: &[u8],
     hash: git_hash::Kind,
-) {
-    fn compute_hash(kind: git_object::Kind, bytes: &[u8], object_hash: git_hash::Kind) -> git_hash::ObjectId {
+) -> Result<(), Error> {
+    fn compute_hash(
+        kind: git_object::Kind,
+        bytes: &[u8],
+        object_hash: git_hash::Kind,
+    ) -> Result<git_hash::ObjectId, git_features::hash::Collision> {
         let mut hasher = git_features::hash::hasher(object_hash);
         hasher.update(&git_object::encode::loose_header(kind, bytes.len()));
         hasher.update(bytes);
-        git_hash::ObjectId::from(hasher.digest())
+        hasher.try_digest()
     }
 
+    if !entry.id.is_null() {
+        return Ok(());
+    }
     let object_kind = pack_entry.header.as_kind().expect("base object as source of iteration");
-    let id = compute_hash(object_kind, decompressed, hash);
+    let id = compute_hash(object_kind, decompressed, hash)?;
     entry.id = id;
+    Ok(())
 }
@@ -18,6 +18,9 @@
     cfg_attr(doc, doc = ::document_features::document_features!())
 )]
 
+///
+pub mod bitmap;
+
 ///
 pub mod bundle;
 /// A bundle of pack data and the corresponding pack index
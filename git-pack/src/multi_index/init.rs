@@ -20,6 +20,8 @@ mod error {
         UnsupportedVersion { version: u8 },
         #[error("Unsupported hash kind: {kind})")]
         UnsupportedObjectHash { kind: u8 },
+        #[error("Multi-index files based on {count} other multi-index file(s) are unsupported")]
+        UnsupportedBaseFiles { count: u8 },
         #[error(transparent)]
         ChunkFileDecode(#[from] git_chunk::file::decode::Error),
         #[error(transparent)]
@@ -85,7 +87,12 @@ impl TryFrom<&Path> for File {
             let (num_chunks, data) = data.split_at(1);
             let num_chunks = num_chunks[0];
 
-            let (_num_base_files, data) = data.split_at(1); // TODO: handle base files once it's clear what this does
+            let (num_base_files, data) = data.split_at(1);
+            if num_base_files[0] != 0 {
+                return Err(Error::UnsupportedBaseFiles {
+                    count: num_base_files[0],
+                });
+            }
 
             let (num_indices, _) = data.split_at(4);
             let num_indices = crate::read_u32(num_indices);
@@ -10,6 +10,8 @@ pub mod checksum {
     pub enum Error {
         #[error("Interrupted by user")]
         Interrupted,
+        #[error("The file to checksum could not be read")]
+        Io(#[from] std::io::Error),
         #[error("index checksum mismatch: expected {expected}, got {actual}")]
         Mismatch {
             expected: git_hash::ObjectId,
@@ -25,6 +27,35 @@ pub fn fan(data: &[u32]) -> Option<usize> {
         .find_map(|(win_index, v)| (v[0] > v[1]).then(|| win_index))
 }
 
+/// Calculate the hash of the given kind by reading the first `data_len` bytes of the file at `data_path`, excluding the trailing hash,
+/// and compare it to `expected`. This is for files that aren't mapped into memory.
+pub fn checksum_on_disk(
+    data_path: &Path,
+    data_len: usize,
+    expected: git_hash::ObjectId,
+    object_hash: git_hash::Kind,
+    mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> Result<git_hash::ObjectId, checksum::Error> {
+    let actual = git_features::hash::bytes_of_file(
+        data_path,
+        data_len - object_hash.len_in_bytes(),
+        object_hash,
+        &mut progress,
+        should_interrupt,
+    )
+    .map_err(|err| match err.kind() {
+        std::io::ErrorKind::Interrupted => checksum::Error::Interrupted,
+        _ => checksum::Error::Io(err),
+    })?;
+
+    if actual == expected {
+        Ok(actual)
+    } else {
+        Err(checksum::Error::Mismatch { actual, expected })
+    }
+}
+
 /// Calculate the hash of the given kind by trying to read the file from disk at `data_path` or falling back on the mapped content in `data`.
 /// `Ok(desired_hash)` or `Err(Some(actual_hash))` is returned if the hash matches or mismatches.
 /// If the `Err(None)` is returned, the operation was interrupted.
@@ -0,0 +1,24 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+git checkout -q -b main
+for round in $(seq 10); do
+  seq "$round" > "file-$round"
+  git add .
+  git commit -qm "$round"
+done
+
+git checkout -q -b other HEAD~5
+for round in $(seq 3); do
+  echo "$round" > "other-$round"
+  git add .
+  git commit -qm "other $round"
+done
+git checkout -q main
+
+git tag -m "a tag object" annotated
+
+git repack -adq --write-bitmap-index
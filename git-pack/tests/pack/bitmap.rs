@@ -0,0 +1,176 @@
+use git_pack::bitmap::{File, PackOrder};
+
+use crate::{hex_to_id, scripted_fixture_repo_read_only};
+
+fn bitmap_and_index() -> crate::Result<(File, git_pack::index::File)> {
+    let pack_dir = scripted_fixture_repo_read_only("make_pack_gen_repo_bitmap.sh")?.join(".git/objects/pack");
+    let bitmap_path = std::fs::read_dir(&pack_dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("bitmap"))
+        .expect("a bitmap was written");
+    let bitmap = File::at(&bitmap_path, git_hash::Kind::Sha1)?;
+    let index = git_pack::index::File::at(bitmap_path.with_extension("idx"), git_hash::Kind::Sha1)?;
+    Ok((bitmap, index))
+}
+
+#[test]
+fn access() -> crate::Result {
+    let (bitmap, index) = bitmap_and_index()?;
+    assert_eq!(bitmap.pack_checksum(), index.pack_checksum());
+    assert_eq!(
+        bitmap.num_commits(),
+        13,
+        "git selects all commits in such a small repository"
+    );
+    assert!(bitmap.has_name_hash_cache());
+    assert!(bitmap.name_hash(index.num_objects() - 1).is_some());
+    assert_eq!(bitmap.name_hash(index.num_objects()), None, "out of bounds");
+
+    let counts: Vec<_> = [
+        git_object::Kind::Commit,
+        git_object::Kind::Tree,
+        git_object::Kind::Blob,
+        git_object::Kind::Tag,
+    ]
+    .iter()
+    .map(|kind| bitmap.objects_of_kind(*kind).count_ones())
+    .collect();
+    assert_eq!(counts, vec![13, 13, 12, 1]);
+    assert_eq!(counts.iter().sum::<usize>(), index.num_objects() as usize);
+    Ok(())
+}
+
+#[test]
+fn reachability_and_set_operations() -> crate::Result {
+    let (bitmap, index) = bitmap_and_index()?;
+    let main = hex_to_id("841b857f52a46283c179d67ae948745c4f60928f");
+    let other = hex_to_id("95e30eecfc1e9c5d22786fb5d2573fc38c9790f6");
+
+    let from_main = bitmap.reachable_from(&index, main).expect("main has a bitmap");
+    let from_other = bitmap.reachable_from(&index, other).expect("other has a bitmap");
+    assert_eq!(from_main.count_ones(), 30, "git rev-list --objects main | wc -l");
+    assert_eq!(from_other.count_ones(), 23, "git rev-list --objects other | wc -l");
+
+    let order = PackOrder::from_index(&index);
+    let ids: Vec<_> = order.ids(&index, &from_main).collect();
+    assert_eq!(ids.len(), 30);
+    assert!(ids.contains(&main.as_ref()));
+    assert!(!ids.contains(&other.as_ref()));
+
+    let commits = bitmap.objects_of_kind(git_object::Kind::Commit);
+    let mut ahead = from_main.clone();
+    ahead.difference_with(&from_other);
+    assert_eq!(ahead.count_ones(), 15, "git rev-list --objects main ^other | wc -l");
+    ahead.intersect_with(&commits);
+    assert_eq!(ahead.count_ones(), 5, "git rev-list --count main ^other");
+
+    let mut behind = from_other.clone();
+    behind.difference_with(&from_main);
+    behind.intersect_with(&commits);
+    assert_eq!(behind.count_ones(), 3, "git rev-list --count other ^main");
+
+    let mut all = from_main;
+    all.union_with(&from_other);
+    assert_eq!(all.count_ones(), 38, "everything but the annotated tag");
+
+    let tag = hex_to_id("e25e97d522ad9e33815656ab8ad8eca2a0e3a21e");
+    assert!(
+        bitmap.reachable_from(&index, tag).is_none(),
+        "only commits have bitmaps"
+    );
+    let tag_position = order
+        .pack_position(index.lookup(tag).expect("tag is in pack"))
+        .expect("in bounds");
+    assert!(bitmap.objects_of_kind(git_object::Kind::Tag).get(tag_position as usize));
+    Ok(())
+}
+
+#[test]
+fn reachability_queries_traverse_objects_without_bitmaps() -> crate::Result {
+    let (bitmap, index) = bitmap_and_index()?;
+    let order = PackOrder::from_index(&index);
+    let db = git_odb::at(scripted_fixture_repo_read_only("make_pack_gen_repo_bitmap.sh")?.join(".git/objects"))?;
+    let main = hex_to_id("841b857f52a46283c179d67ae948745c4f60928f");
+    let other = hex_to_id("95e30eecfc1e9c5d22786fb5d2573fc38c9790f6");
+    let tag = hex_to_id("e25e97d522ad9e33815656ab8ad8eca2a0e3a21e");
+
+    let from_tag = bitmap
+        .objects_reachable_from(&index, &order, Some(tag), &db)?
+        .expect("all objects are in the pack");
+    assert_eq!(from_tag.count_ones(), 31, "the tag and everything reachable from main");
+
+    let ahead = bitmap
+        .objects_reachable_from_excluding(&index, &order, Some(main), Some(other), &db)?
+        .expect("all objects are in the pack");
+    assert_eq!(ahead.count_ones(), 15, "git rev-list --objects main ^other | wc -l");
+
+    assert_eq!(
+        bitmap.is_reachable_from(&index, &order, main, Some(tag), &db)?,
+        Some(true)
+    );
+    assert_eq!(
+        bitmap.is_reachable_from(&index, &order, other, Some(tag), &db)?,
+        Some(false)
+    );
+    assert_eq!(
+        bitmap.objects_reachable_from(
+            &index,
+            &order,
+            Some(git_hash::ObjectId::null(git_hash::Kind::Sha1)),
+            &db
+        )?,
+        None,
+        "objects outside of the pack can't be represented, so the caller has to traverse the object graph"
+    );
+    Ok(())
+}
+
+#[test]
+fn write_and_read_back() -> crate::Result {
+    let (expected, index) = bitmap_and_index()?;
+    let bundle = git_pack::Bundle::at(index.path(), git_hash::Kind::Sha1)?;
+    let main = hex_to_id("841b857f52a46283c179d67ae948745c4f60928f");
+    let other = hex_to_id("95e30eecfc1e9c5d22786fb5d2573fc38c9790f6");
+    let tag = hex_to_id("e25e97d522ad9e33815656ab8ad8eca2a0e3a21e");
+
+    let dir = tempfile::tempdir()?;
+    let bitmap_path = dir.path().join("pack.bitmap");
+    let outcome = File::write_from_bundle(
+        &bundle,
+        vec![main, other, tag],
+        std::fs::File::create(&bitmap_path)?,
+        git_features::progress::Discard,
+        &std::sync::atomic::AtomicBool::default(),
+        Default::default(),
+    )?;
+    assert_eq!(
+        outcome.num_commits, 13,
+        "small repositories get a bitmap for each commit"
+    );
+
+    let actual = File::at(&bitmap_path, git_hash::Kind::Sha1)?;
+    assert_eq!(actual.checksum(), outcome.bitmap_checksum);
+    assert_eq!(actual.pack_checksum(), expected.pack_checksum());
+    assert_eq!(actual.num_commits(), expected.num_commits());
+    assert!(!actual.has_name_hash_cache());
+
+    for kind in &[
+        git_object::Kind::Commit,
+        git_object::Kind::Tree,
+        git_object::Kind::Blob,
+        git_object::Kind::Tag,
+    ] {
+        assert_eq!(actual.objects_of_kind(*kind), expected.objects_of_kind(*kind));
+    }
+    for entry_index in 0..expected.num_commits() {
+        let id = index.oid_at_index(expected.commit_index_position_at(entry_index));
+        assert_eq!(
+            actual.reachable_from(&index, id),
+            Some(expected.reachable_at(entry_index)),
+            "our bitmaps are the same as the ones written by git"
+        );
+    }
+    Ok(())
+}
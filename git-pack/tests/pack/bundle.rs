@@ -98,6 +98,7 @@ mod write_to_directory {
             pack_kind: pack::data::Version::V2,
             index_path: None,
             data_path: None,
+            keep_path: None,
             object_hash: git_hash::Kind::Sha1,
         })
     }
@@ -136,6 +137,17 @@ mod write_to_directory {
         Ok(())
     }
 
+    #[test]
+    fn given_a_directory_and_a_keep_reason() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = TempDir::new()?;
+        let res = write_pack_with_keep(Some(&dir), SMALL_PACK, Some("received".into()))?;
+        let keep_path = res.keep_path.expect("keep file requested");
+        assert_eq!(Some(keep_path.with_extension("pack")), res.data_path);
+        assert_eq!(fs::read(&keep_path)?, b"received\n");
+        assert_eq!(fs::read_dir(&dir)?.count(), 3, "pack, index and keep file");
+        Ok(())
+    }
+
     fn file_name(entry: &fs::DirEntry) -> String {
         entry.path().file_name().unwrap().to_str().unwrap().to_owned()
     }
@@ -143,6 +155,14 @@ mod write_to_directory {
     fn write_pack(
         directory: Option<impl AsRef<Path>>,
         pack_file: &str,
+    ) -> Result<pack::bundle::write::Outcome, Box<dyn std::error::Error>> {
+        write_pack_with_keep(directory, pack_file, None)
+    }
+
+    fn write_pack_with_keep(
+        directory: Option<impl AsRef<Path>>,
+        pack_file: &str,
+        keep: Option<String>,
     ) -> Result<pack::bundle::write::Outcome, Box<dyn std::error::Error>> {
         let pack_file = fs::File::open(fixture_path(pack_file))?;
         static SHOULD_INTERRUPT: AtomicBool = AtomicBool::new(false);
@@ -158,6 +178,7 @@ mod write_to_directory {
                 iteration_mode: pack::data::input::Mode::Verify,
                 index_kind: pack::index::Version::V2,
                 object_hash: git_hash::Kind::Sha1,
+                keep,
             },
         )
         .map_err(Into::into)
@@ -100,7 +100,7 @@ mod decode_entry {
         }
 
         let p = pack_at(SMALL_PACK);
-        let entry = p.entry(offset);
+        let entry = p.entry(offset).expect("valid offset");
         let mut buf = Vec::new();
         p.decode_entry(entry, &mut buf, resolve_with_panic, &mut cache::Never)
             .expect("valid offset provides valid entry");
@@ -151,7 +151,7 @@ mod decompress_entry {
 
     fn decompress_entry_at_offset(offset: u64) -> Vec<u8> {
         let p = pack_at(SMALL_PACK);
-        let entry = p.entry(offset);
+        let entry = p.entry(offset).expect("valid offset");
 
         let size = entry.decompressed_size as usize;
         let mut buf = Vec::with_capacity(size);
@@ -163,3 +163,80 @@ mod decompress_entry {
         buf
     }
 }
+
+mod mapping {
+    use std::sync::atomic::AtomicBool;
+
+    use git_features::progress;
+    use git_pack::{cache, data::Mapping, index};
+
+    use crate::{
+        fixture_path,
+        pack::{SMALL_PACK, V2_PACKS_AND_INDICES},
+    };
+
+    #[test]
+    fn read_errors_are_returned_instead_of_panicking() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let pack_path = dir.path().join("pack.pack");
+        std::fs::copy(fixture_path(SMALL_PACK), &pack_path)?;
+        let pack = git_pack::data::File::at_with_mapping(&pack_path, git_hash::Kind::Sha1, Mapping::Read)?;
+        std::fs::OpenOptions::new().write(true).open(&pack_path)?.set_len(12)?;
+
+        let err = pack.entry(12).expect_err("the entry header can't be read anymore");
+        assert!(
+            matches!(err, git_pack::data::decode_entry::Error::Io(_)),
+            "expected an IO error, got {:?}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn all_mappings_yield_the_same_objects() -> Result<(), Box<dyn std::error::Error>> {
+        for (index_path, pack_path) in V2_PACKS_AND_INDICES {
+            let idx = index::File::at(&fixture_path(index_path), git_hash::Kind::Sha1)?;
+            let mut expected = None;
+            for mapping in [
+                Mapping::Full,
+                Mapping::Windowed {
+                    window_size: 1,
+                    max_windows: 1,
+                },
+                Mapping::Read,
+            ] {
+                let pack =
+                    git_pack::data::File::at_with_mapping(fixture_path(pack_path), git_hash::Kind::Sha1, mapping)?;
+                assert_eq!(pack.checksum(), idx.pack_checksum());
+                assert_eq!(
+                    pack.verify_checksum(progress::Discard, &AtomicBool::new(false))?,
+                    pack.checksum()
+                );
+                for algo in [
+                    index::traverse::Algorithm::Lookup,
+                    index::traverse::Algorithm::DeltaTreeLookup,
+                ] {
+                    let stats = idx
+                        .verify_integrity(
+                            Some(index::verify::PackContext {
+                                data: &pack,
+                                options: index::verify::integrity::Options {
+                                    verify_mode: index::verify::Mode::HashCrc32Decode,
+                                    traversal: algo,
+                                    make_pack_lookup_cache: || cache::Never,
+                                    thread_limit: Some(1),
+                                },
+                            }),
+                            progress::Discard,
+                            &AtomicBool::new(false),
+                        )?
+                        .pack_traverse_statistics
+                        .expect("pack was provided");
+                    let expected = expected.get_or_insert_with(|| stats.clone());
+                    assert_eq!(&stats, expected, "{:?} with {:?} in {}", mapping, algo, pack_path);
+                }
+            }
+        }
+        Ok(())
+    }
+}
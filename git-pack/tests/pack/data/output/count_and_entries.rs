@@ -1,4 +1,7 @@
-use std::{convert::Infallible, sync::atomic::AtomicBool};
+use std::{
+    convert::Infallible,
+    sync::{atomic::AtomicBool, Arc},
+};
 
 use git_features::{
     parallel::{reduce::Finalize, InOrderIter},
@@ -122,6 +125,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 16,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("b920bbb055e1efb9080592a409d3975738b6efb3"),
                 None,
@@ -155,6 +159,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 103,
                     ref_delta_objects: 74,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("25114bd8820b393c402cd53ad8ec7f6a84bb0633"),
                 Some(hex_to_id("29ab9797aff1ca826afb699680356695d19c5acb")),
@@ -188,6 +193,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 29,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("d83d42128e40957c5174920189a0390b5a70f446"),
                 None,
@@ -209,6 +215,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -230,6 +237,7 @@ fn traversals() -> crate::Result {
                     missing_objects: 0,
                     objects_copied_from_pack: 868,
                     ref_delta_objects: 0,
+                    delta_compressed_objects: 0,
                 },
                 hex_to_id("542ad1d1c7c762ea4e36907570ff9e4b5b7dde1b"),
                 None,
@@ -314,17 +322,133 @@ fn traversals() -> crate::Result {
                 "two different ways of counting, still the same in the end"
             );
 
-            write_and_verify(db.clone(), entries, expected_pack_hash, expected_thin_pack_hash)?;
+            write_and_verify(db.clone(), entries, Some(expected_pack_hash), expected_thin_pack_hash)?;
         }
     }
 
     Ok(())
 }
 
+#[test]
+fn delta_compression() -> crate::Result {
+    let db = db(DbKind::DeterministicGeneratedContentWithoutDeltas)?;
+    let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+    let (counts, _stats) = output::count::objects(
+        db.clone(),
+        std::iter::once(Ok::<_, Infallible>(head)),
+        progress::Discard,
+        &AtomicBool::new(false),
+        count::objects::Options {
+            input_object_expansion: count::objects::ObjectExpansion::TreeContents,
+            thread_limit: Some(1),
+            ..Default::default()
+        },
+    )?;
+    let num_counts = counts.len();
+
+    let mut entries_iter = output::entry::iter_from_counts(
+        counts,
+        db.clone(),
+        progress::Discard,
+        output::entry::iter_from_counts::Options {
+            mode: output::entry::iter_from_counts::Mode::PackCopyAndDeltas {
+                window: 10,
+                max_depth: 50,
+            },
+            chunk_size: 100,
+            ..Default::default()
+        },
+    );
+    let entries: Vec<_> = InOrderIter::from(entries_iter.by_ref())
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+    assert_eq!(entries.len(), num_counts);
+    let stats = entries_iter.finalize()?;
+    assert_eq!(stats.missing_objects, 0);
+    assert!(
+        stats.delta_compressed_objects > 0,
+        "some decoded objects are similar enough"
+    );
+    assert_eq!(
+        stats.objects_copied_from_pack + stats.decoded_and_recompressed_objects + stats.delta_compressed_objects,
+        num_counts
+    );
+    assert!(entries.iter().all(|e| match e.kind {
+        output::entry::Kind::DeltaRef { object_index } => entries[object_index].id != e.id,
+        _ => true,
+    }));
+
+    write_and_verify(db, entries, None, None)
+}
+
+#[test]
+fn delta_compression_with_islands() -> crate::Result {
+    let db = db(DbKind::DeterministicGeneratedContentWithoutDeltas)?;
+    let head = hex_to_id("dfcb5e39ac6eb30179808bbab721e8a28ce1b52e");
+    let (counts, _stats) = output::count::objects(
+        db.clone(),
+        std::iter::once(Ok::<_, Infallible>(head)),
+        progress::Discard,
+        &AtomicBool::new(false),
+        count::objects::Options {
+            input_object_expansion: count::objects::ObjectExpansion::TreeContents,
+            thread_limit: Some(1),
+            ..Default::default()
+        },
+    )?;
+    let num_counts = counts.len();
+    let entries_with_islands = |islands: Option<Arc<output::island::Islands>>| -> crate::Result<_> {
+        let mut entries_iter = output::entry::iter_from_counts_with_islands(
+            counts.clone(),
+            db.clone(),
+            islands,
+            progress::Discard,
+            output::entry::iter_from_counts::Options {
+                mode: output::entry::iter_from_counts::Mode::PackCopyAndDeltas {
+                    window: 10,
+                    max_depth: 50,
+                },
+                chunk_size: 100,
+                ..Default::default()
+            },
+        );
+        let entries: Vec<_> = InOrderIter::from(entries_iter.by_ref())
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok((entries, entries_iter.finalize()?))
+    };
+
+    // Each object is its own island, so deltas can only be made against objects reachable from it.
+    let islands = Arc::new(output::island::Islands::from_tips(
+        counts.iter().map(|count| std::iter::once(count.id)),
+        db.clone(),
+        progress::Discard,
+        &AtomicBool::new(false),
+    )?);
+    let (entries, stats) = entries_with_islands(Some(islands.clone()))?;
+    let (_, unconstrained_stats) = entries_with_islands(None)?;
+    assert_eq!(entries.len(), num_counts);
+    assert!(
+        stats.delta_compressed_objects < unconstrained_stats.delta_compressed_objects,
+        "islands prevent most deltas"
+    );
+    assert!(entries.iter().all(|e| match e.kind {
+        output::entry::Kind::DeltaRef { object_index } => islands.may_delta(&e.id, &entries[object_index].id),
+        output::entry::Kind::DeltaOid { .. } => false,
+        output::entry::Kind::Base(_) => true,
+    }));
+
+    write_and_verify(db, entries, None, None)
+}
+
 fn write_and_verify(
     db: git_odb::HandleArc,
     entries: Vec<output::Entry>,
-    expected_pack_hash: git_hash::ObjectId,
+    expected_pack_hash: Option<git_hash::ObjectId>,
     expected_thin_pack_hash: Option<git_hash::ObjectId>,
 ) -> crate::Result {
     let tmp_dir = tempfile::TempDir::new()?;
@@ -367,17 +491,22 @@ fn write_and_verify(
         "the trailer of the pack matches the actually written trailer"
     );
 
-    assert_eq!(hash, expected_pack_hash, "pack hashes are stable if the input is");
+    if let Some(expected_pack_hash) = expected_pack_hash {
+        assert_eq!(hash, expected_pack_hash, "pack hashes are stable if the input is");
+    }
 
     // Re-generate the index from the pack for validation.
     let object_hash = git_hash::Kind::Sha1; // TODO: parameterize this
     let bundle = pack::Bundle::at(
         pack::Bundle::write_to_directory(
-            std::io::BufReader::new(std::fs::File::open(pack_file_path)?),
+            std::io::BufReader::new(std::fs::File::open(&pack_file_path)?),
             Some(tmp_dir.path()),
             progress::Discard,
             &should_interrupt,
-            Some(Box::new(move |oid, buf| db.find(oid, buf).ok().map(|t| t.0))),
+            Some(Box::new({
+                let db = db.clone();
+                move |oid, buf| db.find(oid, buf).ok().map(|t| t.0)
+            })),
             pack::bundle::write::Options::default(),
         )?
         .data_path
@@ -393,6 +522,23 @@ fn write_and_verify(
             actual_checksum, thin_pack_checksum,
             "the thin pack is written reproducibly and checksums pan out"
         );
+
+        static SHOULD_INTERRUPT: AtomicBool = AtomicBool::new(false);
+        let eager_dir = tempfile::TempDir::new()?;
+        let db = db.clone();
+        let outcome = pack::Bundle::write_to_directory_eagerly(
+            std::fs::File::open(&pack_file_path)?,
+            None,
+            Some(eager_dir.path()),
+            progress::Discard,
+            &SHOULD_INTERRUPT,
+            Some(Box::new(move |oid, buf| db.find(oid, buf).ok().map(|t| t.0))),
+            pack::bundle::write::Options::default(),
+        )?;
+        assert_eq!(
+            outcome.index.data_hash, thin_pack_checksum,
+            "thin packs are completed the same way when reading eagerly"
+        );
     }
 
     bundle.verify_integrity(
@@ -247,6 +247,46 @@ fn traverse_with_index_and_forward_ref_deltas() {
     assert_eq!(count.load(Ordering::SeqCst), 9, "we traverse all objects");
 }
 
+#[test]
+fn traverse_with_lookup_reports_corrupt_objects_if_decode_errors_are_not_fatal() -> crate::Result {
+    let dir = tempfile::tempdir()?;
+    let index_path = dir.path().join("pack.idx");
+    std::fs::copy(fixture_path(SMALL_PACK_INDEX), &index_path)?;
+    let mut pack_data = std::fs::read(fixture_path(SMALL_PACK))?;
+    let (corrupt_offset, zlib_header_offset) = (3617, 3619);
+    assert_eq!(pack_data[zlib_header_offset], 0x78, "the start of a zlib stream");
+    pack_data[zlib_header_offset] = 0;
+    std::fs::write(index_path.with_extension("pack"), pack_data)?;
+
+    let index = index::File::at(&index_path, git_hash::Kind::Sha1)?;
+    let data = pack::data::File::at(index.path().with_extension("pack"), git_hash::Kind::Sha1)?;
+    let outcome = index.traverse(
+        &data,
+        progress::Discard,
+        &AtomicBool::new(false),
+        || |_, _, _, _| Ok::<_, std::io::Error>(()),
+        index::traverse::Options {
+            traversal: index::traverse::Algorithm::Lookup,
+            check: index::traverse::SafetyCheck::SkipFileAndObjectChecksumVerificationAndNoAbortOnDecodeError,
+            thread_limit: None,
+            make_pack_lookup_cache: cache::Never::default,
+        },
+    )?;
+    let corrupt_objects = outcome.statistics.corrupt_objects;
+    assert_eq!(corrupt_objects.len(), 1, "it's not a base for any other object");
+    assert_eq!(corrupt_objects[0].pack_offset, corrupt_offset);
+    assert_eq!(
+        corrupt_objects[0].id,
+        hex_to_id("e90926b07092bccb7bf7da445fae6ffdfacf3eae")
+    );
+    assert_eq!(
+        outcome.statistics.objects_per_chain_length.values().sum::<u32>() + 1,
+        index.num_objects(),
+        "all other objects are still traversed"
+    );
+    Ok(())
+}
+
 use common_macros::b_tree_map;
 use git_features::progress;
 use git_pack::{cache, data::decode_entry::Outcome, index};
@@ -295,6 +335,14 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
                 num_tags: 0,
                 num_trees: 15,
                 pack_size: 51875,
+                num_ofs_deltas: 12,
+                num_ref_deltas: 0,
+                bases_per_delta_count: b_tree_map! {
+                    1 => 5,
+                    2 => 2,
+                    3 => 1,
+                },
+                corrupt_objects: Vec::new(),
             },
         ),
         (
@@ -320,6 +368,12 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
                 num_tags: 0,
                 num_trees: 2,
                 pack_size: 49113,
+                num_ofs_deltas: 0,
+                num_ref_deltas: 3,
+                bases_per_delta_count: b_tree_map! {
+                    1 => 3,
+                },
+                corrupt_objects: Vec::new(),
             },
         ),
         (
@@ -346,6 +400,14 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
                 num_tags: 0,
                 num_trees: 14,
                 pack_size: 3732,
+                num_ofs_deltas: 12,
+                num_ref_deltas: 0,
+                bases_per_delta_count: b_tree_map! {
+                    2 => 1,
+                    4 => 1,
+                    6 => 1,
+                },
+                corrupt_objects: Vec::new(),
             },
         ),
     ] {
@@ -386,7 +448,7 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
         let sorted_offsets = idx.sorted_offsets();
         assert_eq!(num_objects, sorted_offsets.len());
         for idx_entry in idx.iter() {
-            let pack_entry = pack.entry(idx_entry.pack_offset);
+            let pack_entry = pack.entry(idx_entry.pack_offset)?;
             assert_ne!(pack_entry.data_offset, idx_entry.pack_offset);
             assert!(sorted_offsets.binary_search(&idx_entry.pack_offset).is_ok());
         }
@@ -399,7 +461,7 @@ fn pack_lookup() -> Result<(), Box<dyn std::error::Error>> {
 
             let mut buf = Vec::new();
             buf.resize(entry.decompressed_size as usize, 0);
-            let pack_entry = pack.entry(offset_from_index);
+            let pack_entry = pack.entry(offset_from_index)?;
             assert_eq!(
                 pack_entry.pack_offset(),
                 entry.pack_offset,
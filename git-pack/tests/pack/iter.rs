@@ -4,7 +4,7 @@ use git_odb::pack;
 fn size_of_entry() {
     assert_eq!(
         std::mem::size_of::<pack::data::input::Entry>(),
-        104,
+        168,
         "let's keep the size in check as we have many of them"
     );
 }
@@ -19,7 +19,7 @@ mod new_from_header {
 
     use crate::{
         fixture_path,
-        pack::{SMALL_PACK, V2_PACKS_AND_INDICES},
+        pack::{SMALL_PACK, SMALL_PACK_INDEX, V2_PACKS_AND_INDICES},
     };
 
     #[test]
@@ -102,6 +102,32 @@ mod new_from_header {
         Ok(())
     }
 
+    #[test]
+    fn object_ids_of_base_objects() -> Result<(), Box<dyn std::error::Error>> {
+        let index = pack::index::File::at(fixture_path(SMALL_PACK_INDEX), git_hash::Kind::Sha1)?;
+        let iter = pack::data::input::BytesToEntriesIter::new_from_header(
+            std::io::BufReader::new(fs::File::open(fixture_path(SMALL_PACK))?),
+            Mode::Verify,
+            EntryDataMode::Ignore,
+            git_hash::Kind::Sha1,
+        )?
+        .with_object_ids(true);
+        let mut num_base_objects = 0;
+        for entry in iter {
+            let entry = entry?;
+            match entry.id {
+                Some(id) => {
+                    let index_entry = index.lookup(id).expect("computed ids are correct");
+                    assert_eq!(index.pack_offset_at_index(index_entry), entry.pack_offset);
+                    num_base_objects += 1;
+                }
+                None => assert!(entry.header.is_delta(), "only deltas have no id"),
+            }
+        }
+        assert!(num_base_objects > 0);
+        Ok(())
+    }
+
     #[test]
     fn restore_missing_trailer() -> Result<(), Box<dyn std::error::Error>> {
         let pack = fs::read(fixture_path(SMALL_PACK))?;
@@ -75,3 +75,20 @@ fn general() {
     }
     assert_eq!(count, file.num_objects());
 }
+
+#[test]
+fn base_files_are_unsupported() -> crate::Result {
+    let (_file, path) = multi_index();
+    let mut data = std::fs::read(path)?;
+    const BASE_FILES_OFFSET: usize = 7;
+    data[BASE_FILES_OFFSET] = 1;
+    let dir = tempfile::TempDir::new()?;
+    let path = dir.path().join("multi-pack-index");
+    std::fs::write(&path, data)?;
+
+    assert!(matches!(
+        git_pack::multi_index::File::at(path),
+        Err(git_pack::multi_index::init::Error::UnsupportedBaseFiles { count: 1 })
+    ));
+    Ok(())
+}
@@ -46,7 +46,22 @@ fn integrity() {
             num_commits: 16,
             num_trees: 40,
             num_tags: 1,
-            num_blobs: 811
+            num_blobs: 811,
+            num_ofs_deltas: 542,
+            num_ref_deltas: 0,
+            bases_per_delta_count: b_tree_map! {
+                1 => 15,
+                2 => 8,
+                3 => 6,
+                4 => 6,
+                5 => 45,
+                6 => 1,
+                12 => 1,
+                56 => 1,
+                78 => 1,
+                92 => 1,
+            },
+            corrupt_objects: Vec::new(),
         }]
     );
 }
@@ -0,0 +1,93 @@
+//! Ask the user for input like usernames, passwords and passphrases the way git does, using an askpass program if one is
+//! configured or the terminal otherwise.
+//!
+//! Use [`openly()`] for input that may be shown while it is typed, and [`securely()`] for secrets.
+#![deny(unsafe_code, missing_docs, rust_2018_idioms)]
+
+use std::{borrow::Cow, path::Path};
+
+mod types;
+pub use types::{Error, Mode, Options};
+
+mod terminal;
+
+/// Ask for input with `prompt` using `opts`, which determine if it is shown while it is typed and which askpass program
+/// to use, if any.
+///
+/// Like in git, the askpass program is tried first, and the terminal is used if it isn't set or if it fails.
+pub fn ask(prompt: &str, opts: &Options<'_>) -> Result<String, Error> {
+    if let Some(askpass) = opts.askpass.as_deref() {
+        if let Some(answer) = ask_program(askpass, prompt) {
+            return Ok(answer);
+        }
+    }
+    match opts.mode {
+        Mode::Disable => Err(Error::Disabled),
+        Mode::Visible | Mode::Hidden => terminal::ask(prompt, opts.mode),
+    }
+}
+
+/// Ask for input with `prompt` which is shown while it's typed, like a username, with options obtained from the environment.
+pub fn openly(prompt: &str) -> Result<String, Error> {
+    ask(
+        prompt,
+        &Options {
+            mode: Mode::Visible,
+            ..Default::default()
+        }
+        .apply_environment(None),
+    )
+}
+
+/// Ask for input with `prompt` which isn't shown while it's typed, like a password, with options obtained from the
+/// environment.
+pub fn securely(prompt: &str) -> Result<String, Error> {
+    ask(
+        prompt,
+        &Options {
+            mode: Mode::Hidden,
+            ..Default::default()
+        }
+        .apply_environment(None),
+    )
+}
+
+/// Run `program` with `prompt` as its only argument and return the first line of its output, or `None` if it failed.
+fn ask_program(program: &Path, prompt: &str) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .arg(prompt)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.lines().next().unwrap_or_default().to_owned())
+}
+
+impl Options<'_> {
+    /// Set the askpass program from the environment unless it was set already, and disable prompting on the terminal
+    /// if `GIT_TERMINAL_PROMPT` is false.
+    ///
+    /// The program is taken from `GIT_ASKPASS`, `core_askpass` which is the value of `core.askPass`, or `SSH_ASKPASS`,
+    /// in that order.
+    pub fn apply_environment(mut self, core_askpass: Option<&Path>) -> Self {
+        if self.askpass.is_none() {
+            self.askpass = std::env::var_os("GIT_ASKPASS")
+                .map(|program| Cow::<Path>::Owned(program.into()))
+                .or_else(|| core_askpass.map(|program| Cow::Owned(program.to_owned())))
+                .or_else(|| std::env::var_os("SSH_ASKPASS").map(|program| Cow::Owned(program.into())))
+                .filter(|program| !program.as_os_str().is_empty());
+        }
+        if std::env::var("GIT_TERMINAL_PROMPT").map_or(false, |value| is_false(&value)) {
+            self.mode = Mode::Disable;
+        }
+        self
+    }
+}
+
+fn is_false(value: &str) -> bool {
+    matches!(value.to_ascii_lowercase().as_str(), "" | "0" | "no" | "off" | "false")
+}
@@ -0,0 +1,73 @@
+use crate::{Error, Mode};
+
+#[cfg(unix)]
+pub(crate) fn ask(prompt: &str, mode: Mode) -> Result<String, Error> {
+    use std::io::{BufRead, Write};
+
+    let tty = std::fs::OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+    let mut out = &tty;
+    out.write_all(prompt.as_bytes())?;
+    out.flush()?;
+
+    let mut answer = String::new();
+    if mode == Mode::Hidden {
+        let _restore_on_drop = unix::EchoDisabled::new(&tty).map_err(Error::TtyConfiguration)?;
+        std::io::BufReader::new(&tty).read_line(&mut answer)?;
+        // The newline typed by the user wasn't echoed.
+        out.write_all(b"\n")?;
+    } else {
+        std::io::BufReader::new(&tty).read_line(&mut answer)?;
+    }
+    Ok(answer.trim_end_matches(&['\r', '\n'][..]).to_owned())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn ask(_prompt: &str, _mode: Mode) -> Result<String, Error> {
+    Err(Error::UnsupportedPlatform)
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::{fs::File, io, os::unix::io::AsRawFd};
+
+    /// Disables echoing of the input on the terminal until it is dropped.
+    pub(crate) struct EchoDisabled<'a> {
+        tty: &'a File,
+        original: libc::termios,
+    }
+
+    impl<'a> EchoDisabled<'a> {
+        pub(crate) fn new(tty: &'a File) -> io::Result<Self> {
+            let original = get(tty)?;
+            let mut hidden = original;
+            hidden.c_lflag &= !libc::ECHO;
+            set(tty, &hidden)?;
+            Ok(EchoDisabled { tty, original })
+        }
+    }
+
+    impl Drop for EchoDisabled<'_> {
+        fn drop(&mut self) {
+            set(self.tty, &self.original).ok();
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn get(tty: &File) -> io::Result<libc::termios> {
+        let mut termios = std::mem::MaybeUninit::<libc::termios>::uninit();
+        // SAFETY: the file descriptor is valid while `tty` is alive, and `termios` is initialized if the call succeeds.
+        if unsafe { libc::tcgetattr(tty.as_raw_fd(), termios.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(unsafe { termios.assume_init() })
+    }
+
+    #[allow(unsafe_code)]
+    fn set(tty: &File, termios: &libc::termios) -> io::Result<()> {
+        // SAFETY: the file descriptor is valid while `tty` is alive, and `termios` is a valid configuration.
+        if unsafe { libc::tcsetattr(tty.as_raw_fd(), libc::TCSAFLUSH, termios) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::{borrow::Cow, path::Path};
+
+use quick_error::quick_error;
+
+quick_error! {
+    /// The error returned by [`ask()`][crate::ask()].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        Disabled {
+            display("Terminal prompts are disabled")
+        }
+        UnsupportedPlatform {
+            display("Prompting on the terminal is not supported on this platform")
+        }
+        TtyIo(err: std::io::Error) {
+            display("Failed to open the terminal or to read from or write to it")
+            from()
+            source(err)
+        }
+        TtyConfiguration(err: std::io::Error) {
+            display("Failed to obtain or change the terminal configuration")
+            source(err)
+        }
+    }
+}
+
+/// How to prompt on the terminal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Don't prompt on the terminal at all, and fail if the askpass program can't provide an answer.
+    Disable,
+    /// Show the input while it's typed.
+    Visible,
+    /// Don't show the input while it's typed, as it's secret like a password.
+    Hidden,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Hidden
+    }
+}
+
+/// The options used in [`ask()`][crate::ask()].
+#[derive(Debug, Default, Clone)]
+pub struct Options<'a> {
+    /// The program to run with the prompt as argument which prints the answer to stdout, tried before prompting on the
+    /// terminal. Typically set using [`apply_environment()`][Options::apply_environment()].
+    pub askpass: Option<Cow<'a, Path>>,
+    /// How to prompt on the terminal if there is no askpass program, or if it failed.
+    pub mode: Mode,
+}
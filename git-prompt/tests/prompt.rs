@@ -0,0 +1,35 @@
+use std::{borrow::Cow, path::Path};
+
+use git_prompt::{Error, Mode, Options};
+
+fn options(askpass: &str, mode: Mode) -> Options<'_> {
+    Options {
+        askpass: Some(Cow::Borrowed(Path::new(askpass))),
+        mode,
+    }
+}
+
+#[test]
+#[cfg(unix)]
+fn askpass_programs_receive_the_prompt_and_provide_the_first_line_of_their_output() {
+    assert_eq!(
+        git_prompt::ask("Username for 'https://example.com': ", &options("echo", Mode::Disable)).unwrap(),
+        "Username for 'https://example.com': "
+    );
+}
+
+#[test]
+fn failing_askpass_programs_fall_back_to_the_terminal_which_may_be_disabled() {
+    for askpass in ["false", "/this/program/does/not/exist"] {
+        assert!(matches!(
+            git_prompt::ask("Password: ", &options(askpass, Mode::Disable)),
+            Err(Error::Disabled)
+        ));
+    }
+}
+
+#[test]
+fn an_explicitly_set_askpass_program_is_kept() {
+    let opts = options("my-askpass", Mode::Visible).apply_environment(Some(Path::new("core-askpass")));
+    assert_eq!(opts.askpass.as_deref(), Some(Path::new("my-askpass")));
+}
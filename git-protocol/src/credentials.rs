@@ -25,6 +25,16 @@ quick_error! {
         CredentialsHelperFailed(code: Option<i32>) {
             display("Credentials helper program failed with status code {:?}", code)
         }
+        Cascade(err: git_credentials::helper::Error) {
+            display("The configured credential helpers could not provide credentials")
+            from()
+            source(err)
+        }
+        Message(err: git_credentials::protocol::Error) {
+            display("Could not encode or decode the credential message")
+            from()
+            source(err)
+        }
     }
 }
 
@@ -139,6 +149,49 @@ pub fn helper(action: Action<'_>) -> Result {
     }
 }
 
+/// Return a function to use in place of [`helper()`] which consults the credential `helpers` configured for the remote
+/// directly, and asks the user through `prompt(question, is_secret)` for whatever they can't provide.
+///
+/// Unlike [`helper()`], this doesn't require a `git` installation.
+pub fn cascade(
+    helpers: git_credentials::helper::Cascade,
+    mut prompt: impl FnMut(&str, bool) -> io::Result<String>,
+) -> impl FnMut(Action<'_>) -> Result {
+    move |action| match action {
+        Action::Fill(url) => {
+            let ctx = match git_url::parse(url.as_bytes()) {
+                Ok(url) => git_credentials::protocol::Context::from_url(&url),
+                Err(_) => git_credentials::protocol::Context {
+                    url: Some(url.into()),
+                    ..Default::default()
+                },
+            };
+            let ctx = helpers.fill(ctx, &mut prompt)?;
+            let previous_output = ctx.to_bstring()?;
+            Ok(Some(Outcome {
+                identity: client::Identity::Account {
+                    username: ctx.username.unwrap_or_default(),
+                    password: ctx.password.unwrap_or_default(),
+                },
+                next: NextAction {
+                    previous_output: previous_output.into(),
+                },
+            }))
+        }
+        Action::Approve(previous) | Action::Reject(previous) if previous.is_empty() => Ok(None),
+        Action::Approve(previous) => {
+            let ctx = git_credentials::protocol::Context::from_bytes(&previous)?;
+            helpers.approve(&ctx)?;
+            Ok(None)
+        }
+        Action::Reject(previous) => {
+            let ctx = git_credentials::protocol::Context::from_bytes(&previous)?;
+            helpers.reject(&ctx)?;
+            Ok(None)
+        }
+    }
+}
+
 /// Encode `url` to `out` for consumption by a `git credentials` helper program.
 pub fn encode_message(url: &str, mut out: impl io::Write) -> io::Result<()> {
     validate(url)?;
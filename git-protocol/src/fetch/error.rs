@@ -44,5 +44,8 @@ quick_error! {
             from()
             source(err)
         }
+        Interrupted {
+            display("The operation was interrupted")
+        }
     }
 }
@@ -1,4 +1,7 @@
-use std::io;
+use std::{
+    io,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use git_features::{progress, progress::Progress};
 use git_transport::{
@@ -47,6 +50,7 @@ impl Default for FetchConnection {
 /// * `authenticate(operation_to_perform)` is used to receive credentials for the connection and potentially store it
 ///   if the server indicates 'permission denied'. Note that not all transport support authentication or authorization.
 /// * `progress` is used to emit progress messages.
+/// * `should_interrupt` is checked before each negotiation round, which stops with an error if it is set.
 ///
 /// _Note_ that depending on the `delegate`, the actual action performed can be `ls-refs`, `clone` or `fetch`.
 #[maybe_async]
@@ -55,6 +59,7 @@ pub async fn fetch<F, D, T>(
     mut delegate: D,
     mut authenticate: F,
     mut progress: impl Progress,
+    should_interrupt: &AtomicBool,
     fetch_mode: FetchConnection,
 ) -> Result<(), Error>
 where
@@ -109,7 +114,9 @@ where
             Err(err) => Err(err),
         }?;
 
+        git_features::trace_info!(protocol = ?actual_protocol, "handshake complete");
         if !supported_versions.is_empty() && !supported_versions.contains(&actual_protocol) {
+            git_features::trace_error!(protocol = ?actual_protocol, "server protocol version is not supported");
             return Err(Error::TransportProtocolPolicyViolation {
                 actual_version: actual_protocol,
             });
@@ -204,8 +211,13 @@ where
     let mut previous_response = None::<Response>;
     let mut round = 1;
     'negotiation: loop {
+        if should_interrupt.load(Ordering::Relaxed) {
+            indicate_end_of_interaction(transport).await?;
+            return Err(Error::Interrupted);
+        }
         progress.step();
         progress.set_name(format!("negotiate (round {})", round));
+        git_features::trace_info!(round, "negotiation round");
         round += 1;
         let action = delegate.negotiate(&parsed_refs, &mut arguments, previous_response.as_ref())?;
         let mut reader = arguments.send(&mut transport, action == Action::Cancel).await?;
@@ -216,6 +228,7 @@ where
         previous_response = if response.has_pack() {
             progress.step();
             progress.set_name("receiving pack");
+            git_features::trace_info!("receiving pack");
             if !sideband_all {
                 setup_remote_progress(&mut progress, &mut reader);
             }
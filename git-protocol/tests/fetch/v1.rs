@@ -1,3 +1,5 @@
+use std::sync::atomic::AtomicBool;
+
 use bstr::ByteSlice;
 use git_features::progress;
 use git_protocol::{fetch, FetchConnection};
@@ -19,6 +21,7 @@ async fn clone() -> crate::Result {
         &mut dlg,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::TerminateOnSuccessfulCompletion,
     )
     .await?;
@@ -41,6 +44,7 @@ async fn ls_remote() -> crate::Result {
         &mut delegate,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::AllowReuse,
     )
     .await?;
@@ -82,6 +86,7 @@ async fn ls_remote_handshake_failure_due_to_downgrade() -> crate::Result {
         delegate,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::AllowReuse,
     )
     .await
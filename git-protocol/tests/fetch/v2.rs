@@ -1,3 +1,5 @@
+use std::sync::atomic::AtomicBool;
+
 use bstr::ByteSlice;
 use git_features::progress;
 use git_protocol::{fetch, FetchConnection};
@@ -23,6 +25,7 @@ async fn clone_abort_prep() -> crate::Result {
         &mut dlg,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::TerminateOnSuccessfulCompletion,
     )
     .await
@@ -52,6 +55,30 @@ async fn clone_abort_prep() -> crate::Result {
     Ok(())
 }
 
+#[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
+async fn clone_interrupted_before_negotiation() -> crate::Result {
+    let mut dlg = CloneDelegate::default();
+    let mut transport = transport(
+        Vec::new(),
+        "v2/clone.response",
+        Protocol::V2,
+        git_transport::client::git::ConnectMode::Daemon,
+    );
+    let err = git_protocol::fetch(
+        &mut transport,
+        &mut dlg,
+        git_protocol::credentials::helper,
+        progress::Discard,
+        &AtomicBool::new(true),
+        FetchConnection::TerminateOnSuccessfulCompletion,
+    )
+    .await
+    .expect_err("fetch interrupted");
+    assert!(matches!(err, fetch::Error::Interrupted));
+    assert_eq!(dlg.pack_bytes, 0, "we stopped before receiving a pack");
+    Ok(())
+}
+
 #[maybe_async::test(feature = "blocking-client", async(feature = "async-client", async_std::test))]
 async fn ls_remote() -> crate::Result {
     let out = Vec::new();
@@ -67,6 +94,7 @@ async fn ls_remote() -> crate::Result {
         &mut delegate,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::AllowReuse,
     )
     .await?;
@@ -120,6 +148,7 @@ async fn ls_remote_abort_in_prep_ls_refs() -> crate::Result {
         &mut delegate,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::AllowReuse,
     )
     .await
@@ -159,6 +188,7 @@ async fn ref_in_want() -> crate::Result {
         &mut delegate,
         git_protocol::credentials::helper,
         progress::Discard,
+        &AtomicBool::default(),
         FetchConnection::TerminateOnSuccessfulCompletion,
     )
     .await?;
@@ -17,7 +17,7 @@ impl Reference {
 }
 
 ///
-pub(crate) mod reflog;
+pub mod reflog;
 
 ///
 pub(crate) mod iter;
@@ -25,6 +25,7 @@ impl<'s> Transaction<'s> {
     /// Note that transactions will be prepared automatically as needed.
     pub fn commit(self, committer: &git_actor::Signature) -> Result<Vec<RefEdit>, Error> {
         let mut updates = self.updates.expect("BUG: must call prepare before commit");
+        let _span = git_features::trace_span!("commit ref transaction", num_edits = updates.len());
         let delete_loose_refs = matches!(
             self.packed_refs,
             PackedRefs::DeletionsAndNonSymbolicUpdatesRemoveLooseSourceReference(_)
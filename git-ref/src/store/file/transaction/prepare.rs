@@ -178,6 +178,7 @@ impl<'s> Transaction<'s> {
         lock_fail_mode: git_lock::acquire::Fail,
     ) -> Result<Self, Error> {
         assert!(self.updates.is_none(), "BUG: Must not call prepare(…) multiple times");
+        let _span = git_features::trace_span!("prepare ref transaction");
         let store = self.store;
         let mut updates: Vec<_> = edits
             .into_iter()
@@ -0,0 +1,59 @@
+//! [Parse][parse()] refspecs as used by `git fetch` and `git push`, and [match][MatchGroup] references against them
+//! to learn where they should be stored.
+//!
+//! See `git help fetch` and `git help push` for details on the syntax.
+#![forbid(unsafe_code)]
+#![deny(rust_2018_idioms, missing_docs)]
+
+use bstr::{BStr, BString};
+
+///
+pub mod parse;
+pub use parse::parse;
+
+mod spec;
+
+///
+pub mod match_group;
+pub use match_group::MatchGroup;
+
+/// The direction of an operation carried out by a refspec, which changes how it is parsed and interpreted.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operation {
+    /// References are sent to the remote, with the source being a local reference.
+    Push,
+    /// References are obtained from the remote, with the source being a remote reference.
+    Fetch,
+}
+
+/// How a refspec is to be applied.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Update the destination only if the change is a fast-forward.
+    Normal,
+    /// Update the destination even if the change isn't a fast-forward, as indicated by a leading `+`.
+    Force,
+    /// Exclude all references matching the source from being affected by other refspecs, as indicated by a leading `^`.
+    Negative,
+}
+
+/// A refspec borrowing its source and destination from the data it was [parsed][parse()] from.
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug)]
+pub struct RefSpecRef<'a> {
+    mode: Mode,
+    op: Operation,
+    src: Option<&'a BStr>,
+    dst: Option<&'a BStr>,
+}
+
+/// An owned refspec, as obtained with [`RefSpecRef::to_owned()`].
+#[derive(PartialEq, Eq, Clone, Hash, Debug)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+pub struct RefSpec {
+    mode: Mode,
+    op: Operation,
+    src: Option<BString>,
+    dst: Option<BString>,
+}
@@ -0,0 +1,194 @@
+use std::borrow::Cow;
+
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{spec::EXPANSION_PREFIXES, Mode, Operation, RefSpecRef};
+
+/// A reference to match against a [group of refspecs][MatchGroup], which is advertised by the remote when fetching,
+/// or a local reference when pushing.
+#[derive(Debug, Copy, Clone)]
+pub struct Item<'a> {
+    /// The full name of the reference, like `refs/heads/main`.
+    pub full_ref_name: &'a BStr,
+    /// The object the reference points to.
+    pub target: &'a git_hash::oid,
+}
+
+/// The source of a [`Mapping`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source<'a> {
+    /// The full name of the [item][Item] that was matched.
+    FullName(&'a BStr),
+    /// An object id given directly in the refspec.
+    ObjectId(git_hash::ObjectId),
+}
+
+impl Source<'_> {
+    /// Return the full reference name if the source is one.
+    pub fn as_name(&self) -> Option<&BStr> {
+        match self {
+            Source::FullName(name) => Some(name),
+            Source::ObjectId(_) => None,
+        }
+    }
+}
+
+/// How an [item][Item] or an object id of a refspec maps to its destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mapping<'item, 'spec> {
+    /// The index into the items passed to [`MatchGroup::match_refs()`], or `None` if the source is an object id.
+    pub item_index: Option<usize>,
+    /// The name of the matched item, or the object id given in the refspec.
+    pub lhs: Source<'item>,
+    /// The name of the reference to store the source in, or `None` if it isn't stored.
+    pub rhs: Option<Cow<'spec, BStr>>,
+    /// The index of the refspec in the group that produced this mapping, to learn about its [mode][RefSpecRef::mode()].
+    pub spec_index: usize,
+}
+
+/// A group of refspecs which are applied together, where negative refspecs exclude references from being matched by all
+/// others.
+#[derive(Debug, Default, Clone)]
+pub struct MatchGroup<'a> {
+    specs: Vec<RefSpecRef<'a>>,
+}
+
+impl<'a> MatchGroup<'a> {
+    /// Create a new group from `specs`, which are all expected to be parsed for the same [operation][Operation].
+    pub fn from_specs(specs: impl IntoIterator<Item = RefSpecRef<'a>>) -> Self {
+        MatchGroup {
+            specs: specs.into_iter().collect(),
+        }
+    }
+
+    /// Return all refspecs of this group, for looking up the refspec of a [`Mapping`].
+    pub fn specs(&self) -> &[RefSpecRef<'a>] {
+        &self.specs
+    }
+
+    /// Match all `items` against the refspecs of this group in order and return how they map to their destinations.
+    ///
+    /// Patterns match all items with the same prefix and suffix, and substitute the part matched by `*` in the destination.
+    /// Other names match the item with the same full name, or the one that is found first when expanding the name like
+    /// git does, trying `refs/`, `refs/tags/`, `refs/heads/`, `refs/remotes/` and `refs/remotes/<name>/HEAD`.
+    /// Object ids are mapped without matching any item.
+    ///
+    /// When pushing, sources without destination are stored under the name of the matched item, and the `:` spec maps
+    /// all items to their own name. Deletions don't match any item.
+    ///
+    /// Items matched by any negative refspec are excluded from all mappings.
+    pub fn match_refs<'item>(&self, items: impl IntoIterator<Item = Item<'item>>) -> Vec<Mapping<'item, 'a>> {
+        let items: Vec<_> = items.into_iter().collect();
+        let mut out = Vec::new();
+        for (spec_index, spec) in self.specs.iter().enumerate() {
+            if spec.mode() == Mode::Negative {
+                continue;
+            }
+            if let Some(id) = spec.source_object_id() {
+                out.push(Mapping {
+                    item_index: None,
+                    lhs: Source::ObjectId(id),
+                    rhs: spec.destination().map(Cow::Borrowed),
+                    spec_index,
+                });
+                continue;
+            }
+            let is_push = spec.operation() == Operation::Push;
+            let same_name = |item: &Item<'item>| is_push.then(|| Cow::Owned(item.full_ref_name.to_owned()));
+            let mut push = |item_index: usize, rhs: Option<Cow<'a, BStr>>| {
+                out.push(Mapping {
+                    item_index: Some(item_index),
+                    lhs: Source::FullName(items[item_index].full_ref_name),
+                    rhs,
+                    spec_index,
+                })
+            };
+            match spec.source() {
+                None if spec.is_push_matching() => {
+                    for (item_index, item) in items.iter().enumerate() {
+                        push(item_index, same_name(item));
+                    }
+                }
+                None => {}
+                Some(src) if spec.is_pattern() => {
+                    for (item_index, item) in items.iter().enumerate() {
+                        if let Some(matched) = match_pattern(src, item.full_ref_name) {
+                            let rhs = match spec.destination() {
+                                Some(dst) => Some(Cow::Owned(dst.replace("*", matched).into())),
+                                None => same_name(item),
+                            };
+                            push(item_index, rhs);
+                        }
+                    }
+                }
+                Some(src) => {
+                    let best_match = items
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(item_index, item)| {
+                            expansion_rank(src, item.full_ref_name).map(|rank| (rank, item_index))
+                        })
+                        .min();
+                    if let Some((_rank, item_index)) = best_match {
+                        let rhs = match spec.destination() {
+                            Some(dst) => Some(Cow::Borrowed(dst)),
+                            None => same_name(&items[item_index]),
+                        };
+                        push(item_index, rhs);
+                    }
+                }
+            }
+        }
+
+        out.retain(|mapping| match mapping.lhs.as_name() {
+            Some(name) => !self.is_excluded(name),
+            None => true,
+        });
+        out
+    }
+
+    /// Return true if `full_ref_name` is matched by a negative refspec.
+    fn is_excluded(&self, full_ref_name: &BStr) -> bool {
+        self.specs
+            .iter()
+            .filter(|spec| spec.mode() == Mode::Negative)
+            .filter_map(|spec| spec.source())
+            .any(|src| {
+                if src.contains(&b'*') {
+                    match_pattern(src, full_ref_name).is_some()
+                } else {
+                    src == full_ref_name
+                }
+            })
+    }
+}
+
+/// Return the portion of `name` matched by the single `*` in `pattern`, if `name` matches.
+fn match_pattern<'a>(pattern: &BStr, name: &'a BStr) -> Option<&'a BStr> {
+    let pos = pattern.find_byte(b'*')?;
+    let (prefix, suffix) = (&pattern[..pos], &pattern[pos + 1..]);
+    (name.len() >= prefix.len() + suffix.len() && name.starts_with(prefix) && name.ends_with(suffix))
+        .then(|| name[prefix.len()..name.len() - suffix.len()].as_bstr())
+}
+
+/// Return the precedence with which the possibly abbreviated `name` expands to `full_ref_name`, with lower values being
+/// preferred, or `None` if it doesn't.
+fn expansion_rank(name: &BStr, full_ref_name: &BStr) -> Option<usize> {
+    if name == full_ref_name {
+        return Some(0);
+    }
+    let expanded = |prefix: &str| {
+        let mut expanded = BString::from(prefix);
+        expanded.extend_from_slice(name);
+        expanded
+    };
+    EXPANSION_PREFIXES
+        .iter()
+        .position(|prefix| expanded(prefix) == full_ref_name)
+        .map(|pos| pos + 1)
+        .or_else(|| {
+            let mut remote_head = expanded("refs/remotes/");
+            remote_head.extend_from_slice(b"/HEAD");
+            (remote_head == full_ref_name).then(|| EXPANSION_PREFIXES.len() + 1)
+        })
+}
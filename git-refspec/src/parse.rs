@@ -0,0 +1,145 @@
+use bstr::{BStr, ByteSlice};
+use quick_error::quick_error;
+
+use crate::{Mode, Operation, RefSpecRef};
+
+quick_error! {
+    /// The error returned by [`parse()`][crate::parse()].
+    #[derive(Debug)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        NegativeWithDestination {
+            display("Negative refspecs cannot have destinations as they exclude sources")
+        }
+        NegativeEmpty {
+            display("Negative refspecs must have a source")
+        }
+        NegativeObjectHash {
+            display("Negative refspecs cannot refer to objects by their hash")
+        }
+        PushToEmpty {
+            display("Pushing to an empty destination is not allowed")
+        }
+        PatternUnsupported { pattern: bstr::BString } {
+            display("The pattern '{}' has more than one '*', which is not supported", pattern)
+        }
+        PatternUnbalanced {
+            display("Both sides of a refspec must either have a '*' or none at all")
+        }
+        ReferenceName(err: git_validate::reference::name::Error) {
+            display("A reference name in the refspec is invalid")
+            from()
+            source(err)
+        }
+    }
+}
+
+/// Parse `spec` for use in `operation` and return it if it is valid.
+///
+/// Like in git, an empty source is `HEAD` when fetching and deletes the destination when pushing, while `@` is short for
+/// `HEAD`. When fetching, an empty destination means the source isn't stored, and `:` alone is a push of all matching
+/// references.
+pub fn parse(mut spec: &BStr, operation: Operation) -> Result<RefSpecRef<'_>, Error> {
+    let mode = match spec.first() {
+        Some(b'^') => {
+            spec = spec[1..].as_bstr();
+            Mode::Negative
+        }
+        Some(b'+') => {
+            spec = spec[1..].as_bstr();
+            Mode::Force
+        }
+        Some(_) | None => Mode::Normal,
+    };
+
+    let (src, dst) = match spec.rfind_byte(b':') {
+        Some(0) if spec.len() == 1 && operation == Operation::Push && mode != Mode::Negative => {
+            return Ok(RefSpecRef {
+                mode,
+                op: operation,
+                src: None,
+                dst: None,
+            })
+        }
+        Some(pos) => (spec[..pos].as_bstr(), Some(spec[pos + 1..].as_bstr())),
+        None => (spec, None),
+    };
+    let src = if src == "@" { "HEAD".into() } else { src };
+
+    let is_glob = match (src.contains(&b'*'), dst.map_or(false, |dst| dst.contains(&b'*'))) {
+        (true, false) if dst.is_some() || (mode != Mode::Negative && operation == Operation::Fetch) => {
+            return Err(Error::PatternUnbalanced)
+        }
+        (false, true) => return Err(Error::PatternUnbalanced),
+        (src_is_glob, _) => src_is_glob,
+    };
+
+    if mode == Mode::Negative {
+        if dst.is_some() {
+            return Err(Error::NegativeWithDestination);
+        }
+        if src.is_empty() {
+            return Err(Error::NegativeEmpty);
+        }
+        if looks_like_object_hash(src) {
+            return Err(Error::NegativeObjectHash);
+        }
+        validated(src, is_glob)?;
+    }
+
+    let (src, dst) = match operation {
+        Operation::Fetch => {
+            let src = if src.is_empty() {
+                "HEAD".into()
+            } else if looks_like_object_hash(src) {
+                src
+            } else {
+                validated(src, is_glob)?
+            };
+            let dst = match dst {
+                Some(dst) if !dst.is_empty() => Some(validated(dst, is_glob)?),
+                Some(_) | None => None,
+            };
+            (Some(src), dst)
+        }
+        Operation::Push => {
+            let src = match src.is_empty() {
+                true => None,
+                false if is_glob => Some(validated(src, is_glob)?),
+                false => Some(src),
+            };
+            let dst = match dst {
+                None => {
+                    validated(src.unwrap_or_else(|| "".into()), is_glob)?;
+                    None
+                }
+                Some(dst) if dst.is_empty() => return Err(Error::PushToEmpty),
+                Some(dst) => Some(validated(dst, is_glob)?),
+            };
+            (src, dst)
+        }
+    };
+    Ok(RefSpecRef {
+        mode,
+        op: operation,
+        src,
+        dst,
+    })
+}
+
+fn looks_like_object_hash(name: &BStr) -> bool {
+    git_hash::ObjectId::from_hex(name).is_ok()
+}
+
+/// Validate `name` as partial reference name, which may contain a single `*` if `is_glob` is true.
+fn validated(name: &BStr, is_glob: bool) -> Result<&BStr, Error> {
+    if is_glob {
+        if name.find_iter("*").count() > 1 {
+            return Err(Error::PatternUnsupported { pattern: name.into() });
+        }
+        git_validate::reference::name_partial(name.replace("*", "a").as_bstr())?;
+    } else {
+        git_validate::reference::name_partial(name)?;
+    }
+    Ok(name)
+}
@@ -0,0 +1,131 @@
+use bstr::{BStr, BString, ByteSlice};
+
+use crate::{Mode, Operation, RefSpec, RefSpecRef};
+
+/// The prefixes git uses to expand abbreviated reference names, in order of precedence, with the name itself being tried
+/// first and `refs/remotes/<name>/HEAD` last.
+pub(crate) const EXPANSION_PREFIXES: &[&str] = &["refs/", "refs/tags/", "refs/heads/", "refs/remotes/"];
+
+/// Access
+impl<'a> RefSpecRef<'a> {
+    /// Return how the spec is to be applied.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Return the operation the spec was parsed for.
+    pub fn operation(&self) -> Operation {
+        self.op
+    }
+
+    /// Return the source, which is a possibly abbreviated reference name, a pattern with a single `*`, or a hexadecimal
+    /// object id when fetching.
+    ///
+    /// It is `None` when pushing if the destination is to be deleted, or if all matching references are pushed.
+    pub fn source(&self) -> Option<&'a BStr> {
+        self.src
+    }
+
+    /// Return the destination, or `None` if the source isn't stored when fetching, or if it's stored under the same name
+    /// when pushing.
+    pub fn destination(&self) -> Option<&'a BStr> {
+        self.dst
+    }
+
+    /// Return true if source and destination are patterns with a single `*` each.
+    pub fn is_pattern(&self) -> bool {
+        self.src.map_or(false, |src| src.contains(&b'*'))
+    }
+
+    /// Return true if this is the `:` spec to push all references which exist locally and on the remote.
+    pub fn is_push_matching(&self) -> bool {
+        self.op == Operation::Push && self.src.is_none() && self.dst.is_none()
+    }
+
+    /// Return the source as object id if it is one, which is only possible when fetching.
+    pub fn source_object_id(&self) -> Option<git_hash::ObjectId> {
+        self.src
+            .filter(|_| self.op == Operation::Fetch)
+            .and_then(|src| git_hash::ObjectId::from_hex(src).ok())
+    }
+
+    /// Return the prefixes of all full reference names the source can match, for use in the `ref-prefix` arguments
+    /// of the `ls-refs` command of the V2 protocol, or an empty list if the source is an object id or unset.
+    pub fn prefixes(&self) -> Vec<BString> {
+        let src = match self.src {
+            Some(src) if self.source_object_id().is_none() => src,
+            _ => return Vec::new(),
+        };
+        match src.find_byte(b'*') {
+            Some(pos) => vec![src[..pos].into()],
+            None if src.starts_with(b"refs/") || src == "HEAD" => vec![src.into()],
+            None => EXPANSION_PREFIXES
+                .iter()
+                .map(|prefix| format!("{}{}", prefix, src).into())
+                .chain(Some(format!("refs/remotes/{}/HEAD", src).into()))
+                .collect(),
+        }
+    }
+
+    /// Serialize this spec into a string that [parses][crate::parse()] back into an equivalent spec.
+    pub fn to_bstring(&self) -> BString {
+        let mut out = BString::from(match self.mode {
+            Mode::Normal => "",
+            Mode::Force => "+",
+            Mode::Negative => "^",
+        });
+        if let Some(src) = self.src {
+            out.extend_from_slice(src);
+        }
+        match (self.op, self.dst) {
+            (_, Some(dst)) => {
+                out.push(b':');
+                out.extend_from_slice(dst);
+            }
+            (Operation::Push, None) if self.src.is_none() => out.push(b':'),
+            (_, None) => {}
+        }
+        out
+    }
+
+    /// Return an owned copy of this spec.
+    pub fn to_owned(&self) -> RefSpec {
+        RefSpec {
+            mode: self.mode,
+            op: self.op,
+            src: self.src.map(ToOwned::to_owned),
+            dst: self.dst.map(ToOwned::to_owned),
+        }
+    }
+}
+
+/// Access
+impl RefSpec {
+    /// Return a borrowed version of this spec for access to all of its information.
+    pub fn to_ref(&self) -> RefSpecRef<'_> {
+        RefSpecRef {
+            mode: self.mode,
+            op: self.op,
+            src: self.src.as_ref().map(|src| src.as_bstr()),
+            dst: self.dst.as_ref().map(|dst| dst.as_bstr()),
+        }
+    }
+}
+
+impl std::fmt::Display for RefSpecRef<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_bstring(), f)
+    }
+}
+
+impl std::fmt::Display for RefSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_ref().fmt(f)
+    }
+}
+
+impl From<RefSpecRef<'_>> for RefSpec {
+    fn from(v: RefSpecRef<'_>) -> Self {
+        v.to_owned()
+    }
+}
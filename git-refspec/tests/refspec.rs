@@ -0,0 +1,255 @@
+use git_refspec::{parse::Error, Mode, Operation, RefSpecRef};
+
+fn fetch(spec: &str) -> Result<RefSpecRef<'_>, Error> {
+    git_refspec::parse(spec.into(), Operation::Fetch)
+}
+
+fn push(spec: &str) -> Result<RefSpecRef<'_>, Error> {
+    git_refspec::parse(spec.into(), Operation::Push)
+}
+
+mod parse {
+    use super::*;
+
+    #[test]
+    fn typical_fetch_specs() {
+        let spec = fetch("+refs/heads/*:refs/remotes/origin/*").unwrap();
+        assert_eq!(spec.mode(), Mode::Force);
+        assert_eq!(spec.operation(), Operation::Fetch);
+        assert!(spec.is_pattern());
+        assert_eq!(spec.source().unwrap(), "refs/heads/*");
+        assert_eq!(spec.destination().unwrap(), "refs/remotes/origin/*");
+
+        let spec = fetch("main").unwrap();
+        assert_eq!(spec.mode(), Mode::Normal);
+        assert_eq!(spec.source().unwrap(), "main");
+        assert_eq!(spec.destination(), None, "not stored");
+    }
+
+    #[test]
+    fn empty_sources_and_at_are_head_when_fetching() {
+        for spec in ["", ":", "@", "@:"] {
+            let spec = fetch(spec).unwrap();
+            assert_eq!(spec.source().unwrap(), "HEAD");
+            assert_eq!(spec.destination(), None);
+        }
+        assert_eq!(fetch("@:refs/heads/x").unwrap().destination().unwrap(), "refs/heads/x");
+    }
+
+    #[test]
+    fn object_ids_are_valid_fetch_sources() {
+        let spec = fetch("0123456789012345678901234567890123456789:refs/heads/x").unwrap();
+        assert_eq!(
+            spec.source_object_id(),
+            Some(git_hash::ObjectId::from_hex(b"0123456789012345678901234567890123456789").unwrap())
+        );
+        assert!(spec.prefixes().is_empty(), "objects aren't advertised");
+        assert!(matches!(
+            fetch("^0123456789012345678901234567890123456789"),
+            Err(Error::NegativeObjectHash)
+        ));
+    }
+
+    #[test]
+    fn negative_specs() {
+        let spec = fetch("^refs/heads/wip/*").unwrap();
+        assert_eq!(spec.mode(), Mode::Negative);
+        assert!(spec.is_pattern(), "negative patterns don't need a destination");
+        assert!(matches!(
+            fetch("^refs/heads/a:refs/heads/b"),
+            Err(Error::NegativeWithDestination)
+        ));
+        assert!(matches!(fetch("^"), Err(Error::NegativeEmpty)));
+        assert!(push("^refs/heads/main").is_ok());
+    }
+
+    #[test]
+    fn patterns_must_be_balanced_and_single() {
+        assert!(matches!(fetch("refs/heads/*"), Err(Error::PatternUnbalanced)));
+        assert!(matches!(fetch("refs/heads/*:refs/x"), Err(Error::PatternUnbalanced)));
+        assert!(matches!(fetch("refs/heads/x:refs/*"), Err(Error::PatternUnbalanced)));
+        assert!(matches!(
+            fetch("refs/*/*:refs/remotes/*/*"),
+            Err(Error::PatternUnsupported { .. })
+        ));
+        assert!(
+            push("refs/heads/*").is_ok(),
+            "pushing patterns without destination is allowed"
+        );
+    }
+
+    #[test]
+    fn invalid_reference_names_are_rejected() {
+        assert!(matches!(fetch("refs/heads/a..b"), Err(Error::ReferenceName(_))));
+        assert!(matches!(fetch("main:refs/heads/a b"), Err(Error::ReferenceName(_))));
+        assert!(matches!(push("refs/heads/main:"), Err(Error::PushToEmpty)));
+        assert!(matches!(push(""), Err(Error::ReferenceName(_))));
+    }
+
+    #[test]
+    fn push_specs() {
+        let matching = push(":").unwrap();
+        assert!(matching.is_push_matching());
+        assert_eq!(push("+:").unwrap().mode(), Mode::Force);
+
+        let delete = push(":refs/heads/gone").unwrap();
+        assert_eq!(delete.source(), None);
+        assert_eq!(delete.destination().unwrap(), "refs/heads/gone");
+        assert!(!delete.is_push_matching());
+
+        let spec = push("HEAD~1:refs/heads/main").unwrap();
+        assert_eq!(
+            spec.source().unwrap(),
+            "HEAD~1",
+            "sources of push specs may be any revision"
+        );
+    }
+
+    #[test]
+    fn serialization_round_trips() {
+        for (spec, op) in [
+            ("+refs/heads/*:refs/remotes/origin/*", Operation::Fetch),
+            ("^refs/heads/wip", Operation::Fetch),
+            ("main", Operation::Fetch),
+            (":", Operation::Push),
+            (":refs/heads/gone", Operation::Push),
+            ("refs/heads/main:refs/heads/other", Operation::Push),
+        ] {
+            let parsed = git_refspec::parse(spec.into(), op).unwrap();
+            assert_eq!(parsed.to_bstring(), spec);
+            assert_eq!(parsed.to_owned().to_ref(), parsed);
+        }
+    }
+
+    #[test]
+    fn prefixes_for_ls_refs() {
+        assert_eq!(
+            fetch("refs/heads/*:refs/remotes/origin/*").unwrap().prefixes(),
+            vec!["refs/heads/"]
+        );
+        assert_eq!(fetch("refs/tags/v1").unwrap().prefixes(), vec!["refs/tags/v1"]);
+        assert_eq!(
+            fetch("main").unwrap().prefixes(),
+            vec![
+                "refs/main",
+                "refs/tags/main",
+                "refs/heads/main",
+                "refs/remotes/main",
+                "refs/remotes/main/HEAD"
+            ]
+        );
+    }
+}
+
+mod match_group {
+    use bstr::ByteSlice;
+    use git_refspec::{
+        match_group::{Item, Source},
+        MatchGroup,
+    };
+
+    use super::*;
+
+    fn id(hex: &str) -> git_hash::ObjectId {
+        git_hash::ObjectId::from_hex(hex.as_bytes()).unwrap()
+    }
+
+    const REFS: &[&str] = &[
+        "HEAD",
+        "refs/heads/main",
+        "refs/heads/wip/feature",
+        "refs/tags/main",
+        "refs/tags/v1.0",
+    ];
+
+    /// Return `(source, destination)` pairs for all mappings produced by `specs`.
+    fn mappings(specs: &[&str], op: Operation) -> Vec<(String, Option<String>)> {
+        let target = id("0123456789012345678901234567890123456789");
+        let specs: Vec<_> = specs
+            .iter()
+            .map(|spec| git_refspec::parse(spec.as_bytes().as_bstr(), op).unwrap())
+            .collect();
+        let group = MatchGroup::from_specs(specs);
+        group
+            .match_refs(REFS.iter().map(|name| Item {
+                full_ref_name: name.as_bytes().as_bstr(),
+                target: &target,
+            }))
+            .into_iter()
+            .map(|m| {
+                let lhs = match m.lhs {
+                    Source::FullName(name) => name.to_string(),
+                    Source::ObjectId(id) => id.to_string(),
+                };
+                (lhs, m.rhs.map(|rhs| rhs.to_string()))
+            })
+            .collect()
+    }
+
+    fn pair(lhs: &str, rhs: Option<&str>) -> (String, Option<String>) {
+        (lhs.into(), rhs.map(Into::into))
+    }
+
+    #[test]
+    fn patterns_substitute_the_matched_portion() {
+        assert_eq!(
+            mappings(&["+refs/heads/*:refs/remotes/origin/*"], Operation::Fetch),
+            vec![
+                pair("refs/heads/main", Some("refs/remotes/origin/main")),
+                pair("refs/heads/wip/feature", Some("refs/remotes/origin/wip/feature")),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_specs_exclude_matches_of_all_others() {
+        assert_eq!(
+            mappings(
+                &["refs/heads/*:refs/remotes/origin/*", "^refs/heads/wip/*"],
+                Operation::Fetch
+            ),
+            vec![pair("refs/heads/main", Some("refs/remotes/origin/main"))]
+        );
+    }
+
+    #[test]
+    fn abbreviated_names_prefer_tags_over_branches_like_git() {
+        assert_eq!(
+            mappings(&["main", "v1.0:refs/tags/v1.0"], Operation::Fetch),
+            vec![
+                pair("refs/tags/main", None),
+                pair("refs/tags/v1.0", Some("refs/tags/v1.0"))
+            ]
+        );
+        assert_eq!(mappings(&["HEAD"], Operation::Fetch), vec![pair("HEAD", None)]);
+        assert!(mappings(&["refs/heads/nonexisting"], Operation::Fetch).is_empty());
+    }
+
+    #[test]
+    fn object_ids_map_without_matching() {
+        assert_eq!(
+            mappings(
+                &["abcdefabcdefabcdefabcdefabcdefabcdefabcd:refs/heads/x"],
+                Operation::Fetch
+            ),
+            vec![pair("abcdefabcdefabcdefabcdefabcdefabcdefabcd", Some("refs/heads/x"))]
+        );
+    }
+
+    #[test]
+    fn push_sources_without_destination_keep_their_name() {
+        assert_eq!(
+            mappings(&["refs/heads/main", "refs/tags/*"], Operation::Push),
+            vec![
+                pair("refs/heads/main", Some("refs/heads/main")),
+                pair("refs/tags/main", Some("refs/tags/main")),
+                pair("refs/tags/v1.0", Some("refs/tags/v1.0")),
+            ]
+        );
+        assert_eq!(mappings(&[":"], Operation::Push).len(), REFS.len());
+        assert!(
+            mappings(&[":refs/heads/main"], Operation::Push).is_empty(),
+            "deletions don't match"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//!
+use git_hash::ObjectId;
+
+use crate::bstr::BString;
+
+mod error {
+    /// The error returned by [`fsck()`][crate::Repository::fsck()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        PackedRefsOpen(#[from] git_ref::packed::buffer::open::Error),
+        #[error("The references could not be traversed")]
+        ReferencesTraversal(#[from] std::io::Error),
+        #[error(transparent)]
+        ReferencesIter(#[from] git_ref::file::iter::loose_then_packed::Error),
+        #[error(transparent)]
+        FindHead(#[from] git_ref::file::find::Error),
+        #[error(transparent)]
+        ReflogOpen(#[from] git_ref::file::loose::reflog::Error),
+        #[error(transparent)]
+        ReflogDecode(#[from] git_ref::file::log::iter::decode::Error),
+        #[cfg(feature = "git-index")]
+        #[error(transparent)]
+        IndexOpen(#[from] git_index::file::init::Error),
+        #[error(transparent)]
+        FindObject(#[from] git_odb::store::find::Error),
+        #[error(transparent)]
+        LoadIndex(#[from] git_odb::store::load_index::Error),
+        #[error(transparent)]
+        IterObjects(#[from] git_odb::loose::iter::Error),
+        #[error("Interrupted")]
+        Interrupted,
+    }
+}
+pub use error::Error;
+
+/// Configure what [`fsck()`][crate::Repository::fsck()] considers reachable and what it reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// If true, objects mentioned in reflogs are reachable as well.
+    pub reflogs: bool,
+    /// If true, objects in the index are reachable as well. Only effective with the `git-index` feature.
+    pub index: bool,
+    /// If true, look at all objects in the object database to find those that are unreachable and aren't referred to by
+    /// other unreachable objects either, which is expensive for large repositories.
+    pub dangling: bool,
+}
+
+impl Default for Options {
+    /// Like `git fsck`, consider reflogs and the index as reachable and report dangling objects.
+    fn default() -> Self {
+        Options {
+            reflogs: true,
+            index: true,
+            dangling: true,
+        }
+    }
+}
+
+/// A place outside of the object database that refers to objects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Tip {
+    /// A reference with the given full name, including `HEAD`.
+    Reference(BString),
+    /// The reflog of the reference with the given full name.
+    Reflog(BString),
+    /// An entry of the index at the given path.
+    IndexEntry(BString),
+}
+
+/// Something that is wrong with a single object even though it could be decoded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Problem {
+    /// A tree entry uses a mode that isn't one of the ones git writes, like zero-padded or group-writable modes.
+    TreeEntryMode {
+        /// The name of the entry.
+        filename: BString,
+        /// The mode as stored in the tree.
+        mode: BString,
+    },
+    /// A tree entry has a name that is empty, `.`, `..`, `.git` or which contains a slash.
+    TreeEntryName {
+        /// The name of the entry.
+        filename: BString,
+    },
+    /// Tree entries aren't sorted or contain duplicates.
+    TreeEntryOrder(String),
+    /// The author or committer of a commit uses a timezone offset of a day or more.
+    CommitTimezone {
+        /// The offset of the time in seconds.
+        offset_in_seconds: i32,
+    },
+}
+
+/// A finding of [`fsck()`][crate::Repository::fsck()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub enum Finding {
+    /// The object `id` referred to by `tip` doesn't exist.
+    MissingTip {
+        /// The place referring to the object.
+        tip: Tip,
+        /// The missing object.
+        id: ObjectId,
+    },
+    /// The object `id` referred to by the object `referenced_by` doesn't exist.
+    Missing {
+        /// The missing object.
+        id: ObjectId,
+        /// The object referring to it.
+        referenced_by: ObjectId,
+    },
+    /// The object `id` couldn't be decoded.
+    Corrupt {
+        /// The object that can't be decoded.
+        id: ObjectId,
+        /// The kind of the object.
+        kind: git_object::Kind,
+        /// A description of the decode error.
+        message: String,
+    },
+    /// The object `id` is of a different kind than what the object referring to it says it is.
+    KindMismatch {
+        /// The object with unexpected kind.
+        id: ObjectId,
+        /// The kind the referring object expects.
+        expected: git_object::Kind,
+        /// The actual kind of the object.
+        actual: git_object::Kind,
+        /// The object referring to it.
+        referenced_by: ObjectId,
+    },
+    /// The object `id` can be decoded but isn't valid as described by `problem`.
+    Invalid {
+        /// The invalid object.
+        id: ObjectId,
+        /// What's wrong with it.
+        problem: Problem,
+    },
+    /// The object `id` is unreachable and no other unreachable object refers to it.
+    Dangling {
+        /// The dangling object.
+        id: ObjectId,
+        /// The kind of the object.
+        kind: git_object::Kind,
+    },
+}
+
+/// The outcome of [`fsck()`][crate::Repository::fsck()].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct Outcome {
+    /// The amount of reachable objects that were checked.
+    pub num_reachable_objects: usize,
+    /// Everything that was found to be wrong, sorted.
+    pub findings: Vec<Finding>,
+}
+
+impl Outcome {
+    /// Return true if there were no findings at all, dangling objects included.
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
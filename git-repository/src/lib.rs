@@ -94,6 +94,7 @@
 //! * [`odb`]
 //!   * [`pack`][odb::pack]
 //! * [`refs`]
+//! * [`refspec`]
 //! * [`interrupt`]
 //! * [`tempfile`]
 //! * [`lock`]
@@ -103,6 +104,8 @@
 //! * [`Progress`]
 //! * [`progress`]
 //! * [`interrupt`]
+//! * [`credentials`]
+//! * [`prompt`]
 //! * [`protocol`]
 //!   * [`transport`][protocol::transport]
 //!     * [`packetline`][protocol::transport::packetline]
@@ -120,6 +123,10 @@ use std::path::PathBuf;
 // This also means that their major version changes affect our major version, but that's alright as we directly expose their
 // APIs/instances anyway.
 pub use git_actor as actor;
+#[cfg(feature = "unstable")]
+pub use git_commitgraph as commitgraph;
+#[cfg(all(feature = "unstable", feature = "git-credentials"))]
+pub use git_credentials as credentials;
 #[cfg(all(feature = "unstable", feature = "git-diff"))]
 pub use git_diff as diff;
 use git_features::threading::OwnShared;
@@ -134,10 +141,14 @@ pub use git_object as objs;
 pub use git_object::bstr;
 #[cfg(feature = "unstable")]
 pub use git_odb as odb;
+#[cfg(all(feature = "unstable", feature = "git-prompt"))]
+pub use git_prompt as prompt;
 #[cfg(all(feature = "unstable", feature = "git-protocol"))]
 pub use git_protocol as protocol;
 pub use git_ref as refs;
 #[cfg(feature = "unstable")]
+pub use git_refspec as refspec;
+#[cfg(feature = "unstable")]
 pub use git_tempfile as tempfile;
 #[cfg(feature = "unstable")]
 pub use git_traverse as traverse;
@@ -186,11 +197,15 @@ mod types;
 pub use types::{Commit, DetachedObject, Head, Id, Object, Reference, Repository, ThreadSafeRepository, Tree};
 
 pub mod commit;
+pub mod fsck;
 pub mod head;
 pub mod id;
 pub mod object;
 pub mod reference;
+#[cfg(feature = "git-url")]
+pub mod remote;
 mod repository;
+pub mod revision;
 pub mod tag;
 
 /// The kind of `Repository`
@@ -263,6 +278,39 @@ pub mod mailmap {
     }
 }
 
+///
+pub mod prune {
+    /// The error returned by [`crate::Repository::prune_loose_objects()`].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        PackedRefsOpen(#[from] git_ref::packed::buffer::open::Error),
+        #[error("The references could not be traversed")]
+        ReferencesTraversal(#[from] std::io::Error),
+        #[error(transparent)]
+        ReferencesIter(#[from] git_ref::file::iter::loose_then_packed::Error),
+        #[error(transparent)]
+        FindHead(#[from] git_ref::file::find::Error),
+        #[error(transparent)]
+        ReflogOpen(#[from] git_ref::file::loose::reflog::Error),
+        #[error(transparent)]
+        ReflogDecode(#[from] git_ref::file::log::iter::decode::Error),
+        #[cfg(feature = "git-index")]
+        #[error(transparent)]
+        IndexOpen(#[from] git_index::file::init::Error),
+        #[error(transparent)]
+        FindObject(#[from] git_odb::store::find::Error),
+        #[error("Object {id} could not be decoded to learn which objects it refers to")]
+        ObjectDecode {
+            source: git_object::decode::Error,
+            id: git_hash::ObjectId,
+        },
+        #[error(transparent)]
+        Prune(#[from] git_odb::loose::prune::Error),
+    }
+}
+
 ///
 pub mod init {
     use std::{convert::TryInto, path::Path};
@@ -90,6 +90,25 @@ impl<'repo> Commit<'repo> {
             .map(|s| s.trim())
     }
 
+    /// Return the commits author with name and email mapped to their canonical form as defined in `mailmap`, which is
+    /// typically obtained with [`Repository::load_mailmap()`][crate::Repository::load_mailmap()].
+    #[cfg(feature = "git-mailmap")]
+    pub fn author_mailmapped(
+        &self,
+        mailmap: &git_mailmap::Snapshot,
+    ) -> Result<git_actor::Signature, git_object::decode::Error> {
+        self.author().map(|author| mailmap.resolve(author))
+    }
+
+    /// Return the commits committer with name and email mapped to their canonical form as defined in `mailmap`.
+    #[cfg(feature = "git-mailmap")]
+    pub fn committer_mailmapped(
+        &self,
+        mailmap: &git_mailmap::Snapshot,
+    ) -> Result<git_actor::Signature, git_object::decode::Error> {
+        self.committer().map(|committer| mailmap.resolve(committer))
+    }
+
     /// Decode this commits parent ids on the fly without allocating.
     // TODO: tests
     pub fn parent_ids(&self) -> impl Iterator<Item = crate::Id<'repo>> + '_ {
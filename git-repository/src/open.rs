@@ -1,4 +1,4 @@
-use std::{borrow::Cow, path::PathBuf};
+use std::{borrow::Cow, convert::TryFrom, path::PathBuf};
 
 use git_config::{
     file::GitConfig,
@@ -10,6 +10,8 @@ use git_features::threading::OwnShared;
 #[derive(Default)]
 pub struct Options {
     object_store_slots: git_odb::store::init::Slots,
+    object_store_refresh: git_odb::store::RefreshMode,
+    object_store_pack_mapping: Option<git_odb::pack::data::Mapping>,
 }
 
 impl Options {
@@ -20,6 +22,21 @@ impl Options {
         self
     }
 
+    /// Set what happens if an object can't be found. By default, the objects directory is scanned again for packs written by
+    /// other processes since the repository was opened, which can be turned off using [`Never`][git_odb::store::RefreshMode::Never]
+    /// if many objects are expected to be missing.
+    pub fn object_store_refresh(mut self, mode: git_odb::store::RefreshMode) -> Self {
+        self.object_store_refresh = mode;
+        self
+    }
+
+    /// Set how pack data files are made accessible, overriding what's configured in `core.packedGitWindowSize` and
+    /// `core.packedGitLimit`. Use this to limit the address space needed for huge packs, or to avoid memory maps entirely.
+    pub fn object_store_pack_mapping(mut self, mapping: git_odb::pack::data::Mapping) -> Self {
+        self.object_store_pack_mapping = Some(mapping);
+        self
+    }
+
     /// Open a repository at `path` with the options set so far.
     pub fn open(self, path: impl Into<std::path::PathBuf>) -> Result<crate::ThreadSafeRepository, Error> {
         crate::ThreadSafeRepository::open_opts(path, self)
@@ -38,10 +55,20 @@ pub enum Error {
     ObjectStoreInitialization(#[from] std::io::Error),
     #[error("Cannot handle objects formatted as {:?}", .name)]
     UnsupportedObjectFormat { name: crate::bstr::BString },
+    #[error("The references to replacement objects could not be traversed")]
+    ReplacementRefsTraversal(#[source] std::io::Error),
+    #[error(transparent)]
+    PackedRefsOpen(#[from] git_ref::packed::buffer::open::Error),
+    #[error(transparent)]
+    ReplacementRefsIter(#[from] git_ref::file::iter::loose_then_packed::Error),
 }
 
 impl crate::ThreadSafeRepository {
     /// Open a git repository at the given `path`, possibly expanding it to `path/.git` if `path` is a work tree dir.
+    ///
+    /// Objects replaced via `refs/replace/*` are transparently substituted when accessed unless `core.useReplaceRefs` is false
+    /// or the `GIT_NO_REPLACE_OBJECTS` environment variable is set. `GIT_REPLACE_REF_BASE` can be used to read replacements from
+    /// a different namespace.
     pub fn open(path: impl Into<std::path::PathBuf>) -> Result<Self, Error> {
         Self::open_opts(path, Options::default())
     }
@@ -64,7 +91,11 @@ impl crate::ThreadSafeRepository {
     pub(crate) fn open_from_paths(
         git_dir: PathBuf,
         mut worktree_dir: Option<PathBuf>,
-        Options { object_store_slots }: Options,
+        Options {
+            object_store_slots,
+            object_store_refresh,
+            object_store_pack_mapping,
+        }: Options,
     ) -> Result<Self, Error> {
         let config = git_config::file::GitConfig::open(git_dir.join("config"))?;
         if worktree_dir.is_none() {
@@ -94,24 +125,57 @@ impl crate::ThreadSafeRepository {
             git_hash::Kind::Sha1
         };
 
+        let loose_write_options = git_odb::loose::write::Options {
+            fsync: if config_bool(&config, "core.fsyncObjectFiles", false) {
+                git_odb::loose::write::Fsync::Objects
+            } else {
+                git_odb::loose::write::Fsync::None
+            },
+            read_only: true,
+            shared_repository: config
+                .value::<Cow<'_, [u8]>>("core", None, "sharedRepository")
+                .ok()
+                .and_then(|value| git_odb::loose::write::SharedRepository::from_config_value(value.as_ref()))
+                .unwrap_or_default(),
+        };
+
+        let pack_mapping = object_store_pack_mapping.unwrap_or_else(|| pack_mapping_from_config(&config));
+
+        let objects_dir = git_dir.join("objects");
+        let refs = crate::RefStore::at(
+            git_dir,
+            if worktree_dir.is_none() {
+                git_ref::store::WriteReflog::Disable
+            } else {
+                git_ref::store::WriteReflog::Normal
+            },
+            object_hash,
+        );
+        let replacements = if std::env::var_os("GIT_NO_REPLACE_OBJECTS").is_none()
+            && config_bool(&config, "core.useReplaceRefs", true)
+        {
+            let ref_base = std::env::var_os("GIT_REPLACE_REF_BASE")
+                .map(PathBuf::from)
+                .unwrap_or_else(|| "refs/replace/".into());
+            replacement_objects(&refs, &ref_base)?
+        } else {
+            Vec::new()
+        };
+
         Ok(crate::ThreadSafeRepository {
             objects: OwnShared::new(git_odb::Store::at_opts(
-                git_dir.join("objects"),
+                objects_dir,
                 git_odb::store::init::Options {
                     slots: object_store_slots,
                     object_hash,
                     use_multi_pack_index,
+                    loose_write_options,
+                    replacements,
+                    refresh: object_store_refresh,
+                    pack_mapping,
                 },
             )?),
-            refs: crate::RefStore::at(
-                git_dir,
-                if worktree_dir.is_none() {
-                    git_ref::store::WriteReflog::Disable
-                } else {
-                    git_ref::store::WriteReflog::Normal
-                },
-                object_hash,
-            ),
+            refs,
             work_tree: worktree_dir,
             object_hash,
             config: config.into(),
@@ -119,6 +183,54 @@ impl crate::ThreadSafeRepository {
     }
 }
 
+/// Return pairs of original and replacement object ids for all references below `ref_base`, which are named after the object they replace.
+fn replacement_objects(
+    refs: &crate::RefStore,
+    ref_base: &std::path::Path,
+) -> Result<Vec<(git_hash::ObjectId, git_hash::ObjectId)>, Error> {
+    use crate::bstr::ByteSlice;
+
+    let platform = refs.iter()?;
+    let mut out = Vec::new();
+    for reference in platform.prefixed(ref_base).map_err(Error::ReplacementRefsTraversal)? {
+        let reference = reference?;
+        let name: &[u8] = reference.name.as_bstr();
+        let original_hex = name.rfind_byte(b'/').map_or(name, |pos| &name[pos + 1..]);
+        if let (Ok(original), Some(replacement)) =
+            (git_hash::ObjectId::from_hex(original_hex), reference.target.as_id())
+        {
+            out.push((original, replacement.to_owned()));
+        }
+    }
+    Ok(out)
+}
+
+/// Use windowed access to pack data if either `core.packedGitWindowSize` or `core.packedGitLimit` is set, filling in
+/// what's not set with git's defaults for this platform.
+fn pack_mapping_from_config(config: &GitConfig<'_>) -> git_odb::pack::data::Mapping {
+    let size = |key: &str| {
+        config.value::<Integer>("core", None, key).ok().and_then(|v| {
+            let shift = v.suffix.map_or(0, |suffix| suffix.bitwise_offset());
+            u64::try_from(v.value).ok()?.checked_mul(1 << shift)
+        })
+    };
+    let (window_size, limit) = (size("packedGitWindowSize"), size("packedGitLimit"));
+    if window_size.is_none() && limit.is_none() {
+        return git_odb::pack::data::Mapping::default();
+    }
+    let (default_window_size, default_limit): (u64, u64) = if cfg!(target_pointer_width = "64") {
+        (1 << 30, 8 << 30)
+    } else {
+        (32 << 20, 256 << 20)
+    };
+    let window_size = window_size.unwrap_or(default_window_size).max(1);
+    let limit = limit.unwrap_or(default_limit);
+    git_odb::pack::data::Mapping::Windowed {
+        window_size: usize::try_from(window_size).unwrap_or(usize::MAX),
+        max_windows: usize::try_from(limit / window_size).unwrap_or(usize::MAX).max(1),
+    }
+}
+
 fn config_bool(config: &GitConfig<'_>, key: &str, default: bool) -> bool {
     let (section, key) = key.split_once('.').expect("valid section.key format");
     config
@@ -0,0 +1,44 @@
+//! Remotes as configured in a repository, and utilities for dealing with their URLs.
+///
+pub mod url;
+
+/// A remote as configured in the `remote.<name>` sections of the git configuration.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Remote {
+    /// The name of the remote, like `origin`.
+    pub name: String,
+    /// The URL to fetch from, with all [rewrite rules][url::Rewrite] applied, or `None` if `remote.<name>.url` isn't set.
+    pub url: Option<git_url::Url>,
+    /// The URL to push to, which is `remote.<name>.pushurl` if set or the URL to fetch from otherwise, with all
+    /// rewrite rules applied.
+    pub push_url: Option<git_url::Url>,
+    /// The refspecs to use when fetching, as configured in `remote.<name>.fetch`.
+    pub fetch_specs: Vec<git_refspec::RefSpec>,
+    /// The refspecs to use when pushing, as configured in `remote.<name>.push`.
+    pub push_specs: Vec<git_refspec::RefSpec>,
+}
+
+///
+pub mod find {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Repository::find_remote()`][crate::Repository::find_remote()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The URL '{url}' of remote '{name}' could not be parsed")]
+        Url {
+            name: String,
+            url: BString,
+            source: git_url::parse::Error,
+        },
+        #[error("The refspec '{spec}' of remote '{name}' could not be parsed")]
+        RefSpec {
+            name: String,
+            spec: BString,
+            source: git_refspec::parse::Error,
+        },
+        #[error(transparent)]
+        Rewrite(#[from] super::url::Error),
+    }
+}
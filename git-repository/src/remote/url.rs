@@ -0,0 +1,209 @@
+use crate::bstr::{BString, ByteSlice, ByteVec};
+use git_config::{file::GitConfig, parser::Key};
+
+mod error {
+    use crate::bstr::BString;
+
+    /// The error returned by [`Rewrite::rewrite()`][super::Rewrite::rewrite()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The URL could not be serialized for rewriting")]
+        Serialize(#[from] std::io::Error),
+        #[error("The rewritten URL '{url}' could not be parsed")]
+        Parse {
+            url: BString,
+            source: git_url::parse::Error,
+        },
+    }
+}
+pub use error::Error;
+
+/// Whether a URL is used to fetch from or to push to a remote.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Direction {
+    /// The URL is used to fetch from the remote.
+    Fetch,
+    /// The URL is used to push to the remote.
+    Push,
+}
+
+/// The fetch and push URLs a URL turns into after applying all [rewrite rules][Rewrite].
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Rewritten {
+    /// The URL to fetch from.
+    pub fetch: git_url::Url,
+    /// The URL to push to.
+    pub push: git_url::Url,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Replace {
+    find: BString,
+    with: BString,
+}
+
+/// The rules to rewrite URLs with, as configured by `url.<base>.insteadOf` and `url.<base>.pushInsteadOf`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Rewrite {
+    url_rewrite: Vec<Replace>,
+    push_url_rewrite: Vec<Replace>,
+}
+
+impl Rewrite {
+    /// Read all rewrite rules from the `url` sections in `config`.
+    pub fn from_config(config: &GitConfig<'_>) -> Self {
+        let mut url_rewrite = Vec::new();
+        let mut push_url_rewrite = Vec::new();
+        for (header, body) in config.sections_by_name_with_header("url") {
+            let base = match header.subsection_name.as_ref() {
+                Some(base) => base,
+                None => continue,
+            };
+            for (key, rules) in &mut [
+                ("insteadOf", &mut url_rewrite),
+                ("pushInsteadOf", &mut push_url_rewrite),
+            ] {
+                rules.extend(body.values(&Key::from(*key)).into_iter().map(|find| Replace {
+                    find: find.into_owned().into(),
+                    with: base.as_bytes().into(),
+                }));
+            }
+        }
+        Rewrite {
+            url_rewrite,
+            push_url_rewrite,
+        }
+    }
+
+    /// Return `true` if there are no rules to apply.
+    pub fn is_empty(&self) -> bool {
+        self.url_rewrite.is_empty() && self.push_url_rewrite.is_empty()
+    }
+
+    /// Rewrite the serialized `url` for use in `direction`, or return `None` if no rule applies.
+    ///
+    /// The rule with the longest prefix of `url` wins, and among rules with the same prefix the one configured first.
+    /// Note that [`Push`][Direction::Push] only applies `pushInsteadOf` rules.
+    pub fn rewrite_url(&self, url: &[u8], direction: Direction) -> Option<BString> {
+        let rules = match direction {
+            Direction::Fetch => &self.url_rewrite,
+            Direction::Push => &self.push_url_rewrite,
+        };
+        let mut longest: Option<&Replace> = None;
+        for rule in rules.iter().filter(|rule| url.starts_with(rule.find.as_slice())) {
+            if longest.map_or(true, |longest| rule.find.len() > longest.find.len()) {
+                longest = Some(rule);
+            }
+        }
+        longest.map(|rule| {
+            let mut rewritten = rule.with.clone();
+            rewritten.push_str(&url[rule.find.len()..]);
+            rewritten
+        })
+    }
+
+    /// Rewrite `url` into the URLs to fetch from and to push to, just like git does for remotes without a `pushurl`.
+    ///
+    /// The fetch URL is rewritten with `insteadOf` rules, whereas the push URL is rewritten with `pushInsteadOf` rules.
+    /// If none of the latter apply, the push URL is the rewritten fetch URL.
+    pub fn rewrite(&self, url: &git_url::Url) -> Result<Rewritten, Error> {
+        let serialized = url.to_bstring()?;
+        let parse = |rewritten: BString| {
+            git_url::parse(rewritten.as_bytes()).map_err(|source| Error::Parse { url: rewritten, source })
+        };
+        let fetch = match self.rewrite_url(serialized.as_bytes(), Direction::Fetch) {
+            Some(rewritten) => parse(rewritten)?,
+            None => url.clone(),
+        };
+        let push = match self.rewrite_url(serialized.as_bytes(), Direction::Push) {
+            Some(rewritten) => parse(rewritten)?,
+            None => fetch.clone(),
+        };
+        Ok(Rewritten { fetch, push })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use super::*;
+
+    fn rewrite(config: &str) -> Rewrite {
+        Rewrite::from_config(&GitConfig::try_from(config).expect("valid config"))
+    }
+
+    fn url(url: &str) -> git_url::Url {
+        git_url::parse(url.as_bytes()).expect("valid url")
+    }
+
+    #[test]
+    fn longest_prefix_wins() {
+        let rewrite = rewrite(
+            r#"
+[url "ssh://git@example.com/"]
+    insteadOf = https://example.com/
+[url "ssh://git@example.com/special/"]
+    insteadOf = https://example.com/special
+[url "ssh://git@example.com/"]
+    insteadOf = https://example.com/special/project"#,
+        );
+        assert_eq!(
+            rewrite.rewrite_url(b"https://example.com/repo", Direction::Fetch),
+            Some("ssh://git@example.com/repo".into())
+        );
+        assert_eq!(
+            rewrite.rewrite_url(b"https://example.com/special-repo", Direction::Fetch),
+            Some("ssh://git@example.com/special/-repo".into())
+        );
+        assert_eq!(
+            rewrite.rewrite_url(b"https://example.com/special/project.git", Direction::Fetch),
+            Some("ssh://git@example.com/.git".into())
+        );
+        assert_eq!(rewrite.rewrite_url(b"https://other.com/repo", Direction::Fetch), None);
+        assert_eq!(
+            rewrite.rewrite_url(b"https://example.com/repo", Direction::Push),
+            None,
+            "insteadOf rules don't apply to pushes directly"
+        );
+    }
+
+    #[test]
+    fn push_urls_fall_back_to_rewritten_fetch_urls() -> Result<(), Error> {
+        let rewrite = rewrite(
+            r#"
+[url "https://mirror.example.com/"]
+    insteadOf = gh:
+[url "git@github.com:"]
+    pushInsteadOf = https://github.com/"#,
+        );
+        assert_eq!(
+            rewrite.rewrite(&url("gh:user/repo"))?,
+            Rewritten {
+                fetch: url("https://mirror.example.com/user/repo"),
+                push: url("https://mirror.example.com/user/repo"),
+            }
+        );
+        let rewritten = rewrite.rewrite(&url("https://github.com/user/repo"))?;
+        assert_eq!(rewritten.fetch, url("https://github.com/user/repo"));
+        assert_eq!(rewritten.push, url("git@github.com:user/repo"));
+        assert_eq!(rewritten.push.to_bstring()?, "git@github.com:user/repo");
+        Ok(())
+    }
+
+    #[test]
+    fn without_rules_urls_are_unchanged() -> Result<(), Error> {
+        let rewrite = rewrite("[core]\n    bare = false");
+        assert!(rewrite.is_empty());
+        let url = url("file:///path/to/repo");
+        assert_eq!(
+            rewrite.rewrite(&url)?,
+            Rewritten {
+                fetch: url.clone(),
+                push: url
+            }
+        );
+        Ok(())
+    }
+}
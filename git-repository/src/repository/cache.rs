@@ -30,31 +30,30 @@ impl crate::Repository {
     /// some gains most of the time. Note that the value given is _per-thread_.
     ///
     /// Use the `GITOXIDE_OBJECT_CACHE_MEMORY=16mb` to set the given amount of memory to store full objects, on a per-thread basis.
+    ///
+    /// Use `GITOXIDE_CACHE_FREE_LIST_LIMIT=16` to keep at most 16 buffers of replaced cache entries for reuse, instead of all of them.
+    ///
+    /// See [`git_odb::cache::Options`] for configuring caches programmatically.
     pub fn apply_environment(self) -> Self {
         // We have no cache types available without this flag currently. Maybe this should change at some point.
         #[cfg(not(feature = "max-performance"))]
         return self;
         #[cfg(feature = "max-performance")]
         {
-            let pack_cache_disabled = std::env::var_os("GITOXIDE_DISABLE_PACK_CACHE").is_some();
             let mut this = self;
-            if !pack_cache_disabled {
-                let bytes = parse_bytes_from_var("GITOXIDE_PACK_CACHE_MEMORY");
-                let new_pack_cache = move || -> Box<git_odb::cache::PackCache> {
-                    match bytes {
-                        Some(bytes) => Box::new(git_pack::cache::lru::MemoryCappedHashmap::new(bytes)),
-                        None => Box::new(git_pack::cache::lru::StaticLinkedList::<64>::default()),
-                    }
-                };
-                this.objects.set_pack_cache(new_pack_cache);
-            } else {
-                this.objects.unset_pack_cache();
-            }
-
-            if let Some(bytes) = parse_bytes_from_var("GITOXIDE_OBJECT_CACHE_MEMORY") {
-                this.objects
-                    .set_object_cache(move || Box::new(git_pack::cache::object::MemoryCappedHashmap::new(bytes)));
-            }
+            this.objects.set_caches(git_odb::cache::Options {
+                pack_cache_bytes: if std::env::var_os("GITOXIDE_DISABLE_PACK_CACHE").is_some() {
+                    Some(0)
+                } else {
+                    parse_bytes_from_var("GITOXIDE_PACK_CACHE_MEMORY")
+                },
+                object_cache_bytes: parse_bytes_from_var("GITOXIDE_OBJECT_CACHE_MEMORY").unwrap_or(0),
+                free_list_limit: std::env::var("GITOXIDE_CACHE_FREE_LIST_LIMIT").ok().and_then(|v| {
+                    v.parse()
+                        .map_err(|err| log::warn!("Failed to parse {:?} as free list limit: {}", v, err))
+                        .ok()
+                }),
+            });
             this
         }
     }
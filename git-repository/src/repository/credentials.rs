@@ -0,0 +1,51 @@
+use git_config::parser::Key;
+
+use crate::bstr::{BStr, BString, ByteSlice};
+
+impl crate::Repository {
+    /// Return the credential helpers configured with `credential.helper` for use with the remote at `url`, in the order
+    /// they are to be consulted.
+    ///
+    /// Values of `credential.<url>.helper` are included if `url` starts with `<url>`, which makes this a simplified
+    /// version of the matching git performs.
+    pub fn credential_helpers(&self, url: &BStr) -> git_credentials::helper::Cascade {
+        let url = url.trim_end_with(|c| c == '/');
+        git_credentials::helper::Cascade::from_config_values(
+            self.config
+                .sections_by_name_with_header("credential")
+                .into_iter()
+                .filter(|(header, _)| match header.subsection_name.as_deref() {
+                    None => true,
+                    Some(prefix) => url.starts_with_str(prefix.trim_end_matches('/')),
+                })
+                .flat_map(|(_, body)| {
+                    body.values(&Key::from("helper"))
+                        .into_iter()
+                        .map(|v| BString::from(v.into_owned()))
+                }),
+        )
+    }
+
+    /// Return the options to prompt the user for credentials with if no credential helper could provide them, using the
+    /// askpass program configured in `core.askPass` unless the environment overrides it.
+    #[cfg(feature = "git-prompt")]
+    pub fn credential_prompt_options(&self) -> git_prompt::Options<'static> {
+        let askpass = self
+            .config
+            .value::<git_config::values::Path<'_>>("core", None, "askPass")
+            .ok()
+            .and_then(|path| {
+                use git_config::values::path::interpolate;
+                let install_dir = self.install_dir().ok()?;
+                let home = interpolate::home_dir();
+                path.interpolate(interpolate::Context {
+                    git_install_dir: Some(install_dir.as_path()),
+                    home_dir: home.as_deref(),
+                    home_for_user: Some(interpolate::home_for_user),
+                })
+                .ok()
+                .map(|path| path.into_owned())
+            });
+        git_prompt::Options::default().apply_environment(askpass.as_deref())
+    }
+}
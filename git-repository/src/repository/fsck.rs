@@ -0,0 +1,252 @@
+use std::{
+    collections::HashSet,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use git_features::progress::Progress;
+use git_hash::ObjectId;
+use git_object::{tree::EntryMode, Kind, ObjectRef};
+use git_odb::Find;
+
+use crate::{
+    bstr::{BStr, BString, ByteSlice},
+    fsck::{Error, Finding, Options, Outcome, Problem, Tip},
+};
+
+/// The tree entry modes git writes, all others are non-canonical.
+const CANONICAL_MODES: &[&[u8]] = &[b"40000", b"100644", b"100755", b"120000", b"160000"];
+
+impl crate::Repository {
+    /// Check all objects reachable from references and, depending on `options`, from their reflogs and the index, similar to
+    /// `git fsck`.
+    ///
+    /// Each reachable object must exist and decode, be of the kind the objects referring to it expect, and be valid beyond
+    /// that, which includes canonical tree entry modes, sane tree entry names and order as well as plausible commit timezones.
+    /// With [`dangling`][Options::dangling] set, all other objects are checked as well, and those which aren't referred to
+    /// by any other unreachable object are reported as dangling.
+    ///
+    /// Replacement objects are ignored, and objects of alternate object databases are considered like our own.
+    pub fn fsck(
+        &self,
+        options: Options,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<Outcome, Error> {
+        let mut objects = self.objects.clone();
+        objects.ignore_replacements = true;
+        let mut findings = Vec::new();
+        let mut buf = Vec::new();
+
+        let mut reachable = HashSet::new();
+        let mut queue = Vec::new();
+        for (tip, id) in self.fsck_tips(options)? {
+            if !objects.contains(&id) {
+                findings.push(Finding::MissingTip { tip, id });
+            } else if reachable.insert(id) {
+                queue.push((id, None, None));
+            }
+        }
+
+        let mut num_reachable_objects = 0;
+        progress.init(None, git_features::progress::count("objects"));
+        while let Some((id, expected, referenced_by)) = queue.pop() {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(Error::Interrupted);
+            }
+            let object = match objects.try_find(&id, &mut buf)? {
+                Some(object) => object,
+                None => {
+                    findings.push(Finding::Missing {
+                        id,
+                        referenced_by: referenced_by.expect("tips are known to exist"),
+                    });
+                    continue;
+                }
+            };
+            num_reachable_objects += 1;
+            progress.inc();
+            if let (Some(expected), Some(referenced_by)) = (expected, referenced_by) {
+                if expected != object.kind {
+                    findings.push(Finding::KindMismatch {
+                        id,
+                        expected,
+                        actual: object.kind,
+                        referenced_by,
+                    });
+                }
+            }
+            check_object(id, object, &mut findings, |child, kind| {
+                if reachable.insert(child) {
+                    queue.push((child, Some(kind), Some(id)));
+                }
+            });
+        }
+
+        if options.dangling {
+            let mut unreachable = Vec::new();
+            let mut referenced = HashSet::new();
+            let mut seen = HashSet::new();
+            for id in objects.iter()? {
+                let id = id?;
+                if should_interrupt.load(Ordering::Relaxed) {
+                    return Err(Error::Interrupted);
+                }
+                if reachable.contains(&id) || !seen.insert(id) {
+                    continue;
+                }
+                progress.inc();
+                if let Some(object) = objects.try_find(&id, &mut buf)? {
+                    unreachable.push((id, object.kind));
+                    check_object(id, object, &mut findings, |child, _kind| {
+                        referenced.insert(child);
+                    });
+                }
+            }
+            findings.extend(
+                unreachable
+                    .into_iter()
+                    .filter(|(id, _)| !referenced.contains(id))
+                    .map(|(id, kind)| Finding::Dangling { id, kind }),
+            );
+        }
+
+        findings.sort();
+        findings.dedup();
+        Ok(Outcome {
+            num_reachable_objects,
+            findings,
+        })
+    }
+
+    /// Return all objects referred to by references, and depending on `options`, their reflogs and the index, along with
+    /// where they were referred to.
+    fn fsck_tips(&self, options: Options) -> Result<Vec<(Tip, ObjectId)>, Error> {
+        let mut tips = Vec::new();
+        let mut buf = Vec::new();
+        let references = self.refs.iter()?;
+        let head = self.refs.try_find("HEAD")?;
+        for reference in references.all()?.chain(head.map(Ok)) {
+            let reference = reference?;
+            let name: BString = reference.name.as_bstr().into();
+            if let Some(id) = reference.target.as_id() {
+                tips.push((Tip::Reference(name.clone()), id.to_owned()));
+            }
+            if !options.reflogs {
+                continue;
+            }
+            if let Some(log) = self.refs.reflog_iter(reference.name.as_bstr(), &mut buf)? {
+                for line in log {
+                    let line = line?;
+                    for id in [line.previous_oid(), line.new_oid()].iter().filter(|id| !id.is_null()) {
+                        tips.push((Tip::Reflog(name.clone()), *id));
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "git-index")]
+        if options.index {
+            if let Some(index) = self.load_index() {
+                let index = index?;
+                for entry in index.entries() {
+                    // Submodule commits live in another repository.
+                    if entry.mode != git_index::entry::Mode::COMMIT {
+                        tips.push((Tip::IndexEntry(entry.path(&index).to_owned()), entry.id));
+                    }
+                }
+            }
+        }
+        Ok(tips)
+    }
+}
+
+/// Validate `object` with `id` and call `refers_to` with each object it refers to along with the kind it is expected to have.
+fn check_object(
+    id: ObjectId,
+    object: git_object::Data<'_>,
+    findings: &mut Vec<Finding>,
+    mut refers_to: impl FnMut(ObjectId, Kind),
+) {
+    let decoded = match object.decode() {
+        Ok(decoded) => decoded,
+        Err(err) => {
+            findings.push(Finding::Corrupt {
+                id,
+                kind: object.kind,
+                message: err.to_string(),
+            });
+            return;
+        }
+    };
+    match decoded {
+        ObjectRef::Commit(commit) => {
+            for time in [commit.author.time, commit.committer.time].iter() {
+                if time.offset_in_seconds.abs() >= 24 * 60 * 60 {
+                    findings.push(Finding::Invalid {
+                        id,
+                        problem: Problem::CommitTimezone {
+                            offset_in_seconds: time.offset_in_seconds,
+                        },
+                    });
+                }
+            }
+            refers_to(commit.tree(), Kind::Tree);
+            for parent in commit.parents() {
+                refers_to(parent, Kind::Commit);
+            }
+        }
+        ObjectRef::Tree(tree) => {
+            if let Err(err) = tree.verify_order() {
+                findings.push(Finding::Invalid {
+                    id,
+                    problem: Problem::TreeEntryOrder(err.to_string()),
+                });
+            }
+            for (filename, mode) in raw_tree_entries(object.data, id.as_bytes().len()) {
+                if !CANONICAL_MODES.iter().any(|canonical| *canonical == mode) {
+                    findings.push(Finding::Invalid {
+                        id,
+                        problem: Problem::TreeEntryMode {
+                            filename: filename.into(),
+                            mode: mode.into(),
+                        },
+                    });
+                }
+            }
+            for entry in &tree.entries {
+                if is_invalid_filename(entry.filename) {
+                    findings.push(Finding::Invalid {
+                        id,
+                        problem: Problem::TreeEntryName {
+                            filename: entry.filename.into(),
+                        },
+                    });
+                }
+                match entry.mode {
+                    // Submodule commits live in another repository.
+                    EntryMode::Commit => {}
+                    EntryMode::Tree => refers_to(entry.oid.to_owned(), Kind::Tree),
+                    EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => {
+                        refers_to(entry.oid.to_owned(), Kind::Blob)
+                    }
+                }
+            }
+        }
+        ObjectRef::Tag(tag) => refers_to(tag.target(), tag.target_kind),
+        ObjectRef::Blob(_) => {}
+    }
+}
+
+/// Return the name and mode of each entry of a tree that is known to decode, with mode as it is stored.
+fn raw_tree_entries<'a>(mut data: &'a [u8], hash_len: usize) -> impl Iterator<Item = (&'a BStr, &'a [u8])> {
+    std::iter::from_fn(move || {
+        let entry: &'a [u8] = data;
+        let mode_end = entry.find_byte(b' ')?;
+        let name_end = mode_end + 1 + entry[mode_end + 1..].find_byte(0)?;
+        data = entry.get(name_end + 1 + hash_len..)?;
+        Some((entry[mode_end + 1..name_end].as_bstr(), &entry[..mode_end]))
+    })
+}
+
+fn is_invalid_filename(name: &BStr) -> bool {
+    name.is_empty() || name == "." || name == ".." || name.eq_ignore_ascii_case(b".git") || name.contains(&b'/')
+}
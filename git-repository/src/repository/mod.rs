@@ -86,8 +86,20 @@ mod cache;
 
 mod config;
 
+#[cfg(feature = "git-url")]
+mod remote;
+
+#[cfg(feature = "git-credentials")]
+mod credentials;
+
 mod reference;
 
 mod object;
 
+mod fsck;
+
+mod prune;
+
+mod revision;
+
 mod thread_safe;
@@ -0,0 +1,96 @@
+use std::{collections::HashSet, sync::atomic::AtomicBool};
+
+use git_features::progress::Progress;
+use git_odb::{loose, Find};
+
+use crate::prune::Error;
+
+impl crate::Repository {
+    /// Remove loose objects that aren't reachable from any reference, reflog entry or the index and that are older than
+    /// the expiry in `options`, similar to `git prune`.
+    ///
+    /// With [`dry_run`][loose::prune::Options::dry_run] set, nothing is removed and the outcome lists the objects that would
+    /// have been removed. Only the objects of this repository are considered, never those of alternate object databases.
+    pub fn prune_loose_objects(
+        &self,
+        options: loose::prune::Options,
+        mut progress: impl Progress,
+        should_interrupt: &AtomicBool,
+    ) -> Result<loose::prune::Outcome, Error> {
+        let reachable = self.reachable_objects(progress.add_child("find reachable objects"))?;
+        let store = loose::Store::at(self.objects.store_ref().path(), self.object_hash);
+        Ok(store.prune(
+            |id| reachable.contains(id),
+            options,
+            progress.add_child("prune loose objects"),
+            should_interrupt,
+        )?)
+    }
+
+    /// Return all objects reachable from references, their reflogs and the index.
+    /// Missing objects are ignored as there is no way to learn about the objects they refer to.
+    fn reachable_objects(&self, mut progress: impl Progress) -> Result<HashSet<git_hash::ObjectId>, Error> {
+        let mut tips = Vec::new();
+        let mut buf = Vec::new();
+        let references = self.refs.iter()?;
+        let head = self.refs.try_find("HEAD")?;
+        for reference in references.all()?.chain(head.map(Ok)) {
+            let reference = reference?;
+            // Symbolic references point to ones that we see anyway, so there is no need to follow them here.
+            tips.extend(reference.target.as_id().map(ToOwned::to_owned));
+            if let Some(log) = self.refs.reflog_iter(reference.name.as_bstr(), &mut buf)? {
+                for line in log {
+                    let line = line?;
+                    tips.extend(
+                        [line.previous_oid(), line.new_oid()]
+                            .iter()
+                            .filter(|id| !id.is_null())
+                            .copied(),
+                    );
+                }
+            }
+        }
+        #[cfg(feature = "git-index")]
+        if let Some(index) = self.load_index() {
+            tips.extend(index?.entries().iter().map(|entry| entry.id));
+        }
+
+        // Like git, see objects as they are, as replacements don't change what's referenced on disk.
+        let mut objects = self.objects.clone();
+        objects.ignore_replacements = true;
+        progress.init(None, git_features::progress::count("objects"));
+        let mut seen: HashSet<_> = tips.iter().copied().collect();
+        while let Some(id) = tips.pop() {
+            progress.inc();
+            let object = match objects.try_find(&id, &mut buf)? {
+                Some(object) => object,
+                None => continue,
+            };
+            // Not knowing what a corrupt object refers to would make us remove objects that are still needed.
+            match object.decode().map_err(|err| Error::ObjectDecode { source: err, id })? {
+                git_object::ObjectRef::Commit(commit) => {
+                    tips.extend(
+                        std::iter::once(commit.tree())
+                            .chain(commit.parents())
+                            .filter(|id| seen.insert(*id)),
+                    );
+                }
+                git_object::ObjectRef::Tree(tree) => tips.extend(
+                    tree.entries
+                        .iter()
+                        .filter(|entry| entry.mode != git_object::tree::EntryMode::Commit)
+                        .map(|entry| entry.oid.to_owned())
+                        .filter(|id| seen.insert(*id)),
+                ),
+                git_object::ObjectRef::Tag(tag) => {
+                    let target = tag.target();
+                    if seen.insert(target) {
+                        tips.push(target);
+                    }
+                }
+                git_object::ObjectRef::Blob(_) => {}
+            }
+        }
+        Ok(seen)
+    }
+}
@@ -0,0 +1,99 @@
+use git_config::parser::Key;
+
+use crate::{
+    bstr::{BString, ByteSlice},
+    remote,
+};
+
+impl crate::Repository {
+    /// Return the rules to rewrite remote URLs with, as configured by `url.<base>.insteadOf` and `url.<base>.pushInsteadOf`.
+    pub fn url_rewrite(&self) -> crate::remote::url::Rewrite {
+        crate::remote::url::Rewrite::from_config(&self.config)
+    }
+
+    /// Return the names of all configured remotes, in the order they were first mentioned in the configuration.
+    pub fn remote_names(&self) -> Vec<String> {
+        let mut names = Vec::<String>::new();
+        for name in self.config.subsection_names("remote") {
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_owned());
+            }
+        }
+        names
+    }
+
+    /// Find the remote with the given `name` and return it, or `None` if there is no `remote.<name>` section.
+    ///
+    /// Like in git, the values of all sections of the same remote are combined, and only the first URL is used.
+    pub fn find_remote(&self, name: &str) -> Result<Option<remote::Remote>, remote::find::Error> {
+        let mut found = false;
+        let (mut urls, mut push_urls, mut fetch_specs, mut push_specs) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (header, body) in self.config.sections_by_name_with_header("remote") {
+            if header.subsection_name.as_deref() != Some(name) {
+                continue;
+            }
+            found = true;
+            for (key, values) in &mut [
+                ("url", &mut urls),
+                ("pushurl", &mut push_urls),
+                ("fetch", &mut fetch_specs),
+                ("push", &mut push_specs),
+            ] {
+                values.extend(
+                    body.values(&Key::from(*key))
+                        .into_iter()
+                        .map(|v| BString::from(v.into_owned())),
+                );
+            }
+        }
+        if !found {
+            return Ok(None);
+        }
+
+        let parse = |url: &BString| {
+            git_url::parse(url.as_bytes()).map_err(|source| remote::find::Error::Url {
+                name: name.to_owned(),
+                url: url.to_owned(),
+                source,
+            })
+        };
+        let parse_specs = |specs: Vec<BString>, operation: git_refspec::Operation| {
+            specs
+                .iter()
+                .map(|spec| {
+                    git_refspec::parse(spec.as_bstr(), operation)
+                        .map(|spec| spec.to_owned())
+                        .map_err(|source| remote::find::Error::RefSpec {
+                            name: name.to_owned(),
+                            spec: spec.to_owned(),
+                            source,
+                        })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let fetch_specs = parse_specs(fetch_specs, git_refspec::Operation::Fetch)?;
+        let push_specs = parse_specs(push_specs, git_refspec::Operation::Push)?;
+        let rewrite = self.url_rewrite();
+        let rewritten = urls
+            .first()
+            .map(parse)
+            .transpose()?
+            .map(|url| rewrite.rewrite(&url))
+            .transpose()?;
+        let push_url = match push_urls.first() {
+            Some(push_url) => Some(match rewrite.rewrite_url(push_url, remote::url::Direction::Push) {
+                Some(rewritten) => parse(&rewritten)?,
+                None => parse(push_url)?,
+            }),
+            None => rewritten.as_ref().map(|r| r.push.clone()),
+        };
+        Ok(Some(remote::Remote {
+            name: name.to_owned(),
+            url: rewritten.map(|r| r.fetch),
+            push_url,
+            fetch_specs,
+            push_specs,
+        }))
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use git_hash::ObjectId;
+use git_object::bstr::BStr;
+use git_odb::FindExt;
+
+use crate::revision;
+
+/// Methods related to revision traversal.
+impl crate::Repository {
+    /// Parse a revision specification like `main~2`, `v1.0^{tree}`, `HEAD:src/lib.rs`, `^v1.0` or `v1.0..main`, similar to
+    /// `git rev-parse`.
+    ///
+    /// Only a subset of what git supports is available. Notably reflog lookups like `@{1}`, symmetric differences like
+    /// `A...B` and searches like `:/message` are not supported.
+    pub fn rev_parse<'a>(&self, spec: impl Into<&'a BStr>) -> Result<revision::Spec<'_>, revision::spec::parse::Error> {
+        revision::spec::parse(self, spec.into())
+    }
+
+    /// Parse a revision specification that refers to a single object, like `main~2` or `HEAD:src/lib.rs`, and return its id.
+    ///
+    /// See [`rev_parse()`][crate::Repository::rev_parse()] for details on what's supported.
+    pub fn rev_parse_single<'a>(
+        &self,
+        spec: impl Into<&'a BStr>,
+    ) -> Result<crate::Id<'_>, revision::spec::parse::Error> {
+        revision::spec::single(self, spec.into())
+    }
+
+    /// Create the baseline for a revision walk by initializing it with the `tips` to start iterating on.
+    ///
+    /// It can be configured further before starting the actual walk, for example to [hide][revision::walk::Platform::hide()]
+    /// commits reachable from others to obtain ranges like `A..B`.
+    pub fn rev_walk(&self, tips: impl IntoIterator<Item = impl Into<ObjectId>>) -> revision::walk::Platform<'_> {
+        revision::walk::Platform::new(tips, self)
+    }
+
+    /// Return true if `ancestor` is reachable from `descendant` or if both are the same commit, similar to
+    /// `git merge-base --is-ancestor`.
+    ///
+    /// If a pack has a reachability bitmap, the answer is obtained from bitmaps of commits where possible. Otherwise, if the
+    /// repository has a commit-graph, parents are read from it and commits with a generation number lower than the one of
+    /// `ancestor` aren't traversed as they can't reach it, which usually avoids traversing the entire history.
+    pub fn is_ancestor(
+        &self,
+        ancestor: impl Into<ObjectId>,
+        descendant: impl Into<ObjectId>,
+    ) -> Result<bool, git_traverse::commit::ancestors::Error> {
+        let ancestor = ancestor.into();
+        let descendant = descendant.into();
+        let objects_dir = self.objects.store_ref().path();
+        if let Some(bitmap) = revision::reachable::Bitmap::open(objects_dir, self.object_hash()) {
+            // Errors are ignored as the walk below is able to report them.
+            if let Ok(Some(is_ancestor)) =
+                bitmap
+                    .file
+                    .is_reachable_from(&bitmap.index, &bitmap.order, ancestor, Some(descendant), &self.objects)
+            {
+                return Ok(is_ancestor);
+            }
+        }
+
+        let commit_graph = git_commitgraph::Graph::from_info_dir(objects_dir.join("info")).ok();
+        let min_generation = commit_graph
+            .as_ref()
+            .and_then(|graph| graph.commit_by_id(ancestor))
+            .map_or(0, |commit| commit.generation());
+        let ancestors = git_traverse::commit::Ancestors::new(
+            Some(descendant),
+            git_traverse::commit::ancestors::State::default(),
+            |oid, buf| self.objects.find_commit_iter(oid, buf),
+        )
+        .commit_graph(commit_graph)
+        .generation_cutoff(min_generation);
+        for id in ancestors {
+            if id? == ancestor {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Return all objects reachable from `tips`, but not from `haves`, including `tips` themselves unless they are reachable
+    /// from `haves`. This is useful to determine which objects to send to a remote that has `haves`.
+    ///
+    /// If a pack has a reachability bitmap, the objects are obtained with set operations on bitmaps of commits where possible,
+    /// falling back to traversing all reachable commits, trees and tags otherwise. The order of returned objects is undefined.
+    pub fn objects_reachable_from(
+        &self,
+        tips: impl IntoIterator<Item = impl Into<ObjectId>>,
+        haves: impl IntoIterator<Item = impl Into<ObjectId>>,
+    ) -> Result<Vec<ObjectId>, revision::reachable::Error> {
+        let tips: Vec<ObjectId> = tips.into_iter().map(Into::into).collect();
+        let haves: Vec<ObjectId> = haves.into_iter().map(Into::into).collect();
+        if let Some(bitmap) = revision::reachable::Bitmap::open(self.objects.store_ref().path(), self.object_hash()) {
+            if let Ok(Some(bits)) = bitmap.file.objects_reachable_from_excluding(
+                &bitmap.index,
+                &bitmap.order,
+                tips.iter().copied(),
+                haves.iter().copied(),
+                &self.objects,
+            ) {
+                return Ok(bitmap.order.ids(&bitmap.index, &bits).map(ToOwned::to_owned).collect());
+            }
+        }
+
+        let mut seen = HashSet::new();
+        revision::reachable::insert_objects_reachable_from(self, haves, &mut seen)?;
+        revision::reachable::insert_objects_reachable_from(self, tips, &mut seen)
+    }
+}
@@ -94,8 +94,14 @@ impl crate::Repository {
             .value::<git_config::values::Path<'_>>("mailmap", None, "file")
             .ok()
             .and_then(|path| {
+                use git_config::values::path::interpolate;
                 let install_dir = self.install_dir().ok()?;
-                match path.interpolate(Some(install_dir.as_path())) {
+                let home = interpolate::home_dir();
+                match path.interpolate(interpolate::Context {
+                    git_install_dir: Some(install_dir.as_path()),
+                    home_dir: home.as_deref(),
+                    home_for_user: Some(interpolate::home_for_user),
+                }) {
                     Ok(path) => Some(path),
                     Err(e) => {
                         err.get_or_insert(e.into());
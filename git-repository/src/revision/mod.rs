@@ -0,0 +1,15 @@
+//! Revisions is the generalized notion of a commit.
+//!
+//! This module provides utilities to parse revision specifications and to walk the revision graph, starting at one or
+//! more tips and optionally hiding all commits reachable from another set of commits.
+pub use spec::Spec;
+pub use walk::Walk;
+
+///
+pub mod spec;
+
+///
+pub mod walk;
+
+///
+pub mod reachable;
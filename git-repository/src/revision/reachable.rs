@@ -0,0 +1,88 @@
+use std::{collections::HashSet, path::Path};
+
+use git_hash::ObjectId;
+use git_object::{tree::EntryMode, Kind};
+use git_odb::FindExt;
+
+/// The error returned by [`Repository::objects_reachable_from()`][crate::Repository::objects_reachable_from()].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error(transparent)]
+    FindExisting(#[from] crate::object::find::existing::OdbError),
+    #[error(transparent)]
+    ObjectDecode(#[from] git_object::decode::Error),
+}
+
+/// A reachability bitmap along with the index of its pack and the pack order of the objects in it.
+pub(crate) struct Bitmap {
+    pub file: git_pack::bitmap::File,
+    pub index: git_pack::index::File,
+    pub order: git_pack::bitmap::PackOrder,
+}
+
+impl Bitmap {
+    /// Open the reachability bitmap of the first pack in `objects_dir` which has one, if any.
+    pub fn open(objects_dir: &Path, object_hash: git_hash::Kind) -> Option<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(objects_dir.join("pack"))
+            .ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "bitmap"))
+            .collect();
+        paths.sort();
+        paths.into_iter().find_map(|path| {
+            let index = git_pack::index::File::at(path.with_extension("idx"), object_hash).ok()?;
+            let file = git_pack::bitmap::File::at(&path, object_hash).ok()?;
+            (file.pack_checksum() == index.pack_checksum()).then(|| Bitmap {
+                order: git_pack::bitmap::PackOrder::from_index(&index),
+                file,
+                index,
+            })
+        })
+    }
+}
+
+/// Insert all objects reachable from `tips` into `seen` by decoding commits, trees and tags, without traversing objects
+/// that are in `seen` already, and return the ones that were inserted.
+pub(crate) fn insert_objects_reachable_from(
+    repo: &crate::Repository,
+    tips: impl IntoIterator<Item = ObjectId>,
+    seen: &mut HashSet<ObjectId>,
+) -> Result<Vec<ObjectId>, Error> {
+    let mut out = Vec::new();
+    let mut queue: Vec<ObjectId> = tips.into_iter().collect();
+    let mut buf = Vec::new();
+    while let Some(id) = queue.pop() {
+        if !seen.insert(id) {
+            continue;
+        }
+        out.push(id);
+        let object = repo.objects.find(&id, &mut buf)?;
+        match object.kind {
+            Kind::Commit => {
+                let mut commit = git_object::CommitRefIter::from_bytes(object.data);
+                queue.push(commit.tree_id()?);
+                queue.extend(commit.parent_ids());
+            }
+            Kind::Tag => queue.push(git_object::TagRefIter::from_bytes(object.data).target_id()?),
+            Kind::Tree => {
+                for entry in git_object::TreeRefIter::from_bytes(object.data) {
+                    let entry = entry?;
+                    match entry.mode {
+                        EntryMode::Tree => queue.push(entry.oid.to_owned()),
+                        EntryMode::Commit => {}
+                        _blob => {
+                            let id = entry.oid.to_owned();
+                            if seen.insert(id) {
+                                out.push(id);
+                            }
+                        }
+                    }
+                }
+            }
+            Kind::Blob => {}
+        }
+    }
+    Ok(out)
+}
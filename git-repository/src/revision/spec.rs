@@ -0,0 +1,195 @@
+use git_hash::ObjectId;
+use git_object::bstr::{BStr, ByteSlice};
+
+use crate::{ext::ObjectIdExt, object::Kind, Id};
+
+/// A revision specification as returned by [`Repository::rev_parse()`][crate::Repository::rev_parse()].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Spec<'repo> {
+    /// A single object, like `HEAD`, `main~2` or `v1.0^{tree}`.
+    Include(Id<'repo>),
+    /// All commits reachable from the object are excluded, like `^v1.0`.
+    Exclude(Id<'repo>),
+    /// All commits reachable from `to`, but not from `from`, like `v1.0..main`.
+    Range {
+        /// The commit whose history is excluded.
+        from: Id<'repo>,
+        /// The commit whose history is included.
+        to: Id<'repo>,
+    },
+}
+
+///
+pub mod parse {
+    use git_hash::ObjectId;
+    use git_object::bstr::BString;
+
+    /// The error returned by [`Repository::rev_parse()`][crate::Repository::rev_parse()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The revision '{}' is unsupported or malformed: {}", .spec, .message)]
+        Syntax { spec: BString, message: &'static str },
+        #[error("The revision '{}' could not be found", .name)]
+        NotFound { name: BString },
+        #[error("The short id {} is ambiguous", .prefix)]
+        AmbiguousPrefix { prefix: git_hash::Prefix },
+        #[error("Commit {} has no parent number {}", .id, .parent)]
+        MissingParent { id: ObjectId, parent: usize },
+        #[error("The path '{}' does not exist in tree {}", .path, .tree)]
+        PathNotFound { path: BString, tree: ObjectId },
+        #[error(transparent)]
+        FindReference(#[from] crate::reference::find::Error),
+        #[error(transparent)]
+        PeelReference(#[from] crate::reference::peel::Error),
+        #[error(transparent)]
+        FindExistingObject(#[from] crate::object::find::existing::OdbError),
+        #[error(transparent)]
+        FindObject(#[from] crate::object::find::OdbError),
+        #[error(transparent)]
+        PeelObject(#[from] crate::object::peel::to_kind::Error),
+        #[error(transparent)]
+        Decode(#[from] git_object::decode::Error),
+    }
+}
+
+/// Parse `spec` in the context of `repo`, supporting everything [`single()`] supports as well as `^<rev>`,
+/// `<rev>..<rev>` and `<rev>..` or `..<rev>`, where an omitted side defaults to `HEAD`.
+pub(crate) fn parse<'repo>(repo: &'repo crate::Repository, spec: &BStr) -> Result<Spec<'repo>, parse::Error> {
+    if let Some(excluded) = spec.strip_prefix(b"^") {
+        return Ok(Spec::Exclude(single(repo, excluded.as_bstr())?));
+    }
+    if spec.find(b"...").is_some() {
+        return Err(syntax_error(spec, "symmetric differences are not supported"));
+    }
+    match spec.find(b"..") {
+        Some(pos) => {
+            let side = |s: &[u8]| single(repo, if s.is_empty() { "HEAD".into() } else { s.as_bstr() });
+            Ok(Spec::Range {
+                from: side(&spec[..pos])?,
+                to: side(&spec[pos + 2..])?,
+            })
+        }
+        None => Ok(Spec::Include(single(repo, spec)?)),
+    }
+}
+
+/// Parse `spec` into a single object, supporting full and abbreviated object ids, reference names, `@` as shortcut for
+/// `HEAD`, any amount of `^<n>`, `~<n>` and `^{<kind>}` suffixes as well as `<rev>:<path>` to obtain a tree entry.
+pub(crate) fn single<'repo>(repo: &'repo crate::Repository, spec: &BStr) -> Result<Id<'repo>, parse::Error> {
+    let (rev, path) = match spec.find_byte(b':') {
+        Some(0) => return Err(syntax_error(spec, "looking up paths in the index is not supported")),
+        Some(pos) => (spec[..pos].as_bstr(), Some(spec[pos + 1..].as_bstr())),
+        None => (spec, None),
+    };
+    if rev.find(b"@{").is_some() {
+        return Err(syntax_error(spec, "reflog lookups are not supported"));
+    }
+    let base_end = rev.find_byteset(b"^~").unwrap_or(rev.len());
+    let mut id = resolve(repo, rev[..base_end].as_bstr())?;
+
+    let mut suffixes: &[u8] = &rev[base_end..];
+    while let Some((&navigation, rest)) = suffixes.split_first() {
+        if navigation == b'^' && rest.starts_with(b"{") {
+            let end = rest
+                .find_byte(b'}')
+                .ok_or_else(|| syntax_error(spec, "unterminated '^{'"))?;
+            let object = repo.find_object(id)?;
+            id = match &rest[1..end] {
+                b"" => object.peel_tags_to_end()?.id,
+                b"object" => object.id,
+                b"commit" => object.peel_to_kind(Kind::Commit)?.id,
+                b"tree" => object.peel_to_kind(Kind::Tree)?.id,
+                b"blob" => object.peel_to_kind(Kind::Blob)?.id,
+                b"tag" => object.peel_to_kind(Kind::Tag)?.id,
+                _ => return Err(syntax_error(spec, "unknown object kind in '^{<kind>}'")),
+            };
+            suffixes = &rest[end + 1..];
+            continue;
+        }
+
+        let num_digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+        let number = match num_digits {
+            0 => 1,
+            _ => rest[..num_digits]
+                .to_str()
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .ok_or_else(|| syntax_error(spec, "the number after '^' or '~' is too large"))?,
+        };
+        suffixes = &rest[num_digits..];
+        let commit = repo.find_object(id)?.peel_to_kind(Kind::Commit)?.into_commit();
+        id = match navigation {
+            b'^' if number == 0 => commit.id,
+            b'^' => commit
+                .parent_ids()
+                .nth(number - 1)
+                .map(|id| id.detach())
+                .ok_or(parse::Error::MissingParent {
+                    id: commit.id,
+                    parent: number,
+                })?,
+            _ => {
+                let mut id = commit.id;
+                for _ in 0..number {
+                    let commit = repo.find_object(id)?.into_commit();
+                    id = commit
+                        .parent_ids()
+                        .next()
+                        .map(|id| id.detach())
+                        .ok_or(parse::Error::MissingParent { id, parent: 1 })?;
+                }
+                id
+            }
+        };
+    }
+
+    if let Some(path) = path {
+        let tree = repo.find_object(id)?.peel_to_kind(Kind::Tree)?.into_tree();
+        let tree_id = tree.id;
+        let path = path.trim_with(|c| c == '/');
+        id = if path.is_empty() {
+            tree_id
+        } else {
+            tree.lookup_path(path.split(|b| *b == b'/'))?
+                .map(|entry| entry.oid)
+                .ok_or_else(|| parse::Error::PathNotFound {
+                    path: path.into(),
+                    tree: tree_id,
+                })?
+        };
+    }
+    Ok(id.attach(repo))
+}
+
+/// Resolve `name` like git does, trying full object ids first, reference names second and abbreviated object ids last.
+fn resolve(repo: &crate::Repository, name: &BStr) -> Result<ObjectId, parse::Error> {
+    let name = match name.as_bytes() {
+        b"" => return Err(syntax_error(name, "a revision must not be empty")),
+        b"@" => "HEAD".into(),
+        _ => name,
+    };
+    let is_hex = name.iter().all(|b| b.is_ascii_hexdigit());
+    if is_hex && name.len() == repo.object_hash().len_in_hex() {
+        return Ok(ObjectId::from_hex(name).expect("valid hex"));
+    }
+    if let Some(mut reference) = repo.try_find_reference(name)? {
+        return Ok(reference.peel_to_id_in_place()?.detach());
+    }
+    if is_hex && name.len() >= 4 {
+        let prefix = git_hash::Prefix::from_hex(name.to_str().expect("hex is valid UTF-8")).expect("valid hex prefix");
+        return match repo.objects.lookup_prefix(prefix)? {
+            Some(Ok(id)) => Ok(id),
+            Some(Err(())) => Err(parse::Error::AmbiguousPrefix { prefix }),
+            None => Err(parse::Error::NotFound { name: name.into() }),
+        };
+    }
+    Err(parse::Error::NotFound { name: name.into() })
+}
+
+fn syntax_error(spec: &[u8], message: &'static str) -> parse::Error {
+    parse::Error::Syntax {
+        spec: spec.into(),
+        message,
+    }
+}
@@ -0,0 +1,519 @@
+use std::{
+    cell::RefCell,
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap},
+    rc::Rc,
+};
+
+use git_hash::{oid, ObjectId};
+use git_object::{
+    bstr::{BString, ByteSlice},
+    WriteTo,
+};
+use git_odb::FindExt;
+use git_traverse::commit::{ancestors, Parents, Sorting};
+
+use crate::{ext::ObjectIdExt, Id};
+
+/// The error returned by [`Platform::all()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Failed to traverse the commits that are to be hidden")]
+    Hidden(#[from] ancestors::Error),
+}
+
+/// Information about a commit that was obtained during a revision walk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Info<'repo> {
+    /// The id of the commit.
+    pub id: Id<'repo>,
+    /// If true, this commit is not part of the range of commits to be returned, but is a direct parent of a commit
+    /// that is, and reachable from one of the hidden commits.
+    ///
+    /// Boundary commits are only returned if [`Platform::boundary()`] was enabled, and always after all other commits.
+    pub is_boundary: bool,
+}
+
+impl<'repo> Info<'repo> {
+    /// Lookup the commit this information refers to.
+    pub fn object(&self) -> Result<crate::Commit<'repo>, crate::object::find::existing::OdbError> {
+        self.id.object().map(|obj| obj.into_commit())
+    }
+}
+
+/// A platform to traverse the revision graph by adding starting points as well as points which shouldn't be crossed,
+/// returned by [`Repository::rev_walk()`][crate::Repository::rev_walk()].
+pub struct Platform<'repo> {
+    pub(crate) repo: &'repo crate::Repository,
+    pub(crate) tips: Vec<ObjectId>,
+    pub(crate) hidden: Vec<ObjectId>,
+    pub(crate) sorting: Sorting,
+    pub(crate) parents: Parents,
+    pub(crate) boundary: bool,
+    pub(crate) paths: Vec<BString>,
+    pub(crate) full_history: bool,
+}
+
+impl<'repo> Platform<'repo> {
+    pub(crate) fn new(tips: impl IntoIterator<Item = impl Into<ObjectId>>, repo: &'repo crate::Repository) -> Self {
+        Platform {
+            repo,
+            tips: tips.into_iter().map(Into::into).collect(),
+            hidden: Vec::new(),
+            sorting: Default::default(),
+            parents: Default::default(),
+            boundary: false,
+            paths: Vec::new(),
+            full_history: false,
+        }
+    }
+}
+
+/// Create-time builder methods
+impl<'repo> Platform<'repo> {
+    /// Set the sort mode for commits to the given value. The default is to order by topology.
+    pub fn sorting(mut self, sorting: Sorting) -> Self {
+        self.sorting = sorting;
+        self
+    }
+
+    /// Only traverse the first parent of the commit graph.
+    pub fn first_parent_only(mut self) -> Self {
+        self.parents = Parents::First;
+        self
+    }
+
+    /// Hide all commits reachable from the given `ids`, including the `ids` themselves, similar to `^id` or `--not id`
+    /// on the command-line. Can be called multiple times to add more commits to hide.
+    ///
+    /// This allows to express ranges like `A..B`, by using `B` as tip and hiding `A`.
+    pub fn hide(mut self, ids: impl IntoIterator<Item = impl Into<ObjectId>>) -> Self {
+        self.hidden.extend(ids.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only return commits which changed any of the given `paths` compared to their parents, similar to `git log -- <path>`.
+    /// Paths are relative to the root of the repository and use slashes as separators. A directory matches all paths within it.
+    /// Can be called multiple times to add more paths.
+    ///
+    /// A commit is considered to change a path if its entry differs from the one of all of its parents, or if it's a root
+    /// commit containing the path. History is simplified like git does by default, hence if a merge commit has the same
+    /// entries as one of its parents, only the first such parent is traversed. Use [`full_history()`][Platform::full_history()]
+    /// to traverse all parents instead.
+    ///
+    /// If the repository has a commit-graph with changed-path Bloom filters, most commits that didn't change any of
+    /// the paths can be skipped without looking at their trees, which speeds up the traversal considerably.
+    pub fn paths(mut self, paths: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        self.paths.extend(
+            paths
+                .into_iter()
+                .map(Into::into)
+                .map(|path| path.trim_with(|c| c == '/').as_bstr().to_owned()),
+        );
+        self
+    }
+
+    /// If `toggle` is true, all parents of merge commits are traversed when limiting the walk to [paths][Platform::paths()],
+    /// even if the merge commit has the same entries at these paths as one of its parents, similar to `git log --full-history`.
+    pub fn full_history(mut self, toggle: bool) -> Self {
+        self.full_history = toggle;
+        self
+    }
+
+    /// If `toggle` is true, hidden commits which are direct parents of returned commits will be returned as well,
+    /// after all other commits and marked as [boundary][Info::is_boundary].
+    pub fn boundary(mut self, toggle: bool) -> Self {
+        self.boundary = toggle;
+        self
+    }
+}
+
+/// Produce the iterator
+impl<'repo> Platform<'repo> {
+    /// Return an iterator to traverse all commits reachable as configured by the [Platform].
+    ///
+    /// Note that hidden commits are determined upfront by traversing the history of tips and hidden commits by commit date
+    /// until only hidden commits are left, similar to what git does. Thus the cost is proportional to the amount of commits
+    /// between them, not to the entire history of hidden commits.
+    pub fn all(self) -> Result<Walk<'repo>, Error> {
+        let Platform {
+            repo,
+            tips,
+            hidden,
+            sorting,
+            parents,
+            boundary,
+            paths,
+            full_history,
+        } = self;
+
+        let hidden = if hidden.is_empty() {
+            BTreeSet::new()
+        } else {
+            hidden_commits(repo, &tips, hidden)?
+        };
+        // Hidden tips aren't parents of returned commits and thus can't be part of the boundary.
+        let tips: Vec<_> = tips.into_iter().filter(|id| !hidden.contains(id)).collect();
+
+        let path_filter =
+            (!paths.is_empty()).then(|| Rc::new(PathFilter::new(repo, paths, matches!(parents, Parents::First))));
+        let simplify_with = path_filter
+            .clone()
+            .filter(|_| !full_history && !matches!(parents, Parents::First));
+        let boundary_commits = Rc::new(RefCell::new(Vec::new()));
+        let inner = git_traverse::commit::Ancestors::filtered(
+            tips,
+            ancestors::State::default(),
+            move |oid, buf| find_commit_simplified(repo, simplify_with.as_deref(), oid, buf),
+            {
+                let boundary_commits = Rc::clone(&boundary_commits);
+                move |id| {
+                    if hidden.contains(id) {
+                        if boundary {
+                            boundary_commits.borrow_mut().push(id.to_owned());
+                        }
+                        false
+                    } else {
+                        true
+                    }
+                }
+            },
+        )
+        .sorting(sorting)
+        .parents(parents);
+
+        Ok(Walk {
+            repo,
+            inner: Box::new(inner),
+            path_filter,
+            boundary_commits,
+            is_boundary_pass: false,
+        })
+    }
+}
+
+/// The amount of commits to keep traversing once only hidden commits are left, to be resilient to some clock skew like git.
+const SLOP: usize = 5;
+
+/// Return all commits reachable from `hidden` that might be reachable from `tips` as well.
+///
+/// Commits are traversed in order of their commit date and marked as hidden if they are reachable from a hidden commit.
+/// Once only hidden commits are left to be traversed, no further commit reachable from `tips` can be hidden, unless commit
+/// dates are skewed by more than a few commits.
+fn hidden_commits(
+    repo: &crate::Repository,
+    tips: &[ObjectId],
+    hidden: Vec<ObjectId>,
+) -> Result<BTreeSet<ObjectId>, ancestors::Error> {
+    struct Commit {
+        is_hidden: bool,
+        is_queued: bool,
+        parents: Vec<ObjectId>,
+    }
+    let mut commits = HashMap::<ObjectId, Commit>::new();
+    let mut queue = BinaryHeap::<(u32, Reverse<usize>, ObjectId)>::new();
+    let mut num_pushed = 0;
+    // The amount of queued commits which aren't hidden, to know when only hidden commits are left.
+    let mut num_visible_queued = 0;
+    let mut buf = Vec::new();
+    let mut push = |id: ObjectId,
+                    is_hidden: bool,
+                    commits: &mut HashMap<ObjectId, Commit>,
+                    queue: &mut BinaryHeap<(u32, Reverse<usize>, ObjectId)>|
+     -> Result<(), ancestors::Error> {
+        let commit = repo
+            .objects
+            .find_commit_iter(id, &mut buf)
+            .map_err(|err| ancestors::Error::FindExisting {
+                oid: id,
+                err: err.into(),
+            })?;
+        let parents = commit.parent_ids().collect();
+        let date = commit.committer()?.time.seconds_since_unix_epoch;
+        commits.insert(
+            id,
+            Commit {
+                is_hidden,
+                is_queued: true,
+                parents,
+            },
+        );
+        queue.push((date, Reverse(num_pushed), id));
+        num_pushed += 1;
+        Ok(())
+    };
+
+    for (id, is_hidden) in hidden
+        .into_iter()
+        .map(|id| (id, true))
+        .chain(tips.iter().map(|id| (*id, false)))
+    {
+        match commits.get_mut(&id) {
+            Some(commit) => {
+                if is_hidden && !commit.is_hidden {
+                    commit.is_hidden = true;
+                    num_visible_queued -= 1;
+                }
+            }
+            None => {
+                push(id, is_hidden, &mut commits, &mut queue)?;
+                if !is_hidden {
+                    num_visible_queued += 1;
+                }
+            }
+        }
+    }
+
+    let mut oldest_visible_date = u32::MAX;
+    let mut slop = SLOP;
+    while let Some((date, _, id)) = queue.pop() {
+        let (is_hidden, parents) = {
+            let commit = commits.get_mut(&id).expect("queued commits are known");
+            commit.is_queued = false;
+            (commit.is_hidden, commit.parents.clone())
+        };
+        if !is_hidden {
+            num_visible_queued -= 1;
+        }
+        if is_hidden {
+            let mut to_hide = parents;
+            while let Some(id) = to_hide.pop() {
+                match commits.get_mut(&id) {
+                    Some(commit) if commit.is_hidden => {}
+                    Some(commit) => {
+                        commit.is_hidden = true;
+                        if commit.is_queued {
+                            num_visible_queued -= 1;
+                        }
+                        to_hide.extend(commit.parents.iter().copied());
+                    }
+                    None => push(id, true, &mut commits, &mut queue)?,
+                }
+            }
+        } else {
+            oldest_visible_date = oldest_visible_date.min(date);
+            for parent_id in parents {
+                if !commits.contains_key(&parent_id) {
+                    push(parent_id, false, &mut commits, &mut queue)?;
+                    num_visible_queued += 1;
+                }
+            }
+        }
+
+        if num_visible_queued == 0 {
+            match queue.peek() {
+                Some((newest_date, _, _)) if *newest_date > oldest_visible_date => slop = SLOP,
+                Some(_) => {
+                    slop -= 1;
+                    if slop == 0 {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        } else {
+            slop = SLOP;
+        }
+    }
+    Ok(commits
+        .into_iter()
+        .filter_map(|(id, commit)| commit.is_hidden.then(|| id))
+        .collect())
+}
+
+/// The iterator returned by [`Platform::all()`], yielding [`Info`] about each commit.
+pub struct Walk<'repo> {
+    repo: &'repo crate::Repository,
+    inner: Box<dyn Iterator<Item = Result<ObjectId, ancestors::Error>> + 'repo>,
+    path_filter: Option<Rc<PathFilter>>,
+    boundary_commits: Rc<RefCell<Vec<ObjectId>>>,
+    is_boundary_pass: bool,
+}
+
+impl<'repo> Iterator for Walk<'repo> {
+    type Item = Result<Info<'repo>, ancestors::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.is_boundary_pass {
+            match self.inner.next() {
+                Some(Ok(id)) => {
+                    if let Some(filter) = self.path_filter.as_ref() {
+                        match filter.is_changed_by(self.repo, &id) {
+                            Ok(true) => {}
+                            Ok(false) => continue,
+                            Err(err) => return Some(Err(err)),
+                        }
+                    }
+                    return Some(Ok(Info {
+                        id: id.attach(self.repo),
+                        is_boundary: false,
+                    }));
+                }
+                Some(Err(err)) => return Some(Err(err)),
+                None => {
+                    self.is_boundary_pass = true;
+                    self.boundary_commits.borrow_mut().reverse();
+                }
+            }
+        }
+        self.boundary_commits.borrow_mut().pop().map(|id| {
+            Ok(Info {
+                id: id.attach(self.repo),
+                is_boundary: true,
+            })
+        })
+    }
+}
+
+/// Find the commit with `id` and write it into `buf`. If `filter` is set and the commit is a merge with the same entries
+/// at the filter's paths as one of its parents, it's rewritten to only have the first such parent to simplify history.
+fn find_commit_simplified<'a>(
+    repo: &crate::Repository,
+    filter: Option<&PathFilter>,
+    id: &oid,
+    buf: &'a mut Vec<u8>,
+) -> Result<git_object::CommitRefIter<'a>, ancestors::Error> {
+    let mut commit = repo
+        .objects
+        .find_commit_iter(id, &mut *buf)
+        .map_err(|err| ancestors::Error::FindExisting {
+            oid: id.to_owned(),
+            err: err.into(),
+        })?;
+    if let Some(filter) = filter {
+        let tree_id = commit.tree_id()?;
+        let parent_ids: Vec<_> = commit.parent_ids().collect();
+        if parent_ids.len() > 1 {
+            if let Some(treesame_parent_id) = filter.first_treesame_parent(repo, id, tree_id, &parent_ids)? {
+                let mut commit: git_object::Commit = git_object::CommitRef::from_bytes(buf)?.into();
+                commit.parents.retain(|parent_id| *parent_id == treesame_parent_id);
+                commit.parents.truncate(1);
+                buf.clear();
+                commit.write_to(&mut *buf).expect("writing to memory never fails");
+            }
+        }
+    }
+    Ok(git_object::CommitRefIter::from_bytes(buf))
+}
+
+/// Decides whether commits change any of a set of paths, using changed-path Bloom filters if available.
+struct PathFilter {
+    paths: Vec<BString>,
+    first_parent_only: bool,
+    bloom: Option<(git_commitgraph::Graph, Vec<Vec<git_commitgraph::bloom::Key>>)>,
+}
+
+impl PathFilter {
+    fn new(repo: &crate::Repository, paths: Vec<BString>, first_parent_only: bool) -> Self {
+        let bloom = git_commitgraph::Graph::from_info_dir(repo.objects.store_ref().path().join("info"))
+            .ok()
+            .and_then(|graph| {
+                let settings = graph.bloom_filter_settings()?;
+                let keys = paths
+                    .iter()
+                    .map(|path| git_commitgraph::bloom::Key::with_leading_directories(path, &settings))
+                    .collect();
+                Some((graph, keys))
+            });
+        PathFilter {
+            paths,
+            first_parent_only,
+            bloom,
+        }
+    }
+
+    fn is_changed_by(&self, repo: &crate::Repository, id: &oid) -> Result<bool, ancestors::Error> {
+        if let Some((graph, keys)) = &self.bloom {
+            // Filters are computed against the first parent, so if it is unchanged the commit is TREESAME to it.
+            if keys
+                .iter()
+                .all(|keys| graph.maybe_changed_path(id, keys) == Some(false))
+            {
+                return Ok(false);
+            }
+        }
+
+        let mut buf = Vec::new();
+        let mut commit = repo
+            .objects
+            .find_commit_iter(id, &mut buf)
+            .map_err(|err| ancestors::Error::FindExisting {
+                oid: id.to_owned(),
+                err: err.into(),
+            })?;
+        let tree_id = commit.tree_id()?;
+        let mut parent_ids: Vec<_> = commit.parent_ids().collect();
+        if self.first_parent_only {
+            parent_ids.truncate(1);
+        }
+
+        if parent_ids.is_empty() {
+            return Ok(self.entries_at(repo, tree_id)?.iter().any(Option::is_some));
+        }
+        Ok(self.first_treesame_parent(repo, id, tree_id, &parent_ids)?.is_none())
+    }
+
+    /// Return the first of `parent_ids` of the commit with `id` and `tree_id` which has the same entries at our paths.
+    fn first_treesame_parent(
+        &self,
+        repo: &crate::Repository,
+        id: &oid,
+        tree_id: ObjectId,
+        parent_ids: &[ObjectId],
+    ) -> Result<Option<ObjectId>, ancestors::Error> {
+        if let (Some((graph, keys)), Some(first_parent_id)) = (&self.bloom, parent_ids.first()) {
+            if keys
+                .iter()
+                .all(|keys| graph.maybe_changed_path(id, keys) == Some(false))
+            {
+                return Ok(Some(*first_parent_id));
+            }
+        }
+
+        let entries = self.entries_at(repo, tree_id)?;
+        for parent_id in parent_ids {
+            let mut buf = Vec::new();
+            let parent_tree_id = repo
+                .objects
+                .find_commit_iter(parent_id, &mut buf)
+                .map_err(|err| ancestors::Error::FindExisting {
+                    oid: *parent_id,
+                    err: err.into(),
+                })?
+                .tree_id()?;
+            if parent_tree_id == tree_id || self.entries_at(repo, parent_tree_id)? == entries {
+                return Ok(Some(*parent_id));
+            }
+        }
+        Ok(None)
+    }
+
+    fn entries_at(
+        &self,
+        repo: &crate::Repository,
+        tree_id: ObjectId,
+    ) -> Result<Vec<Option<git_object::tree::Entry>>, ancestors::Error> {
+        self.paths
+            .iter()
+            .map(|path| {
+                let tree = repo
+                    .find_object(tree_id)
+                    .map_err(|err| ancestors::Error::FindExisting {
+                        oid: tree_id,
+                        err: err.into(),
+                    })?
+                    .try_into_tree()
+                    .map_err(|err| ancestors::Error::FindExisting {
+                        oid: tree_id,
+                        err: err.into(),
+                    })?;
+                tree.lookup_path(path.split(|b| *b == b'/'))
+                    .map_err(|err| ancestors::Error::FindExisting {
+                        oid: tree_id,
+                        err: err.into(),
+                    })
+            })
+            .collect()
+    }
+}
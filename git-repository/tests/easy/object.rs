@@ -35,13 +35,198 @@ mod commit {
         assert_eq!(commit.decode()?.message, "c2\n");
         Ok(())
     }
+
+    #[test]
+    #[cfg(feature = "git-mailmap")]
+    fn signatures_can_be_mapped_with_the_mailmap_of_the_work_tree() -> crate::Result {
+        let (repo, _keep) = crate::basic_rw_repo()?;
+        std::fs::write(
+            repo.work_dir().expect("non-bare").join(".mailmap"),
+            "Canonical Author <canonical@example.com> <AUTHOR@example.com>\n",
+        )?;
+        let mailmap = repo.load_mailmap();
+        let commit = repo.head_commit()?;
+
+        let author = commit.author_mailmapped(&mailmap)?;
+        assert_eq!(author.name, "Canonical Author");
+        assert_eq!(author.email, "canonical@example.com");
+        assert_eq!(author.time, commit.author()?.time, "the time is retained");
+
+        let committer = commit.committer_mailmapped(&mailmap)?;
+        assert_eq!(
+            committer,
+            commit.committer()?.to_owned(),
+            "signatures without mapping are returned as they are"
+        );
+        Ok(())
+    }
+}
+
+mod replacements {
+    use git_repository::ThreadSafeRepository;
+    use git_testtools::hex_to_id;
+
+    fn parent_ids_of_head(repo: &git_repository::Repository) -> crate::Result<Vec<git_repository::hash::ObjectId>> {
+        let commit = repo.head_commit()?;
+        assert_eq!(
+            commit.id,
+            hex_to_id("c05afa735e895a60a2eec1d8a7dcb63ae173c210"),
+            "the replaced commit keeps its id"
+        );
+        Ok(commit.parent_ids().map(|id| id.detach()).collect())
+    }
+
+    #[test]
+    fn replaced_objects_are_substituted_unless_disabled_in_configuration() -> crate::Result {
+        let (repo, tmp) = crate::repo_rw("make_repo_with_replaced_objects.sh")?;
+        assert_eq!(
+            parent_ids_of_head(&repo)?,
+            vec![hex_to_id("e376f96e6a7f1c9335ca16c3f62e172166146bda")],
+            "the graft made c1 the parent of c3"
+        );
+
+        let config_path = repo.git_dir().join("config");
+        let mut config = std::fs::read(&config_path)?;
+        config.extend_from_slice(b"[core]\n\tuseReplaceRefs = false\n");
+        std::fs::write(&config_path, config)?;
+
+        let repo = ThreadSafeRepository::open(tmp.path())?.to_thread_local();
+        assert_eq!(
+            parent_ids_of_head(&repo)?,
+            vec![hex_to_id("3189cd3cb0af8586c39a838aa3e54fd72a872a41")],
+            "the original commit has c2 as parent"
+        );
+        Ok(())
+    }
+}
+
+mod prune {
+    use std::sync::atomic::AtomicBool;
+
+    use git_repository::{odb::loose::prune, prelude::Find, progress};
+
+    fn prune(repo: &git_repository::Repository, options: prune::Options) -> crate::Result<prune::Outcome> {
+        Ok(repo.prune_loose_objects(options, progress::Discard, &AtomicBool::default())?)
+    }
+
+    #[test]
+    fn unreachable_loose_objects_are_removed_after_the_grace_period() -> crate::Result {
+        let (repo, _tmp) = crate::basic_rw_repo()?;
+        let unreachable = repo
+            .write_object(&git_repository::objs::Blob {
+                data: b"unreachable".to_vec(),
+            })?
+            .detach();
+
+        let outcome = prune(
+            &repo,
+            prune::Options {
+                expire: std::time::Duration::from_secs(0),
+                dry_run: true,
+            },
+        )?;
+        assert_eq!(
+            outcome.num_objects, 7,
+            "2 commits, 2 trees, 2 blobs and the unreachable one"
+        );
+        assert_eq!(outcome.pruned, vec![unreachable]);
+        assert!(repo.objects.contains(unreachable), "dry-runs don't delete anything");
+
+        let outcome = prune(&repo, prune::Options::default())?;
+        assert!(
+            outcome.pruned.is_empty(),
+            "new objects are protected by the grace period"
+        );
+
+        let outcome = prune(
+            &repo,
+            prune::Options {
+                expire: std::time::Duration::from_secs(0),
+                dry_run: false,
+            },
+        )?;
+        assert_eq!(outcome.pruned, vec![unreachable]);
+        assert!(!repo.objects.contains(unreachable));
+        assert!(
+            !repo.head_commit()?.tree()?.traverse().breadthfirst.files()?.is_empty(),
+            "reachable objects are still present"
+        );
+        Ok(())
+    }
+}
+
+mod fsck {
+    use std::sync::atomic::AtomicBool;
+
+    use git_repository::{
+        fsck::{Finding, Options, Problem},
+        objs::Kind,
+        prelude::Write,
+        progress,
+    };
+
+    fn fsck(repo: &git_repository::Repository) -> crate::Result<git_repository::fsck::Outcome> {
+        Ok(repo.fsck(Options::default(), progress::Discard, &AtomicBool::default())?)
+    }
+
+    #[test]
+    fn a_fresh_repository_is_clean() -> crate::Result {
+        let (repo, _tmp) = crate::basic_rw_repo()?;
+        let outcome = fsck(&repo)?;
+        assert_eq!(outcome.num_reachable_objects, 6, "2 commits, 2 trees and 2 blobs");
+        assert!(outcome.is_clean(), "{:?}", outcome.findings);
+        Ok(())
+    }
+
+    #[test]
+    fn unreachable_and_invalid_objects_are_reported() -> crate::Result {
+        let (repo, _tmp) = crate::basic_rw_repo()?;
+        let blob = repo
+            .write_object(&git_repository::objs::Blob {
+                data: b"unreachable".to_vec(),
+            })?
+            .detach();
+        let mut tree = b"100644 .git\0".to_vec();
+        tree.extend_from_slice(blob.as_bytes());
+        tree.extend_from_slice(b"100664 a\0");
+        tree.extend_from_slice(blob.as_bytes());
+        let tree = repo.objects.write_buf(Kind::Tree, &tree)?;
+
+        let outcome = fsck(&repo)?;
+        assert_eq!(outcome.num_reachable_objects, 6, "unreachable objects aren't counted");
+        let mut expected = vec![
+            Finding::Invalid {
+                id: tree,
+                problem: Problem::TreeEntryMode {
+                    filename: "a".into(),
+                    mode: "100664".into(),
+                },
+            },
+            Finding::Invalid {
+                id: tree,
+                problem: Problem::TreeEntryName {
+                    filename: ".git".into(),
+                },
+            },
+            Finding::Dangling {
+                id: tree,
+                kind: Kind::Tree,
+            },
+        ];
+        expected.sort();
+        assert_eq!(
+            outcome.findings, expected,
+            "the blob is referred to by the dangling tree, which has two problems"
+        );
+        Ok(())
+    }
 }
 
 #[test]
 fn object_ref_size_in_memory() {
     assert_eq!(
         std::mem::size_of::<git_repository::Object<'_>>(),
-        56,
+        72,
         "the size of this structure should not changed unexpectedly"
     )
 }
@@ -50,7 +235,7 @@ fn object_ref_size_in_memory() {
 fn oid_size_in_memory() {
     assert_eq!(
         std::mem::size_of::<git_repository::Id<'_>>(),
-        32,
+        48,
         "the size of this structure should not changed unexpectedly"
     )
 }
@@ -0,0 +1,28 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config merge.ff false
+
+git checkout -q -b main
+mkdir dir
+echo a > a
+echo b > dir/b
+git add .
+git commit -q -m c1
+
+echo a2 > a
+git commit -q -am c2
+
+git checkout -q -b side
+echo b2 > dir/b
+git commit -q -am c3
+
+git checkout -q main
+echo a3 > a
+git commit -q -am c4
+
+git merge -q side -m merge
+
+git commit-graph write --no-progress --reachable --changed-paths
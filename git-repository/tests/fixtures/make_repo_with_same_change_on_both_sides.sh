@@ -0,0 +1,23 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config merge.ff false
+
+git checkout -q -b main
+echo a > a
+echo b > b
+git add .
+git commit -q -m c1
+
+git checkout -q -b side
+echo a2 > a
+git commit -q -am c2
+
+git checkout -q main
+echo a2 > a
+echo b2 > b
+git commit -q -am c3
+
+git merge -q side -m merge
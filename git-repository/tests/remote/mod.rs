@@ -0,0 +1,65 @@
+use std::io::Write;
+
+fn repo_with_config(config: &str) -> crate::Result<(git_repository::Repository, tempfile::TempDir)> {
+    let tmp = tempfile::tempdir()?;
+    let repo = git_repository::init(tmp.path())?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(repo.git_dir().join("config"))?
+        .write_all(config.as_bytes())?;
+    Ok((git_repository::open(repo.git_dir())?, tmp))
+}
+
+#[test]
+fn names_are_listed_in_order_of_appearance() -> crate::Result {
+    let (repo, _tmp) = repo_with_config(
+        "[remote \"origin\"]\n\turl = https://example.com/a\n[remote \"fork\"]\n\turl = https://example.com/b\n[remote \"origin\"]\n\tfetch = +refs/tags/*:refs/tags/*\n",
+    )?;
+    assert_eq!(repo.remote_names(), vec!["origin".to_string(), "fork".into()]);
+    Ok(())
+}
+
+#[test]
+fn find_combines_sections_and_applies_url_rewrites() -> crate::Result {
+    let (repo, _tmp) = repo_with_config(
+        "[remote \"origin\"]\n\turl = gh:byron/gitoxide\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n[url \"https://github.com/\"]\n\tinsteadOf = gh:\n[url \"ssh://git@github.com/\"]\n\tpushInsteadOf = gh:\n[remote \"origin\"]\n\tfetch = +refs/tags/*:refs/tags/*\n",
+    )?;
+    let remote = repo.find_remote("origin")?.expect("present");
+    assert_eq!(remote.name, "origin");
+    assert_eq!(
+        remote.url.expect("set").to_bstring()?,
+        "https://github.com/byron/gitoxide"
+    );
+    assert_eq!(
+        remote.push_url.expect("set").to_bstring()?,
+        "ssh://git@github.com/byron/gitoxide"
+    );
+    assert_eq!(
+        remote
+            .fetch_specs
+            .iter()
+            .map(|spec| spec.to_ref().to_bstring())
+            .collect::<Vec<_>>(),
+        vec!["+refs/heads/*:refs/remotes/origin/*", "+refs/tags/*:refs/tags/*"]
+    );
+    assert!(remote.push_specs.is_empty());
+    Ok(())
+}
+
+#[test]
+fn find_returns_none_for_unknown_remotes() -> crate::Result {
+    let (repo, _tmp) = repo_with_config("[remote \"origin\"]\n\turl = https://example.com/a\n")?;
+    assert!(repo.find_remote("upstream")?.is_none());
+    Ok(())
+}
+
+#[test]
+fn find_fails_on_invalid_refspecs() -> crate::Result {
+    let (repo, _tmp) =
+        repo_with_config("[remote \"origin\"]\n\turl = https://example.com/a\n\tfetch = refs/heads/*:refs/x\n")?;
+    assert!(matches!(
+        repo.find_remote("origin"),
+        Err(git_repository::remote::find::Error::RefSpec { .. })
+    ));
+    Ok(())
+}
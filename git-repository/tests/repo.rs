@@ -27,3 +27,5 @@ mod discover;
 mod easy;
 mod init;
 mod reference;
+mod remote;
+mod revision;
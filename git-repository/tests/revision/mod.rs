@@ -0,0 +1,306 @@
+mod walk {
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn hidden_commits_and_their_ancestors_are_excluded_and_can_be_marked_as_boundary() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let merge = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+        let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+
+        let ids = repo
+            .rev_walk(Some(merge))
+            .hide(Some(c2))
+            .all()?
+            .map(|info| info.map(|info| info.id.detach()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            ids,
+            vec![merge, hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac")],
+            "only commits that aren't reachable from c2 are returned"
+        );
+
+        let boundary = repo
+            .rev_walk(Some(merge))
+            .hide(Some(c2))
+            .boundary(true)
+            .all()?
+            .filter_map(|info| info.ok().filter(|info| info.is_boundary).map(|info| info.id.detach()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            boundary,
+            vec![c2, hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03")],
+            "hidden parents of returned commits are the boundary"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn hidden_tips_yield_nothing_not_even_a_boundary() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let merge = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+        let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+
+        for (tip, hidden) in [(merge, merge), (c2, merge)] {
+            assert_eq!(
+                repo.rev_walk(Some(tip))
+                    .hide(Some(hidden))
+                    .boundary(true)
+                    .all()?
+                    .count(),
+                0,
+                "the tip is reachable from a hidden commit"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_tips_are_traversed_once() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let tips = [
+            hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7"),
+            hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac"),
+        ];
+        assert_eq!(
+            repo.rev_walk(tips.iter().copied()).all()?.count(),
+            3,
+            "c1 is shared between both tips"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn paths_limit_the_walk_to_commits_changing_them_with_and_without_bloom_filters() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_repo_with_changed_paths.sh")?;
+        let merge = hex_to_id("6f96616331c4377164b500a36210e3780a6082dd");
+        let c1 = hex_to_id("7485b1bafeaff71f32550d87cf5f08064f8ebe82");
+        let c2 = hex_to_id("1c412dbcabb5d52131e2d4f5c0cf11f77a164199");
+        let c3 = hex_to_id("6c706914cc2eb0235620431755cc5543cca2a859");
+        let c4 = hex_to_id("2519278c2e699117554155a7942abae4193edf19");
+
+        let walk = |path: &str| -> crate::Result<Vec<git_repository::hash::ObjectId>> {
+            Ok(repo
+                .rev_walk(Some(merge))
+                .paths(Some(path))
+                .all()?
+                .map(|info| info.map(|info| info.id.detach()))
+                .collect::<Result<_, _>>()?)
+        };
+
+        let commit_graph = repo.objects.store_ref().path().join("info").join("commit-graph");
+        for with_commit_graph in &[true, false] {
+            if !with_commit_graph {
+                std::fs::remove_file(&commit_graph)?;
+            }
+            assert_eq!(
+                walk("dir")?,
+                vec![c3, c1],
+                "the merge is like its second parent, hence it didn't change 'dir'"
+            );
+            assert_eq!(walk("dir/b/")?, vec![c3, c1], "trailing slashes are ignored");
+            assert_eq!(walk("a")?, vec![c4, c2, c1]);
+            assert!(walk("missing")?.is_empty());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn paths_simplify_history_unless_full_history_is_requested() -> crate::Result {
+        let repo = crate::repo("make_repo_with_same_change_on_both_sides.sh")?.to_thread_local();
+        let merge = hex_to_id("61d446021c5a12f0e71879c8356234e79c131b60");
+        let c1 = hex_to_id("861301cf1477690d907c289d92940f897990c54b");
+        let c2 = hex_to_id("94e3e12cd41297a777e85ce7965c1056d0e50b7e");
+        let c3 = hex_to_id("08d82b75d34ecc3814ce9d5eba8b07660e7a1a0f");
+
+        let walk = |full_history: bool| -> crate::Result<Vec<git_repository::hash::ObjectId>> {
+            Ok(repo
+                .rev_walk(Some(merge))
+                .paths(Some("a"))
+                .full_history(full_history)
+                .all()?
+                .map(|info| info.map(|info| info.id.detach()))
+                .collect::<Result<_, _>>()?)
+        };
+
+        assert_eq!(
+            walk(false)?,
+            vec![c3, c1],
+            "the merge is like its first parent, so the side branch isn't traversed"
+        );
+        assert_eq!(
+            walk(true)?,
+            vec![c3, c2, c1],
+            "with full history, all parents are traversed"
+        );
+        Ok(())
+    }
+}
+
+mod is_ancestor {
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn with_and_without_commit_graph() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_repo_with_changed_paths.sh")?;
+        let merge = hex_to_id("6f96616331c4377164b500a36210e3780a6082dd");
+        let c1 = hex_to_id("7485b1bafeaff71f32550d87cf5f08064f8ebe82");
+        let c3 = hex_to_id("6c706914cc2eb0235620431755cc5543cca2a859");
+        let c4 = hex_to_id("2519278c2e699117554155a7942abae4193edf19");
+
+        let commit_graph = repo.objects.store_ref().path().join("info").join("commit-graph");
+        for with_commit_graph in &[true, false] {
+            if !with_commit_graph {
+                std::fs::remove_file(&commit_graph)?;
+            }
+            assert!(repo.is_ancestor(c1, merge)?);
+            assert!(repo.is_ancestor(c3, merge)?);
+            assert!(repo.is_ancestor(c1, c1)?, "a commit is its own ancestor");
+            assert!(!repo.is_ancestor(merge, c1)?);
+            assert!(
+                !repo.is_ancestor(c3, c4)?,
+                "commits on different branches don't reach each other"
+            );
+        }
+        Ok(())
+    }
+}
+
+mod objects_reachable_from {
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn with_and_without_bitmaps() -> crate::Result {
+        let (repo, _keep) = crate::repo_rw("make_packed_repo_with_bitmap.sh")?;
+        let merge = hex_to_id("6f96616331c4377164b500a36210e3780a6082dd");
+        let c1 = hex_to_id("7485b1bafeaff71f32550d87cf5f08064f8ebe82");
+        let c2 = hex_to_id("1c412dbcabb5d52131e2d4f5c0cf11f77a164199");
+        let c3 = hex_to_id("6c706914cc2eb0235620431755cc5543cca2a859");
+        let c4 = hex_to_id("2519278c2e699117554155a7942abae4193edf19");
+
+        let pack_dir = repo.objects.store_ref().path().join("pack");
+        let bitmap = std::fs::read_dir(&pack_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?
+            .into_iter()
+            .find(|path| path.extension().and_then(|ext| ext.to_str()) == Some("bitmap"))
+            .expect("a bitmap was written");
+
+        let mut expected = None;
+        for with_bitmap in &[true, false] {
+            if !with_bitmap {
+                std::fs::remove_file(&bitmap)?;
+            }
+            assert!(repo.is_ancestor(c1, merge)?);
+            assert!(!repo.is_ancestor(c3, c4)?);
+
+            let mut objects = repo.objects_reachable_from(Some(merge), Some(c2))?;
+            assert_eq!(objects.len(), 9, "3 commits, 4 trees and 2 blobs");
+            assert!(objects.contains(&merge));
+            assert!(!objects.contains(&c2));
+            assert_eq!(
+                repo.objects_reachable_from(Some(c3), Some(c4))?.len(),
+                4,
+                "c3, its trees and the blob it changed"
+            );
+
+            objects.sort();
+            match &expected {
+                None => expected = Some(objects),
+                Some(expected) => assert_eq!(&objects, expected, "bitmaps and traversal yield the same objects"),
+            }
+        }
+        Ok(())
+    }
+}
+
+mod rev_parse {
+    use git_repository::revision::Spec;
+    use git_testtools::hex_to_id;
+
+    #[test]
+    fn names_prefixes_and_navigation() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let merge = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+        let b1c1 = hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac");
+        let c2 = hex_to_id("9902e3c3e8f0c569b4ab295ddf473e6de763e1e7");
+        let c1 = hex_to_id("134385f6d781b7e97062102c6a483440bfda2a03");
+
+        for (spec, expected) in [
+            ("main", merge),
+            ("@", merge),
+            ("refs/heads/branch1", b1c1),
+            ("288e509293165cb5630d08f4185bdf2445bf6170", merge),
+            ("9902e3c", c2),
+            ("main^", c2),
+            ("main^0", merge),
+            ("main^2", b1c1),
+            ("main~2", c1),
+            ("main^2~1", c1),
+            ("main^{commit}", merge),
+        ] {
+            assert_eq!(repo.rev_parse_single(spec)?, expected, "{}", spec);
+        }
+        assert_eq!(
+            repo.rev_parse_single("main^{tree}")?.object()?.kind,
+            git_repository::object::Kind::Tree
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ranges_and_exclusions() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        let merge = hex_to_id("288e509293165cb5630d08f4185bdf2445bf6170");
+        let b1c1 = hex_to_id("bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac");
+
+        match repo.rev_parse("branch1..main")? {
+            Spec::Range { from, to } => {
+                assert_eq!(from, b1c1);
+                assert_eq!(to, merge);
+            }
+            other => panic!("unexpected spec: {:?}", other),
+        }
+        match repo.rev_parse("branch1..")? {
+            Spec::Range { to, .. } => assert_eq!(to, merge, "an omitted side is HEAD"),
+            other => panic!("unexpected spec: {:?}", other),
+        }
+        assert_eq!(
+            repo.rev_parse("^branch1")?,
+            Spec::Exclude(repo.rev_parse_single("branch1")?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_or_invalid_specs_fail() -> crate::Result {
+        let repo = crate::repo("make_repo_with_fork_and_dates.sh")?.to_thread_local();
+        for spec in [
+            "main...branch1",
+            "main@{1}",
+            ":path",
+            "main^{foo}",
+            "main^3",
+            "main~10",
+            "does-not-exist",
+            "",
+        ] {
+            assert!(repo.rev_parse(spec).is_err(), "{}", spec);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn paths_in_trees() -> crate::Result {
+        let repo = crate::basic_repo()?;
+        let blob = repo.rev_parse_single("main:this")?.object()?;
+        assert_eq!(blob.kind, git_repository::object::Kind::Blob);
+        assert_eq!(blob.data, b"hello\n");
+        assert_eq!(
+            repo.rev_parse_single("main:")?,
+            repo.rev_parse_single("main^{tree}")?,
+            "an empty path is the tree itself"
+        );
+        assert!(repo.rev_parse_single("main:missing").is_err());
+        Ok(())
+    }
+}
@@ -73,9 +73,11 @@ impl SpawnProcessOnDemand {
             url: git_url::Url {
                 scheme: git_url::Scheme::File,
                 user: None,
+                password: None,
                 host: None,
                 port: None,
                 path: path.clone(),
+                serialize_alternative_form: false,
             },
             path,
             ssh_program: None,
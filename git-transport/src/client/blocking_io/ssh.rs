@@ -67,9 +67,11 @@ pub fn connect(
     let url = git_url::Url {
         scheme: git_url::Scheme::Ssh,
         user: user.map(Into::into),
+        password: None,
         host: Some(host.clone()),
         port,
         path: path.clone(),
+        serialize_alternative_form: false,
     };
     Ok(match args_and_env {
         Some((args, envs)) => blocking_io::file::SpawnProcessOnDemand::new_ssh(
@@ -32,9 +32,11 @@ where
                 git_url::Url {
                     scheme: git_url::Scheme::File,
                     user: None,
+                    password: None,
                     host: None,
                     port: None,
                     path: self.path.clone(),
+                    serialize_alternative_form: false,
                 }
                 .to_string()
             },
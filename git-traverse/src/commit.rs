@@ -5,6 +5,9 @@ pub struct Ancestors<Find, Predicate, StateMut> {
     state: StateMut,
     parents: Parents,
     sorting: Sorting,
+    is_sorted: bool,
+    cache: Option<git_commitgraph::Graph>,
+    min_generation: u32,
 }
 
 /// Specify how to handle commit parents during traversal.
@@ -13,6 +16,9 @@ pub enum Parents {
     /// Traverse all parents, useful for traversing the entire ancestry.
     All,
     /// Only traverse along the first parent, which commonly ignores all branches.
+    ///
+    /// This yields only the commits of the mainline of merge-heavy histories, like `git log --first-parent`, and can be
+    /// combined with any [sorting][Sorting].
     First,
 }
 
@@ -31,6 +37,19 @@ pub enum Sorting {
     ///
     /// Note that since only parents are looked up this ordering is partial.
     ByCommitterDate,
+    /// Commits are never returned before all of their children, and commits of one line of history are kept together,
+    /// matching the output of `git log --topo-order`.
+    ///
+    /// Note that this requires all commits to be traversed before the first one can be returned.
+    TopoOrder,
+    /// Commits are never returned before all of their children, but otherwise the commit with the most recent committer date
+    /// is returned first, and the one that was discovered first if dates are equal. This matches the output of `git log --date-order`.
+    ///
+    /// Unlike [`ByCommitterDate`][Sorting::ByCommitterDate], the order is total and doesn't depend on the order in which parents
+    /// are discovered, but it requires all commits to be traversed before the first one can be returned.
+    DateOrder,
+    /// Like [`DateOrder`][Sorting::DateOrder], but using the author date instead, matching the output of `git log --author-date-order`.
+    AuthorDateOrder,
 }
 
 impl Default for Sorting {
@@ -43,7 +62,8 @@ impl Default for Sorting {
 pub mod ancestors {
     use std::{
         borrow::BorrowMut,
-        collections::{BTreeSet, VecDeque},
+        cmp::Reverse,
+        collections::{BTreeSet, BinaryHeap, HashMap, VecDeque},
     };
 
     use git_hash::{oid, ObjectId};
@@ -66,6 +86,11 @@ pub mod ancestors {
                 source(err)
                 from()
             }
+            CommitGraph(err: git_commitgraph::file::commit::Error) {
+                display("A commit could not be read from the commit-graph")
+                source(err)
+                from()
+            }
         }
     }
 
@@ -77,6 +102,7 @@ pub mod ancestors {
         seen: BTreeSet<ObjectId>,
         parents_with_date: Vec<(ObjectId, u32)>,
         parents_buf: Vec<u8>,
+        parent_ids: Vec<ObjectId>,
     }
 
     impl State {
@@ -84,6 +110,7 @@ pub mod ancestors {
             self.next.clear();
             self.buf.clear();
             self.seen.clear();
+            self.parent_ids.clear();
         }
     }
 
@@ -99,6 +126,26 @@ pub mod ancestors {
             self.sorting = sorting;
             self
         }
+
+        /// Use the given commit-graph to look up the parents and committer dates of commits it contains instead of
+        /// decoding their objects, which speeds up the traversal considerably. Commits not contained in it are still
+        /// looked up with `find`, as are all commits when sorting by [author date][Sorting::AuthorDateOrder].
+        ///
+        /// Note that `find` isn't called for commits in the commit-graph, so it can't be used to alter them.
+        pub fn commit_graph(mut self, cache: Option<git_commitgraph::Graph>) -> Self {
+            self.cache = cache;
+            self
+        }
+
+        /// Don't traverse parents whose generation number in the [commit-graph][Self::commit_graph()] is lower than
+        /// `generation`, as they can't reach any commit with this or a higher generation number. This allows to stop early
+        /// when looking for a specific commit, for example to determine if it's reachable from our tips.
+        ///
+        /// Commits not contained in the commit-graph are always traversed, as are the tips.
+        pub fn generation_cutoff(mut self, generation: u32) -> Self {
+            self.min_generation = generation;
+            self
+        }
     }
 
     impl<Find, StateMut, E> Ancestors<Find, fn(&oid) -> bool, StateMut>
@@ -165,6 +212,9 @@ pub mod ancestors {
                 state,
                 parents: Default::default(),
                 sorting: Default::default(),
+                is_sorted: false,
+                cache: None,
+                min_generation: 0,
             }
         }
     }
@@ -179,13 +229,10 @@ pub mod ancestors {
         type Item = Result<ObjectId, Error>;
 
         fn next(&mut self) -> Option<Self::Item> {
-            if matches!(self.parents, Parents::First) {
-                self.next_by_topology()
-            } else {
-                match self.sorting {
-                    Sorting::Topological => self.next_by_topology(),
-                    Sorting::ByCommitterDate => self.next_by_commit_date(),
-                }
+            match self.sorting {
+                Sorting::Topological => self.next_by_topology(),
+                Sorting::ByCommitterDate => self.next_by_commit_date(),
+                Sorting::TopoOrder | Sorting::DateOrder | Sorting::AuthorDateOrder => self.next_by_topo_order(),
             }
         }
     }
@@ -203,36 +250,31 @@ pub mod ancestors {
             let res = state.next.pop_front();
 
             if let Some(oid) = res {
-                match (self.find)(&oid, &mut state.buf) {
-                    Ok(mut commit_iter) => {
-                        if let Some(Err(decode_tree_err)) = commit_iter.next() {
-                            return Some(Err(decode_tree_err.into()));
-                        }
-
-                        for token in commit_iter {
-                            match token {
-                                Ok(git_object::commit::ref_iter::Token::Parent { id }) => {
-                                    let parent = (self.find)(id.as_ref(), &mut state.parents_buf).ok();
-
-                                    let parent_committer_date = parent
-                                        .and_then(|parent| parent.committer().ok().map(|committer| committer.time));
-
-                                    if let Some(parent_committer_date) = parent_committer_date {
-                                        state
-                                            .parents_with_date
-                                            .push((id, parent_committer_date.seconds_since_unix_epoch));
-                                    }
-
-                                    if matches!(self.parents, Parents::First) {
-                                        break;
-                                    }
-                                }
-                                Ok(_unused_token) => break,
-                                Err(err) => return Some(Err(err.into())),
-                            }
-                        }
+                state.parent_ids.clear();
+                if let Err(err) = lookup_parents(
+                    &mut self.find,
+                    self.cache.as_ref(),
+                    &oid,
+                    &mut state.buf,
+                    self.parents,
+                    self.sorting,
+                    &mut state.parent_ids,
+                ) {
+                    return Some(Err(err));
+                }
+
+                for id in state.parent_ids.iter().copied() {
+                    let parent_committer_date = match self.cache.as_ref().and_then(|graph| graph.commit_by_id(id)) {
+                        Some(parent) => Some(parent.committer_timestamp() as u32),
+                        None => (self.find)(id.as_ref(), &mut state.parents_buf)
+                            .ok()
+                            .and_then(|parent| parent.committer().ok())
+                            .map(|committer| committer.time.seconds_since_unix_epoch),
+                    };
+
+                    if let Some(parent_committer_date) = parent_committer_date {
+                        state.parents_with_date.push((id, parent_committer_date));
                     }
-                    Err(err) => return Some(Err(Error::FindExisting { oid, err: err.into() })),
                 }
             }
 
@@ -243,7 +285,10 @@ pub mod ancestors {
                 let id = parent.0;
                 let was_inserted = state.seen.insert(id);
 
-                if was_inserted && (self.predicate)(&id) {
+                if was_inserted
+                    && !is_below_generation_cutoff(self.cache.as_ref(), self.min_generation, &id)
+                    && (self.predicate)(&id)
+                {
                     state.next.push_back(id);
                 }
             }
@@ -263,31 +308,213 @@ pub mod ancestors {
             let state = self.state.borrow_mut();
             let res = state.next.pop_front();
             if let Some(oid) = res {
-                match (self.find)(&oid, &mut state.buf) {
-                    Ok(mut commit_iter) => {
-                        if let Some(Err(decode_tree_err)) = commit_iter.next() {
-                            return Some(Err(decode_tree_err.into()));
-                        }
-                        for token in commit_iter {
-                            match token {
-                                Ok(git_object::commit::ref_iter::Token::Parent { id }) => {
-                                    let was_inserted = state.seen.insert(id);
-                                    if was_inserted && (self.predicate)(&id) {
-                                        state.next.push_back(id);
-                                    }
-                                    if matches!(self.parents, Parents::First) {
-                                        break;
-                                    }
-                                }
-                                Ok(_a_token_past_the_parents) => break,
-                                Err(err) => return Some(Err(err.into())),
-                            }
-                        }
+                state.parent_ids.clear();
+                if let Err(err) = lookup_parents(
+                    &mut self.find,
+                    self.cache.as_ref(),
+                    &oid,
+                    &mut state.buf,
+                    self.parents,
+                    self.sorting,
+                    &mut state.parent_ids,
+                ) {
+                    return Some(Err(err));
+                }
+                for id in state.parent_ids.iter().copied() {
+                    let was_inserted = state.seen.insert(id);
+                    if was_inserted
+                        && !is_below_generation_cutoff(self.cache.as_ref(), self.min_generation, &id)
+                        && (self.predicate)(&id)
+                    {
+                        state.next.push_back(id);
                     }
-                    Err(err) => return Some(Err(Error::FindExisting { oid, err: err.into() })),
                 }
             }
             res.map(Ok)
         }
     }
+
+    impl<Find, Predicate, StateMut, E> Ancestors<Find, Predicate, StateMut>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        Predicate: FnMut(&oid) -> bool,
+        StateMut: BorrowMut<State>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        fn next_by_topo_order(&mut self) -> Option<Result<ObjectId, Error>> {
+            if !self.is_sorted {
+                self.is_sorted = true;
+                if let Err(err) = self.sort_in_topo_order() {
+                    self.state.borrow_mut().next.clear();
+                    return Some(Err(err));
+                }
+            }
+            self.state.borrow_mut().next.pop_front().map(Ok)
+        }
+
+        /// Traverse all commits reachable from our tips and place them into `next` in the order they are to be returned,
+        /// using Kahn's algorithm with a stack or a queue prioritized by date just like git does.
+        fn sort_in_topo_order(&mut self) -> Result<(), Error> {
+            let state = self.state.borrow_mut();
+            let mut commits = Vec::<(ObjectId, Vec<ObjectId>, u32)>::new();
+            let mut queue = std::mem::take(&mut state.next);
+            while let Some(oid) = queue.pop_front() {
+                let mut parents = Vec::new();
+                let date = lookup_parents(
+                    &mut self.find,
+                    self.cache.as_ref(),
+                    &oid,
+                    &mut state.buf,
+                    self.parents,
+                    self.sorting,
+                    &mut parents,
+                )?;
+                for id in parents.iter().copied() {
+                    let was_inserted = state.seen.insert(id);
+                    if was_inserted
+                        && !is_below_generation_cutoff(self.cache.as_ref(), self.min_generation, &id)
+                        && (self.predicate)(&id)
+                    {
+                        queue.push_back(id);
+                    }
+                }
+                commits.push((oid, parents, date));
+            }
+
+            let index_by_id: HashMap<ObjectId, usize> =
+                commits.iter().enumerate().map(|(idx, (id, _, _))| (*id, idx)).collect();
+            let mut num_children = vec![0usize; commits.len()];
+            for parent_idx in commits
+                .iter()
+                .flat_map(|(_, parents, _)| parents.iter().filter_map(|id| index_by_id.get(id)))
+            {
+                num_children[*parent_idx] += 1;
+            }
+
+            let mut ready = match self.sorting {
+                Sorting::DateOrder | Sorting::AuthorDateOrder => Ready::ByDate {
+                    queue: BinaryHeap::new(),
+                    num_pushed: 0,
+                },
+                _ => Ready::Stack(Vec::new()),
+            };
+            let mut tips: Vec<usize> = (0..commits.len()).filter(|idx| num_children[*idx] == 0).collect();
+            if let Ready::Stack(_) = ready {
+                tips.reverse();
+            }
+            for idx in tips {
+                ready.push(idx, commits[idx].2);
+            }
+            while let Some(idx) = ready.pop() {
+                let (id, parents, _) = &commits[idx];
+                state.next.push_back(*id);
+                for parent_idx in parents.iter().filter_map(|id| index_by_id.get(id)) {
+                    num_children[*parent_idx] -= 1;
+                    if num_children[*parent_idx] == 0 {
+                        ready.push(*parent_idx, commits[*parent_idx].2);
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Append the parents of the commit with `id` to `parent_ids`, or only its first parent if `parents` says so, and return
+    /// the date needed for `sorting`, or 0 if no date is needed. The commit-graph `cache` is used if it contains the commit.
+    fn lookup_parents<Find, E>(
+        find: &mut Find,
+        cache: Option<&git_commitgraph::Graph>,
+        id: &oid,
+        buf: &mut Vec<u8>,
+        parents: Parents,
+        sorting: Sorting,
+        parent_ids: &mut Vec<ObjectId>,
+    ) -> Result<u32, Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<CommitRefIter<'a>, E>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        if let Some((graph, commit)) = cache
+            .filter(|_| !matches!(sorting, Sorting::AuthorDateOrder))
+            .and_then(|graph| graph.commit_by_id(id).map(|commit| (graph, commit)))
+        {
+            for pos in commit.iter_parents() {
+                parent_ids.push(graph.id_at(pos?).to_owned());
+                if matches!(parents, Parents::First) {
+                    break;
+                }
+            }
+            return Ok(commit.committer_timestamp() as u32);
+        }
+
+        let mut commit_iter = find(id, buf).map_err(|err| Error::FindExisting {
+            oid: id.to_owned(),
+            err: err.into(),
+        })?;
+        if let Some(Err(decode_tree_err)) = commit_iter.next() {
+            return Err(decode_tree_err.into());
+        }
+        let num_parents_before = parent_ids.len();
+        let mut date = 0;
+        for token in commit_iter {
+            match token? {
+                git_object::commit::ref_iter::Token::Parent { id } => {
+                    if matches!(parents, Parents::First) && parent_ids.len() > num_parents_before {
+                        continue;
+                    }
+                    parent_ids.push(id);
+                }
+                git_object::commit::ref_iter::Token::Author { signature }
+                    if matches!(sorting, Sorting::AuthorDateOrder) =>
+                {
+                    date = signature.time.seconds_since_unix_epoch;
+                    break;
+                }
+                git_object::commit::ref_iter::Token::Committer { signature } => {
+                    date = signature.time.seconds_since_unix_epoch;
+                    break;
+                }
+                git_object::commit::ref_iter::Token::Author { .. } if matches!(sorting, Sorting::DateOrder) => {}
+                _a_token_past_the_parents_or_dates => break,
+            }
+        }
+        Ok(date)
+    }
+
+    /// Return true if the commit with `id` is known to have a generation number lower than `min_generation`.
+    fn is_below_generation_cutoff(cache: Option<&git_commitgraph::Graph>, min_generation: u32, id: &oid) -> bool {
+        cache
+            .and_then(|graph| graph.commit_by_id(id))
+            .map_or(false, |commit| commit.generation() < min_generation)
+    }
+
+    /// Indices of commits without children left to be returned, in the order they are to be returned.
+    enum Ready {
+        /// The last commit pushed is returned first.
+        Stack(Vec<usize>),
+        /// The commit with the most recent date is returned first, or the one pushed first if dates are equal.
+        ByDate {
+            queue: BinaryHeap<(u32, Reverse<usize>, usize)>,
+            num_pushed: usize,
+        },
+    }
+
+    impl Ready {
+        fn push(&mut self, idx: usize, date: u32) {
+            match self {
+                Ready::Stack(stack) => stack.push(idx),
+                Ready::ByDate { queue, num_pushed } => {
+                    queue.push((date, Reverse(*num_pushed), idx));
+                    *num_pushed += 1;
+                }
+            }
+        }
+
+        fn pop(&mut self) -> Option<usize> {
+            match self {
+                Ready::Stack(stack) => stack.pop(),
+                Ready::ByDate { queue, .. } => queue.pop().map(|(_, _, idx)| idx),
+            }
+        }
+    }
 }
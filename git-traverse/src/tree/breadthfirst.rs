@@ -1,6 +1,7 @@
 use std::collections::VecDeque;
 
 use git_hash::ObjectId;
+use git_object::bstr::BString;
 use quick_error::quick_error;
 
 quick_error! {
@@ -27,12 +28,19 @@ quick_error! {
 pub struct State {
     next: VecDeque<(bool, ObjectId)>,
     buf: Vec<u8>,
+    /// The paths of the trees in `next`, only tracked if a pathspec is used.
+    next_paths: VecDeque<BString>,
+    tree_path: BString,
+    entry_path: BString,
 }
 
 impl State {
     fn clear(&mut self) {
         self.next.clear();
         self.buf.clear();
+        self.next_paths.clear();
+        self.tree_path.clear();
+        self.entry_path.clear();
     }
 }
 
@@ -40,10 +48,10 @@ pub(crate) mod impl_ {
     use std::borrow::BorrowMut;
 
     use git_hash::oid;
-    use git_object::{tree::EntryMode, TreeRefIter};
+    use git_object::{bstr::ByteSlice, tree::EntryMode, TreeRefIter};
 
     use super::{Error, State};
-    use crate::tree::Visit;
+    use crate::tree::{Pathspec, Visit};
 
     /// Start a breadth-first iteration over the `root` trees entries.
     ///
@@ -58,9 +66,30 @@ pub(crate) mod impl_ {
     ///    be escalated into a more specific error if its encountered by the caller.
     /// * `delegate` - A way to observe entries and control the iteration while allowing the optimizer to let you pay only for what you use.
     pub fn traverse<StateMut, Find, V>(
+        root: TreeRefIter<'_>,
+        state: StateMut,
+        find: Find,
+        delegate: &mut V,
+    ) -> Result<(), Error>
+    where
+        Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>>,
+        StateMut: BorrowMut<State>,
+        V: Visit,
+    {
+        traverse_with_pathspec(root, state, find, &Pathspec::default(), delegate)
+    }
+
+    /// Like [`traverse()`], but only entries matching `pathspec` are passed to the `delegate`, and only trees matching it or
+    /// leading to one of its paths are looked up and traversed. This avoids scanning the whole tree if only a small portion
+    /// of it is of interest.
+    ///
+    /// Trees leading to one of the paths of `pathspec` are passed to [`visit_tree()`][Visit::visit_tree()] as well, so
+    /// the delegate may still decline descending into them with [`Action::Skip`][crate::tree::visit::Action::Skip].
+    pub fn traverse_with_pathspec<StateMut, Find, V>(
         root: TreeRefIter<'_>,
         mut state: StateMut,
         mut find: Find,
+        pathspec: &Pathspec,
         delegate: &mut V,
     ) -> Result<(), Error>
     where
@@ -70,10 +99,25 @@ pub(crate) mod impl_ {
     {
         let state = state.borrow_mut();
         state.clear();
+        let track_paths = !pathspec.is_empty();
         let mut tree = root;
         loop {
             for entry in tree {
                 let entry = entry?;
+                if track_paths {
+                    state.entry_path.clear();
+                    state.entry_path.extend_from_slice(&state.tree_path);
+                    if !state.entry_path.is_empty() {
+                        state.entry_path.push(b'/');
+                    }
+                    state.entry_path.extend_from_slice(entry.filename);
+                    let path = state.entry_path.as_bstr();
+                    if !(pathspec.matches(path)
+                        || (entry.mode == EntryMode::Tree && pathspec.is_leading_directory(path)))
+                    {
+                        continue;
+                    }
+                }
                 match entry.mode {
                     EntryMode::Tree => {
                         use crate::tree::visit::Action::*;
@@ -84,7 +128,10 @@ pub(crate) mod impl_ {
                             Continue => {
                                 delegate.pop_path_component();
                                 delegate.push_back_tracked_path_component(entry.filename);
-                                state.next.push_back((true, entry.oid.to_owned()))
+                                state.next.push_back((true, entry.oid.to_owned()));
+                                if track_paths {
+                                    state.next_paths.push_back(state.entry_path.clone());
+                                }
                             }
                             Cancel => {
                                 return Err(Error::Cancelled);
@@ -105,6 +152,9 @@ pub(crate) mod impl_ {
                     if should_pop_path {
                         delegate.pop_front_tracked_path_and_set_current();
                     }
+                    if track_paths {
+                        state.tree_path = state.next_paths.pop_front().expect("a path for each queued tree");
+                    }
                     match find(&oid, &mut state.buf) {
                         Some(tree_iter) => tree = tree_iter,
                         None => return Err(Error::NotFound { oid: oid.to_owned() }),
@@ -36,6 +36,14 @@ pub struct Recorder {
     pub records: Vec<recorder::Entry>,
 }
 
+/// A set of literal paths relative to the root tree to limit a tree traversal to, similar to a pathspec without magic or wildcards.
+///
+/// An entry matches if its path is equal to one of the paths or is located inside of it. An empty pathspec matches everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pathspec {
+    paths: Vec<BString>,
+}
+
 ///
 pub mod visit {
     /// What to do after an entry was [recorded][super::Visit::visit_tree()].
@@ -60,6 +68,13 @@ pub mod visit {
 ///
 pub mod recorder;
 
+///
+pub mod pathspec;
+
 ///
 pub mod breadthfirst;
-pub use breadthfirst::impl_::traverse as breadthfirst;
+pub use breadthfirst::impl_::{traverse as breadthfirst, traverse_with_pathspec as breadthfirst_with_pathspec};
+
+///
+pub mod parallel;
+pub use parallel::traverse as parallel;
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use git_hash::{oid, ObjectId};
+use git_object::{bstr::BString, tree::EntryMode, TreeRefIter};
+
+use crate::tree::{breadthfirst, visit::Action, Visit};
+
+/// Traverse the `root` tree like [`breadthfirst()`][crate::tree::breadthfirst()], but traverse each of its subtrees on one
+/// of up to `thread_limit` threads, which speeds up the traversal of trees with many entries considerably.
+///
+/// * `find` - a way to lookup trees like in [`breadthfirst()`][crate::tree::breadthfirst()], cloned once per thread.
+/// * `new_delegate` - create a new delegate to observe all entries of `root` first, and then another one for each subtree of
+///   `root` it didn't [skip][Action::Skip], to observe all entries of that subtree with their full path.
+///
+/// Returns the delegate observing `root` followed by the delegates of each subtree in the order the subtrees are listed in
+/// `root`, independently of the amount of threads used, for the caller to aggregate their results. Note that subtrees are
+/// only traversed in parallel if the `parallel` feature of `git-features` is enabled.
+///
+/// The traversal fails with [`Cancelled`][breadthfirst::Error::Cancelled] before each subtree if `should_interrupt` is set.
+pub fn traverse<Find, NewDelegate, V>(
+    root: TreeRefIter<'_>,
+    thread_limit: Option<usize>,
+    find: Find,
+    mut new_delegate: NewDelegate,
+    should_interrupt: &AtomicBool,
+) -> Result<Vec<V>, breadthfirst::Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Option<TreeRefIter<'a>> + Send + Clone,
+    NewDelegate: FnMut() -> V + Send + Clone,
+    V: Visit + Send,
+{
+    let mut root_delegate = new_delegate();
+    let mut subtrees = Vec::<(usize, BString, ObjectId)>::new();
+    for entry in root {
+        let entry = entry?;
+        root_delegate.push_path_component(entry.filename);
+        let action = match entry.mode {
+            EntryMode::Tree => root_delegate.visit_tree(&entry),
+            _non_tree => root_delegate.visit_nontree(&entry),
+        };
+        root_delegate.pop_path_component();
+        match action {
+            Action::Cancel => return Err(breadthfirst::Error::Cancelled),
+            Action::Continue if entry.mode == EntryMode::Tree => {
+                subtrees.push((subtrees.len(), entry.filename.to_owned(), entry.oid.to_owned()))
+            }
+            Action::Continue | Action::Skip => {}
+        }
+    }
+
+    let delegates_by_thread = git_features::parallel::in_parallel_with_slice(
+        &subtrees,
+        thread_limit,
+        move |_thread_id| (Vec::new(), breadthfirst::State::default(), find.clone(), Vec::new()),
+        move |(subtree_index, name, id), (delegates, state, find, buf)| {
+            if should_interrupt.load(Ordering::Relaxed) {
+                return Err(breadthfirst::Error::Cancelled);
+            }
+            let mut delegate = new_delegate();
+            delegate.push_back_tracked_path_component(name.as_ref());
+            delegate.pop_path_component();
+            delegate.pop_front_tracked_path_and_set_current();
+            let tree = find(id, buf).ok_or(breadthfirst::Error::NotFound { oid: *id })?;
+            crate::tree::breadthfirst(tree, &mut *state, &mut *find, &mut delegate)?;
+            delegates.push((*subtree_index, delegate));
+            Ok(())
+        },
+        || Some(std::time::Duration::from_millis(10)),
+    )?;
+
+    let mut delegates: Vec<_> = delegates_by_thread
+        .into_iter()
+        .flat_map(|(delegates, _, _, _)| delegates)
+        .collect();
+    delegates.sort_by_key(|(subtree_index, _)| *subtree_index);
+    Ok(std::iter::once(root_delegate)
+        .chain(delegates.into_iter().map(|(_, delegate)| delegate))
+        .collect())
+}
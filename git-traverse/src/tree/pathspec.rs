@@ -0,0 +1,45 @@
+use git_object::bstr::{BStr, BString};
+
+use crate::tree::Pathspec;
+
+impl Pathspec {
+    /// Create a new instance from `paths` which use slashes as separators. Trailing slashes are ignored, and an empty path
+    /// matches everything.
+    pub fn new(paths: impl IntoIterator<Item = impl Into<BString>>) -> Self {
+        Pathspec {
+            paths: paths
+                .into_iter()
+                .map(|path| {
+                    let mut path = path.into();
+                    while path.last() == Some(&b'/') {
+                        path.pop();
+                    }
+                    path
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns true if there are no paths to limit the traversal to.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Returns true if `path` is equal to one of our paths or located inside of it.
+    pub fn matches(&self, path: &BStr) -> bool {
+        self.is_empty()
+            || self.paths.iter().any(|prefix| {
+                prefix.is_empty()
+                    || (path.starts_with(prefix.as_slice())
+                        && (path.len() == prefix.len() || path[prefix.len()] == b'/'))
+            })
+    }
+
+    /// Returns true if the directory at `path` contains one of our paths, hence it must be traversed even if it doesn't
+    /// [match][Pathspec::matches()] itself.
+    pub fn is_leading_directory(&self, path: &BStr) -> bool {
+        self.paths.iter().any(|full_path| {
+            full_path.len() > path.len() && full_path.starts_with(path) && full_path[path.len()] == b'/'
+        })
+    }
+}
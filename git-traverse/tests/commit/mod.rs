@@ -64,16 +64,25 @@ mod ancestor {
             Ok(())
         }
 
+        fn commit_graph(&self) -> crate::Result<Option<git_commitgraph::Graph>> {
+            let dir = git_testtools::scripted_fixture_repo_read_only(self.init_script)?;
+            Ok(git_commitgraph::Graph::from_info_dir(dir.join(".git").join("objects").join("info")).ok())
+        }
+
         fn check(&self) -> crate::Result {
             let (store, tips, expected) = self.setup()?;
-            let oids: Result<Vec<_>, _> =
-                commit::Ancestors::new(tips, commit::ancestors::State::default(), move |oid, buf| {
-                    store.find_commit_iter(oid, buf).map(|t| t.0)
-                })
-                .sorting(self.sorting)
-                .parents(self.mode)
-                .collect();
-            assert_eq!(oids?, expected);
+            for use_commit_graph in &[false, true] {
+                let commit_graph = if *use_commit_graph { self.commit_graph()? } else { None };
+                let oids: Result<Vec<_>, _> =
+                    commit::Ancestors::new(tips.clone(), commit::ancestors::State::default(), |oid, buf| {
+                        store.find_commit_iter(oid, buf).map(|t| t.0)
+                    })
+                    .sorting(self.sorting)
+                    .parents(self.mode)
+                    .commit_graph(commit_graph)
+                    .collect();
+                assert_eq!(oids?, expected, "use commit-graph: {}", use_commit_graph);
+            }
             Ok(())
         }
     }
@@ -193,6 +202,60 @@ mod ancestor {
         })
     }
 
+    #[test]
+    fn commit_graph_is_used_instead_of_decoding_commits_and_generations_cut_off_the_traversal() -> crate::Result {
+        let dir = git_testtools::scripted_fixture_repo_read_only("make_traversal_repo_for_commits.sh")?;
+        let store = git_odb::at(dir.join(".git").join("objects"))?;
+        let graph = || git_commitgraph::Graph::from_info_dir(dir.join(".git").join("objects").join("info"));
+        let tip = hex_to_id("01ec18a3ebf2855708ad3c9d244306bc1fae3e9b");
+        let c4 = hex_to_id("9556057aee5abb06912922e9f26c46386a816822");
+
+        for sorting in &[
+            commit::Sorting::Topological,
+            commit::Sorting::ByCommitterDate,
+            commit::Sorting::TopoOrder,
+            commit::Sorting::DateOrder,
+        ] {
+            let num_decoded_commits = std::cell::Cell::new(0);
+            let count = commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), |oid, buf| {
+                num_decoded_commits.set(num_decoded_commits.get() + 1);
+                store.find_commit_iter(oid, buf).map(|t| t.0)
+            })
+            .sorting(*sorting)
+            .commit_graph(Some(graph()?))
+            .count();
+            assert_eq!(count, 8, "all commits are returned");
+            assert_eq!(
+                num_decoded_commits.get(),
+                0,
+                "all commits are read from the commit-graph"
+            );
+        }
+
+        let min_generation = graph()?
+            .commit_by_id(c4)
+            .expect("c4 is in the commit-graph")
+            .generation();
+        let oids: Result<Vec<_>, _> =
+            commit::Ancestors::new(Some(tip), commit::ancestors::State::default(), |oid, buf| {
+                store.find_commit_iter(oid, buf).map(|t| t.0)
+            })
+            .commit_graph(Some(graph()?))
+            .generation_cutoff(min_generation + 1)
+            .collect();
+        assert_eq!(
+            oids?,
+            vec![
+                tip,
+                hex_to_id("efd9a841189668f1bab5b8ebade9cd0a1b139a37"),
+                hex_to_id("ce2e8ffaa9608a26f7b21afc1db89cadb54fd353"),
+                hex_to_id("9152eeee2328073cf23dcf8e90c949170b711659"),
+            ],
+            "c4 and its ancestors are below the generation cutoff"
+        );
+        Ok(())
+    }
+
     #[test]
     fn graph_sorted_commits() -> crate::Result {
         TraversalAssertion::new(
@@ -236,4 +299,142 @@ mod ancestor {
         .with_parents(commit::Parents::First)
         .check()
     }
+
+    #[test]
+    fn committer_date_sorted_commits_parents_only_with_multiple_tips() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_dates.sh",
+            &[
+                "288e509293165cb5630d08f4185bdf2445bf6170",
+                "bcb05040a6925f2ff5e10d3ae1f9264f2e8c43ac",
+            ],
+            &[
+                "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                "134385f6d781b7e97062102c6a483440bfda2a03",
+            ],
+        )
+        .with_sorting(commit::Sorting::ByCommitterDate)
+        .with_parents(commit::Parents::First)
+        .check()
+    }
+
+    #[test]
+    fn topo_sorted_commits() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+            &[
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "9556057aee5abb06912922e9f26c46386a816822",
+                "17d78c64cef6c33a10a604573fd2c429e477fd63",
+                "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                "134385f6d781b7e97062102c6a483440bfda2a03",
+            ],
+        )
+        .with_sorting(commit::Sorting::TopoOrder)
+        .check()
+    }
+
+    #[test]
+    fn topo_sorted_commits_with_multiple_tips() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &[
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+            ],
+            &[
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+                "9556057aee5abb06912922e9f26c46386a816822",
+                "17d78c64cef6c33a10a604573fd2c429e477fd63",
+                "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                "134385f6d781b7e97062102c6a483440bfda2a03",
+            ],
+        )
+        .with_sorting(commit::Sorting::TopoOrder)
+        .check()
+    }
+
+    #[test]
+    fn date_ordered_commits() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_author_dates.sh",
+            &["5c73ee398c436259a17d6c7e5c260930e3df1402"],
+            &[
+                "a68b4986d1ee0dce2aa0cb1582819f81ea3e7530",
+                "ea12807016627751b821764a2f8f588bb086f55e",
+                "000acf0922d7f39b9e61b3b332529849ce060add",
+                "d1d7449e7e990e24b235ae4c8592a129df69fc24",
+            ],
+        )
+        .with_sorting(commit::Sorting::DateOrder)
+        .check()
+    }
+
+    #[test]
+    fn author_date_ordered_commits() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_author_dates.sh",
+            &["5c73ee398c436259a17d6c7e5c260930e3df1402"],
+            &[
+                "a68b4986d1ee0dce2aa0cb1582819f81ea3e7530",
+                "000acf0922d7f39b9e61b3b332529849ce060add",
+                "ea12807016627751b821764a2f8f588bb086f55e",
+                "d1d7449e7e990e24b235ae4c8592a129df69fc24",
+            ],
+        )
+        .with_sorting(commit::Sorting::AuthorDateOrder)
+        .check()
+    }
+
+    #[test]
+    fn date_ordered_commits_with_equal_dates_are_ordered_by_discovery() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+            &[
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "ce2e8ffaa9608a26f7b21afc1db89cadb54fd353",
+                "9152eeee2328073cf23dcf8e90c949170b711659",
+                "9556057aee5abb06912922e9f26c46386a816822",
+                "17d78c64cef6c33a10a604573fd2c429e477fd63",
+                "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                "134385f6d781b7e97062102c6a483440bfda2a03",
+            ],
+        )
+        .with_sorting(commit::Sorting::DateOrder)
+        .check()
+    }
+
+    #[test]
+    fn topo_and_date_ordered_commits_first_parent_only() -> crate::Result {
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits.sh",
+            &["01ec18a3ebf2855708ad3c9d244306bc1fae3e9b"],
+            &[
+                "efd9a841189668f1bab5b8ebade9cd0a1b139a37",
+                "9556057aee5abb06912922e9f26c46386a816822",
+                "17d78c64cef6c33a10a604573fd2c429e477fd63",
+                "9902e3c3e8f0c569b4ab295ddf473e6de763e1e7",
+                "134385f6d781b7e97062102c6a483440bfda2a03",
+            ],
+        )
+        .with_sorting(commit::Sorting::TopoOrder)
+        .with_parents(commit::Parents::First)
+        .check()?;
+
+        TraversalAssertion::new(
+            "make_traversal_repo_for_commits_with_author_dates.sh",
+            &["5c73ee398c436259a17d6c7e5c260930e3df1402"],
+            &[
+                "ea12807016627751b821764a2f8f588bb086f55e",
+                "d1d7449e7e990e24b235ae4c8592a129df69fc24",
+            ],
+        )
+        .with_sorting(commit::Sorting::DateOrder)
+        .with_parents(commit::Parents::First)
+        .check()
+    }
 }
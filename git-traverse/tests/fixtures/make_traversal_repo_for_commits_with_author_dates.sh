@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config merge.ff false
+
+git checkout -q -b main
+GIT_AUTHOR_DATE="2000-01-02 00:00:00 +0000" GIT_COMMITTER_DATE="2000-01-02 00:00:00 +0000" git commit -q --allow-empty -m c1
+
+git checkout -q -b branch1
+GIT_AUTHOR_DATE="2003-01-02 00:00:00 +0000" GIT_COMMITTER_DATE="2001-01-02 00:00:00 +0000" git commit -q --allow-empty -m b1c1
+
+git checkout -q main
+GIT_AUTHOR_DATE="2001-01-02 00:00:00 +0000" GIT_COMMITTER_DATE="2002-01-02 00:00:00 +0000" git commit -q --allow-empty -m c2
+
+git checkout -q branch1
+GIT_AUTHOR_DATE="2002-01-02 00:00:00 +0000" GIT_COMMITTER_DATE="2003-01-02 00:00:00 +0000" git commit -q --allow-empty -m b1c2
+
+git checkout -q main
+GIT_AUTHOR_DATE="2004-01-02 00:00:00 +0000" GIT_COMMITTER_DATE="2004-01-02 00:00:00 +0000" git merge branch1 -m m1b1
@@ -1,3 +1,5 @@
+use std::sync::atomic::AtomicBool;
+
 use git_odb::pack::FindExt;
 use git_traverse::tree;
 
@@ -95,3 +97,164 @@ fn basic_nesting() -> crate::Result<()> {
     );
     Ok(())
 }
+
+#[test]
+fn pathspec_prunes_the_traversal_and_delegates_can_skip_subtrees() -> crate::Result<()> {
+    use git_traverse::tree::Visit;
+
+    struct SkipTreeNamedD(tree::Recorder);
+    impl Visit for SkipTreeNamedD {
+        fn pop_front_tracked_path_and_set_current(&mut self) {
+            self.0.pop_front_tracked_path_and_set_current()
+        }
+        fn push_back_tracked_path_component(&mut self, component: &git_object::bstr::BStr) {
+            self.0.push_back_tracked_path_component(component)
+        }
+        fn push_path_component(&mut self, component: &git_object::bstr::BStr) {
+            self.0.push_path_component(component)
+        }
+        fn pop_path_component(&mut self) {
+            self.0.pop_path_component()
+        }
+        fn visit_tree(&mut self, entry: &git_object::tree::EntryRef<'_>) -> tree::visit::Action {
+            self.0.visit_tree(entry);
+            if entry.filename == "d" {
+                tree::visit::Action::Skip
+            } else {
+                tree::visit::Action::Continue
+            }
+        }
+        fn visit_nontree(&mut self, entry: &git_object::tree::EntryRef<'_>) -> tree::visit::Action {
+            self.0.visit_nontree(entry)
+        }
+    }
+
+    let db = db()?;
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut commit = db
+        .find_commit_iter(hex_to_id("85df34aa34848b8138b2b3dcff5fb5c2b734e0ce"), &mut buf)?
+        .0;
+    let root = db
+        .find_tree_iter(commit.tree_id().expect("a tree is available in a commit"), &mut buf2)?
+        .0;
+    let filepaths = |records: Vec<tree::recorder::Entry>| -> Vec<String> {
+        records.into_iter().map(|entry| entry.filepath.to_string()).collect()
+    };
+
+    let mut recorder = tree::Recorder::default();
+    git_traverse::tree::breadthfirst_with_pathspec(
+        root.clone(),
+        tree::breadthfirst::State::default(),
+        |oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0),
+        &tree::Pathspec::new(vec!["f/d/", "a"]),
+        &mut recorder,
+    )?;
+    assert_eq!(
+        filepaths(recorder.records),
+        vec!["a", "f", "f/d", "f/d/x"],
+        "only matching entries and the trees leading to them are visited"
+    );
+
+    let mut delegate = SkipTreeNamedD(tree::Recorder::default());
+    git_traverse::tree::breadthfirst_with_pathspec(
+        root,
+        tree::breadthfirst::State::default(),
+        |oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0),
+        &tree::Pathspec::new(vec!["d", "f"]),
+        &mut delegate,
+    )?;
+    assert_eq!(
+        filepaths(delegate.0.records),
+        vec!["d", "f", "f/c", "f/d", "f/z"],
+        "skipped trees are visited but not traversed"
+    );
+    Ok(())
+}
+
+#[test]
+fn parallel_traversal_observes_all_entries_with_one_delegate_per_subtree() -> crate::Result<()> {
+    let db = db()?;
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut commit = db
+        .find_commit_iter(hex_to_id("85df34aa34848b8138b2b3dcff5fb5c2b734e0ce"), &mut buf)?
+        .0;
+    let root = db
+        .find_tree_iter(commit.tree_id().expect("a tree is available in a commit"), &mut buf2)?
+        .0;
+
+    let mut expected = tree::Recorder::default();
+    git_traverse::tree::breadthfirst(
+        root.clone(),
+        tree::breadthfirst::State::default(),
+        |oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0),
+        &mut expected,
+    )?;
+
+    for thread_limit in &[Some(1), Some(2), None] {
+        let db = db.clone().into_arc()?;
+        let delegates = git_traverse::tree::parallel(
+            root.clone(),
+            *thread_limit,
+            move |oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0),
+            tree::Recorder::default,
+            &AtomicBool::default(),
+        )?;
+        let filepaths: Vec<Vec<String>> = delegates
+            .iter()
+            .map(|delegate| {
+                delegate
+                    .records
+                    .iter()
+                    .map(|entry| entry.filepath.to_string())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            filepaths,
+            vec![
+                vec!["a", "b", "c", "d", "e", "f"],
+                vec!["d/a"],
+                vec!["e/b"],
+                vec!["f/c", "f/d", "f/z", "f/d/x"],
+            ],
+            "the root delegate comes first, followed by one delegate per subtree in order"
+        );
+
+        let mut actual: Vec<_> = delegates.into_iter().flat_map(|delegate| delegate.records).collect();
+        let mut expected = expected.records.clone();
+        actual.sort_by(|a, b| a.filepath.cmp(&b.filepath));
+        expected.sort_by(|a, b| a.filepath.cmp(&b.filepath));
+        assert_eq!(
+            actual, expected,
+            "all entries are observed just like in a breadth-first traversal"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn parallel_traversal_is_cancelled_if_interrupted() -> crate::Result<()> {
+    let db = db()?;
+    let mut buf = Vec::new();
+    let mut buf2 = Vec::new();
+    let mut commit = db
+        .find_commit_iter(hex_to_id("85df34aa34848b8138b2b3dcff5fb5c2b734e0ce"), &mut buf)?
+        .0;
+    let root = db
+        .find_tree_iter(commit.tree_id().expect("a tree is available in a commit"), &mut buf2)?
+        .0;
+
+    let db = db.into_arc()?;
+    let err = git_traverse::tree::parallel(
+        root,
+        None,
+        move |oid, buf| db.find_tree_iter(oid, buf).ok().map(|t| t.0),
+        tree::Recorder::default,
+        &AtomicBool::new(true),
+    )
+    .expect_err("interrupted");
+    assert!(matches!(err, tree::breadthfirst::Error::Cancelled));
+    Ok(())
+}
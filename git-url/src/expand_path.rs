@@ -1,7 +1,7 @@
 //! Functions for expanding repository paths.
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use bstr::{BStr, BString, ByteSlice};
+use bstr::{BStr, BString, ByteSlice, ByteVec};
 use quick_error::quick_error;
 
 /// Whether a repository is resolving for the current user, or the given one.
@@ -77,7 +77,6 @@ pub fn parse(path: &BStr) -> Result<(Option<ForUser>, BString), Error> {
 
 /// Expand `path` for use in a shell and return the expanded path.
 pub fn for_shell(path: BString) -> BString {
-    use bstr::ByteVec;
     match parse(path.as_slice().as_bstr()) {
         Ok((user, mut path)) => match user {
             Some(ForUser::Current) => {
@@ -127,3 +126,60 @@ pub fn expand_path(user: Option<&ForUser>, path: &BStr) -> Result<PathBuf, Error
         }
     })
 }
+
+/// Return the path of `url` as the server it points to interprets it, ready to be substituted by a remote shell.
+///
+/// Paths of `ssh` and `git` URLs starting with `/~/` or `/~user/` are relative to the home directory of the current or the
+/// given user on the server, and turn into `~/` and `~user/` respectively. Relative paths of scp-like URLs like `host:repo`
+/// are relative to the home directory as well and lose the leading slash they have in `url`.
+/// All other paths are returned unchanged.
+pub fn for_server(url: &crate::Url) -> BString {
+    match url.scheme {
+        crate::Scheme::Ssh | crate::Scheme::Git => {
+            let path = for_shell(url.path.clone());
+            match path.strip_prefix(b"/") {
+                Some(relative) if url.serialize_alternative_form && url.scheme == crate::Scheme::Ssh => relative.into(),
+                _ => path,
+            }
+        }
+        _ => url.path.clone(),
+    }
+}
+
+/// Expand the `path` of a local repository, as found in `file://` URLs or plain paths, into an absolute path without
+/// `.` and `..` components.
+///
+/// A leading `~/` or `~user/` is expanded to the home directory of the current or the given user, and relative paths are
+/// resolved against `base`, which typically is the current working directory. `..` components are removed along with the
+/// component preceding them without consulting the file system, hence symbolic links aren't resolved.
+pub fn local(path: &BStr, base: &Path) -> Result<PathBuf, Error> {
+    let path = if path.starts_with(b"~") {
+        let mut with_leading_slash = BString::from("/");
+        with_leading_slash.push_str(path);
+        let (user, path) = parse(with_leading_slash.as_bstr())?;
+        expand_path(user.as_ref(), path.as_bstr())?
+    } else {
+        git_features::path::from_byte_slice(path)
+            .map_err(|_| Error::IllformedUtf8 { path: path.to_owned() })?
+            .to_owned()
+    };
+    Ok(normalize(&base.join(path)))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match out.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    out.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => out.push(component),
+            },
+            _ => out.push(component),
+        }
+    }
+    out
+}
@@ -2,10 +2,7 @@
 #![forbid(unsafe_code)]
 #![deny(rust_2018_idioms)]
 
-use std::{
-    convert::TryFrom,
-    fmt::{self, Write},
-};
+use std::{convert::TryFrom, fmt};
 
 use bstr::ByteSlice;
 
@@ -47,21 +44,28 @@ impl fmt::Display for Scheme {
 
 /// A URL with support for specialized git related capabilities.
 ///
-/// Additionally there is support for [deserialization][Url::from_bytes()] and serialization
-/// (_see the `Display::fmt()` implementation_).
+/// Additionally there is support for [deserialization][Url::from_bytes()] and serialization, either
+/// [losslessly][Url::to_bstring()] or as standard URL (_see the `Display::fmt()` implementation_).
 #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
 #[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
 pub struct Url {
     /// The URL scheme.
     pub scheme: Scheme,
-    /// The user to impersonate on the remote.
+    /// The user to impersonate on the remote, percent-decoded.
     pub user: Option<String>,
+    /// The password of `user`, percent-decoded.
+    pub password: Option<String>,
     /// The host to which to connect. Localhost is implied if `None`.
+    ///
+    /// IPv6 addresses are stored without the brackets that enclose them in URLs, like `::1`.
     pub host: Option<String>,
     /// The port to use when connecting to a host. If `None`, standard ports depending on `scheme` will be used.
     pub port: Option<u16>,
-    /// The path portion of the URL, usually the location of the git repository.
+    /// The path portion of the URL, usually the location of the git repository, percent-decoded unless it's a `file://` URL.
     pub path: bstr::BString,
+    /// If `true`, the URL was parsed from the scp-like form `[user@]host:path` with a relative path, or from a plain
+    /// file path without the `file://` prefix, and will be written in that form again by [`to_bstring()`][Url::to_bstring()].
+    pub serialize_alternative_form: bool,
 }
 
 impl Default for Url {
@@ -69,28 +73,22 @@ impl Default for Url {
         Url {
             scheme: Scheme::Ssh,
             user: None,
+            password: None,
             host: None,
             port: None,
             path: bstr::BString::default(),
+            serialize_alternative_form: false,
         }
     }
 }
 
 impl fmt::Display for Url {
+    /// Write this URL as standard URL with its components percent-encoded as needed, except for the path of `file://` URLs,
+    /// which isn't encoded but may be altered if it's not valid UTF-8.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.scheme.fmt(f)?;
-        f.write_str("://")?;
-        match (&self.user, &self.host) {
-            (Some(user), Some(host)) => f.write_fmt(format_args!("{}@{}", user, host)),
-            (None, Some(host)) => f.write_str(host),
-            (None, None) => Ok(()),
-            _ => return Err(fmt::Error),
-        }?;
-        if let Some(port) = &self.port {
-            f.write_char(':')?;
-            f.write_fmt(format_args!("{}", port))?;
-        }
-        f.write_str(self.path.to_str_lossy().as_ref())
+        let mut buf = Vec::with_capacity(self.path.len() + 16);
+        self.write_url_form(&mut buf).map_err(|_| fmt::Error)?;
+        f.write_str(buf.to_str_lossy().as_ref())
     }
 }
 
@@ -99,6 +97,118 @@ impl Url {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, parse::Error> {
         parse(bytes)
     }
+
+    /// Write this URL losslessly to `out`, ready to be parsed again into an equal URL.
+    ///
+    /// Unlike the `Display` implementation, the path isn't altered if it's not valid UTF-8, and scp-like URLs
+    /// as well as plain file paths are written in their [alternative form][Url::serialize_alternative_form].
+    /// This reproduces the original bytes for all but scp-like URLs whose host was enclosed in brackets without need,
+    /// or whose path started with `./`, and URLs with unnecessarily percent-encoded or otherwise normalized components.
+    pub fn write_to(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        match (self.serialize_alternative_form, self.scheme) {
+            (true, Scheme::File) => out.write_all(&self.path),
+            (true, Scheme::Ssh) if self.port.is_none() && self.password.is_none() => {
+                if let Some(user) = &self.user {
+                    write!(out, "{}@", user)?;
+                }
+                let host = self.host.as_ref().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "scp-like URLs must have a host")
+                })?;
+                write_host(&mut out, host)?;
+                out.write_all(b":")?;
+                out.write_all(self.path.strip_prefix(b"/").unwrap_or(&self.path[..]))
+            }
+            _ => self.write_url_form(out),
+        }
+    }
+
+    /// Return this URL losslessly serialized as with [`write_to()`][Url::write_to()], for instance to write it back to
+    /// the git configuration after rewriting it.
+    pub fn to_bstring(&self) -> std::io::Result<bstr::BString> {
+        let mut buf = Vec::with_capacity(self.path.len() + 16);
+        self.write_to(&mut buf)?;
+        Ok(buf.into())
+    }
+
+    /// Expand and normalize the path of `file` URLs for use on the local file system, resolving relative paths against
+    /// `base`, as described in [`expand_path::local()`]. URLs of other schemes are left unchanged.
+    ///
+    /// Use [`expand_path::for_server()`] to obtain the path of other URLs as the server interprets it.
+    pub fn canonicalize(&mut self, base: &std::path::Path) -> Result<(), expand_path::Error> {
+        if self.scheme == Scheme::File {
+            let path = expand_path::local(self.path.as_bstr(), base)?;
+            self.path = git_features::path::into_bytes(path)
+                .map_err(|_| expand_path::Error::IllformedUtf8 {
+                    path: self.path.clone(),
+                })?
+                .into_owned()
+                .into();
+        }
+        Ok(())
+    }
+
+    fn write_url_form(&self, mut out: impl std::io::Write) -> std::io::Result<()> {
+        write!(out, "{}://", self.scheme)?;
+        if self.user.is_some() || self.password.is_some() {
+            if self.host.is_none() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "URLs with a user must have a host",
+                ));
+            }
+            if let Some(user) = &self.user {
+                percent_encode(&mut out, user.as_bytes(), is_user_info_byte)?;
+            }
+            if let Some(password) = &self.password {
+                out.write_all(b":")?;
+                percent_encode(&mut out, password.as_bytes(), is_user_info_byte)?;
+            }
+            out.write_all(b"@")?;
+        }
+        if let Some(host) = &self.host {
+            write_host(&mut out, host)?;
+        }
+        if let Some(port) = &self.port {
+            write!(out, ":{}", port)?;
+        }
+        match self.scheme {
+            Scheme::File => out.write_all(&self.path),
+            _ => percent_encode(&mut out, &self.path, is_path_byte),
+        }
+    }
+}
+
+/// Write `host`, enclosing it in brackets if it's an IPv6 address.
+fn write_host(mut out: impl std::io::Write, host: &str) -> std::io::Result<()> {
+    if host.contains(':') {
+        write!(out, "[{}]", host)
+    } else {
+        out.write_all(host.as_bytes())
+    }
+}
+
+/// Write `data` to `out` with all bytes for which `is_allowed` returns `false` percent-encoded.
+fn percent_encode(mut out: impl std::io::Write, data: &[u8], is_allowed: fn(u8) -> bool) -> std::io::Result<()> {
+    for chunk in data.split_inclusive(|b| !is_allowed(*b)) {
+        match chunk.split_last() {
+            Some((last, head)) if !is_allowed(*last) => {
+                out.write_all(head)?;
+                write!(out, "%{:02X}", last)?;
+            }
+            _ => out.write_all(chunk)?,
+        }
+    }
+    Ok(())
+}
+
+/// Bytes that are unreserved or sub-delimiters according to RFC 3986, and thus allowed unencoded in user and password.
+fn is_user_info_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=".contains(&b)
+}
+
+/// Bytes that don't need encoding in a path.
+fn is_path_byte(b: u8) -> bool {
+    b.is_ascii_graphic() && !b"\"#%<>?`{}".contains(&b)
 }
 
 impl TryFrom<&[u8]> for Url {
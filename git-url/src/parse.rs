@@ -1,5 +1,3 @@
-use std::borrow::Cow;
-
 use bstr::ByteSlice;
 use quick_error::quick_error;
 
@@ -44,22 +42,73 @@ fn str_to_protocol(s: &str) -> Result<Scheme, Error> {
 
 fn guess_protocol(url: &[u8]) -> &str {
     match url.find_byte(b':') {
-        Some(colon_pos) => {
-            if url[..colon_pos].find_byte(b'.').is_some() {
-                "ssh"
-            } else {
-                "file"
-            }
-        }
-        None => "file",
+        Some(colon_pos) if !is_windows_drive(url, colon_pos) && url[..colon_pos].find_byte(b'/').is_none() => "ssh",
+        _ => "file",
     }
 }
 
-fn sanitize_for_protocol<'a>(protocol: &str, url: &'a str) -> Cow<'a, str> {
-    match protocol {
-        "ssh" => url.replacen(':', "/", 1).into(),
-        _ => url.into(),
+/// Returns true if `url` starts with a single letter followed by the colon at `colon_pos`, like `c:`, which makes it a
+/// windows path instead of an scp-like URL with a single-letter host.
+fn is_windows_drive(url: &[u8], colon_pos: usize) -> bool {
+    colon_pos == 1 && url[0].is_ascii_alphabetic()
+}
+
+/// Parse `[user@]host:path`, where `host` may be enclosed in brackets to allow colons, like `[::1]` or `[user@host]`.
+/// The brackets aren't part of the host.
+///
+/// The path is made absolute to be usable in a URL, and `./` at its start is removed. Only URLs whose path was relative
+/// are marked to be serialized in their scp-like form, as their path would otherwise change its meaning.
+fn parse_scp_like(url: &[u8]) -> Result<crate::Url, Error> {
+    let mut in_brackets = false;
+    let colon_pos = url
+        .iter()
+        .position(|b| {
+            match b {
+                b'[' => in_brackets = true,
+                b']' => in_brackets = false,
+                b':' if !in_brackets => return true,
+                _ => {}
+            }
+            false
+        })
+        .ok_or_else(|| Error::Url(format!("scp-like URL '{}' lacks a colon after the host", url.as_bstr())))?;
+    let (mut user_and_host, mut path) = (&url[..colon_pos], &url[colon_pos + 1..]);
+    if user_and_host.starts_with(b"[") && user_and_host.ends_with(b"]") && user_and_host.find_byte(b'@').is_some() {
+        user_and_host = &user_and_host[1..user_and_host.len() - 1];
+    }
+    let (user, host) = match user_and_host.rfind_byte(b'@') {
+        Some(at_pos) => (Some(&user_and_host[..at_pos]), &user_and_host[at_pos + 1..]),
+        None => (None, user_and_host),
+    };
+    let host = host
+        .strip_prefix(b"[")
+        .and_then(|host| host.strip_suffix(b"]"))
+        .unwrap_or(host);
+    while let Some(stripped) = path.strip_prefix(b"./") {
+        path = stripped;
+    }
+    if path.is_empty() {
+        return Err(Error::EmptyPath);
+    }
+    if host.is_empty() {
+        return Err(Error::Url(format!("scp-like URL '{}' lacks a host", url.as_bstr())));
+    }
+
+    let is_relative = !path.starts_with(b"/");
+    let mut absolute_path = bstr::BString::from(Vec::with_capacity(path.len() + 1));
+    if is_relative {
+        absolute_path.push(b'/');
     }
+    absolute_path.extend_from_slice(path);
+    Ok(crate::Url {
+        scheme: Scheme::Ssh,
+        user: user.map(|user| std::str::from_utf8(user).map(Into::into)).transpose()?,
+        password: None,
+        host: Some(std::str::from_utf8(host)?.into()),
+        port: None,
+        path: absolute_path,
+        serialize_alternative_form: is_relative,
+    })
 }
 
 fn has_no_explicit_protocol(url: &[u8]) -> bool {
@@ -74,20 +123,72 @@ fn possibly_strip_file_protocol(url: &[u8]) -> &[u8] {
     }
 }
 
-fn to_owned_url(url: url::Url) -> Result<crate::Url, Error> {
+fn to_owned_url(url: url::Url, input: &str) -> Result<crate::Url, Error> {
+    let decode_utf8 = |encoded: &str| -> Result<String, Error> {
+        String::from_utf8(percent_decode(encoded)).map_err(|err| Error::Utf8(err.utf8_error()))
+    };
     Ok(crate::Url {
         scheme: str_to_protocol(url.scheme())?,
         user: if url.username().is_empty() {
             None
         } else {
-            Some(url.username().into())
+            Some(decode_utf8(url.username())?)
+        },
+        password: url.password().map(decode_utf8).transpose()?,
+        host: match url.host() {
+            Some(url::Host::Ipv6(address)) => Some(address.to_string()),
+            _ => url.host_str().map(Into::into),
         },
-        host: url.host_str().map(Into::into),
-        port: url.port(),
-        path: url.path().into(),
+        port: explicit_port(&url, input),
+        path: percent_decode(url.path()).into(),
+        serialize_alternative_form: false,
+    })
+}
+
+/// Return the port of `url`, including ports in `input` that are the default for the scheme and thus dropped by `url`.
+fn explicit_port(url: &url::Url, input: &str) -> Option<u16> {
+    url.port().or_else(|| {
+        let authority = input.split_once("://")?.1;
+        let authority = &authority[..authority
+            .find(|c| matches!(c, '/' | '?' | '#'))
+            .unwrap_or(authority.len())];
+        let host_and_port = authority.rsplit_once('@').map_or(authority, |(_user_info, rest)| rest);
+        let port = match host_and_port.rfind(']') {
+            Some(bracket_pos) => &host_and_port[bracket_pos + 1..],
+            None => host_and_port,
+        };
+        port.split_once(':')?.1.parse().ok()
     })
 }
 
+/// Decode all `%XX` sequences in `input`, leaving invalid ones as they are.
+fn percent_decode(input: &str) -> Vec<u8> {
+    let input = input.as_bytes();
+    let mut out = Vec::with_capacity(input.len());
+    let mut pos = 0;
+    while pos < input.len() {
+        let decoded = match input[pos] {
+            b'%' => input
+                .get(pos + 1..pos + 3)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                pos += 3;
+            }
+            None => {
+                out.push(input[pos]);
+                pos += 1;
+            }
+        }
+    }
+    out
+}
+
 /// Parse the given `bytes` as git url.
 ///
 /// # Note
@@ -97,34 +198,21 @@ fn to_owned_url(url: url::Url) -> Result<crate::Url, Error> {
 pub fn parse(bytes: &[u8]) -> Result<crate::Url, Error> {
     let guessed_protocol = guess_protocol(bytes);
     if possibly_strip_file_protocol(bytes) != bytes || (has_no_explicit_protocol(bytes) && guessed_protocol == "file") {
+        let path = possibly_strip_file_protocol(bytes);
         return Ok(crate::Url {
             scheme: Scheme::File,
-            path: possibly_strip_file_protocol(bytes).into(),
+            path: path.into(),
+            serialize_alternative_form: path == bytes,
             ..Default::default()
         });
     }
 
-    let url_str = std::str::from_utf8(bytes)?;
-    let mut url = match url::Url::parse(url_str) {
-        Ok(url) => url,
-        Err(::url::ParseError::RelativeUrlWithoutBase) => {
-            // happens with bare paths as well as scp like paths. The latter contain a ':' past the host portion,
-            // which we are trying to detect.
-            url::Url::parse(&format!(
-                "{}://{}",
-                guessed_protocol,
-                sanitize_for_protocol(guessed_protocol, url_str)
-            ))
-            .map_err(|err| Error::Url(err.to_string()))?
-        }
-        Err(err) => return Err(Error::Url(err.to_string())),
-    };
-    // SCP like URLs without user parse as 'something' with the scheme being the 'host'. Hosts always have dots.
-    if url.scheme().find('.').is_some() {
-        // try again with prefixed protocol
-        url = url::Url::parse(&format!("ssh://{}", sanitize_for_protocol("ssh", url_str)))
-            .map_err(|err| Error::Url(err.to_string()))?;
+    if has_no_explicit_protocol(bytes) {
+        return parse_scp_like(bytes);
     }
+
+    let url_str = std::str::from_utf8(bytes)?;
+    let url = url::Url::parse(url_str).map_err(|err| Error::Url(err.to_string()))?;
     if url.scheme() != "rad" && url.path().is_empty() {
         return Err(Error::EmptyPath);
     }
@@ -132,5 +220,5 @@ pub fn parse(bytes: &[u8]) -> Result<crate::Url, Error> {
         return Err(Error::RelativeUrl(url.into()));
     }
 
-    to_owned_url(url)
+    to_owned_url(url, url_str)
 }
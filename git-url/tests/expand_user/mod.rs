@@ -45,3 +45,81 @@ fn with_username() -> crate::Result {
     assert_eq!(resolved_path, expected_path());
     Ok(())
 }
+
+mod for_server {
+    use git_url::expand_path;
+
+    fn for_server(url: &str) -> bstr::BString {
+        expand_path::for_server(&git_url::parse(url.as_bytes()).expect("valid url"))
+    }
+
+    #[test]
+    fn tilde_paths_are_left_to_the_remote_shell() {
+        assert_eq!(for_server("ssh://host.xz/~/repo"), "~/repo");
+        assert_eq!(for_server("git://host.xz/~byron/repo"), "~byron/repo");
+        assert_eq!(for_server("host.xz:~byron/repo"), "~byron/repo");
+    }
+
+    #[test]
+    fn relative_scp_like_paths_stay_relative_to_the_home_directory() {
+        assert_eq!(for_server("git@host.xz:repo.git"), "repo.git");
+        assert_eq!(for_server("git@host.xz:/srv/repo.git"), "/srv/repo.git");
+        assert_eq!(for_server("ssh://host.xz/srv/repo.git"), "/srv/repo.git");
+    }
+
+    #[test]
+    fn other_paths_are_unchanged() {
+        assert_eq!(for_server("https://host.xz/~/repo"), "/~/repo");
+        assert_eq!(for_server("~/repo"), "~/repo");
+    }
+}
+
+#[cfg(not(windows))]
+mod local {
+    use std::path::Path;
+
+    use bstr::ByteSlice;
+    use git_url::expand_path;
+
+    #[test]
+    fn relative_paths_are_resolved_against_the_base_and_normalized() -> crate::Result {
+        let base = Path::new("/base/dir");
+        for (path, expected) in &[
+            ("repo", "/base/dir/repo"),
+            ("./repo/.", "/base/dir/repo"),
+            ("../other/./repo", "/base/other/repo"),
+            ("../../../../repo", "/repo"),
+            ("/abs/../repo", "/repo"),
+        ] {
+            assert_eq!(
+                expand_path::local(path.as_bytes().as_bstr(), base)?,
+                Path::new(expected)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tilde_is_expanded_to_the_home_directory() -> crate::Result {
+        let home = home::home_dir().expect("home directory is known");
+        assert_eq!(
+            expand_path::local(b"~/repo/../other".as_bstr(), Path::new("/base"))?,
+            home.join("other")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn file_urls_are_canonicalized_in_place() -> crate::Result {
+        let mut url = git_url::parse(b"file://../repo")?;
+        url.canonicalize(Path::new("/base/dir"))?;
+        assert_eq!(url.path, "/base/repo");
+        assert_eq!(url.to_bstring()?, "file:///base/repo");
+
+        let mut url = git_url::parse(b"ssh://host.xz/../repo")?;
+        let expected = url.clone();
+        url.canonicalize(Path::new("/base/dir"))?;
+        assert_eq!(url, expected, "only file URLs are affected");
+        Ok(())
+    }
+}
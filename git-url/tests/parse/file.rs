@@ -1,6 +1,6 @@
 use git_url::Scheme;
 
-use crate::parse::{assert_url_and, assert_url_roundtrip, url};
+use crate::parse::{assert_url_and, assert_url_roundtrip, url, url_alternate};
 
 #[test]
 fn file_path_with_protocol() -> crate::Result {
@@ -12,14 +12,22 @@ fn file_path_with_protocol() -> crate::Result {
 
 #[test]
 fn file_path_without_protocol() -> crate::Result {
-    let url = assert_url_and("/path/to/git", url(Scheme::File, None, None, None, b"/path/to/git"))?.to_string();
+    let url = assert_url_and(
+        "/path/to/git",
+        url_alternate(Scheme::File, None, None, None, b"/path/to/git"),
+    )?
+    .to_string();
     assert_eq!(url, "file:///path/to/git");
     Ok(())
 }
 
 #[test]
 fn no_username_expansion_for_file_paths_without_protocol() -> crate::Result {
-    let url = assert_url_and("~/path/to/git", url(Scheme::File, None, None, None, b"~/path/to/git"))?.to_string();
+    let url = assert_url_and(
+        "~/path/to/git",
+        url_alternate(Scheme::File, None, None, None, b"~/path/to/git"),
+    )?
+    .to_string();
     assert_eq!(url, "file://~/path/to/git");
     Ok(())
 }
@@ -34,12 +42,20 @@ fn no_username_expansion_for_file_paths_with_protocol() -> crate::Result {
 #[test]
 fn non_utf8_file_path_without_protocol() -> crate::Result {
     let parsed = git_url::parse(b"/path/to\xff/git")?;
-    assert_eq!(parsed, url(Scheme::File, None, None, None, b"/path/to\xff/git",));
+    assert_eq!(
+        parsed,
+        url_alternate(Scheme::File, None, None, None, b"/path/to\xff/git",)
+    );
     assert_eq!(
         parsed.to_string(),
         "file:///path/to�/git",
         "non-unicode is made unicode safe"
     );
+    assert_eq!(
+        parsed.to_bstring()?,
+        &b"/path/to\xff/git"[..],
+        "but serialized losslessly"
+    );
     Ok(())
 }
 
@@ -47,11 +63,15 @@ fn non_utf8_file_path_without_protocol() -> crate::Result {
 fn relative_file_path_without_protocol() -> crate::Result {
     let parsed = assert_url_and(
         "../../path/to/git",
-        url(Scheme::File, None, None, None, b"../../path/to/git"),
+        url_alternate(Scheme::File, None, None, None, b"../../path/to/git"),
     )?
     .to_string();
     assert_eq!(parsed, "file://../../path/to/git");
-    let url = assert_url_and("path/to/git", url(Scheme::File, None, None, None, b"path/to/git"))?.to_string();
+    let url = assert_url_and(
+        "path/to/git",
+        url_alternate(Scheme::File, None, None, None, b"path/to/git"),
+    )?
+    .to_string();
     assert_eq!(url, "file://path/to/git");
     Ok(())
 }
@@ -60,21 +80,34 @@ fn relative_file_path_without_protocol() -> crate::Result {
 fn interior_relative_file_path_without_protocol() -> crate::Result {
     let url = assert_url_and(
         "/abs/path/../../path/to/git",
-        url(Scheme::File, None, None, None, b"/abs/path/../../path/to/git"),
+        url_alternate(Scheme::File, None, None, None, b"/abs/path/../../path/to/git"),
     )?
     .to_string();
     assert_eq!(url, "file:///abs/path/../../path/to/git");
     Ok(())
 }
 
+#[test]
+fn colon_after_a_slash_is_a_file_path() -> crate::Result {
+    assert_url_and(
+        "./path/with:colon",
+        url_alternate(Scheme::File, None, None, None, b"./path/with:colon"),
+    )?;
+    Ok(())
+}
+
 mod windows {
     use git_url::Scheme;
 
-    use crate::parse::{assert_url_and, assert_url_roundtrip, url};
+    use crate::parse::{assert_url_and, assert_url_roundtrip, url, url_alternate};
 
     #[test]
     fn file_path_without_protocol() -> crate::Result {
-        let url = assert_url_and("x:/path/to/git", url(Scheme::File, None, None, None, b"x:/path/to/git"))?.to_string();
+        let url = assert_url_and(
+            "x:/path/to/git",
+            url_alternate(Scheme::File, None, None, None, b"x:/path/to/git"),
+        )?
+        .to_string();
         assert_eq!(url, "file://x:/path/to/git");
         Ok(())
     }
@@ -83,13 +116,19 @@ mod windows {
     fn file_path_with_backslashes_without_protocol() -> crate::Result {
         let url = assert_url_and(
             "x:\\path\\to\\git",
-            url(Scheme::File, None, None, None, b"x:\\path\\to\\git"),
+            url_alternate(Scheme::File, None, None, None, b"x:\\path\\to\\git"),
         )?
         .to_string();
         assert_eq!(url, "file://x:\\path\\to\\git");
         Ok(())
     }
 
+    #[test]
+    fn drive_letter_is_not_mistaken_for_a_host() -> crate::Result {
+        assert_url_and("c:repo", url_alternate(Scheme::File, None, None, None, b"c:repo"))?;
+        Ok(())
+    }
+
     #[test]
     fn file_path_with_protocol() -> crate::Result {
         assert_url_roundtrip(
@@ -1,6 +1,6 @@
 use git_url::Scheme;
 
-use crate::parse::{assert_url_and, assert_url_roundtrip, url};
+use crate::parse::{assert_url_and, assert_url_roundtrip, url, url_alternate};
 
 #[test]
 fn without_user_and_without_port() -> crate::Result {
@@ -23,6 +23,19 @@ fn host_is_ipv4() -> crate::Result {
     )
 }
 
+#[test]
+fn host_is_ipv6_with_port() -> crate::Result {
+    assert_url_roundtrip("ssh://[::1]:2222/repo", url(Scheme::Ssh, None, "::1", 2222, b"/repo"))
+}
+
+#[test]
+fn percent_encoded_user_and_path_are_decoded() -> crate::Result {
+    assert_url_roundtrip(
+        "ssh://user%40corp@host.xz/path%20with%20spaces/%FF.git",
+        url(Scheme::Ssh, "user@corp", "host.xz", None, b"/path with spaces/\xff.git"),
+    )
+}
+
 #[test]
 fn username_expansion_with_username() -> crate::Result {
     assert_url_roundtrip(
@@ -51,7 +64,7 @@ fn with_user_and_without_port() -> crate::Result {
 fn scp_like_without_user() -> crate::Result {
     let url = assert_url_and(
         "host.xz:path/to/git",
-        url(Scheme::Ssh, None, "host.xz", None, b"/path/to/git"),
+        url_alternate(Scheme::Ssh, None, "host.xz", None, b"/path/to/git"),
     )?
     .to_string();
     assert_eq!(url, "ssh://host.xz/path/to/git");
@@ -62,7 +75,7 @@ fn scp_like_without_user() -> crate::Result {
 fn scp_like_without_user_and_username_expansion_without_username() -> crate::Result {
     let url = assert_url_and(
         "host.xz:~/to/git",
-        url(Scheme::Ssh, None, "host.xz", None, b"/~/to/git"),
+        url_alternate(Scheme::Ssh, None, "host.xz", None, b"/~/to/git"),
     )?
     .to_string();
     assert_eq!(url, "ssh://host.xz/~/to/git");
@@ -73,7 +86,7 @@ fn scp_like_without_user_and_username_expansion_without_username() -> crate::Res
 fn scp_like_without_user_and_username_expansion_with_username() -> crate::Result {
     let url = assert_url_and(
         "host.xz:~byron/to/git",
-        url(Scheme::Ssh, None, "host.xz", None, b"/~byron/to/git"),
+        url_alternate(Scheme::Ssh, None, "host.xz", None, b"/~byron/to/git"),
     )?
     .to_string();
     assert_eq!(url, "ssh://host.xz/~byron/to/git");
@@ -84,9 +97,77 @@ fn scp_like_without_user_and_username_expansion_with_username() -> crate::Result
 fn scp_like_with_user_and_relative_path_turns_into_absolute_path() -> crate::Result {
     let url = assert_url_and(
         "user@host.xz:./relative",
-        url(Scheme::Ssh, "user", "host.xz", None, b"/relative"),
+        url_alternate(Scheme::Ssh, "user", "host.xz", None, b"/relative"),
     )?
     .to_string();
     assert_eq!(url, "ssh://user@host.xz/relative");
     Ok(())
 }
+
+#[test]
+fn scp_like_with_user_as_used_by_hosting_services() -> crate::Result {
+    let url = assert_url_and(
+        "git@github.com:user/repo.git",
+        url_alternate(Scheme::Ssh, "git", "github.com", None, b"/user/repo.git"),
+    )?
+    .to_string();
+    assert_eq!(url, "ssh://git@github.com/user/repo.git");
+    Ok(())
+}
+
+#[test]
+fn scp_like_with_host_without_dots() -> crate::Result {
+    assert_url_and(
+        "git@myhost:repo.git",
+        url_alternate(Scheme::Ssh, "git", "myhost", None, b"/repo.git"),
+    )?;
+    assert_url_and(
+        "localhost:repo.git",
+        url_alternate(Scheme::Ssh, None, "localhost", None, b"/repo.git"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn scp_like_with_colons_in_the_path() -> crate::Result {
+    assert_url_and(
+        "host.xz:path:with:colons",
+        url_alternate(Scheme::Ssh, None, "host.xz", None, b"/path:with:colons"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn scp_like_with_bracketed_host() -> crate::Result {
+    assert_url_and(
+        "user@[::1]:repo",
+        url_alternate(Scheme::Ssh, "user", "::1", None, b"/repo"),
+    )?;
+    assert_url_and(
+        "[user@host.xz]:repo",
+        url_alternate(Scheme::Ssh, "user", "host.xz", None, b"/repo"),
+    )?;
+    assert_url_and(
+        "[host.xz]:repo",
+        url_alternate(Scheme::Ssh, None, "host.xz", None, b"/repo"),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn scp_like_is_serialized_in_its_original_form() -> crate::Result {
+    for url in &["git@github.com:user/repo.git", "host.xz:~/to/git", "user@[::1]:repo"] {
+        assert_eq!(git_url::parse(url.as_bytes())?.to_bstring()?, *url);
+    }
+    Ok(())
+}
+
+#[test]
+fn scp_like_with_absolute_path_is_serialized_as_url_to_keep_the_path_absolute() -> crate::Result {
+    let url = assert_url_and(
+        "user@host.xz:/absolute/path",
+        url(Scheme::Ssh, "user", "host.xz", None, b"/absolute/path"),
+    )?;
+    assert_eq!(url.to_bstring()?, "ssh://user@host.xz/absolute/path");
+    Ok(())
+}
@@ -0,0 +1,280 @@
+//! Run the filter drivers configured with `filter.<driver>.clean`, `filter.<driver>.smudge` and `filter.<driver>.process`
+//! to transform content when it moves between the worktree and the object database, as selected by the `filter` attribute.
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+
+///
+pub mod process;
+
+/// A filter driver as configured in `filter.<name>.*`.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Driver {
+    /// The name of the driver, as used in the `filter` attribute.
+    pub name: BString,
+    /// The command to run to convert worktree content into content for the object database, with `%f` being replaced
+    /// by the quoted path of the file.
+    pub clean: Option<BString>,
+    /// The command to run to convert content from the object database into worktree content, with `%f` being replaced
+    /// by the quoted path of the file.
+    pub smudge: Option<BString>,
+    /// A long-running process to handle all clean and smudge requests, which takes precedence over
+    /// [`clean`][Driver::clean] and [`smudge`][Driver::smudge].
+    pub process: Option<BString>,
+    /// If true, failing to apply this driver is an error. Otherwise the content is used as is if it fails,
+    /// which callers have to implement based on this flag.
+    pub required: bool,
+}
+
+/// The direction in which content is converted.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Operation {
+    /// Convert worktree content into content for the object database.
+    Clean,
+    /// Convert content from the object database into worktree content.
+    Smudge,
+}
+
+impl Operation {
+    /// Return the name of the operation as used in configuration and in the process protocol.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Clean => "clean",
+            Operation::Smudge => "smudge",
+        }
+    }
+}
+
+/// The outcome of [`State::apply()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Outcome {
+    /// The filtered content was written into the output buffer.
+    Filtered,
+    /// The driver has nothing to do for the operation, and the content should be used as is.
+    Unchanged,
+    /// The process of the driver will provide the content later, which can be obtained once it is
+    /// [available][State::list_delayed()] using [`State::fetch_delayed()`].
+    Delayed,
+}
+
+///
+pub mod apply {
+    use bstr::BString;
+
+    /// The error returned by [`State::apply()`][super::State::apply()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not run the {operation} filter of driver '{driver}'")]
+        Io {
+            source: std::io::Error,
+            driver: BString,
+            operation: &'static str,
+        },
+        #[error("The {operation} filter of driver '{driver}' failed with exit code {code:?}")]
+        Failed {
+            driver: BString,
+            operation: &'static str,
+            code: Option<i32>,
+        },
+        #[error("The process of driver '{driver}' could not be started")]
+        Handshake {
+            source: super::process::handshake::Error,
+            driver: BString,
+        },
+        #[error("The process of driver '{driver}' failed to {operation} '{path}'")]
+        Process {
+            source: super::process::invoke::Error,
+            driver: BString,
+            operation: &'static str,
+            path: BString,
+        },
+    }
+}
+
+/// Keeps the long-running processes of drivers alive so that they can handle all paths of an operation, along with
+/// paths whose content was delayed.
+pub struct State {
+    worktree: PathBuf,
+    running: HashMap<BString, process::Client>,
+}
+
+impl State {
+    /// Create a new instance to run all drivers in `worktree`.
+    pub fn new(worktree: impl Into<PathBuf>) -> Self {
+        State {
+            worktree: worktree.into(),
+            running: Default::default(),
+        }
+    }
+
+    /// Apply the `operation` of `driver` to `src`, the content at the slash-separated `rela_path`, and write the
+    /// result into `out`, which is cleared beforehand.
+    ///
+    /// If `can_delay` is true and the process of the driver supports it, it may decide to provide the smudged content
+    /// later. Long-running processes are started once and kept alive for as long as this instance exists.
+    pub fn apply(
+        &mut self,
+        driver: &Driver,
+        operation: Operation,
+        rela_path: &BStr,
+        src: &[u8],
+        out: &mut Vec<u8>,
+        can_delay: bool,
+    ) -> Result<Outcome, apply::Error> {
+        out.clear();
+        if let Some(client) = self.client(driver)? {
+            if !client.supports(operation.as_str()) {
+                return Ok(Outcome::Unchanged);
+            }
+            let can_delay = can_delay && operation == Operation::Smudge && client.supports("delay");
+            let status = client
+                .invoke(operation.as_str(), rela_path, src, can_delay, out)
+                .map_err(|err| apply::Error::Process {
+                    source: err,
+                    driver: driver.name.clone(),
+                    operation: operation.as_str(),
+                    path: rela_path.to_owned(),
+                })?;
+            return Ok(match status {
+                process::Status::Success => Outcome::Filtered,
+                process::Status::Delayed => Outcome::Delayed,
+            });
+        }
+
+        let command = match operation {
+            Operation::Clean => driver.clean.as_ref(),
+            Operation::Smudge => driver.smudge.as_ref(),
+        };
+        match command {
+            Some(command) => {
+                run_command(command.as_ref(), &self.worktree, rela_path, src, out).map_err(|err| match err {
+                    CommandError::Io(err) => apply::Error::Io {
+                        source: err,
+                        driver: driver.name.clone(),
+                        operation: operation.as_str(),
+                    },
+                    CommandError::Failed(code) => apply::Error::Failed {
+                        driver: driver.name.clone(),
+                        operation: operation.as_str(),
+                        code,
+                    },
+                })?;
+                Ok(Outcome::Filtered)
+            }
+            None => Ok(Outcome::Unchanged),
+        }
+    }
+
+    /// Return the paths whose content was [delayed][Outcome::Delayed] by the process of `driver` and is now available,
+    /// blocking until at least one of them is ready. An empty list indicates that there are no delayed paths left.
+    pub fn list_delayed(&mut self, driver: &Driver) -> Result<Vec<BString>, process::invoke::Error> {
+        match self.running.get_mut(&driver.name) {
+            Some(client) => client.list_available_blobs(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Write the content at `rela_path`, previously [delayed][Outcome::Delayed] by the process of `driver` and now
+    /// [available][State::list_delayed()], into `out`. Return false if the process of `driver` isn't running.
+    pub fn fetch_delayed(
+        &mut self,
+        driver: &Driver,
+        rela_path: &BStr,
+        out: &mut Vec<u8>,
+    ) -> Result<bool, process::invoke::Error> {
+        out.clear();
+        match self.running.get_mut(&driver.name) {
+            Some(client) => client
+                .invoke(Operation::Smudge.as_str(), rela_path, &[], false, out)
+                .map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    fn client(&mut self, driver: &Driver) -> Result<Option<&mut process::Client>, apply::Error> {
+        let command = match driver.process.as_ref() {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+        if !self.running.contains_key(&driver.name) {
+            let client = process::Client::spawn(command.as_ref(), &self.worktree, &["clean", "smudge", "delay"])
+                .map_err(|err| apply::Error::Handshake {
+                    source: err,
+                    driver: driver.name.clone(),
+                })?;
+            self.running.insert(driver.name.clone(), client);
+        }
+        Ok(self.running.get_mut(&driver.name))
+    }
+}
+
+enum CommandError {
+    Io(std::io::Error),
+    Failed(Option<i32>),
+}
+
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        CommandError::Io(err)
+    }
+}
+
+/// Run `command` through the shell after replacing `%f` with the quoted `rela_path`, feeding it `src` and collecting
+/// its output in `out`.
+fn run_command(
+    command: &BStr,
+    worktree: &Path,
+    rela_path: &BStr,
+    src: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<(), CommandError> {
+    let command = command.replace("%f", quote(rela_path));
+    let mut child = shell(command.as_bstr(), worktree)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()?;
+
+    // Write the input on another thread to not deadlock if the command produces output before consuming all input.
+    let mut stdin = child.stdin.take().expect("configured");
+    let input = src.to_owned();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    child.stdout.take().expect("configured").read_to_end(out)?;
+    let status = child.wait()?;
+    if let Err(err) = writer.join().expect("no panic") {
+        // Commands may not be interested in all of their input, which is fine as long as they succeed.
+        if err.kind() != std::io::ErrorKind::BrokenPipe {
+            return Err(err.into());
+        }
+    }
+    if !status.success() {
+        return Err(CommandError::Failed(status.code()));
+    }
+    Ok(())
+}
+
+fn shell(command: &BStr, worktree: &Path) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command.to_os_str_lossy()).current_dir(worktree);
+    cmd
+}
+
+/// Quote `path` for use as a single argument in the shell.
+fn quote(path: &BStr) -> BString {
+    let mut quoted = BString::from("'");
+    for b in path.iter() {
+        if *b == b'\'' {
+            quoted.push_str(r"'\''");
+        } else {
+            quoted.push(*b);
+        }
+    }
+    quoted.push(b'\'');
+    quoted
+}
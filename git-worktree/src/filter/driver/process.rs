@@ -0,0 +1,276 @@
+//! A client for the long-running filter process protocol, as used with `filter.<driver>.process`.
+//!
+//! Git and the process communicate using packet lines, starting with a handshake to agree on the protocol version and
+//! the capabilities of the process, followed by any amount of requests which are answered one at a time.
+use std::{
+    io::{Read, Write},
+    path::Path,
+    process::{Child, Stdio},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+
+/// The status of a successful request.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Status {
+    /// The content was produced.
+    Success,
+    /// The process will produce the content later.
+    Delayed,
+}
+
+///
+pub mod handshake {
+    use bstr::BString;
+
+    /// The error returned by [`Client::spawn()`][super::Client::spawn()] and [`Client::from_io()`][super::Client::from_io()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not communicate with the filter process")]
+        Io(#[from] std::io::Error),
+        #[error("The filter process didn't identify itself as 'git-filter-server', but sent '{actual}'")]
+        Welcome { actual: BString },
+        #[error("The filter process doesn't support protocol version 2")]
+        UnsupportedVersion,
+    }
+}
+
+///
+pub mod invoke {
+    use bstr::BString;
+
+    /// The error returned by [`Client::invoke()`][super::Client::invoke()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("Could not communicate with the filter process")]
+        Io(#[from] std::io::Error),
+        #[error("The filter process responded with status '{status}'")]
+        Status { status: BString },
+        #[error("The filter process aborted and won't handle any more requests")]
+        Aborted,
+    }
+}
+
+/// A running filter process after a successful handshake.
+pub struct Client {
+    /// The child process, if we spawned it.
+    child: Option<Child>,
+    input: Box<dyn Write + Send>,
+    output: Box<dyn Read + Send>,
+    capabilities: Vec<BString>,
+    aborted: bool,
+    buf: Vec<u8>,
+}
+
+impl Client {
+    /// Start the filter process by running `command` through the shell in `worktree`, and perform a handshake
+    /// requesting the given `capabilities`, like `clean`, `smudge` or `delay`.
+    pub fn spawn(command: &BStr, worktree: &Path, capabilities: &[&str]) -> Result<Self, handshake::Error> {
+        let mut child = super::shell(command, worktree)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+        let input = child.stdin.take().expect("configured");
+        let output = child.stdout.take().expect("configured");
+        let mut client = Client::from_io(input, output, capabilities)?;
+        client.child = Some(child);
+        Ok(client)
+    }
+
+    /// Perform a handshake requesting the given `capabilities` with a filter process that reads our requests from
+    /// `input` and writes its responses to `output`.
+    pub fn from_io(
+        input: impl Write + Send + 'static,
+        output: impl Read + Send + 'static,
+        capabilities: &[&str],
+    ) -> Result<Self, handshake::Error> {
+        let mut client = Client {
+            child: None,
+            input: Box::new(std::io::BufWriter::new(input)),
+            output: Box::new(std::io::BufReader::new(output)),
+            capabilities: Vec::new(),
+            aborted: false,
+            buf: Vec::new(),
+        };
+
+        client.write_text("git-filter-client")?;
+        client.write_text("version=2")?;
+        client.write_flush()?;
+        let lines = client.read_text_until_flush()?;
+        match lines.first() {
+            Some(welcome) if welcome == "git-filter-server" => {}
+            _ => {
+                return Err(handshake::Error::Welcome {
+                    actual: lines.into_iter().next().unwrap_or_default(),
+                })
+            }
+        }
+        if !lines.iter().skip(1).any(|line| line == "version=2") {
+            return Err(handshake::Error::UnsupportedVersion);
+        }
+
+        for capability in capabilities {
+            client.write_text(&format!("capability={}", capability))?;
+        }
+        client.write_flush()?;
+        client.capabilities = client
+            .read_text_until_flush()?
+            .into_iter()
+            .filter_map(|line| line.strip_prefix(b"capability=").map(Into::into))
+            .collect();
+        Ok(client)
+    }
+
+    /// Return the capabilities the process agreed to.
+    pub fn capabilities(&self) -> &[BString] {
+        &self.capabilities
+    }
+
+    /// Return true if the process supports `capability`.
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Send `command`, like `clean` or `smudge`, for the slash-separated `rela_path` with `content` to the process
+    /// and write the content it produces into `out`.
+    ///
+    /// If `can_delay` is true, the process may decide to [delay][Status::Delayed] its response, in which case
+    /// the content is obtained later by sending the same command with empty `content`
+    /// once `rela_path` is [available][Client::list_available_blobs()].
+    pub fn invoke(
+        &mut self,
+        command: &str,
+        rela_path: &BStr,
+        content: &[u8],
+        can_delay: bool,
+        out: &mut Vec<u8>,
+    ) -> Result<Status, invoke::Error> {
+        if self.aborted {
+            return Err(invoke::Error::Aborted);
+        }
+        self.write_text(&format!("command={}", command))?;
+        let mut pathname = BString::from("pathname=");
+        pathname.extend_from_slice(rela_path);
+        self.write_text(pathname.as_bstr())?;
+        if can_delay {
+            self.write_text("can-delay=1")?;
+        }
+        self.write_flush()?;
+        for chunk in content.chunks(MAX_DATA_LEN) {
+            self.write_line(chunk)?;
+        }
+        self.write_flush()?;
+
+        let status = self.read_status()?;
+        if can_delay && status.as_ref().map_or(false, |status| status == "delayed") {
+            return Ok(Status::Delayed);
+        }
+        if status.as_ref().map_or(true, |status| status != "success") {
+            return Err(self.status_error(status));
+        }
+        while let Some(data) = self.read_line()? {
+            out.extend_from_slice(data);
+        }
+        // The status may change after the content was sent, with an empty list keeping it unchanged.
+        match self.read_status()? {
+            None => Ok(Status::Success),
+            Some(status) if status == "success" => Ok(Status::Success),
+            status => Err(self.status_error(status)),
+        }
+    }
+
+    /// Ask the process which paths it [delayed][Status::Delayed] are now available, blocking until at least one of
+    /// them is. An empty list indicates that there are no delayed paths left.
+    pub fn list_available_blobs(&mut self) -> Result<Vec<BString>, invoke::Error> {
+        if self.aborted {
+            return Err(invoke::Error::Aborted);
+        }
+        self.write_text("command=list_available_blobs")?;
+        self.write_flush()?;
+        let paths = self
+            .read_text_until_flush()?
+            .into_iter()
+            .filter_map(|line| line.strip_prefix(b"pathname=").map(Into::into))
+            .collect();
+        match self.read_status()? {
+            Some(status) if status == "success" => Ok(paths),
+            status => Err(self.status_error(status)),
+        }
+    }
+
+    fn status_error(&mut self, status: Option<BString>) -> invoke::Error {
+        match status {
+            Some(status) if status == "abort" => {
+                self.aborted = true;
+                invoke::Error::Aborted
+            }
+            status => invoke::Error::Status {
+                status: status.unwrap_or_default(),
+            },
+        }
+    }
+
+    /// Read key-value pairs until the next flush packet and return the value of the last `status` key, if present.
+    fn read_status(&mut self) -> Result<Option<BString>, invoke::Error> {
+        Ok(self
+            .read_text_until_flush()?
+            .into_iter()
+            .filter_map(|line| line.strip_prefix(b"status=").map(Into::into))
+            .last())
+    }
+
+    fn read_text_until_flush(&mut self) -> std::io::Result<Vec<BString>> {
+        let mut lines = Vec::new();
+        while let Some(line) = self.read_line()? {
+            lines.push(line.strip_suffix(b"\n").unwrap_or(line).into());
+        }
+        Ok(lines)
+    }
+
+    /// Read the next packet line and return its data, or `None` if it's a flush packet.
+    fn read_line(&mut self) -> std::io::Result<Option<&[u8]>> {
+        use git_packetline::decode::{hex_prefix, PacketLineOrWantedSize};
+        let mut hex = [0u8; 4];
+        self.output.read_exact(&mut hex)?;
+        let len = match hex_prefix(&hex).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))? {
+            PacketLineOrWantedSize::Line(_) => return Ok(None),
+            PacketLineOrWantedSize::Wanted(len) => len as usize,
+        };
+        self.buf.resize(len, 0);
+        self.output.read_exact(&mut self.buf)?;
+        Ok(Some(&self.buf))
+    }
+
+    fn write_text(&mut self, text: impl AsRef<[u8]>) -> std::io::Result<()> {
+        let text = text.as_ref();
+        write!(self.input, "{:04x}", text.len() + 1 + 4)?;
+        self.input.write_all(text)?;
+        self.input.write_all(b"\n")
+    }
+
+    fn write_line(&mut self, data: &[u8]) -> std::io::Result<()> {
+        write!(self.input, "{:04x}", data.len() + 4)?;
+        self.input.write_all(data)
+    }
+
+    fn write_flush(&mut self) -> std::io::Result<()> {
+        self.input.write_all(b"0000")?;
+        self.input.flush()
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.child.take() {
+            // Closing its input tells the process to shut down.
+            self.input = Box::new(std::io::sink());
+            child.wait().ok();
+        }
+    }
+}
+
+/// The maximum amount of data in a single packet line.
+const MAX_DATA_LEN: usize = 65516;
@@ -0,0 +1,378 @@
+//! Conversion of line endings between the worktree and the object database, as controlled by `core.autocrlf`,
+//! `core.eol` and the `text` and `eol` attributes.
+//!
+//! Content is always normalized to `LF` when it enters the object database, and may be converted to `CRLF` when
+//! it is written into the worktree.
+
+/// The value of `core.autocrlf`.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum AutoCrlf {
+    /// Convert line endings of text files to `CRLF` on checkout and to `LF` on checkin.
+    Enabled,
+    /// Only convert line endings of text files to `LF` on checkin.
+    Input,
+    /// Don't convert line endings unless attributes say so.
+    Disabled,
+}
+
+impl Default for AutoCrlf {
+    fn default() -> Self {
+        AutoCrlf::Disabled
+    }
+}
+
+/// A line ending style as used in `core.eol` or the `eol` attribute.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Mode {
+    /// Lines end with `\n`.
+    Lf,
+    /// Lines end with `\r\n`.
+    CrLf,
+}
+
+impl Default for Mode {
+    /// The native line ending of the platform we are compiled for.
+    fn default() -> Self {
+        if cfg!(windows) {
+            Mode::CrLf
+        } else {
+            Mode::Lf
+        }
+    }
+}
+
+/// Line ending related configuration of a repository.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct Configuration {
+    /// The value of `core.autocrlf`, which takes precedence over `eol`.
+    pub auto_crlf: AutoCrlf,
+    /// The value of `core.eol`, the line ending to use in the worktree for files that are marked as text,
+    /// defaulting to the native line ending.
+    pub eol: Mode,
+}
+
+impl Configuration {
+    /// The line ending to use in the worktree for files marked as text without specifying the line ending.
+    fn text_eol(&self) -> Mode {
+        match self.auto_crlf {
+            AutoCrlf::Enabled => Mode::CrLf,
+            AutoCrlf::Input => Mode::Lf,
+            AutoCrlf::Disabled => self.eol,
+        }
+    }
+
+    /// Turn `digest` of the attributes of a path into the conversion to perform for it, using this configuration
+    /// if no attribute affecting line endings was specified.
+    fn resolve(&self, digest: Option<AttributesDigest>) -> AttributesDigest {
+        digest.unwrap_or(match self.auto_crlf {
+            AutoCrlf::Enabled => AttributesDigest::TextAutoCrlf,
+            AutoCrlf::Input => AttributesDigest::TextAutoInput,
+            AutoCrlf::Disabled => AttributesDigest::Binary,
+        })
+    }
+}
+
+/// The combined effect of the `text` and `eol` attributes of a path on its line endings.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum AttributesDigest {
+    /// `-text`, never convert line endings.
+    Binary,
+    /// `text`, always normalize on checkin and use the configured line ending on checkout.
+    Text,
+    /// `text eol=lf` or `eol=lf`, always normalize on checkin and never convert on checkout.
+    TextInput,
+    /// `text eol=crlf` or `eol=crlf`, always normalize on checkin and use `CRLF` on checkout.
+    TextCrlf,
+    /// `text=auto`, like [`Text`][AttributesDigest::Text], but only if the content doesn't look binary.
+    TextAuto,
+    /// `text=auto eol=lf`, like [`TextInput`][AttributesDigest::TextInput], but only if the content doesn't look binary.
+    TextAutoInput,
+    /// `text=auto eol=crlf`, like [`TextCrlf`][AttributesDigest::TextCrlf], but only if the content doesn't look binary.
+    TextAutoCrlf,
+}
+
+impl AttributesDigest {
+    /// Combine the states of the `text` and `eol` attributes of a path, or return `None` if neither affects
+    /// line endings, in which case `core.autocrlf` decides.
+    pub fn from_attributes(text: &git_attributes::State<'_>, eol: &git_attributes::State<'_>) -> Option<Self> {
+        use git_attributes::State;
+        let eol = match eol {
+            State::Value(v) if *v == "lf" => Some(Mode::Lf),
+            State::Value(v) if *v == "crlf" => Some(Mode::CrLf),
+            _ => None,
+        };
+        Some(match (text, eol) {
+            (State::Unset, _) => AttributesDigest::Binary,
+            (State::Value(v), eol) if *v == "auto" => match eol {
+                None => AttributesDigest::TextAuto,
+                Some(Mode::Lf) => AttributesDigest::TextAutoInput,
+                Some(Mode::CrLf) => AttributesDigest::TextAutoCrlf,
+            },
+            (State::Set, None) => AttributesDigest::Text,
+            // The `eol` attribute implies `text`.
+            (_, Some(Mode::Lf)) => AttributesDigest::TextInput,
+            (_, Some(Mode::CrLf)) => AttributesDigest::TextCrlf,
+            (_, None) => return None,
+        })
+    }
+
+    fn is_auto(&self) -> bool {
+        matches!(
+            self,
+            AttributesDigest::TextAuto | AttributesDigest::TextAutoInput | AttributesDigest::TextAutoCrlf
+        )
+    }
+
+    /// The line ending to produce in the worktree, or `None` if content isn't supposed to be converted.
+    fn worktree_eol(&self, config: &Configuration) -> Option<Mode> {
+        match self {
+            AttributesDigest::Binary => None,
+            AttributesDigest::TextInput | AttributesDigest::TextAutoInput => Some(Mode::Lf),
+            AttributesDigest::TextCrlf | AttributesDigest::TextAutoCrlf => Some(Mode::CrLf),
+            AttributesDigest::Text | AttributesDigest::TextAuto => Some(config.text_eol()),
+        }
+    }
+}
+
+/// Statistics about the line endings and characters of a buffer, used to decide if it's text and how to convert it.
+#[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub struct Stats {
+    /// The amount of null bytes.
+    pub null: usize,
+    /// The amount of `\r` not followed by `\n`.
+    pub lone_cr: usize,
+    /// The amount of `\n` not preceded by `\r`.
+    pub lone_lf: usize,
+    /// The amount of `\r\n` pairs.
+    pub crlf: usize,
+    /// The amount of printable characters.
+    pub printable: usize,
+    /// The amount of non-printable characters.
+    pub non_printable: usize,
+}
+
+impl Stats {
+    /// Gather statistics about `buf` just like git does.
+    pub fn from_bytes(buf: &[u8]) -> Self {
+        let mut stats = Stats::default();
+        let mut bytes = buf.iter().peekable();
+        while let Some(b) = bytes.next() {
+            match b {
+                b'\r' => {
+                    if bytes.next_if_eq(&&b'\n').is_some() {
+                        stats.crlf += 1;
+                    } else {
+                        stats.lone_cr += 1;
+                    }
+                }
+                b'\n' => stats.lone_lf += 1,
+                127 => stats.non_printable += 1,
+                b'\x08' | b'\t' | b'\x1b' | b'\x0c' => stats.printable += 1,
+                0 => {
+                    stats.null += 1;
+                    stats.non_printable += 1;
+                }
+                b if *b < 32 => stats.non_printable += 1,
+                _ => stats.printable += 1,
+            }
+        }
+        // An EOF character (^Z) at the very end is a DOS-ism that doesn't make a file binary.
+        if buf.last() == Some(&b'\x1a') {
+            stats.non_printable -= 1;
+        }
+        stats
+    }
+
+    /// Return true if the content these statistics were gathered from should be considered binary, which is when
+    /// it contains lone carriage returns, null bytes or too many non-printable characters.
+    pub fn is_binary(&self) -> bool {
+        self.lone_cr > 0 || self.null > 0 || (self.printable >> 7) < self.non_printable
+    }
+
+    /// Return true if content with these statistics needs to be converted to have `CRLF` line endings in the worktree
+    /// according to `digest`.
+    fn will_convert_lf_to_crlf(&self, digest: AttributesDigest, config: &Configuration) -> bool {
+        if digest.worktree_eol(config) != Some(Mode::CrLf) || self.lone_lf == 0 {
+            return false;
+        }
+        // Files that already have carriage returns in the object database are left alone in automatic mode.
+        !(digest.is_auto() && (self.lone_cr > 0 || self.crlf > 0 || self.is_binary()))
+    }
+}
+
+///
+pub mod convert_to_git {
+    /// Determine what to do if a conversion wouldn't survive a round-trip through the object database, i.e. checking
+    /// out the converted content again wouldn't yield the original content. This is the value of `core.safecrlf`.
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    pub enum RoundTripCheck {
+        /// Fail the conversion with an [error][Error::LossyConversion].
+        Fail,
+        /// Perform the conversion but report the issue in the [outcome][Outcome::lossy_conversion].
+        Warn,
+    }
+
+    /// Configuration for [`convert_to_git()`][super::convert_to_git()].
+    #[derive(Default, PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    pub struct Options {
+        /// The line ending configuration of the repository.
+        pub config: super::Configuration,
+        /// If set, check if the conversion is reversible.
+        pub round_trip_check: Option<RoundTripCheck>,
+    }
+
+    /// The kind of information lost in a conversion.
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    pub enum LossyConversion {
+        /// `CRLF` line endings in the worktree would turn into `LF` when checked out again.
+        CrlfToLf,
+        /// `LF` line endings in the worktree would turn into `CRLF` when checked out again.
+        LfToCrlf,
+    }
+
+    impl std::fmt::Display for LossyConversion {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(match self {
+                LossyConversion::CrlfToLf => "CRLF would be replaced by LF",
+                LossyConversion::LfToCrlf => "LF would be replaced by CRLF",
+            })
+        }
+    }
+
+    /// The outcome of [`convert_to_git()`][super::convert_to_git()].
+    #[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+    pub struct Outcome {
+        /// If true, the converted content was written into the output buffer. Otherwise the input should be used as is.
+        pub converted: bool,
+        /// If [warnings were requested][RoundTripCheck::Warn], this is the reason for the conversion not being reversible.
+        pub lossy_conversion: Option<LossyConversion>,
+    }
+
+    /// The error returned by [`convert_to_git()`][super::convert_to_git()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error("The line ending conversion isn't reversible: {0}")]
+        LossyConversion(LossyConversion),
+    }
+}
+
+/// Normalize the line endings of `src` for storage in the object database according to `digest`, the combined
+/// line ending attributes of its path, or the [configuration][convert_to_git::Options::config] if `None`.
+///
+/// If `src` is converted, the result is written into `out`, which is cleared beforehand.
+/// `index_object` is only called in automatic mode to write the content of the currently staged object at the
+/// same path into the given buffer, returning `None` if there is no such object. Files whose staged content
+/// already contains `CRLF` line endings aren't normalized, to not produce changes for files that were committed
+/// with `CRLF` before.
+pub fn convert_to_git(
+    src: &[u8],
+    digest: Option<AttributesDigest>,
+    out: &mut Vec<u8>,
+    index_object: impl FnOnce(&mut Vec<u8>) -> Option<()>,
+    convert_to_git::Options {
+        config,
+        round_trip_check,
+    }: convert_to_git::Options,
+) -> Result<convert_to_git::Outcome, convert_to_git::Error> {
+    use convert_to_git::{LossyConversion, Outcome, RoundTripCheck};
+    let mut outcome = Outcome {
+        converted: false,
+        lossy_conversion: None,
+    };
+    let digest = config.resolve(digest);
+    if digest == AttributesDigest::Binary || src.is_empty() {
+        return Ok(outcome);
+    }
+
+    let stats = Stats::from_bytes(src);
+    let mut convert_crlf_into_lf = stats.crlf > 0;
+    if digest.is_auto() {
+        if stats.is_binary() {
+            return Ok(outcome);
+        }
+        if convert_crlf_into_lf {
+            out.clear();
+            convert_crlf_into_lf = !(index_object(out).is_some() && {
+                let index_stats = Stats::from_bytes(out);
+                !index_stats.is_binary() && index_stats.crlf > 0
+            });
+        }
+    }
+
+    if let Some(check) = round_trip_check {
+        let mut new_stats = stats;
+        // simulate checkin
+        if convert_crlf_into_lf {
+            new_stats.lone_lf += new_stats.crlf;
+            new_stats.crlf = 0;
+        }
+        // simulate checkout
+        if new_stats.will_convert_lf_to_crlf(digest, &config) {
+            new_stats.crlf += new_stats.lone_lf;
+            new_stats.lone_lf = 0;
+        }
+        let lossy_conversion = if stats.crlf > 0 && new_stats.crlf == 0 {
+            Some(LossyConversion::CrlfToLf)
+        } else if stats.lone_lf > 0 && new_stats.lone_lf == 0 {
+            Some(LossyConversion::LfToCrlf)
+        } else {
+            None
+        };
+        match (check, lossy_conversion) {
+            (RoundTripCheck::Fail, Some(kind)) => return Err(convert_to_git::Error::LossyConversion(kind)),
+            (RoundTripCheck::Warn, kind) => outcome.lossy_conversion = kind,
+            (RoundTripCheck::Fail, None) => {}
+        }
+    }
+
+    if !convert_crlf_into_lf {
+        return Ok(outcome);
+    }
+    out.clear();
+    out.reserve(src.len() - stats.crlf);
+    let mut bytes = src.iter().peekable();
+    while let Some(b) = bytes.next() {
+        if !(*b == b'\r' && bytes.peek() == Some(&&b'\n')) {
+            out.push(*b);
+        }
+    }
+    outcome.converted = true;
+    Ok(outcome)
+}
+
+/// Convert the line endings of `src`, as stored in the object database, for use in the worktree according to `digest`,
+/// the combined line ending attributes of its path, or `config` if `None`.
+///
+/// Return true if `src` was converted, in which case the result was written into `out` which is cleared beforehand.
+pub fn convert_to_worktree(
+    src: &[u8],
+    digest: Option<AttributesDigest>,
+    out: &mut Vec<u8>,
+    config: Configuration,
+) -> bool {
+    let digest = config.resolve(digest);
+    if digest == AttributesDigest::Binary || src.is_empty() {
+        return false;
+    }
+    let stats = Stats::from_bytes(src);
+    if !stats.will_convert_lf_to_crlf(digest, &config) {
+        return false;
+    }
+
+    out.clear();
+    out.reserve(src.len() + stats.lone_lf);
+    let mut previous = None;
+    for b in src {
+        if *b == b'\n' && previous != Some(b'\r') {
+            out.push(b'\r');
+        }
+        out.push(*b);
+        previous = Some(*b);
+    }
+    true
+}
@@ -0,0 +1,5 @@
+//! Filters to transform content when it moves between the worktree and the object database.
+///
+pub mod driver;
+///
+pub mod eol;
@@ -24,7 +24,8 @@ pub struct Capabilities {
 impl Capabilities {
     /// try to determine all values in this context by probing them in the given `git_dir`, which
     /// should be on the file system the git repository is located on.
-    /// `git_dir` is a typical git repository, expected to be populated with the typical files like `config`.
+    /// `git_dir` is a typical git repository, expected to be populated with the typical files like `config`, but
+    /// it may also be any other writable directory, like the root of a worktree.
     ///
     /// All errors are ignored and interpreted on top of the default for the platform the binary is compiled for.
     pub fn probe(git_dir: impl AsRef<Path>) -> Self {
@@ -59,14 +60,28 @@ impl Capabilities {
         Ok(false)
     }
 
-    fn probe_ignore_case(git_dir: &Path) -> std::io::Result<bool> {
-        std::fs::metadata(git_dir.join("cOnFiG")).map(|_| true).or_else(|err| {
-            if err.kind() == std::io::ErrorKind::NotFound {
-                Ok(false)
-            } else {
-                Err(err)
-            }
-        })
+    fn probe_ignore_case(root: &Path) -> std::io::Result<bool> {
+        fn exists(path: &Path) -> std::io::Result<bool> {
+            std::fs::symlink_metadata(path).map(|_| true).or_else(|err| {
+                if err.kind() == std::io::ErrorKind::NotFound {
+                    Ok(false)
+                } else {
+                    Err(err)
+                }
+            })
+        }
+        // git directories have a configuration file we can use, but other directories, like a worktree, need our own.
+        if root.join("config").is_file() {
+            return exists(&root.join("cOnFiG"));
+        }
+        let test_path = root.join("_test_ignore_case");
+        std::fs::OpenOptions::new()
+            .create_new(true)
+            .write(true)
+            .open(&test_path)?;
+        let res = exists(&root.join("_TEST_IGNORE_CASE"));
+        std::fs::remove_file(test_path)?;
+        res
     }
 
     fn probe_precompose_unicode(root: &Path) -> std::io::Result<bool> {
@@ -0,0 +1,138 @@
+//! Learn which paths of a worktree changed since a previous query from the file system monitor configured in
+//! `core.fsmonitor`, so that only entries reported as changed have to be checked against the worktree.
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use bstr::{BStr, BString, ByteSlice};
+use git_index::extension::fs_monitor::{Changed, Outcome, Token};
+
+/// A file system monitor as configured in `core.fsmonitor`.
+#[derive(PartialEq, Eq, Debug, Hash, Clone)]
+pub enum Monitor {
+    /// A hook to run for each query, for example one that asks watchman like the `fsmonitor-watchman` sample hook of git.
+    Hook(OsString),
+    /// The builtin file system monitor daemon of git, listening on the given socket.
+    Daemon {
+        /// The path to the socket, usually `$GIT_DIR/fsmonitor--daemon.ipc`.
+        socket: PathBuf,
+    },
+}
+
+///
+pub mod query {
+    /// The error returned by [`Monitor::query()`][super::Monitor::query()].
+    #[derive(Debug, thiserror::Error)]
+    #[allow(missing_docs)]
+    pub enum Error {
+        #[error(transparent)]
+        Hook(#[from] git_index::extension::fs_monitor::query::Error),
+        #[error("Could not communicate with the file system monitor daemon")]
+        Io(#[from] std::io::Error),
+        #[error("The file system monitor daemon sent a response without token")]
+        MissingToken,
+        #[error("The builtin file system monitor daemon is not supported on this platform")]
+        Unsupported,
+    }
+}
+
+impl Monitor {
+    /// Create a monitor from the `value` of `core.fsmonitor` in the repository at `git_dir`, or return `None` if it is
+    /// disabled.
+    ///
+    /// Like in git, boolean values turn the builtin daemon on or off, and all other values are the path to a hook.
+    pub fn from_config_value(value: &BStr, git_dir: &Path) -> Option<Self> {
+        let lowercase = value.to_ascii_lowercase();
+        match lowercase.as_slice() {
+            b"" | b"false" | b"no" | b"off" | b"0" => None,
+            b"true" | b"yes" | b"on" | b"1" => Some(Monitor::Daemon {
+                socket: git_dir.join("fsmonitor--daemon.ipc"),
+            }),
+            _ => Some(Monitor::Hook(value.to_os_str_lossy().into_owned())),
+        }
+    }
+
+    /// Ask the monitor which paths of `worktree` changed since the query that produced `token`.
+    pub fn query(&self, worktree: &Path, token: &Token) -> Result<Outcome, query::Error> {
+        match self {
+            Monitor::Hook(hook) => Ok(git_index::extension::fs_monitor::query_hook(hook, worktree, token)?),
+            Monitor::Daemon { socket } => query_daemon(socket, token),
+        }
+    }
+
+    /// Query the monitor for changes in `worktree` since `index` was last updated with it, and mark all entries that
+    /// might have changed as such, so that entries with the [`FSMONITOR_VALID`][git_index::entry::Flags::FSMONITOR_VALID]
+    /// flag don't have to be checked against the worktree.
+    ///
+    /// If `index` has no token yet, all entries are considered changed. Like git, hooks are asked using version 2 of
+    /// the protocol first, falling back to version 1 if they fail.
+    pub fn apply_to_index(&self, worktree: &Path, index: &mut git_index::State) -> Result<(), query::Error> {
+        let outcome = match index.fs_monitor().map(|m| m.token.clone()) {
+            Some(token) => self.query(worktree, &token)?,
+            None => match self.query(
+                worktree,
+                &Token::V2 {
+                    token: Default::default(),
+                },
+            ) {
+                Err(query::Error::Hook(_)) => self.query(worktree, &Token::V1 { nanos_since_1970: 0 })?,
+                res => res?,
+            },
+        };
+        index.apply_fs_monitor_changes(outcome);
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn query_daemon(socket: &Path, token: &Token) -> Result<Outcome, query::Error> {
+    use std::io::{Read, Write};
+
+    use git_packetline::decode::{hex_prefix, PacketLineOrWantedSize};
+
+    let token = match token {
+        Token::V2 { token } => token.as_slice(),
+        // The daemon doesn't know timestamps and will answer that everything changed.
+        Token::V1 { .. } => &[],
+    };
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)?;
+    if !token.is_empty() {
+        write!(stream, "{:04x}", token.len() + 4)?;
+        stream.write_all(token)?;
+    }
+    stream.write_all(b"0000")?;
+    stream.flush()?;
+
+    let mut response = Vec::new();
+    let mut hex = [0u8; 4];
+    loop {
+        stream.read_exact(&mut hex)?;
+        match hex_prefix(&hex).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))? {
+            PacketLineOrWantedSize::Line(_) => break,
+            PacketLineOrWantedSize::Wanted(len) => {
+                let start = response.len();
+                response.resize(start + len as usize, 0);
+                stream.read_exact(&mut response[start..])?;
+            }
+        }
+    }
+
+    let mut response = response.split_str("\0");
+    let token = match response.next() {
+        Some(token) if !token.is_empty() => Token::V2 { token: token.into() },
+        _ => return Err(query::Error::MissingToken),
+    };
+    let paths: Vec<BString> = response.filter(|path| !path.is_empty()).map(Into::into).collect();
+    let changed = if paths.iter().any(|path| path == "/") {
+        Changed::All
+    } else {
+        Changed::Paths(paths)
+    };
+    Ok(Outcome { token, changed })
+}
+
+#[cfg(not(unix))]
+fn query_daemon(_socket: &Path, _token: &Token) -> Result<Outcome, query::Error> {
+    Err(query::Error::Unsupported)
+}
@@ -29,6 +29,8 @@ pub struct PathCache {
 
     /// If there is a symlink or a file in our path, try to unlink it before creating the directory.
     pub unlink_on_collision: bool,
+    /// If true, all leading directories were created up front and won't be created again.
+    pub(crate) leading_dirs_exist: bool,
 
     /// just for testing
     #[cfg(debug_assertions)]
@@ -54,6 +56,7 @@ mod cache {
                 #[cfg(debug_assertions)]
                 test_mkdir_calls: 0,
                 unlink_on_collision: false,
+                leading_dirs_exist: false,
             }
         }
 
@@ -97,7 +100,7 @@ mod cache {
                 self.valid_relative.push(comp);
                 self.valid_components += 1;
                 let res = (|| {
-                    if components.peek().is_some() || target_is_dir {
+                    if (components.peek().is_some() || target_is_dir) && !self.leading_dirs_exist {
                         #[cfg(debug_assertions)]
                         {
                             self.test_mkdir_calls += 1;
@@ -150,6 +153,31 @@ pub struct Collision {
     pub error_kind: std::io::ErrorKind,
 }
 
+/// Why two paths are considered the same by the file system.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum NameCollisionKind {
+    /// The paths only differ in case, on a file system that [ignores case][crate::fs::Capabilities::ignore_case].
+    Case,
+    /// The paths only differ in their unicode normalization form, on a file system that
+    /// [decomposes unicode][crate::fs::Capabilities::precompose_unicode].
+    UnicodeNormalization,
+    /// The paths only differ in trailing dots or spaces, which are removed by Windows.
+    TrailingDotOrSpace,
+    /// One path is the short name Windows would generate for the other, like `PROGRA~1` for `Program Files`.
+    ShortName,
+}
+
+/// An index entry that wasn't checked out as its path refers to the same file on disk as the path of a previous entry.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NameCollision {
+    /// The path of the entry that wasn't checked out.
+    pub path: BString,
+    /// The path of the entry or directory it collides with, which was checked out instead.
+    pub colliding_with: BString,
+    /// Why the paths are considered the same.
+    pub kind: NameCollisionKind,
+}
+
 pub struct ErrorRecord {
     /// the path that encountered the error.
     pub path: BString,
@@ -158,11 +186,17 @@ pub struct ErrorRecord {
 }
 
 pub struct Outcome {
+    /// The capabilities of the file system that were used during checkout, possibly obtained by
+    /// [probing][Options::probe_capabilities], and which should be used when checking the worktree for changes.
+    pub fs: crate::fs::Capabilities,
     /// The amount of files updated, or created.
     pub files_updated: usize,
     /// The amount of bytes written to disk,
     pub bytes_written: u64,
     pub collisions: Vec<Collision>,
+    /// Entries that weren't checked out as their path refers to the same file as the one of a previous entry, according to
+    /// the [capabilities of the file system][Options::fs] and [`protect_ntfs`][Options::protect_ntfs].
+    pub name_collisions: Vec<NameCollision>,
     pub errors: Vec<ErrorRecord>,
 }
 
@@ -170,6 +204,24 @@ pub struct Outcome {
 pub struct Options {
     /// capabilities of the file system
     pub fs: crate::fs::Capabilities,
+    /// If true, default false, the capabilities of the file system are probed in the destination directory once
+    /// before checking out any entry, and used instead of `fs`.
+    ///
+    /// Symbolic links are written as files containing the link target if they aren't supported, and the executable
+    /// bit is ignored if it isn't retained.
+    pub probe_capabilities: bool,
+    /// Control how line endings of files are converted when writing them into the worktree, similar to `core.autocrlf`
+    /// and `core.eol`. As attributes aren't considered, the configuration applies to all files.
+    ///
+    /// By default, line endings are left untouched.
+    pub eol: crate::filter::eol::Configuration,
+    /// If true, paths are compared like on NTFS or FAT before checking them out, which ignores trailing dots and
+    /// spaces and considers short names like `PROGRA~1` to be the same as the long names they are generated for.
+    /// Entries whose paths collide with those of previous entries aren't checked out, but reported as
+    /// [name collisions][Outcome::name_collisions].
+    ///
+    /// Defaults to true on Windows, similar to `core.protectNTFS`.
+    pub protect_ntfs: bool,
     /// If set, don't use more than this amount of threads.
     /// Otherwise, usually use as many threads as there are logical cores.
     /// A value of 0 is interpreted as no-limit
@@ -204,6 +256,9 @@ impl Default for Options {
     fn default() -> Self {
         Options {
             fs: Default::default(),
+            probe_capabilities: false,
+            eol: Default::default(),
+            protect_ntfs: cfg!(windows),
             thread_limit: None,
             destination_is_initially_empty: false,
             keep_going: false,
@@ -0,0 +1,216 @@
+//! Find index entries that would be written to the same place on disk, as file systems may consider paths equal even
+//! though their bytes differ.
+use std::collections::{hash_map::Entry, HashMap};
+
+use bstr::{BStr, BString, ByteSlice, ByteVec};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::index::checkout::{NameCollision, NameCollisionKind};
+
+/// The rules by which the file system compares paths.
+#[derive(Clone, Copy)]
+pub struct Rules {
+    pub ignore_case: bool,
+    pub precompose_unicode: bool,
+    pub protect_ntfs: bool,
+}
+
+impl Rules {
+    pub fn from_options(opts: &crate::index::checkout::Options) -> Self {
+        Rules {
+            ignore_case: opts.fs.ignore_case,
+            precompose_unicode: opts.fs.precompose_unicode,
+            protect_ntfs: opts.protect_ntfs,
+        }
+    }
+
+    /// Return true if paths with different bytes can be equal on disk.
+    pub fn may_collide(&self) -> bool {
+        self.ignore_case || self.precompose_unicode || self.protect_ntfs
+    }
+
+    /// Turn `component` into the name the file system will use to identify it.
+    fn normalize_component(&self, component: &[u8], out: &mut BString) {
+        let mut component = component;
+        if self.protect_ntfs {
+            component = component.trim_end_with(|c| c == '.' || c == ' ');
+        }
+        match component.to_str() {
+            Ok(component) if self.precompose_unicode || self.ignore_case => {
+                let component: std::borrow::Cow<'_, str> = if self.precompose_unicode {
+                    component.nfc().collect::<String>().into()
+                } else {
+                    component.into()
+                };
+                if self.ignore_case {
+                    out.push_str(component.to_lowercase());
+                } else {
+                    out.push_str(component.as_ref());
+                }
+            }
+            _ if self.ignore_case => out.extend(component.iter().map(|b| b.to_ascii_lowercase())),
+            _ => out.push_str(component),
+        }
+    }
+
+    /// Return the normalized version of the slash-separated `path`.
+    fn normalize(&self, path: &BStr) -> BString {
+        let mut out = BString::from(Vec::with_capacity(path.len()));
+        for (idx, component) in path.split_str("/").enumerate() {
+            if idx != 0 {
+                out.push(b'/');
+            }
+            self.normalize_component(component, &mut out);
+        }
+        out
+    }
+
+    /// Figure out why `a` and `b` are considered the same path.
+    fn kind(&self, a: &BStr, b: &BStr) -> NameCollisionKind {
+        let only = |rules: Rules| rules.normalize(a) == rules.normalize(b);
+        let none = Rules {
+            ignore_case: false,
+            precompose_unicode: false,
+            protect_ntfs: false,
+        };
+        if self.protect_ntfs
+            && only(Rules {
+                protect_ntfs: true,
+                ..none
+            })
+        {
+            NameCollisionKind::TrailingDotOrSpace
+        } else if self.precompose_unicode
+            && only(Rules {
+                precompose_unicode: true,
+                ..none
+            })
+        {
+            NameCollisionKind::UnicodeNormalization
+        } else {
+            NameCollisionKind::Case
+        }
+    }
+}
+
+/// Return the short name Windows would generate first for the long `name`, or `None` if `name` is a valid short name
+/// already.
+fn short_name(name: &[u8]) -> Option<BString> {
+    let (base, ext) = match name.rfind_byte(b'.') {
+        Some(pos) if pos != 0 => (&name[..pos], &name[pos + 1..]),
+        _ => (name, &b""[..]),
+    };
+    let is_short = base.len() <= 8 && ext.len() <= 3 && !base.contains(&b'.') && !name.contains(&b' ');
+    if is_short || name.is_empty() {
+        return None;
+    }
+    let sanitize = |part: &[u8], max: usize| -> BString {
+        part.iter()
+            .filter(|b| !matches!(b, b' ' | b'.'))
+            .map(|b| b.to_ascii_lowercase())
+            .take(max)
+            .collect::<Vec<u8>>()
+            .into()
+    };
+    let mut short = sanitize(base, 6);
+    short.push_str("~1");
+    let ext = sanitize(ext, 3);
+    if !ext.is_empty() {
+        short.push(b'.');
+        short.push_str(ext);
+    }
+    Some(short)
+}
+
+/// Return true if `name` looks like a short name Windows generates, like `progra~1`.
+fn is_short_name(name: &[u8]) -> bool {
+    let base = name.rfind_byte(b'.').map_or(name, |pos| &name[..pos]);
+    match base.rfind_byte(b'~') {
+        Some(pos) => pos != 0 && pos + 1 < base.len() && base[pos + 1..].iter().all(u8::is_ascii_digit),
+        None => false,
+    }
+}
+
+/// Find all paths of entries in `index` that collide with the path of a previous entry according to `rules`, and
+/// return them along with a list of flags, one per entry, indicating if it should be skipped.
+///
+/// Directories collide with files of the same name, and paths of entries within them are compared with the name
+/// of the directory the file system will use.
+pub fn find(index: &git_index::State, rules: Rules) -> (Vec<NameCollision>, Vec<bool>) {
+    let mut collisions = Vec::new();
+    let mut skip = Vec::new();
+    if !rules.may_collide() {
+        return (collisions, skip);
+    }
+    // normalized path -> (original path, is directory)
+    let mut seen: HashMap<BString, (BString, bool)> = HashMap::new();
+    // normalized short name of a long name -> original path
+    let mut short_names: HashMap<BString, BString> = HashMap::new();
+    skip.resize(index.entries().len(), false);
+
+    for (idx, entry) in index.entries().iter().enumerate() {
+        if entry.flags.contains(git_index::entry::Flags::SKIP_WORKTREE) {
+            continue;
+        }
+        let path = entry.path(index);
+        let num_components = path.split_str("/").count();
+        let mut collision = None;
+        let mut start = 0;
+        for (component_idx, component) in path.split_str("/").enumerate() {
+            let end = start + component.len();
+            let is_dir = component_idx + 1 != num_components;
+            let original = path[..end].as_bstr();
+            let parent = path[..start].as_bstr();
+            // Short names are always compared case-insensitively.
+            let short_name_key = |name: &[u8]| {
+                let mut key = rules.normalize(parent);
+                key.extend(name.iter().map(u8::to_ascii_lowercase));
+                key
+            };
+            start = end + 1;
+
+            if rules.protect_ntfs && is_short_name(component) {
+                if let Some(long) = short_names
+                    .get(&short_name_key(component))
+                    .filter(|long| long.as_slice() != original.as_bytes())
+                {
+                    collision = Some(NameCollision {
+                        path: path.into(),
+                        colliding_with: long.clone(),
+                        kind: NameCollisionKind::ShortName,
+                    });
+                    break;
+                }
+            }
+            match seen.entry(rules.normalize(original)) {
+                Entry::Vacant(e) => {
+                    if let Some(short) = rules.protect_ntfs.then(|| short_name(component)).flatten() {
+                        short_names
+                            .entry(short_name_key(&short))
+                            .or_insert_with(|| original.into());
+                    }
+                    e.insert((original.into(), is_dir));
+                }
+                Entry::Occupied(e) => {
+                    let (existing, existing_is_dir) = e.get();
+                    // Entries of different stages share the same path, and files in directories whose names only
+                    // differ in case end up in the same directory, which is fine unless their names collide as well.
+                    if existing.as_slice() == original.as_bytes() || (*existing_is_dir && is_dir) {
+                        continue;
+                    }
+                    collision = Some(NameCollision {
+                        path: path.into(),
+                        kind: rules.kind(existing.as_bstr(), original),
+                        colliding_with: existing.clone(),
+                    });
+                    break;
+                }
+            }
+        }
+        if let Some(collision) = collision {
+            collisions.push(collision);
+            skip[idx] = true;
+        }
+    }
+    (collisions, skip)
+}
@@ -1,4 +1,4 @@
-use std::{convert::TryInto, fs::OpenOptions, io::Write, path::Path, time::Duration};
+use std::{fs::OpenOptions, io::Write, path::Path};
 
 use bstr::BStr;
 use git_hash::oid;
@@ -21,9 +21,11 @@ pub fn checkout<Find, E>(
         },
         destination_is_initially_empty,
         overwrite_existing,
+        eol,
         ..
     }: index::checkout::Options,
     buf: &mut Vec<u8>,
+    filter_buf: &mut Vec<u8>,
 ) -> Result<usize, index::checkout::Error<E>>
 where
     Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, E>,
@@ -54,8 +56,14 @@ where
                 options.mode(0o777);
             }
 
+            let data = if crate::filter::eol::convert_to_worktree(obj.data, None, filter_buf, eol) {
+                filter_buf.as_slice()
+            } else {
+                obj.data
+            };
+
             let mut file = try_write_or_unlink(dest, overwrite_existing, |p| options.open(p))?;
-            file.write_all(obj.data)?;
+            file.write_all(data)?;
 
             // For possibly existing, overwritten files, we must change the file mode explicitly.
             #[cfg(unix)]
@@ -67,9 +75,9 @@ where
             }
             // NOTE: we don't call `file.sync_all()` here knowing that some filesystems don't handle this well.
             //       revisit this once there is a bug to fix.
-            update_fstat(entry, file.metadata()?)?;
+            update_fstat(entry, file.metadata()?);
             file.close()?;
-            obj.data.len()
+            data.len()
         }
         git_index::entry::Mode::SYMLINK => {
             let obj = find(&entry.id, buf).map_err(|err| index::checkout::Error::Find {
@@ -92,7 +100,7 @@ where
                 file.close()?;
             }
 
-            update_fstat(entry, std::fs::symlink_metadata(&dest)?)?;
+            update_fstat(entry, std::fs::symlink_metadata(&dest)?);
             obj.data.len()
         }
         git_index::entry::Mode::DIR => todo!(),
@@ -161,27 +169,6 @@ fn open_options(path: &Path, destination_is_initially_empty: bool, overwrite_exi
     options
 }
 
-fn update_fstat<E>(entry: &mut Entry, meta: std::fs::Metadata) -> Result<(), index::checkout::Error<E>>
-where
-    E: std::error::Error + Send + Sync + 'static,
-{
-    let ctime = meta
-        .created()
-        .map_or(Ok(Duration::default()), |x| x.duration_since(std::time::UNIX_EPOCH))?;
-    let mtime = meta
-        .modified()
-        .map_or(Ok(Duration::default()), |x| x.duration_since(std::time::UNIX_EPOCH))?;
-
-    let stat = &mut entry.stat;
-    stat.mtime.secs = mtime
-        .as_secs()
-        .try_into()
-        .expect("by 2038 we found a solution for this");
-    stat.mtime.nsecs = mtime.subsec_nanos();
-    stat.ctime.secs = ctime
-        .as_secs()
-        .try_into()
-        .expect("by 2038 we found a solution for this");
-    stat.ctime.nsecs = ctime.subsec_nanos();
-    Ok(())
+fn update_fstat(entry: &mut Entry, meta: std::fs::Metadata) {
+    entry.stat = git_index::entry::Stat::from_fs(&meta);
 }
@@ -6,8 +6,19 @@ use git_hash::oid;
 use crate::index::checkout::PathCache;
 
 pub mod checkout;
+pub(crate) mod collision;
 pub(crate) mod entry;
+pub mod status;
 
+/// Checkout all entries of `index` into `dir`, using `find` to obtain their blobs and processing them in parallel
+/// if `options` allow more than one thread.
+///
+/// Before entries are processed by multiple threads, all leading directories are created at once to avoid threads
+/// racing to create the same directories.
+///
+/// Entries whose paths refer to the same file on disk as the paths of previous entries, for instance because the
+/// file system ignores case, are not checked out and reported as [name collisions][checkout::Outcome::name_collisions].
+///
 /// Note that interruption still produce an `Ok(…)` value, so the caller should look at `should_interrupt` to communicate the outcome.
 pub fn checkout<Find, E>(
     index: &mut git_index::State,
@@ -16,7 +27,7 @@ pub fn checkout<Find, E>(
     files: &mut impl Progress,
     bytes: &mut impl Progress,
     should_interrupt: &AtomicBool,
-    options: checkout::Options,
+    mut options: checkout::Options,
 ) -> Result<checkout::Outcome, checkout::Error<E>>
 where
     Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, E> + Send + Clone,
@@ -24,9 +35,13 @@ where
 {
     let num_files = AtomicUsize::default();
     let dir = dir.into();
+    if options.probe_capabilities {
+        options.fs = crate::fs::Capabilities::probe(&dir);
+    }
 
     let mut ctx = chunk::Context {
         buf: Vec::new(),
+        filter_buf: Vec::new(),
         path_cache: {
             let mut cache = PathCache::new(dir.clone());
             cache.unlink_on_collision = options.overwrite_existing;
@@ -35,15 +50,28 @@ where
         find: find.clone(),
         options,
         num_files: &num_files,
+        should_interrupt,
     };
+    let (name_collisions, skip) = collision::find(index, collision::Rules::from_options(&options));
+    files.inc_by(name_collisions.len());
+    let is_skipped = |idx: usize| skip.get(idx).copied().unwrap_or(false);
+
     let (chunk_size, thread_limit, num_threads) = git_features::parallel::optimize_chunk_size_and_thread_limit(
         100,
         index.entries().len().into(),
         options.thread_limit,
         None,
     );
+    let leading_dirs_exist =
+        num_threads > 1 && chunk::create_leading_directories(index, &is_skipped, &mut ctx.path_cache, should_interrupt);
 
-    let entries_with_paths = interrupt::Iter::new(index.entries_mut_with_paths(), should_interrupt);
+    let entries_with_paths = interrupt::Iter::new(
+        index
+            .entries_mut_with_paths()
+            .enumerate()
+            .filter_map(|(idx, entry)| (!is_skipped(idx)).then(|| entry)),
+        should_interrupt,
+    );
     let chunk::Outcome {
         mut collisions,
         mut errors,
@@ -69,11 +97,14 @@ where
                             path_cache: {
                                 let mut cache = PathCache::new(dir.clone());
                                 cache.unlink_on_collision = options.overwrite_existing;
+                                cache.leading_dirs_exist = leading_dirs_exist;
                                 cache
                             },
                             buf: Vec::new(),
+                            filter_buf: Vec::new(),
                             options,
                             num_files,
+                            should_interrupt,
                         },
                     )
                 }
@@ -89,7 +120,16 @@ where
         )?
     };
 
+    if num_threads > 1 {
+        // Threads finish in any order, but results should be the same for each run.
+        collisions.sort();
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
     for (entry, entry_path) in delayed {
+        if should_interrupt.load(Ordering::Relaxed) {
+            break;
+        }
         bytes_written += chunk::checkout_entry_handle_result(
             entry,
             entry_path,
@@ -102,15 +142,17 @@ where
     }
 
     Ok(checkout::Outcome {
+        fs: options.fs,
         files_updated: ctx.num_files.load(Ordering::Relaxed),
         collisions,
+        name_collisions,
         errors,
         bytes_written,
     })
 }
 
 mod chunk {
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     use bstr::BStr;
     use git_features::progress::Progress;
@@ -189,10 +231,45 @@ mod chunk {
         pub find: Find,
         pub path_cache: PathCache,
         pub buf: Vec<u8>,
+        /// Holds the result of filtering the content in `buf`.
+        pub filter_buf: Vec<u8>,
         pub options: checkout::Options,
         /// We keep these shared so that there is the chance for printing numbers that aren't looking like
         /// multiple of chunk sizes. Purely cosmetic. Otherwise it's the same as `files`.
         pub num_files: &'a AtomicUsize,
+        /// Shared among all threads to stop processing entries as soon as possible.
+        pub should_interrupt: &'a AtomicBool,
+    }
+
+    /// Create the leading directories of all entries that will be checked out, in order, and return true if all of them
+    /// could be created.
+    ///
+    /// Otherwise directories will be created while checking out entries, which is when collisions are detected.
+    pub fn create_leading_directories(
+        index: &git_index::State,
+        is_skipped: impl Fn(usize) -> bool,
+        path_cache: &mut PathCache,
+        should_interrupt: &AtomicBool,
+    ) -> bool {
+        for (idx, entry) in index.entries().iter().enumerate() {
+            if entry.flags.contains(git_index::entry::Flags::SKIP_WORKTREE) || is_skipped(idx) {
+                continue;
+            }
+            if should_interrupt.load(Ordering::Relaxed) {
+                return false;
+            }
+            let created = git_features::path::from_byte_slice(entry.path(index))
+                .ok()
+                .map_or(false, |path| {
+                    path_cache
+                        .append_relative_path_assure_leading_dir(path, entry.mode)
+                        .is_ok()
+                });
+            if !created {
+                return false;
+            }
+        }
+        true
     }
 
     pub fn process<'entry, Find, E>(
@@ -211,6 +288,9 @@ mod chunk {
         let mut bytes_written = 0;
 
         for (entry, entry_path) in entries_with_paths {
+            if ctx.should_interrupt.load(Ordering::Relaxed) {
+                break;
+            }
             // TODO: write test for that
             if entry.flags.contains(git_index::entry::Flags::SKIP_WORKTREE) {
                 files.inc();
@@ -252,15 +332,17 @@ mod chunk {
             find,
             path_cache,
             buf,
+            filter_buf,
             options,
             num_files,
+            should_interrupt: _,
         }: &mut Context<'_, Find>,
     ) -> Result<usize, checkout::Error<E>>
     where
         Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, E>,
         E: std::error::Error + Send + Sync + 'static,
     {
-        let res = entry::checkout(entry, entry_path, find, path_cache, *options, buf);
+        let res = entry::checkout(entry, entry_path, find, path_cache, *options, buf, filter_buf);
         files.inc();
         num_files.fetch_add(1, Ordering::SeqCst);
         match res {
@@ -0,0 +1,284 @@
+//! Determine if files in the worktree differ from their entries in the index, using stat information to avoid reading
+//! files whenever possible.
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use bstr::BString;
+use git_hash::oid;
+use git_index::{
+    entry::{Flags, Mode, Stat, Time},
+    Entry,
+};
+
+/// Configuration for [`compare_to_worktree()`] and [`index_to_worktree()`].
+#[derive(Clone, Copy, Debug)]
+pub struct Options {
+    /// The capabilities of the file system the worktree is on.
+    ///
+    /// Differences in the executable bit are ignored if it isn't retained, and symbolic links are expected to be files
+    /// containing the link target if they aren't supported.
+    pub fs: crate::fs::Capabilities,
+    /// Control how line endings of files are converted when comparing their content to the objects in the index,
+    /// similar to `core.autocrlf` and `core.eol`. As attributes aren't considered, the configuration applies to all files.
+    pub eol: crate::filter::eol::Configuration,
+    /// If true, a files creation time is taken into consideration when checking if a file changed, similar to
+    /// `core.trustCTime`.
+    ///
+    /// Default true.
+    pub trust_ctime: bool,
+    /// If true, all stat fields will be used when checking for up-to-date'ness of the entry. Otherwise
+    /// nano-second parts of mtime and ctime, uid, gid and inode number won't be used, leaving only
+    /// the whole-second part of ctime and mtime and the file size to be checked, similar to `core.checkStat=minimal`.
+    ///
+    /// Default true.
+    pub check_stat: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            fs: Default::default(),
+            eol: Default::default(),
+            trust_ctime: true,
+            check_stat: true,
+        }
+    }
+}
+
+/// How a file in the worktree differs from its entry in the index.
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Change {
+    /// The file doesn't exist anymore.
+    Removed,
+    /// The file changed its type, for example a file became a symbolic link.
+    Type,
+    /// Only the executable bit changed.
+    Mode,
+    /// The content of the file changed.
+    Modified,
+}
+
+/// The outcome of [`compare_to_worktree()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone, Copy)]
+pub enum Outcome {
+    /// The file in the worktree is the same as the one in the index.
+    Unchanged,
+    /// The content of the file is unchanged, but its stat information differs from the one stored in the index.
+    ///
+    /// Storing the contained stat information in the entry avoids reading the file the next time it is compared.
+    UnchangedWithNewStat(Stat),
+    /// The file differs in the given way.
+    Changed(Change),
+}
+
+impl Outcome {
+    /// Return the change if there is one.
+    pub fn change(&self) -> Option<Change> {
+        match self {
+            Outcome::Changed(change) => Some(*change),
+            Outcome::Unchanged | Outcome::UnchangedWithNewStat(_) => None,
+        }
+    }
+}
+
+/// The error returned by [`compare_to_worktree()`] and [`index_to_worktree()`].
+#[derive(Debug, thiserror::Error)]
+#[allow(missing_docs)]
+pub enum Error {
+    #[error("Could not convert path to UTF8: {}", .path)]
+    IllformedUtf8 { path: BString },
+    #[error("Could not read {} to compare it to its entry in the index", .path.display())]
+    Io { source: std::io::Error, path: PathBuf },
+}
+
+/// Compare `entry` to the file at `path` in the worktree and return how it differs, using `find` to obtain objects
+/// from the object database and `buf` as well as `filter_buf` as scratch space.
+///
+/// `index_timestamp` is the time at which the index containing `entry` was written. Entries that were modified at
+/// or after that time might have changed without their stat information changing, which is known as the racy-git
+/// problem, so the content of their files is always compared.
+///
+/// Like git, files are only read and hashed if their stat information doesn't allow to decide if they changed.
+/// Before hashing, line endings are converted according to the [configuration][Options::eol].
+///
+/// Entries marked as valid by the user or a file system monitor, and those that are excluded by sparse checkouts,
+/// are always considered unchanged.
+pub fn compare_to_worktree<Find, E>(
+    entry: &Entry,
+    path: &Path,
+    index_timestamp: Time,
+    mut find: Find,
+    options: Options,
+    buf: &mut Vec<u8>,
+    filter_buf: &mut Vec<u8>,
+) -> Result<Outcome, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, E>,
+{
+    if entry
+        .flags
+        .intersects(Flags::ASSUME_VALID | Flags::SKIP_WORKTREE | Flags::FSMONITOR_VALID)
+    {
+        return Ok(Outcome::Unchanged);
+    }
+    let io_err = |source: std::io::Error| Error::Io {
+        source,
+        path: path.to_owned(),
+    };
+    let meta = match path.symlink_metadata() {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Outcome::Changed(Change::Removed)),
+        Err(err) => return Err(io_err(err)),
+    };
+
+    match entry.mode {
+        Mode::DIR => return Ok(Outcome::Unchanged),
+        // Submodules are expected to be directories, but what's inside of them isn't looked at.
+        Mode::COMMIT => {
+            return Ok(if meta.is_dir() {
+                Outcome::Unchanged
+            } else {
+                Outcome::Changed(Change::Type)
+            })
+        }
+        _ => {}
+    }
+    let mode = match Mode::from_fs(&meta) {
+        Some(mode) => mode,
+        // A directory where a file used to be means the file is gone.
+        None if meta.is_dir() => return Ok(Outcome::Changed(Change::Removed)),
+        None => return Ok(Outcome::Changed(Change::Type)),
+    };
+    let is_symlink_as_file = entry.mode == Mode::SYMLINK && !options.fs.symlink && mode != Mode::SYMLINK;
+    if (entry.mode == Mode::SYMLINK) != (mode == Mode::SYMLINK) && !is_symlink_as_file {
+        return Ok(Outcome::Changed(Change::Type));
+    }
+    if options.fs.executable_bit && entry.mode != Mode::SYMLINK && entry.mode != mode {
+        return Ok(Outcome::Changed(Change::Mode));
+    }
+
+    let stat = Stat::from_fs(&meta);
+    let stat_matches = stat_matches(&entry.stat, &stat, options);
+    // The size of entries may be unknown, for instance after reading a tree into the index.
+    if entry.stat.size != stat.size && entry.stat.size != 0 {
+        return Ok(Outcome::Changed(Change::Modified));
+    }
+    let is_racy = index_timestamp != Time::default() && index_timestamp <= entry.stat.mtime;
+    if stat_matches && !is_racy {
+        return Ok(Outcome::Unchanged);
+    }
+
+    let id = if mode == Mode::SYMLINK {
+        let target = std::fs::read_link(path).map_err(io_err)?;
+        let target = git_features::path::into_bytes(target).map_err(|_| Error::IllformedUtf8 {
+            path: git_features::path::into_bytes_or_panic_on_windows(path)
+                .into_owned()
+                .into(),
+        })?;
+        hash(entry, &git_features::path::convert::to_unix_separators(target)).map_err(io_err)?
+    } else {
+        buf.clear();
+        std::io::Read::read_to_end(&mut std::fs::File::open(path).map_err(io_err)?, buf).map_err(io_err)?;
+        let data = if is_symlink_as_file {
+            buf.as_slice()
+        } else {
+            let outcome = crate::filter::eol::convert_to_git(
+                buf,
+                None,
+                filter_buf,
+                |out| find(&entry.id, out).ok().map(|_| ()),
+                crate::filter::eol::convert_to_git::Options {
+                    config: options.eol,
+                    round_trip_check: None,
+                },
+            )
+            .expect("no round-trip check means the conversion can't fail");
+            if outcome.converted {
+                filter_buf.as_slice()
+            } else {
+                buf.as_slice()
+            }
+        };
+        hash(entry, data).map_err(io_err)?
+    };
+
+    Ok(if id != entry.id {
+        Outcome::Changed(Change::Modified)
+    } else if stat_matches {
+        Outcome::Unchanged
+    } else {
+        Outcome::UnchangedWithNewStat(stat)
+    })
+}
+
+/// A record of an entry of the index that differs from its file in the worktree, as returned by [`index_to_worktree()`].
+#[derive(PartialEq, Eq, Debug, Hash, Ord, PartialOrd, Clone)]
+pub struct Record {
+    /// The index of the entry in the index.
+    pub entry_index: usize,
+    /// The path of the entry, relative to the worktree.
+    pub path: BString,
+    /// How the file in the worktree differs, which is never [`Outcome::Unchanged`].
+    pub outcome: Outcome,
+}
+
+/// Compare all entries of `index` to their files in the `worktree` using [`compare_to_worktree()`], and return records
+/// for all of those whose files don't match them exactly.
+pub fn index_to_worktree<Find, E>(
+    index: &git_index::State,
+    worktree: &Path,
+    mut find: Find,
+    options: Options,
+) -> Result<Vec<Record>, Error>
+where
+    Find: for<'a> FnMut(&oid, &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, E>,
+{
+    let timestamp = index.timestamp();
+    let index_timestamp = Time {
+        secs: timestamp.unix_seconds() as u32,
+        nsecs: timestamp.nanoseconds(),
+    };
+    let (mut buf, mut filter_buf) = (Vec::new(), Vec::new());
+    let mut records = Vec::new();
+    for (entry_index, entry) in index.entries().iter().enumerate() {
+        let entry_path = entry.path(index);
+        let path =
+            worktree.join(
+                git_features::path::from_byte_slice(entry_path).map_err(|_| Error::IllformedUtf8 {
+                    path: entry_path.to_owned(),
+                })?,
+            );
+        let outcome = compare_to_worktree(
+            entry,
+            &path,
+            index_timestamp,
+            &mut find,
+            options,
+            &mut buf,
+            &mut filter_buf,
+        )?;
+        if outcome != Outcome::Unchanged {
+            records.push(Record {
+                entry_index,
+                path: entry_path.to_owned(),
+                outcome,
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// Return true if the stat information `fs` obtained from disk matches the one stored in the index as `index`,
+/// ignoring the size and the device which isn't stable on all file systems.
+fn stat_matches(index: &Stat, fs: &Stat, options: Options) -> bool {
+    let time_matches = |a: Time, b: Time| a.secs == b.secs && (!options.check_stat || a.nsecs == b.nsecs);
+    time_matches(index.mtime, fs.mtime)
+        && (!options.trust_ctime || time_matches(index.ctime, fs.ctime))
+        && (!options.check_stat || (index.uid == fs.uid && index.gid == fs.gid && index.ino == fs.ino))
+}
+
+fn hash(entry: &Entry, data: &[u8]) -> std::io::Result<git_hash::ObjectId> {
+    git_object::compute_hash(entry.id.kind(), git_object::BlobRef { data })
+}
@@ -11,4 +11,8 @@ pub mod fs;
 
 pub mod index;
 
+pub mod filter;
+
+pub mod fsmonitor;
+
 pub(crate) mod os;
@@ -0,0 +1,13 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+git config core.autocrlf false
+
+printf "a\nb\n" > lf
+printf "a\r\nb\r\n" > crlf
+printf "a\0\nb\n" > binary
+
+git add -A
+git commit -m "Commit"
@@ -0,0 +1,21 @@
+#!/bin/bash
+set -eu -o pipefail
+
+git init -q
+git config commit.gpgsign false
+
+empty_oid=$(git hash-object -w --stdin </dev/null)
+
+git update-index --index-info <<-EOF
+100644 $empty_oid	Program Files/file
+100644 $empty_oid	a
+100644 $empty_oid	a.
+100644 $empty_oid	b /c
+100644 $empty_oid	b/c
+100644 $empty_oid	long-name.txt
+100644 $empty_oid	long-n~1.txt
+100644 $empty_oid	progra~1/other
+EOF
+
+git commit -m "init"
+git checkout -f HEAD;
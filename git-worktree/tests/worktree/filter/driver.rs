@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+
+use bstr::{BString, ByteSlice};
+use git_worktree::filter::driver::{self, process, Driver, Operation, Outcome};
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn text(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|line| format!("{:04x}{}\n", line.len() + 5, line))
+        .collect()
+}
+
+fn data(data: &str) -> String {
+    format!("{:04x}{}", data.len() + 4, data)
+}
+
+const FLUSH: &str = "0000";
+
+fn handshake_response(capabilities: &[&str]) -> String {
+    text(&["git-filter-server", "version=2"]) + FLUSH + &text(capabilities) + FLUSH
+}
+
+fn client(response: String) -> Result<(process::Client, Recorder), process::handshake::Error> {
+    let input = Recorder::default();
+    let client = process::Client::from_io(
+        input.clone(),
+        std::io::Cursor::new(response.into_bytes()),
+        &["clean", "smudge", "delay"],
+    )?;
+    Ok((client, input))
+}
+
+mod process_client {
+    use super::*;
+
+    #[test]
+    fn handshake_and_smudge() -> crate::Result {
+        let response = handshake_response(&["capability=clean", "capability=smudge"])
+            + &text(&["status=success"])
+            + FLUSH
+            + &data("HELLO")
+            + FLUSH
+            + FLUSH;
+        let (mut client, input) = client(response)?;
+        assert!(client.supports("clean"));
+        assert!(client.supports("smudge"));
+        assert!(
+            !client.supports("delay"),
+            "the process decides which capabilities to use"
+        );
+
+        let mut out = Vec::new();
+        let status = client.invoke("smudge", "dir/file".into(), b"hello", false, &mut out)?;
+        assert_eq!(status, process::Status::Success);
+        assert_eq!(out.as_bstr(), "HELLO");
+
+        let expected = text(&["git-filter-client", "version=2"])
+            + FLUSH
+            + &text(&["capability=clean", "capability=smudge", "capability=delay"])
+            + FLUSH
+            + &text(&["command=smudge", "pathname=dir/file"])
+            + FLUSH
+            + &data("hello")
+            + FLUSH;
+        assert_eq!(input.0.lock().unwrap().as_bstr(), expected.as_str());
+        Ok(())
+    }
+
+    #[test]
+    fn status_changes_after_sending_content_fail_the_request() -> crate::Result {
+        let response = handshake_response(&["capability=clean"])
+            + &text(&["status=success"])
+            + FLUSH
+            + &data("partial")
+            + FLUSH
+            + &text(&["status=error"])
+            + FLUSH;
+        let (mut client, _) = client(response)?;
+        let err = client
+            .invoke("clean", "file".into(), b"content", false, &mut Vec::new())
+            .unwrap_err();
+        assert!(matches!(err, process::invoke::Error::Status { status } if status == "error"));
+        Ok(())
+    }
+
+    #[test]
+    fn abort_prevents_further_requests() -> crate::Result {
+        let response = handshake_response(&["capability=clean"]) + &text(&["status=abort"]) + FLUSH;
+        let (mut client, _) = client(response)?;
+        for _ in 0..2 {
+            let err = client
+                .invoke("clean", "file".into(), b"content", false, &mut Vec::new())
+                .unwrap_err();
+            assert!(matches!(err, process::invoke::Error::Aborted));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn delayed_smudging() -> crate::Result {
+        let response = handshake_response(&["capability=smudge", "capability=delay"])
+            + &text(&["status=delayed"])
+            + FLUSH
+            + &text(&["pathname=a", "pathname=b"])
+            + FLUSH
+            + &text(&["status=success"])
+            + FLUSH
+            + &text(&["status=success"])
+            + FLUSH
+            + &data("A")
+            + FLUSH
+            + FLUSH
+            + FLUSH
+            + &text(&["status=success"])
+            + FLUSH;
+        let (mut client, input) = client(response)?;
+
+        let mut out = Vec::new();
+        let status = client.invoke("smudge", "a".into(), b"a", true, &mut out)?;
+        assert_eq!(status, process::Status::Delayed);
+        assert!(out.is_empty());
+
+        assert_eq!(
+            client.list_available_blobs()?,
+            vec![BString::from("a"), BString::from("b")]
+        );
+        input.0.lock().unwrap().clear();
+        let status = client.invoke("smudge", "a".into(), &[], false, &mut out)?;
+        assert_eq!(status, process::Status::Success);
+        assert_eq!(out.as_bstr(), "A");
+        assert_eq!(
+            input.0.lock().unwrap().as_bstr(),
+            (text(&["command=smudge", "pathname=a"]) + FLUSH + FLUSH).as_str(),
+            "delayed content is requested without sending content again"
+        );
+
+        assert!(client.list_available_blobs()?.is_empty(), "no more delayed paths");
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_fails_with_unknown_server() {
+        let response = text(&["git-other-server", "version=2"]) + FLUSH;
+        assert!(matches!(
+            client(response),
+            Err(process::handshake::Error::Welcome { actual }) if actual == "git-other-server"
+        ));
+        let response = text(&["git-filter-server", "version=3"]) + FLUSH;
+        assert!(matches!(
+            client(response),
+            Err(process::handshake::Error::UnsupportedVersion)
+        ));
+    }
+}
+
+#[cfg(unix)]
+mod state {
+    use super::*;
+
+    fn driver(clean: Option<&str>, smudge: Option<&str>) -> Driver {
+        Driver {
+            name: "test".into(),
+            clean: clean.map(Into::into),
+            smudge: smudge.map(Into::into),
+            process: None,
+            required: false,
+        }
+    }
+
+    #[test]
+    fn single_invocation_commands() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let mut state = driver::State::new(dir.path());
+        let driver = driver(Some("cat && echo %f"), Some("tr a-z A-Z"));
+        let mut out = Vec::new();
+
+        let outcome = state.apply(&driver, Operation::Smudge, "file".into(), b"hello", &mut out, false)?;
+        assert_eq!(outcome, Outcome::Filtered);
+        assert_eq!(out.as_bstr(), "HELLO");
+
+        let outcome = state.apply(
+            &driver,
+            Operation::Clean,
+            "dir/it's a file".into(),
+            b"content:",
+            &mut out,
+            false,
+        )?;
+        assert_eq!(outcome, Outcome::Filtered);
+        assert_eq!(
+            out.as_bstr(),
+            "content:dir/it's a file\n",
+            "paths are quoted for the shell"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_commands_leave_content_unchanged() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let mut state = driver::State::new(dir.path());
+        let mut out = Vec::new();
+        let outcome = state.apply(
+            &driver(None, None),
+            Operation::Clean,
+            "file".into(),
+            b"hello",
+            &mut out,
+            false,
+        )?;
+        assert_eq!(outcome, Outcome::Unchanged);
+        assert!(out.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn failing_commands_are_errors() -> crate::Result {
+        let dir = tempfile::tempdir()?;
+        let mut state = driver::State::new(dir.path());
+        let err = state
+            .apply(
+                &driver(None, Some("exit 42")),
+                Operation::Smudge,
+                "file".into(),
+                b"hello",
+                &mut Vec::new(),
+                false,
+            )
+            .unwrap_err();
+        assert!(matches!(err, driver::apply::Error::Failed { code: Some(42), .. }));
+        Ok(())
+    }
+}
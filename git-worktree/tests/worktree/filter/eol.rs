@@ -0,0 +1,235 @@
+use bstr::ByteSlice;
+use git_attributes::State;
+use git_worktree::filter::eol::{
+    self, convert_to_git, convert_to_worktree, AttributesDigest, AutoCrlf, Configuration, Mode, Stats,
+};
+
+fn config(auto_crlf: AutoCrlf, eol: Mode) -> Configuration {
+    Configuration { auto_crlf, eol }
+}
+
+fn to_git(
+    src: &str,
+    digest: impl Into<Option<AttributesDigest>>,
+    config: Configuration,
+) -> Result<(Option<String>, eol::convert_to_git::Outcome), eol::convert_to_git::Error> {
+    to_git_with_index(src, digest, None, config, None)
+}
+
+fn to_git_with_index(
+    src: &str,
+    digest: impl Into<Option<AttributesDigest>>,
+    index_object: Option<&str>,
+    config: Configuration,
+    round_trip_check: Option<eol::convert_to_git::RoundTripCheck>,
+) -> Result<(Option<String>, eol::convert_to_git::Outcome), eol::convert_to_git::Error> {
+    let mut buf = Vec::new();
+    let outcome = convert_to_git(
+        src.as_bytes(),
+        digest.into(),
+        &mut buf,
+        |buf| {
+            index_object.map(|obj| {
+                buf.clear();
+                buf.extend_from_slice(obj.as_bytes());
+            })
+        },
+        eol::convert_to_git::Options {
+            config,
+            round_trip_check,
+        },
+    )?;
+    Ok((outcome.converted.then(|| buf.to_str().unwrap().to_owned()), outcome))
+}
+
+fn to_worktree(src: &str, digest: impl Into<Option<AttributesDigest>>, config: Configuration) -> Option<String> {
+    let mut buf = Vec::new();
+    convert_to_worktree(src.as_bytes(), digest.into(), &mut buf, config).then(|| buf.to_str().unwrap().to_owned())
+}
+
+#[test]
+fn stats() {
+    assert_eq!(
+        Stats::from_bytes(b"a\r\nb\nc\rd\0\x7f\t\x1a"),
+        Stats {
+            null: 1,
+            lone_cr: 1,
+            lone_lf: 1,
+            crlf: 1,
+            printable: 5,
+            non_printable: 2,
+        },
+        "a trailing EOF character doesn't count as non-printable"
+    );
+    assert!(!Stats::from_bytes(b"hello\r\nworld\n").is_binary());
+    assert!(Stats::from_bytes(b"hello\rworld").is_binary(), "lone carriage returns");
+    assert!(Stats::from_bytes(b"hello\0world").is_binary(), "null bytes");
+    assert!(
+        Stats::from_bytes(b"\x01\x02").is_binary(),
+        "too many non-printable characters"
+    );
+}
+
+#[test]
+fn digest_from_attributes() {
+    let auto = State::Value("auto".into());
+    let lf = State::Value("lf".into());
+    let crlf = State::Value("crlf".into());
+    for (text, eol, expected) in [
+        (&State::Unspecified, &State::Unspecified, None),
+        (&State::Unset, &crlf, Some(AttributesDigest::Binary)),
+        (&State::Set, &State::Unspecified, Some(AttributesDigest::Text)),
+        (&State::Set, &lf, Some(AttributesDigest::TextInput)),
+        (&State::Unspecified, &crlf, Some(AttributesDigest::TextCrlf)),
+        (&auto, &State::Unspecified, Some(AttributesDigest::TextAuto)),
+        (&auto, &lf, Some(AttributesDigest::TextAutoInput)),
+        (&auto, &crlf, Some(AttributesDigest::TextAutoCrlf)),
+    ] {
+        assert_eq!(AttributesDigest::from_attributes(text, eol), expected);
+    }
+}
+
+mod to_git {
+    use super::*;
+
+    #[test]
+    fn nothing_happens_without_autocrlf_or_attributes() -> crate::Result {
+        let (out, _) = to_git("a\r\nb\r\n", None, config(AutoCrlf::Disabled, Mode::CrLf))?;
+        assert_eq!(out, None);
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_is_normalized_with_autocrlf_unless_binary() -> crate::Result {
+        for auto_crlf in [AutoCrlf::Enabled, AutoCrlf::Input] {
+            let config = config(auto_crlf, Mode::Lf);
+            assert_eq!(to_git("a\r\nb\n", None, config)?.0.as_deref(), Some("a\nb\n"));
+            assert_eq!(to_git("a\nb\n", None, config)?.0, None, "nothing to convert");
+            assert_eq!(to_git("a\r\nb\0", None, config)?.0, None, "binary files are left alone");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_in_index_prevents_normalization_in_auto_mode_only() -> crate::Result {
+        let config = config(AutoCrlf::Enabled, Mode::Lf);
+        let (out, _) = to_git_with_index("a\r\n", None, Some("a\r\nb\r\n"), config, None)?;
+        assert_eq!(out, None);
+        let (out, _) = to_git_with_index("a\r\n", None, Some("a\nb\n"), config, None)?;
+        assert_eq!(out.as_deref(), Some("a\n"));
+        let (out, _) = to_git_with_index("a\r\n", AttributesDigest::Text, Some("a\r\nb\r\n"), config, None)?;
+        assert_eq!(out.as_deref(), Some("a\n"), "the text attribute forces normalization");
+        Ok(())
+    }
+
+    #[test]
+    fn binary_attribute_prevents_conversion() -> crate::Result {
+        let (out, _) = to_git("a\r\n", AttributesDigest::Binary, config(AutoCrlf::Enabled, Mode::CrLf))?;
+        assert_eq!(out, None);
+        Ok(())
+    }
+
+    #[test]
+    fn text_attribute_forces_conversion_even_of_binary_looking_content() -> crate::Result {
+        let (out, _) = to_git("a\r\n\0", AttributesDigest::Text, Default::default())?;
+        assert_eq!(out.as_deref(), Some("a\n\0"));
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip_check() -> crate::Result {
+        use eol::convert_to_git::{LossyConversion, RoundTripCheck};
+        let input = config(AutoCrlf::Input, Mode::Lf);
+        let (out, outcome) = to_git_with_index("a\r\n", None, None, input, Some(RoundTripCheck::Warn))?;
+        assert_eq!(out.as_deref(), Some("a\n"), "warnings don't prevent the conversion");
+        assert_eq!(outcome.lossy_conversion, Some(LossyConversion::CrlfToLf));
+
+        let err = to_git_with_index("a\r\n", None, None, input, Some(RoundTripCheck::Fail)).unwrap_err();
+        assert!(matches!(
+            err,
+            eol::convert_to_git::Error::LossyConversion(LossyConversion::CrlfToLf)
+        ));
+
+        let enabled = config(AutoCrlf::Enabled, Mode::Lf);
+        let (_, outcome) = to_git_with_index("a\r\nb\r\n", None, None, enabled, Some(RoundTripCheck::Warn))?;
+        assert_eq!(
+            outcome.lossy_conversion, None,
+            "after normalization, all line endings become CRLF again on checkout"
+        );
+        let (_, outcome) = to_git_with_index("a\nb\r\n", None, None, enabled, Some(RoundTripCheck::Warn))?;
+        assert_eq!(
+            outcome.lossy_conversion,
+            Some(LossyConversion::LfToCrlf),
+            "mixed line endings all become CRLF on checkout"
+        );
+
+        let (out, outcome) = to_git_with_index(
+            "a\nb\n",
+            AttributesDigest::TextCrlf,
+            None,
+            Default::default(),
+            Some(RoundTripCheck::Warn),
+        )?;
+        assert_eq!(out, None);
+        assert_eq!(outcome.lossy_conversion, Some(LossyConversion::LfToCrlf));
+        Ok(())
+    }
+}
+
+mod to_worktree {
+    use super::*;
+
+    #[test]
+    fn lf_becomes_crlf_with_autocrlf_enabled() {
+        let config = config(AutoCrlf::Enabled, Mode::Lf);
+        assert_eq!(to_worktree("a\nb\n", None, config).as_deref(), Some("a\r\nb\r\n"));
+        assert_eq!(
+            to_worktree("a\r\nb\n", None, config),
+            None,
+            "existing CRLF are left alone"
+        );
+        assert_eq!(to_worktree("a\nb\0", None, config), None, "binary files are left alone");
+    }
+
+    #[test]
+    fn nothing_happens_with_autocrlf_input_or_disabled() {
+        for auto_crlf in [AutoCrlf::Input, AutoCrlf::Disabled] {
+            assert_eq!(to_worktree("a\nb\n", None, config(auto_crlf, Mode::CrLf)), None);
+        }
+    }
+
+    #[test]
+    fn core_eol_applies_to_text_files_only() {
+        let config = config(AutoCrlf::Disabled, Mode::CrLf);
+        assert_eq!(
+            to_worktree("a\nb\r\n", AttributesDigest::Text, config).as_deref(),
+            Some("a\r\nb\r\n")
+        );
+        assert_eq!(
+            to_worktree("a\nb\r\n", AttributesDigest::TextAuto, config),
+            None,
+            "automatic mode leaves files with CRLF alone"
+        );
+        assert_eq!(
+            to_worktree("a\n", AttributesDigest::TextInput, config),
+            None,
+            "eol=lf takes precedence"
+        );
+    }
+
+    #[test]
+    fn eol_attribute_takes_precedence_over_autocrlf() {
+        assert_eq!(
+            to_worktree("a\n", AttributesDigest::TextCrlf, config(AutoCrlf::Input, Mode::Lf)).as_deref(),
+            Some("a\r\n")
+        );
+        assert_eq!(
+            to_worktree(
+                "a\n",
+                AttributesDigest::TextAutoInput,
+                config(AutoCrlf::Enabled, Mode::CrLf)
+            ),
+            None
+        );
+    }
+}
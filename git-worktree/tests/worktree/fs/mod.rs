@@ -17,3 +17,19 @@ fn from_probing_cwd() {
         entries
     );
 }
+
+#[test]
+fn from_probing_a_directory_without_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let ctx = git_worktree::fs::Capabilities::probe(dir.path());
+    assert_eq!(
+        ctx.ignore_case,
+        cfg!(any(windows, target_os = "macos")),
+        "typical file systems are assumed here"
+    );
+    assert_eq!(
+        std::fs::read_dir(dir.path()).unwrap().count(),
+        0,
+        "there should be no left-over files after probing"
+    );
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use git_index::entry::Flags;
+use git_worktree::fsmonitor::Monitor;
+
+#[test]
+fn monitors_are_created_from_configuration() {
+    let git_dir = Path::new("repo/.git");
+    for disabled in ["", "false", "No", "off", "0"] {
+        assert_eq!(Monitor::from_config_value(disabled.into(), git_dir), None);
+    }
+    for enabled in ["true", "YES", "on", "1"] {
+        assert_eq!(
+            Monitor::from_config_value(enabled.into(), git_dir),
+            Some(Monitor::Daemon {
+                socket: git_dir.join("fsmonitor--daemon.ipc")
+            })
+        );
+    }
+    assert_eq!(
+        Monitor::from_config_value(".git/hooks/query-watchman".into(), git_dir),
+        Some(Monitor::Hook(".git/hooks/query-watchman".into()))
+    );
+}
+
+fn valid_paths(index: &git_index::State) -> Vec<String> {
+    index
+        .entries()
+        .iter()
+        .filter(|e| e.flags.contains(Flags::FSMONITOR_VALID))
+        .map(|e| e.path(index).to_string())
+        .collect()
+}
+
+#[cfg(unix)]
+mod hook {
+    use git_index::extension::fs_monitor::Token;
+    use git_worktree::fsmonitor::Monitor;
+
+    use super::valid_paths;
+    use crate::fixture_path;
+
+    #[test]
+    fn entries_reported_as_changed_are_not_valid() -> crate::Result {
+        let worktree = fixture_path("make_mixed_without_submodules");
+        let mut index = git_index::File::at(worktree.join(".git/index"), Default::default())?;
+        let tmp = tempfile::tempdir()?;
+        let hook = tmp.path().join("hook");
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\ntest \"$1\" = 2 || exit 1\nprintf 'next-token\\0empty\\0dir/sub-dir/\\0'\n",
+        )?;
+        let monitor = Monitor::Hook(format!("sh {}", hook.display()).into());
+
+        monitor.apply_to_index(&worktree, &mut index)?;
+        assert!(
+            valid_paths(&index).is_empty(),
+            "without previous token, everything is considered changed"
+        );
+        for entry in index.entries_mut() {
+            entry.flags.insert(super::Flags::FSMONITOR_VALID);
+        }
+
+        monitor.apply_to_index(&worktree, &mut index)?;
+        assert_eq!(valid_paths(&index), ["dir/content", "executable"]);
+        assert_eq!(
+            index.fs_monitor().map(|m| m.token.clone()),
+            Some(Token::V2 {
+                token: "next-token".into()
+            })
+        );
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+mod daemon {
+    use std::{
+        io::{Read, Write},
+        os::unix::net::UnixListener,
+    };
+
+    use git_index::extension::fs_monitor::{Changed, Token};
+    use git_worktree::fsmonitor::Monitor;
+
+    /// Answer a single request with `response` and return the request.
+    fn serve_once(listener: UnixListener, response: &'static [u8]) -> std::thread::JoinHandle<Vec<u8>> {
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1];
+            while !request.ends_with(b"0000") {
+                stream.read_exact(&mut buf).unwrap();
+                request.push(buf[0]);
+            }
+            for chunk in response.chunks(5) {
+                write!(stream, "{:04x}", chunk.len() + 4).unwrap();
+                stream.write_all(chunk).unwrap();
+            }
+            stream.write_all(b"0000").unwrap();
+            request
+        })
+    }
+
+    #[test]
+    fn changed_paths_are_received_in_packet_lines() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let socket = tmp.path().join("fsmonitor--daemon.ipc");
+        let server = serve_once(UnixListener::bind(&socket)?, b"builtin:1:2\0a\0dir/b\0");
+
+        let outcome = Monitor::Daemon { socket }.query(
+            tmp.path(),
+            &Token::V2 {
+                token: "builtin:1:1".into(),
+            },
+        )?;
+        assert_eq!(server.join().unwrap(), b"000fbuiltin:1:10000");
+        assert_eq!(
+            outcome.token,
+            Token::V2 {
+                token: "builtin:1:2".into()
+            }
+        );
+        assert_eq!(outcome.changed, Changed::Paths(vec!["a".into(), "dir/b".into()]));
+        Ok(())
+    }
+
+    #[test]
+    fn trivial_responses_indicate_that_everything_changed() -> crate::Result {
+        let tmp = tempfile::tempdir()?;
+        let socket = tmp.path().join("fsmonitor--daemon.ipc");
+        let server = serve_once(UnixListener::bind(&socket)?, b"builtin:2:0\0/\0");
+
+        let outcome = Monitor::Daemon { socket }.query(tmp.path(), &Token::V1 { nanos_since_1970: 0 })?;
+        assert_eq!(server.join().unwrap(), b"0000", "there is no token to send");
+        assert_eq!(outcome.changed, Changed::All);
+        Ok(())
+    }
+}
@@ -101,7 +101,7 @@ mod cache {
 use std::os::unix::prelude::MetadataExt;
 use std::{
     fs,
-    io::{ErrorKind, ErrorKind::AlreadyExists},
+    io::ErrorKind::AlreadyExists,
     path::{Path, PathBuf},
     sync::atomic::{AtomicBool, AtomicUsize, Ordering},
 };
@@ -109,7 +109,11 @@ use std::{
 use git_features::progress;
 use git_object::bstr::ByteSlice;
 use git_odb::FindExt;
-use git_worktree::{fs::Capabilities, index, index::checkout::Collision};
+use git_worktree::{
+    fs::Capabilities,
+    index,
+    index::checkout::{Collision, NameCollision, NameCollisionKind},
+};
 use tempfile::TempDir;
 
 use crate::fixture_path;
@@ -270,6 +274,28 @@ fn allow_or_disallow_symlinks() -> crate::Result {
     Ok(())
 }
 
+#[test]
+fn line_endings_of_text_files_are_converted_if_configured() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.eol.auto_crlf = git_worktree::filter::eol::AutoCrlf::Enabled;
+    let (_source_tree, destination, _index, outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_line_endings")?;
+
+    assert_eq!(std::fs::read(destination.path().join("lf"))?, b"a\r\nb\r\n");
+    assert_eq!(
+        std::fs::read(destination.path().join("crlf"))?,
+        b"a\r\nb\r\n",
+        "files with CRLF in the object database are left alone"
+    );
+    assert_eq!(
+        std::fs::read(destination.path().join("binary"))?,
+        b"a\0\nb\n",
+        "binary files are never converted"
+    );
+    assert_eq!(outcome.bytes_written, 6 + 6 + 6);
+    assert!(outcome.collisions.is_empty());
+    Ok(())
+}
+
 #[test]
 fn keep_going_collects_results() {
     let mut opts = opts_from_probe();
@@ -341,6 +367,7 @@ fn no_case_related_collisions_on_case_sensitive_filesystem() {
         checkout_index_in_tmp_dir(opts, "make_ignorecase_collisions").unwrap();
 
     assert!(outcome.collisions.is_empty());
+    assert!(outcome.name_collisions.is_empty());
     let num_files = assert_equality(&source_tree, &destination, opts.fs.symlink).unwrap();
     assert_eq!(num_files, index.entries().len(), "it checks out all files");
 }
@@ -363,62 +390,136 @@ fn collisions_are_detected_on_a_case_insensitive_filesystem() {
     );
 
     let dest_files = dir_structure(&destination);
-    if multi_threaded() {
-        assert_eq!(
-            dest_files.len(),
-            5,
-            "can only assert on number as it's racily creating files so unclear which one clashes"
-        );
-    } else {
-        assert_eq!(
-            stripped_prefix(&destination, &dest_files),
-            paths(["D/B", "D/C", "FILE_X", "X", "link-to-X"]),
-            "we checkout files in order and generally handle collision detection differently, hence the difference"
-        );
-    }
+    assert_eq!(
+        stripped_prefix(&destination, &dest_files),
+        paths(["D/B", "D/C", "FILE_X", "X", "link-to-X"]),
+        "we checkout files in order and generally handle collision detection differently, hence the difference"
+    );
 
-    let error_kind = ErrorKind::AlreadyExists;
-    #[cfg(windows)]
-    let error_kind_dir = ErrorKind::PermissionDenied;
-    #[cfg(not(windows))]
-    let error_kind_dir = error_kind;
+    assert!(
+        outcome.collisions.is_empty(),
+        "colliding paths are detected before they are written"
+    );
+    assert_eq!(
+        outcome.name_collisions,
+        vec![
+            name_collision("FILE_x", "FILE_X", NameCollisionKind::Case),
+            name_collision("d", "D", NameCollisionKind::Case),
+            name_collision("file_X", "FILE_X", NameCollisionKind::Case),
+            name_collision("file_x", "FILE_X", NameCollisionKind::Case),
+            name_collision("x", "X", NameCollisionKind::Case),
+        ],
+        "these files couldn't be checked out"
+    );
+}
 
-    if multi_threaded() {
-        assert_eq!(
-            outcome.collisions.len(),
-            5,
-            "can only assert on number as it's racily creating files so unclear which one clashes"
-        );
-    } else {
-        assert_eq!(
-            outcome.collisions,
-            vec![
-                Collision {
-                    path: "FILE_x".into(),
-                    error_kind,
-                },
-                Collision {
-                    path: "d".into(),
-                    error_kind: error_kind_dir,
-                },
-                Collision {
-                    path: "file_X".into(),
-                    error_kind,
-                },
-                Collision {
-                    path: "file_x".into(),
-                    error_kind,
-                },
-                Collision {
-                    path: "x".into(),
-                    error_kind,
-                },
-            ],
-            "these files couldn't be checked out"
-        );
+#[test]
+fn case_collisions_are_detected_up_front_even_if_overwriting_is_allowed() {
+    let mut opts = opts_from_probe();
+    opts.fs.ignore_case = true;
+    opts.overwrite_existing = true;
+    let (_source_tree, destination, _index, outcome) =
+        checkout_index_in_tmp_dir(opts, "make_ignorecase_collisions").unwrap();
+
+    assert_eq!(
+        stripped_prefix(&destination, &dir_structure(&destination)),
+        paths(["D/B", "D/C", "FILE_X", "X", "link-to-X"]),
+        "the first of all colliding entries is checked out"
+    );
+    assert!(outcome.collisions.is_empty());
+    assert_eq!(
+        outcome
+            .name_collisions
+            .iter()
+            .map(|c| c.path.to_str_lossy().into_owned())
+            .collect::<Vec<_>>(),
+        ["FILE_x", "d", "file_X", "file_x", "x"]
+    );
+}
+
+#[test]
+fn ntfs_collisions_are_detected_if_ntfs_is_protected() {
+    let mut opts = opts_from_probe();
+    opts.protect_ntfs = true;
+    let (_source_tree, destination, _index, outcome) = checkout_index_in_tmp_dir(opts, "make_ntfs_collisions").unwrap();
+
+    assert_eq!(
+        outcome.name_collisions,
+        vec![
+            name_collision("a.", "a", NameCollisionKind::TrailingDotOrSpace),
+            name_collision("b/c", "b /c", NameCollisionKind::TrailingDotOrSpace),
+            name_collision("long-n~1.txt", "long-name.txt", NameCollisionKind::ShortName),
+            name_collision("progra~1/other", "Program Files", NameCollisionKind::ShortName),
+        ]
+    );
+    assert_eq!(
+        dir_structure(&destination).len(),
+        4,
+        "only the first of each colliding entries was checked out"
+    );
+}
+
+#[test]
+fn ntfs_names_are_not_special_by_default_on_unix() {
+    if cfg!(windows) {
+        return;
+    }
+    let (source_tree, destination, index, outcome) =
+        checkout_index_in_tmp_dir(opts_from_probe(), "make_ntfs_collisions").unwrap();
+    assert!(outcome.name_collisions.is_empty());
+    let num_files = assert_equality(&source_tree, &destination, true).unwrap();
+    assert_eq!(num_files, index.entries().len(), "it checks out all files");
+}
+
+fn name_collision(path: &str, colliding_with: &str, kind: NameCollisionKind) -> NameCollision {
+    NameCollision {
+        path: path.into(),
+        colliding_with: colliding_with.into(),
+        kind,
     }
 }
 
+#[test]
+fn interruption_prevents_entries_from_being_checked_out() -> crate::Result {
+    let source_tree = fixture_path("make_mixed_without_submodules");
+    let git_dir = source_tree.join(".git");
+    let mut index = git_index::File::at(git_dir.join("index"), Default::default())?;
+    let odb = git_odb::at(git_dir.join("objects"))?.into_inner().into_arc()?;
+    let destination = tempfile::tempdir_in(std::env::current_dir()?)?;
+
+    let outcome = index::checkout(
+        &mut index,
+        destination.path(),
+        move |oid, buf| odb.find_blob(oid, buf),
+        &mut progress::Discard,
+        &mut progress::Discard,
+        &AtomicBool::new(true),
+        opts_from_probe(),
+    )?;
+    assert_eq!(outcome.files_updated, 0);
+    assert!(outcome.collisions.is_empty());
+    assert!(dir_structure(&destination).is_empty());
+    Ok(())
+}
+
+#[test]
+fn probed_capabilities_are_used_and_returned() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.fs = Capabilities {
+        precompose_unicode: !opts.fs.precompose_unicode,
+        ignore_case: !opts.fs.ignore_case,
+        executable_bit: !opts.fs.executable_bit,
+        symlink: !opts.fs.symlink,
+    };
+    opts.probe_capabilities = true;
+    let (source_tree, destination, _index, outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+
+    assert_eq!(outcome.fs, git_worktree::fs::Capabilities::probe(destination.path()));
+    assert_equality(&source_tree, &destination, outcome.fs.symlink)?;
+    assert!(outcome.collisions.is_empty());
+    Ok(())
+}
+
 fn multi_threaded() -> bool {
     git_features::parallel::num_threads(None) > 1
 }
@@ -464,7 +565,7 @@ pub fn dir_structure<P: AsRef<std::path::Path>>(path: P) -> Vec<std::path::PathB
     files
 }
 
-fn checkout_index_in_tmp_dir(
+pub fn checkout_index_in_tmp_dir(
     opts: index::checkout::Options,
     name: &str,
 ) -> crate::Result<(
@@ -522,7 +623,7 @@ fn probe_gitoxide_dir() -> crate::Result<Capabilities> {
     ))
 }
 
-fn opts_from_probe() -> index::checkout::Options {
+pub fn opts_from_probe() -> index::checkout::Options {
     index::checkout::Options {
         fs: probe_gitoxide_dir().unwrap(),
         destination_is_initially_empty: true,
@@ -0,0 +1,188 @@
+use std::path::Path;
+
+use git_odb::FindExt;
+use git_worktree::index::status::{self, compare_to_worktree, Change, Outcome};
+
+use crate::index::checkout::{checkout_index_in_tmp_dir, opts_from_probe};
+
+fn status_options(checkout: git_worktree::index::checkout::Options) -> status::Options {
+    status::Options {
+        fs: checkout.fs,
+        eol: checkout.eol,
+        ..Default::default()
+    }
+}
+
+fn index_to_worktree(
+    source_tree: &Path,
+    index: &git_index::State,
+    worktree: &Path,
+    options: status::Options,
+) -> crate::Result<Vec<(String, Outcome)>> {
+    let odb = git_odb::at(source_tree.join(".git").join("objects"))?
+        .into_inner()
+        .into_arc()?;
+    Ok(
+        status::index_to_worktree(index, worktree, |oid, buf| odb.find_blob(oid, buf), options)?
+            .into_iter()
+            .map(|r| (r.path.to_string(), r.outcome))
+            .collect(),
+    )
+}
+
+/// Replace the file at `path` with a new one of the same content, which changes all of its stat information.
+fn rewrite(path: &Path) -> crate::Result {
+    let content = std::fs::read(path)?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)?;
+    std::fs::set_permissions(&tmp, std::fs::symlink_metadata(path)?.permissions())?;
+    std::fs::rename(tmp, path)?;
+    Ok(())
+}
+
+#[test]
+fn freshly_checked_out_files_are_unchanged() -> crate::Result {
+    let opts = opts_from_probe();
+    let (source_tree, destination, index, _outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+
+    assert_eq!(
+        index_to_worktree(&source_tree, &index, destination.path(), status_options(opts))?,
+        vec![]
+    );
+    Ok(())
+}
+
+#[test]
+fn removed_modified_and_type_changes_are_detected() -> crate::Result {
+    let opts = opts_from_probe();
+    let (source_tree, destination, index, _outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+    let dir = destination.path();
+
+    std::fs::remove_file(dir.join("empty"))?;
+    std::fs::write(dir.join("dir/content"), "changed")?;
+    std::fs::remove_file(dir.join("dir/sub-dir/symlink"))?;
+    std::fs::write(dir.join("dir/sub-dir/symlink"), "../content")?;
+    rewrite(&dir.join("executable"))?;
+
+    let records = index_to_worktree(&source_tree, &index, dir, status_options(opts))?;
+    let changes: Vec<_> = records
+        .iter()
+        .map(|(path, outcome)| (path.as_str(), outcome.change()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("dir/content", Some(Change::Modified)),
+            ("dir/sub-dir/symlink", Some(Change::Type)),
+            ("empty", Some(Change::Removed)),
+            ("executable", None),
+        ]
+    );
+    assert!(
+        matches!(records[3].1, Outcome::UnchangedWithNewStat(_)),
+        "files whose content is the same are unchanged, but their stat information is returned for updating the index"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn executable_bit_changes_are_only_detected_if_the_file_system_retains_it() -> crate::Result {
+    use std::os::unix::fs::PermissionsExt;
+
+    let opts = opts_from_probe();
+    let (source_tree, destination, index, _outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+    let executable = destination.path().join("executable");
+    std::fs::set_permissions(&executable, std::fs::Permissions::from_mode(0o644))?;
+
+    let mut options = status_options(opts);
+    options.fs.executable_bit = true;
+    assert_eq!(
+        index_to_worktree(&source_tree, &index, destination.path(), options)?,
+        vec![("executable".into(), Outcome::Changed(Change::Mode))]
+    );
+
+    options.fs.executable_bit = false;
+    assert!(index_to_worktree(&source_tree, &index, destination.path(), options)?
+        .iter()
+        .all(|(_, outcome)| outcome.change().is_none()));
+    Ok(())
+}
+
+#[test]
+fn symlinks_checked_out_as_files_are_compared_by_content() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.fs.symlink = false;
+    let (source_tree, destination, index, _outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+    let symlink = destination.path().join("dir/sub-dir/symlink");
+    rewrite(&symlink)?;
+
+    let records = index_to_worktree(&source_tree, &index, destination.path(), status_options(opts))?;
+    assert_eq!(records.len(), 1);
+    assert!(matches!(records[0].1, Outcome::UnchangedWithNewStat(_)));
+
+    std::fs::write(&symlink, "../CONTENT")?;
+    assert_eq!(
+        index_to_worktree(&source_tree, &index, destination.path(), status_options(opts))?,
+        vec![("dir/sub-dir/symlink".into(), Outcome::Changed(Change::Modified))]
+    );
+    Ok(())
+}
+
+#[test]
+fn line_endings_are_converted_before_comparing_content() -> crate::Result {
+    let mut opts = opts_from_probe();
+    opts.eol.auto_crlf = git_worktree::filter::eol::AutoCrlf::Enabled;
+    let (source_tree, destination, index, _outcome) = checkout_index_in_tmp_dir(opts, "make_mixed_line_endings")?;
+    for name in &["lf", "crlf", "binary"] {
+        rewrite(&destination.path().join(name))?;
+    }
+
+    let records = index_to_worktree(&source_tree, &index, destination.path(), status_options(opts))?;
+    assert_eq!(records.len(), 3);
+    assert!(
+        records.iter().all(|(_, outcome)| outcome.change().is_none()),
+        "files with CRLF in the object database are not normalized, neither are binary files"
+    );
+    Ok(())
+}
+
+#[test]
+fn racily_clean_entries_have_their_content_compared() -> crate::Result {
+    let opts = opts_from_probe();
+    let (_source_tree, destination, mut index, _outcome) =
+        checkout_index_in_tmp_dir(opts, "make_mixed_without_submodules")?;
+    let idx = index
+        .entry_index_by_path_and_stage("dir/content".into(), 0)
+        .expect("present");
+    let entry = &mut index.entries_mut()[idx];
+    entry.id = entry.id.kind().null();
+    let path = destination.path().join("dir/content");
+    let compare = |timestamp| {
+        compare_to_worktree(
+            &index.entries()[idx],
+            &path,
+            timestamp,
+            no_object,
+            status_options(opts),
+            &mut Vec::new(),
+            &mut Vec::new(),
+        )
+    };
+
+    assert_eq!(
+        compare(git_index::entry::Time::default())?,
+        Outcome::Unchanged,
+        "without a timestamp the stat information is trusted, even though the id doesn't match anymore"
+    );
+    assert_eq!(
+        compare(index.entries()[idx].stat.mtime)?,
+        Outcome::Changed(Change::Modified),
+        "entries modified at the time the index was written are racy and need their content to be checked"
+    );
+    Ok(())
+}
+
+fn no_object<'a>(_oid: &git_hash::oid, _buf: &'a mut Vec<u8>) -> Result<git_object::BlobRef<'a>, std::io::Error> {
+    Err(std::io::ErrorKind::NotFound.into())
+}
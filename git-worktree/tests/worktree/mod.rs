@@ -1,4 +1,6 @@
+mod filter;
 mod fs;
+mod fsmonitor;
 mod index;
 
 use std::path::{Path, PathBuf};
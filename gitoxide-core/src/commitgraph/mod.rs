@@ -1 +1,2 @@
 pub mod verify;
+pub mod write;
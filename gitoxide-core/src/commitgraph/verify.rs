@@ -48,7 +48,7 @@ where
 
     #[cfg_attr(not(feature = "serde1"), allow(clippy::single_match))]
     match output_statistics {
-        Some(OutputFormat::Human) => drop(print_human_output(&mut out, &stats)),
+        Some(OutputFormat::Human) => drop(print_human_output(&mut out, &g, &stats)),
         #[cfg(feature = "serde1")]
         Some(OutputFormat::Json) => serde_json::to_writer_pretty(out, &stats)?,
         _ => {}
@@ -57,7 +57,7 @@ where
     Ok(stats)
 }
 
-fn print_human_output(out: &mut impl io::Write, stats: &Outcome) -> io::Result<()> {
+fn print_human_output(out: &mut impl io::Write, graph: &Graph, stats: &Outcome) -> io::Result<()> {
     writeln!(out, "number of commits with the given number of parents")?;
     let mut parent_counts: Vec<_> = stats.parent_counts.iter().map(|(a, b)| (*a, *b)).collect();
     parent_counts.sort_by_key(|e| e.0);
@@ -73,5 +73,16 @@ fn print_human_output(out: &mut impl io::Write, stats: &Outcome) -> io::Result<(
         writeln!(out, "unknown")?;
     }
 
+    write!(out, "changed-path Bloom filters: ")?;
+    if let Some(settings) = graph.bloom_filter_settings() {
+        writeln!(
+            out,
+            "{} hashes, {} bits per entry",
+            settings.num_hashes, settings.bits_per_entry
+        )?;
+    } else {
+        writeln!(out, "none")?;
+    }
+
     Ok(())
 }
@@ -0,0 +1,154 @@
+use std::{io, path::PathBuf, time::Instant};
+
+use anyhow::{anyhow, Context as AnyhowContext, Result};
+use git_commitgraph::{bloom, file, graph, Graph};
+use git_repository as git;
+use git_repository::{
+    bstr::BString, diff::tree::recorder::Change, hash::ObjectId, interrupt, objs::tree::EntryMode, odb::FindExt,
+    progress, Progress,
+};
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+pub struct Options {
+    /// Compute and write changed-path Bloom filters, which speed up obtaining the history of paths.
+    pub changed_paths: bool,
+    /// Add a new layer to the commit-graph chain instead of replacing the `commit-graph` file.
+    pub split: bool,
+}
+
+/// Write all commits reachable from `HEAD` and all references of the repository at `repository` into its commit-graph,
+/// similar to `git commit-graph write --reachable`.
+pub fn write(
+    repository: PathBuf,
+    mut progress: impl Progress,
+    mut out: impl io::Write,
+    Options { changed_paths, split }: Options,
+) -> Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    let tips: Vec<ObjectId> = repo
+        .references()?
+        .all()?
+        .peeled()
+        .filter_map(Result::ok)
+        .filter_map(|r| r.target().as_id().map(ToOwned::to_owned))
+        .chain(repo.head_id().ok().map(|id| id.detach()))
+        .filter_map(|id| {
+            repo.find_object(id)
+                .ok()?
+                .peel_to_kind(git::object::Kind::Commit)
+                .ok()
+                .map(|commit| commit.id)
+        })
+        .collect();
+
+    let start = Instant::now();
+    let bloom_settings = changed_paths.then(bloom::Settings::default);
+    let mut commits = Vec::new();
+    {
+        let mut progress = progress.add_child("Traverse commit graph");
+        progress.init(None, progress::count("commits"));
+        let mut diff_state = git::diff::tree::State::default();
+        for info in interrupt::Iter::new(repo.rev_walk(tips).all()?, || anyhow!("Cancelled by user")) {
+            let commit = info??.object()?;
+            let tree_id = commit.tree_id()?;
+            let parents: Vec<ObjectId> = commit.parent_ids().map(|id| id.detach()).collect();
+            let changed_paths = match bloom_settings {
+                Some(_) => {
+                    let parent_tree_id = parents
+                        .first()
+                        .map(|id| repo.find_object(*id).map(|parent| parent.into_commit().tree_id()))
+                        .transpose()?
+                        .transpose()?;
+                    Some(changed_paths_between(&repo, parent_tree_id, tree_id, &mut diff_state)?)
+                }
+                None => None,
+            };
+            commits.push(file::write::Commit {
+                id: commit.id,
+                root_tree_id: tree_id,
+                parents,
+                committer_timestamp: commit.committer()?.time.seconds_since_unix_epoch as u64,
+                changed_paths,
+            });
+            progress.inc();
+        }
+        progress.show_throughput(start);
+    }
+
+    let num_commits = commits.len();
+    let info_dir = repo.objects.store_ref().path().join("info");
+    let outcome = Graph::write_to_info_dir(
+        &info_dir,
+        commits,
+        graph::write::Options {
+            file: file::write::Options {
+                object_hash: repo.object_hash(),
+                changed_paths: bloom_settings,
+                ..Default::default()
+            },
+            split: split.then(graph::write::Split::default),
+        },
+    )
+    .with_context(|| format!("Could not write commit-graph into '{}'", info_dir.display()))?;
+    progress.done(format!(
+        "Wrote commit-graph with {} reachable commits in {:?}",
+        num_commits,
+        start.elapsed()
+    ));
+
+    match (outcome.path, outcome.written) {
+        (Some(path), Some(written)) => writeln!(
+            out,
+            "{} commits written to {} with checksum {}, the commit-graph consists of {} file(s)",
+            written.num_commits,
+            path.display(),
+            written.checksum,
+            outcome.num_files
+        )?,
+        _ => writeln!(
+            out,
+            "All commits are already in the commit-graph, which consists of {} file(s)",
+            outcome.num_files
+        )?,
+    }
+    Ok(())
+}
+
+/// Return all paths of files changed between the trees with `previous` and `current` id, or all paths in `current`
+/// if there is no `previous` tree.
+fn changed_paths_between(
+    repo: &git::Repository,
+    previous: Option<ObjectId>,
+    current: ObjectId,
+    state: &mut git::diff::tree::State,
+) -> Result<Vec<BString>> {
+    let mut previous_buf = Vec::new();
+    let mut current_buf = Vec::new();
+    let previous = previous
+        .map(|id| repo.objects.find_tree_iter(id, &mut previous_buf))
+        .transpose()?;
+    let current = repo.objects.find_tree_iter(current, &mut current_buf)?;
+    let mut recorder = git::diff::tree::Recorder::default();
+    git::diff::tree::Changes::from(previous).needed_to_obtain(
+        current,
+        state,
+        |id, buf| repo.objects.find_tree_iter(id, buf).ok(),
+        &mut recorder,
+    )?;
+    Ok(recorder
+        .records
+        .into_iter()
+        .filter_map(|change| match change {
+            Change::Addition { entry_mode, path, .. } | Change::Deletion { entry_mode, path, .. } => {
+                (entry_mode != EntryMode::Tree).then(|| path)
+            }
+            Change::Modification {
+                previous_entry_mode,
+                entry_mode,
+                path,
+                ..
+            } => (previous_entry_mode != EntryMode::Tree || entry_mode != EntryMode::Tree).then(|| path),
+        })
+        .collect())
+}
@@ -92,30 +92,10 @@ where
                 .ok()
         })
         .collect::<Vec<_>>();
-    all_commits.sort_by(|a, b| {
-        a.email.cmp(&b.email).then(
-            a.time
-                .seconds_since_unix_epoch
-                .cmp(&b.time.seconds_since_unix_epoch)
-                .reverse(),
-        )
-    });
     if all_commits.is_empty() {
         bail!("No commits to process");
     }
-    let mut current_email = &all_commits[0].email;
-    let mut slice_start = 0;
-    let mut results_by_hours = Vec::new();
-    for (idx, elm) in all_commits.iter().enumerate() {
-        if elm.email != *current_email {
-            results_by_hours.push(estimate_hours(&all_commits[slice_start..idx]));
-            slice_start = idx;
-            current_email = &elm.email;
-        }
-    }
-    if let Some(commits) = all_commits.get(slice_start..) {
-        results_by_hours.push(estimate_hours(commits));
-    }
+    let results_by_hours = estimate_hours_by_email(&mut all_commits);
 
     let num_authors = results_by_hours.len();
     let mut results_by_hours = if !omit_unify_identities {
@@ -169,7 +149,31 @@ where
 }
 
 const MINUTES_PER_HOUR: f32 = 60.0;
-const HOURS_PER_WORKDAY: f32 = 8.0;
+pub(crate) const HOURS_PER_WORKDAY: f32 = 8.0;
+
+/// Sort the author signatures of all `commits` by email and estimate the work of each author.
+pub(crate) fn estimate_hours_by_email(commits: &mut [actor::Signature]) -> Vec<WorkByEmail> {
+    commits.sort_by(|a, b| {
+        a.email.cmp(&b.email).then(
+            a.time
+                .seconds_since_unix_epoch
+                .cmp(&b.time.seconds_since_unix_epoch)
+                .reverse(),
+        )
+    });
+    let mut results_by_hours = Vec::new();
+    let mut slice_start = 0;
+    for idx in 1..commits.len() {
+        if commits[idx].email != commits[slice_start].email {
+            results_by_hours.push(estimate_hours(&commits[slice_start..idx]));
+            slice_start = idx;
+        }
+    }
+    if slice_start < commits.len() {
+        results_by_hours.push(estimate_hours(&commits[slice_start..]));
+    }
+    results_by_hours
+}
 
 fn estimate_hours(commits: &[actor::Signature]) -> WorkByEmail {
     assert!(!commits.is_empty());
@@ -271,9 +275,9 @@ impl<'a> Display for WorkByPerson<'a> {
 }
 
 #[derive(Debug)]
-struct WorkByEmail {
-    name: BString,
-    email: BString,
-    hours: f32,
-    num_commits: u32,
+pub(crate) struct WorkByEmail {
+    pub name: BString,
+    pub email: BString,
+    pub hours: f32,
+    pub num_commits: u32,
 }
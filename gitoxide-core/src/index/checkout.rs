@@ -59,7 +59,7 @@ pub fn checkout_exclusive(
     }
 
     let opts = git::worktree::index::checkout::Options {
-        fs: git::worktree::fs::Capabilities::probe(dest_directory),
+        probe_capabilities: true,
 
         destination_is_initially_empty: true,
         overwrite_existing: false,
@@ -80,8 +80,10 @@ pub fn checkout_exclusive(
     let checkout::Outcome {
         errors,
         collisions,
+        name_collisions,
         files_updated,
         bytes_written,
+        fs: _,
     } = match repo {
         Some(repo) => git::worktree::index::checkout(
             &mut index,
@@ -132,7 +134,7 @@ pub fn checkout_exclusive(
             .then(|| {
                 format!(
                     " of {}",
-                    entries_for_checkout.saturating_sub(errors.len() + collisions.len())
+                    entries_for_checkout.saturating_sub(errors.len() + collisions.len() + name_collisions.len())
                 )
             })
             .unwrap_or_default(),
@@ -141,7 +143,7 @@ pub fn checkout_exclusive(
             .display(bytes_written as usize, None, None)
     ));
 
-    if !(collisions.is_empty() && errors.is_empty()) {
+    if !(collisions.is_empty() && name_collisions.is_empty() && errors.is_empty()) {
         let mut messages = Vec::new();
         if !errors.is_empty() {
             messages.push(format!("kept going through {} errors(s)", errors.len()));
@@ -155,6 +157,17 @@ pub fn checkout_exclusive(
                 writeln!(err, "{}: collision ({:?})", col.path, col.error_kind).ok();
             }
         }
+        if !name_collisions.is_empty() {
+            messages.push(format!("skipped {} colliding path(s)", name_collisions.len()));
+            for col in name_collisions {
+                writeln!(
+                    err,
+                    "{}: collides with '{}' ({:?})",
+                    col.path, col.colliding_with, col.kind
+                )
+                .ok();
+            }
+        }
         bail!(
             "One or more errors occurred - checkout is incomplete: {}",
             messages.join(", ")
@@ -13,8 +13,8 @@ mod serde_only {
             name: String,
             /// The id of the directory tree of the associated tree object.
             id: String,
-            /// The amount of non-tree entries contained within, and definitely not zero.
-            num_entries: u32,
+            /// The amount of non-tree entries contained within, and definitely not zero, or `None` if invalidated.
+            num_entries: Option<u32>,
             children: Vec<Tree>,
         }
 
@@ -7,36 +7,67 @@ use crate::OutputFormat;
 
 pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
 
+/// Parse the mailmap at `path` and report each line that can't be parsed as well as entries that are overwritten by later
+/// ones, failing if there are unparsable lines.
+///
+/// With the JSON format, an object is printed with the total amount of `lines`, a list of `errors` as strings and a list of
+/// `overwritten` entries with their `old_email`, `old_name`, `new_email` and `new_name`, where all but `old_email` may be `null`.
 pub fn verify(path: impl AsRef<Path>, format: OutputFormat, mut out: impl Write) -> anyhow::Result<()> {
-    if format != OutputFormat::Human {
-        bail!("Only 'human' format is currently supported");
-    }
     let path = path.as_ref();
     let buf = std::fs::read(path).with_context(|| format!("Failed to read mailmap file at '{}'", path.display()))?;
-    let mut err_count = 0;
-    for err in git::mailmap::parse(&buf).filter_map(Result::err) {
-        err_count += 1;
-        writeln!(out, "{}", err)?;
-    }
+    let errors: Vec<_> = git::mailmap::parse(&buf)
+        .filter_map(Result::err)
+        .map(|err| err.to_string())
+        .collect();
 
     let mut seen = HashSet::<(_, _)>::default();
-    for entry in git::mailmap::parse(&buf).filter_map(Result::ok) {
-        if !seen.insert((entry.old_email(), entry.old_name())) {
-            writeln!(
+    let overwritten: Vec<_> = git::mailmap::parse(&buf)
+        .filter_map(Result::ok)
+        .filter(|entry| !seen.insert((entry.old_email(), entry.old_name())))
+        .collect();
+    let num_lines = git::mailmap::parse(&buf).count();
+
+    match format {
+        OutputFormat::Human => {
+            for err in &errors {
+                writeln!(out, "{}", err)?;
+            }
+            for entry in &overwritten {
+                writeln!(
+                    out,
+                    "NOTE: entry ({:?}, {:?}) -> ({:?}, {:?}) is being overwritten",
+                    entry.old_email(),
+                    entry.old_name(),
+                    entry.new_email(),
+                    entry.new_name()
+                )?;
+            }
+            if errors.is_empty() {
+                writeln!(out, "{} lines OK", num_lines)?;
+            }
+        }
+        #[cfg(feature = "serde1")]
+        OutputFormat::Json => {
+            let lossy = |s: Option<&git::bstr::BStr>| s.map(|s| s.to_string());
+            serde_json::to_writer_pretty(
                 out,
-                "NOTE: entry ({:?}, {:?}) -> ({:?}, {:?}) is being overwritten",
-                entry.old_email(),
-                entry.old_name(),
-                entry.new_email(),
-                entry.new_name()
+                &serde_json::json!({
+                    "lines": num_lines,
+                    "errors": errors,
+                    "overwritten": overwritten.iter().map(|entry| serde_json::json!({
+                        "old_email": entry.old_email().to_string(),
+                        "old_name": lossy(entry.old_name()),
+                        "new_email": lossy(entry.new_email()),
+                        "new_name": lossy(entry.new_name()),
+                    })).collect::<Vec<_>>(),
+                }),
             )?;
         }
     }
 
-    if err_count == 0 {
-        writeln!(out, "{} lines OK", git::mailmap::parse(&buf).count())?;
+    if errors.is_empty() {
         Ok(())
     } else {
-        bail!("{} lines in '{}' could not be parsed", err_count, path.display());
+        bail!("{} lines in '{}' could not be parsed", errors.len(), path.display());
     }
 }
@@ -18,12 +18,12 @@ impl Default for Mode {
     }
 }
 
-enum RepoKind {
+pub(crate) enum RepoKind {
     Bare,
     WorkingTree,
 }
 
-fn find_git_repository_workdirs<P: Progress>(
+pub(crate) fn find_git_repository_workdirs<P: Progress>(
     root: impl AsRef<Path>,
     mut progress: P,
     debug: bool,
@@ -6,7 +6,7 @@ use git_repository::{
     hash,
     hash::ObjectId,
     interrupt,
-    objs::bstr::ByteVec,
+    objs::bstr::{ByteSlice, ByteVec},
     odb::{pack, pack::FindExt},
     parallel::InOrderIter,
     prelude::Finalize,
@@ -73,7 +73,18 @@ pub struct Context<W> {
     pub nondeterministic_thread_count: Option<usize>,
     /// If true, delta objects may refer to their base as reference, allowing it not to be included in the created back.
     /// Otherwise these have to be recompressed in order to make the pack self-contained.
+    ///
+    /// As thin packs aren't valid at rest, no index is written for them.
     pub thin: bool,
+    /// If `Some(window)`, objects which aren't deltas in existing packs will be delta-compressed against one of the `window`
+    /// objects before them, as long as delta chains don't get longer than `max_depth`.
+    /// Otherwise these are stored as base objects.
+    pub delta_window: Option<usize>,
+    /// The maximum length of delta chains when delta-compressing objects, similar to `--depth` in `git pack-objects`.
+    pub max_depth: usize,
+    /// If true and no output directory is given, the pack is written to `out` for consumption by other programs,
+    /// instead of just printing its name.
+    pub pack_to_out: bool,
     /// If set, don't use more than this amount of threads.
     /// Otherwise, usually use as many threads as there are logical cores.
     /// A value of 0 is interpreted as no-limit
@@ -106,6 +117,9 @@ pub fn create<W>(
         expansion,
         nondeterministic_thread_count,
         thin,
+        delta_window,
+        max_depth,
+        pack_to_out,
         thread_limit,
         statistics,
         pack_cache_size_in_bytes,
@@ -127,27 +141,54 @@ where
         None => {
             let mut progress = progress.add_child("traversing");
             progress.init(None, progress::count("commits"));
-            let tips = tips
-                .map({
-                    let easy = repo.to_thread_local();
-                    move |tip| {
-                        ObjectId::from_hex(&Vec::from_os_str_lossy(tip.as_ref())).or_else(|_| {
-                            easy.find_reference(tip.as_ref())
-                                .map_err(anyhow::Error::from)
-                                .and_then(|r| r.into_fully_peeled_id().map(|oid| oid.detach()).map_err(Into::into))
-                        })
+            let (included, walked) = {
+                let easy = repo.to_thread_local();
+                let resolve = |tip: &[u8]| {
+                    ObjectId::from_hex(tip).or_else(|_| {
+                        easy.find_reference(tip.as_bstr())
+                            .map_err(anyhow::Error::from)
+                            .and_then(|r| r.into_fully_peeled_id().map(|oid| oid.detach()).map_err(Into::into))
+                    })
+                };
+                let (mut included, mut excluded) = (Vec::new(), Vec::new());
+                for tip in tips {
+                    let tip = Vec::from_os_str_lossy(tip.as_ref());
+                    match tip.strip_prefix(b"^") {
+                        Some(exclusion) => excluded.push(resolve(exclusion)?),
+                        None => included.push(resolve(&tip)?),
                     }
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+                }
+                // Commits reachable from exclusions have to be known before the first commit can be returned, hence
+                // the walk is performed upfront.
+                let walked = if excluded.is_empty() {
+                    None
+                } else {
+                    Some(
+                        easy.rev_walk(included.iter().copied())
+                            .hide(excluded)
+                            .all()?
+                            .map(|info| info.map(|info| info.id.detach()))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )
+                };
+                (included, walked)
+            };
+            // The object store must not be shared anymore to be turned into an `Arc`.
             let handle = repo.objects.into_shared_arc().to_cache_arc();
-            let iter = Box::new(
-                traverse::commit::Ancestors::new(tips, traverse::commit::ancestors::State::default(), {
-                    let handle = handle.clone();
-                    move |oid, buf| handle.find_commit_iter(oid, buf).map(|t| t.0)
-                })
-                .map(|res| res.map_err(Into::into))
-                .inspect(move |_| progress.inc()),
-            );
+            let iter: Box<dyn Iterator<Item = Result<ObjectId, input_iteration::Error>> + Send> = match walked {
+                None => Box::new(
+                    traverse::commit::Ancestors::new(included, traverse::commit::ancestors::State::default(), {
+                        let handle = handle.clone();
+                        move |oid, buf| handle.find_commit_iter(oid, buf).map(|t| t.0)
+                    })
+                    .map(|res| res.map_err(Into::into))
+                    .inspect(move |_| progress.inc()),
+                ),
+                Some(commits) => {
+                    progress.inc_by(commits.len());
+                    Box::new(commits.into_iter().map(Ok))
+                }
+            };
             (handle, iter)
         }
         Some(input) => {
@@ -171,7 +212,7 @@ where
     };
 
     let mut stats = Statistics::default();
-    let chunk_size = 1000; // What's a good value for this?
+    let chunk_size = delta_window.map_or(1000, |window| window.max(1000)); // What's a good value for this?
     let counts = {
         let mut progress = progress.add_child("counting");
         progress.init(None, progress::count("objects"));
@@ -242,7 +283,12 @@ where
             progress,
             pack::data::output::entry::iter_from_counts::Options {
                 thread_limit,
-                mode: pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                mode: match delta_window {
+                    Some(window) => {
+                        pack::data::output::entry::iter_from_counts::Mode::PackCopyAndDeltas { window, max_depth }
+                    }
+                    None => pack::data::output::entry::iter_from_counts::Mode::PackCopyAndBaseObjects,
+                },
                 allow_thin_pack: thin,
                 chunk_size,
                 version: Default::default(),
@@ -263,6 +309,7 @@ where
             named_tempfile_store = Some(tempfile::NamedTempFile::new_in(dir.as_ref())?);
             (named_tempfile_store.as_mut().expect("packfile just set"), Some(dir))
         }
+        None if pack_to_out => (&mut out, None),
         None => {
             sink_store = std::io::sink();
             (&mut sink_store, None)
@@ -292,12 +339,32 @@ where
         .digest()
         .expect("iteration is done");
     let pack_name = format!("{}.pack", hash);
-    if let (Some(pack_file), Some(dir)) = (named_tempfile_store.take(), output_directory) {
-        pack_file.persist(dir.as_ref().join(pack_name))?;
-    } else {
-        writeln!(out, "{}", pack_name)?;
-    }
     stats.entries = in_order_entries.inner.finalize()?;
+    match (named_tempfile_store.take(), output_directory) {
+        (Some(pack_file), Some(dir)) if thin => {
+            pack_file.persist(dir.as_ref().join(pack_name))?;
+        }
+        (Some(pack_file), Some(dir)) => {
+            // Indexing the pack also verifies it and moves it into place alongside its index.
+            let outcome = pack::Bundle::write_to_directory(
+                io::BufReader::new(pack_file.reopen()?),
+                Some(dir.as_ref()),
+                progress.add_child("indexing"),
+                &interrupt::IS_INTERRUPTED,
+                None,
+                pack::bundle::write::Options {
+                    thread_limit,
+                    iteration_mode: pack::data::input::Mode::Verify,
+                    index_kind: pack::index::Version::default(),
+                    object_hash: hash::Kind::default(),
+                    keep: None,
+                },
+            )?;
+            debug_assert_eq!(outcome.index.data_hash, hash, "indexing doesn't alter the pack");
+        }
+        _ if pack_to_out => {}
+        _ => writeln!(out, "{}", pack_name)?,
+    }
 
     write_progress.show_throughput(start);
     entries_progress.show_throughput(start);
@@ -332,6 +399,7 @@ fn human_output(
                 missing_objects,
                 objects_copied_from_pack,
                 ref_delta_objects,
+                delta_compressed_objects,
             },
     }: Statistics,
     mut out: impl std::io::Write,
@@ -352,8 +420,9 @@ fn human_output(
     #[rustfmt::skip]
     writeln!(
         out,
-        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
+        "\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}\n\t{:<width$} {}",
         "decoded and recompressed", decoded_and_recompressed_objects,
+        "delta compressed", delta_compressed_objects,
         "pack-to-pack copies", objects_copied_from_pack,
         "ref-delta-objects", ref_delta_objects,
         "missing objects", missing_objects,
@@ -87,6 +87,7 @@ pub fn from_pack(
         iteration_mode: ctx.iteration_mode.into(),
         index_kind: pack::index::Version::default(),
         object_hash: ctx.object_hash,
+        keep: None,
     };
     let out = ctx.out;
     let format = ctx.format;
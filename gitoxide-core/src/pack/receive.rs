@@ -151,6 +151,7 @@ mod blocking_io {
         ctx: Context<W>,
     ) -> anyhow::Result<()> {
         let transport = net::connect(url.as_bytes(), protocol.unwrap_or_default().into())?;
+        let should_interrupt = ctx.should_interrupt.clone();
         let delegate = CloneDelegate {
             ctx,
             directory,
@@ -163,6 +164,7 @@ mod blocking_io {
             delegate,
             protocol::credentials::helper,
             progress,
+            &should_interrupt,
             protocol::FetchConnection::TerminateOnSuccessfulCompletion,
         )?;
         Ok(())
@@ -202,7 +204,7 @@ mod async_io {
                 self.directory.take(),
                 self.refs_directory.take(),
                 &mut self.ctx,
-                futures_lite::io::BlockOn::new(input),
+                git_features::io::bridge::BlockOn::new(input),
                 progress,
                 &refs,
             )
@@ -219,6 +221,7 @@ mod async_io {
         ctx: Context<W>,
     ) -> anyhow::Result<()> {
         let transport = net::connect(url.as_bytes(), protocol.unwrap_or_default().into()).await?;
+        let should_interrupt = ctx.should_interrupt.clone();
         let mut delegate = CloneDelegate {
             ctx,
             directory,
@@ -232,6 +235,7 @@ mod async_io {
                 delegate,
                 protocol::credentials::helper,
                 progress,
+                &should_interrupt,
                 protocol::FetchConnection::TerminateOnSuccessfulCompletion,
             ))
         })
@@ -332,6 +336,7 @@ fn receive_pack_blocking<W: io::Write>(
         index_kind: pack::index::Version::V2,
         iteration_mode: pack::data::input::Mode::Verify,
         object_hash: ctx.object_hash,
+        keep: None,
     };
     let outcome =
         pack::Bundle::write_to_directory(input, directory.take(), progress, &ctx.should_interrupt, None, options)
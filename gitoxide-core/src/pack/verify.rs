@@ -212,6 +212,11 @@ fn print_statistics(out: &mut impl io::Write, stats: &index::traverse::Statistic
     }
     writeln!(out, "\t->: {}", total_object_count)?;
 
+    writeln!(out, "\nbase objects per amount of deltas based on them")?;
+    for (num_deltas, base_count) in &stats.bases_per_delta_count {
+        writeln!(out, "\t{:>2}: {}", num_deltas, base_count)?;
+    }
+
     let pack::data::decode_entry::Outcome {
         kind: _,
         num_deltas,
@@ -253,6 +258,14 @@ fn print_statistics(out: &mut impl io::Write, stats: &index::traverse::Statistic
         "num tags", stats.num_tags,
         width = width
     )?;
+    #[rustfmt::skip]
+    writeln!(
+        out,
+        "\t{:<width$}: {}\n\t{:<width$}: {}",
+        "num ofs deltas", stats.num_ofs_deltas,
+        "num ref deltas", stats.num_ref_deltas,
+        width = width
+    )?;
     let compression_ratio = stats.total_decompressed_entries_size as f64 / stats.total_compressed_entries_size as f64;
     let delta_compression_ratio = stats.total_object_size as f64 / stats.total_compressed_entries_size as f64;
     #[rustfmt::skip]
@@ -265,5 +278,16 @@ fn print_statistics(out: &mut impl io::Write, stats: &index::traverse::Statistic
         "pack overhead", (1.0 - (stats.total_compressed_entries_size as f64 / stats.pack_size as f64)) * 100.0,
         width = width
     )?;
+
+    if !stats.corrupt_objects.is_empty() {
+        writeln!(out, "\ncorrupt objects")?;
+        for object in &stats.corrupt_objects {
+            writeln!(
+                out,
+                "\t{} at offset {}: {}",
+                object.id, object.pack_offset, object.message
+            )?;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,183 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use anyhow::{anyhow, bail};
+use git_repository as git;
+use git_repository::{actor, interrupt, progress, Progress};
+use rusqlite::{params, OptionalExtension};
+
+use crate::{hours, organize};
+
+/// The name of the database file placed into the root directory if no other location is specified.
+pub const DEFAULT_DATABASE_NAME: &str = "ein.query";
+
+pub struct Options {
+    /// The database file to store results in and read them from, or [`DEFAULT_DATABASE_NAME`] in the root directory if unset.
+    pub database: Option<PathBuf>,
+    /// Recompute the results of all repositories even if they didn't change since they were last stored.
+    pub refresh: bool,
+}
+
+/// Find all repositories in `root`, estimate the amount of commits and hours per author of each one that changed since
+/// the last invocation, and print the accumulated results of all of them to `out`.
+///
+/// Results are stored per repository along with the commit `HEAD` pointed to at the time, and are only recomputed if it
+/// changed. As each repository is stored as soon as it is done, interrupted runs don't lose the work done so far.
+/// Repositories which can't be found anymore are removed from the database.
+pub fn query<P: Progress>(
+    root: impl AsRef<Path>,
+    mut out: impl io::Write,
+    mut progress: P,
+    Options { database, refresh }: Options,
+) -> anyhow::Result<()>
+where
+    <<P as Progress>::SubProgress as Progress>::SubProgress: Sync,
+{
+    let root = root.as_ref();
+    let mut db = open(&database.unwrap_or_else(|| root.join(DEFAULT_DATABASE_NAME)))?;
+
+    let start = Instant::now();
+    let mut seen = Vec::new();
+    let mut num_updated = 0;
+    {
+        let mut repo_progress = progress.add_child("Update repositories");
+        repo_progress.init(None, progress::count("repositories"));
+        for (workdir, _kind) in
+            organize::find_git_repository_workdirs(root, progress.add_child("Searching repositories"), false)
+        {
+            if interrupt::is_triggered() {
+                bail!("Cancelled by user");
+            }
+            repo_progress.inc();
+            let path = workdir.to_string_lossy().into_owned();
+            match update(&mut db, &path, &workdir, refresh, &mut repo_progress) {
+                Ok(was_updated) => num_updated += usize::from(was_updated),
+                Err(err) => repo_progress.fail(format!("Skipping repository at '{}': {:#}", path, err)),
+            }
+            seen.push(path);
+        }
+        repo_progress.show_throughput(start);
+    }
+    remove_unseen(&mut db, &seen)?;
+    progress.done(format!(
+        "Updated {} of {} repositories in {:?}",
+        num_updated,
+        seen.len(),
+        start.elapsed()
+    ));
+
+    let mut statement = db.prepare(
+        "SELECT MAX(name), email, SUM(commits), SUM(hours), COUNT(repository) FROM author
+         GROUP BY email ORDER BY SUM(hours) DESC, email",
+    )?;
+    let mut rows = statement.query([])?;
+    while let Some(row) = rows.next()? {
+        let (name, email, commits, hours, repositories): (String, String, u32, f64, u32) =
+            (row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?);
+        writeln!(
+            out,
+            "{} <{}>: {} commits in {} repositories, {:.02}h ({:.02} 8h days)",
+            name,
+            email,
+            commits,
+            repositories,
+            hours,
+            hours / f64::from(hours::HOURS_PER_WORKDAY)
+        )?;
+    }
+    Ok(())
+}
+
+fn open(path: &Path) -> anyhow::Result<rusqlite::Connection> {
+    let db = rusqlite::Connection::open(path)
+        .map_err(|err| anyhow!("Could not open database at '{}': {}", path.display(), err))?;
+    db.execute_batch(
+        "CREATE TABLE IF NOT EXISTS repository (
+             path TEXT PRIMARY KEY NOT NULL,
+             head TEXT NOT NULL
+         );
+         CREATE TABLE IF NOT EXISTS author (
+             repository TEXT NOT NULL REFERENCES repository (path) ON DELETE CASCADE,
+             name TEXT NOT NULL,
+             email TEXT NOT NULL,
+             commits INTEGER NOT NULL,
+             hours REAL NOT NULL,
+             PRIMARY KEY (repository, email)
+         );
+         PRAGMA foreign_keys = ON;",
+    )?;
+    Ok(db)
+}
+
+/// Recompute and store the results for the repository at `workdir` stored under `path` if its `HEAD` changed or if
+/// `refresh` is set, and return true if it was updated.
+fn update(
+    db: &mut rusqlite::Connection,
+    path: &str,
+    workdir: &Path,
+    refresh: bool,
+    progress: &mut impl Progress,
+) -> anyhow::Result<bool> {
+    let repo = git::open(workdir)?.apply_environment();
+    let head = repo.head_id()?;
+    let head_hex = head.to_hex().to_string();
+    let stored_head: Option<String> = db
+        .query_row("SELECT head FROM repository WHERE path = ?", [path], |row| row.get(0))
+        .optional()?;
+    if !refresh && stored_head.as_deref() == Some(head_hex.as_str()) {
+        return Ok(false);
+    }
+
+    let mailmap = repo.load_mailmap();
+    let mut authors: Vec<actor::Signature> = Vec::new();
+    for id in interrupt::Iter::new(head.ancestors().all(), || anyhow!("Cancelled by user")) {
+        let commit = id??.object()?.into_commit();
+        authors.push(commit.author_mailmapped(&mailmap)?);
+    }
+    let work_by_email = hours::estimate_hours_by_email(&mut authors);
+
+    let transaction = db.transaction()?;
+    transaction.execute("DELETE FROM repository WHERE path = ?", [path])?;
+    transaction.execute(
+        "INSERT INTO repository (path, head) VALUES (?, ?)",
+        params![path, head_hex],
+    )?;
+    {
+        let mut insert = transaction
+            .prepare("INSERT INTO author (repository, name, email, commits, hours) VALUES (?, ?, ?, ?, ?)")?;
+        for work in &work_by_email {
+            insert.execute(params![
+                path,
+                work.name.to_string(),
+                work.email.to_string(),
+                work.num_commits,
+                work.hours
+            ])?;
+        }
+    }
+    transaction.commit()?;
+    progress.info(format!(
+        "Stored {} commits by {} authors of repository at '{}'",
+        authors.len(),
+        work_by_email.len(),
+        path
+    ));
+    Ok(true)
+}
+
+/// Remove all repositories from the database whose path isn't in `seen`.
+fn remove_unseen(db: &mut rusqlite::Connection, seen: &[String]) -> anyhow::Result<()> {
+    let stored: Vec<String> = db
+        .prepare("SELECT path FROM repository")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    let transaction = db.transaction()?;
+    for path in stored.iter().filter(|path| !seen.contains(path)) {
+        transaction.execute("DELETE FROM repository WHERE path = ?", [path])?;
+    }
+    transaction.commit()?;
+    Ok(())
+}
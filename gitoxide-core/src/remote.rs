@@ -1,5 +1,6 @@
 pub mod refs {
     use git_repository::{
+        bstr::BString,
         protocol,
         protocol::{
             fetch::{Action, Arguments, Ref, Response},
@@ -15,10 +16,28 @@ pub mod refs {
 
     #[derive(Default)]
     struct LsRemotes {
+        /// If not empty, ask the server to only advertise references starting with one of these prefixes.
+        prefixes: Vec<BString>,
         refs: Vec<Ref>,
     }
 
     impl protocol::fetch::DelegateBlocking for LsRemotes {
+        fn prepare_ls_refs(
+            &mut self,
+            server: &transport::client::Capabilities,
+            arguments: &mut Vec<BString>,
+            _features: &mut Vec<(&str, Option<&str>)>,
+        ) -> io::Result<protocol::fetch::LsRefsAction> {
+            if server.contains("ls-refs") {
+                arguments.extend(
+                    self.prefixes
+                        .iter()
+                        .map(|prefix| format!("ref-prefix {}", prefix).into()),
+                );
+            }
+            Ok(protocol::fetch::LsRefsAction::Continue)
+        }
+
         fn prepare_fetch(
             &mut self,
             _version: transport::Protocol,
@@ -47,6 +66,7 @@ pub mod refs {
         use async_trait::async_trait;
         use futures_io::AsyncBufRead;
         use git_repository::{
+            bstr::BString,
             protocol,
             protocol::fetch::{Ref, Response},
             Progress,
@@ -68,6 +88,29 @@ pub mod refs {
             }
         }
 
+        pub(crate) async fn receive(
+            protocol: Option<net::Protocol>,
+            url: &[u8],
+            prefixes: Vec<BString>,
+            progress: impl Progress,
+        ) -> anyhow::Result<Vec<Ref>> {
+            let transport = net::connect(url, protocol.unwrap_or_default().into()).await?;
+            let mut delegate = LsRemotes {
+                prefixes,
+                ..Default::default()
+            };
+            protocol::fetch(
+                transport,
+                &mut delegate,
+                protocol::credentials::helper,
+                progress,
+                &git_repository::interrupt::IS_INTERRUPTED,
+                protocol::FetchConnection::TerminateOnSuccessfulCompletion,
+            )
+            .await?;
+            Ok(delegate.refs)
+        }
+
         pub async fn list(
             protocol: Option<net::Protocol>,
             url: &str,
@@ -88,6 +131,7 @@ pub mod refs {
                             &mut delegate,
                             protocol::credentials::helper,
                             progress,
+                            &git_repository::interrupt::IS_INTERRUPTED,
                             protocol::FetchConnection::TerminateOnSuccessfulCompletion,
                         )
                         .await?;
@@ -109,12 +153,15 @@ pub mod refs {
     }
     #[cfg(feature = "async-client")]
     pub use self::async_io::list;
+    #[cfg(feature = "async-client")]
+    pub(crate) use self::async_io::receive;
 
     #[cfg(feature = "blocking-client")]
     mod blocking_io {
         use std::io;
 
         use git_repository::{
+            bstr::BString,
             protocol,
             protocol::fetch::{Ref, Response},
             Progress,
@@ -137,35 +184,49 @@ pub mod refs {
             }
         }
 
-        pub fn list(
+        pub(crate) fn receive(
             protocol: Option<net::Protocol>,
-            url: &str,
+            url: &[u8],
+            prefixes: Vec<BString>,
             progress: impl Progress,
-            ctx: Context<impl io::Write>,
-        ) -> anyhow::Result<()> {
-            let transport = net::connect(url.as_bytes(), protocol.unwrap_or_default().into())?;
-            let mut delegate = LsRemotes::default();
+        ) -> anyhow::Result<Vec<Ref>> {
+            let transport = net::connect(url, protocol.unwrap_or_default().into())?;
+            let mut delegate = LsRemotes {
+                prefixes,
+                ..Default::default()
+            };
             protocol::fetch(
                 transport,
                 &mut delegate,
                 protocol::credentials::helper,
                 progress,
+                &git_repository::interrupt::IS_INTERRUPTED,
                 protocol::FetchConnection::TerminateOnSuccessfulCompletion,
             )?;
+            Ok(delegate.refs)
+        }
 
+        pub fn list(
+            protocol: Option<net::Protocol>,
+            url: &str,
+            progress: impl Progress,
+            ctx: Context<impl io::Write>,
+        ) -> anyhow::Result<()> {
+            let refs = receive(protocol, url.as_bytes(), Vec::new(), progress)?;
             match ctx.format {
-                OutputFormat::Human => drop(print(ctx.out, &delegate.refs)),
+                OutputFormat::Human => drop(print(ctx.out, &refs)),
                 #[cfg(feature = "serde1")]
-                OutputFormat::Json => serde_json::to_writer_pretty(
-                    ctx.out,
-                    &delegate.refs.into_iter().map(JsonRef::from).collect::<Vec<_>>(),
-                )?,
+                OutputFormat::Json => {
+                    serde_json::to_writer_pretty(ctx.out, &refs.into_iter().map(JsonRef::from).collect::<Vec<_>>())?
+                }
             };
             Ok(())
         }
     }
     #[cfg(feature = "blocking-client")]
     pub use blocking_io::list;
+    #[cfg(feature = "blocking-client")]
+    pub(crate) use blocking_io::receive;
 
     pub struct Context<W: io::Write> {
         pub thread_limit: Option<usize>,
@@ -0,0 +1,78 @@
+use std::{io, path::PathBuf};
+
+use anyhow::bail;
+use git_repository as git;
+use git_repository::{bstr::ByteSlice, objs::tree::EntryMode};
+
+/// What to print about an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Print the kind of the object, like `commit` or `blob`.
+    Kind,
+    /// Print the size of the object's data in bytes.
+    Size,
+    /// Print the object's data, with trees being converted into one line per entry.
+    Pretty,
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Pretty
+    }
+}
+
+pub fn cat_file(repository: PathBuf, spec: &str, mode: Mode, mut out: impl io::Write) -> anyhow::Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    let object = repo.rev_parse_single(spec)?.object()?;
+    match mode {
+        Mode::Kind => writeln!(out, "{}", object.kind)?,
+        Mode::Size => writeln!(out, "{}", object.data.len())?,
+        Mode::Pretty => match object.kind {
+            git::object::Kind::Tree => {
+                for entry in git::objs::TreeRefIter::from_bytes(&object.data) {
+                    let entry = entry?;
+                    let kind = match entry.mode {
+                        EntryMode::Tree => "tree",
+                        EntryMode::Commit => "commit",
+                        EntryMode::Blob | EntryMode::BlobExecutable | EntryMode::Link => "blob",
+                    };
+                    writeln!(
+                        out,
+                        "{:06o} {} {}\t{}",
+                        entry.mode as u16, kind, entry.oid, entry.filename
+                    )?;
+                }
+            }
+            git::object::Kind::Commit | git::object::Kind::Tag | git::object::Kind::Blob => {
+                out.write_all(&object.data)?
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Read one revision per line from `input` and write the id, kind and size of each object followed by its data to `out`,
+/// similar to `git cat-file --batch`.
+///
+/// Revisions that can't be resolved are reported as `<revision> missing` and don't abort the operation.
+pub fn batch(repository: PathBuf, input: impl io::BufRead, mut out: impl io::Write) -> anyhow::Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    for line in input.split(b'\n') {
+        let line = line?;
+        let spec = line.trim_end_with(|c| c == '\r').as_bstr();
+        if spec.is_empty() {
+            bail!("Empty lines are not allowed in batch mode");
+        }
+        let object = match repo.rev_parse_single(spec) {
+            Ok(id) => id.object()?,
+            Err(_) => {
+                writeln!(out, "{} missing", spec)?;
+                continue;
+            }
+        };
+        writeln!(out, "{} {} {}", object.id, object.kind, object.data.len())?;
+        out.write_all(&object.data)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,506 @@
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    io,
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use anyhow::{bail, Context as AnyhowContext};
+use git_repository as git;
+use git_repository::{
+    bstr::{BStr, BString, ByteSlice},
+    hash::ObjectId,
+    odb::{pack, FindExt},
+    protocol,
+    protocol::{
+        fetch::{response::ShallowUpdate, Action, Arguments, Ref, Response},
+        transport,
+        transport::client::Capabilities,
+    },
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+    Progress,
+};
+
+use crate::net;
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+/// The name of the remote the repository was cloned from.
+const REMOTE_NAME: &str = "origin";
+static FILTER: &[&str] = &["HEAD", "refs/heads/", "refs/tags/"];
+
+pub struct Options {
+    pub protocol: Option<net::Protocol>,
+    /// If set, create a bare repository without a work tree.
+    pub bare: bool,
+    /// If set, the history will be truncated to this amount of commits.
+    pub depth: Option<NonZeroU32>,
+    pub thread_limit: Option<usize>,
+    pub should_interrupt: Arc<AtomicBool>,
+}
+
+/// The information obtained from the remote while fetching.
+#[derive(Default)]
+struct Fetched {
+    refs: Vec<Ref>,
+    pack: Option<pack::bundle::write::Outcome>,
+    shallow: Vec<ObjectId>,
+}
+
+struct CloneDelegate {
+    depth: Option<NonZeroU32>,
+    pack_directory: PathBuf,
+    thread_limit: Option<usize>,
+    object_hash: git::hash::Kind,
+    should_interrupt: Arc<AtomicBool>,
+    fetched: Fetched,
+}
+
+fn is_wanted(r: &Ref) -> bool {
+    let (path, _) = r.unpack();
+    FILTER.iter().any(|prefix| path.starts_with_str(prefix))
+}
+
+impl protocol::fetch::DelegateBlocking for CloneDelegate {
+    fn prepare_ls_refs(
+        &mut self,
+        server: &Capabilities,
+        arguments: &mut Vec<BString>,
+        _features: &mut Vec<(&str, Option<&str>)>,
+    ) -> io::Result<protocol::fetch::LsRefsAction> {
+        if server.contains("ls-refs") {
+            arguments.extend(FILTER.iter().map(|r| format!("ref-prefix {}", r).into()));
+        }
+        Ok(protocol::fetch::LsRefsAction::Continue)
+    }
+
+    fn prepare_fetch(
+        &mut self,
+        _version: transport::Protocol,
+        _server: &Capabilities,
+        _features: &mut Vec<(&str, Option<&str>)>,
+        refs: &[Ref],
+    ) -> io::Result<Action> {
+        self.fetched.refs = refs.iter().filter(|r| is_wanted(r)).cloned().collect();
+        Ok(if self.fetched.refs.is_empty() {
+            Action::Cancel
+        } else {
+            Action::Continue
+        })
+    }
+
+    fn negotiate(
+        &mut self,
+        _refs: &[Ref],
+        arguments: &mut Arguments,
+        _previous_response: Option<&Response>,
+    ) -> io::Result<Action> {
+        let mut seen = HashSet::new();
+        for r in &self.fetched.refs {
+            let (_, id) = r.unpack();
+            if seen.insert(*id) {
+                arguments.want(id);
+            }
+        }
+        if let Some(depth) = self.depth {
+            if !arguments.can_use_deepen() {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "The remote doesn't support shallow clones",
+                ));
+            }
+            arguments.deepen(depth.get() as usize);
+        }
+        Ok(Action::Cancel)
+    }
+}
+
+impl CloneDelegate {
+    fn write_pack(&mut self, input: impl io::BufRead, progress: impl Progress, response: &Response) -> io::Result<()> {
+        let options = pack::bundle::write::Options {
+            thread_limit: self.thread_limit,
+            index_kind: pack::index::Version::V2,
+            iteration_mode: pack::data::input::Mode::Verify,
+            object_hash: self.object_hash,
+            keep: None,
+        };
+        let outcome = pack::Bundle::write_to_directory(
+            input,
+            Some(&self.pack_directory),
+            progress,
+            &self.should_interrupt,
+            None,
+            options,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.fetched.pack = Some(outcome);
+        self.fetched.shallow = response
+            .shallow_updates()
+            .iter()
+            .filter_map(|update| match update {
+                ShallowUpdate::Shallow(id) => Some(*id),
+                ShallowUpdate::Unshallow(_) => None,
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "blocking-client")]
+mod blocking_io {
+    use std::{io, io::BufRead, path::PathBuf};
+
+    use git_repository::{
+        protocol,
+        protocol::fetch::{Ref, Response},
+        Progress,
+    };
+
+    use super::{CloneDelegate, Fetched, Options};
+    use crate::net;
+
+    impl protocol::fetch::Delegate for CloneDelegate {
+        fn receive_pack(
+            &mut self,
+            input: impl BufRead,
+            progress: impl Progress,
+            _refs: &[Ref],
+            previous_response: &Response,
+        ) -> io::Result<()> {
+            self.write_pack(input, progress, previous_response)
+        }
+    }
+
+    pub(super) fn fetch(
+        url: &str,
+        pack_directory: PathBuf,
+        object_hash: git_repository::hash::Kind,
+        progress: impl Progress,
+        opts: &Options,
+    ) -> anyhow::Result<Fetched> {
+        let transport = net::connect(url.as_bytes(), opts.protocol.unwrap_or_default().into())?;
+        let mut delegate = CloneDelegate {
+            depth: opts.depth,
+            pack_directory,
+            thread_limit: opts.thread_limit,
+            object_hash,
+            should_interrupt: opts.should_interrupt.clone(),
+            fetched: Fetched::default(),
+        };
+        protocol::fetch(
+            transport,
+            &mut delegate,
+            protocol::credentials::helper,
+            progress,
+            &opts.should_interrupt,
+            protocol::FetchConnection::TerminateOnSuccessfulCompletion,
+        )?;
+        Ok(delegate.fetched)
+    }
+}
+
+#[cfg(feature = "async-client")]
+mod async_io {
+    use std::{io, path::PathBuf};
+
+    use async_trait::async_trait;
+    use futures_io::AsyncBufRead;
+    use git_repository::{
+        protocol,
+        protocol::fetch::{Ref, Response},
+        Progress,
+    };
+
+    use super::{CloneDelegate, Fetched, Options};
+    use crate::net;
+
+    #[async_trait(?Send)]
+    impl protocol::fetch::Delegate for CloneDelegate {
+        async fn receive_pack(
+            &mut self,
+            input: impl AsyncBufRead + Unpin + 'async_trait,
+            progress: impl Progress,
+            _refs: &[Ref],
+            previous_response: &Response,
+        ) -> io::Result<()> {
+            self.write_pack(futures_lite::io::BlockOn::new(input), progress, previous_response)
+        }
+    }
+
+    pub(super) fn fetch(
+        url: &str,
+        pack_directory: PathBuf,
+        object_hash: git_repository::hash::Kind,
+        progress: impl Progress,
+        opts: &Options,
+    ) -> anyhow::Result<Fetched> {
+        futures_lite::future::block_on(async {
+            let transport = net::connect(url.as_bytes(), opts.protocol.unwrap_or_default().into()).await?;
+            let mut delegate = CloneDelegate {
+                depth: opts.depth,
+                pack_directory,
+                thread_limit: opts.thread_limit,
+                object_hash,
+                should_interrupt: opts.should_interrupt.clone(),
+                fetched: Fetched::default(),
+            };
+            protocol::fetch(
+                transport,
+                &mut delegate,
+                protocol::credentials::helper,
+                progress,
+                &opts.should_interrupt,
+                protocol::FetchConnection::TerminateOnSuccessfulCompletion,
+            )
+            .await?;
+            Ok::<_, anyhow::Error>(delegate.fetched)
+        })
+    }
+}
+
+#[cfg(feature = "async-client")]
+use async_io::fetch;
+#[cfg(feature = "blocking-client")]
+use blocking_io::fetch;
+
+/// Derive the directory to clone into from the last component of the path in `url`, like `git clone` does.
+pub fn directory_from_url(url: &str, bare: bool) -> anyhow::Result<PathBuf> {
+    let url = git::url::parse(url.as_bytes())?;
+    let path = url.path.trim_end_with(|c| c == '/');
+    let path = path.strip_suffix(b"/.git").unwrap_or(path);
+    let name = path.rsplit_str("/").next().unwrap_or_default();
+    let name = name.strip_suffix(b".git").unwrap_or(name);
+    if name.is_empty() {
+        bail!("Could not derive a directory name from the url - please specify it")
+    }
+    let mut name = name.to_str()?.to_owned();
+    if bare {
+        name.push_str(".git");
+    }
+    Ok(name.into())
+}
+
+/// Clone the repository at `url` into `directory`, or into a directory named after the last component of `url` if unset.
+///
+/// All branches of the remote are made available as remote tracking branches, all of its tags as tags and the branch
+/// its `HEAD` points to is checked out.
+pub fn clone(
+    url: &str,
+    directory: Option<PathBuf>,
+    mut progress: impl Progress,
+    mut out: impl io::Write,
+    opts: Options,
+) -> anyhow::Result<()> {
+    let directory = match directory {
+        Some(directory) => directory,
+        None => directory_from_url(url, opts.bare)?,
+    };
+    let existed = directory.exists();
+    if existed && std::fs::read_dir(&directory)?.next().is_some() {
+        bail!(
+            "Destination path '{}' already exists and is not an empty directory",
+            directory.display()
+        )
+    }
+    progress.info(format!("Cloning into '{}'", directory.display()));
+    std::fs::create_dir_all(&directory)?;
+
+    let res = clone_into(url, &directory, &mut progress, &mut out, &opts);
+    if res.is_err() {
+        if existed {
+            for entry in std::fs::read_dir(&directory)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    std::fs::remove_dir_all(path).ok();
+                } else {
+                    std::fs::remove_file(path).ok();
+                }
+            }
+        } else {
+            std::fs::remove_dir_all(&directory).ok();
+        }
+    }
+    res
+}
+
+fn clone_into(
+    url: &str,
+    directory: &Path,
+    progress: &mut impl Progress,
+    mut out: impl io::Write,
+    opts: &Options,
+) -> anyhow::Result<()> {
+    let repo = if opts.bare {
+        git::init_bare(directory)
+    } else {
+        git::init(directory)
+    }
+    .with_context(|| "Repository initialization failed")?;
+    let git_dir = repo.git_dir().to_owned();
+
+    let fetched = fetch(
+        url,
+        git_dir.join("objects").join("pack"),
+        repo.object_hash(),
+        progress.add_child("fetch"),
+        opts,
+    )?;
+    if fetched.refs.is_empty() {
+        progress.info("The remote appears to be an empty repository");
+    }
+
+    let head_branch = fetched.refs.iter().find_map(|r| match r {
+        Ref::Symbolic { path, target, .. } if path == "HEAD" => {
+            target.strip_prefix(b"refs/heads/").map(|b| b.as_bstr())
+        }
+        _ => None,
+    });
+    write_config(&git_dir, url, head_branch, opts.bare)?;
+    if !fetched.shallow.is_empty() {
+        let mut shallow: Vec<_> = fetched.shallow.iter().map(|id| format!("{}\n", id)).collect();
+        shallow.sort();
+        std::fs::write(git_dir.join("shallow"), shallow.concat())?;
+    }
+    let head = write_refs(&repo, &fetched.refs, url, opts.bare)?;
+
+    if let Some(pack) = &fetched.pack {
+        writeln!(
+            out,
+            "Received pack {} with {} objects",
+            pack.index.data_hash, pack.index.num_objects
+        )?;
+    }
+    writeln!(out, "Created {} reference(s)", fetched.refs.len())?;
+
+    match repo.work_dir() {
+        Some(work_dir) if head.is_some() => {
+            let files_updated = checkout(&repo, work_dir, progress, opts)?;
+            writeln!(out, "Checked out {} file(s)", files_updated)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Configure our remote to point to `url` and, unless `bare`, let the checked-out `head_branch` track its remote counterpart.
+fn write_config(git_dir: &Path, url: &str, head_branch: Option<&BStr>, bare: bool) -> io::Result<()> {
+    use std::io::Write;
+    let mut config = std::fs::OpenOptions::new().append(true).open(git_dir.join("config"))?;
+    writeln!(config, "[remote \"{}\"]\n\turl = {}", REMOTE_NAME, url)?;
+    if !bare {
+        writeln!(config, "\tfetch = +refs/heads/*:refs/remotes/{}/*", REMOTE_NAME)?;
+        if let Some(branch) = head_branch {
+            writeln!(
+                config,
+                "[branch \"{0}\"]\n\tremote = {1}\n\tmerge = refs/heads/{0}",
+                branch, REMOTE_NAME
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Turn the `refs` of the remote into our own references and point `HEAD` to the remote's `HEAD`.
+///
+/// Return the id `HEAD` points to, if the remote has a `HEAD`.
+fn write_refs(repo: &git::Repository, refs: &[Ref], url: &str, bare: bool) -> anyhow::Result<Option<ObjectId>> {
+    let message: BString = format!("clone: from {}", url).into();
+    let edit = |name: FullName, new: Target| RefEdit {
+        change: Change::Update {
+            log: LogChange {
+                mode: RefLog::AndReference,
+                force_create_reflog: false,
+                message: message.clone(),
+            },
+            expected: PreviousValue::Any,
+            new,
+        },
+        name,
+        deref: false,
+    };
+    let local_name = |path: &BString| -> anyhow::Result<FullName> {
+        Ok(match path.strip_prefix(b"refs/heads/") {
+            Some(branch) if !bare => format!("refs/remotes/{}/{}", REMOTE_NAME, branch.as_bstr()).try_into()?,
+            _ => path.as_bstr().try_into()?,
+        })
+    };
+
+    let mut edits = Vec::new();
+    let mut head = None;
+    for r in refs {
+        match r {
+            Ref::Symbolic { path, target, object } if path == "HEAD" => {
+                head = Some(*object);
+                if !bare {
+                    edits.push(edit(target.as_bstr().try_into()?, Target::Peeled(*object)));
+                    edits.push(edit(
+                        format!("refs/remotes/{}/HEAD", REMOTE_NAME).try_into()?,
+                        Target::Symbolic(local_name(target)?),
+                    ));
+                }
+                edits.push(edit("HEAD".try_into()?, Target::Symbolic(target.as_bstr().try_into()?)));
+            }
+            Ref::Direct { path, object } if path == "HEAD" => {
+                head = Some(*object);
+                edits.push(edit("HEAD".try_into()?, Target::Peeled(*object)));
+            }
+            Ref::Symbolic { path, object, .. }
+            | Ref::Direct { path, object }
+            | Ref::Peeled { path, tag: object, .. } => edits.push(edit(local_name(path)?, Target::Peeled(*object))),
+        }
+    }
+    repo.edit_references(edits, git::lock::acquire::Fail::Immediately, None)?;
+    Ok(head)
+}
+
+/// Checkout the tree of the `HEAD` commit into `work_dir` and write the index, returning the amount of written files.
+fn checkout(
+    repo: &git::Repository,
+    work_dir: &Path,
+    progress: &mut impl Progress,
+    opts: &Options,
+) -> anyhow::Result<usize> {
+    let tree = repo.head_commit()?.tree_id()?;
+    let objects = repo.objects.clone().into_arc()?;
+    let mut index = git::index::State::from_tree(&tree, |oid, buf| objects.find_tree_iter(oid, buf).ok())?;
+
+    let mut files = progress.add_child("checkout");
+    let mut bytes = progress.add_child("writing");
+    files.init(Some(index.entries().len()), git::progress::count("files"));
+    bytes.init(None, git::progress::bytes());
+    let start = std::time::Instant::now();
+
+    let outcome = git::worktree::index::checkout(
+        &mut index,
+        work_dir,
+        move |oid, buf| objects.find_blob(oid, buf),
+        &mut files,
+        &mut bytes,
+        &opts.should_interrupt,
+        git::worktree::index::checkout::Options {
+            fs: git::worktree::fs::Capabilities::probe(repo.git_dir()),
+            destination_is_initially_empty: true,
+            thread_limit: opts.thread_limit,
+            ..Default::default()
+        },
+    )?;
+    files.show_throughput(start);
+    bytes.show_throughput(start);
+
+    if !(outcome.errors.is_empty() && outcome.collisions.is_empty() && outcome.name_collisions.is_empty()) {
+        bail!(
+            "Checkout is incomplete: encountered {} error(s), {} collision(s) and skipped {} colliding path(s)",
+            outcome.errors.len(),
+            outcome.collisions.len(),
+            outcome.name_collisions.len()
+        )
+    }
+    git::index::File::from_state(index, repo.git_dir().join("index"))
+        .write(Default::default(), git::lock::acquire::Fail::Immediately)?;
+    Ok(outcome.files_updated)
+}
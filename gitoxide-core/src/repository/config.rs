@@ -0,0 +1,288 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail};
+use git_config::file::{from_paths, GitConfig};
+use git_repository as git;
+use git_repository::bstr::ByteSlice;
+
+use crate::OutputFormat;
+
+/// The configuration files git reads, in the order they are read in, with values in later ones overriding earlier ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde1", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde1", serde(rename_all = "lowercase"))]
+pub enum Scope {
+    /// The installation-wide configuration, usually at `/etc/gitconfig`.
+    System,
+    /// The configuration of the user, at `~/.gitconfig` or `$XDG_CONFIG_HOME/git/config`.
+    Global,
+    /// The configuration of the repository, at `.git/config`.
+    Local,
+    /// Values passed through the `GIT_CONFIG_COUNT`, `GIT_CONFIG_KEY_<n>` and `GIT_CONFIG_VALUE_<n>` environment variables.
+    Command,
+}
+
+impl Scope {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Scope::System => "system",
+            Scope::Global => "global",
+            Scope::Local => "local",
+            Scope::Command => "command",
+        }
+    }
+}
+
+impl std::str::FromStr for Scope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "system" => Scope::System,
+            "global" => Scope::Global,
+            "local" => Scope::Local,
+            _ => {
+                return Err(format!(
+                    "Invalid scope: {:?}, valid values are 'system', 'global' and 'local'",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+/// What to do with the configuration.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Print all values of all scopes.
+    List,
+    /// Print the value of `key` like `core.bare` that takes effect, which is the last one that was read.
+    Get { key: String },
+    /// Set `key` to `value` in the configuration file of `scope`, replacing its last occurrence if it exists.
+    Set { key: String, value: String, scope: Scope },
+    /// Remove the last occurrence of `key` from the configuration file of `scope`.
+    Unset { key: String, scope: Scope },
+}
+
+pub struct Options {
+    pub format: OutputFormat,
+    /// Print the file each value was read from.
+    pub show_origin: bool,
+    /// Print the scope each value was read from.
+    pub show_scope: bool,
+}
+
+/// A value along with where it was defined, as printed by [`config()`].
+///
+/// With the JSON format, a list of these is printed when listing, and a single one when getting a value.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize))]
+pub struct Entry {
+    /// The scope the value is defined in.
+    pub scope: Scope,
+    /// The file the value is defined in, or `None` if it wasn't read from a file.
+    pub path: Option<PathBuf>,
+    /// The 1-based line of the value in `path`.
+    pub line: Option<usize>,
+    /// The full key of the value, like `remote.origin.url`, with the section and value name in lower case.
+    pub key: String,
+    /// The uninterpreted value.
+    pub value: String,
+}
+
+/// List, query or change the configuration of the repository at `repository` along with the system and global
+/// configuration, similar to `git config`.
+///
+/// If `repository` isn't a repository, only the system, global and command scopes are available.
+pub fn config(
+    repository: PathBuf,
+    action: Action,
+    mut out: impl io::Write,
+    Options {
+        format,
+        show_origin,
+        show_scope,
+    }: Options,
+) -> anyhow::Result<()> {
+    let git_dir = git::discover(repository).ok().map(|repo| repo.git_dir().to_owned());
+    match action {
+        Action::List => {
+            let entries = entries(git_dir.as_deref())?;
+            match format {
+                OutputFormat::Human => {
+                    for entry in &entries {
+                        print_entry(&mut out, entry, show_origin, show_scope, true)?;
+                    }
+                }
+                #[cfg(feature = "serde1")]
+                OutputFormat::Json => serde_json::to_writer_pretty(out, &entries)?,
+            }
+        }
+        Action::Get { key } => {
+            let (section, subsection, name) = split_key(&key)?;
+            let entry = entries(git_dir.as_deref())?
+                .into_iter()
+                .rev()
+                .find(|entry| {
+                    split_key(&entry.key).map_or(false, |(s, sub, n)| {
+                        s.eq_ignore_ascii_case(section) && sub == subsection && n.eq_ignore_ascii_case(name)
+                    })
+                })
+                .ok_or_else(|| anyhow!("The key '{}' isn't set", key))?;
+            match format {
+                OutputFormat::Human => print_entry(&mut out, &entry, show_origin, show_scope, false)?,
+                #[cfg(feature = "serde1")]
+                OutputFormat::Json => serde_json::to_writer_pretty(out, &entry)?,
+            }
+        }
+        Action::Set { key, value, scope } => {
+            let path = writable_path(scope, git_dir.as_deref())?;
+            let mut config = open_or_new(&path)?;
+            config.set_raw_value_by_key(&key, value.as_bytes())?;
+            config.write_to_path(&path, git::lock::acquire::Fail::Immediately)?;
+        }
+        Action::Unset { key, scope } => {
+            let path = writable_path(scope, git_dir.as_deref())?;
+            let mut config = open_or_new(&path)?;
+            if config.unset_by_key(&key)?.is_none() {
+                bail!("The key '{}' isn't set in '{}'", key, path.display());
+            }
+            config.write_to_path(&path, git::lock::acquire::Fail::Immediately)?;
+        }
+    }
+    Ok(())
+}
+
+fn print_entry(
+    mut out: impl io::Write,
+    entry: &Entry,
+    show_origin: bool,
+    show_scope: bool,
+    with_key: bool,
+) -> io::Result<()> {
+    if show_scope {
+        write!(out, "{}\t", entry.scope.as_str())?;
+    }
+    if show_origin {
+        match &entry.path {
+            Some(path) => write!(out, "file:{}\t", path.display())?,
+            None => write!(out, "command line:\t")?,
+        }
+    }
+    if with_key {
+        write!(out, "{}=", entry.key)?;
+    }
+    writeln!(out, "{}", entry.value)
+}
+
+/// Read all values of all scopes in the order git reads them, including the repository configuration if `git_dir` is set.
+fn entries(git_dir: Option<&Path>) -> anyhow::Result<Vec<Entry>> {
+    let options = from_paths::Options {
+        git_dir,
+        ..Default::default()
+    };
+    let mut configs = Vec::new();
+    for scope in [Scope::System, Scope::Global, Scope::Local] {
+        for path in readable_paths(scope, git_dir) {
+            if path.is_file() {
+                configs.push((scope, GitConfig::from_paths(vec![path], &options)?));
+            }
+        }
+    }
+    if let Some(config) = GitConfig::from_env(&options)? {
+        configs.push((Scope::Command, config));
+    }
+
+    Ok(configs
+        .iter()
+        .flat_map(|(scope, config)| {
+            config.entries().into_iter().map(move |entry| Entry {
+                scope: *scope,
+                path: entry.origin.and_then(|o| o.path.map(ToOwned::to_owned)),
+                line: entry.origin.map(|o| o.line),
+                key: match &entry.section.subsection_name {
+                    Some(subsection) => format!(
+                        "{}.{}.{}",
+                        entry.section.name.to_ascii_lowercase(),
+                        subsection,
+                        entry.key.to_ascii_lowercase()
+                    ),
+                    None => format!(
+                        "{}.{}",
+                        entry.section.name.to_ascii_lowercase(),
+                        entry.key.to_ascii_lowercase()
+                    ),
+                },
+                value: entry.value.as_bstr().to_string(),
+            })
+        })
+        .collect())
+}
+
+/// The files of `scope` in the order git reads them, which may not exist.
+fn readable_paths(scope: Scope, git_dir: Option<&Path>) -> Vec<PathBuf> {
+    let var = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    match scope {
+        Scope::System => {
+            if env_var_is_true("GIT_CONFIG_NOSYSTEM") {
+                Vec::new()
+            } else {
+                vec![var("GIT_CONFIG_SYSTEM").unwrap_or_else(|| "/etc/gitconfig".into())]
+            }
+        }
+        Scope::Global => match var("GIT_CONFIG_GLOBAL") {
+            Some(path) => vec![path],
+            None => var("XDG_CONFIG_HOME")
+                .map(|dir| dir.join("git").join("config"))
+                .or_else(|| var("HOME").map(|home| home.join(".config").join("git").join("config")))
+                .into_iter()
+                .chain(var("HOME").map(|home| home.join(".gitconfig")))
+                .collect(),
+        },
+        Scope::Local => git_dir.map(|git_dir| git_dir.join("config")).into_iter().collect(),
+        Scope::Command => Vec::new(),
+    }
+}
+
+/// The file of `scope` to write changes to, which is the last one git reads.
+fn writable_path(scope: Scope, git_dir: Option<&Path>) -> anyhow::Result<PathBuf> {
+    if scope == Scope::Local && git_dir.is_none() {
+        bail!("Not in a git repository, use another scope to write to")
+    }
+    if scope == Scope::System && env_var_is_true("GIT_CONFIG_NOSYSTEM") {
+        bail!("The system configuration is disabled by GIT_CONFIG_NOSYSTEM")
+    }
+    readable_paths(scope, git_dir)
+        .pop()
+        .ok_or_else(|| anyhow!("Couldn't determine where to write {} configuration to", scope.as_str()))
+}
+
+fn open_or_new(path: &Path) -> anyhow::Result<GitConfig<'static>> {
+    Ok(if path.is_file() {
+        GitConfig::open(path)?
+    } else {
+        GitConfig::new()
+    })
+}
+
+/// Split `key` like `remote.origin.url` into its section, optional subsection and value name.
+fn split_key(key: &str) -> anyhow::Result<(&str, Option<&str>, &str)> {
+    let (section, rest) = key
+        .split_once('.')
+        .ok_or_else(|| anyhow!("'{}' is not a valid key of the form 'section[.subsection].name'", key))?;
+    Ok(match rest.rsplit_once('.') {
+        Some((subsection, name)) => (section, Some(subsection), name),
+        None => (section, None, rest),
+    })
+}
+
+/// Return true if the environment variable `name` is set to a value git considers true.
+fn env_var_is_true(name: &str) -> bool {
+    std::env::var(name).map_or(false, |value| match value.to_ascii_lowercase().as_str() {
+        "" | "no" | "off" | "false" => false,
+        "yes" | "on" | "true" => true,
+        number => number.parse::<i64>().map_or(true, |n| n != 0),
+    })
+}
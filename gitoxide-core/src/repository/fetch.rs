@@ -0,0 +1,711 @@
+use std::{
+    collections::HashSet,
+    convert::{TryFrom, TryInto},
+    io,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc},
+};
+
+use anyhow::{anyhow, bail};
+use git_repository as git;
+use git_repository::{
+    bstr::{BString, ByteSlice},
+    hash::ObjectId,
+    odb::pack,
+    protocol,
+    protocol::{
+        fetch::{Action, Arguments, Ref, Response},
+        transport,
+        transport::client::Capabilities,
+    },
+    refs::{
+        transaction::{Change, LogChange, PreviousValue, RefEdit, RefLog},
+        FullName, Target,
+    },
+    refspec,
+    refspec::RefSpec,
+    Progress,
+};
+
+use crate::{net, OutputFormat};
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=3;
+
+/// The name of the remote to fetch from if none is given.
+const DEFAULT_REMOTE: &str = "origin";
+
+pub struct Options {
+    pub protocol: Option<net::Protocol>,
+    /// If set, print how references of the remote map to local ones, without fetching a pack or updating references.
+    pub dry_run: bool,
+    pub thread_limit: Option<usize>,
+    pub should_interrupt: Arc<AtomicBool>,
+    /// With the JSON format, print an object with the `url` fetched from, the `pack` that was received, if any, and a list
+    /// of [`MappedRef`]s in `refs` instead of git-like update lines.
+    pub format: OutputFormat,
+}
+
+/// How a reference of the remote maps to a local one.
+#[derive(Debug, Clone)]
+struct Mapping {
+    /// The full name of the reference on the remote.
+    remote: BString,
+    /// The object it points to, which is the tag object for annotated tags.
+    id: ObjectId,
+    /// The reference to store `id` in, if any.
+    local: Option<FullName>,
+    /// If true, the local reference is updated even if it isn't a fast-forward.
+    force: bool,
+}
+
+/// The way a local reference is updated by a [`Mapping`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Update {
+    /// The remote reference isn't stored in a local reference.
+    NotStored,
+    /// The local reference doesn't exist yet.
+    New,
+    /// The local reference already points to the desired object.
+    UpToDate,
+    /// The new object is a descendant of the one the local reference points to.
+    FastForward { previous: ObjectId },
+    /// The new object isn't a descendant of the previous one, but the refspec allows forced updates.
+    Forced { previous: ObjectId },
+    /// The update isn't a fast-forward or would change an existing tag, and it's not forced.
+    Rejected { previous: ObjectId },
+    /// The local reference would change, but as the new object isn't available locally it's unknown how.
+    Unknown { previous: ObjectId },
+}
+
+struct FetchDelegate<'a> {
+    repo: &'a git::Repository,
+    specs: &'a [RefSpec],
+    dry_run: bool,
+    haves: Vec<ObjectId>,
+    thread_limit: Option<usize>,
+    should_interrupt: Arc<AtomicBool>,
+    /// All references advertised by the remote.
+    refs: Vec<Ref>,
+    mappings: Vec<Mapping>,
+    pack: Option<pack::bundle::write::Outcome>,
+}
+
+impl<'a> FetchDelegate<'a> {
+    /// The ids of all mapped objects that aren't present locally.
+    fn wants(&self) -> Vec<ObjectId> {
+        let mut seen = HashSet::new();
+        self.mappings
+            .iter()
+            .map(|m| m.id)
+            .filter(|id| seen.insert(*id) && !git::odb::Find::contains(&self.repo.objects, id))
+            .collect()
+    }
+
+    fn write_pack(&mut self, input: impl io::BufRead, progress: impl Progress) -> io::Result<()> {
+        let options = pack::bundle::write::Options {
+            thread_limit: self.thread_limit,
+            index_kind: pack::index::Version::V2,
+            iteration_mode: pack::data::input::Mode::Verify,
+            object_hash: self.repo.object_hash(),
+            keep: None,
+        };
+        let objects = self.repo.objects.clone();
+        let outcome = pack::Bundle::write_to_directory(
+            input,
+            Some(self.repo.objects.store_ref().path().join("pack")),
+            progress,
+            &self.should_interrupt,
+            // Packs sent during fetches may be thin, referring to base objects we already have.
+            Some(Box::new(move |oid, buf| {
+                git::odb::Find::try_find(&objects, oid, buf).ok().flatten()
+            })),
+            options,
+        )
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.pack = Some(outcome);
+        Ok(())
+    }
+}
+
+impl<'a> protocol::fetch::DelegateBlocking for FetchDelegate<'a> {
+    fn prepare_ls_refs(
+        &mut self,
+        server: &Capabilities,
+        arguments: &mut Vec<BString>,
+        _features: &mut Vec<(&str, Option<&str>)>,
+    ) -> io::Result<protocol::fetch::LsRefsAction> {
+        if server.contains("ls-refs") {
+            arguments.extend(
+                self.specs
+                    .iter()
+                    .flat_map(|spec| spec.to_ref().prefixes())
+                    .map(|prefix| format!("ref-prefix {}", prefix).into()),
+            );
+        }
+        Ok(protocol::fetch::LsRefsAction::Continue)
+    }
+
+    fn prepare_fetch(
+        &mut self,
+        _version: transport::Protocol,
+        _server: &Capabilities,
+        _features: &mut Vec<(&str, Option<&str>)>,
+        refs: &[Ref],
+    ) -> io::Result<Action> {
+        self.refs = refs.into();
+        let specs = self.specs;
+        let group = refspec::MatchGroup::from_specs(specs.iter().map(RefSpec::to_ref));
+        let mut stored = HashSet::new();
+        for mapping in group.match_refs(refs.iter().map(|r| {
+            let (path, id) = r.unpack();
+            refspec::match_group::Item {
+                full_ref_name: path.as_bstr(),
+                target: id,
+            }
+        })) {
+            let (remote, id) = match mapping.lhs {
+                refspec::match_group::Source::FullName(name) => (
+                    name.to_owned(),
+                    *refs[mapping.item_index.expect("set for names")].unpack().1,
+                ),
+                refspec::match_group::Source::ObjectId(id) => (id.to_string().into(), id),
+            };
+            let local: Option<FullName> = mapping
+                .rhs
+                .map(|name| name.into_owned().try_into())
+                .transpose()
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+            if let Some(local) = &local {
+                if !stored.insert(local.clone()) {
+                    continue;
+                }
+            }
+            self.mappings.push(Mapping {
+                remote,
+                id,
+                local,
+                force: group.specs()[mapping.spec_index].mode() == refspec::Mode::Force,
+            });
+        }
+        Ok(if self.dry_run || self.wants().is_empty() {
+            Action::Cancel
+        } else {
+            Action::Continue
+        })
+    }
+
+    fn negotiate(
+        &mut self,
+        _refs: &[Ref],
+        arguments: &mut Arguments,
+        _previous_response: Option<&Response>,
+    ) -> io::Result<Action> {
+        for id in self.wants() {
+            arguments.want(id);
+        }
+        for id in &self.haves {
+            arguments.have(id);
+        }
+        Ok(Action::Cancel)
+    }
+}
+
+#[cfg(feature = "blocking-client")]
+mod blocking_io {
+    use std::{io, io::BufRead};
+
+    use git_repository::{
+        protocol,
+        protocol::fetch::{Ref, Response},
+        Progress,
+    };
+
+    use super::FetchDelegate;
+    use crate::net;
+
+    impl<'a> protocol::fetch::Delegate for FetchDelegate<'a> {
+        fn receive_pack(
+            &mut self,
+            input: impl BufRead,
+            progress: impl Progress,
+            _refs: &[Ref],
+            _previous_response: &Response,
+        ) -> io::Result<()> {
+            self.write_pack(input, progress)
+        }
+    }
+
+    pub(super) fn fetch(
+        url: &[u8],
+        protocol: Option<net::Protocol>,
+        delegate: &mut FetchDelegate<'_>,
+        progress: impl Progress,
+    ) -> anyhow::Result<()> {
+        let transport = net::connect(url, protocol.unwrap_or_default().into())?;
+        let should_interrupt = delegate.should_interrupt.clone();
+        protocol::fetch(
+            transport,
+            delegate,
+            protocol::credentials::helper,
+            progress,
+            &should_interrupt,
+            protocol::FetchConnection::TerminateOnSuccessfulCompletion,
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "async-client")]
+mod async_io {
+    use std::io;
+
+    use async_trait::async_trait;
+    use futures_io::AsyncBufRead;
+    use git_repository::{
+        protocol,
+        protocol::fetch::{Ref, Response},
+        Progress,
+    };
+
+    use super::FetchDelegate;
+    use crate::net;
+
+    #[async_trait(?Send)]
+    impl<'a> protocol::fetch::Delegate for FetchDelegate<'a> {
+        async fn receive_pack(
+            &mut self,
+            input: impl AsyncBufRead + Unpin + 'async_trait,
+            progress: impl Progress,
+            _refs: &[Ref],
+            _previous_response: &Response,
+        ) -> io::Result<()> {
+            self.write_pack(futures_lite::io::BlockOn::new(input), progress)
+        }
+    }
+
+    pub(super) fn fetch(
+        url: &[u8],
+        protocol: Option<net::Protocol>,
+        delegate: &mut FetchDelegate<'_>,
+        progress: impl Progress,
+    ) -> anyhow::Result<()> {
+        futures_lite::future::block_on(async {
+            let transport = net::connect(url, protocol.unwrap_or_default().into()).await?;
+            let should_interrupt = delegate.should_interrupt.clone();
+            protocol::fetch(
+                transport,
+                delegate,
+                protocol::credentials::helper,
+                progress,
+                &should_interrupt,
+                protocol::FetchConnection::TerminateOnSuccessfulCompletion,
+            )
+            .await?;
+            Ok::<_, anyhow::Error>(())
+        })
+    }
+}
+
+#[cfg(feature = "async-client")]
+use async_io::fetch as fetch_pack;
+#[cfg(feature = "blocking-client")]
+use blocking_io::fetch as fetch_pack;
+
+/// Fetch from `remote` into the repository at `repository`, which is either the name of a configured remote or a URL,
+/// defaulting to `origin`.
+///
+/// The given `refspecs` are used instead of the ones configured for the remote, and if there are none, only `HEAD` is
+/// fetched without storing it. References that would be updated in a way that isn't a fast-forward are rejected unless
+/// their refspec is prefixed with `+`, and so are changes to existing tags.
+pub fn fetch(
+    repository: PathBuf,
+    remote: Option<String>,
+    refspecs: Vec<BString>,
+    mut progress: impl Progress,
+    mut out: impl io::Write,
+    opts: Options,
+) -> anyhow::Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    let remote = remote.unwrap_or_else(|| DEFAULT_REMOTE.into());
+    let (url, specs) = resolve_remote(&repo, &remote, refspecs)?;
+
+    let haves = repo
+        .references()?
+        .all()?
+        .filter_map(Result::ok)
+        .filter_map(|r| r.target().as_id().map(ToOwned::to_owned))
+        .filter(|id| git::odb::Find::contains(&repo.objects, id))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let mut delegate = FetchDelegate {
+        repo: &repo,
+        specs: &specs,
+        dry_run: opts.dry_run,
+        haves,
+        thread_limit: opts.thread_limit,
+        should_interrupt: opts.should_interrupt.clone(),
+        refs: Vec::new(),
+        mappings: Vec::new(),
+        pack: None,
+    };
+    fetch_pack(
+        url.as_slice(),
+        opts.protocol,
+        &mut delegate,
+        progress.add_child("fetch"),
+    )?;
+    let FetchDelegate { mappings, pack, .. } = delegate;
+
+    if opts.format == OutputFormat::Human {
+        if let Some(pack) = &pack {
+            writeln!(
+                out,
+                "Received pack {} with {} objects",
+                pack.index.data_hash, pack.index.num_objects
+            )?;
+        }
+        writeln!(out, "From {}", url)?;
+    }
+    let mut edits = Vec::new();
+    #[cfg(feature = "serde1")]
+    let mut entries = Vec::new();
+    let mut num_rejected = 0;
+    for mapping in &mappings {
+        let update = classify(&repo, mapping)?;
+        match opts.format {
+            OutputFormat::Human => print_update(&mut out, mapping, update)?,
+            #[cfg(feature = "serde1")]
+            OutputFormat::Json => entries.push(MappedRef {
+                remote: mapping.remote.to_string(),
+                object: mapping.id.to_string(),
+                local: mapping.local.as_ref().map(|local| local.as_bstr().to_string()),
+                force: mapping.force,
+                update: Some(update.into()),
+            }),
+        }
+        let (previous, reason) = match update {
+            Update::New if mapping.remote.starts_with(b"refs/tags/") => (PreviousValue::MustNotExist, "storing tag"),
+            Update::New => (PreviousValue::MustNotExist, "storing head"),
+            Update::FastForward { previous } => (
+                PreviousValue::MustExistAndMatch(Target::Peeled(previous)),
+                "fast-forward",
+            ),
+            Update::Forced { previous } => (
+                PreviousValue::MustExistAndMatch(Target::Peeled(previous)),
+                "forced-update",
+            ),
+            Update::Rejected { .. } => {
+                num_rejected += 1;
+                continue;
+            }
+            Update::NotStored | Update::UpToDate | Update::Unknown { .. } => continue,
+        };
+        edits.push(RefEdit {
+            change: Change::Update {
+                log: LogChange {
+                    mode: RefLog::AndReference,
+                    force_create_reflog: false,
+                    message: format!("fetch {}: {}", remote, reason).into(),
+                },
+                expected: previous,
+                new: Target::Peeled(mapping.id),
+            },
+            name: mapping.local.clone().expect("only stored mappings are updated"),
+            deref: false,
+        });
+    }
+
+    #[cfg(feature = "serde1")]
+    if opts.format == OutputFormat::Json {
+        serde_json::to_writer_pretty(
+            &mut out,
+            &serde_json::json!({
+                "url": url.to_string(),
+                "pack": pack.as_ref().map(|pack| serde_json::json!({
+                    "id": pack.index.data_hash.to_string(),
+                    "num_objects": pack.index.num_objects,
+                })),
+                "refs": entries,
+            }),
+        )?;
+    }
+
+    if opts.dry_run {
+        progress.info("Dry-run: no references were updated");
+        return Ok(());
+    }
+    repo.edit_references(edits, git::lock::acquire::Fail::Immediately, None)?;
+    if num_rejected != 0 {
+        bail!(
+            "Rejected {} reference update(s) which weren't fast-forwards",
+            num_rejected
+        )
+    }
+    Ok(())
+}
+
+/// Connect to `remote`, which is either the name of a configured remote or a URL and defaults to `origin`, and print how
+/// the references it advertises map to local references according to `refspecs` or the fetch refspecs configured for
+/// the remote, along with how the local references would be updated by a fetch.
+///
+/// Nothing is fetched and no reference is changed. See [`MappedRef`] for the JSON output.
+pub fn ref_map(
+    repository: PathBuf,
+    remote: Option<String>,
+    refspecs: Vec<BString>,
+    mut progress: impl Progress,
+    mut out: impl io::Write,
+    protocol: Option<net::Protocol>,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    let remote = remote.unwrap_or_else(|| DEFAULT_REMOTE.into());
+    let (url, specs) = resolve_remote(&repo, &remote, refspecs)?;
+    let mut delegate = FetchDelegate {
+        repo: &repo,
+        specs: &specs,
+        dry_run: true,
+        haves: Vec::new(),
+        thread_limit: None,
+        should_interrupt: Default::default(),
+        refs: Vec::new(),
+        mappings: Vec::new(),
+        pack: None,
+    };
+    fetch_pack(url.as_slice(), protocol, &mut delegate, progress.add_child("ls-refs"))?;
+
+    let mut entries = Vec::new();
+    for r in &delegate.refs {
+        let (path, id) = r.unpack();
+        let mut mappings = delegate.mappings.iter().filter(|m| m.remote == *path).peekable();
+        if mappings.peek().is_none() {
+            entries.push(MappedRef {
+                remote: path.to_string(),
+                object: id.to_string(),
+                local: None,
+                force: false,
+                update: None,
+            });
+        }
+        for mapping in mappings {
+            entries.push(MappedRef {
+                remote: path.to_string(),
+                object: id.to_string(),
+                local: mapping.local.as_ref().map(|local| local.as_bstr().to_string()),
+                force: mapping.force,
+                update: Some(classify(&repo, mapping)?.into()),
+            });
+        }
+    }
+
+    match format {
+        OutputFormat::Human => {
+            writeln!(out, "From {}", url)?;
+            for entry in entries {
+                let (local, update) = match (&entry.local, &entry.update) {
+                    (Some(local), Some(update)) => (local.as_str(), update.to_string()),
+                    (None, Some(update)) => ("(not stored)", update.to_string()),
+                    (_, None) => {
+                        writeln!(out, "{} {} (unmapped)", entry.object, entry.remote)?;
+                        continue;
+                    }
+                };
+                writeln!(
+                    out,
+                    "{} {} -> {}{} [{}]",
+                    entry.object,
+                    entry.remote,
+                    if entry.force { "+" } else { "" },
+                    local,
+                    update
+                )?;
+            }
+        }
+        #[cfg(feature = "serde1")]
+        OutputFormat::Json => serde_json::to_writer_pretty(
+            out,
+            &serde_json::json!({
+                "url": url.to_string(),
+                "refs": entries,
+            }),
+        )?,
+    }
+    Ok(())
+}
+
+/// An advertised reference of a remote and how it maps to a local reference, as printed by [`ref_map()`].
+///
+/// With the JSON format, `ref_map()` prints an object with the `url` connected to and a list of these in `refs`.
+#[cfg_attr(feature = "serde1", derive(serde::Serialize))]
+pub struct MappedRef {
+    /// The full name of the reference on the remote.
+    pub remote: String,
+    /// The hex id of the object the remote reference points to, which is the tag object for annotated tags.
+    pub object: String,
+    /// The full name of the local reference it is stored in, or `None` if it isn't stored.
+    pub local: Option<String>,
+    /// If true, the local reference is updated even if the update isn't a fast-forward.
+    pub force: bool,
+    /// How the local reference would be updated by a fetch, or `None` if no refspec matches the remote reference.
+    pub update: Option<MappedUpdate>,
+}
+
+/// How a local reference would be updated by a fetch, as part of a [`MappedRef`].
+#[cfg_attr(feature = "serde1", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde1", serde(tag = "kind", rename_all = "kebab-case"))]
+pub enum MappedUpdate {
+    /// The remote reference isn't stored locally.
+    NotStored,
+    /// The local reference doesn't exist yet.
+    New,
+    /// The local reference already points to the remote object.
+    UpToDate,
+    /// The remote object is a descendant of the `previous` one.
+    FastForward { previous: String },
+    /// The remote object isn't a descendant of the `previous` one, but the refspec allows forced updates.
+    Forced { previous: String },
+    /// The update isn't a fast-forward or would change an existing tag, and isn't forced.
+    Rejected { previous: String },
+    /// The local reference would change from `previous`, but as the remote object isn't available locally it's unknown how.
+    Unknown { previous: String },
+}
+
+impl From<Update> for MappedUpdate {
+    fn from(v: Update) -> Self {
+        match v {
+            Update::NotStored => MappedUpdate::NotStored,
+            Update::New => MappedUpdate::New,
+            Update::UpToDate => MappedUpdate::UpToDate,
+            Update::FastForward { previous } => MappedUpdate::FastForward {
+                previous: previous.to_string(),
+            },
+            Update::Forced { previous } => MappedUpdate::Forced {
+                previous: previous.to_string(),
+            },
+            Update::Rejected { previous } => MappedUpdate::Rejected {
+                previous: previous.to_string(),
+            },
+            Update::Unknown { previous } => MappedUpdate::Unknown {
+                previous: previous.to_string(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for MappedUpdate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let short = |id: &str| id.get(..7).unwrap_or(id).to_owned();
+        match self {
+            MappedUpdate::NotStored => f.write_str("not stored"),
+            MappedUpdate::New => f.write_str("new"),
+            MappedUpdate::UpToDate => f.write_str("up to date"),
+            MappedUpdate::FastForward { previous } => write!(f, "fast-forward from {}", short(previous)),
+            MappedUpdate::Forced { previous } => write!(f, "forced update from {}", short(previous)),
+            MappedUpdate::Rejected { previous } => write!(f, "rejected non-fast-forward from {}", short(previous)),
+            MappedUpdate::Unknown { previous } => {
+                write!(f, "changed from {}, object not present locally", short(previous))
+            }
+        }
+    }
+}
+
+/// Obtain the URL of `remote`, which is either the name of a configured remote or a URL, along with the parsed refspecs to
+/// use, which are `refspecs` if not empty, or the configured ones, or `HEAD` if there are none.
+fn resolve_remote(
+    repo: &git::Repository,
+    remote: &str,
+    refspecs: Vec<BString>,
+) -> anyhow::Result<(BString, Vec<RefSpec>)> {
+    let (url, configured_specs) = match repo.find_remote(remote)? {
+        Some(configured) => (
+            configured
+                .url
+                .ok_or_else(|| anyhow!("Remote '{}' has no URL configured", remote))?
+                .to_bstring()?,
+            configured.fetch_specs,
+        ),
+        None if repo.remote_names().is_empty() && remote == DEFAULT_REMOTE => {
+            bail!("No remote named '{}' is configured", remote)
+        }
+        None => (git::url::parse(remote.as_bytes())?.to_bstring()?, Vec::new()),
+    };
+    let parse = |specs: Vec<BString>| {
+        specs
+            .iter()
+            .map(|spec| {
+                refspec::parse(spec.as_bstr(), refspec::Operation::Fetch)
+                    .map(|spec| spec.to_owned())
+                    .map_err(|err| anyhow!("Invalid refspec '{}': {}", spec, err))
+            })
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let specs = match (refspecs.is_empty(), configured_specs.is_empty()) {
+        (false, _) => parse(refspecs)?,
+        (true, false) => configured_specs,
+        (true, true) => parse(vec!["HEAD".into()])?,
+    };
+    Ok((url, specs))
+}
+
+/// Determine how the local reference of `mapping` would change.
+fn classify(repo: &git::Repository, mapping: &Mapping) -> anyhow::Result<Update> {
+    let local = match &mapping.local {
+        Some(local) => local,
+        None => return Ok(Update::NotStored),
+    };
+    let previous = match repo.try_find_reference(local.to_ref())? {
+        Some(mut existing) => match existing.target().as_id().map(ToOwned::to_owned) {
+            Some(id) => id,
+            None => existing.peel_to_id_in_place()?.detach(),
+        },
+        None => return Ok(Update::New),
+    };
+    if previous == mapping.id {
+        return Ok(Update::UpToDate);
+    }
+    if !git::odb::Find::contains(&repo.objects, mapping.id) {
+        return Ok(Update::Unknown { previous });
+    }
+    let is_tag = local.as_bstr().starts_with(b"refs/tags/");
+    Ok(if !is_tag && repo.is_ancestor(previous, mapping.id).unwrap_or(false) {
+        Update::FastForward { previous }
+    } else if mapping.force {
+        Update::Forced { previous }
+    } else {
+        Update::Rejected { previous }
+    })
+}
+
+fn print_update(mut out: impl io::Write, mapping: &Mapping, update: Update) -> io::Result<()> {
+    let short = |id: &ObjectId| id.to_hex_with_len(7).to_string();
+    let (flag, summary, note) = match update {
+        Update::NotStored | Update::New => {
+            let kind = if mapping.remote.starts_with(b"refs/tags/") {
+                "[new tag]"
+            } else if mapping.remote.starts_with(b"refs/heads/") {
+                "[new branch]"
+            } else {
+                "[new ref]"
+            };
+            ('*', kind.to_string(), "")
+        }
+        Update::UpToDate => ('=', "[up to date]".into(), ""),
+        Update::FastForward { previous } => (' ', format!("{}..{}", short(&previous), short(&mapping.id)), ""),
+        Update::Forced { previous } => (
+            '+',
+            format!("{}...{}", short(&previous), short(&mapping.id)),
+            "  (forced update)",
+        ),
+        Update::Rejected { .. } => ('!', "[rejected]".into(), "  (non-fast-forward)"),
+        Update::Unknown { previous } => (' ', format!("{}..{}", short(&previous), short(&mapping.id)), ""),
+    };
+    let remote = FullName::try_from(mapping.remote.as_bstr())
+        .map(|name| name.shorten().to_owned())
+        .unwrap_or_else(|_| mapping.remote.clone());
+    let local = mapping
+        .local
+        .as_ref()
+        .map_or_else(|| BString::from("(not stored)"), |local| local.shorten().to_owned());
+    writeln!(out, " {} {:<17} {:<10} -> {}{}", flag, summary, remote, local, note)
+}
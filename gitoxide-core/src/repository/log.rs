@@ -0,0 +1,135 @@
+use std::{io, path::PathBuf};
+
+use anyhow::bail;
+use git_repository as git;
+use git_repository::{bstr::ByteSlice, revision::Spec, traverse::commit::Sorting};
+
+/// The order in which commits are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// Follow the commit graph, preferring commits with a more recent committer date if possible.
+    Default,
+    /// Don't show parents before all of their children, and keep lines of history together, like `--topo-order`.
+    Topo,
+    /// Don't show parents before all of their children, and show commits by committer date otherwise, like `--date-order`.
+    Date,
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Order::Default
+    }
+}
+
+/// How to print each commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The abbreviated commit id followed by the title line of its message.
+    Oneline,
+    /// The commit id, the author, the author date and the full message.
+    Medium,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Medium
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "oneline" => Format::Oneline,
+            "medium" => Format::Medium,
+            _ => {
+                return Err(format!(
+                    "Invalid format: {:?}, valid values are 'oneline' and 'medium'",
+                    s
+                ))
+            }
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Only follow the first parent of merge commits.
+    pub first_parent: bool,
+    /// Stop after printing this many commits.
+    pub max_count: Option<usize>,
+    pub order: Order,
+    pub format: Format,
+}
+
+pub fn log(
+    repository: PathBuf,
+    spec: Option<String>,
+    paths: Vec<git::bstr::BString>,
+    mut out: impl io::Write,
+    Options {
+        first_parent,
+        max_count,
+        order,
+        format,
+    }: Options,
+) -> anyhow::Result<()> {
+    let repo = git::discover(repository)?.apply_environment();
+    let (tip, hidden) = match repo.rev_parse(spec.as_deref().unwrap_or("HEAD"))? {
+        Spec::Include(id) => (id, None),
+        Spec::Range { from, to } => (to, Some(from)),
+        Spec::Exclude(_) => bail!("At least one revision must be included, but {:?} only excludes", spec),
+    };
+
+    let to_commit = |id: git::Id<'_>| -> anyhow::Result<git::hash::ObjectId> {
+        Ok(id.object()?.peel_to_kind(git::object::Kind::Commit)?.id)
+    };
+    let mut walk = repo
+        .rev_walk(Some(to_commit(tip)?))
+        .hide(hidden.map(to_commit).transpose()?)
+        .sorting(match order {
+            Order::Default => Sorting::ByCommitterDate,
+            Order::Topo => Sorting::TopoOrder,
+            Order::Date => Sorting::DateOrder,
+        })
+        .paths(paths);
+    if first_parent {
+        walk = walk.first_parent_only();
+    }
+
+    let mailmap = repo.load_mailmap();
+    for (nr, info) in walk.all()?.enumerate() {
+        if max_count.map_or(false, |max| nr >= max) {
+            break;
+        }
+        let commit = info?.object()?;
+        let message = commit.message()?;
+        match format {
+            Format::Oneline => writeln!(out, "{} {}", commit.short_id()?, message.summary())?,
+            Format::Medium => {
+                if nr != 0 {
+                    writeln!(out)?;
+                }
+                let author = commit.author_mailmapped(&mailmap)?;
+                writeln!(out, "commit {}", commit.id)?;
+                let mut parents = commit.parent_ids();
+                if let (Some(first), Some(second)) = (parents.next(), parents.next()) {
+                    write!(out, "Merge: {} {}", first.shorten()?, second.shorten()?)?;
+                    for parent in parents {
+                        write!(out, " {}", parent.shorten()?)?;
+                    }
+                    writeln!(out)?;
+                }
+                writeln!(out, "Author: {} <{}>", author.name, author.email)?;
+                write!(out, "Date:   ")?;
+                author.time.write_to(&mut out)?;
+                writeln!(out, "\n")?;
+                for line in commit.message_raw()?.trim_end().lines() {
+                    writeln!(out, "    {}", line.as_bstr())?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
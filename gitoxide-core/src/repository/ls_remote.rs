@@ -0,0 +1,96 @@
+use std::{io, path::PathBuf};
+
+use git_repository as git;
+use git_repository::{bstr::BString, protocol::fetch::Ref, Progress};
+
+use crate::{net, remote::refs::receive, OutputFormat};
+
+pub const PROGRESS_RANGE: std::ops::RangeInclusive<u8> = 1..=2;
+
+pub struct Options {
+    pub protocol: Option<net::Protocol>,
+    pub format: OutputFormat,
+    /// Don't show peeled tags or references outside of `refs/`, like `HEAD`.
+    pub refs_only: bool,
+    /// Show references in `refs/heads/`. If this and `tags` are unset, all references are shown.
+    pub heads: bool,
+    /// Show references in `refs/tags/`. If this and `heads` are unset, all references are shown.
+    pub tags: bool,
+    /// Print the URL that would be connected to after resolving remote names and applying `insteadOf` rewrites, and exit.
+    pub get_url: bool,
+}
+
+/// List the references advertised by `remote`, which is either the name of a remote configured in the repository at
+/// `repository` or a URL, similar to `git ls-remote`.
+///
+/// If `repository` isn't a repository, `remote` must be a URL which is used as is.
+pub fn ls_remote(
+    repository: PathBuf,
+    remote: String,
+    progress: impl Progress,
+    mut out: impl io::Write,
+    Options {
+        protocol,
+        format,
+        refs_only,
+        heads,
+        tags,
+        get_url,
+    }: Options,
+) -> anyhow::Result<()> {
+    let url = match git::discover(repository).ok().map(|repo| repo.apply_environment()) {
+        Some(repo) => match repo.find_remote(&remote)? {
+            Some(configured) => configured
+                .url
+                .ok_or_else(|| anyhow::anyhow!("Remote '{}' has no URL configured", remote))?,
+            None => repo.url_rewrite().rewrite(&git::url::parse(remote.as_bytes())?)?.fetch,
+        },
+        None => git::url::parse(remote.as_bytes())?,
+    }
+    .to_bstring()?;
+    if get_url {
+        writeln!(out, "{}", url)?;
+        return Ok(());
+    }
+
+    let prefixes: Vec<BString> = match (heads, tags) {
+        (false, false) => Vec::new(),
+        (heads, tags) => heads
+            .then(|| "refs/heads/".into())
+            .into_iter()
+            .chain(tags.then(|| "refs/tags/".into()))
+            .collect(),
+    };
+    #[cfg(feature = "blocking-client")]
+    let refs = receive(protocol, &url, prefixes.clone(), progress)?;
+    #[cfg(feature = "async-client")]
+    let refs = futures_lite::future::block_on(receive(protocol, &url, prefixes.clone(), progress))?;
+    let refs = refs.into_iter().filter(|r| {
+        let path = r.unpack().0;
+        (prefixes.is_empty() || prefixes.iter().any(|prefix| path.starts_with(prefix)))
+            && (!refs_only || path.starts_with(b"refs/"))
+    });
+
+    match format {
+        OutputFormat::Human => {
+            for r in refs {
+                match r {
+                    Ref::Direct { path, object } | Ref::Symbolic { path, object, .. } => {
+                        writeln!(out, "{}\t{}", object, path)?
+                    }
+                    Ref::Peeled { path, tag, object } => {
+                        writeln!(out, "{}\t{}", tag, path)?;
+                        if !refs_only {
+                            writeln!(out, "{}\t{}^{{}}", object, path)?;
+                        }
+                    }
+                }
+            }
+        }
+        #[cfg(feature = "serde1")]
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(out, &refs.map(crate::remote::refs::JsonRef::from).collect::<Vec<_>>())?
+        }
+    }
+    Ok(())
+}
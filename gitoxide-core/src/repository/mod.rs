@@ -7,6 +7,28 @@ pub fn init(directory: Option<PathBuf>) -> Result<git_repository::Path> {
         .with_context(|| "Repository initialization failed")
 }
 
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub mod clone;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub use clone::clone;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub mod fetch;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub use fetch::fetch;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub mod ls_remote;
+#[cfg(any(feature = "async-client", feature = "blocking-client"))]
+pub use ls_remote::ls_remote;
+
+pub mod config;
+pub use config::config;
+
+pub mod cat_file;
+pub use cat_file::cat_file;
+
+pub mod log;
+pub use log::log;
+
 pub mod tree;
 
 pub mod verify;
@@ -1,7 +1,13 @@
-use std::{io, path::PathBuf};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
 
 use anyhow::bail;
+use bytesize::ByteSize;
 use git_repository as git;
+use git_repository::Progress;
 
 use crate::OutputFormat;
 
@@ -20,6 +26,151 @@ mod info {
     }
 }
 
+mod statistics {
+    use std::path::PathBuf;
+
+    #[cfg_attr(feature = "serde1", derive(serde::Serialize))]
+    pub struct Statistics {
+        pub path: PathBuf,
+        pub num_packs: usize,
+        pub packs_size_in_bytes: u64,
+        pub indices_size_in_bytes: u64,
+        pub num_loose_objects: usize,
+        pub loose_objects_size_in_bytes: u64,
+        pub has_multi_pack_index: bool,
+        pub alternates: Vec<PathBuf>,
+    }
+}
+
+/// Print the amount and size of packs and loose objects as well as alternate object databases of the repository
+/// at `repository`, without looking at the object database of alternates.
+pub fn statistics(repository: PathBuf, format: OutputFormat, mut out: impl io::Write) -> anyhow::Result<()> {
+    let repo = git::open(repository)?.apply_environment();
+    let objects_dir = repo.objects.store_ref().path().to_owned();
+    let pack_dir = objects_dir.join("pack");
+
+    let mut stats = statistics::Statistics {
+        path: objects_dir.clone(),
+        num_packs: 0,
+        packs_size_in_bytes: 0,
+        indices_size_in_bytes: 0,
+        num_loose_objects: 0,
+        loose_objects_size_in_bytes: 0,
+        has_multi_pack_index: multi_index_path(&pack_dir).is_file(),
+        alternates: git::odb::alternate::resolve(&objects_dir)?,
+    };
+    if pack_dir.is_dir() {
+        for entry in std::fs::read_dir(&pack_dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("pack") => {
+                    stats.num_packs += 1;
+                    stats.packs_size_in_bytes += entry.metadata()?.len();
+                }
+                Some("idx") => stats.indices_size_in_bytes += entry.metadata()?.len(),
+                _ => {}
+            }
+        }
+    }
+    for entry in std::fs::read_dir(&objects_dir)? {
+        let entry = entry?;
+        let is_fan_out_dir = entry.file_name().to_str().map_or(false, |name| {
+            name.len() == 2 && name.bytes().all(|b| b.is_ascii_hexdigit())
+        });
+        if !is_fan_out_dir || !entry.file_type()?.is_dir() {
+            continue;
+        }
+        for object in std::fs::read_dir(entry.path())? {
+            let object = object?;
+            stats.num_loose_objects += 1;
+            stats.loose_objects_size_in_bytes += object.metadata()?.len();
+        }
+    }
+
+    match format {
+        OutputFormat::Human => {
+            writeln!(out, "path: {}", stats.path.display())?;
+            writeln!(
+                out,
+                "packs: {} ({} in packs, {} in indices)",
+                stats.num_packs,
+                ByteSize(stats.packs_size_in_bytes),
+                ByteSize(stats.indices_size_in_bytes)
+            )?;
+            writeln!(
+                out,
+                "loose objects: {} ({})",
+                stats.num_loose_objects,
+                ByteSize(stats.loose_objects_size_in_bytes)
+            )?;
+            writeln!(
+                out,
+                "multi-pack index: {}",
+                if stats.has_multi_pack_index { "yes" } else { "no" }
+            )?;
+            writeln!(out, "alternates: {}", stats.alternates.len())?;
+            for alternate in &stats.alternates {
+                writeln!(out, "\t{}", alternate.display())?;
+            }
+        }
+        #[cfg(feature = "serde1")]
+        OutputFormat::Json => serde_json::to_writer_pretty(out, &stats)?,
+    }
+    Ok(())
+}
+
+/// Create or replace the multi-pack index of the repository at `repository` to cover all of its packs.
+pub fn create_multi_index(
+    repository: PathBuf,
+    progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let repo = git::open(repository)?.apply_environment();
+    let pack_dir = repo.objects.store_ref().path().join("pack");
+    let mut index_paths = Vec::new();
+    if pack_dir.is_dir() {
+        for entry in std::fs::read_dir(&pack_dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(false, |ext| ext == "idx") {
+                index_paths.push(path);
+            }
+        }
+    }
+    if index_paths.is_empty() {
+        bail!(
+            "There are no packs in '{}' to create a multi-pack index for",
+            pack_dir.display()
+        );
+    }
+    index_paths.sort();
+    crate::pack::multi_index::create(
+        index_paths,
+        multi_index_path(&pack_dir),
+        progress,
+        should_interrupt,
+        repo.object_hash(),
+    )
+}
+
+/// Verify the multi-pack index of the repository at `repository`.
+pub fn verify_multi_index(
+    repository: PathBuf,
+    progress: impl Progress,
+    should_interrupt: &AtomicBool,
+) -> anyhow::Result<()> {
+    let repo = git::open(repository)?.apply_environment();
+    let path = multi_index_path(&repo.objects.store_ref().path().join("pack"));
+    if !path.is_file() {
+        bail!("The repository doesn't have a multi-pack index at '{}'", path.display());
+    }
+    crate::pack::multi_index::verify(path, progress, should_interrupt)
+}
+
+fn multi_index_path(pack_dir: &Path) -> PathBuf {
+    pack_dir.join("multi-pack-index")
+}
+
 #[cfg_attr(not(feature = "serde1"), allow(unused_variables))]
 pub fn info(
     repository: PathBuf,
@@ -13,10 +13,15 @@ use gitoxide_core as core;
 use gitoxide_core::pack::verify;
 
 #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
-use crate::plumbing::options::remote;
+use crate::plumbing::options::{clone, fetch, ls_remote, remote};
 use crate::{
-    plumbing::options::{commitgraph, index, mailmap, pack, pack::multi_index, repo, Args, Subcommands},
-    shared::pretty::prepare_and_run,
+    plumbing::options::{
+        cat_file, commitgraph, config, index, log, mailmap, pack, pack::multi_index, repo, Args, Subcommands,
+    },
+    shared::{
+        pretty::{prepare_and_run, ProgressMode},
+        ProgressFormat,
+    },
 };
 
 #[cfg(feature = "gitoxide-core-async-client")]
@@ -65,6 +70,10 @@ pub fn main() -> Result<()> {
         progress = false;
         progress_keep_open = false;
     }
+    let progress = match args.progress_format {
+        ProgressFormat::JsonLines => ProgressMode::JsonLines,
+        ProgressFormat::Human => ProgressMode::from(progress),
+    };
 
     let should_interrupt = Arc::new(AtomicBool::new(false));
     git_repository::interrupt::init_handler({
@@ -73,6 +82,206 @@ pub fn main() -> Result<()> {
     })?;
 
     match cmd {
+        #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+        Subcommands::Clone(clone::Platform {
+            protocol,
+            bare,
+            depth,
+            url,
+            directory,
+        }) => prepare_and_run(
+            "clone",
+            verbose,
+            progress,
+            progress_keep_open,
+            core::repository::clone::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::clone(
+                    &url,
+                    directory,
+                    progress,
+                    out,
+                    core::repository::clone::Options {
+                        protocol,
+                        bare,
+                        depth,
+                        thread_limit,
+                        should_interrupt,
+                    },
+                )
+            },
+        ),
+        #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+        Subcommands::Fetch(fetch::Platform {
+            repository,
+            protocol,
+            dry_run,
+            remote,
+            refspecs,
+        }) => prepare_and_run(
+            "fetch",
+            verbose,
+            progress,
+            progress_keep_open,
+            core::repository::fetch::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::fetch(
+                    repository,
+                    remote,
+                    refspecs.into_iter().map(Into::into).collect(),
+                    progress,
+                    out,
+                    core::repository::fetch::Options {
+                        protocol,
+                        dry_run,
+                        thread_limit,
+                        should_interrupt,
+                        format,
+                    },
+                )
+            },
+        ),
+        #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+        Subcommands::LsRemote(ls_remote::Platform {
+            repository,
+            protocol,
+            refs,
+            heads,
+            tags,
+            get_url,
+            remote,
+        }) => prepare_and_run(
+            "ls-remote",
+            verbose,
+            progress,
+            progress_keep_open,
+            core::repository::ls_remote::PROGRESS_RANGE,
+            move |progress, out, _err| {
+                core::repository::ls_remote(
+                    repository,
+                    remote,
+                    progress,
+                    out,
+                    core::repository::ls_remote::Options {
+                        protocol,
+                        format,
+                        refs_only: refs,
+                        heads,
+                        tags,
+                        get_url,
+                    },
+                )
+            },
+        ),
+        Subcommands::Completions { shell } => {
+            use clap::CommandFactory;
+            use std::io::Write;
+            let mut out = std::io::stdout();
+            clap_complete::generate(shell, &mut Args::command(), "gix", &mut out);
+            if let Some(completions) = ref_name_completions(shell) {
+                out.write_all(completions.as_bytes())?;
+            }
+            Ok(())
+        }
+        Subcommands::Config(config::Platform {
+            repository,
+            show_origin,
+            show_scope,
+            set,
+            unset,
+            scope,
+            key,
+        }) => prepare_and_run(
+            "config",
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                use core::repository::config::Action;
+                core::repository::config(
+                    repository,
+                    match (key, set) {
+                        (Some(key), Some(value)) => Action::Set { key, value, scope },
+                        (Some(key), None) if unset => Action::Unset { key, scope },
+                        (Some(key), None) => Action::Get { key },
+                        (None, _) => Action::List,
+                    },
+                    out,
+                    core::repository::config::Options {
+                        format,
+                        show_origin,
+                        show_scope,
+                    },
+                )
+            },
+        ),
+        Subcommands::CatFile(cat_file::Platform {
+            repository,
+            kind,
+            size,
+            batch,
+            revision,
+        }) => prepare_and_run(
+            "cat-file",
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                if batch {
+                    return core::repository::cat_file::batch(repository, BufReader::new(stdin()), out);
+                }
+                core::repository::cat_file(
+                    repository,
+                    &revision.expect("clap assures revision is set without --batch"),
+                    if kind {
+                        core::repository::cat_file::Mode::Kind
+                    } else if size {
+                        core::repository::cat_file::Mode::Size
+                    } else {
+                        core::repository::cat_file::Mode::Pretty
+                    },
+                    out,
+                )
+            },
+        ),
+        Subcommands::Log(log::Platform {
+            repository,
+            first_parent,
+            max_count,
+            topo_order,
+            date_order,
+            format,
+            revision,
+            paths,
+        }) => prepare_and_run(
+            "log",
+            verbose,
+            progress,
+            progress_keep_open,
+            None,
+            move |_progress, out, _err| {
+                core::repository::log(
+                    repository,
+                    revision,
+                    paths.into_iter().map(Into::into).collect(),
+                    out,
+                    core::repository::log::Options {
+                        first_parent,
+                        max_count,
+                        order: if topo_order {
+                            core::repository::log::Order::Topo
+                        } else if date_order {
+                            core::repository::log::Order::Date
+                        } else {
+                            core::repository::log::Order::Default
+                        },
+                        format,
+                    },
+                )
+            },
+        ),
         Subcommands::Mailmap(mailmap::Platform { path, cmd }) => match cmd {
             mailmap::Subcommands::Verify => prepare_and_run(
                 "mailmap-verify",
@@ -183,6 +392,36 @@ pub fn main() -> Result<()> {
                     None,
                     move |_progress, out, err| core::repository::odb::info(repository, format, out, err),
                 ),
+                repo::odb::Subcommands::Stats => prepare_and_run(
+                    "repository-odb-stats",
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    None,
+                    move |_progress, out, _err| core::repository::odb::statistics(repository, format, out),
+                ),
+                repo::odb::Subcommands::MultiIndex { cmd } => match cmd {
+                    repo::odb::multi_index::Subcommands::Create => prepare_and_run(
+                        "repository-odb-multi-index-create",
+                        verbose,
+                        progress,
+                        progress_keep_open,
+                        core::pack::multi_index::PROGRESS_RANGE,
+                        move |progress, _out, _err| {
+                            core::repository::odb::create_multi_index(repository, progress, &should_interrupt)
+                        },
+                    ),
+                    repo::odb::multi_index::Subcommands::Verify => prepare_and_run(
+                        "repository-odb-multi-index-verify",
+                        verbose,
+                        progress,
+                        progress_keep_open,
+                        core::pack::multi_index::PROGRESS_RANGE,
+                        move |progress, _out, _err| {
+                            core::repository::odb::verify_multi_index(repository, progress, &should_interrupt)
+                        },
+                    ),
+                },
             },
             repo::Subcommands::Tree { cmd } => match cmd {
                 repo::tree::Subcommands::Entries {
@@ -258,7 +497,10 @@ pub fn main() -> Result<()> {
                 pack_cache_size_mb,
                 counting_threads,
                 object_cache_size_mb,
+                window,
+                depth,
                 output_directory,
+                stdout,
             } => {
                 let has_tips = !tips.is_empty();
                 prepare_and_run(
@@ -282,6 +524,9 @@ pub fn main() -> Result<()> {
                         let context = core::pack::create::Context {
                             thread_limit,
                             thin,
+                            delta_window: window,
+                            max_depth: depth,
+                            pack_to_out: stdout,
                             nondeterministic_thread_count: nondeterministic_count.then(|| counting_threads),
                             pack_cache_size_in_bytes: pack_cache_size_mb.unwrap_or(0) * 1_000_000,
                             object_cache_size_in_bytes: object_cache_size_mb.unwrap_or(0) * 1_000_000,
@@ -506,6 +751,29 @@ pub fn main() -> Result<()> {
         },
         #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
         Subcommands::Remote(subcommands) => match subcommands {
+            remote::Subcommands::RefMap {
+                repository,
+                protocol,
+                remote,
+                refspecs,
+            } => prepare_and_run(
+                "remote-ref-map",
+                verbose,
+                progress,
+                progress_keep_open,
+                core::repository::fetch::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::repository::fetch::ref_map(
+                        repository,
+                        remote,
+                        refspecs.into_iter().map(Into::into).collect(),
+                        progress,
+                        out,
+                        protocol,
+                        format,
+                    )
+                },
+            ),
             #[cfg(feature = "gitoxide-core-async-client")]
             remote::Subcommands::RefList { protocol, url } => {
                 let (_handle, progress) =
@@ -563,6 +831,25 @@ pub fn main() -> Result<()> {
                 },
             )
             .map(|_| ()),
+            commitgraph::Subcommands::Write {
+                repository,
+                changed_paths,
+                split,
+            } => prepare_and_run(
+                "commitgraph-write",
+                verbose,
+                progress,
+                progress_keep_open,
+                core::commitgraph::write::PROGRESS_RANGE,
+                move |progress, out, _err| {
+                    core::commitgraph::write::write(
+                        repository,
+                        progress,
+                        out,
+                        core::commitgraph::write::Options { changed_paths, split },
+                    )
+                },
+            ),
         },
     }?;
     Ok(())
@@ -575,3 +862,35 @@ fn verify_mode(decode: bool, re_encode: bool) -> verify::Mode {
         (false, false) => verify::Mode::HashCrc32,
     }
 }
+
+/// Return a script to complete ref names as revisions of `gix log` and `gix cat-file` for `shell`, to be appended to the
+/// completions generated by `clap`, or `None` if `shell` isn't supported.
+fn ref_name_completions(shell: clap_complete::Shell) -> Option<&'static str> {
+    use clap_complete::Shell;
+    Some(match shell {
+        Shell::Bash => {
+            r#"
+_gix_with_ref_names() {
+    _gix "$@"
+    local i cur="${COMP_WORDS[COMP_CWORD]}"
+    [[ ${cur} == -* ]] && return 0
+    for i in "${COMP_WORDS[@]:1:COMP_CWORD-1}"; do
+        case "${i}" in
+            log|cat-file)
+                COMPREPLY+=( $(compgen -W "$(git for-each-ref --format='%(refname:short)' 2>/dev/null)" -- "${cur}") )
+                return 0
+                ;;
+        esac
+    done
+}
+
+complete -F _gix_with_ref_names -o bashdefault -o default gix
+"#
+        }
+        Shell::Fish => {
+            r#"complete -c gix -n "__fish_seen_subcommand_from log cat-file" -a "(git for-each-ref --format='%(refname:short)' 2>/dev/null)"
+"#
+        }
+        _ => return None,
+    })
+}
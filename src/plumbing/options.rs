@@ -27,7 +27,21 @@ pub struct Args {
     #[clap(long, conflicts_with("verbose"), requires("progress"))]
     pub progress_keep_open: bool,
 
-    /// Determine the format to use when outputting statistics.
+    /// Determine how to display progress.
+    ///
+    /// The 'json-lines' format prints a JSON object with a snapshot of all tasks, their counts and throughput to stderr
+    /// a few times per second, for display by other programs like IDEs.
+    #[clap(
+        long,
+        default_value = "human",
+        possible_values(crate::shared::ProgressFormat::variants())
+    )]
+    #[cfg_attr(feature = "prodash-render-tui", clap(conflicts_with("progress")))]
+    pub progress_format: crate::shared::ProgressFormat,
+
+    /// Determine the format to use when outputting statistics and other structured results.
+    ///
+    /// The 'json' format is meant to be consumed by scripts and other tools, and its structure is kept stable.
     #[clap(
         long,
         short = 'f',
@@ -46,6 +60,30 @@ pub struct Args {
 
 #[derive(Debug, clap::Subcommand)]
 pub enum Subcommands {
+    /// Clone a repository into a new directory, checking out the branch its `HEAD` points to.
+    #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+    Clone(clone::Platform),
+    /// Fetch references and the objects they point to from a remote and update the corresponding local references.
+    #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+    Fetch(fetch::Platform),
+    /// List the references advertised by a remote, like `git ls-remote`.
+    #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+    LsRemote(ls_remote::Platform),
+    /// Generate shell completions for `gix` and print them to stdout.
+    ///
+    /// For example, use `gix completions --shell bash > /usr/share/bash-completion/completions/gix` to install them for bash.
+    /// With bash and fish, ref names are also completed as revisions of `gix log` and `gix cat-file`.
+    Completions {
+        /// The shell to generate completions for.
+        #[clap(long, short = 's', arg_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// List, query or change configuration values of the system, global and repository configuration files.
+    Config(config::Platform),
+    /// Show the kind, size or content of an object, or of many objects whose names are read from stdin.
+    CatFile(cat_file::Platform),
+    /// Show the commit history starting at a revision, optionally limited to commits changing the given paths.
+    Log(log::Platform),
     /// Subcommands for interacting with packs and their indices.
     #[clap(subcommand)]
     Pack(pack::Subcommands),
@@ -64,6 +102,219 @@ pub enum Subcommands {
     Mailmap(mailmap::Platform),
 }
 
+///
+#[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+pub mod clone {
+    use std::{num::NonZeroU32, path::PathBuf};
+
+    use gitoxide_core as core;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The protocol version to use. Valid values are 1 and 2
+        #[clap(long, short = 'p')]
+        pub protocol: Option<core::net::Protocol>,
+
+        /// Create a bare repository without a work tree.
+        #[clap(long)]
+        pub bare: bool,
+
+        /// Create a shallow clone with a history truncated to the given amount of commits.
+        #[clap(long)]
+        pub depth: Option<NonZeroU32>,
+
+        /// The URL or path of the repository to clone.
+        ///
+        /// See here for a list of supported URLs: <https://www.git-scm.com/docs/git-clone#_git_urls>
+        #[clap(value_hint = clap::ValueHint::Url)]
+        pub url: String,
+
+        /// The directory to clone into, which must not exist or be empty.
+        ///
+        /// If unset, it is derived from the last component of the URL.
+        #[clap(value_hint = clap::ValueHint::DirPath)]
+        pub directory: Option<PathBuf>,
+    }
+}
+
+///
+#[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+pub mod fetch {
+    use std::path::PathBuf;
+
+    use gitoxide_core as core;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The repository to fetch into.
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+        pub repository: PathBuf,
+
+        /// The protocol version to use. Valid values are 1 and 2
+        #[clap(long, short = 'p')]
+        pub protocol: Option<core::net::Protocol>,
+
+        /// Print how references of the remote map to local ones, without fetching objects or updating references.
+        #[clap(long, short = 'n')]
+        pub dry_run: bool,
+
+        /// The name of a configured remote or the URL to fetch from, defaulting to `origin`.
+        pub remote: Option<String>,
+
+        /// The refspecs to use instead of the ones configured for the remote, like `+refs/heads/*:refs/remotes/origin/*`.
+        #[clap(requires = "remote")]
+        pub refspecs: Vec<String>,
+    }
+}
+
+///
+#[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
+pub mod ls_remote {
+    use std::path::PathBuf;
+
+    use gitoxide_core as core;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The repository whose remotes and URL rewrite rules to use, if it exists.
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+        pub repository: PathBuf,
+
+        /// The protocol version to use. Valid values are 1 and 2
+        #[clap(long, short = 'p')]
+        pub protocol: Option<core::net::Protocol>,
+
+        /// Don't show peeled tags or pseudo-references like `HEAD`.
+        #[clap(long)]
+        pub refs: bool,
+
+        /// Show branches, in `refs/heads/`. Can be combined with `--tags`.
+        #[clap(long, short = 'b')]
+        pub heads: bool,
+
+        /// Show tags, in `refs/tags/`. Can be combined with `--heads`.
+        #[clap(long, short = 't')]
+        pub tags: bool,
+
+        /// Print the URL to connect to after resolving remote names and applying `insteadOf` rewrites, without connecting.
+        #[clap(long = "get-url", alias = "url")]
+        pub get_url: bool,
+
+        /// The name of a configured remote or a URL.
+        ///
+        /// See here for a list of supported URLs: <https://www.git-scm.com/docs/git-clone#_git_urls>
+        pub remote: String,
+    }
+}
+
+///
+pub mod config {
+    use std::path::PathBuf;
+
+    use gitoxide_core as core;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The repository whose configuration to use in addition to the system and global one, if it exists.
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+        pub repository: PathBuf,
+
+        /// Print the file each value is defined in, or 'command line:' for values set by environment variables.
+        #[clap(long)]
+        pub show_origin: bool,
+
+        /// Print the scope each value is defined in, one of 'system', 'global', 'local' or 'command'.
+        #[clap(long)]
+        pub show_scope: bool,
+
+        /// Set the value of the key to the given one.
+        #[clap(long, requires = "key", value_name = "VALUE")]
+        pub set: Option<String>,
+
+        /// Remove the last occurrence of the key.
+        #[clap(long, requires = "key", conflicts_with = "set")]
+        pub unset: bool,
+
+        /// The scope to write to with `--set` or `--unset`. Valid values are 'system', 'global' and 'local'.
+        #[clap(long, default_value = "local")]
+        pub scope: core::repository::config::Scope,
+
+        /// The key to print the value of, like `core.bare` or `remote.origin.url`. If unset, all values are listed.
+        pub key: Option<String>,
+    }
+}
+
+///
+pub mod cat_file {
+    use std::path::PathBuf;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The repository to read objects from.
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+        pub repository: PathBuf,
+
+        /// Print the kind of the object instead of its content.
+        #[clap(short = 't', conflicts_with = "size")]
+        pub kind: bool,
+
+        /// Print the size of the object in bytes instead of its content.
+        #[clap(short = 's')]
+        pub size: bool,
+
+        /// Read one revision per line from stdin and print `<id> <kind> <size>` followed by the raw object data for each.
+        ///
+        /// Revisions that can't be found are printed as `<revision> missing`.
+        #[clap(long, conflicts_with_all = &["kind", "size", "revision"])]
+        pub batch: bool,
+
+        /// The revision of the object to show, like `HEAD`, `main~2^{tree}` or `v1.0:README.md`.
+        #[clap(required_unless_present = "batch")]
+        pub revision: Option<String>,
+    }
+}
+
+///
+pub mod log {
+    use std::path::PathBuf;
+
+    use gitoxide_core as core;
+
+    #[derive(Debug, clap::Parser)]
+    pub struct Platform {
+        /// The repository to show the history of.
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+        pub repository: PathBuf,
+
+        /// Only follow the first parent of merge commits.
+        #[clap(long)]
+        pub first_parent: bool,
+
+        /// Stop after showing this many commits.
+        #[clap(long, short = 'n')]
+        pub max_count: Option<usize>,
+
+        /// Never show parents before all of their children, and keep lines of history together.
+        #[clap(long, conflicts_with = "date-order")]
+        pub topo_order: bool,
+
+        /// Never show parents before all of their children, and show commits by committer date otherwise.
+        #[clap(long)]
+        pub date_order: bool,
+
+        /// How to print commits. Valid values are 'oneline' and 'medium'.
+        #[clap(long, default_value = "medium")]
+        pub format: core::repository::log::Format,
+
+        /// The revision to start from, like `main`, `HEAD~10` or `v1.0..main`, defaulting to `HEAD`.
+        pub revision: Option<String>,
+
+        /// Only show commits changing at least one of these paths.
+        #[clap(last = true)]
+        pub paths: Vec<String>,
+    }
+}
+
 ///
 pub mod pack {
     use std::{ffi::OsString, path::PathBuf};
@@ -133,12 +384,27 @@ pub mod pack {
             /// Packs produced with this option enabled are only valid in transit, but not at rest.
             thin: bool,
 
-            /// The directory into which to write the pack file.
+            #[clap(long)]
+            /// If set, objects that aren't deltas in existing packs will be delta-compressed against one of the given amount
+            /// of objects before them, similar to `--window` in `git pack-objects`.
+            ///
+            /// If unset, these objects are stored without delta compression, which is fast but produces larger packs.
+            window: Option<usize>,
+
+            #[clap(long, default_value_t = 50, requires = "window")]
+            /// The maximum length of delta chains when delta-compressing objects.
+            depth: usize,
+
+            /// The directory into which to write the pack file along with its index.
             #[clap(long, short = 'o')]
             output_directory: Option<PathBuf>,
 
+            /// Write the pack to standard output instead of only printing its name.
+            #[clap(long, conflicts_with_all = &["output-directory", "statistics"])]
+            stdout: bool,
+
             /// The tips from which to start the commit graph iteration, either as fully qualified commit hashes
-            /// or as branch names.
+            /// or as branch names. Tips prefixed with `^`, like `^v1.0`, exclude all commits reachable from them.
             ///
             /// If empty, we expect to read objects on stdin and default to 'none' as expansion mode.
             /// Otherwise the expansion mode is 'tree-traversal' by default.
@@ -160,6 +426,7 @@ pub mod pack {
             /// The URLs or path from which to receive the pack.
             ///
             /// See here for a list of supported URLs: <https://www.git-scm.com/docs/git-clone#_git_urls>
+            #[clap(value_hint = clap::ValueHint::Url)]
             url: String,
 
             /// If set once or more times, these references will be fetched instead of all advertised ones.
@@ -334,7 +601,7 @@ pub mod repo {
     #[derive(Debug, clap::Parser)]
     pub struct Platform {
         /// The repository to access.
-        #[clap(short = 'r', long, default_value = ".")]
+        #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
         pub repository: PathBuf,
 
         /// Subcommands
@@ -382,6 +649,23 @@ pub mod repo {
             Entries,
             /// Provide general information about the object database.
             Info,
+            /// Show the amount and size of packs and loose objects, whether there is a multi-pack index, and alternates.
+            Stats,
+            /// Interact with the multi-pack index covering all packs of the object database.
+            MultiIndex {
+                #[clap(subcommand)]
+                cmd: multi_index::Subcommands,
+            },
+        }
+
+        pub mod multi_index {
+            #[derive(Debug, clap::Subcommand)]
+            pub enum Subcommands {
+                /// Create or replace the multi-pack index to cover all packs in the object database.
+                Create,
+                /// Verify the integrity of the multi-pack index.
+                Verify,
+            }
         }
     }
 
@@ -499,12 +783,29 @@ pub mod commitgraph {
             #[clap(long, short = 's')]
             statistics: bool,
         },
+        /// Write all commits reachable from HEAD and all references into the commit-graph of a repository.
+        ///
+        /// This is the plumbing equivalent of `git commit-graph write --reachable`.
+        Write {
+            /// The repository whose commit-graph to write.
+            #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+            repository: PathBuf,
+            /// Compute and store changed-path Bloom filters, which speed up obtaining the history of paths.
+            #[clap(long)]
+            changed_paths: bool,
+            /// Add a new layer to the commit-graph chain instead of replacing the 'commit-graph' file,
+            /// merging existing layers that are less than twice the size of the new one.
+            #[clap(long)]
+            split: bool,
+        },
     }
 }
 
 ///
 #[cfg(any(feature = "gitoxide-core-async-client", feature = "gitoxide-core-blocking-client"))]
 pub mod remote {
+    use std::path::PathBuf;
+
     use gitoxide_core as core;
 
     #[derive(Debug, clap::Subcommand)]
@@ -521,7 +822,28 @@ pub mod remote {
             /// the URLs or path from which to receive references
             ///
             /// See here for a list of supported URLs: <https://www.git-scm.com/docs/git-clone#_git_urls>
+            #[clap(value_hint = clap::ValueHint::Url)]
             url: String,
         },
+        /// Show how the references advertised by a remote map to local references according to the fetch refspecs,
+        /// and how each local reference would be updated by a fetch.
+        ///
+        /// Nothing is fetched and no reference is changed, which makes this useful to debug refspec configuration.
+        RefMap {
+            /// The repository whose remotes and references to use.
+            #[clap(short = 'r', long, default_value = ".", value_hint = clap::ValueHint::DirPath)]
+            repository: PathBuf,
+
+            /// The protocol version to use. Valid values are 1 and 2
+            #[clap(long, short = 'p')]
+            protocol: Option<core::net::Protocol>,
+
+            /// The name of a configured remote or the URL to connect to, defaulting to `origin`.
+            remote: Option<String>,
+
+            /// The refspecs to use instead of the ones configured for the remote, like `+refs/heads/*:refs/remotes/origin/*`.
+            #[clap(requires = "remote")]
+            refspecs: Vec<String>,
+        },
     }
 }
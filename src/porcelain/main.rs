@@ -63,6 +63,29 @@ pub fn main() -> Result<()> {
                     },
                 )
             }
+            #[cfg(feature = "gitoxide-core-tools-query")]
+            crate::porcelain::options::ToolCommands::Query(crate::porcelain::options::Query {
+                database,
+                refresh,
+                root,
+            }) => {
+                use gitoxide_core::query;
+                prepare_and_run(
+                    "query",
+                    verbose,
+                    progress,
+                    progress_keep_open,
+                    crate::shared::STANDARD_RANGE,
+                    move |progress, out, _err| {
+                        query::query(
+                            root.unwrap_or_else(|| [std::path::Component::CurDir].iter().collect()),
+                            out,
+                            git_features::progress::DoOrDiscard::from(progress),
+                            query::Options { database, refresh },
+                        )
+                    },
+                )
+            }
             crate::porcelain::options::ToolCommands::Find { root, debug } => {
                 use gitoxide_core::organize;
                 prepare_and_run(
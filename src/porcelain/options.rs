@@ -77,6 +77,8 @@ pub enum ToolCommands {
         destination_directory: Option<PathBuf>,
     },
     EstimateHours(EstimateHours),
+    #[cfg(feature = "gitoxide-core-tools-query")]
+    Query(Query),
 }
 
 #[derive(Debug, clap::Parser)]
@@ -104,6 +106,29 @@ pub struct EstimateHours {
     pub omit_unify_identities: bool,
 }
 
+#[cfg(feature = "gitoxide-core-tools-query")]
+#[derive(Debug, clap::Parser)]
+#[clap(
+    about = "Estimate commits and hours per author of all repositories in a directory",
+    long_about = "Results of each repository are stored in a database and only recomputed if its HEAD changed, \
+                  making repeated queries fast",
+    visible_alias = "q"
+)]
+pub struct Query {
+    /// The database file to store results in.
+    ///
+    /// Defaults to 'ein.query' in the root directory.
+    #[clap(long, short = 'd')]
+    pub database: Option<PathBuf>,
+    /// Recompute the results of all repositories, even those whose HEAD didn't change.
+    #[clap(long, short = 'r')]
+    pub refresh: bool,
+    /// The directory in which to find all git repositories.
+    ///
+    /// Defaults to the current working directory.
+    pub root: Option<PathBuf>,
+}
+
 mod validator {
     use std::{ffi::OsStr, path::PathBuf};
 
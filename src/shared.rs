@@ -43,6 +43,34 @@ fn progress_tree() -> LogCreator {
     LogCreator
 }
 
+/// The way progress is presented, as selected on the command-line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// Display progress for humans, line by line or using a terminal user interface.
+    Human,
+    /// Print a JSON object with a snapshot of all tasks per line to stderr, to be consumed by other programs.
+    JsonLines,
+}
+
+impl ProgressFormat {
+    #[allow(unused)]
+    pub fn variants() -> &'static [&'static str] {
+        &["human", "json-lines"]
+    }
+}
+
+impl std::str::FromStr for ProgressFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "human" => ProgressFormat::Human,
+            "json-lines" => ProgressFormat::JsonLines,
+            _ => return Err(format!("Invalid progress format: '{}'", s)),
+        })
+    }
+}
+
 #[cfg(feature = "pretty-cli")]
 pub mod pretty {
     use std::io::{stderr, stdout};
@@ -52,10 +80,32 @@ pub mod pretty {
 
     use crate::shared::ProgressRange;
 
+    /// How to display progress while running an operation.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ProgressMode {
+        /// Render progress lines if verbose.
+        Lines,
+        /// Bring up the terminal user interface.
+        Tui,
+        /// Print snapshots of all progress as JSON lines to stderr.
+        JsonLines,
+    }
+
+    impl From<bool> for ProgressMode {
+        /// Use the terminal user interface if `tui` is true.
+        fn from(tui: bool) -> Self {
+            if tui {
+                ProgressMode::Tui
+            } else {
+                ProgressMode::Lines
+            }
+        }
+    }
+
     pub fn prepare_and_run<T: Send + 'static>(
         name: &str,
         verbose: bool,
-        progress: bool,
+        progress: impl Into<ProgressMode>,
         #[cfg_attr(not(feature = "prodash-render-tui"), allow(unused_variables))] progress_keep_open: bool,
         #[cfg_attr(not(feature = "prodash-render-line"), allow(unused_variables))] range: impl Into<Option<ProgressRange>>,
         run: impl FnOnce(
@@ -69,6 +119,25 @@ pub mod pretty {
     ) -> Result<T> {
         crate::shared::init_env_logger();
 
+        let progress = match progress.into() {
+            ProgressMode::JsonLines => {
+                let progress = crate::shared::progress_tree();
+                let sub_progress = progress.add_child(name);
+                let handle = crate::shared::json_lines::render(
+                    std::sync::Arc::downgrade(&progress),
+                    stderr(),
+                    std::time::Duration::from_secs_f32(1.0 / crate::shared::DEFAULT_FRAME_RATE),
+                );
+
+                let mut out = Vec::<u8>::new();
+                let res = run(progress::DoOrDiscard::from(Some(sub_progress)), &mut out, &mut stderr());
+                handle.shutdown_and_wait();
+                std::io::Write::write_all(&mut stdout(), &out)?;
+                return res;
+            }
+            ProgressMode::Tui => true,
+            ProgressMode::Lines => false,
+        };
         match (verbose, progress) {
             (false, false) => {
                 let stdout = stdout();
@@ -153,6 +222,100 @@ pub mod pretty {
     }
 }
 
+/// A renderer for machine-readable progress, printing one JSON object per line with a snapshot of all tasks.
+///
+/// Each line looks like `{"elapsed_seconds":1.5,"tasks":[{"level":1,"name":"clone","step":10,"done_at":null,"steps_per_second":6.6}]}`,
+/// where `level` is the depth of the task in the task tree, and `done_at` is the step at which the task is complete if known.
+/// Tasks without progress, which only serve to group other tasks, have `null` as `step`.
+#[cfg(feature = "pretty-cli")]
+pub mod json_lines {
+    use std::{
+        collections::BTreeMap,
+        io::Write,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Weak,
+        },
+        time::{Duration, Instant},
+    };
+
+    /// The handle to the thread printing progress, which has to be shut down to print the final snapshot.
+    pub struct JoinHandle {
+        should_stop: Arc<AtomicBool>,
+        thread: std::thread::JoinHandle<()>,
+    }
+
+    impl JoinHandle {
+        /// Print a final snapshot and wait for the printing thread to stop.
+        pub fn shutdown_and_wait(self) {
+            self.should_stop.store(true, Ordering::SeqCst);
+            self.thread.thread().unpark();
+            self.thread.join().ok();
+        }
+    }
+
+    /// Print a snapshot of `progress` to `out` every `interval` until the returned handle is shut down or `progress` is dropped.
+    pub fn render(
+        progress: Weak<prodash::Tree>,
+        mut out: impl Write + Send + 'static,
+        interval: Duration,
+    ) -> JoinHandle {
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let should_stop = Arc::clone(&should_stop);
+            move || {
+                let start = Instant::now();
+                let mut tasks = Vec::new();
+                let mut previous = (start, BTreeMap::<prodash::progress::Key, usize>::new());
+                loop {
+                    let is_last = should_stop.load(Ordering::SeqCst);
+                    let progress = match progress.upgrade() {
+                        Some(progress) => progress,
+                        None => break,
+                    };
+                    progress.sorted_snapshot(&mut tasks);
+                    drop(progress);
+
+                    let now = Instant::now();
+                    let elapsed_since_previous = now.duration_since(previous.0).as_secs_f32();
+                    let mut steps = BTreeMap::new();
+                    let line = serde_json::json!({
+                        "elapsed_seconds": now.duration_since(start).as_secs_f32(),
+                        "tasks": tasks
+                            .iter()
+                            .map(|(key, task)| {
+                                let value = task.progress.as_ref();
+                                let step = value.map(|value| value.step);
+                                let steps_per_second = step.zip(previous.1.get(key)).and_then(|(step, previous_step)| {
+                                    (elapsed_since_previous > 0.0).then(|| {
+                                        step.saturating_sub(*previous_step) as f32 / elapsed_since_previous
+                                    })
+                                });
+                                if let Some(step) = step {
+                                    steps.insert(*key, step);
+                                }
+                                serde_json::json!({
+                                    "level": key.level(),
+                                    "name": task.name,
+                                    "step": step,
+                                    "done_at": value.and_then(|value| value.done_at),
+                                    "steps_per_second": steps_per_second,
+                                })
+                            })
+                            .collect::<Vec<_>>(),
+                    });
+                    previous = (now, steps);
+                    if writeln!(out, "{}", line).is_err() || is_last {
+                        break;
+                    }
+                    std::thread::park_timeout(interval);
+                }
+            }
+        });
+        JoinHandle { should_stop, thread }
+    }
+}
+
 #[allow(unused)]
 #[cfg(feature = "prodash-render-line")]
 pub fn setup_line_renderer_range(
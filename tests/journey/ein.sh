@@ -64,6 +64,46 @@ title "Porcelain ${kind}"
           )
         )
       )
+      (with "a few repositories with a tiny commit history"
+        (sandbox
+          repo-with-remotes one
+          repo-with-remotes two
+          title "gix tools query"
+          (when "running 'query'"
+            snapshot="$snapshot/query"
+            (with "no database yet"
+              it "succeeds and prints the commits and hours of all authors" && {
+                WITH_SNAPSHOT="$snapshot/first-run-success" \
+                expect_run_sh $SUCCESSFULLY "$exe tools query 2>/dev/null"
+              }
+              it "creates the database in the root directory" && {
+                expect_exists ein.query
+              }
+            )
+            (with "no repository HEAD changed since the last run"
+              echo "Sebastian Thiel <mapped@example.com> <git@example.com>" > one/.mailmap
+              it "succeeds and doesn't recompute the results, ignoring the new mailmap" && {
+                WITH_SNAPSHOT="$snapshot/first-run-success" \
+                expect_run_sh $SUCCESSFULLY "$exe tools query 2>/dev/null"
+              }
+            )
+            (with "a new commit in one of the repositories"
+              (cd two && git commit --allow-empty -m "second") &>/dev/null
+              it "succeeds and updates the amount of commits of its author" && {
+                WITH_SNAPSHOT="$snapshot/new-commit-success" \
+                expect_run_sh $SUCCESSFULLY "$exe tools query 2>/dev/null"
+              }
+            )
+            (with "a repository that disappeared"
+              rm -Rf one
+              it "succeeds and removes its results" && {
+                WITH_SNAPSHOT="$snapshot/removed-repository-success" \
+                expect_run_sh $SUCCESSFULLY "$exe tools query 2>/dev/null"
+              }
+            )
+          )
+        )
+      )
       (with "a mix of repositories"
         (sandbox
           repo-with-remotes dir/one-origin origin https://example.com/one-origin
@@ -66,10 +66,334 @@ title "gix repository"
         }
       )
       fi
+      (with "--progress-format json-lines"
+        it "prints a snapshot of all tasks as one JSON object per line to stderr" && {
+          WITH_SNAPSHOT="$snapshot/progress-format-json-lines" \
+          expect_run_sh $SUCCESSFULLY "$exe_plumbing --progress-format json-lines repo verify 2>&1 >/dev/null | sed -E 's/^\{\"elapsed_seconds\":[0-9.e-]+,\"tasks\":\[.*\]\}\$/json-line/' | sort -u"
+        }
+      )
+    )
+    (with "the 'odb' sub-command"
+      snapshot="$snapshot/odb"
+      (with "only loose objects"
+        it "shows statistics about loose objects" && {
+          WITH_SNAPSHOT="$snapshot/stats-loose-objects" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" repo odb stats
+        }
+        it "fails to verify a multi-pack index that doesn't exist" && {
+          WITH_SNAPSHOT="$snapshot/multi-index-verify-missing-failure" \
+          expect_run $WITH_FAILURE "$exe_plumbing" repo odb multi-index verify
+        }
+      )
+      (with "a pack and a multi-pack index"
+        git repack -adq
+        it "creates a multi-pack index" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" repo odb multi-index create
+        }
+        it "creates a multi-pack index that git can verify" && {
+          expect_run $SUCCESSFULLY git multi-pack-index verify
+        }
+        it "verifies the multi-pack index" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" repo odb multi-index verify
+        }
+        it "shows statistics about packs and the multi-pack index" && {
+          WITH_SNAPSHOT="$snapshot/stats-packs-with-multi-index" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" repo odb stats
+        }
+        if test "$kind" = "max"; then
+        (with "--format json"
+          it "generates the correct output in JSON format" && {
+            WITH_SNAPSHOT="$snapshot/stats-packs-with-multi-index-json" \
+            expect_run $SUCCESSFULLY "$exe_plumbing" --format json repo odb stats
+          }
+        )
+        fi
+      )
+    )
+  )
+)
+
+title "gix log"
+(when "running 'log'"
+  snapshot="$snapshot/log"
+  (small-repo-in-sandbox
+    (with "no arguments"
+      it "shows the history of HEAD" && {
+        WITH_SNAPSHOT="$snapshot/head-medium" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" log
+      }
+    )
+    (with "--format oneline and --max-count"
+      it "shows the given amount of commits on one line each" && {
+        WITH_SNAPSHOT="$snapshot/head-oneline-max-count" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" log --format oneline -n 2
+      }
+    )
+    (with "a range"
+      it "shows only the commits not reachable from the start of the range" && {
+        WITH_SNAPSHOT="$snapshot/range-oneline" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" log --format oneline --topo-order dev..main
+      }
+    )
+    (with "a path"
+      it "shows only the commits changing the path" && {
+        WITH_SNAPSHOT="$snapshot/path-oneline" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" log --format oneline main -- a
+      }
+    )
+  )
+)
+
+title "gix cat-file"
+(when "running 'cat-file'"
+  snapshot="$snapshot/cat-file"
+  (small-repo-in-sandbox
+    (with "-t"
+      it "prints the kind of the object" && {
+        WITH_SNAPSHOT="$snapshot/kind" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" cat-file -t HEAD
+      }
+    )
+    (with "-s"
+      it "prints the size of the object" && {
+        WITH_SNAPSHOT="$snapshot/size" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" cat-file -s HEAD:b
+      }
+    )
+    (with "a commit"
+      it "prints the commit as is" && {
+        WITH_SNAPSHOT="$snapshot/pretty-commit" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" cat-file HEAD
+      }
+    )
+    (with "a tree"
+      it "prints one line per entry" && {
+        WITH_SNAPSHOT="$snapshot/pretty-tree" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" cat-file 'HEAD^{tree}'
+      }
+    )
+    (with "--batch"
+      it "prints each object with a header and reports missing ones" && {
+        WITH_SNAPSHOT="$snapshot/batch" \
+        expect_run_sh $SUCCESSFULLY "printf 'main\nHEAD:b\ndoes-not-exist\n' | $exe_plumbing cat-file --batch"
+      }
+    )
+  )
+)
+
+title "gix config"
+(when "running 'config'"
+  snapshot="$snapshot/config"
+  (small-repo-in-sandbox
+    (with "a key"
+      it "prints its value" && {
+        WITH_SNAPSHOT="$snapshot/get" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" config core.bare
+      }
+    )
+    (with "a key and --show-scope"
+      it "prints its value along with the scope it is defined in" && {
+        WITH_SNAPSHOT="$snapshot/get-show-scope" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" config --show-scope commit.gpgsign
+      }
+    )
+    (with "a key that isn't set"
+      it "fails with a helpful error message" && {
+        WITH_SNAPSHOT="$snapshot/get-missing-failure" \
+        expect_run $WITH_FAILURE "$exe_plumbing" config does.not-exist
+      }
+    )
+    (with "--set"
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe_plumbing" config --set "Jane Doe" user.name
+      }
+      it "writes the value into the repository configuration" && {
+        WITH_SNAPSHOT="$snapshot/set-value" \
+        expect_run $SUCCESSFULLY git config --local user.name
+      }
+      (with "--unset"
+        it "succeeds" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" config --unset user.name
+        }
+        it "removes the value from the repository configuration" && {
+          expect_run $WITH_FAILURE git config --local user.name
+        }
+      )
     )
   )
 )
 
+title "gix clone"
+(when "running 'clone'"
+  snapshot="$snapshot/clone"
+  (small-repo-in-sandbox
+    if [[ "$kind" != "small" ]]; then
+    launch-git-daemon
+    (with "no flags"
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe_plumbing" clone git://localhost/ plain
+      }
+      it "creates local and remote-tracking references like git" && {
+        WITH_SNAPSHOT="$snapshot/refs" \
+        expect_run $SUCCESSFULLY git -C plain for-each-ref --format '%(objectname) %(refname)%(if)%(symref)%(then) -> %(symref)%(end)'
+      }
+      it "points HEAD to the branch of the remote HEAD" && {
+        WITH_SNAPSHOT="$snapshot/head" \
+        expect_run $SUCCESSFULLY git -C plain symbolic-ref HEAD
+      }
+      it "configures the remote and lets the checked-out branch track it" && {
+        WITH_SNAPSHOT="$snapshot/config" \
+        expect_run $SUCCESSFULLY git -C plain config --get-regexp '^(core\.bare|remote\.|branch\.)'
+      }
+      it "writes the index" && {
+        WITH_SNAPSHOT="$snapshot/index" \
+        expect_run $SUCCESSFULLY git -C plain ls-files --stage
+      }
+      it "checks out the worktree" && {
+        expect_run $SUCCESSFULLY git -C plain diff --quiet HEAD
+      }
+    )
+    (with "--bare"
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe_plumbing" clone --bare git://localhost/ bare.git
+      }
+      it "creates local references like git" && {
+        WITH_SNAPSHOT="$snapshot/bare-refs" \
+        expect_run $SUCCESSFULLY git -C bare.git for-each-ref --format '%(objectname) %(refname)%(if)%(symref)%(then) -> %(symref)%(end)'
+      }
+      it "points HEAD to the branch of the remote HEAD" && {
+        WITH_SNAPSHOT="$snapshot/head" \
+        expect_run $SUCCESSFULLY git -C bare.git symbolic-ref HEAD
+      }
+      it "configures the remote without a refspec or tracking branch" && {
+        WITH_SNAPSHOT="$snapshot/bare-config" \
+        expect_run $SUCCESSFULLY git -C bare.git config --get-regexp '^(core\.bare|remote\.|branch\.)'
+      }
+      it "has no index or worktree" && {
+        expect_run $WITH_FAILURE test -e bare.git/index
+        expect_run $WITH_FAILURE test -e bare.git/a
+      }
+    )
+    (with "--depth 1"
+      it "succeeds" && {
+        expect_run $SUCCESSFULLY "$exe_plumbing" clone --depth 1 git://localhost/ shallow
+      }
+      it "creates local and remote-tracking references like git" && {
+        WITH_SNAPSHOT="$snapshot/refs" \
+        expect_run $SUCCESSFULLY git -C shallow for-each-ref --format '%(objectname) %(refname)%(if)%(symref)%(then) -> %(symref)%(end)'
+      }
+      it "records the shallow boundary" && {
+        WITH_SNAPSHOT="$snapshot/shallow" \
+        expect_run $SUCCESSFULLY cat shallow/.git/shallow
+      }
+      it "configures the remote and lets the checked-out branch track it" && {
+        WITH_SNAPSHOT="$snapshot/config" \
+        expect_run $SUCCESSFULLY git -C shallow config --get-regexp '^(core\.bare|remote\.|branch\.)'
+      }
+      it "writes the index" && {
+        WITH_SNAPSHOT="$snapshot/index" \
+        expect_run $SUCCESSFULLY git -C shallow ls-files --stage
+      }
+      it "checks out the worktree" && {
+        expect_run $SUCCESSFULLY git -C shallow diff --quiet HEAD
+      }
+    )
+    else
+      it "fails as the CLI doesn't include networking in 'small' mode" && {
+        expect_run $WITH_CLAP_FAILURE "$exe_plumbing" clone git://localhost/ plain
+      }
+    fi
+  )
+)
+
+title "gix fetch"
+(when "running 'fetch'"
+  snapshot="$snapshot/fetch"
+  (small-repo-in-sandbox
+    if [[ "$kind" != "small" ]]; then
+    launch-git-daemon
+    {
+      git clone git://localhost/ without-force
+      git clone git://localhost/ with-force
+      git commit --allow-empty -m "fourth"
+      git checkout dev
+      git commit --allow-empty --amend -m "second, amended"
+      git checkout main
+    } &>/dev/null
+    (with "a refspec without '+' and a remote with a fast-forward and a forced update"
+      it "fast-forwards, rejects the forced update and fails" && {
+        WITH_SNAPSHOT="$snapshot/without-force" \
+        expect_run_sh $WITH_FAILURE "$exe_plumbing fetch -r without-force origin 'refs/heads/*:refs/remotes/origin/*' 2>/dev/null | grep -- '->'"
+      }
+      it "only updates the fast-forwarded reference" && {
+        WITH_SNAPSHOT="$snapshot/without-force-refs" \
+        expect_run $SUCCESSFULLY git -C without-force for-each-ref --format '%(objectname) %(refname)' refs/remotes
+      }
+    )
+    (with "a refspec with '+' and a remote with a fast-forward and a forced update"
+      it "fast-forwards, force-updates and succeeds" && {
+        WITH_SNAPSHOT="$snapshot/with-force" \
+        expect_run_sh $SUCCESSFULLY "$exe_plumbing fetch -r with-force origin '+refs/heads/*:refs/remotes/origin/*' 2>/dev/null | grep -- '->'"
+      }
+      it "updates all references" && {
+        WITH_SNAPSHOT="$snapshot/with-force-refs" \
+        expect_run $SUCCESSFULLY git -C with-force for-each-ref --format '%(objectname) %(refname)' refs/remotes
+      }
+      (when "fetching again"
+        it "finds everything up to date" && {
+          WITH_SNAPSHOT="$snapshot/with-force-again" \
+          expect_run_sh $SUCCESSFULLY "$exe_plumbing fetch -r with-force origin '+refs/heads/*:refs/remotes/origin/*' 2>/dev/null | grep -- '->'"
+        }
+      )
+    )
+    else
+      it "fails as the CLI doesn't include networking in 'small' mode" && {
+        expect_run $WITH_CLAP_FAILURE "$exe_plumbing" fetch
+      }
+    fi
+  )
+)
+
+title "gix ls-remote"
+(when "running 'ls-remote'"
+  snapshot="$snapshot/ls-remote"
+  (small-repo-in-sandbox
+    if [[ "$kind" != "small" ]]; then
+    launch-git-daemon
+    git remote add origin git://localhost/
+    (with "a URL"
+      it "lists all references, including HEAD and peeled tags" && {
+        WITH_SNAPSHOT="$snapshot/url" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" ls-remote git://localhost/
+      }
+    )
+    (with "the name of a remote and --heads"
+      it "lists only branches" && {
+        WITH_SNAPSHOT="$snapshot/remote-heads" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" ls-remote --heads origin
+      }
+    )
+    (with "--refs"
+      it "lists neither HEAD nor peeled tags" && {
+        WITH_SNAPSHOT="$snapshot/remote-refs" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" ls-remote --refs origin
+      }
+    )
+    (with "--get-url and an 'insteadOf' rewrite"
+      git config url.git://localhost/.insteadOf https://example.com/
+      it "prints the rewritten URL without connecting" && {
+        WITH_SNAPSHOT="$snapshot/get-url-rewritten" \
+        expect_run $SUCCESSFULLY "$exe_plumbing" ls-remote --get-url https://example.com/repo
+      }
+    )
+    else
+      it "fails as the CLI doesn't include networking in 'small' mode" && {
+        expect_run $WITH_CLAP_FAILURE "$exe_plumbing" ls-remote origin
+      }
+    fi
+  )
+)
+
 title "gix pack"
 (when "running 'pack'"
   snapshot="$snapshot/pack"
@@ -227,6 +551,50 @@ title "gix pack"
       fi
     )
   )
+  title "gix pack create"
+  (with "the 'create' sub-command"
+    snapshot="$snapshot/create"
+    (small-repo-in-sandbox
+      (with "a branch name as tip and NO output directory"
+        it "prints the name of the pack it created" && {
+          expect_run_sh $SUCCESSFULLY "$exe_plumbing pack create -r . main | grep -qE '^[0-9a-f]{40}\.pack$'"
+        }
+      )
+      (with "--stdout"
+        it "writes a pack with all objects reachable from the tip" && {
+          WITH_SNAPSHOT="$snapshot/stdout-objects" \
+          expect_run_sh $SUCCESSFULLY "$exe_plumbing pack create -r . --stdout main > main.pack && git index-pack main.pack >/dev/null && git show-index < main.idx | cut -d' ' -f2"
+        }
+        (with "an excluded tip"
+          it "writes a pack without the commits reachable from the excluded tip" && {
+            WITH_SNAPSHOT="$snapshot/stdout-exclusion-objects" \
+            expect_run_sh $SUCCESSFULLY "$exe_plumbing pack create -r . --stdout main ^dev > range.pack && git index-pack range.pack >/dev/null && git show-index < range.idx | cut -d' ' -f2"
+          }
+        )
+      )
+      (with "an output directory and --window"
+        mkdir out
+        it "succeeds" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" pack create -r . -o out --window 10 main
+        }
+        it "writes a pack with deltas along with an index that git can verify" && {
+          WITH_SNAPSHOT="$snapshot/output-dir-with-window-delta-chains" \
+          expect_run_sh $SUCCESSFULLY "git verify-pack -v out/*.idx | grep -E '^(non delta|chain length)'"
+        }
+      )
+      (with "an output directory and --thin"
+        mkdir thin
+        it "succeeds" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" pack create -r . -o thin --thin main ^dev
+        }
+        it "writes only the pack, as thin packs can't be indexed" && {
+          WITH_SNAPSHOT="$snapshot/output-dir-thin-content" \
+          expect_run_sh $SUCCESSFULLY "ls thin | sed -E 's/^[0-9a-f]{40}//'"
+        }
+      )
+    )
+  )
+
   (with "the 'index' sub-command"
     snapshot="$snapshot/index"
     title "gix pack index create"
@@ -606,6 +974,41 @@ title "gix remote"
       fi
     )
   )
+  title "gix remote ref-map"
+  (with "the 'ref-map' subcommand"
+    snapshot="$snapshot/ref-map"
+    (small-repo-in-sandbox
+      if [[ "$kind" != "small" ]]; then
+      launch-git-daemon
+      git remote add origin git://localhost/
+      (with "the refspecs configured for 'origin'"
+        it "shows how remote branches map to remote tracking branches" && {
+          WITH_SNAPSHOT="$snapshot/configured-refspecs" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" remote ref-map
+        }
+      )
+      (with "refspecs given on the command-line"
+        it "shows that the local tags are up to date" && {
+          WITH_SNAPSHOT="$snapshot/tags-refspec" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" remote ref-map origin 'refs/tags/*:refs/tags/*'
+        }
+      )
+      if test "$kind" = "max"; then
+      (with "--format json"
+        it "generates the correct output in JSON format" && {
+          WITH_SNAPSHOT="$snapshot/configured-refspecs-json" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --format json remote ref-map
+        }
+      )
+      fi
+      else
+        it "fails as the CLI doesn't include networking in 'small' mode" && {
+          WITH_SNAPSHOT="$snapshot/remote ref-map-no-networking-in-small-failure" \
+          expect_run 2 "$exe_plumbing" remote ref-map
+        }
+      fi
+    )
+  )
 )
 
 
@@ -637,4 +1040,92 @@ title "gix commit-graph"
       )
     )
   )
+
+  title "gix commit-graph write"
+  (with "the 'write' sub-command"
+    snapshot="$snapshot/write"
+
+    (small-repo-in-sandbox
+      (with "no flags"
+        it "succeeds" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" commit-graph write
+        }
+        it "writes a commit-graph that git can verify" && {
+          expect_run $SUCCESSFULLY git commit-graph verify
+        }
+        it "writes no changed-path Bloom filters" && {
+          WITH_SNAPSHOT="$snapshot/statistics-without-changed-paths" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" commit-graph verify -s .git/objects/info
+        }
+      )
+      (with "--changed-paths"
+        it "succeeds" && {
+          expect_run $SUCCESSFULLY "$exe_plumbing" commit-graph write --changed-paths
+        }
+        it "writes changed-path Bloom filters" && {
+          WITH_SNAPSHOT="$snapshot/statistics-with-changed-paths" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" commit-graph verify -s .git/objects/info
+        }
+      )
+      (with "--split"
+        it "has nothing to write as all commits are present" && {
+          WITH_SNAPSHOT="$snapshot/split-nothing-to-write" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" commit-graph write --split
+        }
+        it "turns the commit-graph file into the only layer of a chain" && {
+          WITH_SNAPSHOT="$snapshot/split-chain-content" \
+          expect_run $SUCCESSFULLY ls .git/objects/info/commit-graphs
+        }
+        it "writes a commit-graph chain that git can verify" && {
+          expect_run $SUCCESSFULLY git commit-graph verify
+        }
+      )
+    )
+  )
+)
+
+title "gix mailmap"
+(when "running 'mailmap'"
+  snapshot="$snapshot/mailmap"
+  title "gix mailmap verify"
+  (with "the 'verify' sub-command"
+    snapshot="$snapshot/verify"
+    (sandbox
+      {
+        echo "Sebastian Thiel <byronimo@gmail.com> <git@example.com>"
+        echo "Sebastian T. <sebastian.thiel@icloud.com> <git@example.com>"
+      } > .mailmap
+      (with "an entry that is overwritten"
+        it "notes the overwritten entry" && {
+          WITH_SNAPSHOT="$snapshot/overwritten-success" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" mailmap verify
+        }
+      )
+      if test "$kind" = "max"; then
+      (with "--format json"
+        it "generates the correct output in JSON format" && {
+          WITH_SNAPSHOT="$snapshot/overwritten-json-success" \
+          expect_run $SUCCESSFULLY "$exe_plumbing" --format json mailmap verify
+        }
+      )
+      fi
+    )
+  )
+)
+
+title "gix completions"
+(when "running 'completions'"
+  snapshot="$snapshot/completions"
+  (with "--shell bash"
+    it "completes ref names as revisions" && {
+      WITH_SNAPSHOT="$snapshot/bash-ref-names" \
+      expect_run_sh $SUCCESSFULLY "$exe_plumbing completions --shell bash | grep for-each-ref"
+    }
+  )
+  (with "--shell fish"
+    it "completes ref names as revisions" && {
+      WITH_SNAPSHOT="$snapshot/fish-ref-names" \
+      expect_run_sh $SUCCESSFULLY "$exe_plumbing completions --shell fish | grep for-each-ref"
+    }
+  )
 )